@@ -0,0 +1,130 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Methods related to the admin log of channels.
+use crate::types::{AdminLogEvent, AdminLogFilter, ChatMap, IterBuffer};
+use crate::Client;
+pub use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+const MAX_LIMIT: usize = 100;
+
+pub type AdminLogIter = IterBuffer<tl::functions::channels::GetAdminLog, AdminLogEvent>;
+
+impl AdminLogIter {
+    fn new(client: &Client, chat: PackedChat) -> Self {
+        Self::from_request(
+            client,
+            MAX_LIMIT,
+            tl::functions::channels::GetAdminLog {
+                channel: chat
+                    .try_to_input_channel()
+                    .unwrap_or(tl::enums::InputChannel::Empty),
+                q: String::new(),
+                events_filter: None,
+                admins: None,
+                max_id: 0,
+                min_id: 0,
+                limit: 0,
+            },
+        )
+    }
+
+    /// Only return the events of the kinds selected in the filter.
+    pub fn filter(mut self, filter: AdminLogFilter) -> Self {
+        self.request.events_filter = filter.to_raw();
+        self
+    }
+
+    /// Only return the events matching the query, such as the text of deleted messages.
+    pub fn query(mut self, query: &str) -> Self {
+        self.request.q = query.to_string();
+        self
+    }
+
+    /// Only return the events caused by the given administrators.
+    pub fn admins<C: Into<PackedChat>, I: IntoIterator<Item = C>>(mut self, admins: I) -> Self {
+        self.request.admins = Some(
+            admins
+                .into_iter()
+                .map(|admin| admin.into().to_input_user_lossy())
+                .collect(),
+        );
+        self
+    }
+
+    /// Return the next `AdminLogEvent` from the internal buffer, filling the buffer previously if
+    /// it's empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no events left.
+    pub async fn next(&mut self) -> Result<Option<AdminLogEvent>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        self.request.limit = self.determine_limit(MAX_LIMIT);
+        let tl::enums::channels::AdminLogResults::Results(results) =
+            self.client.invoke(&self.request).await?;
+
+        self.last_chunk = results.events.len() < self.request.limit as usize;
+
+        {
+            let mut state = self.client.0.state.write().unwrap();
+            // Telegram can return peers without hash (e.g. Users with 'min: true')
+            let _ = state.chat_hashes.extend(&results.users, &results.chats);
+        }
+
+        let chats = ChatMap::new(results.users, results.chats);
+        let client = self.client.clone();
+        self.buffer.extend(
+            results
+                .events
+                .into_iter()
+                .map(|event| AdminLogEvent::from_raw(&client, event, &chats)),
+        );
+
+        // Events are returned newest first.
+        if let Some(last) = self.buffer.back() {
+            self.request.max_id = last.id();
+        }
+
+        Ok(self.pop_item())
+    }
+}
+
+/// Method implementations related to the admin log of channels.
+impl Client {
+    /// Iterate over the recent actions performed by the administrators and participants of a
+    /// channel or supergroup, newest first.
+    ///
+    /// The logged-in user must be an administrator. Telegram only keeps the last 48 hours of
+    /// events.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::{AdminLogAction, AdminLogFilter};
+    ///
+    /// let mut events = client
+    ///     .iter_admin_log(&chat)
+    ///     .filter(AdminLogFilter::new().delete(true));
+    ///
+    /// while let Some(event) = events.next().await? {
+    ///     if let AdminLogAction::Delete(message) = event.action() {
+    ///         println!("Deleted: {}", message.text());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_admin_log<C: Into<PackedChat>>(&self, chat: C) -> AdminLogIter {
+        AdminLogIter::new(self, chat.into())
+    }
+}
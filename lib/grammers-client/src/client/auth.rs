@@ -5,7 +5,6 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-use super::net::connect_sender;
 use super::Client;
 use crate::types::{LoginToken, PasswordToken, TermsOfService, User};
 use crate::utils;
@@ -79,6 +78,12 @@ impl Client {
         }
     }
 
+    fn store_future_auth_token(&self, token: Vec<u8>) {
+        if self.0.config.params.future_auth_tokens {
+            self.0.config.session.add_future_auth_token(token);
+        }
+    }
+
     async fn complete_login(
         &self,
         auth: tl::types::auth::Authorization,
@@ -87,6 +92,10 @@ impl Client {
         // `message_box` will try to correct its state as updates arrive.
         let update_state = self.invoke(&tl::functions::updates::GetState {}).await.ok();
 
+        if let Some(token) = auth.future_auth_token {
+            self.store_future_auth_token(token.to_vec());
+        }
+
         let user = User::from_raw(auth.user);
 
         let sync_state = {
@@ -153,21 +162,7 @@ impl Client {
             bot_auth_token: token.to_string(),
         };
 
-        let result = match self.invoke(&request).await {
-            Ok(x) => x,
-            Err(InvocationError::Rpc(err)) if err.code == 303 => {
-                let dc_id = err.value.unwrap() as i32;
-                let (sender, request_tx) = connect_sender(dc_id, &self.0.config).await?;
-                {
-                    *self.0.conn.sender.lock().await = sender;
-                    *self.0.conn.request_tx.write().unwrap() = request_tx;
-                    let mut state = self.0.state.write().unwrap();
-                    state.dc_id = dc_id;
-                }
-                self.invoke(&request).await?
-            }
-            Err(e) => return Err(e.into()),
-        };
+        let result = self.invoke(&request).await?;
 
         match result {
             tl::enums::auth::Authorization::Authorization(x) => {
@@ -204,6 +199,14 @@ impl Client {
     /// # }
     /// ```
     pub async fn request_login_code(&self, phone: &str) -> Result<LoginToken, AuthorizationError> {
+        let logout_tokens = if self.0.config.params.future_auth_tokens {
+            Some(self.0.config.session.future_auth_tokens())
+                .filter(|tokens| !tokens.is_empty())
+                .map(|tokens| tokens.into_iter().map(tl::byte_string).collect())
+        } else {
+            None
+        };
+
         let request = tl::functions::auth::SendCode {
             phone_number: phone.to_string(),
             api_id: self.0.config.api_id,
@@ -214,7 +217,7 @@ impl Client {
                 allow_app_hash: false,
                 allow_missed_call: false,
                 allow_firebase: false,
-                logout_tokens: None,
+                logout_tokens,
                 token: None,
                 app_sandbox: None,
                 unknown_number: false,
@@ -224,32 +227,11 @@ impl Client {
 
         use tl::enums::auth::SentCode as SC;
 
-        let sent_code: tl::types::auth::SentCode = match self.invoke(&request).await {
-            Ok(x) => match x {
-                SC::Code(code) => code,
-                SC::Success(_) => panic!("should not have logged in yet"),
-            },
-            Err(InvocationError::Rpc(err)) if err.code == 303 => {
-                // Since we are not logged in (we're literally requesting for
-                // the code to login now), there's no need to export the current
-                // authorization and re-import it at a different datacenter.
-                //
-                // Just connect and generate a new authorization key with it
-                // before trying again.
-                let dc_id = err.value.unwrap() as i32;
-                let (sender, request_tx) = connect_sender(dc_id, &self.0.config).await?;
-                {
-                    *self.0.conn.sender.lock().await = sender;
-                    *self.0.conn.request_tx.write().unwrap() = request_tx;
-                    let mut state = self.0.state.write().unwrap();
-                    state.dc_id = dc_id;
-                }
-                match self.invoke(&request).await? {
-                    SC::Code(code) => code,
-                    SC::Success(_) => panic!("should not have logged in yet"),
-                }
-            }
-            Err(e) => return Err(e.into()),
+        // If the phone number belongs to a different datacenter, the client migrates there.
+        // Since we are not logged in yet, there is no authorization to transfer.
+        let sent_code: tl::types::auth::SentCode = match self.invoke(&request).await? {
+            SC::Code(code) => code,
+            SC::Success(_) => panic!("should not have logged in yet"),
         };
 
         Ok(LoginToken {
@@ -329,6 +311,79 @@ impl Client {
         }
     }
 
+    /// Signs in to one of the accounts reserved for testing in Telegram's test datacenters.
+    ///
+    /// The client must have been configured with [`InitParams::test_mode`]. The phone number
+    /// used is `99966XYYYY`, where `X` is the `dc` and `YYYY` the `phone_suffix`, and the login
+    /// code is always `X` repeated five times. If the account does not exist yet, it is signed
+    /// up. This makes it possible to run integration tests against real servers.
+    ///
+    /// The same account may be used by other developers, so do not store anything sensitive in
+    /// it. See [Test accounts](https://core.telegram.org/api/auth#test-accounts) for more.
+    ///
+    /// Fails with [`InvocationError::InvalidInput`] if the client is not in test mode, if `dc` is
+    /// not between 1 and 3, or if the `phone_suffix` has more than four digits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let user = client.test_sign_in(2, 1234).await?;
+    /// println!("Signed in as {}", user.phone().unwrap_or("?"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`InitParams::test_mode`]: crate::InitParams::test_mode
+    pub async fn test_sign_in(&self, dc: i32, phone_suffix: u16) -> Result<User, SignInError> {
+        let invalid_input = if !self.0.config.params.test_mode {
+            Some("test accounts can only be used in test mode")
+        } else if !(1..=3).contains(&dc) {
+            Some("test datacenters range from 1 to 3")
+        } else if phone_suffix > 9999 {
+            Some("phone suffix must have four digits")
+        } else {
+            None
+        };
+        if let Some(reason) = invalid_input {
+            return Err(SignInError::Other(InvocationError::InvalidInput(reason)));
+        }
+
+        let phone = format!("99966{dc}{phone_suffix:04}");
+        let code = dc.to_string().repeat(5);
+
+        let token = self
+            .request_login_code(&phone)
+            .await
+            .map_err(|e| SignInError::Other(e.into()))?;
+
+        match self.sign_in(&token, &code).await {
+            Err(SignInError::SignUpRequired { .. }) => {
+                match self
+                    .invoke(&tl::functions::auth::SignUp {
+                        no_joined_notifications: true,
+                        phone_number: token.phone,
+                        phone_code_hash: token.phone_code_hash,
+                        first_name: format!("Test {phone_suffix:04}"),
+                        last_name: String::new(),
+                    })
+                    .await
+                {
+                    Ok(tl::enums::auth::Authorization::Authorization(x)) => {
+                        self.complete_login(x).await.map_err(SignInError::Other)
+                    }
+                    Ok(tl::enums::auth::Authorization::SignUpRequired(_)) => {
+                        Err(SignInError::Other(utils::unexpected_response::<
+                            tl::types::auth::AuthorizationSignUpRequired,
+                        >()))
+                    }
+                    Err(error) => Err(SignInError::Other(error)),
+                }
+            }
+            result => result,
+        }
+    }
+
     /// Extract information needed for the two-factor authentication
     /// It's called automatically when we get SESSION_PASSWORD_NEEDED error during sign in.
     async fn get_password_information(&self) -> Result<PasswordToken, InvocationError> {
@@ -403,15 +458,15 @@ impl Client {
         let (salt1, salt2, p, g) = params;
 
         let g_b = password_info.srp_b.unwrap();
-        let a: Vec<u8> = password_info.secure_random;
+        let a: Vec<u8> = password_info.secure_random.to_vec();
 
-        let (m1, g_a) = calculate_2fa(salt1, salt2, p, g, g_b, a, password);
+        let (m1, g_a) = calculate_2fa(salt1, salt2, p, g, g_b.to_vec(), a, password);
 
         let check_password = tl::functions::auth::CheckPassword {
             password: tl::enums::InputCheckPasswordSrp::Srp(tl::types::InputCheckPasswordSrp {
                 srp_id: password_info.srp_id.unwrap(),
-                a: g_a.to_vec(),
-                m1: m1.to_vec(),
+                a: tl::byte_string(g_a.to_vec()),
+                m1: tl::byte_string(m1.to_vec()),
             }),
         };
 
@@ -447,7 +502,12 @@ impl Client {
     /// # }
     /// ```
     pub async fn sign_out(&self) -> Result<tl::enums::auth::LoggedOut, InvocationError> {
-        self.invoke(&tl::functions::auth::LogOut {}).await
+        let result = self.invoke(&tl::functions::auth::LogOut {}).await?;
+        let tl::enums::auth::LoggedOut::Out(logged_out) = &result;
+        if let Some(token) = &logged_out.future_auth_token {
+            self.store_future_auth_token(token.to_vec());
+        }
+        Ok(result)
     }
 
     /// Synchronize all state to the session file and provide mutable access to it.
@@ -460,6 +520,18 @@ impl Client {
         &self.0.config.session
     }
 
+    /// Export the authorization of this client, so a future [`Config::ephemeral`] client can
+    /// reuse it without logging in again.
+    ///
+    /// See [`Session::export_auth`] for what is included.
+    ///
+    /// [`Config::ephemeral`]: crate::Config::ephemeral
+    /// [`Session::export_auth`]: grammers_session::Session::export_auth
+    #[must_use]
+    pub fn export_auth(&self) -> Vec<u8> {
+        self.0.config.session.export_auth()
+    }
+
     /// Calls [`Client::sign_out`] and disconnects.
     ///
     /// The client will be disconnected even if signing out fails.
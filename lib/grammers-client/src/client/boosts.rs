@@ -0,0 +1,176 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Methods related to channel boosts.
+
+use crate::types::{Boost, BoostStatus, IterBuffer, User};
+use crate::Client;
+pub use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+use std::collections::HashMap;
+
+const MAX_LIMIT: usize = 100;
+
+pub type BoostIter = IterBuffer<tl::functions::premium::GetBoostsList, Boost>;
+
+impl BoostIter {
+    fn new(client: &Client, channel: PackedChat) -> Self {
+        Self::from_request(
+            client,
+            MAX_LIMIT,
+            tl::functions::premium::GetBoostsList {
+                gifts: false,
+                peer: channel.to_input_peer(),
+                offset: String::new(),
+                limit: 0,
+            },
+        )
+    }
+
+    /// Only return the boosts that come from gift codes.
+    pub fn gifts(mut self, gifts: bool) -> Self {
+        self.request.gifts = gifts;
+        self
+    }
+
+    /// Determines how many boosts there are in total.
+    ///
+    /// This only performs a network call if `next` has not been called before.
+    pub async fn total(&mut self) -> Result<usize, InvocationError> {
+        if let Some(total) = self.total {
+            return Ok(total);
+        }
+
+        self.request.limit = 1;
+        let tl::enums::premium::BoostsList::List(boosts) =
+            self.client.invoke(&self.request).await?;
+        let total = boosts.count as usize;
+        self.total = Some(total);
+        Ok(total)
+    }
+
+    /// Return the next `Boost` from the internal buffer, filling the buffer previously if it's
+    /// empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no boosts left.
+    pub async fn next(&mut self) -> Result<Option<Boost>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        self.request.limit = self.determine_limit(MAX_LIMIT);
+        let tl::enums::premium::BoostsList::List(boosts) =
+            self.client.invoke(&self.request).await?;
+
+        {
+            let mut state = self.client.0.state.write().unwrap();
+            // Telegram can return peers without hash (e.g. Users with 'min: true')
+            let _ = state.chat_hashes.extend(&boosts.users, &[]);
+        }
+
+        self.total = Some(boosts.count as usize);
+        match boosts.next_offset {
+            Some(offset) if !offset.is_empty() => self.request.offset = offset,
+            _ => self.last_chunk = true,
+        }
+
+        let users = boosts
+            .users
+            .into_iter()
+            .map(|user| {
+                let user = User::from_raw(user);
+                (user.id(), user)
+            })
+            .collect::<HashMap<_, _>>();
+
+        self.buffer.extend(boosts.boosts.into_iter().map(|boost| {
+            let user = match &boost {
+                tl::enums::Boost::Boost(boost) => {
+                    boost.user_id.and_then(|id| users.get(&id).cloned())
+                }
+            };
+            Boost::from_raw(boost, user)
+        }));
+
+        Ok(self.pop_item())
+    }
+}
+
+/// Method implementations related to channel boosts.
+impl Client {
+    /// Get the boost level of a channel, and how many boosts are needed to reach the next one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(channel: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let status = client.get_boost_status(&channel).await?;
+    /// println!("Level {} with {} boosts", status.level(), status.boosts());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_boost_status<C: Into<PackedChat>>(
+        &self,
+        channel: C,
+    ) -> Result<BoostStatus, InvocationError> {
+        self.invoke(&tl::functions::premium::GetBoostsStatus {
+            peer: channel.into().to_input_peer(),
+        })
+        .await
+        .map(BoostStatus::from_raw)
+    }
+
+    /// Iterate over the boosts applied to a channel.
+    ///
+    /// The logged-in user must be an administrator of the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(channel: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut boosts = client.iter_boosts(&channel);
+    ///
+    /// while let Some(boost) = boosts.next().await? {
+    ///     if let Some(user) = boost.user() {
+    ///         println!("{} boosted until {}", user.full_name(), boost.expires());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_boosts<C: Into<PackedChat>>(&self, channel: C) -> BoostIter {
+        BoostIter::new(self, channel.into())
+    }
+
+    /// Boost a channel using one of the boost slots of the logged-in user, which must have
+    /// Telegram Premium.
+    ///
+    /// If the slot is `None`, Telegram picks one. Using a slot that is already boosting a
+    /// different channel moves the boost to this one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(channel: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.apply_boost(&channel, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn apply_boost<C: Into<PackedChat>>(
+        &self,
+        channel: C,
+        slot: Option<i32>,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::premium::ApplyBoost {
+            slots: slot.map(|slot| vec![slot]),
+            peer: channel.into().to_input_peer(),
+        })
+        .await
+        .map(drop)
+    }
+}
@@ -6,15 +6,27 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 use crate::client::messages::parse_mention_entities;
+use crate::types::inline::query::InlineResult as TypedInlineResult;
+use crate::types::{BotCommand, BotCommandScope, GameHighScore, Message, User};
 use crate::utils::generate_random_id;
 use crate::Client;
 use crate::{types::IterBuffer, InputMessage};
 pub use grammers_mtsender::{AuthorizationError, InvocationError};
 use grammers_session::PackedChat;
 use grammers_tl_types as tl;
+use std::collections::HashMap;
 
 const MAX_LIMIT: usize = 50;
 
+/// A builder for setting the score of a user in a game.
+///
+/// It will be executed once [`GameScoreBuilder::send`] is called.
+pub struct GameScoreBuilder {
+    client: Client,
+    chat: PackedChat,
+    request: tl::functions::messages::SetGameScore,
+}
+
 pub struct InlineResult {
     client: Client,
     query_id: i64,
@@ -125,6 +137,60 @@ impl InlineResultIter {
     }
 }
 
+impl GameScoreBuilder {
+    fn new(
+        client: &Client,
+        chat: PackedChat,
+        message_id: i32,
+        user: PackedChat,
+        score: i32,
+    ) -> Self {
+        Self {
+            client: client.clone(),
+            chat,
+            request: tl::functions::messages::SetGameScore {
+                edit_message: true,
+                force: false,
+                peer: chat.to_input_peer(),
+                id: message_id,
+                user_id: user.to_input_user_lossy(),
+                score,
+            },
+        }
+    }
+
+    /// Whether the score should be set even if it is lower than the current score of the user.
+    ///
+    /// This is useful to reset scores, for example, after detecting cheating.
+    pub fn force(mut self, force: bool) -> Self {
+        self.request.force = force;
+        self
+    }
+
+    /// Whether the game message should be edited to include the new high score table.
+    ///
+    /// Enabled by default.
+    pub fn edit_message(mut self, edit_message: bool) -> Self {
+        self.request.edit_message = edit_message;
+        self
+    }
+
+    /// Set the score.
+    ///
+    /// Fails with `BOT_SCORE_NOT_MODIFIED` if the score is not greater than the current one,
+    /// unless [`GameScoreBuilder::force`] is used.
+    pub async fn send(self) -> Result<(), InvocationError> {
+        let mut chat = self.chat;
+        self.client
+            .invoke_migrating(&mut chat, |chat| tl::functions::messages::SetGameScore {
+                peer: chat.to_input_peer(),
+                ..self.request.clone()
+            })
+            .await
+            .map(drop)
+    }
+}
+
 /// Method implementations related to dealing with bots.
 impl Client {
     /// Perform an inline query to the specified bot.
@@ -195,4 +261,217 @@ impl Client {
         };
         Ok(result)
     }
+
+    /// Set the commands shown in the menu of the logged-in bot for the given scope and language.
+    ///
+    /// The language is a two-letter ISO 639-1 code. If empty, the commands will apply to all users
+    /// in the scope for whose language there are no dedicated commands.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::{BotCommand, BotCommandScope};
+    ///
+    /// client
+    ///     .set_bot_commands(
+    ///         BotCommandScope::Default,
+    ///         "",
+    ///         vec![
+    ///             BotCommand::new("start", "Start using the bot"),
+    ///             BotCommand::new("help", "Show the available commands"),
+    ///         ],
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_bot_commands<I: IntoIterator<Item = BotCommand>>(
+        &self,
+        scope: BotCommandScope,
+        lang_code: &str,
+        commands: I,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::bots::SetBotCommands {
+            scope: scope.to_raw(),
+            lang_code: lang_code.to_string(),
+            commands: commands.into_iter().map(|c| c.to_raw()).collect(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Get the commands shown in the menu of the logged-in bot for the given scope and language.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::BotCommandScope;
+    ///
+    /// for command in client.get_bot_commands(BotCommandScope::Default, "").await? {
+    ///     println!("/{} - {}", command.command, command.description);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_bot_commands(
+        &self,
+        scope: BotCommandScope,
+        lang_code: &str,
+    ) -> Result<Vec<BotCommand>, InvocationError> {
+        Ok(self
+            .invoke(&tl::functions::bots::GetBotCommands {
+                scope: scope.to_raw(),
+                lang_code: lang_code.to_string(),
+            })
+            .await?
+            .into_iter()
+            .map(BotCommand::from_raw)
+            .collect())
+    }
+
+    /// Answer a query sent by a Web App through its `query_id`, sending the given result as a
+    /// message on behalf of the user who opened it.
+    ///
+    /// The query identifier is found in the `initData` of the Web App, which should be checked
+    /// with [`validate_init_data`] first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client, query_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::inline::query::InlineResult;
+    ///
+    /// client
+    ///     .answer_web_app_query(query_id, InlineResult::article("Order", "Order placed!"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`validate_init_data`]: crate::types::web_app::validate_init_data
+    pub async fn answer_web_app_query<R: Into<TypedInlineResult>>(
+        &self,
+        query_id: &str,
+        result: R,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::SendWebViewResultMessage {
+            bot_query_id: query_id.to_string(),
+            result: result.into().into(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Set the score of a user in the game contained in the given message.
+    ///
+    /// By default, the score is only updated if it is greater than the current one, and the game
+    /// message is edited to show the new high scores. See [`GameScoreBuilder`] to change this.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message, user: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_game_score(&user, &message, 1200).force(true).send().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_game_score<U: Into<PackedChat>>(
+        &self,
+        user: U,
+        message: &Message,
+        score: i32,
+    ) -> GameScoreBuilder {
+        GameScoreBuilder::new(
+            self,
+            message.chat().pack(),
+            message.id(),
+            user.into(),
+            score,
+        )
+    }
+
+    /// Get the high score table of the game contained in the given message, around the given
+    /// user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message, user: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for score in client.get_game_high_scores(&message, &user).await? {
+    ///     println!("{}. {} points by {}", score.position(), score.score(), score.user_id());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_game_high_scores<U: Into<PackedChat>>(
+        &self,
+        message: &Message,
+        user: U,
+    ) -> Result<Vec<GameHighScore>, InvocationError> {
+        let mut chat = message.chat().pack();
+        let user = user.into().to_input_user_lossy();
+        let tl::enums::messages::HighScores::Scores(scores) = self
+            .invoke_migrating(&mut chat, |chat| {
+                tl::functions::messages::GetGameHighScores {
+                    peer: chat.to_input_peer(),
+                    id: message.id(),
+                    user_id: user.clone(),
+                }
+            })
+            .await?;
+
+        {
+            let mut state = self.0.state.write().unwrap();
+            // Telegram can return peers without hash (e.g. Users with 'min: true')
+            let _ = state.chat_hashes.extend(&scores.users, &[]);
+        }
+
+        let mut users = scores
+            .users
+            .into_iter()
+            .map(|user| {
+                let user = User::from_raw(user);
+                (user.id(), user)
+            })
+            .collect::<HashMap<_, _>>();
+
+        Ok(scores
+            .scores
+            .into_iter()
+            .map(|score| {
+                let tl::enums::HighScore::Score(score) = score;
+                let user = users.remove(&score.user_id);
+                GameHighScore::from_raw(score, user)
+            })
+            .collect())
+    }
+
+    /// Delete the commands of the logged-in bot for the given scope and language.
+    ///
+    /// Users will see the commands of the next broader scope instead, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::BotCommandScope;
+    ///
+    /// client.delete_bot_commands(BotCommandScope::Chat(chat.pack()), "").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_bot_commands(
+        &self,
+        scope: BotCommandScope,
+        lang_code: &str,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::bots::ResetBotCommands {
+            scope: scope.to_raw(),
+            lang_code: lang_code.to_string(),
+        })
+        .await
+        .map(drop)
+    }
 }
@@ -0,0 +1,93 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Methods related to phone calls.
+
+use crate::types::phone_call::{CallError, PhoneCall, State};
+use crate::Client;
+use grammers_crypto::calls;
+use grammers_crypto::secret_chat as crypto;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+/// Method implementations related to phone calls.
+impl Client {
+    /// Call the given user, using a protocol which must be supported by the media library.
+    ///
+    /// The library only performs the signaling and key exchange needed to establish the call.
+    /// The media itself must be handled by a library such as `libtgvoip` or `tgcalls`, with the
+    /// parameters exposed by the [`PhoneCall`] once [`CallUpdate::Established`] is received.
+    ///
+    /// [`CallUpdate::Established`]: crate::types::CallUpdate::Established
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::grammers_tl_types as tl;
+    /// use grammers_client::types::CallUpdate;
+    ///
+    /// let protocol = tl::types::PhoneCallProtocol {
+    ///     udp_p2p: true,
+    ///     udp_reflector: true,
+    ///     min_layer: 65,
+    ///     max_layer: 92,
+    ///     library_versions: vec!["2.4.4".to_string()],
+    /// };
+    ///
+    /// let mut call = client.request_call(&user, false, protocol).await?;
+    /// // Updates are only forwarded to the call while `client.next_update()` is being polled.
+    /// loop {
+    ///     match call.next_update().await? {
+    ///         CallUpdate::Established => {
+    ///             println!("verify the emojis: {:?}", call.emojis());
+    ///             // Hand `call.key()` and `call.connections()` to the media library.
+    ///         }
+    ///         CallUpdate::Discarded { .. } => break,
+    ///         _ => {}
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn request_call<C: Into<PackedChat>>(
+        &self,
+        user: C,
+        video: bool,
+        protocol: tl::types::PhoneCallProtocol,
+    ) -> Result<PhoneCall, CallError> {
+        let user = user.into();
+        let (dh, random) = self
+            .get_dh_params()
+            .await?
+            .ok_or(CallError::UnsafeParameters)?;
+        let secret = crypto::generate_secret(&random);
+        let g_a = crypto::calculate_g_a(dh.g, &dh.p, &secret).ok_or(CallError::UnsafeParameters)?;
+
+        let tl::enums::phone::PhoneCall::Call(call) = self
+            .invoke(&tl::functions::phone::RequestCall {
+                video,
+                user_id: user.to_input_user_lossy(),
+                random_id: crate::utils::generate_random_id() as i32,
+                g_a_hash: tl::byte_string(calls::g_a_hash(&g_a).to_vec()),
+                protocol: protocol.clone().into(),
+            })
+            .await?;
+
+        match call.phone_call {
+            tl::enums::PhoneCall::Waiting(call) => Ok(PhoneCall::new(
+                self,
+                call.id,
+                call.access_hash,
+                call.participant_id,
+                video,
+                State::new(true, dh, secret, protocol, g_a.to_vec()),
+            )),
+            _ => Err(CallError::Discarded),
+        }
+    }
+}
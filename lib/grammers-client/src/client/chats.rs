@@ -10,22 +10,32 @@
 
 use super::Client;
 use crate::types::{
-    chats::AdminRightsBuilderInner, chats::BannedRightsBuilderInner, AdminRightsBuilder,
-    BannedRightsBuilder, Chat, ChatMap, IterBuffer, Message, Participant, Photo, User,
+    chats::AdminRightsBuilderInner, chats::BannedRightsBuilderInner, AdminRights,
+    AdminRightsBuilder, BannedRightsBuilder, Chat, ChatMap, ChatPermissions, IterBuffer, Message,
+    Participant, Photo, SponsoredMessage, SponsoredMessages, User,
 };
+use crate::utils;
+use chrono::{DateTime, Utc};
 use grammers_mtsender::RpcError;
 pub use grammers_mtsender::{AuthorizationError, InvocationError};
 use grammers_session::{PackedChat, PackedType};
 use grammers_tl_types as tl;
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::watch;
 
 const MAX_PARTICIPANT_LIMIT: usize = 200;
 const MAX_PHOTO_LIMIT: usize = 100;
 const KICK_BAN_DURATION: i32 = 60; // in seconds, in case the second request fails
 
+/// Convert the chat into a channel, for the requests which only work with channels.
+pub(crate) fn input_channel(chat: PackedChat) -> Result<tl::enums::InputChannel, InvocationError> {
+    chat.try_to_input_channel()
+        .ok_or(InvocationError::InvalidInput("chat is not a channel"))
+}
+
 pub enum ParticipantIter {
     Empty,
     Chat {
@@ -35,6 +45,11 @@ pub enum ParticipantIter {
         total: Option<usize>,
     },
     Channel(IterBuffer<tl::functions::channels::GetParticipants, Participant>),
+    Aggressive {
+        iter: IterBuffer<tl::functions::channels::GetParticipants, Participant>,
+        queries: VecDeque<String>,
+        seen: HashSet<i64>,
+    },
 }
 
 impl ParticipantIter {
@@ -76,7 +91,7 @@ impl ParticipantIter {
                     self.fill_buffer().await
                 }
             }
-            Self::Channel(iter) => {
+            Self::Channel(iter) | Self::Aggressive { iter, .. } => {
                 if let Some(total) = iter.total {
                     Ok(total)
                 } else {
@@ -135,48 +150,79 @@ impl ParticipantIter {
                 *total = Some(buffer.len());
                 Ok(buffer.len())
             }
-            Self::Channel(iter) => {
-                assert!(iter.buffer.is_empty());
-                use tl::enums::channels::ChannelParticipants::*;
-
-                iter.request.limit = iter.determine_limit(MAX_PARTICIPANT_LIMIT);
-                let (count, participants, chats, users) =
-                    match iter.client.invoke(&iter.request).await? {
-                        Participants(p) => (p.count, p.participants, p.chats, p.users),
-                        NotModified => {
-                            panic!("API returned Dialogs::NotModified even though hash = 0")
-                        }
-                    };
+            Self::Channel(iter) => Self::fill_channel_buffer(iter).await,
+            Self::Aggressive {
+                iter,
+                queries,
+                seen,
+            } => {
+                // Only the first request knows the real amount of participants.
+                let total = iter.total;
+                loop {
+                    if iter.last_chunk {
+                        let Some(q) = queries.pop_front() else {
+                            break;
+                        };
+                        // `aggressive` only allows filters that take a query.
+                        iter.request.filter = with_query(&iter.request.filter, q).unwrap();
+                        iter.request.offset = 0;
+                        iter.last_chunk = false;
+                    }
 
-                {
-                    let mut state = iter.client.0.state.write().unwrap();
-                    // Telegram can return peers without hash (e.g. Users with 'min: true')
-                    let _ = state.chat_hashes.extend(&users, &chats);
+                    Self::fill_channel_buffer(iter).await?;
+                    iter.buffer.retain(|p| seen.insert(p.user.id()));
+                    if !iter.buffer.is_empty() {
+                        break;
+                    }
+                }
+                if total.is_some() {
+                    iter.total = total;
                 }
+                Ok(iter.total.unwrap_or(seen.len()))
+            }
+        }
+    }
 
-                // Telegram can return less participants than asked for but the count being higher
-                // (for example, count=4825, participants=199, users=200). The missing participant
-                // was an admin bot account, not sure why it's not included.
-                //
-                // In any case we pick whichever size is highest to avoid weird cases like this.
-                iter.last_chunk =
-                    usize::max(participants.len(), users.len()) < iter.request.limit as usize;
-                iter.request.offset += participants.len() as i32;
+    async fn fill_channel_buffer(
+        iter: &mut IterBuffer<tl::functions::channels::GetParticipants, Participant>,
+    ) -> Result<usize, InvocationError> {
+        assert!(iter.buffer.is_empty());
+        use tl::enums::channels::ChannelParticipants::*;
+
+        iter.request.limit = iter.determine_limit(MAX_PARTICIPANT_LIMIT);
+        let (count, participants, chats, users) = match iter.client.invoke(&iter.request).await? {
+            Participants(p) => (p.count, p.participants, p.chats, p.users),
+            NotModified => {
+                panic!("API returned Dialogs::NotModified even though hash = 0")
+            }
+        };
 
-                // Don't actually care for the chats, just the users.
-                let mut chats = ChatMap::new(users, Vec::new());
-                let chats = Arc::get_mut(&mut chats).unwrap();
+        {
+            let mut state = iter.client.0.state.write().unwrap();
+            // Telegram can return peers without hash (e.g. Users with 'min: true')
+            let _ = state.chat_hashes.extend(&users, &chats);
+        }
 
-                iter.buffer.extend(
-                    participants
-                        .into_iter()
-                        .map(|p| Participant::from_raw_channel(chats, p)),
-                );
+        // Telegram can return less participants than asked for but the count being higher
+        // (for example, count=4825, participants=199, users=200). The missing participant
+        // was an admin bot account, not sure why it's not included.
+        //
+        // In any case we pick whichever size is highest to avoid weird cases like this.
+        iter.last_chunk = usize::max(participants.len(), users.len()) < iter.request.limit as usize;
+        iter.request.offset += participants.len() as i32;
 
-                iter.total = Some(count as usize);
-                Ok(count as usize)
-            }
-        }
+        // Don't actually care for the chats, just the users.
+        let mut chats = ChatMap::new(users, Vec::new());
+        let chats = Arc::get_mut(&mut chats).unwrap();
+
+        iter.buffer.extend(
+            participants
+                .into_iter()
+                .map(|p| Participant::from_raw_channel(chats, p)),
+        );
+
+        iter.total = Some(count as usize);
+        Ok(count as usize)
     }
 
     /// Return the next `Participant` from the internal buffer, filling the buffer previously if
@@ -198,6 +244,14 @@ impl ParticipantIter {
                 }
                 self.fill_buffer().await?;
             }
+            Self::Aggressive { iter, queries, .. } => {
+                if iter.limit_reached() {
+                    return Ok(None);
+                }
+                if iter.buffer.is_empty() && !(iter.last_chunk && queries.is_empty()) {
+                    self.fill_buffer().await?;
+                }
+            }
         }
 
         match self {
@@ -210,21 +264,88 @@ impl ParticipantIter {
                 Ok(result)
             }
             Self::Channel(iter) => Ok(iter.pop_item()),
+            Self::Aggressive { iter, .. } => Ok(iter.pop_item()),
         }
     }
 
     /// apply a filter on fetched participants, note that this filter will apply only on large `Channel` and not small groups
-    pub fn filter(mut self, filter: tl::enums::ChannelParticipantsFilter) -> Self {
+    ///
+    /// Either a [`ParticipantFilter`] or the raw filter can be used.
+    ///
+    /// [`ParticipantFilter`]: crate::types::ParticipantFilter
+    pub fn filter<F: Into<tl::enums::ChannelParticipantsFilter>>(self, filter: F) -> Self {
+        match self {
+            ParticipantIter::Channel(mut c) => {
+                c.request.filter = filter.into();
+                ParticipantIter::Channel(c)
+            }
+            ParticipantIter::Aggressive { iter, .. } => {
+                ParticipantIter::Channel(iter).filter(filter).aggressive()
+            }
+            _ => self,
+        }
+    }
+
+    /// Try harder to fetch every participant of large channels.
+    ///
+    /// Telegram stops returning participants after the first ten thousand or so. In aggressive
+    /// mode, once that happens, the iterator keeps going by searching for participants whose
+    /// name starts with each letter and digit, skipping the ones that were already returned.
+    ///
+    /// This is considerably slower and more likely to hit flood waits, and may still miss
+    /// participants whose names use other scripts. It only affects large `Channel`s, and any
+    /// search query set through [`ParticipantIter::filter`] is used only for the first round.
+    ///
+    /// The filter itself is kept for every round. Filters that cannot be searched by name, such
+    /// as [`ParticipantFilter::Admins`] or [`ParticipantFilter::Bots`], are not affected by this
+    /// mode.
+    ///
+    /// [`ParticipantFilter::Admins`]: crate::types::ParticipantFilter::Admins
+    /// [`ParticipantFilter::Bots`]: crate::types::ParticipantFilter::Bots
+    pub fn aggressive(self) -> Self {
         match self {
-            ParticipantIter::Channel(ref mut c) => {
-                c.request.filter = filter;
-                self
+            ParticipantIter::Channel(iter)
+                if with_query(&iter.request.filter, String::new()).is_some() =>
+            {
+                ParticipantIter::Aggressive {
+                    iter,
+                    queries: ('a'..='z')
+                        .chain('0'..='9')
+                        .map(|c| c.to_string())
+                        .collect(),
+                    seen: HashSet::new(),
+                }
             }
             _ => self,
         }
     }
 }
 
+/// Return a copy of the `filter` searching for participants matching `q`, if it can search.
+///
+/// The recent participants filter is turned into a search, while the rest keep their kind.
+fn with_query(
+    filter: &tl::enums::ChannelParticipantsFilter,
+    q: String,
+) -> Option<tl::enums::ChannelParticipantsFilter> {
+    use tl::enums::ChannelParticipantsFilter as F;
+
+    Some(match filter {
+        F::ChannelParticipantsRecent | F::ChannelParticipantsSearch(_) => {
+            tl::types::ChannelParticipantsSearch { q }.into()
+        }
+        F::ChannelParticipantsKicked(_) => tl::types::ChannelParticipantsKicked { q }.into(),
+        F::ChannelParticipantsBanned(_) => tl::types::ChannelParticipantsBanned { q }.into(),
+        F::ChannelParticipantsContacts(_) => tl::types::ChannelParticipantsContacts { q }.into(),
+        F::ChannelParticipantsMentions(mentions) => tl::types::ChannelParticipantsMentions {
+            q: Some(q),
+            top_msg_id: mentions.top_msg_id,
+        }
+        .into(),
+        F::ChannelParticipantsAdmins | F::ChannelParticipantsBots => return None,
+    })
+}
+
 pub enum ProfilePhotoIter {
     User(IterBuffer<tl::functions::photos::GetUserPhotos, Photo>),
     Chat(IterBuffer<tl::functions::messages::Search, Message>),
@@ -293,8 +414,7 @@ impl ProfilePhotoIter {
                     iter.request.offset += photos.len() as i32;
                 }
 
-                iter.buffer
-                    .extend(photos.into_iter().map(|x| Photo::from_raw(x)));
+                iter.buffer.extend(photos.into_iter().map(Photo::from_raw));
 
                 Ok(total)
             }
@@ -355,6 +475,64 @@ fn updates_to_chat(id: Option<i64>, updates: tl::enums::Updates) -> Option<Chat>
     .map(Chat::from_raw)
 }
 
+/// A builder for creating a new channel or megagroup.
+///
+/// It will be executed once [`ChannelBuilder::send`] is called.
+pub struct ChannelBuilder {
+    client: Client,
+    request: tl::functions::channels::CreateChannel,
+}
+
+impl ChannelBuilder {
+    fn new(client: &Client, title: String, about: String) -> Self {
+        Self {
+            client: client.clone(),
+            request: tl::functions::channels::CreateChannel {
+                broadcast: true,
+                megagroup: false,
+                for_import: false,
+                forum: false,
+                title,
+                about,
+                geo_point: None,
+                address: None,
+                ttl_period: None,
+            },
+        }
+    }
+
+    /// Whether to create a megagroup (also known as supergroup), where every member can post,
+    /// instead of a broadcast channel.
+    pub fn megagroup(mut self, megagroup: bool) -> Self {
+        self.request.megagroup = megagroup;
+        self.request.broadcast = !megagroup;
+        if !megagroup {
+            self.request.forum = false;
+        }
+        self
+    }
+
+    /// Whether to create a megagroup with forum topics enabled. Implies [`ChannelBuilder::megagroup`].
+    pub fn forum(mut self, forum: bool) -> Self {
+        self.request.forum = forum;
+        if forum {
+            self.request.megagroup = true;
+            self.request.broadcast = false;
+        }
+        self
+    }
+
+    /// Create the channel.
+    pub async fn send(self) -> Result<Chat, InvocationError> {
+        let updates = self.client.invoke(&self.request).await?;
+        updates_to_chat(None, updates).ok_or_else(chat_missing)
+    }
+}
+
+fn chat_missing() -> InvocationError {
+    utils::unexpected_response::<tl::types::Chat>()
+}
+
 /// Method implementations related to dealing with chats or other users.
 impl Client {
     /// Resolves a username into the chat that owns it, if any.
@@ -431,6 +609,87 @@ impl Client {
         Ok(User::from_raw(res.pop().unwrap()))
     }
 
+    /// Update the profile of the logged-in user, returning the updated user.
+    ///
+    /// Only the fields which are `Some` are changed. An empty `about` removes the bio.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.update_profile(Some("Ferris"), None, Some("Crab enthusiast")).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn update_profile(
+        &self,
+        first_name: Option<&str>,
+        last_name: Option<&str>,
+        about: Option<&str>,
+    ) -> Result<User, InvocationError> {
+        self.invoke(&tl::functions::account::UpdateProfile {
+            first_name: first_name.map(str::to_string),
+            last_name: last_name.map(str::to_string),
+            about: about.map(str::to_string),
+        })
+        .await
+        .map(User::from_raw)
+    }
+
+    /// Change the username of the logged-in user, returning the updated user.
+    ///
+    /// An empty username removes the current one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_username("ferris").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_username(&self, username: &str) -> Result<User, InvocationError> {
+        self.invoke(&tl::functions::account::UpdateUsername {
+            username: username.to_string(),
+        })
+        .await
+        .map(User::from_raw)
+    }
+
+    /// Set the custom emoji shown next to the name of the logged-in user, which requires
+    /// Telegram Premium. `None` removes the current emoji status.
+    ///
+    /// If `until` is given, the status will be removed automatically at that date.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let custom_emoji_id = 5368324170671202286;
+    /// client.set_emoji_status(Some(custom_emoji_id), None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_emoji_status(
+        &self,
+        document_id: Option<i64>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<(), InvocationError> {
+        let emoji_status = match (document_id, until) {
+            (None, _) => tl::enums::EmojiStatus::Empty,
+            (Some(document_id), None) => tl::types::EmojiStatus { document_id }.into(),
+            (Some(document_id), Some(until)) => tl::types::EmojiStatusUntil {
+                document_id,
+                until: until.timestamp() as i32,
+            }
+            .into(),
+        };
+
+        self.invoke(&tl::functions::account::UpdateEmojiStatus { emoji_status })
+            .await
+            .map(drop)
+    }
+
     /// Iterate over the participants of a chat.
     ///
     /// The participants are returned in no particular order.
@@ -604,6 +863,93 @@ impl Client {
         )
     }
 
+    /// Ban a user from the chat, removing them and preventing them from joining again.
+    ///
+    /// The ban lasts until the given time, or forever if `until` is `None`. In small group chats,
+    /// the user is removed from the chat, since bans are not supported there.
+    ///
+    /// This is a shorthand for [`Client::set_banned_rights`] taking away `view_messages`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use chrono::{Duration, Utc};
+    ///
+    /// // Ban the user for a week
+    /// let until = Utc::now() + Duration::weeks(1);
+    /// client.ban_member(&chat, &user, Some(until)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn ban_member<C: Into<PackedChat>, U: Into<PackedChat>>(
+        &self,
+        chat: C,
+        user: U,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<(), InvocationError> {
+        let mut permissions = ChatPermissions::new().view_messages(false);
+        if let Some(until) = until {
+            permissions = permissions.until(until);
+        }
+        self.restrict_member(chat, user, &permissions).await
+    }
+
+    /// Apply the given permissions to a non-admin user in the chat.
+    ///
+    /// Every permission that is not granted is taken away from the user, until the date set in the
+    /// permissions, or forever. Passing the default [`ChatPermissions`] lifts all restrictions.
+    ///
+    /// Small group chats only support removing the user (taking away `view_messages`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::ChatPermissions;
+    ///
+    /// let muted = ChatPermissions::new().send_messages(false);
+    /// client.restrict_member(&chat, &user, &muted).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn restrict_member<C: Into<PackedChat>, U: Into<PackedChat>>(
+        &self,
+        chat: C,
+        user: U,
+        permissions: &ChatPermissions,
+    ) -> Result<(), InvocationError> {
+        self.set_banned_rights(chat, user).rights(permissions).await
+    }
+
+    /// Turn a user into an administrator of the chat with the given rights.
+    ///
+    /// Any right that is not granted is taken away if the user was already an administrator.
+    /// Passing the default [`AdminRights`] demotes them back to a normal user.
+    ///
+    /// Small group chats do not support granular rights, so the user is made an administrator if
+    /// any of the rights is granted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::AdminRights;
+    ///
+    /// let rights = AdminRights::new().pin_messages(true).delete_messages(true);
+    /// client.promote_member(&chat, &user, &rights).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn promote_member<C: Into<PackedChat>, U: Into<PackedChat>>(
+        &self,
+        chat: C,
+        user: U,
+        rights: &AdminRights,
+    ) -> Result<(), InvocationError> {
+        self.set_admin_rights(chat, user).rights(rights).await
+    }
+
     /// Iterate over the history of profile photos for the given user or chat.
     ///
     /// Note that the current photo might not be present in the history, and to avoid doing more
@@ -629,6 +975,83 @@ impl Client {
         ProfilePhotoIter::new(self, chat.into())
     }
 
+    /// Find out which supergroup a small group was migrated to, if it was upgraded.
+    ///
+    /// Returns `None` if the chat is not a small group or if it was not migrated.
+    ///
+    /// Methods that send messages already retry against the migrated supergroup when the small
+    /// group they were given can no longer be used.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn f(group: grammers_client::types::chat::PackedChat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// if let Some(supergroup) = client.get_migrated_chat(group).await? {
+    ///     println!("Group was migrated to supergroup {}", supergroup.id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_migrated_chat(
+        &self,
+        chat: PackedChat,
+    ) -> Result<Option<PackedChat>, InvocationError> {
+        if !chat.is_chat() {
+            return Ok(None);
+        }
+
+        let chats = match self
+            .invoke(&tl::functions::messages::GetChats { id: vec![chat.id] })
+            .await?
+        {
+            tl::enums::messages::Chats::Chats(chats) => chats.chats,
+            tl::enums::messages::Chats::Slice(chat_slice) => chat_slice.chats,
+        };
+
+        {
+            let mut state = self.0.state.write().unwrap();
+            let _ = state.chat_hashes.extend(&[], &chats);
+        }
+
+        Ok(chats
+            .into_iter()
+            .map(Chat::from_raw)
+            .find(|c| c.id() == chat.id)
+            .and_then(|c| c.migrated_to()))
+    }
+
+    /// Invoke a request built for the given chat, retrying once against the supergroup the chat
+    /// was migrated to if the small group can no longer be used.
+    ///
+    /// On success, `chat` is updated to the chat the request was finally sent to.
+    pub(crate) async fn invoke_migrating<
+        R: tl::RemoteCall + tl::Identifiable,
+        F: Fn(PackedChat) -> R,
+    >(
+        &self,
+        chat: &mut PackedChat,
+        request: F,
+    ) -> Result<R::Return, InvocationError> {
+        let first = request(*chat);
+        self.wait_rate_limit::<R>(Some(chat.id)).await;
+        match self.invoke(&first).await {
+            Err(err)
+                if chat.is_chat() && (err.is("CHAT_ID_INVALID") || err.is("CHAT_DEACTIVATED")) =>
+            {
+                match self.get_migrated_chat(*chat).await? {
+                    Some(migrated) => {
+                        *chat = migrated;
+                        let retry = request(migrated);
+                        self.wait_rate_limit::<R>(Some(migrated.id)).await;
+                        self.invoke(&retry).await
+                    }
+                    None => Err(err),
+                }
+            }
+            result => result,
+        }
+    }
+
     /// Convert a [`PackedChat`] back into a [`Chat`].
     ///
     /// # Example
@@ -766,7 +1189,7 @@ impl Client {
         let url_parse = url_parse_result.unwrap();
         let scheme = url_parse.scheme();
         let path = url_parse.path();
-        if url_parse.host_str().is_none() || !vec!["https", "http"].contains(&scheme) {
+        if url_parse.host_str().is_none() || !["https", "http"].contains(&scheme) {
             return None;
         }
         let host = url_parse.host_str().unwrap();
@@ -840,14 +1263,7 @@ impl Client {
         chat: C,
     ) -> Result<Option<Chat>, InvocationError> {
         let chat: PackedChat = chat.into();
-        let channel = chat.try_to_input_channel().ok_or_else(|| {
-            InvocationError::Rpc(RpcError {
-                code: 400,
-                name: "CHANNEL_INVALID".to_owned(),
-                value: None,
-                caused_by: None,
-            })
-        })?;
+        let channel = input_channel(chat)?;
         Ok(updates_to_chat(
             Some(chat.id),
             self.invoke(&tl::functions::channels::JoinChannel { channel })
@@ -855,6 +1271,243 @@ impl Client {
         ))
     }
 
+    /// Create a new basic group with the given title and initial members.
+    ///
+    /// At least one user other than the logged-in user is required by Telegram. Chats which are
+    /// not users are ignored. Use [`Client::create_channel`] for groups without members.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let group = client.create_group("Weekend plans", &[user.pack()]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_group(
+        &self,
+        title: &str,
+        users: &[PackedChat],
+    ) -> Result<Chat, InvocationError> {
+        let tl::enums::messages::InvitedUsers::Users(invited) = self
+            .invoke(&tl::functions::messages::CreateChat {
+                users: users
+                    .iter()
+                    .filter_map(|user| user.try_to_input_user())
+                    .collect(),
+                title: title.to_string(),
+                ttl_period: None,
+            })
+            .await?;
+
+        updates_to_chat(None, invited.updates).ok_or_else(chat_missing)
+    }
+
+    /// Prepare to create a new broadcast channel with the given title and description.
+    ///
+    /// The returned builder can also create megagroups instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let group = client
+    ///     .create_channel("Rust enthusiasts", "Talk about Rust here")
+    ///     .megagroup(true)
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_channel(&self, title: &str, about: &str) -> ChannelBuilder {
+        ChannelBuilder::new(self, title.to_string(), about.to_string())
+    }
+
+    /// Delete a group or channel owned by the logged-in user, for every member.
+    ///
+    /// This cannot be undone, and is different from [`Client::delete_dialog`], which only leaves
+    /// the chat.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.delete_chat(&chat).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_chat<C: Into<PackedChat>>(&self, chat: C) -> Result<(), InvocationError> {
+        let chat = chat.into();
+        if let Some(channel) = chat.try_to_input_channel() {
+            self.invoke(&tl::functions::channels::DeleteChannel { channel })
+                .await
+                .map(drop)
+        } else if let Some(chat_id) = chat.try_to_chat_id() {
+            self.invoke(&tl::functions::messages::DeleteChat { chat_id })
+                .await
+                .map(drop)
+        } else {
+            Err(InvocationError::InvalidInput(
+                "chat is not a group or channel",
+            ))
+        }
+    }
+
+    /// Change the title of a group or channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_chat_title(&chat, "New title").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_chat_title<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        title: &str,
+    ) -> Result<(), InvocationError> {
+        let chat = chat.into();
+        if let Some(channel) = chat.try_to_input_channel() {
+            self.invoke(&tl::functions::channels::EditTitle {
+                channel,
+                title: title.to_string(),
+            })
+            .await
+            .map(drop)
+        } else if let Some(chat_id) = chat.try_to_chat_id() {
+            self.invoke(&tl::functions::messages::EditChatTitle {
+                chat_id,
+                title: title.to_string(),
+            })
+            .await
+            .map(drop)
+        } else {
+            Err(InvocationError::InvalidInput(
+                "chat is not a group or channel",
+            ))
+        }
+    }
+
+    /// Change the description of a group or channel. An empty description removes it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.set_chat_description(&chat, "A place to talk about crabs").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_chat_description<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        description: &str,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::EditChatAbout {
+            peer: chat.into().to_input_peer(),
+            about: description.to_string(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Whether users must join the megagroup before they can send messages in it.
+    ///
+    /// This only has an effect on megagroups linked as the discussion group of a channel, where
+    /// users can otherwise comment without joining.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.toggle_join_to_send(&chat, true).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn toggle_join_to_send<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        enabled: bool,
+    ) -> Result<(), InvocationError> {
+        let channel = input_channel(chat.into())?;
+
+        self.invoke(&tl::functions::channels::ToggleJoinToSend { channel, enabled })
+            .await
+            .map(drop)
+    }
+
+    /// Fetch the sponsored messages that should be displayed in a broadcast channel.
+    ///
+    /// User accounts are expected to display these messages as required by Telegram's client
+    /// guidelines. Bot accounts cannot use this method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(channel: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let sponsored = client.get_sponsored_messages(&channel).await?;
+    ///
+    /// for message in sponsored.messages() {
+    ///     println!("{}: {}", message.title(), message.text());
+    ///     client.mark_sponsored_viewed(&channel, message).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_sponsored_messages<C: Into<PackedChat>>(
+        &self,
+        channel: C,
+    ) -> Result<SponsoredMessages, InvocationError> {
+        let channel = input_channel(channel.into())?;
+        self.invoke(&tl::functions::channels::GetSponsoredMessages { channel })
+            .await
+            .map(SponsoredMessages::from_raw)
+    }
+
+    /// Report that a sponsored message was displayed to the user.
+    ///
+    /// See [`Client::get_sponsored_messages`] for an example.
+    pub async fn mark_sponsored_viewed<C: Into<PackedChat>>(
+        &self,
+        channel: C,
+        message: &SponsoredMessage,
+    ) -> Result<(), InvocationError> {
+        let channel = input_channel(channel.into())?;
+        self.invoke(&tl::functions::channels::ViewSponsoredMessage {
+            channel,
+            random_id: tl::byte_string(message.random_id().to_vec()),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Report that the user clicked on a sponsored message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(channel: grammers_client::types::Chat, message: grammers_client::types::SponsoredMessage, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.mark_sponsored_clicked(&channel, &message).await?;
+    /// println!("Opening {}", message.url());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn mark_sponsored_clicked<C: Into<PackedChat>>(
+        &self,
+        channel: C,
+        message: &SponsoredMessage,
+    ) -> Result<(), InvocationError> {
+        let channel = input_channel(channel.into())?;
+        self.invoke(&tl::functions::channels::ClickSponsoredMessage {
+            channel,
+            random_id: tl::byte_string(message.random_id().to_vec()),
+        })
+        .await
+        .map(drop)
+    }
+
     /// Send a message action (such as typing, uploading photo, or viewing an emoji interaction)
     ///
     /// # Examples
@@ -913,6 +1566,65 @@ impl Client {
     pub fn action<C: Into<PackedChat>>(&self, chat: C) -> crate::types::ActionSender {
         crate::types::ActionSender::new(self, chat)
     }
+
+    /// Keep sending an action to the chat for as long as the future takes to complete.
+    ///
+    /// Shorthand for [`ActionSender::start`], dropping the guard once the future is done.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    ///
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::ChatAction;
+    ///
+    /// let heavy_task = async {
+    ///     tokio::time::sleep(Duration::from_secs(10)).await;
+    ///
+    ///     42
+    /// };
+    ///
+    /// let result = client.action_while(&chat, ChatAction::Typing, heavy_task).await;
+    /// assert_eq!(result, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`ActionSender::start`]: crate::types::ActionSender::start
+    pub async fn action_while<C, A, F>(&self, chat: C, action: A, future: F) -> F::Output
+    where
+        C: Into<PackedChat>,
+        A: Into<tl::enums::SendMessageAction>,
+        F: Future,
+    {
+        let _guard = self.action(chat).start(action);
+        future.await
+    }
+
+    /// Subscribe to be notified when the actions in the chat and topic should stop being renewed.
+    pub(crate) fn chat_action_stop(
+        &self,
+        chat: PackedChat,
+        topic: Option<i32>,
+    ) -> watch::Receiver<()> {
+        let mut actions = self.0.chat_actions.lock().unwrap();
+        actions.retain(|_, tx| tx.receiver_count() > 0);
+        actions
+            .entry((chat.id, topic))
+            .or_insert_with(|| watch::channel(()).0)
+            .subscribe()
+    }
+
+    /// Stop renewing the actions in the chat and topic, because a message is being sent there,
+    /// which clears them anyway.
+    pub(crate) fn stop_chat_actions(&self, chat: PackedChat, topic: Option<i32>) {
+        self.0
+            .chat_actions
+            .lock()
+            .unwrap()
+            .remove(&(chat.id, topic));
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -980,3 +1692,46 @@ impl ParticipantPermissions {
         }
     }
 }
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::client::testing::fixtures::*;
+    use crate::client::testing::MockServer;
+
+    #[test]
+    fn aggressive_participant_search_keeps_the_filter() {
+        let server = MockServer::new();
+        let client = client(&server);
+        let rounds = 1 + 26 + 10;
+        for _ in 0..rounds {
+            server.respond::<tl::functions::channels::GetParticipants>(
+                tl::types::channels::ChannelParticipants {
+                    count: 0,
+                    participants: Vec::new(),
+                    chats: Vec::new(),
+                    users: Vec::new(),
+                }
+                .into(),
+            );
+        }
+
+        let channel = PackedChat {
+            ty: PackedType::Megagroup,
+            id: 1,
+            access_hash: Some(2),
+        };
+        let mut participants = client
+            .iter_participants(channel)
+            .filter(tl::types::ChannelParticipantsKicked { q: String::new() })
+            .aggressive();
+        assert!(block_on(participants.next()).unwrap().is_none());
+
+        let requests = server.take_requests::<tl::functions::channels::GetParticipants>();
+        assert_eq!(requests.len(), rounds);
+        assert!(requests.iter().all(|request| matches!(
+            request.filter,
+            tl::enums::ChannelParticipantsFilter::ChannelParticipantsKicked(_)
+        )));
+    }
+}
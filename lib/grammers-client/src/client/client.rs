@@ -5,23 +5,36 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
+use crate::types::secret_chat::DhParams;
 use grammers_mtproto::mtp;
-use grammers_mtsender::{self as sender, ReconnectionPolicy, Sender, ServerAddr};
+use grammers_mtsender::{
+    self as sender, ExponentialBackoff, NoObserver, ReconnectionPolicy, Sender, SenderObserver,
+    ServerAddr, TransportKind,
+};
 use grammers_session::{ChatHashCache, MessageBox, Session};
 use grammers_tl_types as tl;
 use sender::Enqueuer;
 use std::collections::{HashMap, VecDeque};
 use std::fmt;
-use std::sync::atomic::AtomicU32;
-use std::sync::{Arc, RwLock};
-use tokio::sync::{Mutex as AsyncMutex, RwLock as AsyncRwLock};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicI32, AtomicU32, AtomicUsize};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch, Mutex as AsyncMutex, Notify, RwLock as AsyncRwLock};
 use web_time::Instant;
 
 use super::net;
+use super::rate_limit::RateLimiter;
 
 /// When no locale is found, use this one instead.
 const DEFAULT_LOCALE: &str = "en";
 
+/// The transports used by default, and when [`InitParams::transports`] is empty.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) const DEFAULT_TRANSPORTS: &[TransportKind] = &TransportKind::FALLBACK_ORDER;
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) const DEFAULT_TRANSPORTS: &[TransportKind] = &[TransportKind::Obfuscated];
+
 /// Configuration required to create a [`Client`] instance.
 ///
 /// [`Client`]: struct.Client.html
@@ -80,7 +93,8 @@ pub struct InitParams {
     /// Telegram passively sends updates to the client through the open connection, so they must
     /// be buffered until the application has the capacity to consume them.
     ///
-    /// Upon reaching this limit, updates will be dropped, and a warning log message will be
+    /// Upon reaching this limit, updates will be dropped according to the
+    /// [`InitParams::update_overflow_policy`], and a warning log message will be
     /// emitted (but not too often, to avoid spamming the log), in order to let the developer
     /// know that they should either change how they handle updates or increase the limit.
     ///
@@ -95,6 +109,112 @@ pub struct InitParams {
     ///
     /// When the limit is `Some`, a buffer to hold that many updates will be pre-allocated.
     pub update_queue_limit: Option<usize>,
+    /// What should happen to new updates once the [`InitParams::update_queue_limit`] is reached?
+    ///
+    /// The amount of updates discarded because of this can be queried with
+    /// [`Client::dropped_updates`].
+    ///
+    /// By default, [`UpdateOverflowPolicy::DropNewest`] is used.
+    pub update_overflow_policy: UpdateOverflowPolicy,
+    /// Should every request made through [`Client::invoke`] be wrapped in `invokeWithoutUpdates`?
+    ///
+    /// Telegram subscribes a connection to the account's update stream as soon as it is used to
+    /// invoke requests. If several clients share the same account, the server may then deliver
+    /// updates to any of them, and the primary client responsible for handling them could miss
+    /// some. Enabling this option on "worker" clients prevents them from stealing updates.
+    ///
+    /// Individual requests can also be sent this way with [`Client::invoke_without_updates`].
+    ///
+    /// By default, requests are invoked normally, and the client does receive updates.
+    pub invoke_without_updates: bool,
+    /// How long should temporary authorization keys last, if [Perfect Forward Secrecy] is used?
+    ///
+    /// When set, every connection generates a temporary authorization key bound to the
+    /// permanent one stored in the session, and only the temporary key is used to encrypt
+    /// messages. Shortly before it expires, the connection is made again with a new key, and
+    /// the requests in flight are sent again through it.
+    ///
+    /// By default, this is `None`, and the permanent key is used directly.
+    ///
+    /// [Perfect Forward Secrecy]: https://core.telegram.org/api/pfs
+    pub temp_auth_key_expiry: Option<Duration>,
+    /// How many connections to the home datacenter should the client keep?
+    ///
+    /// Requests made through [`Client::invoke`] are distributed across all connections, which
+    /// can improve throughput when many requests are made concurrently, such as in busy bots.
+    /// Updates may arrive through any of them, so [`Client::step`] drives all of them.
+    ///
+    /// By default, a single connection is used. A value of zero is treated as one.
+    pub connection_count: usize,
+    /// Should IPv6 addresses be preferred when connecting to Telegram's datacenters?
+    ///
+    /// If connecting through IPv6 fails and no override is configured for the datacenter, the
+    /// library falls back to IPv4. The address that worked is stored in the session, and is
+    /// tried first the next time the same address family is used.
+    ///
+    /// By default, this is `false`, and only IPv4 addresses are used.
+    pub use_ipv6: bool,
+    /// Addresses to use instead of the built-in ones when connecting to specific datacenters.
+    ///
+    /// This can be used to point the library to Telegram's test servers, or to self-hosted
+    /// MTProto endpoints. If several options exist for the same datacenter, those matching the
+    /// preferred address family (see [`InitParams::use_ipv6`]) are tried first.
+    ///
+    /// Unlike [`InitParams::server_addr`], which forces every connection to the same address,
+    /// overrides only apply to the datacenter they are for.
+    ///
+    /// By default, there are no overrides.
+    pub dc_overrides: Vec<DcOption>,
+    /// Should the client connect to Telegram's test datacenters instead of the production ones?
+    ///
+    /// Accounts and authorization keys are not shared between both environments, so a
+    /// different session should be used for each. Only the datacenters 1 to 3 exist in the test
+    /// environment. See [`Client::test_sign_in`] to log in with the test phone numbers.
+    ///
+    /// By default, this is `false`, and the production datacenters are used.
+    pub test_mode: bool,
+    /// Transports to try, in order, when connecting to a datacenter.
+    ///
+    /// If the connection is reset or garbage is received while connecting, which can happen
+    /// in networks that interfere with Telegram's traffic, the next transport is tried. The
+    /// transport that worked is stored in the session, and is tried first next time.
+    ///
+    /// By default, [`TransportKind::FALLBACK_ORDER`] is used, ending with the HTTP transport. On
+    /// the web, only the obfuscated transport is used, since it's the only one supported there.
+    /// The default transports are also used if this is empty.
+    pub transports: Vec<TransportKind>,
+    /// Every how often should keepalive pings be sent to Telegram?
+    ///
+    /// Pings keep the connection active, and are used to measure the round-trip time reported
+    /// by [`Client::last_rtt`].
+    ///
+    /// By default, a ping is sent every minute.
+    pub ping_interval: Duration,
+    /// After how long without receiving a new ping should Telegram close the connection?
+    ///
+    /// This must be larger than [`InitParams::ping_interval`]. The difference between both is
+    /// how long pings may take to arrive before the connection is considered dead, and
+    /// reconnected according to the [`InitParams::reconnection_policy`]. The value is sent
+    /// with second precision.
+    ///
+    /// By default, the connection is closed after 75 seconds without pings.
+    pub ping_disconnect_delay: Duration,
+    /// Requests whose serialized size is at least this many bytes are packed with gzip before
+    /// being sent, as long as that makes them smaller.
+    ///
+    /// Compression reduces the bandwidth used by large requests, such as messages with many
+    /// entities, at the cost of some CPU time. If `None`, requests are never compressed.
+    ///
+    /// By default, requests of 512 bytes or more are compressed.
+    pub compression_threshold: Option<usize>,
+    /// Should the tokens Telegram sends when logging in or out be stored in the session?
+    ///
+    /// Official clients do this, and send the stored tokens the next time a login code is
+    /// requested, so that Telegram recognises the device and can avoid sending a new code
+    /// through SMS when the same user logs in again.
+    ///
+    /// By default, this is `true`, and the tokens are stored and sent.
+    pub future_auth_tokens: bool,
     /// URL of the proxy to use. Requires the `proxy` feature to be enabled.
     ///
     /// The scheme must be `socks5`. Username and password are optional.
@@ -107,7 +227,7 @@ pub struct InitParams {
 
     /// specify the reconnection policy which will be used by client to determine whether to re-connect on failure or not.
     ///
-    ///it can be one of the 2 default implementation [`NoReconnect`] and [`FixedReconnect`];
+    ///it can be one of the 3 default implementation [`NoReconnect`], [`FixedReconnect`] and [`ExponentialBackoff`];
     ///
     /// **OR** your own custom implementation of trait [`ReconnectionPolicy`].
     ///
@@ -115,18 +235,104 @@ pub struct InitParams {
     ///
     /// [`NoReconnect`]: grammers_mtsender::NoReconnect
     /// [`FixedReconnect`]: grammers_mtsender::FixedReconnect
+    /// [`ExponentialBackoff`]: grammers_mtsender::ExponentialBackoff
     /// [`ReconnectionPolicy`]: grammers_mtsender::ReconnectionPolicy
+    ///
+    /// by default, an [`ExponentialBackoff`] with jitter is used, starting at one second and
+    /// waiting up to a minute between attempts, which never gives up.
     pub reconnection_policy: &'static dyn ReconnectionPolicy,
+
+    /// Policy used to determine whether requests that failed due to a temporary issue on
+    /// Telegram's side should be sent again, and how long to wait before doing so.
+    ///
+    /// This applies to internal server errors (any error with code `500`, as well as `-500`,
+    /// `AUTH_RESTART` or `RPC_CALL_FAIL`) and timeouts (code `-503`, `TIMEOUT` or `TIMEDOUT`),
+    /// which are not caused by the request itself. Any other error is returned immediately. Use
+    /// [`NoReconnect`] to never retry.
+    ///
+    /// By default, an [`ExponentialBackoff`] with jitter is used, starting at one second and
+    /// retrying up to three times, waiting at most eight seconds between attempts.
+    ///
+    /// [`NoReconnect`]: grammers_mtsender::NoReconnect
+    /// [`ExponentialBackoff`]: grammers_mtsender::ExponentialBackoff
+    pub retry_policy: &'static dyn ReconnectionPolicy,
+
+    /// Observer notified of the events happening in every connection made by the client, such
+    /// as the amount of bytes sent and received, or the reconnections, once connected.
+    ///
+    /// This can be used to export metrics about the client. Events from all connections are
+    /// reported to the same observer.
+    ///
+    /// By default, a [`NoObserver`] is used, which ignores every event.
+    ///
+    /// [`NoObserver`]: grammers_mtsender::NoObserver
+    pub sender_observer: &'static dyn SenderObserver,
+
+    /// Limits on how often certain requests may be sent, enforced by the client itself.
+    ///
+    /// Requests exceeding a limit are delayed until they may be sent. This can be used to avoid
+    /// running into `FLOOD_WAIT` errors in the first place, such as by limiting
+    /// `messages.sendMessage` to 25 requests per second in bots. See [`RateLimit`] for details.
+    ///
+    /// By default, there are no limits.
+    ///
+    /// [`RateLimit`]: crate::RateLimit
+    pub rate_limits: Vec<super::RateLimit>,
+}
+
+/// Policy applied when the update queue is full, used in [`InitParams::update_overflow_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UpdateOverflowPolicy {
+    /// Discard the updates that arrive while the queue is full.
+    #[default]
+    DropNewest,
+    /// Discard the oldest updates in the queue to make room for the ones that arrive.
+    DropOldest,
+    /// Stop reading from the network while the queue is full, until updates are consumed.
+    ///
+    /// No updates are dropped. Instead, Telegram is slowed down by TCP's own flow control.
+    /// Note that requests still need to read their responses, and updates received alongside
+    /// them will be queued even if the limit was reached.
+    PauseReads,
+}
+
+/// Address override for a single datacenter, used in [`InitParams::dc_overrides`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DcOption {
+    /// Identifier of the datacenter this address belongs to.
+    pub id: i32,
+    /// Socket address where the datacenter can be reached.
+    pub address: SocketAddr,
 }
 
+// The chat identifier and topic where an action is shown
+pub(crate) type ChatActionKey = (i64, Option<i32>);
+
 pub(crate) struct ClientInner {
     // Used to implement `PartialEq`.
     pub(crate) id: i64,
     pub(crate) config: Config,
     pub(crate) conn: Connection,
     pub(crate) state: RwLock<ClientState>,
+    // Held while migrating the home datacenter, so that concurrent requests only migrate once
+    pub(crate) migration: AsyncMutex<()>,
     // Stores per-datacenter downloader instances
     pub(crate) downloader_map: AsyncRwLock<HashMap<i32, Arc<Connection>>>,
+    // Additional connections to the home datacenter, used along `conn` to spread requests
+    pub(crate) pool: RwLock<Vec<Arc<Connection>>>,
+    pub(crate) pool_counter: AtomicUsize,
+    // Secret chats alive, to which their updates are forwarded
+    pub(crate) secret_chats: Mutex<HashMap<i32, mpsc::UnboundedSender<tl::enums::Update>>>,
+    // Phone calls alive, to which their updates are forwarded
+    pub(crate) calls: Mutex<HashMap<i64, mpsc::UnboundedSender<tl::enums::Update>>>,
+    // Last Diffie-Hellman parameters validated for secret chats
+    pub(crate) dh_params: Mutex<Option<DhParams>>,
+    // Notified when updates are consumed from the queue, to resume paused reads
+    pub(crate) update_room: Notify,
+    // Delays requests to stay within the configured rate limits
+    pub(crate) rate_limiter: RateLimiter,
+    // Chat actions renewed in the background, stopped when their sender is dropped
+    pub(crate) chat_actions: Mutex<HashMap<ChatActionKey, watch::Sender<()>>>,
 }
 
 pub(crate) struct ClientState {
@@ -136,13 +342,30 @@ pub(crate) struct ClientState {
     // When did we last warn the user that the update queue filled up?
     // This is used to avoid spamming the log.
     pub(crate) last_update_limit_warn: Option<Instant>,
+    // How many updates were dropped because the update queue was full.
+    pub(crate) dropped_updates: u64,
     pub(crate) updates: VecDeque<(tl::enums::Update, Arc<crate::types::ChatMap>)>,
 }
 
+// What performs the IO of a connection. There is only one per connection, so its size is not
+// worth boxing the network sender for.
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum Driver {
+    Network(Sender<net::Transport, mtp::Encrypted>),
+    #[cfg(feature = "testing")]
+    Mock(super::testing::MockDriver),
+}
+
 pub(crate) struct Connection {
-    pub(crate) sender: AsyncMutex<Sender<net::Transport, mtp::Encrypted>>,
+    // Datacenter the sender is connected to, used to connect it again with a different key
+    pub(crate) dc_id: AtomicI32,
+    pub(crate) sender: AsyncMutex<Driver>,
     pub(crate) request_tx: RwLock<Enqueuer>,
     pub(crate) step_counter: AtomicU32,
+    // Round-trip time last measured by the keepalive pings of the sender or `Client::ping`
+    pub(crate) last_rtt: RwLock<Option<Duration>>,
+    // Difference between the server's clock and ours, in seconds, as last known by the sender
+    pub(crate) time_offset: AtomicI32,
 }
 
 /// A client capable of connecting to Telegram and invoking requests.
@@ -159,6 +382,20 @@ pub(crate) struct Connection {
 #[derive(Clone)]
 pub struct Client(pub(crate) Arc<ClientInner>);
 
+static DEFAULT_RECONNECTION_POLICY: ExponentialBackoff = ExponentialBackoff {
+    initial_delay: Duration::from_secs(1),
+    max_delay: Duration::from_secs(60),
+    max_attempts: None,
+    jitter: true,
+};
+
+static DEFAULT_RETRY_POLICY: ExponentialBackoff = ExponentialBackoff {
+    initial_delay: Duration::from_secs(1),
+    max_delay: Duration::from_secs(8),
+    max_attempts: Some(3),
+    jitter: true,
+};
+
 impl Default for InitParams {
     fn default() -> Self {
         let info = os_info::get();
@@ -188,13 +425,81 @@ impl Default for InitParams {
             server_addr: None,
             flood_sleep_threshold: 60,
             update_queue_limit: Some(100),
+            update_overflow_policy: UpdateOverflowPolicy::DropNewest,
+            invoke_without_updates: false,
+            temp_auth_key_expiry: None,
+            connection_count: 1,
+            use_ipv6: false,
+            dc_overrides: Vec::new(),
+            test_mode: false,
+            transports: DEFAULT_TRANSPORTS.to_vec(),
+            ping_interval: sender::PING_DELAY,
+            ping_disconnect_delay: sender::NO_PING_DISCONNECT,
+            compression_threshold: grammers_mtproto::DEFAULT_COMPRESSION_THRESHOLD,
+            future_auth_tokens: true,
             #[cfg(feature = "proxy")]
             proxy_url: None,
-            reconnection_policy: &grammers_mtsender::NoReconnect,
+            reconnection_policy: &DEFAULT_RECONNECTION_POLICY,
+            retry_policy: &DEFAULT_RETRY_POLICY,
+            sender_observer: &NoObserver,
+            rate_limits: Vec::new(),
         }
     }
 }
 
+impl Config {
+    /// Configuration for a short-lived client that keeps its session in memory only.
+    ///
+    /// If `exported` is `None`, the client will perform a new key exchange upon connecting.
+    /// Otherwise, it must have been produced by [`Client::export_auth`] or
+    /// [`Session::export_auth`], and the client will reuse the authorization key (and signed-in
+    /// user, if any) contained in it, which is much faster than starting from scratch.
+    ///
+    /// Nothing is ever written to disk. Update state is not restored either, so the client will
+    /// not catch up on updates that occurred between runs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grammers_client::{Client, Config};
+    ///
+    /// # async fn f(bot_token: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// let exported = std::env::var_os("BOT_AUTH").map(|blob| blob.into_encoded_bytes());
+    ///
+    /// let client = Client::connect(Config::ephemeral(
+    ///     932939,
+    ///     "514727c32270b9eb8cc16daf17e21e57",
+    ///     exported.as_deref(),
+    /// )?)
+    /// .await?;
+    ///
+    /// if !client.is_authorized().await? {
+    ///     client.bot_sign_in(bot_token).await?;
+    /// }
+    ///
+    /// // ...run a few requests, then keep `client.export_auth()` for the next run.
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Session::export_auth`]: grammers_session::Session::export_auth
+    pub fn ephemeral(
+        api_id: i32,
+        api_hash: impl Into<String>,
+        exported: Option<&[u8]>,
+    ) -> Result<Self, grammers_session::Error> {
+        Ok(Self {
+            session: match exported {
+                Some(data) => Session::load(data)?,
+                None => Session::new(),
+            },
+            api_id,
+            api_hash: api_hash.into(),
+            params: Default::default(),
+        })
+    }
+}
+
 // TODO move some stuff like drop into ClientInner?
 impl Drop for Client {
     fn drop(&mut self) {
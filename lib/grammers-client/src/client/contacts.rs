@@ -0,0 +1,308 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Methods related to the contact list and the blocked users.
+
+use crate::types::{Chat, ChatMap, IterBuffer, PhoneContact, User};
+use crate::Client;
+pub use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+use std::collections::HashMap;
+
+const MAX_LIMIT: usize = 100;
+
+pub type ContactIter = IterBuffer<tl::functions::contacts::GetContacts, User>;
+
+impl ContactIter {
+    fn new(client: &Client) -> Self {
+        Self::from_request(
+            client,
+            MAX_LIMIT,
+            tl::functions::contacts::GetContacts { hash: 0 },
+        )
+    }
+
+    /// Return the next `User` from the internal buffer, filling the buffer previously if it's
+    /// empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no contacts left.
+    pub async fn next(&mut self) -> Result<Option<User>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        // The contact list is always returned in full, so a single request is enough.
+        self.last_chunk = true;
+        let contacts = match self.client.invoke(&self.request).await? {
+            tl::enums::contacts::Contacts::Contacts(contacts) => contacts,
+            tl::enums::contacts::Contacts::NotModified => {
+                panic!("API returned Contacts::NotModified even though hash = 0")
+            }
+        };
+
+        {
+            let mut state = self.client.0.state.write().unwrap();
+            // Telegram can return peers without hash (e.g. Users with 'min: true')
+            let _ = state.chat_hashes.extend(&contacts.users, &[]);
+        }
+
+        let mut users = contacts
+            .users
+            .into_iter()
+            .map(|user| {
+                let user = User::from_raw(user);
+                (user.id(), user)
+            })
+            .collect::<HashMap<_, _>>();
+
+        self.total = Some(contacts.contacts.len());
+        self.buffer
+            .extend(contacts.contacts.into_iter().filter_map(|contact| {
+                let tl::enums::Contact::Contact(contact) = contact;
+                users.remove(&contact.user_id)
+            }));
+
+        Ok(self.pop_item())
+    }
+}
+
+pub type BlockedIter = IterBuffer<tl::functions::contacts::GetBlocked, Chat>;
+
+impl BlockedIter {
+    fn new(client: &Client) -> Self {
+        Self::from_request(
+            client,
+            MAX_LIMIT,
+            tl::functions::contacts::GetBlocked {
+                my_stories_from: false,
+                offset: 0,
+                limit: 0,
+            },
+        )
+    }
+
+    /// Determines how many chats are blocked in total.
+    ///
+    /// This only performs a network call if `next` has not been called before.
+    pub async fn total(&mut self) -> Result<usize, InvocationError> {
+        if let Some(total) = self.total {
+            return Ok(total);
+        }
+
+        self.request.limit = 1;
+        let total = match self.client.invoke(&self.request).await? {
+            tl::enums::contacts::Blocked::Blocked(blocked) => blocked.blocked.len(),
+            tl::enums::contacts::Blocked::Slice(blocked) => blocked.count as usize,
+        };
+        self.total = Some(total);
+        Ok(total)
+    }
+
+    /// Return the next blocked `Chat` from the internal buffer, filling the buffer previously if
+    /// it's empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no blocked chats left.
+    pub async fn next(&mut self) -> Result<Option<Chat>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        self.request.limit = self.determine_limit(MAX_LIMIT);
+        let (blocked, users, chats) = match self.client.invoke(&self.request).await? {
+            tl::enums::contacts::Blocked::Blocked(blocked) => {
+                self.last_chunk = true;
+                self.total = Some(blocked.blocked.len());
+                (blocked.blocked, blocked.users, blocked.chats)
+            }
+            tl::enums::contacts::Blocked::Slice(blocked) => {
+                self.last_chunk = blocked.blocked.len() < self.request.limit as usize;
+                self.total = Some(blocked.count as usize);
+                (blocked.blocked, blocked.users, blocked.chats)
+            }
+        };
+
+        {
+            let mut state = self.client.0.state.write().unwrap();
+            // Telegram can return peers without hash (e.g. Users with 'min: true')
+            let _ = state.chat_hashes.extend(&users, &chats);
+        }
+
+        self.request.offset += blocked.len() as i32;
+        let chats = ChatMap::new(users, chats);
+        self.buffer
+            .extend(blocked.into_iter().filter_map(|blocked| {
+                let tl::enums::PeerBlocked::Blocked(blocked) = blocked;
+                chats.get(&blocked.peer_id).cloned()
+            }));
+
+        Ok(self.pop_item())
+    }
+}
+
+/// Method implementations related to contacts and blocked users.
+impl Client {
+    /// Add phone numbers to the contact list of the logged-in user.
+    ///
+    /// Returns the users that could be imported, in the same order as the input contacts. Those
+    /// phone numbers without a Telegram account or which could not be imported will be `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::PhoneContact;
+    ///
+    /// let contacts = [PhoneContact::new("+1234567890", "Alice").last_name("Smith")];
+    /// for user in client.import_contacts(&contacts).await?.into_iter().flatten() {
+    ///     println!("Imported {}", user.full_name());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn import_contacts(
+        &self,
+        contacts: &[PhoneContact],
+    ) -> Result<Vec<Option<User>>, InvocationError> {
+        // The index of each contact doubles as its client identifier, to map the results back.
+        let tl::enums::contacts::ImportedContacts::Contacts(imported) = self
+            .invoke(&tl::functions::contacts::ImportContacts {
+                contacts: contacts
+                    .iter()
+                    .enumerate()
+                    .map(|(i, contact)| contact.to_raw(i as i64))
+                    .collect(),
+            })
+            .await?;
+
+        {
+            let mut state = self.0.state.write().unwrap();
+            // Telegram can return peers without hash (e.g. Users with 'min: true')
+            let _ = state.chat_hashes.extend(&imported.users, &[]);
+        }
+
+        let mut users = imported
+            .users
+            .into_iter()
+            .map(|user| {
+                let user = User::from_raw(user);
+                (user.id(), user)
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut result = vec![None; contacts.len()];
+        for contact in imported.imported {
+            let tl::enums::ImportedContact::Contact(contact) = contact;
+            if let Some(slot) = result.get_mut(contact.client_id as usize) {
+                *slot = users.remove(&contact.user_id);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Remove users from the contact list of the logged-in user.
+    ///
+    /// Chats which are not users are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.delete_contacts(&[user.pack()]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_contacts(&self, users: &[PackedChat]) -> Result<(), InvocationError> {
+        let id = users
+            .iter()
+            .filter_map(|user| user.try_to_input_user())
+            .collect::<Vec<_>>();
+
+        if id.is_empty() {
+            return Ok(());
+        }
+
+        self.invoke(&tl::functions::contacts::DeleteContacts { id })
+            .await
+            .map(drop)
+    }
+
+    /// Iterate over the contact list of the logged-in user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut contacts = client.iter_contacts();
+    ///
+    /// while let Some(user) = contacts.next().await? {
+    ///     println!("{}", user.full_name());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_contacts(&self) -> ContactIter {
+        ContactIter::new(self)
+    }
+
+    /// Block a user or chat, so that they can no longer send messages to the logged-in user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.block_user(&user).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn block_user<C: Into<PackedChat>>(&self, user: C) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::contacts::Block {
+            my_stories_from: false,
+            id: user.into().to_input_peer(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Unblock a previously-blocked user or chat.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.unblock_user(&user).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn unblock_user<C: Into<PackedChat>>(&self, user: C) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::contacts::Unblock {
+            my_stories_from: false,
+            id: user.into().to_input_peer(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Iterate over the users and chats blocked by the logged-in user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut blocked = client.iter_blocked();
+    ///
+    /// while let Some(chat) = blocked.next().await? {
+    ///     println!("{} is blocked", chat.name().unwrap_or("Deleted"));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_blocked(&self) -> BlockedIter {
+        BlockedIter::new(self)
+    }
+}
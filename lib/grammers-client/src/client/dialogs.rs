@@ -5,7 +5,7 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-use crate::types::{ChatMap, Dialog, IterBuffer, Message};
+use crate::types::{ChatMap, Dialog, IterBuffer, Message, PeerFolder};
 use crate::Client;
 use grammers_mtsender::InvocationError;
 use grammers_session::PackedChat;
@@ -16,6 +16,9 @@ const MAX_LIMIT: usize = 100;
 
 pub type DialogIter = IterBuffer<tl::functions::messages::GetDialogs, Dialog>;
 
+/// Iterator over the pinned dialogs of the logged-in user, created by [`DialogIter::pinned_only`].
+pub type PinnedDialogIter = IterBuffer<tl::functions::messages::GetPinnedDialogs, Dialog>;
+
 impl DialogIter {
     fn new(client: &Client) -> Self {
         // TODO let users tweak all the options from the request
@@ -34,6 +37,30 @@ impl DialogIter {
         )
     }
 
+    /// Only return the dialogs in the given folder.
+    ///
+    /// By default, only the dialogs which are not archived are returned, along with an entry
+    /// for the archive itself.
+    pub fn folder(mut self, folder: PeerFolder) -> Self {
+        self.request.folder_id = Some(folder.id());
+        self
+    }
+
+    /// Only return the dialogs which are pinned, fetching all of them in a single request.
+    ///
+    /// The folder and limit set so far are kept.
+    pub fn pinned_only(self) -> PinnedDialogIter {
+        let mut iter = PinnedDialogIter::from_request(
+            &self.client,
+            MAX_LIMIT,
+            tl::functions::messages::GetPinnedDialogs {
+                folder_id: self.request.folder_id.unwrap_or(0),
+            },
+        );
+        iter.limit = self.limit;
+        iter
+    }
+
     /// Determines how many dialogs there are in total.
     ///
     /// This only performs a network call if `next` has not been called before.
@@ -82,35 +109,7 @@ impl DialogIter {
             }
         };
 
-        {
-            let mut state = self.client.0.state.write().unwrap();
-            // Telegram can return peers without hash (e.g. Users with 'min: true')
-            let _ = state.chat_hashes.extend(&users, &chats);
-        }
-
-        let chats = ChatMap::new(users, chats);
-        let mut messages = messages
-            .into_iter()
-            .flat_map(|m| Message::from_raw(&self.client, m, &chats))
-            .map(|m| ((&m.raw.peer_id).into(), m))
-            .collect::<HashMap<_, _>>();
-
-        {
-            let mut state = self.client.0.state.write().unwrap();
-            self.buffer.extend(dialogs.into_iter().map(|dialog| {
-                if let tl::enums::Dialog::Dialog(tl::types::Dialog {
-                    peer: tl::enums::Peer::Channel(channel),
-                    pts: Some(pts),
-                    ..
-                }) = &dialog
-                {
-                    state
-                        .message_box
-                        .try_set_channel_state(channel.channel_id, *pts);
-                }
-                Dialog::new(dialog, &mut messages, &chats)
-            }));
-        }
+        extend_buffer(self, dialogs, messages, users, chats);
 
         // Don't bother updating offsets if this is the last time stuff has to be fetched.
         if !self.last_chunk && !self.buffer.is_empty() {
@@ -134,6 +133,86 @@ impl DialogIter {
     }
 }
 
+impl PinnedDialogIter {
+    /// Determines how many pinned dialogs there are in total.
+    ///
+    /// This only performs a network call if `next` has not been called before.
+    pub async fn total(&mut self) -> Result<usize, InvocationError> {
+        if self.total.is_none() {
+            self.fill_buffer().await?;
+        }
+        Ok(self.total.unwrap_or(0))
+    }
+
+    /// Return the next pinned `Dialog`, fetching all of them if it's the first call.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no dialogs left.
+    pub async fn next(&mut self) -> Result<Option<Dialog>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        self.fill_buffer().await?;
+        Ok(self.pop_item())
+    }
+
+    /// Fetch all the pinned dialogs at once, as Telegram does not split them in chunks.
+    async fn fill_buffer(&mut self) -> Result<(), InvocationError> {
+        let tl::enums::messages::PeerDialogs::Dialogs(dialogs) =
+            self.client.invoke(&self.request).await?;
+
+        self.last_chunk = true;
+        self.total = Some(dialogs.dialogs.len());
+        extend_buffer(
+            self,
+            dialogs.dialogs,
+            dialogs.messages,
+            dialogs.users,
+            dialogs.chats,
+        );
+        Ok(())
+    }
+}
+
+fn extend_buffer<R>(
+    iter: &mut IterBuffer<R, Dialog>,
+    dialogs: Vec<tl::enums::Dialog>,
+    messages: Vec<tl::enums::Message>,
+    users: Vec<tl::enums::User>,
+    chats: Vec<tl::enums::Chat>,
+) {
+    let client = &iter.client;
+    {
+        let mut state = client.0.state.write().unwrap();
+        // Telegram can return peers without hash (e.g. Users with 'min: true')
+        let _ = state.chat_hashes.extend(&users, &chats);
+    }
+
+    let chats = ChatMap::new(users, chats);
+    let mut messages = messages
+        .into_iter()
+        .flat_map(|m| Message::from_raw(client, m, &chats))
+        .map(|m| ((&m.raw.peer_id).into(), m))
+        .collect::<HashMap<_, _>>();
+
+    {
+        let mut state = client.0.state.write().unwrap();
+        iter.buffer.extend(dialogs.into_iter().map(|dialog| {
+            if let tl::enums::Dialog::Dialog(tl::types::Dialog {
+                peer: tl::enums::Peer::Channel(channel),
+                pts: Some(pts),
+                ..
+            }) = &dialog
+            {
+                state
+                    .message_box
+                    .try_set_channel_state(channel.channel_id, *pts);
+            }
+            Dialog::new(dialog, &mut messages, &chats)
+        }));
+    }
+}
+
 /// Method implementations related to open conversations.
 impl Client {
     /// Returns a new iterator over the dialogs.
@@ -236,6 +315,69 @@ impl Client {
         }
     }
 
+    /// Get how many messages in a chat have not been read yet by the logged-in user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let unread = client.get_unread_count(&chat).await?;
+    /// println!("{} unread messages", unread);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_unread_count<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<usize, InvocationError> {
+        let tl::enums::messages::PeerDialogs::Dialogs(dialogs) = self
+            .invoke(&tl::functions::messages::GetPeerDialogs {
+                peers: vec![tl::types::InputDialogPeer {
+                    peer: chat.into().to_input_peer(),
+                }
+                .into()],
+            })
+            .await?;
+
+        Ok(dialogs
+            .dialogs
+            .into_iter()
+            .find_map(|dialog| match dialog {
+                tl::enums::Dialog::Dialog(dialog) => Some(dialog.unread_count.max(0) as usize),
+                tl::enums::Dialog::Folder(_) => None,
+            })
+            .unwrap_or(0))
+    }
+
+    /// Mark a chat as unread, or remove that mark.
+    ///
+    /// This only sets a visual mark on the dialog and does not change which messages were read.
+    /// The mark is removed automatically once the chat is marked as read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.mark_as_unread(&chat, true).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn mark_as_unread<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        unread: bool,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::MarkDialogUnread {
+            unread,
+            peer: tl::types::InputDialogPeer {
+                peer: chat.into().to_input_peer(),
+            }
+            .into(),
+        })
+        .await
+        .map(drop)
+    }
+
     /// Clears all pending mentions from a chat, marking them as read.
     ///
     /// # Examples
@@ -257,4 +399,125 @@ impl Client {
         .await
         .map(drop)
     }
+
+    /// Move a chat to the archive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.archive(&chat).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn archive<C: Into<PackedChat>>(&self, chat: C) -> Result<(), InvocationError> {
+        self.move_to_folder(chat.into(), PeerFolder::Archived).await
+    }
+
+    /// Move a chat out of the archive, back to the main list of dialogs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.unarchive(&chat).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn unarchive<C: Into<PackedChat>>(&self, chat: C) -> Result<(), InvocationError> {
+        self.move_to_folder(chat.into(), PeerFolder::Main).await
+    }
+
+    async fn move_to_folder(
+        &self,
+        chat: PackedChat,
+        folder: PeerFolder,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::folders::EditPeerFolders {
+            folder_peers: vec![tl::types::InputFolderPeer {
+                peer: chat.to_input_peer(),
+                folder_id: folder.id(),
+            }
+            .into()],
+        })
+        .await
+        .map(drop)
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::client::testing::fixtures::*;
+    use crate::client::testing::MockServer;
+
+    fn pinned_dialog(user_id: i64) -> tl::enums::Dialog {
+        tl::types::Dialog {
+            pinned: true,
+            unread_mark: false,
+            view_forum_as_messages: false,
+            peer: tl::types::PeerUser { user_id }.into(),
+            top_message: 0,
+            read_inbox_max_id: 0,
+            read_outbox_max_id: 0,
+            unread_count: 0,
+            unread_mentions_count: 0,
+            unread_reactions_count: 0,
+            notify_settings: tl::types::PeerNotifySettings {
+                show_previews: None,
+                silent: None,
+                mute_until: None,
+                ios_sound: None,
+                android_sound: None,
+                other_sound: None,
+                stories_muted: None,
+                stories_hide_sender: None,
+                stories_ios_sound: None,
+                stories_android_sound: None,
+                stories_other_sound: None,
+            }
+            .into(),
+            pts: None,
+            draft: None,
+            folder_id: None,
+            ttl_period: None,
+        }
+        .into()
+    }
+
+    #[test]
+    fn pinned_dialogs_respect_the_limit() {
+        let server = MockServer::new();
+        let client = client(&server);
+        server.respond::<tl::functions::messages::GetPinnedDialogs>(
+            tl::types::messages::PeerDialogs {
+                dialogs: vec![pinned_dialog(1), pinned_dialog(2)],
+                messages: Vec::new(),
+                chats: Vec::new(),
+                users: vec![
+                    tl::types::UserEmpty { id: 1 }.into(),
+                    tl::types::UserEmpty { id: 2 }.into(),
+                ],
+                state: tl::types::updates::State {
+                    pts: 0,
+                    qts: 0,
+                    date: 0,
+                    seq: 0,
+                    unread_count: 0,
+                }
+                .into(),
+            }
+            .into(),
+        );
+
+        let mut dialogs = client.iter_dialogs().limit(1).pinned_only();
+        block_on(async {
+            assert_eq!(dialogs.next().await?.map(|d| d.chat().id()), Some(1));
+            assert!(dialogs.next().await?.is_none());
+            assert_eq!(dialogs.total().await?, 2);
+            Ok::<_, InvocationError>(())
+        })
+        .unwrap();
+        assert_eq!(server.request_count(), 1);
+    }
 }
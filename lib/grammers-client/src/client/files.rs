@@ -6,12 +6,14 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use crate::types::{photo_sizes::PhotoSize, Downloadable, Uploaded};
+use crate::types::{photo_sizes::PhotoSize, Downloadable, Media, Uploaded};
 use crate::utils::generate_random_id;
 use crate::Client;
 use futures_util::stream::{FuturesUnordered, StreamExt as _};
 use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
 use grammers_tl_types as tl;
+use std::fmt;
 use std::sync::Arc;
 use tokio::{
     io::{self, AsyncRead, AsyncReadExt},
@@ -20,7 +22,6 @@ use tokio::{
 
 #[cfg(feature = "fs")]
 use {
-    crate::types::Media,
     std::{io::SeekFrom, path::Path},
     tokio::{
         fs,
@@ -40,6 +41,130 @@ pub struct DownloadIter {
     done: bool,
     request: tl::functions::upload::GetFile,
     photo_size_data: Option<Vec<u8>>,
+    source: Option<MediaSource>,
+}
+
+/// Where some downloadable media comes from.
+///
+/// Used to refresh the file reference of the media when it expires.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum MediaSource {
+    Message { chat: PackedChat, message_id: i32 },
+    Story { peer: PackedChat, story_id: i32 },
+}
+
+/// Error produced while downloading a file.
+#[derive(Debug)]
+pub enum DownloadError {
+    /// The request to Telegram failed.
+    Invocation(InvocationError),
+    /// The file reference expired, and there was no source message or story to refresh it from.
+    NoSource,
+    /// The file reference expired, and the source message or story it came from no longer
+    /// exists or no longer has media.
+    SourceUnavailable,
+    /// The file reference expired, and the source message or story it came from now has a
+    /// different file.
+    MediaChanged,
+}
+
+impl fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invocation(e) => write!(f, "download error: {e}"),
+            Self::NoSource => write!(f, "download error: file reference expired with no source"),
+            Self::SourceUnavailable => {
+                write!(f, "download error: file reference source unavailable")
+            }
+            Self::MediaChanged => write!(f, "download error: file reference source changed"),
+        }
+    }
+}
+
+impl std::error::Error for DownloadError {}
+
+impl From<InvocationError> for DownloadError {
+    fn from(error: InvocationError) -> Self {
+        Self::Invocation(error)
+    }
+}
+
+impl MediaSource {
+    /// Fetch the source again, and update the file reference in `location` if it still
+    /// contains the same file.
+    async fn refresh(
+        &self,
+        client: &Client,
+        location: &mut tl::enums::InputFileLocation,
+    ) -> Result<(), DownloadError> {
+        use tl::enums::InputFileLocation as L;
+
+        let media = match *self {
+            Self::Message { chat, message_id } => client
+                .get_messages_by_id(chat, &[message_id])
+                .await?
+                .pop()
+                .flatten()
+                .and_then(|message| message.media()),
+            Self::Story { peer, story_id } => {
+                let tl::enums::stories::Stories::Stories(stories) = client
+                    .invoke(&tl::functions::stories::GetStoriesById {
+                        peer: peer.to_input_peer(),
+                        id: vec![story_id],
+                    })
+                    .await?;
+                stories.stories.into_iter().find_map(|story| match story {
+                    tl::enums::StoryItem::Item(story) if story.id == story_id => {
+                        Media::from_raw(story.media)
+                    }
+                    _ => None,
+                })
+            }
+        };
+        let fresh = media
+            .and_then(|media| media.to_raw_input_location())
+            .ok_or(DownloadError::SourceUnavailable)?;
+
+        match (location, fresh) {
+            (L::InputDocumentFileLocation(old), L::InputDocumentFileLocation(new))
+                if old.id == new.id =>
+            {
+                old.access_hash = new.access_hash;
+                old.file_reference = new.file_reference;
+                Ok(())
+            }
+            (L::InputPhotoFileLocation(old), L::InputPhotoFileLocation(new))
+                if old.id == new.id =>
+            {
+                old.access_hash = new.access_hash;
+                old.file_reference = new.file_reference;
+                Ok(())
+            }
+            _ => Err(DownloadError::MediaChanged),
+        }
+    }
+
+    /// Refresh the file reference after the `error`, unless it was already refreshed.
+    ///
+    /// The error is returned as-is if it has nothing to do with file references.
+    async fn refresh_after(
+        source: Option<Self>,
+        error: InvocationError,
+        refreshed: &mut bool,
+        client: &Client,
+        location: &mut tl::enums::InputFileLocation,
+    ) -> Result<(), DownloadError> {
+        match &error {
+            InvocationError::Rpc(err) if err.is("FILE_REFERENCE_*") && !*refreshed => {
+                *refreshed = true;
+                match source {
+                    Some(source) => source.refresh(client, location).await,
+                    None => Err(DownloadError::NoSource),
+                }
+            }
+            _ => Err(error.into()),
+        }
+    }
 }
 
 impl DownloadIter {
@@ -70,6 +195,7 @@ impl DownloadIter {
                 limit: MAX_CHUNK_SIZE,
             },
             photo_size_data: None,
+            source: None,
         }
     }
 
@@ -85,7 +211,7 @@ impl DownloadIter {
                     tl::types::InputPhotoFileLocation {
                         id: 0,
                         access_hash: 0,
-                        file_reference: vec![],
+                        file_reference: Default::default(),
                         thumb_size: "".to_string(),
                     },
                 ),
@@ -93,6 +219,7 @@ impl DownloadIter {
                 limit: MAX_CHUNK_SIZE,
             },
             photo_size_data: Some(data),
+            source: None,
         }
     }
 
@@ -117,21 +244,49 @@ impl DownloadIter {
         self
     }
 
+    /// Indicate the message the media being downloaded belongs to.
+    ///
+    /// If the file reference of the media expires during the download (which can happen if the
+    /// download takes long enough), the message will be fetched again to refresh the reference,
+    /// and the download will continue where it left off. Without a source, the download fails
+    /// with [`DownloadError::NoSource`] instead.
+    pub fn source_message<C: Into<PackedChat>>(mut self, chat: C, message_id: i32) -> Self {
+        self.source = Some(MediaSource::Message {
+            chat: chat.into(),
+            message_id,
+        });
+        self
+    }
+
+    /// Like [`DownloadIter::source_message`], but for media that belongs to the story with the
+    /// identifier `story_id`, posted by `peer`.
+    pub fn source_story<C: Into<PackedChat>>(mut self, peer: C, story_id: i32) -> Self {
+        self.source = Some(MediaSource::Story {
+            peer: peer.into(),
+            story_id,
+        });
+        self
+    }
+
     /// Fetch and return the next chunk.
-    pub async fn next(&mut self) -> Result<Option<Vec<u8>>, InvocationError> {
+    ///
+    /// With the `bytes` feature, the chunk points inside the response received from Telegram,
+    /// rather than being a copy of it.
+    pub async fn next(&mut self) -> Result<Option<tl::ByteString>, DownloadError> {
         if self.done {
             return Ok(None);
         }
 
         if let Some(data) = &self.photo_size_data {
             self.done = true;
-            return Ok(Some(data.clone()));
+            return Ok(Some(tl::byte_string(data.clone())));
         }
 
         use tl::enums::upload::File;
 
         // TODO handle maybe FILEREF_UPGRADE_NEEDED
         let mut dc: Option<u32> = None;
+        let mut refreshed = false;
         loop {
             let result = match dc.take() {
                 None => self.client.invoke(&self.request).await,
@@ -157,7 +312,17 @@ impl DownloadIter {
                     dc = err.value;
                     continue;
                 }
-                Err(e) => Err(e),
+                Err(e) => {
+                    MediaSource::refresh_after(
+                        self.source,
+                        e,
+                        &mut refreshed,
+                        &self.client,
+                        &mut self.request.location,
+                    )
+                    .await?;
+                    continue;
+                }
             };
         }
     }
@@ -206,13 +371,25 @@ impl Client {
         &self,
         downloadable: &Downloadable,
         path: P,
+    ) -> Result<(), io::Error> {
+        self.download_media_from(downloadable, path, None).await
+    }
+
+    /// Downloads a media file into the specified path, refreshing its file reference from the
+    /// source message if it expires mid-download.
+    #[cfg(feature = "fs")]
+    pub(crate) async fn download_media_from<P: AsRef<Path>>(
+        &self,
+        downloadable: &Downloadable,
+        path: P,
+        source: Option<MediaSource>,
     ) -> Result<(), io::Error> {
         // Concurrent downloader
         if let Downloadable::Media(media) = downloadable {
             if let Media::Document(document) = media {
                 if document.size() as usize > BIG_FILE_SIZE {
                     return self
-                        .download_media_concurrent(media, path, WORKER_COUNT)
+                        .download_media_concurrent(media, path, WORKER_COUNT, source)
                         .await;
                 }
             }
@@ -226,10 +403,7 @@ impl Client {
                     photo_size.data()
                 }
                 _ => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::Other,
-                        "media not downloadable",
-                    ));
+                    return Err(io::Error::other("media not downloadable"));
                 }
             };
 
@@ -242,17 +416,14 @@ impl Client {
         }
 
         let mut download = self.iter_download(downloadable);
+        download.source = source;
         Client::load(path, &mut download).await
     }
 
     #[cfg(feature = "fs")]
     async fn load<P: AsRef<Path>>(path: P, download: &mut DownloadIter) -> Result<(), io::Error> {
         let mut file = fs::File::create(path).await?;
-        while let Some(chunk) = download
-            .next()
-            .await
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
-        {
+        while let Some(chunk) = download.next().await.map_err(io::Error::other)? {
             file.write_all(&chunk).await?;
         }
 
@@ -266,6 +437,7 @@ impl Client {
         media: &Media,
         path: P,
         workers: usize,
+        source: Option<MediaSource>,
     ) -> Result<(), io::Error> {
         let document = match media {
             Media::Document(document) => document,
@@ -288,8 +460,10 @@ impl Client {
             let part_index = part_index.clone();
             let client = self.clone();
             let task = tokio::task::spawn(async move {
+                let mut location = location;
                 let mut retry_offset = None;
                 let mut dc = None;
+                let mut refreshed = false;
                 loop {
                     // Calculate file offset
                     let offset: i64 = {
@@ -319,6 +493,7 @@ impl Client {
                     };
                     match res {
                         Ok(tl::enums::upload::File::File(file)) => {
+                            refreshed = false;
                             tx.send((offset as u64, file.bytes)).unwrap();
                         }
                         Ok(tl::enums::upload::File::CdnRedirect(_)) => {
@@ -332,12 +507,21 @@ impl Client {
                                 retry_offset = Some(offset);
                                 continue;
                             }
-                            return Err(InvocationError::Rpc(err));
+                            MediaSource::refresh_after(
+                                source,
+                                InvocationError::Rpc(err),
+                                &mut refreshed,
+                                &client,
+                                &mut location,
+                            )
+                            .await?;
+                            retry_offset = Some(offset);
+                            continue;
                         }
-                        Err(e) => return Err(e),
+                        Err(e) => return Err(e.into()),
                     }
                 }
-                Ok::<(), InvocationError>(())
+                Ok::<(), DownloadError>(())
             });
             tasks.push(task);
         }
@@ -355,8 +539,7 @@ impl Client {
 
         // Check if all tasks finished succesfully
         for task in tasks {
-            task.await?
-                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            task.await?.map_err(io::Error::other)?;
         }
         Ok(())
     }
@@ -429,16 +612,13 @@ impl Client {
                                 file_id,
                                 file_part: part,
                                 file_total_parts: total_parts,
-                                bytes,
+                                bytes: tl::byte_string(bytes),
                             })
                             .await
-                            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+                            .map_err(io::Error::other)?;
 
                         if !ok {
-                            return Err(io::Error::new(
-                                io::ErrorKind::Other,
-                                "server failed to store uploaded data",
-                            ));
+                            return Err(io::Error::other("server failed to store uploaded data"));
                         }
                     }
                     Ok(())
@@ -466,16 +646,13 @@ impl Client {
                     .invoke(&tl::functions::upload::SaveFilePart {
                         file_id,
                         file_part: part,
-                        bytes,
+                        bytes: tl::byte_string(bytes),
                     })
                     .await
-                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+                    .map_err(io::Error::other)?;
 
                 if !ok {
-                    return Err(io::Error::new(
-                        io::ErrorKind::Other,
-                        "server failed to store uploaded data",
-                    ));
+                    return Err(io::Error::other("server failed to store uploaded data"));
                 }
             }
             Ok(Uploaded::from_raw(
@@ -490,6 +667,32 @@ impl Client {
         }
     }
 
+    /// Uploads an in-memory JPEG image to be used as the custom thumbnail of a document.
+    ///
+    /// Telegram expects thumbnails to be JPEG images of at most 320 pixels in width and height,
+    /// and less than 200 KB in size. Refer to [`InputMessage::thumbnail`] to attach the result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client, thumb_bytes: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::InputMessage;
+    ///
+    /// let video = client.upload_file("video.mp4").await?;
+    /// let thumb = client.upload_thumbnail(thumb_bytes).await?;
+    ///
+    /// client.send_message(&chat, InputMessage::text("").document(video).thumbnail(thumb)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`InputMessage::thumbnail`]: crate::InputMessage::thumbnail
+    pub async fn upload_thumbnail(&self, bytes: &[u8]) -> Result<Uploaded, io::Error> {
+        let mut stream = std::io::Cursor::new(bytes);
+        self.upload_stream(&mut stream, bytes.len(), "thumb.jpg".to_string())
+            .await
+    }
+
     /// Uploads a local file to Telegram servers.
     ///
     /// The file is not sent to any chat, but can be used as media when sending messages for a
@@ -542,7 +745,7 @@ struct PartStream<'a, S: AsyncRead + Unpin> {
 
 impl<'a, S: AsyncRead + Unpin> PartStream<'a, S> {
     fn new(stream: &'a mut S, size: usize) -> Self {
-        let total_parts = ((size + MAX_CHUNK_SIZE as usize - 1) / MAX_CHUNK_SIZE as usize) as i32;
+        let total_parts = size.div_ceil(MAX_CHUNK_SIZE as usize) as i32;
         Self {
             inner: AsyncMutex::new(PartStreamInner {
                 stream,
@@ -590,3 +793,120 @@ impl<'a, S: AsyncRead + Unpin> PartStream<'a, S> {
         res
     }
 }
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::client::testing::fixtures::*;
+    use crate::client::testing::MockServer;
+
+    fn photo_media(file_reference: &[u8]) -> tl::enums::MessageMedia {
+        tl::types::MessageMediaPhoto {
+            spoiler: false,
+            photo: Some(
+                tl::types::Photo {
+                    has_stickers: false,
+                    id: 1,
+                    access_hash: 2,
+                    file_reference: tl::byte_string(file_reference.to_vec()),
+                    date: 0,
+                    sizes: Vec::new(),
+                    video_sizes: None,
+                    dc_id: 2,
+                }
+                .into(),
+            ),
+            ttl_seconds: None,
+        }
+        .into()
+    }
+
+    fn photo_download(client: &Client) -> DownloadIter {
+        let media = Media::from_raw(photo_media(b"old")).unwrap();
+        client.iter_download(&Downloadable::Media(media))
+    }
+
+    #[test]
+    fn expired_file_references_without_source_fail() {
+        let server = MockServer::new();
+        let client = client(&server);
+        server.respond_error::<tl::functions::upload::GetFile>(rpc_error(
+            400,
+            "FILE_REFERENCE_EXPIRED",
+        ));
+
+        let error = block_on(photo_download(&client).next()).unwrap_err();
+        assert!(matches!(error, DownloadError::NoSource));
+    }
+
+    #[test]
+    fn expired_file_references_are_refreshed_from_stories() {
+        let server = MockServer::new();
+        let client = client(&server);
+        server.respond_error::<tl::functions::upload::GetFile>(rpc_error(
+            400,
+            "FILE_REFERENCE_EXPIRED",
+        ));
+        server.respond::<tl::functions::stories::GetStoriesById>(
+            tl::types::stories::Stories {
+                count: 1,
+                stories: vec![tl::types::StoryItem {
+                    pinned: false,
+                    public: false,
+                    close_friends: false,
+                    min: false,
+                    noforwards: false,
+                    edited: false,
+                    contacts: false,
+                    selected_contacts: false,
+                    out: false,
+                    id: 3,
+                    date: 0,
+                    from_id: None,
+                    fwd_from: None,
+                    expire_date: 0,
+                    caption: None,
+                    entities: None,
+                    media: photo_media(b"new"),
+                    media_areas: None,
+                    privacy: None,
+                    views: None,
+                    sent_reaction: None,
+                }
+                .into()],
+                pinned_to_top: None,
+                chats: Vec::new(),
+                users: Vec::new(),
+            }
+            .into(),
+        );
+        server.respond::<tl::functions::upload::GetFile>(
+            tl::types::upload::File {
+                r#type: tl::enums::storage::FileType::FileUnknown,
+                mtime: 0,
+                bytes: tl::byte_string(b"data".to_vec()),
+            }
+            .into(),
+        );
+
+        let peer = PackedChat {
+            ty: grammers_session::PackedType::User,
+            id: 4,
+            access_hash: Some(5),
+        };
+        let chunk = block_on(photo_download(&client).source_story(peer, 3).next()).unwrap();
+        assert_eq!(chunk.as_deref(), Some(&b"data"[..]));
+
+        let requests = server.take_requests::<tl::functions::upload::GetFile>();
+        let references = requests
+            .into_iter()
+            .map(|request| match request.location {
+                tl::enums::InputFileLocation::InputPhotoFileLocation(location) => {
+                    location.file_reference.to_vec()
+                }
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(references, [b"old".to_vec(), b"new".to_vec()]);
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Methods related to the folders in which dialogs are organized.
+
+use crate::types::{Folder, FolderBuilder};
+use crate::Client;
+use grammers_mtsender::InvocationError;
+use grammers_tl_types as tl;
+
+// Identifiers below this are reserved by Telegram, with 0 being the folder with every dialog and 1
+// the archive.
+const MIN_FOLDER_ID: i32 = 2;
+
+/// Method implementations related to dialog folders.
+impl Client {
+    /// Get the folders of the logged-in user, in the order they are displayed.
+    ///
+    /// The folder which contains every dialog is not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for folder in client.get_folders().await? {
+    ///     println!("Folder {}: {}", folder.id(), folder.title());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_folders(&self) -> Result<Vec<Folder>, InvocationError> {
+        let tl::enums::messages::DialogFilters::Filters(filters) = self
+            .invoke(&tl::functions::messages::GetDialogFilters {})
+            .await?;
+
+        Ok(filters
+            .filters
+            .into_iter()
+            .filter_map(Folder::from_raw)
+            .collect())
+    }
+
+    /// Create a new folder, which will be displayed after the existing ones.
+    ///
+    /// Returns the identifier of the new folder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::FolderBuilder;
+    ///
+    /// let folder = FolderBuilder::new("Work")
+    ///     .groups(true)
+    ///     .exclude_muted(true)
+    ///     .pin(&chat);
+    ///
+    /// let folder_id = client.create_folder(folder).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_folder(&self, folder: FolderBuilder) -> Result<i32, InvocationError> {
+        let id = self
+            .get_folders()
+            .await?
+            .iter()
+            .map(|folder| folder.id() + 1)
+            .max()
+            .unwrap_or(MIN_FOLDER_ID)
+            .max(MIN_FOLDER_ID);
+
+        self.edit_folder(id, folder).await?;
+        Ok(id)
+    }
+
+    /// Replace the configuration of an existing folder.
+    ///
+    /// A [`FolderBuilder`] can be created from an existing [`Folder`] to only change part of it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::FolderBuilder;
+    ///
+    /// if let Some(folder) = client.get_folders().await?.pop() {
+    ///     let id = folder.id();
+    ///     client.edit_folder(id, FolderBuilder::from(folder).title("Renamed")).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn edit_folder(
+        &self,
+        folder_id: i32,
+        folder: FolderBuilder,
+    ) -> Result<(), InvocationError> {
+        if folder_id < MIN_FOLDER_ID {
+            return Err(InvocationError::InvalidInput(
+                "folder identifier is reserved",
+            ));
+        }
+
+        self.invoke(&tl::functions::messages::UpdateDialogFilter {
+            id: folder_id,
+            filter: Some(folder.to_raw(folder_id)),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Delete a folder. The dialogs inside of it are not affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.delete_folder(2).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_folder(&self, folder_id: i32) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::UpdateDialogFilter {
+            id: folder_id,
+            filter: None,
+        })
+        .await
+        .map(drop)
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Methods related to group calls, also known as voice chats.
+
+use crate::types::{ChatMap, GroupCallConnection, GroupCallParticipant, IterBuffer};
+use crate::{utils, Client};
+pub use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+const MAX_PARTICIPANTS_LIMIT: usize = 100;
+
+pub type GroupCallParticipantIter =
+    IterBuffer<tl::functions::phone::GetGroupParticipants, GroupCallParticipant>;
+
+impl GroupCallParticipantIter {
+    fn new(client: &Client, call: tl::enums::InputGroupCall) -> Self {
+        Self::from_request(
+            client,
+            MAX_PARTICIPANTS_LIMIT,
+            tl::functions::phone::GetGroupParticipants {
+                call,
+                ids: Vec::new(),
+                sources: Vec::new(),
+                offset: String::new(),
+                limit: 0,
+            },
+        )
+    }
+
+    /// Determines how many participants there are in total.
+    ///
+    /// This only performs a network call if `next` has not been called before.
+    pub async fn total(&mut self) -> Result<usize, InvocationError> {
+        if let Some(total) = self.total {
+            return Ok(total);
+        }
+
+        self.request.limit = 1;
+        let tl::enums::phone::GroupParticipants::Participants(participants) =
+            self.client.invoke(&self.request).await?;
+        let total = participants.count as usize;
+        self.total = Some(total);
+        Ok(total)
+    }
+
+    /// Return the next `GroupCallParticipant` from the internal buffer, filling the buffer
+    /// previously if it's empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no participants left.
+    pub async fn next(&mut self) -> Result<Option<GroupCallParticipant>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        self.request.limit = self.determine_limit(MAX_PARTICIPANTS_LIMIT);
+        let tl::enums::phone::GroupParticipants::Participants(participants) =
+            self.client.invoke(&self.request).await?;
+
+        {
+            let mut state = self.client.0.state.write().unwrap();
+            // Telegram can return peers without hash (e.g. Users with 'min: true')
+            let _ = state
+                .chat_hashes
+                .extend(&participants.users, &participants.chats);
+        }
+
+        self.total = Some(participants.count as usize);
+        if participants.next_offset.is_empty() || participants.participants.is_empty() {
+            self.last_chunk = true;
+        } else {
+            self.request.offset = participants.next_offset;
+        }
+
+        let chats = ChatMap::new(participants.users, participants.chats);
+        self.buffer
+            .extend(participants.participants.into_iter().map(|participant| {
+                let tl::enums::GroupCallParticipant::Participant(raw) = &participant;
+                let chat = chats.get(&raw.peer).cloned();
+                GroupCallParticipant::from_raw(participant, chat)
+            }));
+
+        Ok(self.pop_item())
+    }
+}
+
+/// Method implementations related to group calls.
+impl Client {
+    /// Get the group call currently active in the given group or channel.
+    async fn get_input_group_call(
+        &self,
+        chat: PackedChat,
+    ) -> Result<tl::enums::InputGroupCall, InvocationError> {
+        let call = if let Some(chat_id) = chat.try_to_chat_id() {
+            let tl::enums::messages::ChatFull::Full(full) = self
+                .invoke(&tl::functions::messages::GetFullChat { chat_id })
+                .await?;
+            match full.full_chat {
+                tl::enums::ChatFull::Full(full) => full.call,
+                tl::enums::ChatFull::ChannelFull(full) => full.call,
+            }
+        } else if let Some(channel) = chat.try_to_input_channel() {
+            let tl::enums::messages::ChatFull::Full(full) = self
+                .invoke(&tl::functions::channels::GetFullChannel { channel })
+                .await?;
+            match full.full_chat {
+                tl::enums::ChatFull::Full(full) => full.call,
+                tl::enums::ChatFull::ChannelFull(full) => full.call,
+            }
+        } else {
+            None
+        };
+
+        call.ok_or(InvocationError::InvalidInput("chat has no group call"))
+    }
+
+    /// Join the group call currently active in the given group or channel.
+    ///
+    /// `params_json` is the WebRTC offer produced by the media stack, containing the `ufrag`,
+    /// `pwd`, `fingerprints` and `ssrc` to use. The answer from Telegram is available through
+    /// [`GroupCallConnection::transport`].
+    ///
+    /// [`InvocationError::InvalidInput`] is returned if there is no active group call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// # let offer = String::new();
+    /// let connection = client.join_group_call(&chat, &offer).await?;
+    /// println!("feed this to the WebRTC stack: {}", connection.transport());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn join_group_call<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        params_json: &str,
+    ) -> Result<GroupCallConnection, InvocationError> {
+        let call = self.get_input_group_call(chat.into()).await?;
+        let updates = self
+            .invoke(&tl::functions::phone::JoinGroupCall {
+                muted: false,
+                video_stopped: true,
+                call: call.clone(),
+                join_as: tl::enums::InputPeer::PeerSelf,
+                invite_hash: None,
+                params: tl::types::DataJson {
+                    data: params_json.to_string(),
+                }
+                .into(),
+            })
+            .await?;
+
+        let updates = match updates {
+            tl::enums::Updates::UpdateShort(update) => vec![update.update],
+            tl::enums::Updates::Combined(updates) => updates.updates,
+            tl::enums::Updates::Updates(updates) => updates.updates,
+            _ => Vec::new(),
+        };
+
+        updates
+            .into_iter()
+            .find_map(|update| match update {
+                tl::enums::Update::GroupCallConnection(tl::types::UpdateGroupCallConnection {
+                    presentation: false,
+                    params: tl::enums::DataJson::Json(params),
+                }) => Some(GroupCallConnection {
+                    call: call.clone(),
+                    params: params.data,
+                }),
+                _ => None,
+            })
+            .ok_or_else(utils::unexpected_response::<tl::types::UpdateGroupCallConnection>)
+    }
+
+    /// Leave a group call previously joined with [`Client::join_group_call`].
+    ///
+    /// `source` is the audio source identifier (SSRC) used when joining.
+    pub async fn leave_group_call(
+        &self,
+        connection: &GroupCallConnection,
+        source: i32,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::phone::LeaveGroupCall {
+            call: connection.call.clone(),
+            source,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Iterate over the participants of the group call currently active in the given group
+    /// or channel.
+    ///
+    /// [`InvocationError::InvalidInput`] is returned if there is no active group call.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut participants = client.iter_group_call_participants(&chat).await?;
+    ///
+    /// while let Some(participant) = participants.next().await? {
+    ///     println!("{:?} is muted: {}", participant.chat(), participant.is_muted());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn iter_group_call_participants<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<GroupCallParticipantIter, InvocationError> {
+        let call = self.get_input_group_call(chat.into()).await?;
+        Ok(GroupCallParticipantIter::new(self, call))
+    }
+}
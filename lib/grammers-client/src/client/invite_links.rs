@@ -0,0 +1,219 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Methods related to invite links.
+use crate::types::{InviteLink, InviteLinkBuilder, IterBuffer};
+use crate::{utils, Client};
+pub use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+const MAX_LIMIT: usize = 100;
+
+pub type InviteLinkIter = IterBuffer<tl::functions::messages::GetExportedChatInvites, InviteLink>;
+
+impl InviteLinkIter {
+    fn new(client: &Client, chat: PackedChat) -> Self {
+        Self::from_request(
+            client,
+            MAX_LIMIT,
+            tl::functions::messages::GetExportedChatInvites {
+                revoked: false,
+                peer: chat.to_input_peer(),
+                admin_id: tl::enums::InputUser::UserSelf,
+                offset_date: None,
+                offset_link: None,
+                limit: 0,
+            },
+        )
+    }
+
+    /// Return the links that were revoked instead of the ones that still work.
+    pub fn revoked(mut self) -> Self {
+        self.request.revoked = true;
+        self
+    }
+
+    /// Return the links created by the given administrator instead of the logged-in user.
+    pub fn admin<U: Into<PackedChat>>(mut self, admin: U) -> Self {
+        self.request.admin_id = admin.into().to_input_user_lossy();
+        self
+    }
+
+    /// Determines how many invite links there are in total.
+    ///
+    /// This only performs a network call if `next` has not been called before.
+    pub async fn total(&mut self) -> Result<usize, InvocationError> {
+        if let Some(total) = self.total {
+            return Ok(total);
+        }
+
+        self.request.limit = 1;
+        let tl::enums::messages::ExportedChatInvites::Invites(invites) =
+            self.client.invoke(&self.request).await?;
+        self.total = Some(invites.count as usize);
+        Ok(invites.count as usize)
+    }
+
+    /// Return the next `InviteLink` from the internal buffer, filling the buffer previously if
+    /// it's empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no invite links left.
+    pub async fn next(&mut self) -> Result<Option<InviteLink>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        self.request.limit = self.determine_limit(MAX_LIMIT);
+        let tl::enums::messages::ExportedChatInvites::Invites(invites) =
+            self.client.invoke(&self.request).await?;
+
+        self.total = Some(invites.count as usize);
+        self.last_chunk = invites.invites.len() < self.request.limit as usize;
+
+        let links = invites
+            .invites
+            .into_iter()
+            .filter_map(InviteLink::from_raw)
+            .collect::<Vec<_>>();
+
+        if let Some(last) = links.last() {
+            self.request.offset_date = Some(last.raw.date);
+            self.request.offset_link = Some(last.raw.link.clone());
+        } else {
+            self.last_chunk = true;
+        }
+        self.buffer.extend(links);
+
+        Ok(self.pop_item())
+    }
+}
+
+/// Method implementations related to invite links of groups and channels.
+impl Client {
+    /// Generate a new primary invite link for the chat, revoking the previous one.
+    ///
+    /// The logged-in user must be an administrator with the right to invite users.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let link = client.export_invite_link(&chat).await?;
+    /// println!("Join us at {}", link);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn export_invite_link<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<String, InvocationError> {
+        let invite = self
+            .invoke(&tl::functions::messages::ExportChatInvite {
+                legacy_revoke_permanent: true,
+                request_needed: false,
+                peer: chat.into().to_input_peer(),
+                expire_date: None,
+                usage_limit: None,
+                title: None,
+                subscription_pricing: None,
+            })
+            .await?;
+
+        match invite {
+            tl::enums::ExportedChatInvite::ChatInviteExported(invite) => Ok(invite.link),
+            tl::enums::ExportedChatInvite::ChatInvitePublicJoinRequests => {
+                Err(utils::unexpected_response::<
+                    tl::types::ChatInvitePublicJoinRequests,
+                >())
+            }
+        }
+    }
+
+    /// Create an additional invite link for the chat.
+    ///
+    /// Returns a new [`InviteLinkBuilder`] to configure the link before it's created with
+    /// [`InviteLinkBuilder::send`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use chrono::{Duration, Utc};
+    ///
+    /// let link = client
+    ///     .create_invite_link(&chat)
+    ///     .expire(Utc::now() + Duration::days(1))
+    ///     .member_limit(10)
+    ///     .send()
+    ///     .await?;
+    ///
+    /// println!("Link for the first ten members: {}", link.link());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_invite_link<C: Into<PackedChat>>(&self, chat: C) -> InviteLinkBuilder {
+        InviteLinkBuilder::new(self, chat.into().to_input_peer())
+    }
+
+    /// Revoke an invite link of the chat, so that it no longer works.
+    ///
+    /// Returns the revoked link. If it was the primary link of the chat, it will be replaced by a
+    /// new one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.revoke_invite_link(&chat, "https://t.me/+AbCdEf0123456789").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn revoke_invite_link<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        link: &str,
+    ) -> Result<InviteLink, InvocationError> {
+        let invite = match self
+            .invoke(&tl::functions::messages::EditExportedChatInvite {
+                revoked: true,
+                peer: chat.into().to_input_peer(),
+                link: link.to_string(),
+                expire_date: None,
+                usage_limit: None,
+                request_needed: None,
+                title: None,
+            })
+            .await?
+        {
+            tl::enums::messages::ExportedChatInvite::Invite(invite) => invite.invite,
+            tl::enums::messages::ExportedChatInvite::Replaced(invite) => invite.invite,
+        };
+
+        InviteLink::from_raw(invite)
+            .ok_or_else(utils::unexpected_response::<tl::types::ChatInvitePublicJoinRequests>)
+    }
+
+    /// Iterate over the invite links created by the logged-in user in the chat.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut links = client.iter_invite_links(&chat);
+    ///
+    /// while let Some(link) = links.next().await? {
+    ///     println!("{} was used {} times", link.link(), link.usage());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_invite_links<C: Into<PackedChat>>(&self, chat: C) -> InviteLinkIter {
+        InviteLinkIter::new(self, chat.into())
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Methods related to resolving links to chats, messages and invites.
+
+use crate::types::{Chat, InvitePreview, ResolvedLink};
+use crate::Client;
+use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+const HOSTS: [&str; 4] = ["t.me", "telegram.me", "telegram.dog", "telesco.pe"];
+
+#[derive(Debug, PartialEq)]
+enum LinkTarget {
+    Username { username: String, post: Option<i32> },
+    Private { channel_id: i64, post: i32 },
+    Invite(String),
+}
+
+fn parse_link(link: &str) -> Option<LinkTarget> {
+    let link = if link.contains("://") {
+        link.to_string()
+    } else {
+        format!("https://{link}")
+    };
+    let url = url::Url::parse(&link).ok()?;
+    let query = |key: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.into_owned())
+    };
+
+    match url.scheme() {
+        "tg" => match url.host_str()? {
+            "resolve" => Some(LinkTarget::Username {
+                username: query("domain")?,
+                post: query("post").and_then(|post| post.parse().ok()),
+            }),
+            "join" => Some(LinkTarget::Invite(query("invite")?)),
+            "privatepost" => Some(LinkTarget::Private {
+                channel_id: query("channel")?.parse().ok()?,
+                post: query("post")?.parse().ok()?,
+            }),
+            _ => None,
+        },
+        "http" | "https" => {
+            if !HOSTS.contains(&url.host_str()?) {
+                return None;
+            }
+
+            let mut path = url.path_segments()?.filter(|s| !s.is_empty());
+            match (path.next()?, path.next(), path.next()) {
+                ("joinchat", hash, _) => Some(LinkTarget::Invite(hash?.to_string())),
+                ("c", channel_id, post) => Some(LinkTarget::Private {
+                    channel_id: channel_id?.parse().ok()?,
+                    post: post?.parse().ok()?,
+                }),
+                (first, _, _) if first.starts_with('+') => {
+                    Some(LinkTarget::Invite(first[1..].to_string()))
+                }
+                ("s", Some(username), _) => Some(LinkTarget::Username {
+                    username: username.to_string(),
+                    post: None,
+                }),
+                (username, post, _) => Some(LinkTarget::Username {
+                    username: username.to_string(),
+                    post: match post {
+                        Some(post) => Some(post.parse().ok()?),
+                        None => None,
+                    },
+                }),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Method implementations related to Telegram links.
+impl Client {
+    /// Resolve a Telegram link into what it points to.
+    ///
+    /// The following kinds of links are understood, with or without the `https://` prefix:
+    ///
+    /// * `t.me/username`, and `tg://resolve?domain=username`, pointing to a chat.
+    /// * `t.me/username/123`, and `tg://resolve?domain=username&post=123`, pointing to a message.
+    /// * `t.me/c/1234/123`, and `tg://privatepost?channel=1234&post=123`, pointing to a message
+    ///   in a private channel. These only resolve if the logged-in user has seen the channel.
+    /// * `t.me/+hash`, `t.me/joinchat/hash`, and `tg://join?invite=hash`, being invite links.
+    ///
+    /// Returns `None` if the chat or message the link points to does not exist. Unrecognised
+    /// links fail with [`InvocationError::InvalidInput`].
+    ///
+    /// Invite links to chats the logged-in user is already a member of resolve to the chat
+    /// itself. Otherwise, [`InvitePreview::join`] can be used to join the chat.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::ResolvedLink;
+    ///
+    /// match client.resolve_link("https://t.me/+AbCdEf0123456789").await? {
+    ///     Some(ResolvedLink::Invite(invite)) => {
+    ///         println!("Joining {}", invite.title());
+    ///         invite.join().await?;
+    ///     }
+    ///     Some(ResolvedLink::Chat(chat)) => println!("Already in {:?}", chat.name()),
+    ///     _ => {}
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resolve_link(&self, link: &str) -> Result<Option<ResolvedLink>, InvocationError> {
+        let target =
+            parse_link(link).ok_or(InvocationError::InvalidInput("link is not a Telegram link"))?;
+
+        match target {
+            LinkTarget::Username { username, post } => {
+                let chat = match self.resolve_username(&username).await? {
+                    Some(chat) => chat,
+                    None => return Ok(None),
+                };
+                match post {
+                    Some(post) => self.resolve_post(chat.pack(), post).await,
+                    None => Ok(Some(ResolvedLink::Chat(chat))),
+                }
+            }
+            LinkTarget::Private { channel_id, post } => {
+                let chat = self.0.state.read().unwrap().chat_hashes.get(channel_id);
+                match chat {
+                    Some(chat) => self.resolve_post(chat, post).await,
+                    None => Ok(None),
+                }
+            }
+            LinkTarget::Invite(hash) => {
+                match self
+                    .invoke(&tl::functions::messages::CheckChatInvite { hash: hash.clone() })
+                    .await?
+                {
+                    tl::enums::ChatInvite::Already(tl::types::ChatInviteAlready { chat })
+                    | tl::enums::ChatInvite::Peek(tl::types::ChatInvitePeek { chat, .. }) => {
+                        Ok(Some(ResolvedLink::Chat(Chat::from_raw(chat))))
+                    }
+                    tl::enums::ChatInvite::Invite(invite) => Ok(Some(ResolvedLink::Invite(
+                        InvitePreview::from_raw(self, invite, hash),
+                    ))),
+                }
+            }
+        }
+    }
+
+    async fn resolve_post(
+        &self,
+        chat: PackedChat,
+        post: i32,
+    ) -> Result<Option<ResolvedLink>, InvocationError> {
+        Ok(self
+            .get_messages_by_id(chat, &[post])
+            .await?
+            .pop()
+            .flatten()
+            .map(ResolvedLink::Message))
+    }
+
+    pub(crate) async fn join_invite_hash(
+        &self,
+        hash: &str,
+    ) -> Result<Option<Chat>, InvocationError> {
+        let updates = self
+            .invoke(&tl::functions::messages::ImportChatInvite {
+                hash: hash.to_string(),
+            })
+            .await?;
+
+        Ok(match updates {
+            tl::enums::Updates::Combined(updates) => updates.chats.into_iter().next(),
+            tl::enums::Updates::Updates(updates) => updates.chats.into_iter().next(),
+            _ => None,
+        }
+        .map(Chat::from_raw))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn username(username: &str, post: Option<i32>) -> Option<LinkTarget> {
+        Some(LinkTarget::Username {
+            username: username.to_string(),
+            post,
+        })
+    }
+
+    #[test]
+    fn parse_username_links() {
+        assert_eq!(
+            parse_link("https://t.me/grammers"),
+            username("grammers", None)
+        );
+        assert_eq!(parse_link("t.me/grammers/"), username("grammers", None));
+        assert_eq!(
+            parse_link("telegram.me/grammers/12"),
+            username("grammers", Some(12))
+        );
+        assert_eq!(
+            parse_link("https://t.me/s/grammers"),
+            username("grammers", None)
+        );
+        assert_eq!(
+            parse_link("tg://resolve?domain=grammers&post=3"),
+            username("grammers", Some(3))
+        );
+        assert_eq!(parse_link("https://t.me/grammers/abc"), None);
+    }
+
+    #[test]
+    fn parse_private_links() {
+        let expected = Some(LinkTarget::Private {
+            channel_id: 1234,
+            post: 56,
+        });
+        assert_eq!(parse_link("https://t.me/c/1234/56"), expected);
+        assert_eq!(
+            parse_link("tg://privatepost?channel=1234&post=56"),
+            expected
+        );
+        assert_eq!(parse_link("https://t.me/c/1234"), None);
+    }
+
+    #[test]
+    fn parse_invite_links() {
+        let expected = Some(LinkTarget::Invite("AbCd".to_string()));
+        assert_eq!(parse_link("https://t.me/+AbCd"), expected);
+        assert_eq!(parse_link("https://t.me/joinchat/AbCd"), expected);
+        assert_eq!(parse_link("tg://join?invite=AbCd"), expected);
+    }
+
+    #[test]
+    fn parse_unknown_links() {
+        assert_eq!(parse_link("https://example.com/grammers"), None);
+        assert_eq!(parse_link("tg://settings"), None);
+        assert_eq!(parse_link("ftp://t.me/grammers"), None);
+    }
+}
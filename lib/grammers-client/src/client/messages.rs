@@ -9,9 +9,9 @@
 //! Methods related to sending messages.
 use crate::types::message::EMPTY_MESSAGE;
 use crate::types::{InputReactions, IterBuffer, Message};
-use crate::utils::{generate_random_id, generate_random_ids};
+use crate::utils::{self, generate_random_id, generate_random_ids};
 use crate::{types, ChatMap, Client, InputMedia};
-use chrono::{DateTime, FixedOffset};
+use chrono::{DateTime, FixedOffset, Utc};
 pub use grammers_mtsender::{AuthorizationError, InvocationError};
 use grammers_session::PackedChat;
 use grammers_tl_types as tl;
@@ -19,6 +19,22 @@ use log::{log_enabled, warn, Level};
 use std::collections::HashMap;
 use tl::enums::InputPeer;
 
+/// Build the `reply_to` of a request sending messages, which is also how the topic is specified.
+fn input_reply_to(reply_to: Option<i32>, topic: Option<i32>) -> Option<tl::enums::InputReplyTo> {
+    let reply_to_msg_id = reply_to.or(topic)?;
+    Some(
+        tl::types::InputReplyToMessage {
+            reply_to_msg_id,
+            top_msg_id: topic.filter(|&topic| topic != reply_to_msg_id),
+            reply_to_peer_id: None,
+            quote_text: None,
+            quote_entities: None,
+            quote_offset: None,
+        }
+        .into(),
+    )
+}
+
 fn map_random_ids_to_messages(
     client: &Client,
     random_ids: &[i64],
@@ -117,7 +133,9 @@ pub(crate) fn parse_mention_entities(
 
 const MAX_LIMIT: usize = 100;
 
-impl<R: tl::RemoteCall<Return = tl::enums::messages::Messages>> IterBuffer<R, Message> {
+impl<R: tl::RemoteCall<Return = tl::enums::messages::Messages> + tl::Identifiable>
+    IterBuffer<R, Message>
+{
     /// Fetches the total unless cached.
     ///
     /// The `request.limit` should be set to the right value before calling this method.
@@ -337,8 +355,17 @@ impl SearchIter {
     }
 
     /// Changes the media filter. Only messages with this type of media will be fetched.
-    pub fn filter(mut self, filter: tl::enums::MessagesFilter) -> Self {
-        self.request.filter = filter;
+    ///
+    /// ```
+    /// use grammers_client::types::MessageFilter;
+    ///
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut photos = client.search_messages(&chat).filter(MessageFilter::Photos);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn filter<F: Into<tl::enums::MessagesFilter>>(mut self, filter: F) -> Self {
+        self.request.filter = filter.into();
         self
     }
 
@@ -367,8 +394,9 @@ impl SearchIter {
         // Don't bother updating offsets if this is the last time stuff has to be fetched.
         if !self.last_chunk && !self.buffer.is_empty() {
             let last = &self.buffer[self.buffer.len() - 1];
+            // Only move the offset. Overwriting `max_date` with the date of the last message would
+            // both drop messages sent on the same second and discard the user's date range.
             self.request.offset_id = last.raw.id;
-            self.request.max_date = last.raw.date;
         }
 
         Ok(self.pop_item())
@@ -412,8 +440,26 @@ impl GlobalSearchIter {
     }
 
     /// Changes the media filter. Only messages with this type of media will be fetched.
-    pub fn filter(mut self, filter: tl::enums::MessagesFilter) -> Self {
-        self.request.filter = filter;
+    pub fn filter<F: Into<tl::enums::MessagesFilter>>(mut self, filter: F) -> Self {
+        self.request.filter = filter.into();
+        self
+    }
+
+    /// Returns only messages with date bigger than date_time.
+    pub fn min_date(mut self, date_time: &DateTime<FixedOffset>) -> Self {
+        self.request.min_date = date_time.timestamp() as i32;
+        self
+    }
+
+    /// Returns only messages with date smaller than date_time.
+    pub fn max_date(mut self, date_time: &DateTime<FixedOffset>) -> Self {
+        self.request.max_date = date_time.timestamp() as i32;
+        self
+    }
+
+    /// Restricts results to messages posted in broadcast channels.
+    pub fn broadcasts_only(mut self) -> Self {
+        self.request.broadcasts_only = true;
         self
     }
 
@@ -449,6 +495,103 @@ impl GlobalSearchIter {
     }
 }
 
+/// A builder for forwarding messages with custom options.
+///
+/// It will be executed once [`ForwardBuilder::send`] is called.
+pub struct ForwardBuilder {
+    client: Client,
+    destination: PackedChat,
+    request: tl::functions::messages::ForwardMessages,
+}
+
+impl ForwardBuilder {
+    fn new(
+        client: &Client,
+        destination: PackedChat,
+        message_ids: &[i32],
+        source: PackedChat,
+    ) -> Self {
+        Self {
+            client: client.clone(),
+            destination,
+            request: tl::functions::messages::ForwardMessages {
+                silent: false,
+                background: false,
+                with_my_score: false,
+                drop_author: false,
+                drop_media_captions: false,
+                from_peer: source.to_input_peer(),
+                id: message_ids.to_vec(),
+                random_id: generate_random_ids(message_ids.len()),
+                to_peer: destination.to_input_peer(),
+                top_msg_id: None,
+                schedule_date: None,
+                send_as: None,
+                noforwards: false,
+                quick_reply_shortcut: None,
+            },
+        }
+    }
+
+    /// Whether to hide the original author of the messages, so they appear as sent by the
+    /// logged-in user.
+    pub fn drop_author(mut self, drop: bool) -> Self {
+        self.request.drop_author = drop;
+        self
+    }
+
+    /// Whether to remove the captions from forwarded media. Implies [`ForwardBuilder::drop_author`].
+    pub fn drop_media_captions(mut self, drop: bool) -> Self {
+        self.request.drop_media_captions = drop;
+        if drop {
+            self.request.drop_author = true;
+        }
+        self
+    }
+
+    /// Whether the forwarded messages should notify the members of the destination chat.
+    pub fn silent(mut self, silent: bool) -> Self {
+        self.request.silent = silent;
+        self
+    }
+
+    /// Whether the forwarded messages should be sent in the background.
+    pub fn background(mut self, background: bool) -> Self {
+        self.request.background = background;
+        self
+    }
+
+    /// The forum topic of the destination chat where the messages should be forwarded.
+    pub fn topic(mut self, topic: Option<i32>) -> Self {
+        self.request.top_msg_id = topic;
+        self
+    }
+
+    /// Forward the messages.
+    ///
+    /// Returns the new forwarded messages in a list, keeping the same order as the input IDs.
+    /// Those messages that could not be forwarded will be `None`.
+    pub async fn send(self) -> Result<Vec<Option<Message>>, InvocationError> {
+        let mut destination = self.destination;
+        self.client
+            .stop_chat_actions(destination, self.request.top_msg_id);
+        let result = self
+            .client
+            .invoke_migrating(&mut destination, |chat| {
+                tl::functions::messages::ForwardMessages {
+                    to_peer: chat.to_input_peer(),
+                    ..self.request.clone()
+                }
+            })
+            .await?;
+        Ok(map_random_ids_to_messages(
+            &self.client,
+            &self.request.random_id,
+            result,
+        ))
+    }
+}
+
 /// Method implementations related to sending, modifying or getting messages.
 impl Client {
     /// Sends a message to the desired chat.
@@ -482,32 +625,23 @@ impl Client {
         chat: C,
         message: M,
     ) -> Result<Message, InvocationError> {
-        let chat = chat.into();
+        let mut chat = chat.into();
         let message = message.into();
+        self.stop_chat_actions(chat, message.topic);
         let random_id = generate_random_id();
         let entities = parse_mention_entities(self, message.entities.clone());
         let updates = if let Some(media) = message.media.clone() {
-            self.invoke(&tl::functions::messages::SendMedia {
+            self.invoke_migrating(&mut chat, |chat| tl::functions::messages::SendMedia {
                 silent: message.silent,
                 background: message.background,
                 clear_draft: message.clear_draft,
                 peer: chat.to_input_peer(),
-                reply_to: message.reply_to.map(|reply_to_msg_id| {
-                    tl::types::InputReplyToMessage {
-                        reply_to_msg_id,
-                        top_msg_id: None,
-                        reply_to_peer_id: None,
-                        quote_text: None,
-                        quote_entities: None,
-                        quote_offset: None,
-                    }
-                    .into()
-                }),
-                media,
+                reply_to: input_reply_to(message.reply_to, message.topic),
+                media: media.clone(),
                 message: message.text.clone(),
                 random_id,
                 reply_markup: message.reply_markup.clone(),
-                entities,
+                entities: entities.clone(),
                 schedule_date: message.schedule_date,
                 send_as: None,
                 noforwards: false,
@@ -518,27 +652,17 @@ impl Client {
             })
             .await
         } else {
-            self.invoke(&tl::functions::messages::SendMessage {
+            self.invoke_migrating(&mut chat, |chat| tl::functions::messages::SendMessage {
                 no_webpage: !message.link_preview,
                 silent: message.silent,
                 background: message.background,
                 clear_draft: message.clear_draft,
                 peer: chat.to_input_peer(),
-                reply_to: message.reply_to.map(|reply_to_msg_id| {
-                    tl::types::InputReplyToMessage {
-                        reply_to_msg_id,
-                        top_msg_id: None,
-                        reply_to_peer_id: None,
-                        quote_text: None,
-                        quote_entities: None,
-                        quote_offset: None,
-                    }
-                    .into()
-                }),
+                reply_to: input_reply_to(message.reply_to, message.topic),
                 message: message.text.clone(),
                 random_id,
                 reply_markup: message.reply_markup.clone(),
-                entities,
+                entities: entities.clone(),
                 schedule_date: message.schedule_date,
                 send_as: None,
                 noforwards: false,
@@ -586,6 +710,11 @@ impl Client {
     /// [`Client::upload_file`] first.
     ///
     /// Refer to [`InputMedia`] to learn more formatting options, such as using markdown.
+    /// [`InputMessage`]s with media can be used as well.
+    ///
+    /// All the media will be sent grouped together, and the resulting messages are returned in
+    /// the same order as the input. Every item must contain media, or
+    /// [`InvocationError::InvalidInput`] is returned without sending anything.
     ///
     /// See also: [`Message::respond_album`], [`Message::reply_album`].
     ///
@@ -596,17 +725,36 @@ impl Client {
     /// use grammers_client::InputMedia;
     ///
     /// client.send_album(&chat, vec![InputMedia::caption("A album").photo_url("https://example.com/cat.jpg")]).await?;
+    ///
+    /// // Messages can be used too.
+    /// use grammers_client::InputMessage;
+    ///
+    /// client.send_album(&chat, vec![
+    ///     InputMessage::text("Cats").photo_url("https://example.com/cat.jpg"),
+    ///     InputMessage::text("Dogs").photo_url("https://example.com/dog.jpg"),
+    /// ]).await?;
     /// # Ok(())
     /// # }
     /// ```
     ///
     /// [`InputMedia`]: crate::InputMedia
-    pub async fn send_album<C: Into<PackedChat>>(
+    /// [`InputMessage`]: crate::InputMessage
+    pub async fn send_album<C: Into<PackedChat>, M: Into<InputMedia>>(
         &self,
         chat: C,
-        mut medias: Vec<InputMedia>,
+        medias: Vec<M>,
     ) -> Result<Vec<Option<Message>>, InvocationError> {
-        let chat = chat.into();
+        let mut chat = chat.into();
+        let mut medias = medias
+            .into_iter()
+            .map(Into::into)
+            .collect::<Vec<InputMedia>>();
+        if medias.is_empty() {
+            return Ok(Vec::new());
+        }
+        if medias.iter().any(|media| media.media.is_none()) {
+            return Err(InvocationError::InvalidInput("media is empty"));
+        }
         let random_ids = generate_random_ids(medias.len());
 
         // Upload external files
@@ -621,10 +769,10 @@ impl Client {
                     | tl::enums::InputMedia::DocumentExternal(_)
             ) {
                 let uploaded = self
-                    .invoke(&tl::functions::messages::UploadMedia {
+                    .invoke_migrating(&mut chat, |chat| tl::functions::messages::UploadMedia {
                         business_connection_id: None,
                         peer: chat.to_input_peer(),
-                        media: raw_media,
+                        media: raw_media.clone(),
                     })
                     .await?;
                 media.media = Some(
@@ -636,41 +784,34 @@ impl Client {
             }
         }
 
-        let first_media = medias.first().unwrap();
+        let reply_to = medias.first().unwrap().reply_to;
+        let topic = medias.first().unwrap().topic;
+        self.stop_chat_actions(chat, topic);
+        let multi_media = medias
+            .into_iter()
+            .zip(random_ids.iter())
+            .map(|(input_media, random_id)| {
+                let entities = parse_mention_entities(self, input_media.entities);
+                let raw_media = input_media.media.unwrap();
+
+                tl::enums::InputSingleMedia::Media(tl::types::InputSingleMedia {
+                    media: raw_media,
+                    random_id: *random_id,
+                    message: input_media.caption,
+                    entities,
+                })
+            })
+            .collect::<Vec<_>>();
 
         let updates = self
-            .invoke(&tl::functions::messages::SendMultiMedia {
+            .invoke_migrating(&mut chat, |chat| tl::functions::messages::SendMultiMedia {
                 silent: false,
                 background: false,
                 clear_draft: false,
                 peer: chat.to_input_peer(),
-                reply_to: first_media.reply_to.map(|reply_to_msg_id| {
-                    tl::types::InputReplyToMessage {
-                        reply_to_msg_id,
-                        top_msg_id: None,
-                        reply_to_peer_id: None,
-                        quote_text: None,
-                        quote_entities: None,
-                        quote_offset: None,
-                    }
-                    .into()
-                }),
+                reply_to: input_reply_to(reply_to, topic),
                 schedule_date: None,
-                multi_media: medias
-                    .into_iter()
-                    .zip(random_ids.iter())
-                    .map(|(input_media, random_id)| {
-                        let entities = parse_mention_entities(self, input_media.entities);
-                        let raw_media = input_media.media.unwrap();
-
-                        tl::enums::InputSingleMedia::Media(tl::types::InputSingleMedia {
-                            media: raw_media,
-                            random_id: *random_id,
-                            message: input_media.caption,
-                            entities,
-                        })
-                    })
-                    .collect(),
+                multi_media: multi_media.clone(),
                 send_as: None,
                 noforwards: false,
                 update_stickersets_order: false,
@@ -708,12 +849,13 @@ impl Client {
         message_id: i32,
         new_message: M,
     ) -> Result<(), InvocationError> {
+        let chat = chat.into();
         let new_message = new_message.into();
         let entities = parse_mention_entities(self, new_message.entities);
-        self.invoke(&tl::functions::messages::EditMessage {
+        let request = tl::functions::messages::EditMessage {
             no_webpage: !new_message.link_preview,
             invert_media: new_message.invert_media,
-            peer: chat.into().to_input_peer(),
+            peer: chat.to_input_peer(),
             id: message_id,
             message: Some(new_message.text),
             media: new_message.media,
@@ -721,8 +863,10 @@ impl Client {
             entities,
             schedule_date: new_message.schedule_date,
             quick_reply_shortcut_id: None,
-        })
-        .await?;
+        };
+        self.wait_rate_limit::<tl::functions::messages::EditMessage>(Some(chat.id))
+            .await;
+        self.invoke(&request).await?;
 
         Ok(())
     }
@@ -790,7 +934,7 @@ impl Client {
     /// message IDs, and the indices from the list of IDs map to the indices in the result so
     /// you can find which messages were forwarded and which message they became.
     ///
-    /// See also: [`Message::forward_to`].
+    /// See also: [`Message::forward_to`] and [`Client::forward`] to customize the options.
     ///
     /// # Examples
     ///
@@ -810,25 +954,34 @@ impl Client {
         message_ids: &[i32],
         source: S,
     ) -> Result<Vec<Option<Message>>, InvocationError> {
-        // TODO let user customize more options
-        let request = tl::functions::messages::ForwardMessages {
-            silent: false,
-            background: false,
-            with_my_score: false,
-            drop_author: false,
-            drop_media_captions: false,
-            from_peer: source.into().to_input_peer(),
-            id: message_ids.to_vec(),
-            random_id: generate_random_ids(message_ids.len()),
-            to_peer: destination.into().to_input_peer(),
-            top_msg_id: None,
-            schedule_date: None,
-            send_as: None,
-            noforwards: false,
-            quick_reply_shortcut: None,
-        };
-        let result = self.invoke(&request).await?;
-        Ok(map_random_ids_to_messages(self, &request.random_id, result))
+        self.forward(destination, message_ids, source).send().await
+    }
+
+    /// Prepares to forward up to 100 messages from `source` into `destination`, allowing to
+    /// customize how they are forwarded.
+    ///
+    /// The arguments are the same as in [`Client::forward_messages`], which this method extends.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(destination: grammers_client::types::Chat, source: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let messages = client
+    ///     .forward(&destination, &[123, 456], &source)
+    ///     .drop_author(true)
+    ///     .silent(true)
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn forward<C: Into<PackedChat>, S: Into<PackedChat>>(
+        &self,
+        destination: C,
+        message_ids: &[i32],
+        source: S,
+    ) -> ForwardBuilder {
+        ForwardBuilder::new(self, destination.into(), message_ids, source.into())
     }
 
     /// Gets the [`Message`] to which the input message is replying to.
@@ -973,7 +1126,11 @@ impl Client {
         GlobalSearchIter::new(self)
     }
 
-    /// Get up to 100 messages using their ID.
+    /// Get messages using their ID.
+    ///
+    /// Telegram only allows fetching up to 100 messages at once, so longer lists of IDs are split
+    /// into several requests. Channels use `channels.getMessages`, while private chats and small
+    /// groups use `messages.getMessages`.
     ///
     /// Returns the new retrieved messages in a list. Those messages that could not be retrieved
     /// or do not belong to the input chat will be `None`. The length of the resulting list is the
@@ -998,35 +1155,43 @@ impl Client {
         message_ids: &[i32],
     ) -> Result<Vec<Option<Message>>, InvocationError> {
         let chat = chat.into();
-        let id = message_ids
-            .iter()
-            .map(|&id| tl::enums::InputMessage::Id(tl::types::InputMessageId { id }))
-            .collect();
+        let mut map = HashMap::with_capacity(message_ids.len());
 
-        let result = if let Some(channel) = chat.try_to_input_channel() {
-            self.invoke(&tl::functions::channels::GetMessages { channel, id })
-                .await
-        } else {
-            self.invoke(&tl::functions::messages::GetMessages { id })
-                .await
-        }?;
+        for chunk in message_ids.chunks(MAX_LIMIT) {
+            let id = chunk
+                .iter()
+                .map(|&id| tl::enums::InputMessage::Id(tl::types::InputMessageId { id }))
+                .collect();
 
-        let (messages, users, chats) = match result {
-            tl::enums::messages::Messages::Messages(m) => (m.messages, m.users, m.chats),
-            tl::enums::messages::Messages::Slice(m) => (m.messages, m.users, m.chats),
-            tl::enums::messages::Messages::ChannelMessages(m) => (m.messages, m.users, m.chats),
-            tl::enums::messages::Messages::NotModified(_) => {
-                panic!("API returned Messages::NotModified even though GetMessages was used")
-            }
-        };
+            let result = if let Some(channel) = chat.try_to_input_channel() {
+                self.invoke(&tl::functions::channels::GetMessages { channel, id })
+                    .await
+            } else {
+                self.invoke(&tl::functions::messages::GetMessages { id })
+                    .await
+            }?;
 
-        let chats = ChatMap::new(users, chats);
-        let mut map = messages
-            .into_iter()
-            .flat_map(|m| Message::from_raw(self, m, &chats))
-            .filter(|m| m.chat().pack() == chat)
-            .map(|m| (m.raw.id, m))
-            .collect::<HashMap<_, _>>();
+            let (messages, users, chats) = match result {
+                tl::enums::messages::Messages::Messages(m) => (m.messages, m.users, m.chats),
+                tl::enums::messages::Messages::Slice(m) => (m.messages, m.users, m.chats),
+                tl::enums::messages::Messages::ChannelMessages(m) => (m.messages, m.users, m.chats),
+                tl::enums::messages::Messages::NotModified(_) => {
+                    panic!("API returned Messages::NotModified even though GetMessages was used")
+                }
+            };
+
+            // `messages.getMessages` looks up IDs among every private chat and small group, so
+            // messages from other chats must be discarded. Only the ID is compared, because the
+            // access hash of the returned chat may differ from the one given as input.
+            let chats = ChatMap::new(users, chats);
+            map.extend(
+                messages
+                    .into_iter()
+                    .flat_map(|m| Message::from_raw(self, m, &chats))
+                    .filter(|m| m.chat().id() == chat.id)
+                    .map(|m| (m.raw.id, m)),
+            );
+        }
 
         Ok(message_ids.iter().map(|id| map.remove(id)).collect())
     }
@@ -1149,9 +1314,181 @@ impl Client {
         &self,
         chat: C,
     ) -> Result<(), InvocationError> {
-        self.invoke(&tl::functions::messages::UnpinAllMessages {
+        self.unpin_all(chat.into(), None).await
+    }
+
+    /// Unpin all currently-pinned messages from a single forum topic of the chat.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let topic_id = 123;
+    /// client.unpin_all_topic_messages(&chat, topic_id).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn unpin_all_topic_messages<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        topic_id: i32,
+    ) -> Result<(), InvocationError> {
+        self.unpin_all(chat.into(), Some(topic_id)).await
+    }
+
+    async fn unpin_all(&self, chat: PackedChat, topic: Option<i32>) -> Result<(), InvocationError> {
+        let request = tl::functions::messages::UnpinAllMessages {
+            peer: chat.to_input_peer(),
+            top_msg_id: topic,
+        };
+
+        // Telegram may unpin the messages in several steps, and signals there is more left to do
+        // with a non-zero offset.
+        loop {
+            let tl::enums::messages::AffectedHistory::History(affected) =
+                self.invoke(&request).await?;
+            if affected.offset <= 0 {
+                break Ok(());
+            }
+        }
+    }
+
+    /// Save a draft in the chat, replacing any previous draft.
+    ///
+    /// The draft is synchronized across all the devices of the logged-in user. The text, its
+    /// formatting, media, link preview and reply settings of the message are kept, while the rest
+    /// of its options are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.save_draft(&chat, "I will finish this later").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn save_draft<C: Into<PackedChat>, M: Into<types::InputMessage>>(
+        &self,
+        chat: C,
+        message: M,
+    ) -> Result<(), InvocationError> {
+        let message = message.into();
+        self.invoke(&tl::functions::messages::SaveDraft {
+            no_webpage: !message.link_preview,
+            invert_media: message.invert_media,
+            reply_to: input_reply_to(message.reply_to, message.topic),
             peer: chat.into().to_input_peer(),
-            top_msg_id: None,
+            entities: parse_mention_entities(self, message.entities.clone()),
+            message: message.text,
+            media: message.media,
+            effect: None,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Clear the draft in the chat, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.clear_draft(&chat).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn clear_draft<C: Into<PackedChat>>(&self, chat: C) -> Result<(), InvocationError> {
+        self.save_draft(chat, types::InputMessage::text("")).await
+    }
+
+    /// Get the messages scheduled to be sent in a chat, which have not been sent yet.
+    ///
+    /// Messages can be scheduled with [`InputMessage::schedule_date`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for message in client.get_scheduled_messages(&chat).await? {
+    ///     println!("Will send {} at {}", message.text(), message.date());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`InputMessage::schedule_date`]: crate::InputMessage::schedule_date
+    pub async fn get_scheduled_messages<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<Vec<Message>, InvocationError> {
+        use tl::enums::messages::Messages;
+
+        let (messages, users, chats) = match self
+            .invoke(&tl::functions::messages::GetScheduledHistory {
+                peer: chat.into().to_input_peer(),
+                hash: 0,
+            })
+            .await?
+        {
+            Messages::Messages(m) => (m.messages, m.users, m.chats),
+            Messages::Slice(m) => (m.messages, m.users, m.chats),
+            Messages::ChannelMessages(m) => (m.messages, m.users, m.chats),
+            Messages::NotModified(_) => {
+                panic!("API returned Messages::NotModified even though hash = 0")
+            }
+        };
+
+        let chats = ChatMap::new(users, chats);
+        Ok(messages
+            .into_iter()
+            .flat_map(|m| Message::from_raw(self, m, &chats))
+            .collect())
+    }
+
+    /// Send the given scheduled messages right away, instead of waiting until their scheduled
+    /// date.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let scheduled_ids = [123, 456];
+    /// client.send_scheduled_now(&chat, &scheduled_ids).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_scheduled_now<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_ids: &[i32],
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::SendScheduledMessages {
+            peer: chat.into().to_input_peer(),
+            id: message_ids.to_vec(),
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Delete the given scheduled messages, so that they are never sent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let scheduled_ids = [123, 456];
+    /// client.delete_scheduled(&chat, &scheduled_ids).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_scheduled<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_ids: &[i32],
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::DeleteScheduledMessages {
+            peer: chat.into().to_input_peer(),
+            id: message_ids.to_vec(),
         })
         .await?;
         Ok(())
@@ -1217,4 +1554,311 @@ impl Client {
 
         Ok(())
     }
+
+    /// Fetch the up-to-date reactions of the given messages.
+    ///
+    /// The length of the resulting list is the same as the length of the input message IDs, and
+    /// the indices from the list of IDs map to the indices in the result. Messages without
+    /// reactions (or that could not be found) will be `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let message_ids = [123, 456];
+    ///
+    /// for reactions in client.get_message_reactions(&chat, &message_ids).await?.into_iter().flatten() {
+    ///     println!("{} different reactions", reactions.results.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_message_reactions<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_ids: &[i32],
+    ) -> Result<Vec<Option<tl::types::MessageReactions>>, InvocationError> {
+        let updates = match self
+            .invoke(&tl::functions::messages::GetMessagesReactions {
+                peer: chat.into().to_input_peer(),
+                id: message_ids.to_vec(),
+            })
+            .await?
+        {
+            tl::enums::Updates::Updates(updates) => updates.updates,
+            tl::enums::Updates::Combined(updates) => updates.updates,
+            _ => Vec::new(),
+        };
+
+        let mut map = updates
+            .into_iter()
+            .filter_map(|update| match update {
+                tl::enums::Update::MessageReactions(update) => {
+                    let tl::enums::MessageReactions::Reactions(reactions) = update.reactions;
+                    Some((update.msg_id, reactions))
+                }
+                _ => None,
+            })
+            .collect::<HashMap<_, _>>();
+
+        Ok(message_ids.iter().map(|id| map.remove(id)).collect())
+    }
+
+    /// Vote in the poll contained in the given message.
+    ///
+    /// The options are given by their index, in the same order as [`Poll::iter_answers`].
+    /// Passing an empty list retracts the previous vote.
+    ///
+    /// [`Poll::iter_answers`]: crate::types::media::Poll::iter_answers
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// // Vote for the first option
+    /// client.vote(&message, &[0]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn vote(
+        &self,
+        message: &Message,
+        option_indices: &[usize],
+    ) -> Result<(), InvocationError> {
+        let poll = message
+            .poll()
+            .ok_or(InvocationError::InvalidInput("message has no poll"))?;
+        let answers = poll.iter_answers().collect::<Vec<_>>();
+        let options = option_indices
+            .iter()
+            .map(|&i| answers.get(i).map(|answer| answer.option.clone()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(InvocationError::InvalidInput(
+                "poll option index out of range",
+            ))?;
+
+        self.invoke(&tl::functions::messages::SendVote {
+            peer: message.chat().pack().to_input_peer(),
+            msg_id: message.id(),
+            options,
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Close the poll contained in the given message, so that no more votes can be cast.
+    ///
+    /// Only the sender of the poll can close it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.close_poll(&message).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn close_poll(&self, message: &Message) -> Result<(), InvocationError> {
+        let mut poll = message
+            .poll()
+            .ok_or(InvocationError::InvalidInput("message has no poll"))?;
+        poll.raw.closed = true;
+
+        self.invoke(&tl::functions::messages::EditMessage {
+            no_webpage: false,
+            invert_media: false,
+            peer: message.chat().pack().to_input_peer(),
+            id: message.id(),
+            message: None,
+            media: Some(poll.to_raw_input_media().into()),
+            reply_markup: None,
+            entities: None,
+            schedule_date: None,
+            quick_reply_shortcut_id: None,
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Translate the given text into the given language, keeping its formatting entities.
+    ///
+    /// The language is a two-letter ISO 639-1 code. Translation requires Telegram Premium.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let translated = client.translate_text("Hola, mundo", "en").await?;
+    /// println!("{}", translated.text());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn translate_text<T: Into<types::EntityText>>(
+        &self,
+        text: T,
+        to_lang: &str,
+    ) -> Result<types::EntityText, InvocationError> {
+        let (text, entities) = text.into().into_parts();
+        let tl::enums::messages::TranslatedText::TranslateResult(result) = self
+            .invoke(&tl::functions::messages::TranslateText {
+                peer: None,
+                id: None,
+                text: Some(vec![tl::types::TextWithEntities { text, entities }.into()]),
+                to_lang: to_lang.to_string(),
+            })
+            .await?;
+
+        Ok(result
+            .result
+            .into_iter()
+            .next()
+            .map(translated_text)
+            .unwrap_or_default())
+    }
+
+    /// Translate the text of the given messages into the given language, keeping their
+    /// formatting entities.
+    ///
+    /// The translations are returned in the same order as the input message IDs.
+    ///
+    /// See also: [`Message::translate`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for translated in client.translate_messages(&chat, &[123, 456], "en").await? {
+    ///     println!("{}", translated.text());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn translate_messages<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_ids: &[i32],
+        to_lang: &str,
+    ) -> Result<Vec<types::EntityText>, InvocationError> {
+        let mut chat = chat.into();
+        let tl::enums::messages::TranslatedText::TranslateResult(result) = self
+            .invoke_migrating(&mut chat, |chat| tl::functions::messages::TranslateText {
+                peer: Some(chat.to_input_peer()),
+                id: Some(message_ids.to_vec()),
+                text: None,
+                to_lang: to_lang.to_string(),
+            })
+            .await?;
+
+        Ok(result.result.into_iter().map(translated_text).collect())
+    }
+
+    /// Get which members of a small group have read the given outgoing message, along with
+    /// the date when they did.
+    ///
+    /// Telegram only keeps track of this for a limited time in groups with few members.
+    ///
+    /// See also: [`Message::get_read_participants`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for (user_id, date) in client.get_message_read_participants(&chat, 123).await? {
+    ///     println!("{user_id} read the message on {date}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_message_read_participants<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_id: i32,
+    ) -> Result<Vec<(i64, DateTime<Utc>)>, InvocationError> {
+        let mut chat = chat.into();
+        let participants = self
+            .invoke_migrating(&mut chat, |chat| {
+                tl::functions::messages::GetMessageReadParticipants {
+                    peer: chat.to_input_peer(),
+                    msg_id: message_id,
+                }
+            })
+            .await?;
+
+        Ok(participants
+            .into_iter()
+            .map(|participant| {
+                let tl::enums::ReadParticipantDate::Date(participant) = participant;
+                (participant.user_id, utils::date(participant.date))
+            })
+            .collect())
+    }
+
+    /// Get the view, forward and reply counters of the given messages, in the same order
+    /// as the input message IDs.
+    ///
+    /// If `increment` is `true`, the messages are also marked as viewed by the logged-in user,
+    /// which increments their view counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for views in client.get_message_views(&chat, &[123, 456], false).await? {
+    ///     println!("viewed {:?} times", views.views);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_message_views<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_ids: &[i32],
+        increment: bool,
+    ) -> Result<Vec<tl::types::MessageViews>, InvocationError> {
+        let mut chat = chat.into();
+        let tl::enums::messages::MessageViews::Views(views) = self
+            .invoke_migrating(&mut chat, |chat| {
+                tl::functions::messages::GetMessagesViews {
+                    peer: chat.to_input_peer(),
+                    id: message_ids.to_vec(),
+                    increment,
+                }
+            })
+            .await?;
+
+        {
+            let mut state = self.0.state.write().unwrap();
+            // Telegram can return peers without hash (e.g. Users with 'min: true')
+            let _ = state.chat_hashes.extend(&views.users, &views.chats);
+        }
+
+        Ok(views
+            .views
+            .into_iter()
+            .map(|views| {
+                let tl::enums::MessageViews::Views(views) = views;
+                views
+            })
+            .collect())
+    }
+
+    /// Mark the given message as viewed by the logged-in user, incrementing its view counter.
+    ///
+    /// The same account can only increment the counter of a message once every several hours.
+    pub async fn mark_message_viewed<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        message_id: i32,
+    ) -> Result<(), InvocationError> {
+        self.get_message_views(chat, &[message_id], true)
+            .await
+            .map(drop)
+    }
+}
+
+fn translated_text(text: tl::enums::TextWithEntities) -> types::EntityText {
+    let tl::enums::TextWithEntities::Entities(text) = text;
+    types::EntityText::new(text.text, text.entities)
 }
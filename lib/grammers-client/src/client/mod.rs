@@ -5,17 +5,37 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
+pub mod admin_log;
 pub mod auth;
+pub mod boosts;
 pub mod bots;
+pub mod calls;
 pub mod chats;
 #[allow(clippy::module_inception)]
 pub mod client;
+pub mod contacts;
 pub mod dialogs;
 pub mod files;
+pub mod folders;
+pub mod group_calls;
+pub mod invite_links;
+#[cfg(feature = "parse_invite_link")]
+pub mod links;
 pub mod messages;
 pub mod net;
+pub mod payments;
+pub mod rate_limit;
+pub mod secret_chats;
+pub mod stats;
+pub mod stickers;
+pub mod stories;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod topics;
 pub mod updates;
 
 pub use auth::SignInError;
 pub(crate) use client::ClientInner;
-pub use client::{Client, Config, InitParams};
+pub use client::{Client, Config, DcOption, InitParams, UpdateOverflowPolicy};
+pub use files::DownloadError;
+pub use rate_limit::{Rate, RateLimit};
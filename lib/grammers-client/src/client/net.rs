@@ -5,25 +5,34 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-use super::client::{ClientState, Connection};
+use super::client::{ClientState, Connection, Driver, DEFAULT_TRANSPORTS};
+use super::rate_limit::RateLimiter;
 use super::{Client, ClientInner, Config};
 use crate::utils;
-use grammers_mtproto::mtp;
+use futures_util::future::select_all;
 use grammers_mtproto::transport;
+use grammers_mtproto::{authentication, mtp};
 use grammers_mtsender::ServerAddr;
 use grammers_mtsender::{
-    self as sender, utils::sleep, AuthorizationError, InvocationError, RpcError, Sender,
+    self as sender, utils::sleep, AuthorizationError, InvocationError, ReadError, RequestHandle,
+    RpcError, RpcErrorKind, Sender, TransportKind,
 };
 use grammers_session::{ChatHashCache, MessageBox};
-use grammers_tl_types::{self as tl, Deserializable};
-use log::{debug, info};
+use grammers_tl_types::{self as tl, Deserializable, Identifiable, Serializable};
+use log::{debug, info, warn};
 use sender::Enqueuer;
 use std::collections::{HashMap, VecDeque};
-use std::net::Ipv4Addr;
-use std::sync::atomic::{AtomicU32, Ordering};
-use std::sync::{Arc, RwLock};
-use tokio::sync::oneshot::error::TryRecvError;
-use tokio::sync::{Mutex as AsyncMutex, RwLock as AsyncRwLock};
+use std::future::Future;
+use std::iter;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicI32, AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::oneshot::{self, error::TryRecvError};
+use tokio::sync::{Mutex as AsyncMutex, Notify, RwLock as AsyncRwLock};
+use web_time::{Instant, SystemTime};
 
 /// Socket addresses to Telegram datacenters, where the index into this array
 /// represents the data center ID.
@@ -40,6 +49,67 @@ const DC_ADDRESSES: [(Ipv4Addr, u16); 6] = [
     (Ipv4Addr::new(91, 108, 56, 190), 443),
 ];
 
+/// IPv6 socket addresses to Telegram datacenters, where the index into this array represents
+/// the data center ID.
+///
+/// The addresses were obtained from the `static` addresses through a call to
+/// `functions::help::GetConfig`.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+const DC_ADDRESSES_V6: [(Ipv6Addr, u16); 6] = [
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0), 0),
+    (
+        Ipv6Addr::new(0x2001, 0x0b28, 0xf23d, 0xf001, 0, 0, 0, 0xa),
+        443,
+    ),
+    (
+        Ipv6Addr::new(0x2001, 0x067c, 0x04e8, 0xf002, 0, 0, 0, 0xa),
+        443,
+    ),
+    (
+        Ipv6Addr::new(0x2001, 0x0b28, 0xf23d, 0xf003, 0, 0, 0, 0xa),
+        443,
+    ),
+    (
+        Ipv6Addr::new(0x2001, 0x067c, 0x04e8, 0xf004, 0, 0, 0, 0xa),
+        443,
+    ),
+    (
+        Ipv6Addr::new(0x2001, 0x0b28, 0xf23f, 0xf005, 0, 0, 0, 0xa),
+        443,
+    ),
+];
+
+/// Socket addresses to Telegram's test datacenters, where the index into this array represents
+/// the data center ID.
+///
+/// See [Using test servers](https://core.telegram.org/api/auth#test-accounts).
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+const TEST_DC_ADDRESSES: [(Ipv4Addr, u16); 4] = [
+    (Ipv4Addr::new(0, 0, 0, 0), 0),
+    (Ipv4Addr::new(149, 154, 175, 10), 443),
+    (Ipv4Addr::new(149, 154, 167, 40), 443),
+    (Ipv4Addr::new(149, 154, 175, 117), 443),
+];
+
+/// IPv6 socket addresses to Telegram's test datacenters, where the index into this array
+/// represents the data center ID.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+const TEST_DC_ADDRESSES_V6: [(Ipv6Addr, u16); 4] = [
+    (Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0), 0),
+    (
+        Ipv6Addr::new(0x2001, 0x0b28, 0xf23d, 0xf001, 0, 0, 0, 0xe),
+        443,
+    ),
+    (
+        Ipv6Addr::new(0x2001, 0x067c, 0x04e8, 0xf002, 0, 0, 0, 0xe),
+        443,
+    ),
+    (
+        Ipv6Addr::new(0x2001, 0x0b28, 0xf23d, 0xf003, 0, 0, 0, 0xe),
+        443,
+    ),
+];
+
 /// WebSocket addresses to Telegram datacenters, where the index into this array
 /// represents the data center ID.
 ///
@@ -55,31 +125,103 @@ const WS_ADDRESSES: [&str; 6] = [
     "wss://flora.web.telegram.org/apiws",
 ];
 
-#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
-pub(crate) type Transport = transport::Full;
-
+/// Suffix appended to the WebSocket addresses in order to reach the test datacenters.
 #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
-pub(crate) type Transport = transport::Obfuscated<transport::Intermediate>;
+const WS_TEST_SUFFIX: &str = "_test";
+
+/// Offset added to the datacenter ID when binding temporary keys in the test datacenters.
+const TEST_DC_ID_OFFSET: i32 = 10000;
+
+pub(crate) type Transport = sender::DynTransport;
 
 const DEFAULT_DC: i32 = 2;
 
-pub(crate) async fn connect_sender(
-    dc_id: i32,
-    config: &Config,
-) -> Result<(Sender<Transport, mtp::Encrypted>, Enqueuer), AuthorizationError> {
-    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
-    let transport = transport::Full::new();
+/// Transport error code sent by the server when it does not know the auth key used.
+const AUTH_KEY_NOT_FOUND: i32 = -404;
 
-    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
-    let transport = transport::Obfuscated::new(transport::Intermediate::new());
+/// How long before a temporary auth key expires to replace it with a new one, unless the
+/// configured expiry is so short that a quarter of it should be used instead.
+const TEMP_AUTH_KEY_RENEWAL_MARGIN: Duration = Duration::from_secs(60);
 
-    let addr: ServerAddr = if let Some(ref sa) = config.params.server_addr {
-        sa.clone()
-    } else {
-        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
-        let addr = {
-            let tcp_addr = DC_ADDRESSES[dc_id as usize].into();
+/// Wraps a request in `invokeWithoutUpdates` by reference, so that it does not need to be cloned.
+struct WithoutUpdates<'a, R: tl::RemoteCall>(&'a R);
 
+impl<R: tl::RemoteCall> Serializable for WithoutUpdates<'_, R> {
+    fn serialize(&self, buf: &mut impl Extend<u8>) {
+        tl::functions::InvokeWithoutUpdates::<R>::CONSTRUCTOR_ID.serialize(buf);
+        self.0.serialize(buf);
+    }
+}
+
+impl<R: tl::RemoteCall> tl::RemoteCall for WithoutUpdates<'_, R> {
+    type Return = R::Return;
+}
+
+/// Service message to query the state of sent messages, answered with `msgs_state_info`.
+struct MsgsStateReq(tl::enums::MsgsStateReq);
+
+impl Serializable for MsgsStateReq {
+    fn serialize(&self, buf: &mut impl Extend<u8>) {
+        self.0.serialize(buf);
+    }
+}
+
+impl tl::RemoteCall for MsgsStateReq {
+    type Return = tl::enums::MsgsStateInfo;
+}
+
+/// Determine the socket addresses to try, in order, when connecting to the given datacenter.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn dc_socket_addrs(dc_id: i32, config: &Config) -> Vec<SocketAddr> {
+    let use_ipv6 = config.params.use_ipv6;
+
+    let mut overrides = config
+        .params
+        .dc_overrides
+        .iter()
+        .filter(|dc| dc.id == dc_id)
+        .map(|dc| dc.address)
+        .collect::<Vec<_>>();
+
+    if !overrides.is_empty() {
+        // The sort is stable, so options of the same family keep their configured order.
+        overrides.sort_by_key(|address| address.is_ipv6() != use_ipv6);
+        return overrides;
+    }
+
+    let stored = config.session.dc_tcp_addr(dc_id);
+    let families: &[bool] = if use_ipv6 { &[true, false] } else { &[false] };
+
+    let mut addrs = Vec::new();
+    for &ipv6 in families {
+        if let Some(address) = stored.filter(|address| address.is_ipv6() == ipv6) {
+            addrs.push(address);
+        }
+        // There may be no default for the datacenter, such as the test ones above 3.
+        let index = dc_id as usize;
+        let default = match (config.params.test_mode, ipv6) {
+            (false, false) => DC_ADDRESSES.get(index).map(|&addr| addr.into()),
+            (false, true) => DC_ADDRESSES_V6.get(index).map(|&addr| addr.into()),
+            (true, false) => TEST_DC_ADDRESSES.get(index).map(|&addr| addr.into()),
+            (true, true) => TEST_DC_ADDRESSES_V6.get(index).map(|&addr| addr.into()),
+        };
+        if let Some(default) = default.filter(|default| !addrs.contains(default)) {
+            addrs.push(default);
+        }
+    }
+    addrs
+}
+
+/// Determine the server addresses to try, in order, when connecting to the given datacenter.
+fn server_addrs(dc_id: i32, config: &Config) -> Vec<ServerAddr> {
+    if let Some(ref sa) = config.params.server_addr {
+        return vec![sa.clone()];
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    let addrs = dc_socket_addrs(dc_id, config)
+        .into_iter()
+        .map(|tcp_addr| {
             #[cfg(not(feature = "proxy"))]
             let addr = ServerAddr::Tcp { address: tcp_addr };
 
@@ -94,82 +236,309 @@ pub(crate) async fn connect_sender(
             };
 
             addr
-        };
+        })
+        .collect();
 
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    let addrs = vec![ServerAddr::Ws {
+        address: if config.params.test_mode {
+            format!("{}{}", WS_ADDRESSES[dc_id as usize], WS_TEST_SUFFIX)
+        } else {
+            WS_ADDRESSES[dc_id as usize].to_string()
+        },
+    }];
+
+    addrs
+}
+
+/// Remember the address used to reach the datacenter, along with its permanent `auth_key`.
+fn store_dc_addr(dc_id: i32, addr: &ServerAddr, auth_key: [u8; 256], config: &Config) {
+    match addr {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        ServerAddr::Tcp { address, .. } => {
+            config.session.insert_dc_tcp(dc_id, address, auth_key);
+        }
+        #[cfg(all(
+            not(all(target_arch = "wasm32", target_os = "unknown")),
+            feature = "proxy"
+        ))]
+        ServerAddr::Proxied { address, .. } => {
+            config.session.insert_dc_tcp(dc_id, address, auth_key);
+        }
         #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
-        let addr = ServerAddr::Ws {
-            address: WS_ADDRESSES[dc_id as usize].to_string(),
-        };
+        ServerAddr::Ws { address } => {
+            config.session.insert_dc_ws(dc_id, address, auth_key);
+        }
+    }
+}
 
-        addr
+async fn connect_sender_to(
+    dc_id: i32,
+    kind: TransportKind,
+    addr: ServerAddr,
+    config: &Config,
+) -> Result<(Sender<Transport, mtp::Encrypted>, Enqueuer), AuthorizationError> {
+    // Temporary keys must be bound to an offset identifier in the test datacenters.
+    let temp_dc_id = if config.params.test_mode {
+        dc_id + TEST_DC_ID_OFFSET
+    } else {
+        dc_id
     };
 
     let (mut sender, request_tx) = if let Some(auth_key) = config.session.dc_auth_key(dc_id) {
         info!(
-            "creating a new sender with existing auth key to dc {} {:?}",
-            dc_id, addr
+            "creating a new sender with existing auth key to dc {} {:?} using {:?}",
+            dc_id, addr, kind
         );
 
-        sender::connect_with_auth(transport, addr, auth_key, config.params.reconnection_policy)
+        let result = if let Some(expiry) = config.params.temp_auth_key_expiry {
+            sender::connect_with_temp_auth(
+                kind.new_transport(&addr),
+                addr.clone(),
+                auth_key,
+                temp_dc_id,
+                expiry,
+                config.params.reconnection_policy,
+            )
             .await?
+        } else {
+            let (mut sender, tx) = sender::connect_with_auth(
+                kind.new_transport(&addr),
+                addr.clone(),
+                auth_key,
+                config.params.reconnection_policy,
+            )
+            .await?;
+            // Nothing was exchanged yet to learn the offset, so start with the last known one.
+            sender.set_time_offset(config.session.time_offset());
+            (sender, tx)
+        };
+
+        store_dc_addr(dc_id, &addr, auth_key, config);
+        result
     } else {
         info!(
-            "creating a new sender and auth key in dc {} {:?}",
-            dc_id, addr
+            "creating a new sender and auth key in dc {} {:?} using {:?}",
+            dc_id, addr, kind
         );
 
-        let (sender, tx) =
-            sender::connect(transport, addr.clone(), config.params.reconnection_policy).await?;
+        let (sender, tx) = sender::connect(
+            kind.new_transport(&addr),
+            addr.clone(),
+            config.params.reconnection_policy,
+        )
+        .await?;
 
-        match addr {
-            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
-            ServerAddr::Tcp { ref address, .. } => {
-                config
-                    .session
-                    .insert_dc_tcp(dc_id, address, sender.auth_key());
-            }
-            #[cfg(all(
-                not(all(target_arch = "wasm32", target_os = "unknown")),
-                feature = "proxy"
-            ))]
-            ServerAddr::Proxied { ref address, .. } => {
-                config
-                    .session
-                    .insert_dc_tcp(dc_id, address, sender.auth_key());
-            }
-            #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
-            ServerAddr::Ws { ref address } => {
-                config
-                    .session
-                    .insert_dc_ws(dc_id, address, sender.auth_key());
-            }
+        store_dc_addr(dc_id, &addr, sender.auth_key(), config);
+
+        if let Some(expiry) = config.params.temp_auth_key_expiry {
+            // The new permanent key should not encrypt any message, so connect again with a
+            // temporary key bound to it.
+            let auth_key = sender.auth_key();
+            drop(sender);
+            sender::connect_with_temp_auth(
+                kind.new_transport(&addr),
+                addr,
+                auth_key,
+                temp_dc_id,
+                expiry,
+                config.params.reconnection_policy,
+            )
+            .await?
+        } else {
+            (sender, tx)
         }
-        (sender, tx)
     };
 
-    // TODO handle -404 (we had a previously-valid authkey, but server no longer knows about it)
-    // TODO all up-to-date server addresses should be stored in the session for future initial connections
-    let _remote_config = sender
-        .invoke(&tl::functions::InvokeWithLayer {
-            layer: tl::LAYER,
-            query: tl::functions::InitConnection {
-                api_id: config.api_id,
-                device_model: config.params.device_model.clone(),
-                system_version: config.params.system_version.clone(),
-                app_version: config.params.app_version.clone(),
-                system_lang_code: config.params.system_lang_code.clone(),
-                lang_pack: "".into(),
-                lang_code: config.params.lang_code.clone(),
-                proxy: None,
-                params: None,
-                query: tl::functions::help::GetConfig {},
-            },
-        })
-        .await?;
+    sender.set_ping_delays(
+        config.params.ping_interval,
+        config.params.ping_disconnect_delay,
+    );
+    sender.set_compression_threshold(config.params.compression_threshold);
+    sender.set_observer(config.params.sender_observer);
+    #[cfg(feature = "tracing")]
+    sender.span().record("dc_id", dc_id);
+
+    // The first request doubles as the check that the transport works.
+    let get_config = tl::functions::help::GetConfig {};
+    let _remote_config = if config.params.invoke_without_updates {
+        sender
+            .invoke(&init_connection(config, WithoutUpdates(&get_config)))
+            .await?
+    } else {
+        sender.invoke(&init_connection(config, get_config)).await?
+    };
+    config.session.set_time_offset(sender.time_offset());
 
     Ok((sender, request_tx))
 }
 
+pub(crate) async fn connect_sender(
+    dc_id: i32,
+    config: &Config,
+) -> Result<(Sender<Transport, mtp::Encrypted>, Enqueuer), AuthorizationError> {
+    let result = match connect_sender_once(dc_id, config).await {
+        // Temporary keys are generated on every connection, so another one will be made.
+        Err(e) if config.params.temp_auth_key_expiry.is_some() && is_auth_key_not_found(&e) => {
+            warn!(
+                "server does not know the temporary auth key for dc {}; binding a new one",
+                dc_id
+            );
+            connect_sender_once(dc_id, config).await
+        }
+        result => result,
+    };
+
+    match result {
+        // Permanent keys are useless if the server does not know about them anymore.
+        Err(e) if is_auth_key_not_found(&e) || is_temp_auth_key_bind_failure(&e) => {
+            warn!(
+                "server does not know the auth key for dc {}; generating a new one",
+                dc_id
+            );
+            config.session.remove_dc(dc_id);
+            connect_sender_once(dc_id, config).await
+        }
+        result => result,
+    }
+}
+
+fn is_auth_key_not_found(error: &AuthorizationError) -> bool {
+    matches!(
+        error,
+        AuthorizationError::Invoke(InvocationError::Read(ReadError::Transport(
+            transport::Error::TransportCode(AUTH_KEY_NOT_FOUND),
+        )))
+    )
+}
+
+/// Whether binding a temporary key failed, which happens when the permanent one was revoked.
+fn is_temp_auth_key_bind_failure(error: &AuthorizationError) -> bool {
+    match error {
+        AuthorizationError::Gen(authentication::Error::TempKeyNotBound) => true,
+        AuthorizationError::Invoke(InvocationError::Rpc(err)) => {
+            err.is("ENCRYPTED_MESSAGE_INVALID")
+                && err.caused_by == Some(tl::functions::auth::BindTempAuthKey::CONSTRUCTOR_ID)
+        }
+        _ => false,
+    }
+}
+
+async fn connect_sender_once(
+    dc_id: i32,
+    config: &Config,
+) -> Result<(Sender<Transport, mtp::Encrypted>, Enqueuer), AuthorizationError> {
+    let transports = if config.params.transports.is_empty() {
+        DEFAULT_TRANSPORTS
+    } else {
+        &config.params.transports
+    };
+    // The transport known to work is tried first.
+    let stored = config
+        .session
+        .dc_transport(dc_id)
+        .and_then(TransportKind::from_id)
+        .filter(|kind| transports.contains(kind));
+    let kinds = stored
+        .into_iter()
+        .chain(
+            transports
+                .iter()
+                .copied()
+                .filter(|kind| Some(*kind) != stored),
+        )
+        .collect::<Vec<_>>();
+
+    let mut addrs = server_addrs(dc_id, config).into_iter().peekable();
+    let (result, kind) = loop {
+        let Some(addr) = addrs.next() else {
+            return Err(AuthorizationError::Invoke(InvocationError::InvalidInput(
+                "no known address for the datacenter",
+            )));
+        };
+        match sender::connect_with_fallback(&kinds, &addr, |kind, addr| {
+            connect_sender_to(dc_id, kind, addr, config)
+        })
+        .await
+        {
+            Ok(result) => break result,
+            Err(AuthorizationError::Invoke(InvocationError::Read(ReadError::Io(e))))
+                if addrs.peek().is_some() =>
+            {
+                warn!(
+                    "failed to connect to dc {} {:?}, trying the next address: {}",
+                    dc_id, addr, e
+                );
+            }
+            Err(e) => return Err(e),
+        }
+    };
+
+    config.session.set_dc_transport(dc_id, kind.id());
+    Ok(result)
+}
+
+/// Connect the additional senders to the home datacenter, as configured by
+/// `InitParams::connection_count`.
+async fn connect_pool(
+    dc_id: i32,
+    config: &Config,
+) -> Result<Vec<Arc<Connection>>, AuthorizationError> {
+    let mut pool = Vec::new();
+    for _ in 1..config.params.connection_count {
+        let (sender, request_tx) = connect_sender(dc_id, config).await?;
+        pool.push(Arc::new(Connection::new(dc_id, sender, request_tx)));
+    }
+    Ok(pool)
+}
+
+fn init_connection<R: tl::RemoteCall>(
+    config: &Config,
+    query: R,
+) -> tl::functions::InvokeWithLayer<tl::functions::InitConnection<R>> {
+    tl::functions::InvokeWithLayer {
+        layer: tl::LAYER,
+        query: tl::functions::InitConnection {
+            api_id: config.api_id,
+            device_model: config.params.device_model.clone(),
+            system_version: config.params.system_version.clone(),
+            app_version: config.params.app_version.clone(),
+            system_lang_code: config.params.system_lang_code.clone(),
+            lang_pack: "".into(),
+            lang_code: config.params.lang_code.clone(),
+            proxy: None,
+            params: None,
+            query,
+        },
+    }
+}
+
+/// The datacenter the logged-in account belongs to, or the default one if not logged in.
+pub(crate) fn home_dc_id(config: &Config) -> i32 {
+    config
+        .session
+        .get_user()
+        .map(|u| u.dc)
+        .unwrap_or(DEFAULT_DC)
+}
+
+/// Return the datacenter the account must be used from, if the error asks to migrate the
+/// home connection there.
+///
+/// Other migrations, such as `FILE_MIGRATE` or `STATS_MIGRATE`, only apply to a single
+/// request, and are handled by [`Client::invoke_in_dc`] instead.
+fn home_migration_dc(err: &RpcError) -> Option<i32> {
+    if err.code != 303 {
+        return None;
+    }
+    match err.kind() {
+        RpcErrorKind::PhoneMigrate(dc_id)
+        | RpcErrorKind::UserMigrate(dc_id)
+        | RpcErrorKind::NetworkMigrate(dc_id) => Some(dc_id as i32),
+        _ => None,
+    }
+}
+
 /// Method implementations directly related with network connectivity.
 impl Client {
     /// Creates and returns a new client instance upon successful connection to Telegram.
@@ -200,13 +569,39 @@ impl Client {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn connect(mut config: Config) -> Result<Self, AuthorizationError> {
-        let dc_id = config
-            .session
-            .get_user()
-            .map(|u| u.dc)
-            .unwrap_or(DEFAULT_DC);
+    pub async fn connect(config: Config) -> Result<Self, AuthorizationError> {
+        let dc_id = home_dc_id(&config);
         let (sender, request_tx) = connect_sender(dc_id, &config).await?;
+        let client =
+            Self::with_connection(config, dc_id, Connection::new(dc_id, sender, request_tx));
+
+        let pool = connect_pool(dc_id, &client.0.config).await?;
+        *client.0.pool.write().unwrap() = pool;
+
+        // Don't bother getting pristine update state if we're not logged in.
+        let should_get_state = client.0.state.read().unwrap().message_box.is_empty()
+            && client.0.config.session.signed_in();
+
+        if should_get_state {
+            match client.invoke(&tl::functions::updates::GetState {}).await {
+                Ok(state) => {
+                    {
+                        client.0.state.write().unwrap().message_box.set_state(state);
+                    }
+                    client.sync_update_state();
+                }
+                Err(_err) => {
+                    // The account may no longer actually be logged in, or it can rarely fail.
+                    // `message_box` will try to correct its state as updates arrive.
+                }
+            }
+        }
+
+        Ok(client)
+    }
+
+    /// Create the client around its already-connected main connection.
+    pub(crate) fn with_connection(mut config: Config, dc_id: i32, conn: Connection) -> Self {
         let message_box = if config.params.catch_up {
             if let Some(state) = config.session.get_state() {
                 MessageBox::load(state)
@@ -232,41 +627,31 @@ impl Client {
         }
 
         let self_user = config.session.get_user();
+        let rate_limiter = RateLimiter::new(&config.params.rate_limits);
 
-        // Don't bother getting pristine update state if we're not logged in.
-        let should_get_state = message_box.is_empty() && config.session.signed_in();
-
-        // TODO Sender doesn't have a way to handle backpressure yet
-        let client = Self(Arc::new(ClientInner {
+        Self(Arc::new(ClientInner {
             id: utils::generate_random_id(),
             config,
-            conn: Connection::new(sender, request_tx),
+            conn,
             state: RwLock::new(ClientState {
                 dc_id,
                 message_box,
                 chat_hashes: ChatHashCache::new(self_user.map(|u| (u.id, u.bot))),
                 last_update_limit_warn: None,
+                dropped_updates: 0,
                 updates,
             }),
+            migration: AsyncMutex::new(()),
             downloader_map: AsyncRwLock::new(HashMap::new()),
-        }));
-
-        if should_get_state {
-            match client.invoke(&tl::functions::updates::GetState {}).await {
-                Ok(state) => {
-                    {
-                        client.0.state.write().unwrap().message_box.set_state(state);
-                    }
-                    client.sync_update_state();
-                }
-                Err(_err) => {
-                    // The account may no longer actually be logged in, or it can rarely fail.
-                    // `message_box` will try to correct its state as updates arrive.
-                }
-            }
-        }
-
-        Ok(client)
+            pool: RwLock::new(Vec::new()),
+            pool_counter: AtomicUsize::new(0),
+            secret_chats: Mutex::new(HashMap::new()),
+            calls: Mutex::new(HashMap::new()),
+            dh_params: Mutex::new(None),
+            update_room: Notify::new(),
+            rate_limiter,
+            chat_actions: Mutex::new(HashMap::new()),
+        }))
     }
 
     /// Invoke a raw API call. This directly sends the request to Telegram's servers.
@@ -296,17 +681,271 @@ impl Client {
         &self,
         request: &R,
     ) -> Result<R::Return, InvocationError> {
-        self.0
+        if self.0.config.params.invoke_without_updates {
+            return self.invoke_without_updates(request).await;
+        }
+
+        self.wait_request_rate_limit(request).await;
+        let result = self.invoke_in_pool(request).await;
+
+        match result {
+            Err(InvocationError::Rpc(err)) if home_migration_dc(&err).is_some() => {
+                self.migrate_to(home_migration_dc(&err).unwrap()).await?;
+                self.invoke_in_pool(request).await
+            }
+            result => result,
+        }
+    }
+
+    /// Like [`Client::invoke`], but the request is wrapped in `invokeWithoutUpdates`.
+    ///
+    /// Invoking a request this way will not subscribe the connection to the account's updates.
+    /// This is useful when several clients are logged in to the same account, and only one of
+    /// them is responsible for handling updates. See [`InitParams::invoke_without_updates`] to
+    /// enable this behaviour for all requests.
+    ///
+    /// <div class="stab unstable">
+    ///
+    /// **Warning**: this method is **not** part of the stability guarantees of semantic
+    /// versioning. It **may** break during *minor* version changes (but not on patch version
+    /// changes). Use with care.
+    ///
+    /// </div>
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_tl_types as tl;
+    ///
+    /// dbg!(client.invoke_without_updates(&tl::functions::updates::GetState {}).await?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`InitParams::invoke_without_updates`]: crate::InitParams::invoke_without_updates
+    pub async fn invoke_without_updates<R: tl::RemoteCall>(
+        &self,
+        request: &R,
+    ) -> Result<R::Return, InvocationError> {
+        self.wait_request_rate_limit(request).await;
+        let result = self.invoke_in_pool(&WithoutUpdates(request)).await;
+
+        match result {
+            Err(InvocationError::Rpc(err)) if home_migration_dc(&err).is_some() => {
+                self.migrate_to(home_migration_dc(&err).unwrap()).await?;
+                self.invoke_in_pool(&WithoutUpdates(request)).await
+            }
+            result => result,
+        }
+    }
+
+    /// Like [`Client::invoke`], but the server will only process the request after it's done
+    /// processing the request identified by `after`, by wrapping it in `invokeAfterMsg`.
+    ///
+    /// The request is enqueued right away, and the returned handle can be used to make further
+    /// requests depend on it. This makes it possible to guarantee the order in which the server
+    /// handles several requests (for example, to send messages strictly in sequence) while
+    /// awaiting all of them at once, rather than one after another.
+    ///
+    /// Ordered requests always go through the main connection, and are not retried on flood
+    /// waits or migrated to another datacenter, since that would break the order. If `after`
+    /// has already completed, or the main connection changed since it was sent, there is nothing
+    /// to wait on and the request is sent as-is.
+    ///
+    /// <div class="stab unstable">
+    ///
+    /// **Warning**: this method is **not** part of the stability guarantees of semantic
+    /// versioning. It **may** break during *minor* version changes (but not on patch version
+    /// changes). Use with care.
+    ///
+    /// </div>
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_tl_types as tl;
+    ///
+    /// let (first, first_result) = client.invoke_after(None, &tl::functions::Ping { ping_id: 1 });
+    /// let (_, second_result) = client.invoke_after(Some(first), &tl::functions::Ping { ping_id: 2 });
+    ///
+    /// // The server answers the first one before processing the second one.
+    /// let first_pong = first_result.await?;
+    /// let second_pong = second_result.await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn invoke_after<R: tl::RemoteCall>(
+        &self,
+        after: Option<RequestHandle>,
+        request: &R,
+    ) -> (
+        RequestHandle,
+        impl Future<Output = Result<R::Return, InvocationError>> + '_,
+    ) {
+        let request_tx = self.0.conn.request_tx.read().unwrap();
+        let (handle, rx) = if self.0.config.params.invoke_without_updates {
+            request_tx.enqueue_after(&WithoutUpdates(request), after)
+        } else {
+            request_tx.enqueue_after(request, after)
+        };
+        drop(request_tx);
+
+        let result = async move {
+            let body = self
+                .0
+                .conn
+                .receive(rx, &self.0.config, |updates| {
+                    self.process_socket_updates(updates)
+                })
+                .await?;
+            R::Return::from_owned(body).map_err(|e| e.into())
+        };
+        (handle, result)
+    }
+
+    /// Ask the server about the state of previously-sent messages.
+    ///
+    /// The identifiers are MTProto message identifiers (not the identifiers of chat messages),
+    /// such as the ones logged when a request is sent. The returned states are in the same order
+    /// as the input identifiers.
+    ///
+    /// This is mostly useful to diagnose requests which seemingly never got a response, for
+    /// example, to find out whether the server ever received them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let msg_id = 7_315_054_472_743_880_704;
+    ///
+    /// for state in client.get_msgs_state(&[msg_id]).await? {
+    ///     println!("received by server: {}", state.received());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_msgs_state(
+        &self,
+        msg_ids: &[i64],
+    ) -> Result<Vec<mtp::MsgState>, InvocationError> {
+        // Service messages cannot be wrapped in other requests, so `Client::invoke` is not used.
+        let tl::enums::MsgsStateInfo::Info(info) = self
+            .0
             .conn
             .invoke(
-                request,
-                self.0.config.params.flood_sleep_threshold,
+                &MsgsStateReq(
+                    tl::types::MsgsStateReq {
+                        msg_ids: msg_ids.to_vec(),
+                    }
+                    .into(),
+                ),
+                &self.0.config,
                 |updates| self.process_socket_updates(updates),
             )
-            .await
+            .await?;
+
+        Ok(info.info.into_iter().map(mtp::MsgState::new).collect())
     }
 
-    async fn export_authorization(
+    /// Invoke the request through the next connection to the home datacenter, so that
+    /// requests are spread across all of them.
+    async fn invoke_in_pool<R: tl::RemoteCall>(
+        &self,
+        request: &R,
+    ) -> Result<R::Return, InvocationError> {
+        let conn = {
+            let pool = self.0.pool.read().unwrap();
+            if pool.is_empty() {
+                None
+            } else {
+                let index = self.0.pool_counter.fetch_add(1, Ordering::Relaxed) % (pool.len() + 1);
+                index.checked_sub(1).map(|index| pool[index].clone())
+            }
+        };
+
+        let on_updates = |updates| self.process_socket_updates(updates);
+        match conn {
+            Some(conn) => conn.invoke(request, &self.0.config, on_updates).await,
+            None => {
+                self.0
+                    .conn
+                    .invoke(request, &self.0.config, on_updates)
+                    .await
+            }
+        }
+    }
+
+    /// Move the main connection to a different datacenter, which becomes the new home of the
+    /// account, transferring the authorization if the user is already logged in.
+    pub(crate) async fn migrate_to(&self, dc_id: i32) -> Result<(), InvocationError> {
+        let _migration = self.0.migration.lock().await;
+        if self.0.state.read().unwrap().dc_id == dc_id {
+            // Another request got the same error and already migrated while this one waited.
+            return Ok(());
+        }
+
+        info!("migrating home datacenter to {}", dc_id);
+
+        // Use the connection directly, as invoking through the client could migrate again.
+        let authorization = if self.0.config.session.get_user().is_some() {
+            let tl::enums::auth::ExportedAuthorization::Authorization(authorization) = self
+                .0
+                .conn
+                .invoke(
+                    &tl::functions::auth::ExportAuthorization { dc_id },
+                    &self.0.config,
+                    drop,
+                )
+                .await?;
+            Some(authorization)
+        } else {
+            None
+        };
+
+        let (mut sender, _) = connect_sender(dc_id, &self.0.config).await?;
+
+        if let Some(authorization) = authorization {
+            sender
+                .invoke(&tl::functions::auth::ImportAuthorization {
+                    id: authorization.id,
+                    bytes: authorization.bytes,
+                })
+                .await?;
+        }
+
+        self.0.conn.set_sender(dc_id, sender).await;
+        self.0.state.write().unwrap().dc_id = dc_id;
+        if let Some(user) = self.0.config.session.get_user() {
+            self.0.config.session.set_user(user.id, dc_id, user.bot);
+        }
+
+        // The rest of connections can reuse the authorization key the main one now has.
+        let pool = self.0.pool.read().unwrap().clone();
+        for conn in pool {
+            let (sender, _) = connect_sender(dc_id, &self.0.config).await?;
+            conn.set_sender(dc_id, sender).await;
+        }
+        Ok(())
+    }
+
+    /// Export the authorization of the logged-in user, so that it can be imported into a
+    /// connection to a different datacenter with `auth.importAuthorization`.
+    ///
+    /// [`Client::invoke_in_dc`] already takes care of this, so this method is only needed
+    /// when managing connections to other datacenters manually.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let authorization = client.export_authorization(4).await?;
+    /// println!("Exported authorization {}", authorization.id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn export_authorization(
         &self,
         target_dc_id: i32,
     ) -> Result<tl::types::auth::ExportedAuthorization, InvocationError> {
@@ -323,10 +962,14 @@ impl Client {
 
     async fn connect_sender(&self, dc_id: i32) -> Result<Arc<Connection>, InvocationError> {
         let mut mutex = self.0.downloader_map.write().await;
+        // Another task may have connected while the lock was being acquired.
+        if let Some(downloader) = mutex.get(&dc_id) {
+            return Ok(downloader.clone());
+        }
         debug!("Connecting new datacenter {}", dc_id);
         match connect_sender(dc_id, &self.0.config).await {
             Ok((new_sender, new_tx)) => {
-                let new_downloader = Arc::new(Connection::new(new_sender, new_tx));
+                let new_downloader = Arc::new(Connection::new(dc_id, new_sender, new_tx));
 
                 // export auth
                 let authorization = self.export_authorization(dc_id).await?;
@@ -337,45 +980,73 @@ impl Client {
                     bytes: authorization.bytes,
                 };
                 new_downloader
-                    .invoke(&request, self.0.config.params.flood_sleep_threshold, drop)
+                    .invoke(&request, &self.0.config, drop)
                     .await?;
 
                 mutex.insert(dc_id, new_downloader.clone());
                 Ok(new_downloader.clone())
             }
-            Err(AuthorizationError::Invoke(e)) => Err(e),
-            Err(AuthorizationError::Gen(e)) => {
-                panic!("authorization key generation failed: {e}")
-            }
+            Err(e) => Err(e.into()),
         }
     }
 
     async fn get_downloader(&self, dc_id: i32) -> Result<Option<Arc<Connection>>, InvocationError> {
-        return Ok({
+        Ok({
             let guard = self.0.downloader_map.read().await;
             guard.get(&dc_id).cloned()
-        });
+        })
     }
 
+    /// Invoke a raw API call in a different datacenter, such as the ones which store media or
+    /// statistics.
+    ///
+    /// The first time a datacenter is used, a new connection is made to it and the
+    /// authorization of the logged-in user is transferred. Later calls reuse that connection.
+    ///
+    /// If `dc_id` is the datacenter the account belongs to, this behaves like
+    /// [`Client::invoke`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_tl_types as tl;
+    ///
+    /// let config = client.invoke_in_dc(&tl::functions::help::GetConfig {}, 4).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub async fn invoke_in_dc<R: tl::RemoteCall>(
         &self,
         request: &R,
         dc_id: i32,
     ) -> Result<R::Return, InvocationError> {
+        if dc_id == self.0.state.read().unwrap().dc_id {
+            return self.invoke(request).await;
+        }
+
         let downloader = match self.get_downloader(dc_id).await? {
             None => self.connect_sender(dc_id).await?,
             Some(fd) => fd,
         };
-        downloader
-            .invoke(request, self.0.config.params.flood_sleep_threshold, drop)
-            .await
+        downloader.invoke(request, &self.0.config, drop).await
     }
 
     /// Perform a single network step.
     ///
+    /// Every connection to the home datacenter is driven, including those in the pool made
+    /// because of [`InitParams::connection_count`], so that their updates are received and their
+    /// messages acknowledged. The step completes as soon as any of them does.
+    ///
     /// Most commonly, you will want to use the higher-level abstraction [`Client::next_update`]
     /// instead.
     ///
+    /// When using [`UpdateOverflowPolicy::PauseReads`], this waits until there is room in the
+    /// update queue before reading from the network.
+    ///
+    /// [`InitParams::connection_count`]: crate::InitParams::connection_count
+    /// [`UpdateOverflowPolicy::PauseReads`]: crate::UpdateOverflowPolicy::PauseReads
+    ///
     /// # Examples
     ///
     /// ```
@@ -388,11 +1059,107 @@ impl Client {
     /// # }
     /// ```
     pub async fn step(&self) -> Result<(), sender::ReadError> {
-        let updates = self.0.conn.step().await?;
+        self.wait_for_update_room().await;
+        let pool = self.0.pool.read().unwrap().clone();
+        let updates = if pool.is_empty() {
+            self.0.conn.step(&self.0.config).await?
+        } else {
+            let steps = iter::once(&self.0.conn)
+                .chain(pool.iter().map(|conn| conn.as_ref()))
+                .map(|conn| Box::pin(conn.step(&self.0.config)));
+            select_all(steps).await.0?
+        };
         self.process_socket_updates(updates);
         Ok(())
     }
 
+    /// Returns the round-trip time to Telegram measured by the last keepalive ping or call to
+    /// [`Client::ping`], if any.
+    ///
+    /// Keepalive pings are sent every [`InitParams::ping_interval`] while the client is being
+    /// stepped, so this can be used by long-running programs to monitor the health of the link.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn f(client: grammers_client::Client) {
+    /// if let Some(rtt) = client.last_rtt() {
+    ///     println!("Telegram is {:?} away", rtt);
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// [`InitParams::ping_interval`]: crate::InitParams::ping_interval
+    pub fn last_rtt(&self) -> Option<Duration> {
+        *self.0.conn.last_rtt.read().unwrap()
+    }
+
+    /// Returns the current time according to Telegram's servers.
+    ///
+    /// The difference between the server's clock and the local one is learnt when connecting,
+    /// and corrected whenever the server reports that the local clock is wrong. It is also
+    /// stored in the session, so that the next connection can use it from the start.
+    ///
+    /// This is useful to compare against the dates sent by Telegram on devices whose clock
+    /// is not accurate. The offset has a precision of one second.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn f(client: grammers_client::Client) {
+    /// use std::time::{SystemTime, UNIX_EPOCH};
+    ///
+    /// let now = client.server_time().duration_since(UNIX_EPOCH).unwrap();
+    /// println!("It is {} seconds since the epoch in Telegram", now.as_secs());
+    /// # }
+    /// ```
+    pub fn server_time(&self) -> SystemTime {
+        let offset = self.0.conn.time_offset.load(Ordering::Relaxed);
+        let now = SystemTime::now();
+        let delta = Duration::from_secs(offset.unsigned_abs() as u64);
+        if offset >= 0 {
+            now + delta
+        } else {
+            now - delta
+        }
+    }
+
+    /// Sends a ping to Telegram through the main connection, and returns how long it took to
+    /// receive the answer.
+    ///
+    /// The measured round-trip time will also be returned by [`Client::last_rtt`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let rtt = client.ping().await?;
+    /// println!("Pong after {:?}", rtt);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn ping(&self) -> Result<Duration, InvocationError> {
+        let request = tl::functions::Ping {
+            ping_id: utils::generate_random_id(),
+        };
+
+        // Sent only once, without sleeping on flood waits or retrying, so the time is accurate.
+        let start = Instant::now();
+        let rx = self.0.conn.request_tx.read().unwrap().enqueue(&request);
+        let body = self
+            .0
+            .conn
+            .receive(rx, &self.0.config, |updates| {
+                self.process_socket_updates(updates)
+            })
+            .await?;
+        tl::enums::Pong::from_bytes(&body)?;
+        let rtt = start.elapsed();
+
+        *self.0.conn.last_rtt.write().unwrap() = Some(rtt);
+        Ok(rtt)
+    }
+
     /// Run the client by repeatedly calling [`Client::step`] until a graceful disconnection
     /// occurs, or a network error occurs. Incoming updates are ignored and simply dropped.
     /// instead.
@@ -414,27 +1181,40 @@ impl Client {
 }
 
 impl Connection {
-    fn new(sender: Sender<Transport, mtp::Encrypted>, request_tx: Enqueuer) -> Self {
+    fn new(dc_id: i32, sender: Sender<Transport, mtp::Encrypted>, request_tx: Enqueuer) -> Self {
+        let time_offset = sender.time_offset();
+        let conn = Self::with_driver(dc_id, Driver::Network(sender), request_tx);
+        conn.time_offset.store(time_offset, Ordering::Relaxed);
+        conn
+    }
+
+    pub(crate) fn with_driver(dc_id: i32, driver: Driver, request_tx: Enqueuer) -> Self {
         Self {
-            sender: AsyncMutex::new(sender),
+            dc_id: AtomicI32::new(dc_id),
+            sender: AsyncMutex::new(driver),
             request_tx: RwLock::new(request_tx),
             step_counter: AtomicU32::new(0),
+            last_rtt: RwLock::new(None),
+            time_offset: AtomicI32::new(0),
         }
     }
 
     pub(crate) async fn invoke<R: tl::RemoteCall, F: Fn(Vec<tl::enums::Updates>)>(
         &self,
         request: &R,
-        flood_sleep_threshold: u32,
+        config: &Config,
         on_updates: F,
     ) -> Result<R::Return, InvocationError> {
+        let flood_sleep_threshold = config.params.flood_sleep_threshold;
+        let retry_policy = config.params.retry_policy;
         let mut slept_flood = false;
+        let mut retries = 0;
 
         let mut rx = { self.request_tx.read().unwrap().enqueue(request) };
         loop {
             match rx.try_recv() {
                 Ok(response) => match response {
-                    Ok(body) => break R::Return::from_bytes(&body).map_err(|e| e.into()),
+                    Ok(body) => break R::Return::from_owned(body).map_err(|e| e.into()),
                     Err(InvocationError::Rpc(RpcError {
                         name,
                         code: 420,
@@ -453,19 +1233,82 @@ impl Connection {
                         rx = self.request_tx.read().unwrap().enqueue(request);
                         continue;
                     }
+                    Err(InvocationError::Rpc(error)) if is_transient(&error) => {
+                        retries += 1;
+                        match retry_policy.should_retry(retries) {
+                            ControlFlow::Continue(delay) => {
+                                info!(
+                                    "retrying {} in {:?} after transient error: {}",
+                                    std::any::type_name::<R>(),
+                                    delay,
+                                    error
+                                );
+                                sleep(delay).await;
+                                rx = self.request_tx.read().unwrap().enqueue(request);
+                                continue;
+                            }
+                            ControlFlow::Break(()) => break Err(InvocationError::Rpc(error)),
+                        }
+                    }
                     Err(e) => break Err(e),
                 },
                 Err(TryRecvError::Empty) => {
-                    on_updates(self.step().await?);
+                    on_updates(self.step(config).await?);
                 }
-                Err(TryRecvError::Closed) => {
-                    panic!("request channel dropped before receiving a result")
+                Err(TryRecvError::Closed) => break Err(InvocationError::Dropped),
+            }
+        }
+    }
+
+    /// Replace the sender of this connection with one connected to `dc_id`.
+    ///
+    /// The requests in flight are sent again through the new sender, instead of being dropped
+    /// along with the old one.
+    async fn set_sender(&self, dc_id: i32, sender: Sender<Transport, mtp::Encrypted>) {
+        let mut driver = self.sender.lock().await;
+        match &mut *driver {
+            Driver::Network(current) => {
+                let old_sender = std::mem::replace(current, sender);
+                current.take_over(old_sender);
+            }
+            #[cfg(feature = "testing")]
+            Driver::Mock(_) => *driver = Driver::Network(sender),
+        }
+        self.dc_id.store(dc_id, Ordering::Relaxed);
+    }
+
+    /// Connect a new sender to the datacenter of this connection, which takes over the requests
+    /// of the current one, so that it can use a new authorization key.
+    async fn replace_sender(
+        &self,
+        sender: &mut Sender<Transport, mtp::Encrypted>,
+        config: &Config,
+    ) -> Result<(), AuthorizationError> {
+        let (new_sender, _) = connect_sender(self.dc_id.load(Ordering::Relaxed), config).await?;
+        let old_sender = std::mem::replace(sender, new_sender);
+        sender.take_over(old_sender);
+        Ok(())
+    }
+
+    /// Drive IO until the result of an enqueued request is received.
+    async fn receive<F: Fn(Vec<tl::enums::Updates>)>(
+        &self,
+        mut rx: oneshot::Receiver<Result<Vec<u8>, InvocationError>>,
+        config: &Config,
+        on_updates: F,
+    ) -> Result<Vec<u8>, InvocationError> {
+        loop {
+            match rx.try_recv() {
+                Ok(response) => break response,
+                Err(TryRecvError::Empty) => {
+                    on_updates(self.step(config).await?);
                 }
+                Err(TryRecvError::Closed) => break Err(InvocationError::Dropped),
             }
         }
     }
 
-    async fn step(&self) -> Result<Vec<tl::enums::Updates>, sender::ReadError> {
+    async fn step(&self, config: &Config) -> Result<Vec<tl::enums::Updates>, sender::ReadError> {
         let ticket_number = self.step_counter.load(Ordering::SeqCst);
         let mut sender = self.sender.lock().await;
         match self.step_counter.compare_exchange(
@@ -475,8 +1318,300 @@ impl Connection {
             Ordering::SeqCst,
             Ordering::SeqCst,
         ) {
-            Ok(_) => sender.step().await, // We're the one to drive IO.
-            Err(_) => Ok(Vec::new()),     // A different task drove IO.
+            Ok(_) => match &mut *sender {
+                // We're the one to drive IO.
+                Driver::Network(sender) => {
+                    if temp_auth_key_renewal_due(
+                        sender.temp_auth_key_expiry(),
+                        config,
+                        Instant::now(),
+                    ) {
+                        info!("temporary auth key is about to expire; binding a new one");
+                        if let Err(e) = self.replace_sender(sender, config).await {
+                            warn!("failed to replace the temporary auth key: {}", e);
+                        }
+                    }
+
+                    let mut result = sender.step().await;
+                    if let Err(ReadError::Transport(transport::Error::TransportCode(
+                        AUTH_KEY_NOT_FOUND,
+                    ))) = result
+                    {
+                        let dc_id = self.dc_id.load(Ordering::Relaxed);
+                        if config.params.temp_auth_key_expiry.is_none() {
+                            warn!(
+                                "server does not know the auth key for dc {}; generating a new one",
+                                dc_id
+                            );
+                            config.session.remove_dc(dc_id);
+                        } else {
+                            warn!(
+                                "server does not know the temporary auth key for dc {}; binding a new one",
+                                dc_id
+                            );
+                        }
+                        result = match self.replace_sender(sender, config).await {
+                            // Updates may have been missed while the key was not usable.
+                            Ok(()) => Ok(vec![tl::enums::Updates::TooLong]),
+                            Err(AuthorizationError::Invoke(InvocationError::Read(e))) => Err(e),
+                            Err(e) => {
+                                warn!("failed to replace the auth key: {}", e);
+                                result
+                            }
+                        };
+                    }
+
+                    if let Some(rtt) = sender.take_rtt() {
+                        *self.last_rtt.write().unwrap() = Some(rtt);
+                    }
+                    self.time_offset
+                        .store(sender.time_offset(), Ordering::Relaxed);
+                    result
+                }
+                #[cfg(feature = "testing")]
+                Driver::Mock(mock) => mock.step().await,
+            },
+            Err(_) => Ok(Vec::new()), // A different task drove IO.
+        }
+    }
+}
+
+/// Whether the temporary auth key expiring at `expiry` should be replaced by `now`.
+fn temp_auth_key_renewal_due(expiry: Option<Instant>, config: &Config, now: Instant) -> bool {
+    match (expiry, config.params.temp_auth_key_expiry) {
+        (Some(expiry), Some(expires_in)) => {
+            now + TEMP_AUTH_KEY_RENEWAL_MARGIN.min(expires_in / 4) >= expiry
         }
+        _ => false,
+    }
+}
+
+/// Errors caused by temporary issues on Telegram's side, after which the same request may succeed.
+fn is_transient(error: &RpcError) -> bool {
+    error.code == 500
+        || error.code == -500
+        || error.code == -503
+        || error.is("AUTH_RESTART")
+        || error.is("RPC_CALL_FAIL")
+        || error.is("TIMEOUT")
+        || error.name.eq_ignore_ascii_case("TIMEDOUT")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(code: i32, name: &str) -> RpcError {
+        RpcError {
+            code,
+            name: name.to_string(),
+            value: None,
+            caused_by: None,
+        }
+    }
+
+    #[test]
+    fn rejected_temp_key_binds_are_detected() {
+        assert!(is_temp_auth_key_bind_failure(&AuthorizationError::Gen(
+            authentication::Error::TempKeyNotBound
+        )));
+        assert!(is_temp_auth_key_bind_failure(&AuthorizationError::Invoke(
+            InvocationError::Rpc(
+                error(400, "ENCRYPTED_MESSAGE_INVALID")
+                    .with_caused_by(tl::functions::auth::BindTempAuthKey::CONSTRUCTOR_ID)
+            )
+        )));
+        assert!(!is_temp_auth_key_bind_failure(&AuthorizationError::Invoke(
+            InvocationError::Rpc(error(400, "ENCRYPTED_MESSAGE_INVALID"))
+        )));
+    }
+
+    #[test]
+    fn server_errors_are_transient() {
+        assert!(is_transient(&error(500, "INTERNAL")));
+        assert!(is_transient(&error(500, "HISTORY_GET_FAILED")));
+        assert!(is_transient(&error(-500, "No workers running")));
+        assert!(is_transient(&error(-503, "Timeout")));
+        assert!(is_transient(&error(500, "AUTH_RESTART")));
+        assert!(is_transient(&error(500, "RPC_CALL_FAIL")));
+        assert!(is_transient(&error(-503, "Timedout")));
+    }
+
+    #[test]
+    fn request_errors_are_not_transient() {
+        assert!(!is_transient(&error(400, "PEER_ID_INVALID")));
+        assert!(!is_transient(&error(403, "CHAT_WRITE_FORBIDDEN")));
+        assert!(!is_transient(&error(420, "FLOOD_WAIT")));
+        assert!(!is_transient(&error(303, "USER_MIGRATE")));
+    }
+
+    fn temp_auth_config(expires_in: Option<Duration>) -> Config {
+        Config {
+            session: grammers_session::Session::new(),
+            api_id: 0,
+            api_hash: String::new(),
+            params: crate::InitParams {
+                temp_auth_key_expiry: expires_in,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn short_temp_auth_keys_are_renewed_ahead_of_expiry() {
+        let expires_in = Duration::from_secs(8);
+        let config = temp_auth_config(Some(expires_in));
+        let now = Instant::now();
+        let expiry = Some(now + expires_in);
+
+        assert!(!temp_auth_key_renewal_due(expiry, &config, now));
+        assert!(!temp_auth_key_renewal_due(
+            expiry,
+            &config,
+            now + Duration::from_secs(5)
+        ));
+        assert!(temp_auth_key_renewal_due(
+            expiry,
+            &config,
+            now + Duration::from_secs(6)
+        ));
+        assert!(temp_auth_key_renewal_due(expiry, &config, now + expires_in));
+    }
+
+    #[test]
+    fn long_temp_auth_keys_are_renewed_a_minute_ahead() {
+        let expires_in = Duration::from_secs(24 * 60 * 60);
+        let config = temp_auth_config(Some(expires_in));
+        let now = Instant::now();
+        let expiry = Some(now + expires_in);
+
+        assert!(!temp_auth_key_renewal_due(
+            expiry,
+            &config,
+            now + expires_in / 2
+        ));
+        assert!(!temp_auth_key_renewal_due(
+            expiry,
+            &config,
+            now + expires_in - Duration::from_secs(61)
+        ));
+        assert!(temp_auth_key_renewal_due(
+            expiry,
+            &config,
+            now + expires_in - Duration::from_secs(60)
+        ));
+    }
+
+    #[test]
+    fn permanent_auth_keys_are_not_renewed() {
+        let now = Instant::now();
+        assert!(!temp_auth_key_renewal_due(
+            None,
+            &temp_auth_config(None),
+            now
+        ));
+        assert!(!temp_auth_key_renewal_due(
+            None,
+            &temp_auth_config(Some(Duration::from_secs(8))),
+            now
+        ));
+    }
+
+    #[test]
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn test_datacenters_without_default_address() {
+        let config = Config {
+            session: grammers_session::Session::new(),
+            api_id: 0,
+            api_hash: String::new(),
+            params: crate::InitParams {
+                test_mode: true,
+                ..Default::default()
+            },
+        };
+        assert!(dc_socket_addrs(5, &config).is_empty());
+
+        let stored = "127.0.0.1:443".parse().unwrap();
+        config.session.insert_dc_tcp(5, &stored, [0; 256]);
+        assert_eq!(dc_socket_addrs(5, &config), [stored]);
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod mock_tests {
+    use super::*;
+    use crate::client::testing::fixtures::*;
+    use crate::client::testing::MockServer;
+    use grammers_mtsender::FixedReconnect;
+
+    #[test]
+    fn updates_from_pool_connections_are_received() {
+        let server = MockServer::new();
+        let client = client(&server);
+
+        let pool_server = MockServer::new();
+        let connection = pool_server.connection(home_dc_id(&client.0.config));
+        client.0.pool.write().unwrap().push(Arc::new(connection));
+        pool_server.push_update(tl::types::UpdateConfig {}.into(), Vec::new(), Vec::new());
+
+        let (update, _) = block_on(client.next_raw_update()).unwrap();
+        assert!(matches!(update, tl::enums::Update::Config));
+    }
+
+    #[test]
+    fn migrating_to_the_current_datacenter_does_nothing() {
+        let server = MockServer::new();
+        let client = client(&server);
+        let dc_id = client.0.state.read().unwrap().dc_id;
+
+        // Like a request that waited on another's migration to the same datacenter.
+        block_on(client.migrate_to(dc_id)).unwrap();
+        assert_eq!(server.request_count(), 0);
+    }
+
+    static RETRY_TWICE: FixedReconnect = FixedReconnect {
+        attempts: 2,
+        delay: Duration::ZERO,
+    };
+
+    fn retrying_client(server: &MockServer) -> Client {
+        client_with(
+            server,
+            crate::InitParams {
+                retry_policy: &RETRY_TWICE,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn transient_errors_are_retried() {
+        let server = MockServer::new();
+        let client = retrying_client(&server);
+        server.respond_error::<tl::functions::Ping>(rpc_error(500, "INTERNAL"));
+        server.respond_error::<tl::functions::Ping>(rpc_error(500, "INTERNAL"));
+        server.respond::<tl::functions::Ping>(
+            tl::types::Pong {
+                msg_id: 0,
+                ping_id: 1,
+            }
+            .into(),
+        );
+
+        block_on(client.invoke(&tl::functions::Ping { ping_id: 1 })).unwrap();
+        assert_eq!(server.take_requests::<tl::functions::Ping>().len(), 3);
+    }
+
+    #[test]
+    fn transient_errors_give_up_after_retries() {
+        let server = MockServer::new();
+        let client = retrying_client(&server);
+        for _ in 0..4 {
+            server.respond_error::<tl::functions::Ping>(rpc_error(500, "INTERNAL"));
+        }
+
+        let error = block_on(client.invoke(&tl::functions::Ping { ping_id: 1 })).unwrap_err();
+        assert!(error.is("INTERNAL"));
+        assert_eq!(server.take_requests::<tl::functions::Ping>().len(), 3);
     }
 }
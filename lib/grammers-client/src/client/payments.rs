@@ -0,0 +1,272 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::types::{ChatMap, Invoice, IterBuffer, Message, StarsRevenue, StarsSubscription};
+use crate::{utils, Client, InputMessage};
+pub use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+pub type StarsSubscriptionIter =
+    IterBuffer<tl::functions::payments::GetStarsSubscriptions, StarsSubscription>;
+
+impl StarsSubscriptionIter {
+    fn new(client: &Client) -> Self {
+        // The server decides how many subscriptions are returned with every request.
+        Self::from_request(
+            client,
+            0,
+            tl::functions::payments::GetStarsSubscriptions {
+                missing_balance: false,
+                peer: tl::enums::InputPeer::PeerSelf,
+                offset: String::new(),
+            },
+        )
+    }
+
+    /// Only return the subscriptions that could not be renewed due to an insufficient balance.
+    pub fn missing_balance(mut self) -> Self {
+        self.request.missing_balance = true;
+        self
+    }
+
+    /// Return the next `StarsSubscription` from the internal buffer, filling the buffer
+    /// previously if it's empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no subscriptions left.
+    pub async fn next(&mut self) -> Result<Option<StarsSubscription>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        let tl::enums::payments::StarsStatus::Status(tl::types::payments::StarsStatus {
+            subscriptions,
+            subscriptions_next_offset,
+            chats,
+            users,
+            ..
+        }) = self.client.invoke(&self.request).await?;
+
+        let subscriptions = subscriptions.unwrap_or_default();
+        match subscriptions_next_offset {
+            Some(offset) if !subscriptions.is_empty() => self.request.offset = offset,
+            _ => self.last_chunk = true,
+        }
+
+        let chats = ChatMap::new(users, chats);
+        self.buffer.extend(
+            subscriptions
+                .into_iter()
+                .map(|s| StarsSubscription::from_raw(s, &chats)),
+        );
+
+        Ok(self.pop_item())
+    }
+}
+
+/// Method implementations related to payments and Telegram Stars.
+impl Client {
+    /// Send an invoice to the given chat, so that users can pay it from there.
+    ///
+    /// Only bot accounts can send invoices. Once a user pays, the bot will receive a
+    /// [`Update::PreCheckoutQuery`] that must be answered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::Invoice;
+    ///
+    /// let invoice = Invoice::stars("Coffee", "A virtual cup of coffee", "coffee", 5);
+    /// client.send_invoice(&chat, &invoice).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`Update::PreCheckoutQuery`]: crate::types::Update::PreCheckoutQuery
+    pub async fn send_invoice<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        invoice: &Invoice,
+    ) -> Result<Message, InvocationError> {
+        self.send_message(
+            chat,
+            InputMessage::default().media(invoice.to_raw_input_media()),
+        )
+        .await
+    }
+
+    /// Export an invoice as a link which can be shared with users so they can pay it.
+    ///
+    /// Only bot accounts can export invoices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::Invoice;
+    ///
+    /// let invoice = Invoice::stars("Premium", "Unlock all features", "premium-1", 50);
+    /// let link = client.export_invoice(&invoice).await?;
+    /// println!("Pay here: {}", link);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn export_invoice(&self, invoice: &Invoice) -> Result<String, InvocationError> {
+        let tl::enums::payments::ExportedInvoice::Invoice(exported) = self
+            .invoke(&tl::functions::payments::ExportInvoice {
+                invoice_media: invoice.to_raw_input_media(),
+            })
+            .await?;
+
+        Ok(exported.url)
+    }
+
+    /// Create an invite link for the given channel which requires a Telegram Stars subscription
+    /// to join.
+    ///
+    /// Users joining through the link will be charged `amount` Stars every `period` seconds.
+    /// Telegram currently only accepts a period of 30 days (`2592000` seconds).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(channel: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// const MONTH: i32 = 30 * 24 * 60 * 60;
+    ///
+    /// let link = client.export_subscription_invite_link(&channel, MONTH, 100, Some("Members")).await?;
+    /// println!("Subscribe here: {}", link);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn export_subscription_invite_link<C: Into<PackedChat>>(
+        &self,
+        channel: C,
+        period: i32,
+        amount: i64,
+        title: Option<&str>,
+    ) -> Result<String, InvocationError> {
+        let invite = self
+            .invoke(&tl::functions::messages::ExportChatInvite {
+                legacy_revoke_permanent: false,
+                request_needed: false,
+                peer: channel.into().to_input_peer(),
+                expire_date: None,
+                usage_limit: None,
+                title: title.map(|t| t.to_string()),
+                subscription_pricing: Some(
+                    tl::types::StarsSubscriptionPricing { period, amount }.into(),
+                ),
+            })
+            .await?;
+
+        match invite {
+            tl::enums::ExportedChatInvite::ChatInviteExported(invite) => Ok(invite.link),
+            tl::enums::ExportedChatInvite::ChatInvitePublicJoinRequests => {
+                Err(utils::unexpected_response::<
+                    tl::types::ChatInvitePublicJoinRequests,
+                >())
+            }
+        }
+    }
+
+    /// Iterate over the Telegram Stars subscriptions of the logged-in user.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut subscriptions = client.iter_stars_subscriptions();
+    ///
+    /// while let Some(subscription) = subscriptions.next().await? {
+    ///     println!("Paying {} stars until {}", subscription.amount(), subscription.until_date());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_stars_subscriptions(&self) -> StarsSubscriptionIter {
+        StarsSubscriptionIter::new(self)
+    }
+
+    /// Cancel a Telegram Stars subscription, so that it is not renewed once the current period
+    /// ends, or restore a previously-canceled one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(subscription: grammers_client::types::StarsSubscription, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.cancel_stars_subscription(subscription.id(), true).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn cancel_stars_subscription(
+        &self,
+        subscription_id: &str,
+        canceled: bool,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::payments::ChangeStarsSubscription {
+            peer: tl::enums::InputPeer::PeerSelf,
+            subscription_id: subscription_id.to_string(),
+            canceled: Some(canceled),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Renew a Telegram Stars subscription that could not be renewed automatically, for example,
+    /// due to an insufficient balance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(subscription: grammers_client::types::StarsSubscription, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// if subscription.can_refulfill() {
+    ///     client.renew_stars_subscription(subscription.id()).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn renew_stars_subscription(
+        &self,
+        subscription_id: &str,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::payments::FulfillStarsSubscription {
+            peer: tl::enums::InputPeer::PeerSelf,
+            subscription_id: subscription_id.to_string(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Get the Telegram Stars revenue of a channel or bot owned by the logged-in user, such as
+    /// the Stars earned from paid media, and whether they can currently be withdrawn.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(channel: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let revenue = client.get_stars_revenue(&channel).await?;
+    /// println!(
+    ///     "Earned {} stars so far (about ${:.2})",
+    ///     revenue.overall_revenue(),
+    ///     revenue.overall_revenue() as f64 * revenue.usd_rate(),
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_stars_revenue<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<StarsRevenue, InvocationError> {
+        self.invoke(&tl::functions::payments::GetStarsRevenueStats {
+            dark: false,
+            peer: chat.into().to_input_peer(),
+        })
+        .await
+        .map(StarsRevenue::from_raw)
+    }
+}
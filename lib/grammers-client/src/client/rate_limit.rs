@@ -0,0 +1,443 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Client-side rate limiting of requests, configured through [`InitParams::rate_limits`].
+//!
+//! [`InitParams::rate_limits`]: crate::InitParams::rate_limits
+use crate::Client;
+use grammers_mtsender::utils::sleep;
+use grammers_tl_types::{self as tl, Identifiable};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use web_time::Instant;
+
+/// How many requests may be sent within a period of time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rate {
+    /// How many requests may be sent at once, in a burst, which then refill over the period.
+    pub requests: u32,
+    /// How long it takes for all the requests to become available again.
+    pub period: Duration,
+}
+
+/// Client-side limit on how often a request may be sent, used in [`InitParams::rate_limits`].
+///
+/// Requests exceeding the limit are delayed until they may be sent, rather than failing. This
+/// lets bots with bursts of activity (for example, broadcasting a message to many chats) stay
+/// below the limits enforced by Telegram, instead of waiting for a `FLOOD_WAIT` error.
+///
+/// # Examples
+///
+/// ```
+/// use grammers_client::{InitParams, Rate, RateLimit};
+/// use grammers_tl_types::{self as tl, Identifiable};
+///
+/// let params = InitParams {
+///     rate_limits: vec![RateLimit {
+///         constructor_id: tl::functions::messages::SendMessage::CONSTRUCTOR_ID,
+///         global: Some(Rate::per_second(25)),
+///         per_chat: Some(Rate::per_second(1)),
+///     }],
+///     ..Default::default()
+/// };
+/// ```
+///
+/// [`InitParams::rate_limits`]: crate::InitParams::rate_limits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Constructor identifier of the function being limited.
+    pub constructor_id: u32,
+    /// Limit shared by every request of this function, if any.
+    pub global: Option<Rate>,
+    /// Limit applied separately to the requests of this function sent to each chat, if any.
+    ///
+    /// Only the requests made through the methods that know which chat they are for (such as
+    /// [`Client::send_message`] or [`Client::edit_message`]) are accounted by chat. The chat is
+    /// not extracted from raw requests, so invoking a function such as `messages.sendMessage`
+    /// through [`Client::invoke`] is only subject to the [`RateLimit::global`] limit.
+    pub per_chat: Option<Rate>,
+}
+
+// Bucket of tokens consumed by every request, refilled continuously over time.
+// Tokens may go negative, to reserve a spot for the requests that are waiting.
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+// Buffer keeping only the first bytes of a serialized function, its constructor identifier.
+#[derive(Default)]
+struct ConstructorId {
+    bytes: [u8; 4],
+    len: usize,
+}
+
+pub(crate) struct RateLimiter {
+    limits: HashMap<u32, RateLimit>,
+    buckets: Mutex<HashMap<(u32, Option<i64>), Bucket>>,
+}
+
+impl Rate {
+    /// Allow `requests` every second.
+    pub fn per_second(requests: u32) -> Self {
+        Self {
+            requests,
+            period: Duration::from_secs(1),
+        }
+    }
+
+    /// Allow `requests` every minute.
+    pub fn per_minute(requests: u32) -> Self {
+        Self {
+            requests,
+            period: Duration::from_secs(60),
+        }
+    }
+
+    fn capacity(&self) -> f64 {
+        self.requests.max(1) as f64
+    }
+
+    fn tokens_per_second(&self) -> f64 {
+        self.capacity() / self.period.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+impl Bucket {
+    fn new(rate: &Rate, now: Instant) -> Self {
+        Self {
+            tokens: rate.capacity(),
+            last_refill: now,
+        }
+    }
+
+    /// Take a token, returning how long to wait before it may be used.
+    fn take(&mut self, rate: &Rate, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * rate.tokens_per_second()).min(rate.capacity());
+        self.last_refill = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate.tokens_per_second())
+        }
+    }
+}
+
+impl Extend<u8> for ConstructorId {
+    fn extend<I: IntoIterator<Item = u8>>(&mut self, iter: I) {
+        for byte in iter.into_iter().take(self.bytes.len() - self.len) {
+            self.bytes[self.len] = byte;
+            self.len += 1;
+        }
+    }
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limits: &[RateLimit]) -> Self {
+        Self {
+            limits: limits
+                .iter()
+                .map(|limit| (limit.constructor_id, limit.clone()))
+                .collect(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.limits.is_empty()
+    }
+
+    /// Reserve a slot for a request, returning how long to wait before sending it.
+    ///
+    /// If `chat` is `None`, the global limit is used. Otherwise, the limit for that chat is.
+    fn reserve(&self, constructor_id: u32, chat: Option<i64>, now: Instant) -> Duration {
+        let Some(limit) = self.limits.get(&constructor_id) else {
+            return Duration::ZERO;
+        };
+        let rate = match chat {
+            None => limit.global,
+            Some(_) => limit.per_chat,
+        };
+        let Some(rate) = rate else {
+            return Duration::ZERO;
+        };
+
+        self.buckets
+            .lock()
+            .unwrap()
+            .entry((constructor_id, chat))
+            .or_insert_with(|| Bucket::new(&rate, now))
+            .take(&rate, now)
+    }
+}
+
+impl Client {
+    /// Wait until a request of type `R` may be sent according to the configured rate limits.
+    ///
+    /// If `chat` is `None`, the global limit is used. Otherwise, the limit for that chat is.
+    pub(crate) async fn wait_rate_limit<R: Identifiable>(&self, chat: Option<i64>) {
+        self.wait_rate_limit_by_id(R::CONSTRUCTOR_ID, chat).await;
+    }
+
+    /// Like [`Client::wait_rate_limit`], for a request whose type may not be [`Identifiable`].
+    ///
+    /// The global limit is used. The constructor identifier is read from the serialized
+    /// request, which is only done if there are rate limits configured.
+    pub(crate) async fn wait_request_rate_limit<R: tl::RemoteCall>(&self, request: &R) {
+        if self.0.rate_limiter.is_empty() {
+            return;
+        }
+
+        let mut constructor_id = ConstructorId::default();
+        request.serialize(&mut constructor_id);
+        self.wait_rate_limit_by_id(u32::from_le_bytes(constructor_id.bytes), None)
+            .await;
+    }
+
+    async fn wait_rate_limit_by_id(&self, constructor_id: u32, chat: Option<i64>) {
+        let limiter = &self.0.rate_limiter;
+        if limiter.is_empty() {
+            return;
+        }
+
+        let delay = limiter.reserve(constructor_id, chat, Instant::now());
+        if !delay.is_zero() {
+            log::debug!(
+                "delaying request {:08x} by {:?} to stay within the rate limit",
+                constructor_id,
+                delay
+            );
+            sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: u32 = 0x1234_5678;
+
+    fn limiter(global: Option<Rate>, per_chat: Option<Rate>) -> RateLimiter {
+        RateLimiter::new(&[RateLimit {
+            constructor_id: ID,
+            global,
+            per_chat,
+        }])
+    }
+
+    #[test]
+    fn burst_then_wait() {
+        let limiter = limiter(Some(Rate::per_second(2)), None);
+        let now = Instant::now();
+        assert_eq!(limiter.reserve(ID, None, now), Duration::ZERO);
+        assert_eq!(limiter.reserve(ID, None, now), Duration::ZERO);
+        assert_eq!(limiter.reserve(ID, None, now), Duration::from_millis(500));
+        assert_eq!(limiter.reserve(ID, None, now), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn tokens_refill_over_time() {
+        let limiter = limiter(Some(Rate::per_second(1)), None);
+        let now = Instant::now();
+        assert_eq!(limiter.reserve(ID, None, now), Duration::ZERO);
+        assert_eq!(
+            limiter.reserve(ID, None, now + Duration::from_millis(250)),
+            Duration::from_millis(750)
+        );
+        assert_eq!(
+            limiter.reserve(ID, None, now + Duration::from_secs(3)),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn chats_are_limited_separately() {
+        let limiter = limiter(None, Some(Rate::per_minute(1)));
+        let now = Instant::now();
+        assert_eq!(limiter.reserve(ID, None, now), Duration::ZERO);
+        assert_eq!(limiter.reserve(ID, Some(1), now), Duration::ZERO);
+        assert_eq!(limiter.reserve(ID, Some(2), now), Duration::ZERO);
+        assert_eq!(limiter.reserve(ID, Some(1), now), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn other_requests_are_not_limited() {
+        let limiter = limiter(Some(Rate::per_second(1)), Some(Rate::per_second(1)));
+        let now = Instant::now();
+        for _ in 0..5 {
+            assert_eq!(limiter.reserve(ID + 1, None, now), Duration::ZERO);
+            assert_eq!(limiter.reserve(ID + 1, Some(1), now), Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn constructor_id_of_serialized_request() {
+        let mut constructor_id = ConstructorId::default();
+        tl::Serializable::serialize(
+            &tl::functions::messages::SendMessage {
+                no_webpage: false,
+                silent: false,
+                background: false,
+                clear_draft: false,
+                noforwards: false,
+                update_stickersets_order: false,
+                invert_media: false,
+                peer: tl::enums::InputPeer::Empty,
+                reply_to: None,
+                message: "long enough to span several extends".to_string(),
+                random_id: 0,
+                reply_markup: None,
+                entities: None,
+                schedule_date: None,
+                send_as: None,
+                quick_reply_shortcut: None,
+                effect: None,
+            },
+            &mut constructor_id,
+        );
+        assert_eq!(
+            u32::from_le_bytes(constructor_id.bytes),
+            tl::functions::messages::SendMessage::CONSTRUCTOR_ID
+        );
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod mock_tests {
+    use super::*;
+    use crate::client::testing::fixtures::*;
+    use crate::client::testing::MockServer;
+    use grammers_session::{PackedChat, PackedType};
+
+    fn rate_limited_client(server: &MockServer, limit: RateLimit) -> Client {
+        client_with(
+            server,
+            crate::InitParams {
+                rate_limits: vec![limit],
+                ..Default::default()
+            },
+        )
+    }
+
+    const RATE_PERIOD: Duration = Duration::from_millis(200);
+
+    #[test]
+    fn invoke_waits_for_the_rate_limit() {
+        let server = MockServer::new();
+        let client = rate_limited_client(
+            &server,
+            RateLimit {
+                constructor_id: tl::functions::Ping::CONSTRUCTOR_ID,
+                global: Some(Rate {
+                    requests: 1,
+                    period: RATE_PERIOD,
+                }),
+                per_chat: None,
+            },
+        );
+        for ping_id in 0..2 {
+            server.respond::<tl::functions::Ping>(tl::types::Pong { msg_id: 0, ping_id }.into());
+        }
+
+        let start = Instant::now();
+        block_on(async {
+            client.invoke(&tl::functions::Ping { ping_id: 0 }).await?;
+            client.invoke(&tl::functions::Ping { ping_id: 1 }).await
+        })
+        .unwrap();
+        assert!(start.elapsed() >= RATE_PERIOD / 2);
+    }
+
+    #[test]
+    fn migrated_requests_wait_for_the_new_chat_rate_limit() {
+        let server = MockServer::new();
+        let client = rate_limited_client(
+            &server,
+            RateLimit {
+                constructor_id: tl::functions::messages::TranslateText::CONSTRUCTOR_ID,
+                global: None,
+                per_chat: Some(Rate {
+                    requests: 1,
+                    period: RATE_PERIOD,
+                }),
+            },
+        );
+        let translate = |chat: PackedChat| tl::functions::messages::TranslateText {
+            peer: Some(chat.to_input_peer()),
+            id: Some(vec![1]),
+            text: None,
+            to_lang: "en".to_string(),
+        };
+        let group = PackedChat {
+            ty: PackedType::Chat,
+            id: 1,
+            access_hash: None,
+        };
+        let megagroup = PackedChat {
+            ty: PackedType::Megagroup,
+            id: 2,
+            access_hash: Some(3),
+        };
+
+        server.respond_error::<tl::functions::messages::TranslateText>(rpc_error(
+            400,
+            "CHAT_ID_INVALID",
+        ));
+        server.respond::<tl::functions::messages::GetChats>(
+            tl::types::messages::Chats {
+                chats: vec![tl::types::Chat {
+                    creator: false,
+                    left: false,
+                    deactivated: true,
+                    call_active: false,
+                    call_not_empty: false,
+                    noforwards: false,
+                    id: group.id,
+                    title: String::new(),
+                    photo: tl::enums::ChatPhoto::Empty,
+                    participants_count: 0,
+                    date: 0,
+                    version: 0,
+                    migrated_to: Some(
+                        tl::types::InputChannel {
+                            channel_id: megagroup.id,
+                            access_hash: 3,
+                        }
+                        .into(),
+                    ),
+                    admin_rights: None,
+                    default_banned_rights: None,
+                }
+                .into()],
+            }
+            .into(),
+        );
+        server.respond::<tl::functions::messages::TranslateText>(
+            tl::types::messages::TranslateResult { result: Vec::new() }.into(),
+        );
+
+        let mut chat = group;
+        let start = Instant::now();
+        block_on(async {
+            // Use up the limit of the megagroup before the request gets migrated to it.
+            client
+                .wait_rate_limit::<tl::functions::messages::TranslateText>(Some(megagroup.id))
+                .await;
+            client.invoke_migrating(&mut chat, translate).await
+        })
+        .unwrap();
+        assert_eq!(chat, megagroup);
+        assert!(start.elapsed() >= RATE_PERIOD / 2);
+    }
+}
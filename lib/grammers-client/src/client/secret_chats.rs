@@ -0,0 +1,111 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Methods related to secret chats, which are end-to-end encrypted.
+
+use crate::types::secret_chat::{DhParams, SecretChat, SecretChatError, State};
+use crate::Client;
+use grammers_crypto::secret_chat::{self as crypto, KEY_SIZE};
+use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+/// Method implementations related to secret chats.
+impl Client {
+    /// Request a new secret chat with the given user.
+    ///
+    /// The chat cannot be used to send messages until the other party accepts it, which is
+    /// reported by [`SecretChatUpdate::Accepted`](crate::types::SecretChatUpdate::Accepted).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::SecretChatUpdate;
+    ///
+    /// let mut chat = client.request_secret_chat(&user).await?;
+    /// // Updates are only forwarded to the chat while `client.next_update()` is being polled.
+    /// loop {
+    ///     match chat.next_update().await? {
+    ///         SecretChatUpdate::Accepted => {
+    ///             chat.send_message("Hello, privately!").await?;
+    ///         }
+    ///         SecretChatUpdate::NewMessage { message, .. } => println!("{}", message.message),
+    ///         SecretChatUpdate::Discarded => break,
+    ///         _ => {}
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn request_secret_chat<C: Into<PackedChat>>(
+        &self,
+        user: C,
+    ) -> Result<SecretChat, SecretChatError> {
+        let user = user.into();
+        let (dh, random) = self
+            .get_dh_params()
+            .await?
+            .ok_or(SecretChatError::UnsafeParameters)?;
+        let secret = crypto::generate_secret(&random);
+        let g_a =
+            crypto::calculate_g_a(dh.g, &dh.p, &secret).ok_or(SecretChatError::UnsafeParameters)?;
+
+        let chat = self
+            .invoke(&tl::functions::messages::RequestEncryption {
+                user_id: user.to_input_user_lossy(),
+                random_id: crate::utils::generate_random_id() as i32,
+                g_a: tl::byte_string(g_a.to_vec()),
+            })
+            .await?;
+
+        match chat {
+            tl::enums::EncryptedChat::Waiting(chat) => Ok(SecretChat::new(
+                self,
+                chat.id,
+                chat.access_hash,
+                chat.participant_id,
+                State::waiting(dh, secret),
+            )),
+            _ => Err(SecretChatError::Discarded),
+        }
+    }
+
+    /// Fetch the Diffie-Hellman parameters, validating them only if they changed.
+    ///
+    /// Also returns the random bytes the server provides to generate the secret, or `None`
+    /// if the parameters are not safe to use.
+    pub(crate) async fn get_dh_params(
+        &self,
+    ) -> Result<Option<(DhParams, Vec<u8>)>, InvocationError> {
+        let cached = self.0.dh_params.lock().unwrap().clone();
+        let config = self
+            .invoke(&tl::functions::messages::GetDhConfig {
+                version: cached.as_ref().map(|dh| dh.version).unwrap_or(0),
+                random_length: KEY_SIZE as i32,
+            })
+            .await?;
+
+        Ok(match config {
+            tl::enums::messages::DhConfig::Config(config) => {
+                if !crypto::check_dh_params(config.g, &config.p) {
+                    return Ok(None);
+                }
+                let dh = DhParams {
+                    version: config.version,
+                    g: config.g,
+                    p: config.p.to_vec(),
+                };
+                *self.0.dh_params.lock().unwrap() = Some(dh.clone());
+                Some((dh, config.random.to_vec()))
+            }
+            tl::enums::messages::DhConfig::NotModified(config) => {
+                cached.map(|dh| (dh, config.random.to_vec()))
+            }
+        })
+    }
+}
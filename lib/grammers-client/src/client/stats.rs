@@ -0,0 +1,167 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Methods related to channel and message statistics.
+
+use super::chats::input_channel;
+use crate::types::{BroadcastStats, MegagroupStats, Message, MessageStats, StatsGraph};
+use crate::Client;
+pub use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+/// Method implementations related to channel and message statistics.
+impl Client {
+    /// Invoke a statistics request, following the redirect to the datacenter that holds
+    /// the statistics of the channel if necessary.
+    ///
+    /// Returns the result along with the datacenter it came from, if it was not the home one.
+    async fn invoke_stats<R: tl::RemoteCall + tl::Identifiable>(
+        &self,
+        request: &R,
+        dc_id: Option<i32>,
+    ) -> Result<(R::Return, Option<i32>), InvocationError> {
+        if let Some(dc_id) = dc_id {
+            return self
+                .invoke_in_dc(request, dc_id)
+                .await
+                .map(|result| (result, Some(dc_id)));
+        }
+
+        match self.invoke(request).await {
+            Ok(result) => Ok((result, None)),
+            Err(InvocationError::Rpc(err)) if err.is("STATS_MIGRATE") && err.value.is_some() => {
+                let dc_id = err.value.unwrap() as i32;
+                self.invoke_in_dc(request, dc_id)
+                    .await
+                    .map(|result| (result, Some(dc_id)))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Fetch the statistics of a broadcast channel.
+    ///
+    /// The logged-in user must be an administrator of the channel, and the channel must be
+    /// large enough for Telegram to produce statistics.
+    ///
+    /// If the statistics are stored in a different datacenter, a connection to it is made
+    /// automatically.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(channel: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let stats = client.get_broadcast_stats(&channel).await?;
+    /// println!("{} followers", stats.followers().current);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_broadcast_stats<C: Into<PackedChat>>(
+        &self,
+        channel: C,
+    ) -> Result<BroadcastStats, InvocationError> {
+        let request = tl::functions::stats::GetBroadcastStats {
+            dark: false,
+            channel: input_channel(channel.into())?,
+        };
+        self.invoke_stats(&request, None)
+            .await
+            .map(|(stats, dc_id)| BroadcastStats::from_raw(stats, dc_id))
+    }
+
+    /// Fetch the statistics of a megagroup.
+    ///
+    /// The logged-in user must be an administrator of the group, and the group must be
+    /// large enough for Telegram to produce statistics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(group: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let stats = client.get_megagroup_stats(&group).await?;
+    /// println!("{} messages sent", stats.messages().current);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_megagroup_stats<C: Into<PackedChat>>(
+        &self,
+        group: C,
+    ) -> Result<MegagroupStats, InvocationError> {
+        let request = tl::functions::stats::GetMegagroupStats {
+            dark: false,
+            channel: input_channel(group.into())?,
+        };
+        self.invoke_stats(&request, None)
+            .await
+            .map(|(stats, dc_id)| MegagroupStats::from_raw(stats, dc_id))
+    }
+
+    /// Fetch the statistics of a message sent in a broadcast channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let stats = client.get_message_stats(&message).await?;
+    /// if let Some(json) = stats.views_graph().json() {
+    ///     println!("Views: {}", json);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_message_stats(
+        &self,
+        message: &Message,
+    ) -> Result<MessageStats, InvocationError> {
+        let request = tl::functions::stats::GetMessageStats {
+            dark: false,
+            channel: input_channel(message.chat().pack())?,
+            msg_id: message.id(),
+        };
+        self.invoke_stats(&request, None)
+            .await
+            .map(|(stats, dc_id)| MessageStats::from_raw(stats, dc_id))
+    }
+
+    /// Load a statistics graph which [needs loading](StatsGraph::needs_loading), or the
+    /// zoomed-in version of a loaded graph at the given `x` coordinate.
+    ///
+    /// Graphs which are already loaded and have nothing to zoom into are returned as-is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(channel: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let stats = client.get_broadcast_stats(&channel).await?;
+    /// let mut graph = stats.languages_graph();
+    /// if graph.needs_loading() {
+    ///     graph = client.load_stats_graph(&graph, None).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn load_stats_graph(
+        &self,
+        graph: &StatsGraph,
+        x: Option<i64>,
+    ) -> Result<StatsGraph, InvocationError> {
+        let token = match &graph.raw {
+            tl::enums::StatsGraph::Async(graph) => graph.token.clone(),
+            tl::enums::StatsGraph::Graph(tl::types::StatsGraph {
+                zoom_token: Some(token),
+                ..
+            }) if x.is_some() => token.clone(),
+            _ => return Ok(graph.clone()),
+        };
+
+        let request = tl::functions::stats::LoadAsyncGraph { token, x };
+        self.invoke_stats(&request, graph.dc_id)
+            .await
+            .map(|(graph, dc_id)| StatsGraph::from_raw(graph, dc_id))
+    }
+}
@@ -0,0 +1,383 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Methods related to sticker sets.
+
+use crate::types::media::{Document, Sticker};
+use crate::types::{InputSticker, IterBuffer, StickerSet};
+use crate::{utils, Client};
+pub use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+const MAX_LIMIT: usize = 100;
+
+pub type StickerSetIter = IterBuffer<tl::functions::messages::GetAllStickers, StickerSet>;
+
+impl StickerSetIter {
+    fn new(client: &Client) -> Self {
+        Self::from_request(
+            client,
+            MAX_LIMIT,
+            tl::functions::messages::GetAllStickers { hash: 0 },
+        )
+    }
+
+    /// Return the next `StickerSet` from the internal buffer, filling the buffer previously if
+    /// it's empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no sticker sets left.
+    pub async fn next(&mut self) -> Result<Option<StickerSet>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        // Every installed set is returned at once, so a single request is enough.
+        self.last_chunk = true;
+        let sets = match self.client.invoke(&self.request).await? {
+            tl::enums::messages::AllStickers::Stickers(stickers) => stickers.sets,
+            tl::enums::messages::AllStickers::NotModified => {
+                return Err(utils::unexpected_response::<
+                    tl::types::messages::AllStickersNotModified,
+                >());
+            }
+        };
+
+        self.total = Some(sets.len());
+        self.buffer
+            .extend(sets.into_iter().map(StickerSet::from_raw));
+
+        Ok(self.pop_item())
+    }
+}
+
+/// Method implementations related to sticker sets.
+impl Client {
+    /// Fetch a sticker set, along with all of its stickers, by its short name.
+    ///
+    /// Returns `None` if there is no set with that name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// if let Some(set) = client.get_sticker_set("AnimatedEmojies").await? {
+    ///     println!("{} has {} stickers", set.title(), set.stickers().len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_sticker_set(
+        &self,
+        short_name: &str,
+    ) -> Result<Option<StickerSet>, InvocationError> {
+        self.fetch_sticker_set(
+            tl::types::InputStickerSetShortName {
+                short_name: short_name.to_string(),
+            }
+            .into(),
+        )
+        .await
+    }
+
+    /// Fetch the sticker set a sticker belongs to, along with all of its stickers.
+    ///
+    /// Returns `None` if the sticker does not belong to any set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// if let Some(sticker) = message.sticker() {
+    ///     if let Some(set) = client.get_sticker_set_of(&sticker).await? {
+    ///         println!("The sticker comes from {}", set.title());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_sticker_set_of(
+        &self,
+        sticker: &Sticker,
+    ) -> Result<Option<StickerSet>, InvocationError> {
+        match sticker.raw_attrs.stickerset {
+            tl::enums::InputStickerSet::Empty => Ok(None),
+            ref set => self.fetch_sticker_set(set.clone()).await,
+        }
+    }
+
+    async fn fetch_sticker_set(
+        &self,
+        stickerset: tl::enums::InputStickerSet,
+    ) -> Result<Option<StickerSet>, InvocationError> {
+        match self
+            .invoke(&tl::functions::messages::GetStickerSet {
+                stickerset,
+                hash: 0,
+            })
+            .await
+        {
+            Ok(tl::enums::messages::StickerSet::Set(set)) => Ok(Some(StickerSet::from_full(set))),
+            Ok(tl::enums::messages::StickerSet::NotModified) => Err(utils::unexpected_response::<
+                tl::types::messages::StickerSetNotModified,
+            >()),
+            Err(err) if err.is("STICKERSET_INVALID") => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Iterate over the sticker sets installed by the logged-in user.
+    ///
+    /// The sets do not include their stickers, only their information.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut sets = client.iter_installed_sticker_sets();
+    ///
+    /// while let Some(set) = sets.next().await? {
+    ///     println!("{} ({} stickers)", set.title(), set.count());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_installed_sticker_sets(&self) -> StickerSetIter {
+        StickerSetIter::new(self)
+    }
+
+    /// Install a sticker set, optionally archiving it right away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// if let Some(set) = client.get_sticker_set("AnimatedEmojies").await? {
+    ///     client.install_sticker_set(&set, false).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn install_sticker_set(
+        &self,
+        set: &StickerSet,
+        archived: bool,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::InstallStickerSet {
+            stickerset: set.to_input(),
+            archived,
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Uninstall a previously-installed sticker set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(set: grammers_client::types::StickerSet, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// client.uninstall_sticker_set(&set).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn uninstall_sticker_set(&self, set: &StickerSet) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::messages::UninstallStickerSet {
+            stickerset: set.to_input(),
+        })
+        .await
+        .map(drop)
+    }
+
+    /// Fetch the documents of custom emoji, given their identifiers.
+    ///
+    /// The identifiers can be found in the custom emoji formatting entities of messages. The
+    /// documents are returned in no particular order, and unknown identifiers are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let documents = client.get_custom_emoji_documents(&[5368324170671202286]).await?;
+    /// for document in documents {
+    ///     println!("Custom emoji document {}", document.id());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_custom_emoji_documents(
+        &self,
+        document_ids: &[i64],
+    ) -> Result<Vec<Document>, InvocationError> {
+        let documents = self
+            .invoke(&tl::functions::messages::GetCustomEmojiDocuments {
+                document_id: document_ids.to_vec(),
+            })
+            .await?;
+
+        Ok(documents
+            .into_iter()
+            .filter(|document| matches!(document, tl::enums::Document::Document(_)))
+            .map(|document| {
+                Document::from_raw_media(tl::types::MessageMediaDocument {
+                    nopremium: false,
+                    spoiler: false,
+                    video: false,
+                    round: false,
+                    voice: false,
+                    document: Some(document),
+                    alt_document: None,
+                    ttl_seconds: None,
+                })
+            })
+            .collect())
+    }
+
+    /// Create a new sticker set owned by the given user. Only bot accounts can use this method.
+    ///
+    /// The short name must end in `_by_<bot username>`. The stickers are uploaded on behalf of
+    /// the user, who must have started a conversation with the bot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::InputSticker;
+    ///
+    /// let file = client.upload_file("sticker.png").await?;
+    /// let set = client
+    ///     .create_sticker_set(&user, "My stickers", "mine_by_my_bot", &[InputSticker::new(file, "😀")])
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_sticker_set<C: Into<PackedChat>>(
+        &self,
+        user: C,
+        title: &str,
+        short_name: &str,
+        stickers: &[InputSticker],
+    ) -> Result<StickerSet, InvocationError> {
+        let user = user.into();
+        let mut items = Vec::with_capacity(stickers.len());
+        for sticker in stickers {
+            items.push(self.upload_sticker(user, sticker).await?);
+        }
+
+        let set = self
+            .invoke(&tl::functions::stickers::CreateStickerSet {
+                masks: false,
+                emojis: false,
+                text_color: false,
+                user_id: user.to_input_user_lossy(),
+                title: title.to_string(),
+                short_name: short_name.to_string(),
+                thumb: None,
+                stickers: items,
+                software: None,
+            })
+            .await?;
+
+        sticker_set_result(set)
+    }
+
+    /// Add a sticker to a set previously created by the bot. Only bot accounts can use this
+    /// method.
+    ///
+    /// The sticker is uploaded on behalf of the owner of the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(user: grammers_client::types::User, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::InputSticker;
+    ///
+    /// let file = client.upload_file("another.png").await?;
+    /// client
+    ///     .add_sticker_to_set(&user, "mine_by_my_bot", &InputSticker::new(file, "🎉"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn add_sticker_to_set<C: Into<PackedChat>>(
+        &self,
+        owner: C,
+        short_name: &str,
+        sticker: &InputSticker,
+    ) -> Result<StickerSet, InvocationError> {
+        let item = self.upload_sticker(owner.into(), sticker).await?;
+        let set = self
+            .invoke(&tl::functions::stickers::AddStickerToSet {
+                stickerset: tl::types::InputStickerSetShortName {
+                    short_name: short_name.to_string(),
+                }
+                .into(),
+                sticker: item,
+            })
+            .await?;
+
+        sticker_set_result(set)
+    }
+
+    async fn upload_sticker(
+        &self,
+        owner: PackedChat,
+        sticker: &InputSticker,
+    ) -> Result<tl::enums::InputStickerSetItem, InvocationError> {
+        let media = self
+            .invoke(&tl::functions::messages::UploadMedia {
+                business_connection_id: None,
+                peer: owner.to_input_peer(),
+                media: tl::types::InputMediaUploadedDocument {
+                    nosound_video: false,
+                    force_file: true,
+                    spoiler: false,
+                    file: sticker.file.raw.clone(),
+                    thumb: None,
+                    mime_type: sticker.mime_type.clone(),
+                    attributes: vec![tl::types::DocumentAttributeFilename {
+                        file_name: sticker.file.name().to_string(),
+                    }
+                    .into()],
+                    stickers: None,
+                    ttl_seconds: None,
+                }
+                .into(),
+            })
+            .await?;
+
+        let document = match media {
+            tl::enums::MessageMedia::Document(tl::types::MessageMediaDocument {
+                document: Some(tl::enums::Document::Document(document)),
+                ..
+            }) => document,
+            _ => return Err(utils::unexpected_response::<tl::types::MessageMediaDocument>()),
+        };
+
+        Ok(tl::types::InputStickerSetItem {
+            document: tl::types::InputDocument {
+                id: document.id,
+                access_hash: document.access_hash,
+                file_reference: document.file_reference,
+            }
+            .into(),
+            emoji: sticker.emoji.clone(),
+            mask_coords: None,
+            keywords: sticker.keywords.clone(),
+        }
+        .into())
+    }
+}
+
+fn sticker_set_result(set: tl::enums::messages::StickerSet) -> Result<StickerSet, InvocationError> {
+    match set {
+        tl::enums::messages::StickerSet::Set(set) => Ok(StickerSet::from_full(set)),
+        tl::enums::messages::StickerSet::NotModified => Err(utils::unexpected_response::<
+            tl::types::messages::StickerSetNotModified,
+        >()),
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Methods related to stories.
+use crate::types::{ChatMap, IterBuffer, Story, StoryPrivacy};
+use crate::utils::{self, generate_random_id};
+use crate::{Client, InputMedia};
+pub use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+const MAX_LIMIT: usize = 100;
+
+pub type StoryIter = IterBuffer<tl::functions::stories::GetPinnedStories, Story>;
+
+impl StoryIter {
+    fn new(client: &Client, chat: PackedChat) -> Self {
+        Self::from_request(
+            client,
+            MAX_LIMIT,
+            tl::functions::stories::GetPinnedStories {
+                peer: chat.to_input_peer(),
+                offset_id: 0,
+                limit: 0,
+            },
+        )
+    }
+
+    /// Determines how many stories there are in total.
+    ///
+    /// This only performs a network call if `next` has not been called before.
+    pub async fn total(&mut self) -> Result<usize, InvocationError> {
+        if let Some(total) = self.total {
+            return Ok(total);
+        }
+
+        self.request.limit = 1;
+        let tl::enums::stories::Stories::Stories(stories) =
+            self.client.invoke(&self.request).await?;
+        self.total = Some(stories.count as usize);
+        Ok(stories.count as usize)
+    }
+
+    /// Return the next `Story` from the internal buffer, filling the buffer previously if it's
+    /// empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no stories left.
+    pub async fn next(&mut self) -> Result<Option<Story>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        self.request.limit = self.determine_limit(MAX_LIMIT);
+        let tl::enums::stories::Stories::Stories(stories) =
+            self.client.invoke(&self.request).await?;
+
+        self.total = Some(stories.count as usize);
+        self.last_chunk = stories.stories.len() < self.request.limit as usize;
+        if let Some(last) = stories.stories.last() {
+            self.request.offset_id = last.id();
+        }
+
+        {
+            let mut state = self.client.0.state.write().unwrap();
+            // Telegram can return peers without hash (e.g. Users with 'min: true')
+            let _ = state.chat_hashes.extend(&stories.users, &stories.chats);
+        }
+
+        // Stories only include who posted them when it differs from the requested peer.
+        let peer = match &self.request.peer {
+            tl::enums::InputPeer::User(user) => tl::types::PeerUser {
+                user_id: user.user_id,
+            }
+            .into(),
+            tl::enums::InputPeer::Channel(channel) => tl::types::PeerChannel {
+                channel_id: channel.channel_id,
+            }
+            .into(),
+            tl::enums::InputPeer::Chat(chat) => tl::types::PeerChat {
+                chat_id: chat.chat_id,
+            }
+            .into(),
+            _ => {
+                return Err(InvocationError::InvalidInput(
+                    "chat is not a user, group or channel",
+                ))
+            }
+        };
+        let chats = ChatMap::new(stories.users, stories.chats);
+        self.buffer.extend(
+            stories
+                .stories
+                .into_iter()
+                .filter_map(|story| Story::from_raw(story, &peer, &chats)),
+        );
+
+        Ok(self.pop_item())
+    }
+}
+
+/// Method implementations related to stories.
+impl Client {
+    /// Post a story to the profile of the logged-in user.
+    ///
+    /// The media must contain a photo or video, and its caption will be used as the caption of
+    /// the story. Stories expire after a day.
+    ///
+    /// Returns the identifier of the new story.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::{types::StoryPrivacy, InputMedia};
+    ///
+    /// let photo = client.upload_file("sunset.jpg").await?;
+    /// let media = InputMedia::caption("What a view!").photo(photo);
+    /// client.send_story(media, StoryPrivacy::Contacts).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn send_story<M: Into<InputMedia>>(
+        &self,
+        media: M,
+        privacy: StoryPrivacy,
+    ) -> Result<i32, InvocationError> {
+        let media = media.into();
+        let raw_media = media
+            .media
+            .ok_or(InvocationError::InvalidInput("story media is empty"))?;
+        let random_id = generate_random_id();
+
+        let updates = self
+            .invoke(&tl::functions::stories::SendStory {
+                pinned: false,
+                noforwards: false,
+                fwd_modified: false,
+                peer: tl::enums::InputPeer::PeerSelf,
+                media: raw_media,
+                media_areas: None,
+                caption: Some(media.caption),
+                entities: Some(media.entities),
+                privacy_rules: privacy.to_raw(),
+                random_id,
+                period: None,
+                fwd_from_id: None,
+                fwd_from_story: None,
+            })
+            .await?;
+
+        let updates = match updates {
+            tl::enums::Updates::Updates(updates) => updates.updates,
+            tl::enums::Updates::Combined(updates) => updates.updates,
+            _ => Vec::new(),
+        };
+
+        updates
+            .into_iter()
+            .find_map(|update| match update {
+                tl::enums::Update::StoryId(update) if update.random_id == random_id => {
+                    Some(update.id)
+                }
+                _ => None,
+            })
+            .ok_or_else(utils::unexpected_response::<tl::types::UpdateStoryId>)
+    }
+
+    /// Iterate over the stories pinned to the profile of a user or channel, newest first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut stories = client.iter_stories(&chat);
+    ///
+    /// while let Some(story) = stories.next().await? {
+    ///     println!("{}: {}", story.id(), story.caption());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_stories<C: Into<PackedChat>>(&self, chat: C) -> StoryIter {
+        StoryIter::new(self, chat.into())
+    }
+
+    /// Get the stories of a user or channel which have not expired yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// for story in client.get_active_stories(&chat).await? {
+    ///     println!("Story {} expires at {}", story.id(), story.expire_date());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_active_stories<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+    ) -> Result<Vec<Story>, InvocationError> {
+        let tl::enums::stories::PeerStories::Stories(response) = self
+            .invoke(&tl::functions::stories::GetPeerStories {
+                peer: chat.into().to_input_peer(),
+            })
+            .await?;
+
+        {
+            let mut state = self.0.state.write().unwrap();
+            // Telegram can return peers without hash (e.g. Users with 'min: true')
+            let _ = state.chat_hashes.extend(&response.users, &response.chats);
+        }
+
+        let tl::enums::PeerStories::Stories(stories) = response.stories;
+        let chats = ChatMap::new(response.users, response.chats);
+        Ok(stories
+            .stories
+            .into_iter()
+            .filter_map(|story| Story::from_raw(story, &stories.peer, &chats))
+            .collect())
+    }
+
+    /// Delete stories posted by the logged-in user.
+    ///
+    /// Returns the identifiers of the stories that were actually deleted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let story_id = 7;
+    /// client.delete_story(&[story_id]).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_story(&self, story_ids: &[i32]) -> Result<Vec<i32>, InvocationError> {
+        self.invoke(&tl::functions::stories::DeleteStories {
+            peer: tl::enums::InputPeer::PeerSelf,
+            id: story_ids.to_vec(),
+        })
+        .await
+    }
+}
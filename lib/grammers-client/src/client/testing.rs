@@ -0,0 +1,345 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! An in-memory stand-in for Telegram, to test code using a [`Client`] without a network.
+//!
+//! Only available with the `testing` feature.
+
+use super::client::{Connection, Driver};
+use super::net;
+use crate::{Client, Config};
+use futures_util::future::{pending, select, Either};
+use grammers_mtsender::{
+    enqueuer_channel, InvocationError, PendingRequest, ReadError, RequestReceiver, RpcError,
+};
+use grammers_tl_types::{self as tl, Deserializable, Identifiable, RemoteCall, Serializable};
+use log::warn;
+use std::collections::{HashMap, VecDeque};
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+// Wraps every request when `InitParams::invoke_without_updates` is set.
+const INVOKE_WITHOUT_UPDATES: u32 =
+    tl::functions::InvokeWithoutUpdates::<tl::functions::Ping>::CONSTRUCTOR_ID;
+
+/// A fake Telegram server, which answers the requests made by the [`Client`]s created from it
+/// with canned responses, and sends them fake updates.
+///
+/// Every response queued with [`MockServer::respond`] or [`MockServer::respond_error`] answers
+/// a single request of that type, in the same order they were queued. Requests for which no
+/// response was queued fail with a `MOCK_RESPONSE_MISSING` error.
+///
+/// Requests sent to datacenters other than the one the account belongs to still go through
+/// the network.
+///
+/// # Examples
+///
+/// ```
+/// # async fn f() -> Result<(), Box<dyn std::error::Error>> {
+/// use grammers_client::testing::MockServer;
+/// use grammers_client::Config;
+/// use grammers_session::Session;
+/// use grammers_tl_types as tl;
+///
+/// let server = MockServer::new();
+/// let client = server.client(Config {
+///     session: Session::new(),
+///     api_id: 1,
+///     api_hash: String::new(),
+///     params: Default::default(),
+/// });
+///
+/// server.respond::<tl::functions::Ping>(tl::types::Pong { msg_id: 0, ping_id: 7 }.into());
+/// client.invoke(&tl::functions::Ping { ping_id: 7 }).await?;
+///
+/// let pings = server.take_requests::<tl::functions::Ping>();
+/// assert_eq!(pings[0].ping_id, 7);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct MockServer(Arc<MockState>);
+
+#[derive(Default)]
+struct MockState {
+    inner: Mutex<MockInner>,
+    // Notified when new updates are pushed, to wake up the clients being stepped
+    updates_pushed: Notify,
+}
+
+#[derive(Default)]
+struct MockInner {
+    // Queued responses, by constructor identifier of the request they answer
+    responses: HashMap<u32, VecDeque<Result<Vec<u8>, RpcError>>>,
+    // Serialized requests made, which have not been taken yet
+    requests: Vec<Vec<u8>>,
+    updates: Vec<tl::enums::Updates>,
+}
+
+/// Drives the connection of a client created by a [`MockServer`].
+pub(crate) struct MockDriver {
+    server: MockServer,
+    requests: RequestReceiver,
+}
+
+impl MockServer {
+    /// Creates a new server, with no responses queued.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a client whose requests are answered by this server.
+    ///
+    /// The client never connects to Telegram, so the authorization key and datacenter options
+    /// in the session are ignored. The logged-in user is not fetched either.
+    pub fn client(&self, config: Config) -> Client {
+        let dc_id = net::home_dc_id(&config);
+        let connection = self.connection(dc_id);
+        Client::with_connection(config, dc_id, connection)
+    }
+
+    /// Creates a connection to the given datacenter whose requests are answered by this server.
+    pub(crate) fn connection(&self, dc_id: i32) -> Connection {
+        let (request_tx, requests) = enqueuer_channel();
+        let driver = Driver::Mock(MockDriver {
+            server: self.clone(),
+            requests,
+        });
+        Connection::with_driver(dc_id, driver, request_tx)
+    }
+
+    /// Queues the `response` to the next request of type `R`.
+    pub fn respond<R>(&self, response: R::Return)
+    where
+        R: RemoteCall + Identifiable,
+        R::Return: Serializable,
+    {
+        self.queue_response(R::CONSTRUCTOR_ID, Ok(response.to_bytes()));
+    }
+
+    /// Makes the next request of type `R` fail with the given `error`.
+    pub fn respond_error<R: RemoteCall + Identifiable>(&self, error: RpcError) {
+        self.queue_response(R::CONSTRUCTOR_ID, Err(error));
+    }
+
+    fn queue_response(&self, id: u32, response: Result<Vec<u8>, RpcError>) {
+        self.0
+            .inner
+            .lock()
+            .unwrap()
+            .responses
+            .entry(id)
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Removes and returns the requests of type `R` made so far, in the order they were made.
+    pub fn take_requests<R: RemoteCall + Identifiable + Deserializable>(&self) -> Vec<R> {
+        let mut taken = Vec::new();
+        self.0.inner.lock().unwrap().requests.retain(|body| {
+            if body[..4] != R::CONSTRUCTOR_ID.to_le_bytes() {
+                return true;
+            }
+            // Like types, functions are deserialized without their constructor identifier.
+            taken.push(R::from_bytes(&body[4..]).expect("request made by the client to be valid"));
+            false
+        });
+        taken
+    }
+
+    /// How many requests of any type were made and not taken yet.
+    pub fn request_count(&self) -> usize {
+        self.0.inner.lock().unwrap().requests.len()
+    }
+
+    /// Sends the `updates` to the clients being stepped, as if they came from Telegram.
+    ///
+    /// Like with Telegram, the users and chats referenced by the updates must be included in
+    /// them, or be known by the client already. Otherwise, the updates will be dropped.
+    pub fn push_updates(&self, updates: tl::enums::Updates) {
+        self.0.inner.lock().unwrap().updates.push(updates);
+        self.0.updates_pushed.notify_one();
+    }
+
+    /// Like [`MockServer::push_updates`], but for a single update.
+    pub fn push_update(
+        &self,
+        update: tl::enums::Update,
+        users: Vec<tl::enums::User>,
+        chats: Vec<tl::enums::Chat>,
+    ) {
+        self.push_updates(
+            tl::types::Updates {
+                updates: vec![update],
+                users,
+                chats,
+                date: 0,
+                seq: 0,
+            }
+            .into(),
+        );
+    }
+
+    fn answer(&self, request: PendingRequest) {
+        let mut body = request.body();
+        if body[..4] == INVOKE_WITHOUT_UPDATES.to_le_bytes() {
+            body = &body[4..];
+        }
+        let id = u32::from_le_bytes(body[..4].try_into().unwrap());
+
+        let response = {
+            let mut inner = self.0.inner.lock().unwrap();
+            inner.requests.push(body.to_vec());
+            inner.responses.get_mut(&id).and_then(VecDeque::pop_front)
+        };
+        let response = response.unwrap_or_else(|| {
+            warn!("no mock response queued for {}", tl::name_for_id(id));
+            Err(RpcError {
+                // Not a server error, so that it's not retried.
+                code: 400,
+                name: "MOCK_RESPONSE_MISSING".to_string(),
+                value: None,
+                caused_by: Some(id),
+            })
+        });
+
+        request.respond(response.map_err(InvocationError::Rpc));
+    }
+
+    fn take_updates(&self) -> Vec<tl::enums::Updates> {
+        std::mem::take(&mut self.0.inner.lock().unwrap().updates)
+    }
+}
+
+impl MockDriver {
+    /// Answers the requests received so far, returning once any was answered or updates
+    /// were pushed.
+    pub(crate) async fn step(&mut self) -> Result<Vec<tl::enums::Updates>, ReadError> {
+        loop {
+            let mut answered = false;
+            while let Some(request) = self.requests.try_recv() {
+                self.server.answer(request);
+                answered = true;
+            }
+
+            let updates = self.server.take_updates();
+            if answered || !updates.is_empty() {
+                return Ok(updates);
+            }
+
+            let received = pin!(self.requests.recv());
+            let pushed = pin!(self.server.0.updates_pushed.notified());
+            match select(received, pushed).await {
+                Either::Left((Some(request), _)) => {
+                    self.server.answer(request);
+                    return Ok(self.server.take_updates());
+                }
+                // The connection owns an enqueuer, so this cannot happen while it's alive.
+                Either::Left((None, _)) => pending().await,
+                Either::Right(_) => {}
+            }
+        }
+    }
+}
+
+/// Helpers for the tests of other modules which use a [`MockServer`].
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::MockServer;
+    use crate::{Client, Config, InitParams};
+    use grammers_mtsender::RpcError;
+    use grammers_session::Session;
+    use std::future::Future;
+
+    pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    pub(crate) fn client(server: &MockServer) -> Client {
+        client_with(server, Default::default())
+    }
+
+    pub(crate) fn client_with(server: &MockServer, params: InitParams) -> Client {
+        server.client(Config {
+            session: Session::new(),
+            api_id: 1,
+            api_hash: String::new(),
+            params,
+        })
+    }
+
+    pub(crate) fn rpc_error(code: i32, name: &str) -> RpcError {
+        RpcError {
+            code,
+            name: name.to_string(),
+            value: None,
+            caused_by: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::*;
+    use super::*;
+
+    #[test]
+    fn responses_are_returned_in_order() {
+        let server = MockServer::new();
+        let client = client(&server);
+        server.respond::<tl::functions::Ping>(
+            tl::types::Pong {
+                msg_id: 0,
+                ping_id: 1,
+            }
+            .into(),
+        );
+        server.respond_error::<tl::functions::Ping>(rpc_error(400, "PING_INVALID"));
+
+        block_on(async {
+            let tl::enums::Pong::Pong(pong) = client
+                .invoke(&tl::functions::Ping { ping_id: 1 })
+                .await
+                .unwrap();
+            assert_eq!(pong.ping_id, 1);
+
+            let error = client
+                .invoke(&tl::functions::Ping { ping_id: 2 })
+                .await
+                .unwrap_err();
+            assert!(error.is("PING_INVALID"));
+
+            let error = client
+                .invoke(&tl::functions::Ping { ping_id: 3 })
+                .await
+                .unwrap_err();
+            assert!(error.is("MOCK_RESPONSE_MISSING"));
+        });
+
+        let pings = server.take_requests::<tl::functions::Ping>();
+        assert_eq!(
+            pings.iter().map(|ping| ping.ping_id).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        assert_eq!(server.request_count(), 0);
+    }
+
+    #[test]
+    fn pushed_updates_are_received() {
+        let server = MockServer::new();
+        let client = client(&server);
+        server.push_update(tl::types::UpdateConfig {}.into(), Vec::new(), Vec::new());
+
+        let (update, _) = block_on(client.next_raw_update()).unwrap();
+        assert!(matches!(update, tl::enums::Update::Config));
+    }
+}
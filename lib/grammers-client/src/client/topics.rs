@@ -0,0 +1,240 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Methods related to forum topics.
+use super::chats::input_channel;
+use crate::types::{ForumTopic, IterBuffer};
+use crate::utils::{self, generate_random_id};
+use crate::Client;
+pub use grammers_mtsender::InvocationError;
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+const MAX_LIMIT: usize = 100;
+
+pub type TopicIter = IterBuffer<tl::functions::channels::GetForumTopics, ForumTopic>;
+
+impl TopicIter {
+    fn new(client: &Client, chat: PackedChat) -> Result<Self, InvocationError> {
+        Ok(Self::from_request(
+            client,
+            MAX_LIMIT,
+            tl::functions::channels::GetForumTopics {
+                channel: input_channel(chat)?,
+                q: None,
+                offset_date: 0,
+                offset_id: 0,
+                offset_topic: 0,
+                limit: 0,
+            },
+        ))
+    }
+
+    /// Only return the topics with a title containing the given query.
+    pub fn query(mut self, query: &str) -> Self {
+        self.request.q = Some(query.to_string());
+        self
+    }
+
+    /// Determines how many topics there are in total.
+    ///
+    /// This only performs a network call if `next` has not been called before.
+    pub async fn total(&mut self) -> Result<usize, InvocationError> {
+        if let Some(total) = self.total {
+            return Ok(total);
+        }
+
+        self.request.limit = 1;
+        let tl::enums::messages::ForumTopics::Topics(topics) =
+            self.client.invoke(&self.request).await?;
+        self.total = Some(topics.count as usize);
+        Ok(topics.count as usize)
+    }
+
+    /// Return the next `ForumTopic` from the internal buffer, filling the buffer previously if
+    /// it's empty.
+    ///
+    /// Returns `None` if the `limit` is reached or there are no topics left.
+    pub async fn next(&mut self) -> Result<Option<ForumTopic>, InvocationError> {
+        if let Some(result) = self.next_raw() {
+            return result;
+        }
+
+        self.request.limit = self.determine_limit(MAX_LIMIT);
+        let tl::enums::messages::ForumTopics::Topics(topics) =
+            self.client.invoke(&self.request).await?;
+
+        self.total = Some(topics.count as usize);
+        self.last_chunk = topics.topics.len() < self.request.limit as usize;
+
+        {
+            let mut state = self.client.0.state.write().unwrap();
+            // Telegram can return peers without hash (e.g. Users with 'min: true')
+            let _ = state.chat_hashes.extend(&topics.users, &topics.chats);
+        }
+
+        let topics = topics
+            .topics
+            .into_iter()
+            .filter_map(ForumTopic::from_raw)
+            .collect::<Vec<_>>();
+
+        if let Some(last) = topics.last() {
+            self.request.offset_date = last.raw.date;
+            self.request.offset_id = last.raw.top_message;
+            self.request.offset_topic = last.raw.id;
+        } else {
+            self.last_chunk = true;
+        }
+        self.buffer.extend(topics);
+
+        Ok(self.pop_item())
+    }
+}
+
+/// Method implementations related to forum topics in supergroups.
+impl Client {
+    /// Create a new topic in a supergroup with topics enabled.
+    ///
+    /// The `icon` is the identifier of a custom emoji to use for the topic, if any. Otherwise,
+    /// Telegram will pick a colored default icon.
+    ///
+    /// Returns the identifier of the new topic, which can be used with [`InputMessage::topic`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::InputMessage;
+    ///
+    /// let topic_id = client.create_topic(&chat, "Announcements", None).await?;
+    /// client
+    ///     .send_message(&chat, InputMessage::text("Welcome!").topic(Some(topic_id)))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// [`InputMessage::topic`]: crate::InputMessage::topic
+    pub async fn create_topic<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        title: &str,
+        icon: Option<i64>,
+    ) -> Result<i32, InvocationError> {
+        let updates = self
+            .invoke(&tl::functions::channels::CreateForumTopic {
+                channel: input_channel(chat.into())?,
+                title: title.to_string(),
+                icon_color: None,
+                icon_emoji_id: icon,
+                random_id: generate_random_id(),
+                send_as: None,
+            })
+            .await?;
+
+        let updates = match updates {
+            tl::enums::Updates::Updates(updates) => updates.updates,
+            tl::enums::Updates::Combined(updates) => updates.updates,
+            _ => Vec::new(),
+        };
+
+        // The topic is identified by the service message announcing its creation.
+        updates
+            .into_iter()
+            .find_map(|update| match update {
+                tl::enums::Update::NewChannelMessage(tl::types::UpdateNewChannelMessage {
+                    message: tl::enums::Message::Service(message),
+                    ..
+                }) if matches!(message.action, tl::enums::MessageAction::TopicCreate(_)) => {
+                    Some(message.id)
+                }
+                _ => None,
+            })
+            .ok_or_else(utils::unexpected_response::<tl::types::UpdateNewChannelMessage>)
+    }
+
+    /// Edit an existing topic in a supergroup.
+    ///
+    /// Only the values that are `Some` will be changed. Closing a topic prevents anyone but
+    /// administrators from sending messages to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let topic_id = 123;
+    /// client.edit_topic(&chat, topic_id, Some("Archive"), None, Some(true)).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn edit_topic<C: Into<PackedChat>>(
+        &self,
+        chat: C,
+        topic_id: i32,
+        title: Option<&str>,
+        icon: Option<i64>,
+        closed: Option<bool>,
+    ) -> Result<(), InvocationError> {
+        self.invoke(&tl::functions::channels::EditForumTopic {
+            channel: input_channel(chat.into())?,
+            topic_id,
+            title: title.map(|title| title.to_string()),
+            icon_emoji_id: icon,
+            closed,
+            hidden: None,
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Iterate over the topics of a supergroup with topics enabled, most recently active first.
+    ///
+    /// Deleted topics are skipped. Fails if the chat is not a channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut topics = client.iter_topics(&chat)?;
+    ///
+    /// while let Some(topic) = topics.next().await? {
+    ///     println!("{}: {}", topic.id(), topic.title());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_topics<C: Into<PackedChat>>(&self, chat: C) -> Result<TopicIter, InvocationError> {
+        TopicIter::new(self, chat.into())
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::client::testing::fixtures::*;
+    use crate::client::testing::MockServer;
+
+    #[test]
+    fn topics_of_non_channels_are_rejected() {
+        let server = MockServer::new();
+        let client = client(&server);
+        let user = PackedChat {
+            ty: grammers_session::PackedType::User,
+            id: 1,
+            access_hash: Some(2),
+        };
+
+        assert!(matches!(
+            client.iter_topics(user),
+            Err(InvocationError::InvalidInput(_))
+        ));
+        assert_eq!(server.request_count(), 0);
+    }
+}
@@ -8,7 +8,7 @@
 
 //! Methods to deal with and offer access to updates.
 
-use super::Client;
+use super::{Client, UpdateOverflowPolicy};
 use crate::types::{ChatMap, Update};
 use futures_util::future::{select, Either};
 use grammers_mtsender::utils::sleep_until;
@@ -16,9 +16,13 @@ pub use grammers_mtsender::{AuthorizationError, InvocationError};
 use grammers_session::channel_id;
 pub use grammers_session::{PrematureEndReason, UpdateState};
 use grammers_tl_types as tl;
+use std::collections::VecDeque;
+use std::future::Future;
 use std::pin::pin;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::{mpsc, Semaphore};
 use web_time::Instant;
 
 /// How long to wait after warning the user that the updates limit was exceeded.
@@ -82,6 +86,7 @@ impl Client {
             let (deadline, get_diff, get_channel_diff) = {
                 let state = &mut *self.0.state.write().unwrap();
                 if let Some(update) = state.updates.pop_front() {
+                    self.0.update_room.notify_waiters();
                     return Ok(update);
                 }
                 (
@@ -182,7 +187,7 @@ impl Client {
                 continue;
             }
 
-            let sleep = pin!(async { sleep_until(deadline.into()).await });
+            let sleep = pin!(async { sleep_until(deadline).await });
             let step = pin!(async { self.step().await });
 
             match select(sleep, step).await {
@@ -192,6 +197,108 @@ impl Client {
         }
     }
 
+    /// Continuously fetch updates and run `handler` on each of them using a pool of `workers`
+    /// concurrent tasks.
+    ///
+    /// Updates belonging to the same chat are always sent to the same worker, so they are
+    /// processed sequentially and in the order they were received, while updates for different
+    /// chats may be handled in parallel. Queries made by users without a chat (such as inline
+    /// queries) are ordered by their sender instead, and updates with no chat at all (such as
+    /// [`Update::Raw`]) are distributed among the workers.
+    ///
+    /// A `workers` count of zero is treated as one.
+    ///
+    /// Each worker holds as many pending updates as the [`InitParams::update_queue_limit`]. Once
+    /// a worker is full, no more updates are fetched until it makes progress. If the `handler`
+    /// panics, the panic is logged and the worker moves on to its next update.
+    ///
+    /// This method only returns once fetching the next update fails. Before returning the error,
+    /// the updates that were already dispatched are given a chance to finish.
+    ///
+    /// [`InitParams::update_queue_limit`]: crate::InitParams::update_queue_limit
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn f(client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::Update;
+    ///
+    /// client
+    ///     .run_updates_by_chat(4, |update| async move {
+    ///         if let Update::NewMessage(message) = update {
+    ///             if !message.outgoing() {
+    ///                 let _ = message.respond(message.text()).await;
+    ///             }
+    ///         }
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run_updates_by_chat<F, Fut>(
+        &self,
+        workers: usize,
+        handler: F,
+    ) -> Result<(), InvocationError>
+    where
+        F: Fn(Update) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let handler = Arc::new(handler);
+        let capacity = self
+            .0
+            .config
+            .params
+            .update_queue_limit
+            .unwrap_or(Semaphore::MAX_PERMITS)
+            .clamp(1, Semaphore::MAX_PERMITS);
+        let (senders, tasks): (Vec<_>, Vec<_>) = (0..workers.max(1))
+            .map(|_| {
+                let (tx, mut rx) = mpsc::channel::<Update>(capacity);
+                let handler = Arc::clone(&handler);
+                let task = tokio::task::spawn(async move {
+                    while let Some(update) = rx.recv().await {
+                        // Handled in a task of its own, so that a panic doesn't stop the worker.
+                        let handler = Arc::clone(&handler);
+                        if let Err(e) =
+                            tokio::task::spawn(async move { handler(update).await }).await
+                        {
+                            log::warn!("update handler failed: {}", e);
+                        }
+                    }
+                });
+                (tx, task)
+            })
+            .unzip();
+
+        let mut next_unordered = 0;
+        let result = loop {
+            let update = match self.next_update().await {
+                Ok(update) => update,
+                Err(e) => break Err(e),
+            };
+            let index = match update_order_key(&update) {
+                Some(id) => (id.unsigned_abs() % senders.len() as u64) as usize,
+                None => {
+                    next_unordered = (next_unordered + 1) % senders.len();
+                    next_unordered
+                }
+            };
+            if senders[index].send(update).await.is_err() {
+                // The worker only stops early if the runtime is shutting down.
+                log::warn!("update worker {} is gone; dropping update", index);
+            }
+        };
+
+        drop(senders);
+        for task in tasks {
+            if let Err(e) = task.await {
+                log::warn!("update worker failed: {}", e);
+            }
+        }
+        result
+    }
+
     pub(crate) fn process_socket_updates(&self, all_updates: Vec<tl::enums::Updates>) {
         if all_updates.is_empty() {
             return;
@@ -232,42 +339,142 @@ impl Client {
         }
     }
 
-    fn extend_update_queue(&self, mut updates: Vec<tl::enums::Update>, chat_map: Arc<ChatMap>) {
+    fn extend_update_queue(&self, updates: Vec<tl::enums::Update>, chat_map: Arc<ChatMap>) {
         let mut state = self.0.state.write().unwrap();
+        let mut incoming = updates
+            .into_iter()
+            .map(|u| (u, chat_map.clone()))
+            .collect::<Vec<_>>();
 
         if let Some(limit) = self.0.config.params.update_queue_limit {
-            if let Some(exceeds) = (state.updates.len() + updates.len()).checked_sub(limit + 1) {
-                let exceeds = exceeds + 1;
+            let dropped = apply_overflow_policy(
+                &mut state.updates,
+                &mut incoming,
+                limit,
+                self.0.config.params.update_overflow_policy,
+            );
+            if dropped != 0 {
                 let now = Instant::now();
                 let notify = match state.last_update_limit_warn {
                     None => true,
                     Some(instant) => now - instant > UPDATE_LIMIT_EXCEEDED_LOG_COOLDOWN,
                 };
 
-                updates.truncate(updates.len() - exceeds);
                 if notify {
                     log::warn!(
                         "{} updates were dropped because the update_queue_limit was exceeded",
-                        exceeds
+                        dropped
                     );
                 }
 
                 state.last_update_limit_warn = Some(now);
+                state.dropped_updates += dropped as u64;
             }
         }
 
-        state
-            .updates
-            .extend(updates.into_iter().map(|u| (u, chat_map.clone())));
+        state.updates.extend(incoming);
+    }
+
+    /// Returns how many updates have been dropped so far because the update queue was full.
+    ///
+    /// A growing count means updates are not being consumed fast enough. Consider handling them
+    /// concurrently, increasing the [`InitParams::update_queue_limit`], or changing the
+    /// [`InitParams::update_overflow_policy`].
+    ///
+    /// [`InitParams::update_queue_limit`]: crate::InitParams::update_queue_limit
+    /// [`InitParams::update_overflow_policy`]: crate::InitParams::update_overflow_policy
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn f(client: grammers_client::Client) {
+    /// if client.dropped_updates() > 0 {
+    ///     println!("Some updates were lost!");
+    /// }
+    /// # }
+    /// ```
+    pub fn dropped_updates(&self) -> u64 {
+        self.0.state.read().unwrap().dropped_updates
+    }
+
+    /// Wait until the update queue has room if reads should pause while it's full.
+    pub(crate) async fn wait_for_update_room(&self) {
+        if self.0.config.params.update_overflow_policy != UpdateOverflowPolicy::PauseReads {
+            return;
+        }
+        let Some(limit) = self.0.config.params.update_queue_limit else {
+            return;
+        };
+        loop {
+            // Created before checking, so that consuming an update in between isn't missed.
+            let notified = self.0.update_room.notified();
+            if self.0.state.read().unwrap().updates.len() < limit {
+                break;
+            }
+            notified.await;
+        }
     }
 
-    /// Synchronize the updates state to the session.
+    /// Synchronize the updates state to the session, along with the last known time offset.
     pub fn sync_update_state(&self) {
         let state = self.0.state.read().unwrap();
         self.0
             .config
             .session
             .set_state(state.message_box.session_state());
+        self.0
+            .config
+            .session
+            .set_time_offset(self.0.conn.time_offset.load(Ordering::Relaxed));
+    }
+}
+
+/// Make room for the `incoming` items in the `queue` according to the `policy`, returning how
+/// many items were dropped.
+fn apply_overflow_policy<T>(
+    queue: &mut VecDeque<T>,
+    incoming: &mut Vec<T>,
+    limit: usize,
+    policy: UpdateOverflowPolicy,
+) -> usize {
+    let exceeds = (queue.len() + incoming.len()).saturating_sub(limit);
+    if exceeds == 0 {
+        return 0;
+    }
+
+    match policy {
+        UpdateOverflowPolicy::DropNewest => {
+            incoming.truncate(incoming.len() - exceeds);
+            exceeds
+        }
+        UpdateOverflowPolicy::DropOldest => {
+            let from_incoming = exceeds.saturating_sub(queue.len());
+            queue.drain(..exceeds - from_incoming);
+            incoming.drain(..from_incoming);
+            exceeds
+        }
+        UpdateOverflowPolicy::PauseReads => 0,
+    }
+}
+
+/// The identifier used to keep the processing order of updates coming from the same place.
+fn update_order_key(update: &Update) -> Option<i64> {
+    match update {
+        Update::NewMessage(message) | Update::MessageEdited(message) => Some(message.chat().id()),
+        Update::MessageDeleted(deletion) => deletion.channel_id(),
+        Update::CallbackQuery(query) => Some(query.chat().id()),
+        Update::InlineQuery(query) => Some(query.sender().id()),
+        Update::InlineSend(send) => Some(send.sender().id()),
+        Update::PreCheckoutQuery(query) => Some(query.sender().id()),
+        Update::ShippingQuery(query) => Some(query.sender().id()),
+        Update::NewStory(story) => story.chat().map(|chat| chat.id()),
+        Update::ChatJoinRequest(request) => Some(request.chat().id()),
+        Update::ChatMigrated { from, .. } => Some(from.id),
+        Update::WebAppData(data) => Some(data.chat().id()),
+        Update::DraftUpdated(draft) => draft.chat().map(|chat| chat.id()),
+        Update::SecretChatRequest(request) => Some(request.user_id()),
+        Update::IncomingCall(call) => Some(call.user_id()),
+        Update::Raw(_) => None,
     }
 }
 
@@ -280,6 +487,61 @@ mod tests {
         panic!()
     }
 
+    #[test]
+    fn overflow_drops_newest() {
+        let mut queue = VecDeque::from([1, 2, 3]);
+        let mut incoming = vec![4, 5, 6];
+        let dropped = apply_overflow_policy(
+            &mut queue,
+            &mut incoming,
+            4,
+            UpdateOverflowPolicy::DropNewest,
+        );
+        assert_eq!(dropped, 2);
+        assert_eq!(queue, [1, 2, 3]);
+        assert_eq!(incoming, [4]);
+    }
+
+    #[test]
+    fn overflow_drops_oldest() {
+        let mut queue = VecDeque::from([1, 2, 3]);
+        let mut incoming = vec![4, 5];
+        let dropped = apply_overflow_policy(
+            &mut queue,
+            &mut incoming,
+            4,
+            UpdateOverflowPolicy::DropOldest,
+        );
+        assert_eq!(dropped, 1);
+        assert_eq!(queue, [2, 3]);
+        assert_eq!(incoming, [4, 5]);
+
+        let mut incoming = vec![6, 7, 8, 9, 10];
+        let dropped = apply_overflow_policy(
+            &mut queue,
+            &mut incoming,
+            4,
+            UpdateOverflowPolicy::DropOldest,
+        );
+        assert_eq!(dropped, 3);
+        assert!(queue.is_empty());
+        assert_eq!(incoming, [7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn overflow_pausing_reads_keeps_everything() {
+        let mut queue = VecDeque::from([1, 2, 3]);
+        let mut incoming = vec![4, 5];
+        let dropped = apply_overflow_policy(
+            &mut queue,
+            &mut incoming,
+            2,
+            UpdateOverflowPolicy::PauseReads,
+        );
+        assert_eq!(dropped, 0);
+        assert_eq!(queue.len() + incoming.len(), 5);
+    }
+
     #[test]
     #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
     fn ensure_next_update_future_impls_send() {
@@ -287,6 +549,110 @@ mod tests {
             // We just want it to type-check, not actually run.
             fn typeck(_: impl Future + Send) {}
             typeck(get_client().next_update());
+            typeck(get_client().run_updates_by_chat(1, |_| async {}));
         }
     }
 }
+
+#[cfg(all(test, feature = "testing"))]
+mod mock_tests {
+    use super::*;
+    use crate::client::testing::fixtures::*;
+    use crate::client::testing::MockServer;
+    use tokio::sync::Notify;
+
+    /// Push an inline query from the user with the given identifier, used to order its handling.
+    fn push_inline_query(server: &MockServer, user_id: i64, text: &str) {
+        server.push_update(
+            tl::types::UpdateBotInlineQuery {
+                query_id: 0,
+                user_id,
+                query: text.to_string(),
+                geo: None,
+                peer_type: None,
+                offset: String::new(),
+            }
+            .into(),
+            vec![tl::types::UserEmpty { id: user_id }.into()],
+            Vec::new(),
+        );
+    }
+
+    /// Run the updates through two workers until `count` of them are handled, returning the text of
+    /// the handled inline queries in the order they finished.
+    fn run_inline_queries<F, Fut>(client: &Client, count: usize, handler: F) -> Vec<String>
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let (done_tx, mut done_rx) = mpsc::unbounded_channel();
+        let handler = Arc::new(handler);
+        let run = client.run_updates_by_chat(2, move |update| {
+            let handler = Arc::clone(&handler);
+            let done_tx = done_tx.clone();
+            async move {
+                let Update::InlineQuery(query) = update else {
+                    return;
+                };
+                let text = query.text().to_string();
+                handler(text.clone()).await;
+                done_tx.send(text).unwrap();
+            }
+        });
+        let done = async {
+            let mut done = Vec::new();
+            while done.len() < count {
+                done.push(done_rx.recv().await.unwrap());
+            }
+            done
+        };
+
+        block_on(async {
+            let run = pin!(run);
+            let done = pin!(tokio::time::timeout(Duration::from_secs(5), done));
+            match select(run, done).await {
+                Either::Left((result, _)) => panic!("updates stopped: {result:?}"),
+                Either::Right((done, _)) => done.expect("updates were not handled in time"),
+            }
+        })
+    }
+
+    #[test]
+    fn updates_are_handled_in_order_per_chat_and_concurrently_across_chats() {
+        let server = MockServer::new();
+        let client = client(&server);
+        // The users land in different workers.
+        push_inline_query(&server, 1, "a1");
+        push_inline_query(&server, 2, "b1");
+        push_inline_query(&server, 1, "a2");
+
+        // The first update of one chat can only finish once the other chat makes progress.
+        let release = Arc::new(Notify::new());
+        let done = run_inline_queries(&client, 3, move |text| {
+            let release = Arc::clone(&release);
+            async move {
+                match text.as_str() {
+                    "a1" => release.notified().await,
+                    "b1" => release.notify_one(),
+                    _ => {}
+                }
+            }
+        });
+        assert_eq!(done, ["b1", "a1", "a2"]);
+    }
+
+    #[test]
+    fn panicking_handlers_do_not_stop_the_worker() {
+        let server = MockServer::new();
+        let client = client(&server);
+        push_inline_query(&server, 1, "panic");
+        push_inline_query(&server, 1, "after");
+
+        let done = run_inline_queries(&client, 1, |text| async move {
+            if text == "panic" {
+                panic!("handler failed");
+            }
+        });
+        assert_eq!(done, ["after"]);
+    }
+}
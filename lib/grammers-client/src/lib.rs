@@ -52,10 +52,19 @@ pub(crate) mod utils;
 #[cfg(all(feature = "fs", target_arch = "wasm32", target_os = "unknown"))]
 compile_error!("The `fs` feature is not supported on wasm32-unknown-unknown.");
 
-pub use client::{Client, Config, InitParams, SignInError};
+#[cfg(feature = "testing")]
+pub use client::testing;
+pub use client::{
+    Client, Config, DcOption, DownloadError, InitParams, Rate, RateLimit, SignInError,
+    UpdateOverflowPolicy,
+};
 pub use types::{button, reply_markup, ChatMap, InputMedia, InputMessage, Update};
 
 pub use grammers_mtproto::transport;
-pub use grammers_mtsender::{FixedReconnect, InvocationError, NoReconnect, ReconnectionPolicy};
+pub use grammers_mtsender::{
+    buffer_pool, request_buffer_pool, BufferPool, ExponentialBackoff, FixedReconnect,
+    InvocationError, NoObserver, NoReconnect, ReconnectionPolicy, RequestHandle, RpcErrorKind,
+    SenderObserver, TransportKind,
+};
 pub use grammers_session as session;
 pub use grammers_tl_types;
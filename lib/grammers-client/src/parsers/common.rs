@@ -11,6 +11,7 @@ use std::cmp::Ordering;
 use std::fmt::{self, Write as _};
 
 pub const MENTION_URL_PREFIX: &str = "tg://user?id=";
+pub const EMOJI_URL_PREFIX: &str = "tg://emoji?id=";
 
 /// The length of a string, according to Telegram.
 ///
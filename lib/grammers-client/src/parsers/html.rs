@@ -72,7 +72,7 @@ pub fn parse_html_message(message: &str) -> (String, Vec<tl::enums::MessageEntit
                         entities.push(tl::types::MessageEntitySpoiler { offset, length }.into());
                     }
                     tag!("code") => {
-                        match entities.iter_mut().rev().next() {
+                        match entities.iter_mut().next_back() {
                             // If the previous tag is an open `<pre>`, don't add `<code>`;
                             // we most likely want to indicate `class="language-foo"`.
                             Some(tl::enums::MessageEntity::Pre(e)) if e.length == 0 => {
@@ -101,6 +101,23 @@ pub fn parse_html_message(message: &str) -> (String, Vec<tl::enums::MessageEntit
                             .into(),
                         );
                     }
+                    n if &*n == "emoji" || &*n == "tg-emoji" => {
+                        let document_id = attrs
+                            .into_iter()
+                            .find(|a| &*a.name.local == "id" || &*a.name.local == "emoji-id")
+                            .and_then(|a| a.value.parse::<i64>().ok());
+
+                        if let Some(document_id) = document_id {
+                            entities.push(
+                                tl::types::MessageEntityCustomEmoji {
+                                    offset,
+                                    length,
+                                    document_id,
+                                }
+                                .into(),
+                            );
+                        }
+                    }
                     tag!("a") => {
                         let url = attrs
                             .into_iter()
@@ -108,8 +125,8 @@ pub fn parse_html_message(message: &str) -> (String, Vec<tl::enums::MessageEntit
                             .map(|a| a.value.to_string())
                             .unwrap_or_else(|| "".to_string());
 
-                        if url.starts_with(MENTION_URL_PREFIX) {
-                            let user_id = url[MENTION_URL_PREFIX.len()..].parse::<i64>().unwrap();
+                        if let Some(user_id) = url.strip_prefix(MENTION_URL_PREFIX) {
+                            let user_id = user_id.parse::<i64>().unwrap();
                             entities.push(
                                 tl::types::MessageEntityMentionName {
                                     offset,
@@ -156,7 +173,7 @@ pub fn parse_html_message(message: &str) -> (String, Vec<tl::enums::MessageEntit
                         update_entity_len!(Spoiler(offset) in entities);
                     }
                     tag!("code") => {
-                        match entities.iter_mut().rev().next() {
+                        match entities.iter_mut().next_back() {
                             // If the previous tag is an open `<pre>`, don't update `<code>` len;
                             // we most likely want to indicate `class="language-foo"`.
                             Some(tl::enums::MessageEntity::Pre(e)) if e.length == 0 => {}
@@ -168,8 +185,11 @@ pub fn parse_html_message(message: &str) -> (String, Vec<tl::enums::MessageEntit
                     tag!("pre") => {
                         update_entity_len!(Pre(offset) in entities);
                     }
+                    n if &*n == "emoji" || &*n == "tg-emoji" => {
+                        update_entity_len!(CustomEmoji(offset) in entities);
+                    }
                     tag!("a") => {
-                        match entities.iter_mut().rev().next() {
+                        match entities.iter_mut().next_back() {
                             // If the previous url is a mention, don't close with `</a>`;
                             Some(tl::enums::MessageEntity::MentionName(_)) => {
                                 update_entity_len!(MentionName(offset) in entities);
@@ -196,7 +216,7 @@ pub fn parse_html_message(message: &str) -> (String, Vec<tl::enums::MessageEntit
         }
     }
 
-    let mut input = BufferQueue::default();
+    let input = BufferQueue::default();
     input.push_back(StrTendril::from_slice(message).try_reinterpret().unwrap());
 
     let tok = Tokenizer::new(
@@ -207,7 +227,7 @@ pub fn parse_html_message(message: &str) -> (String, Vec<tl::enums::MessageEntit
         },
         Default::default(),
     );
-    let _ = tok.feed(&mut input);
+    let _ = tok.feed(&input);
     tok.end();
 
     let Sink { text, entities, .. } = tok.sink;
@@ -240,6 +260,7 @@ pub fn generate_html_message(message: &str, entities: &[tl::enums::MessageEntity
                 ME::Strike(_) => 2,
                 ME::Blockquote(_) => 2,
                 ME::Spoiler(_) => 2,
+                ME::CustomEmoji(_) => 4,
                 _ => 0,
             })
             .sum(),
@@ -325,7 +346,12 @@ pub fn generate_html_message(message: &str, entities: &[tl::enums::MessageEntity
                     Segment::Fixed("</details>"),
                 ));
             }
-            ME::CustomEmoji(_) => {}
+            ME::CustomEmoji(e) => {
+                insertions.push((before(i, 0, e.offset), Segment::Fixed("<emoji id=\"")));
+                insertions.push((before(i, 1, e.offset), Segment::Number(e.document_id)));
+                insertions.push((before(i, 2, e.offset), Segment::Fixed("\">")));
+                insertions.push((after(i, 0, e.offset + e.length), Segment::Fixed("</emoji>")));
+            }
         });
 
     inject_into_message(message, insertions)
@@ -555,4 +581,44 @@ mod tests {
         let generated = generate_html_message(&text, &entities);
         assert_eq!(generated, markdown);
     }
+
+    #[test]
+    fn parse_custom_emoji() {
+        let (text, entities) =
+            parse_html_message("A <emoji id=\"5368324170671202286\">👍</emoji> custom emoji");
+        assert_eq!(text, "A 👍 custom emoji");
+        assert_eq!(
+            entities,
+            vec![tl::types::MessageEntityCustomEmoji {
+                offset: 2,
+                length: 2,
+                document_id: 5368324170671202286
+            }
+            .into()]
+        );
+    }
+
+    #[test]
+    fn parse_tg_emoji() {
+        let (text, entities) =
+            parse_html_message("<tg-emoji emoji-id=\"5368324170671202286\">👍</tg-emoji>");
+        assert_eq!(text, "👍");
+        assert_eq!(
+            entities,
+            vec![tl::types::MessageEntityCustomEmoji {
+                offset: 0,
+                length: 2,
+                document_id: 5368324170671202286
+            }
+            .into()]
+        );
+    }
+
+    #[test]
+    fn parse_then_unparse_custom_emoji() {
+        let message = "A <emoji id=\"5368324170671202286\">👍</emoji> custom emoji";
+        let (text, entities) = parse_html_message(message);
+        let generated = generate_html_message(&text, &entities);
+        assert_eq!(generated, message);
+    }
 }
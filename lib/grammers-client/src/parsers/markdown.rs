@@ -8,7 +8,8 @@
 #![cfg(feature = "markdown")]
 
 use super::common::{
-    after, before, inject_into_message, telegram_string_len, Segment, MENTION_URL_PREFIX,
+    after, before, inject_into_message, telegram_string_len, Segment, EMOJI_URL_PREFIX,
+    MENTION_URL_PREFIX,
 };
 use crate::update_entity_len;
 use grammers_tl_types as tl;
@@ -53,8 +54,8 @@ pub fn parse_markdown_message(message: &str) -> (String, Vec<tl::enums::MessageE
 
         // [text link](https://example.com) or [user mention](tg://user?id=12345678)
         Event::Start(Tag::Link { dest_url, .. }) => {
-            if dest_url.starts_with(MENTION_URL_PREFIX) {
-                let user_id = dest_url[MENTION_URL_PREFIX.len()..].parse::<i64>().unwrap();
+            if let Some(user_id) = dest_url.strip_prefix(MENTION_URL_PREFIX) {
+                let user_id = user_id.parse::<i64>().unwrap();
                 entities.push(
                     tl::types::MessageEntityMentionName {
                         offset,
@@ -92,6 +93,23 @@ pub fn parse_markdown_message(message: &str) -> (String, Vec<tl::enums::MessageE
             }
         }
 
+        // ![👍](tg://emoji?id=5368324170671202286)
+        Event::Start(Tag::Image { dest_url, .. }) if dest_url.starts_with(EMOJI_URL_PREFIX) => {
+            if let Ok(document_id) = dest_url[EMOJI_URL_PREFIX.len()..].parse::<i64>() {
+                entities.push(
+                    tl::types::MessageEntityCustomEmoji {
+                        offset,
+                        length,
+                        document_id,
+                    }
+                    .into(),
+                );
+            }
+        }
+        Event::End(TagEnd::Image) => {
+            update_entity_len!(CustomEmoji(offset) in entities);
+        }
+
         // ```lang\npre```
         Event::Start(Tag::CodeBlock(kind)) => {
             let lang = match kind {
@@ -148,6 +166,7 @@ pub fn generate_markdown_message(message: &str, entities: &[tl::enums::MessageEn
                 }
                 ME::TextUrl(_) => 4,
                 ME::MentionName(_) => 4,
+                ME::CustomEmoji(_) => 4,
                 _ => 0,
             })
             .sum(),
@@ -209,7 +228,18 @@ pub fn generate_markdown_message(message: &str, entities: &[tl::enums::MessageEn
             ME::Blockquote(_) => {}
             ME::BankCard(_) => {}
             ME::Spoiler(_) => {}
-            ME::CustomEmoji(_) => {}
+            ME::CustomEmoji(e) => {
+                insertions.push((before(i, 0, e.offset), Segment::Fixed("![")));
+                insertions.push((
+                    after(i, 0, e.offset + e.length),
+                    Segment::Fixed("](tg://emoji?id="),
+                ));
+                insertions.push((
+                    after(i, 1, e.offset + e.length),
+                    Segment::Number(e.document_id),
+                ));
+                insertions.push((after(i, 2, e.offset + e.length), Segment::Fixed(")")));
+            }
         });
 
     inject_into_message(message, insertions)
@@ -372,4 +402,28 @@ mod tests {
         let generated = generate_markdown_message(&text, &entities);
         assert_eq!(generated, markdown);
     }
+
+    #[test]
+    fn parse_custom_emoji() {
+        let (text, entities) =
+            parse_markdown_message("A ![👍](tg://emoji?id=5368324170671202286) custom emoji");
+        assert_eq!(text, "A 👍 custom emoji");
+        assert_eq!(
+            entities,
+            vec![tl::types::MessageEntityCustomEmoji {
+                offset: 2,
+                length: 2,
+                document_id: 5368324170671202286
+            }
+            .into()]
+        );
+    }
+
+    #[test]
+    fn parse_then_unparse_custom_emoji() {
+        let message = "A ![👍](tg://emoji?id=5368324170671202286) custom emoji";
+        let (text, entities) = parse_markdown_message(message);
+        let generated = generate_markdown_message(&text, &entities);
+        assert_eq!(generated, message);
+    }
 }
@@ -18,6 +18,59 @@ use crate::Client;
 
 const DEFAULT_REPEAT_DELAY: Duration = Duration::from_secs(4);
 
+/// The most common actions a chat member can be seen performing.
+///
+/// Converts into the raw [`SendMessageAction`], so it can be used anywhere an action is expected.
+/// Actions with progress report none, because most clients do not display it anyway.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChatAction {
+    Typing,
+    RecordVideo,
+    UploadVideo,
+    RecordVoice,
+    UploadVoice,
+    UploadPhoto,
+    UploadDocument,
+    ChooseLocation,
+    ChooseContact,
+    ChooseSticker,
+    PlayGame,
+    RecordRound,
+    UploadRound,
+}
+
+impl From<ChatAction> for SendMessageAction {
+    fn from(action: ChatAction) -> Self {
+        match action {
+            ChatAction::Typing => Self::SendMessageTypingAction,
+            ChatAction::RecordVideo => Self::SendMessageRecordVideoAction,
+            ChatAction::UploadVideo => {
+                tl::types::SendMessageUploadVideoAction { progress: 0 }.into()
+            }
+            ChatAction::RecordVoice => Self::SendMessageRecordAudioAction,
+            ChatAction::UploadVoice => {
+                tl::types::SendMessageUploadAudioAction { progress: 0 }.into()
+            }
+            ChatAction::UploadPhoto => {
+                tl::types::SendMessageUploadPhotoAction { progress: 0 }.into()
+            }
+            ChatAction::UploadDocument => {
+                tl::types::SendMessageUploadDocumentAction { progress: 0 }.into()
+            }
+            ChatAction::ChooseLocation => Self::SendMessageGeoLocationAction,
+            ChatAction::ChooseContact => Self::SendMessageChooseContactAction,
+            ChatAction::ChooseSticker => Self::SendMessageChooseStickerAction,
+            ChatAction::PlayGame => Self::SendMessageGamePlayAction,
+            ChatAction::RecordRound => Self::SendMessageRecordRoundAction,
+            ChatAction::UploadRound => {
+                tl::types::SendMessageUploadRoundAction { progress: 0 }.into()
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct ActionSender {
     client: Client,
     chat: PackedChat,
@@ -25,6 +78,18 @@ pub struct ActionSender {
     repeat_delay: Duration,
 }
 
+/// Keeps an action alive in the background.
+///
+/// The action is repeated until the guard is dropped, or until a message is sent through the same
+/// client to the chat and topic of the action, since Telegram clears the action once it arrives.
+#[must_use = "the action stops as soon as the guard is dropped"]
+pub struct ActionGuard {
+    client: Client,
+    chat: PackedChat,
+    topic_id: Option<i32>,
+    task: tokio::task::JoinHandle<()>,
+}
+
 impl ActionSender {
     pub fn new<C: Into<PackedChat>>(client: &Client, chat: C) -> Self {
         Self {
@@ -132,4 +197,121 @@ impl ActionSender {
 
         (future_output, request_result)
     }
+
+    /// Start repeating the action in the background until the returned guard is dropped
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::ChatAction;
+    ///
+    /// let _typing = client.action(&chat).start(ChatAction::Typing);
+    /// let text = "this took a while to write".to_string();
+    ///
+    /// // Sending the message stops the action.
+    /// client.send_message(&chat, text).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start<A: Into<SendMessageAction>>(&self, action: A) -> ActionGuard {
+        let sender = self.clone();
+        let action = action.into();
+        let mut stop = self.client.chat_action_stop(self.chat, self.topic_id);
+        let task = tokio::task::spawn(async move {
+            // Stop on the first error, as retrying is unlikely to make it succeed.
+            while sender.oneshot(action.clone()).await.is_ok() {
+                let delay = utils::sleep(sender.repeat_delay);
+                let stopped = stop.changed();
+                tokio::pin!(delay);
+                tokio::pin!(stopped);
+
+                if let Either::Right(_) = futures_util::future::select(delay, stopped).await {
+                    break;
+                }
+            }
+        });
+
+        ActionGuard {
+            client: self.client.clone(),
+            chat: self.chat,
+            topic_id: self.topic_id,
+            task,
+        }
+    }
+}
+
+impl ActionGuard {
+    /// Stop repeating the action and immediately clear it, instead of letting it fade away
+    pub async fn cancel(self) -> Result<(), InvocationError> {
+        self.task.abort();
+        self.client
+            .invoke(&tl::functions::messages::SetTyping {
+                peer: self.chat.to_input_peer(),
+                top_msg_id: self.topic_id,
+                action: SendMessageAction::SendMessageCancelAction,
+            })
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl Drop for ActionGuard {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod mock_tests {
+    use super::*;
+    use crate::client::testing::fixtures::*;
+    use crate::client::testing::MockServer;
+    use grammers_session::PackedType;
+
+    const REPEAT_DELAY: Duration = Duration::from_millis(10);
+
+    #[test]
+    fn actions_stop_once_a_message_is_sent() {
+        let server = MockServer::new();
+        let client = client(&server);
+        let chat = PackedChat {
+            ty: PackedType::User,
+            id: 1,
+            access_hash: Some(0),
+        };
+        for _ in 0..100 {
+            server.respond::<tl::functions::messages::SetTyping>(true);
+        }
+        server.respond::<tl::functions::messages::SendMessage>(
+            tl::types::UpdateShortSentMessage {
+                out: true,
+                id: 1,
+                pts: 1,
+                pts_count: 1,
+                date: 0,
+                media: None,
+                entities: None,
+                ttl_period: None,
+            }
+            .into(),
+        );
+
+        block_on(async {
+            let action = client.action(chat).repeat_delay(REPEAT_DELAY);
+            let _typing = action.start(ChatAction::Typing);
+            let _topic_typing = action.topic_id(5).start(ChatAction::Typing);
+            utils::sleep(REPEAT_DELAY * 5).await;
+
+            client.send_message(chat, "done").await.unwrap();
+            server.take_requests::<tl::functions::messages::SetTyping>();
+            utils::sleep(REPEAT_DELAY * 5).await;
+        });
+
+        // Only the action in the topic the message was not sent to is still renewed.
+        let renewed = server.take_requests::<tl::functions::messages::SetTyping>();
+        assert!(!renewed.is_empty());
+        assert!(renewed.iter().all(|request| request.top_msg_id == Some(5)));
+    }
 }
@@ -0,0 +1,342 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{Chat, ChatMap, InviteLink, Message, Participant, Restrictions, User};
+use crate::{utils, Client};
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+use std::sync::Arc;
+
+/// Which kinds of events should be returned when iterating over the admin log of a channel.
+///
+/// By default, no kind is selected, which returns every event.
+///
+/// # Examples
+///
+/// ```
+/// use grammers_client::types::AdminLogFilter;
+///
+/// let moderation = AdminLogFilter::new().ban(true).kick(true).delete(true);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdminLogFilter {
+    raw: tl::types::ChannelAdminLogEventsFilter,
+}
+
+/// A typed action performed in a channel, as recorded in its admin log.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+#[allow(clippy::large_enum_variant)]
+pub enum AdminLogAction {
+    /// A user joined the channel.
+    Join,
+    /// A user left the channel.
+    Leave,
+    /// A user joined the channel through an invite link, if it's known.
+    JoinByInvite(Option<InviteLink>),
+    /// A user was invited to the channel.
+    Invite(Participant),
+    /// The title of the channel was changed.
+    ChangeTitle { old: String, new: String },
+    /// The description of the channel was changed.
+    ChangeAbout { old: String, new: String },
+    /// A message was sent.
+    Send(Message),
+    /// A message was edited.
+    Edit { old: Message, new: Message },
+    /// A message was deleted.
+    Delete(Message),
+    /// A message was pinned or unpinned.
+    Pin(Message),
+    /// A user was banned, restricted, removed, or had these lifted.
+    Ban { old: Participant, new: Participant },
+    /// A user was promoted to administrator, demoted, or had their rights changed.
+    Promote { old: Participant, new: Participant },
+    /// The default permissions of the participants were changed.
+    DefaultPermissions {
+        old: Restrictions,
+        new: Restrictions,
+    },
+    /// Any other action, in its raw form.
+    Other(tl::enums::ChannelAdminLogEventAction),
+}
+
+/// An event in the admin log of a channel.
+#[derive(Clone, Debug)]
+pub struct AdminLogEvent {
+    pub raw: tl::types::ChannelAdminLogEvent,
+    user: Option<User>,
+    action: AdminLogAction,
+}
+
+impl Default for AdminLogFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdminLogFilter {
+    /// Create a new filter with no kind selected.
+    pub fn new() -> Self {
+        Self {
+            raw: tl::types::ChannelAdminLogEventsFilter {
+                join: false,
+                leave: false,
+                invite: false,
+                ban: false,
+                unban: false,
+                kick: false,
+                unkick: false,
+                promote: false,
+                demote: false,
+                info: false,
+                settings: false,
+                pinned: false,
+                edit: false,
+                delete: false,
+                group_call: false,
+                invites: false,
+                send: false,
+                forums: false,
+            },
+        }
+    }
+
+    /// Whether to include users joining the channel.
+    pub fn join(mut self, val: bool) -> Self {
+        self.raw.join = val;
+        self
+    }
+
+    /// Whether to include users leaving the channel.
+    pub fn leave(mut self, val: bool) -> Self {
+        self.raw.leave = val;
+        self
+    }
+
+    /// Whether to include users being invited to the channel.
+    pub fn invite(mut self, val: bool) -> Self {
+        self.raw.invite = val;
+        self
+    }
+
+    /// Whether to include users being banned or restricted.
+    pub fn ban(mut self, val: bool) -> Self {
+        self.raw.ban = val;
+        self
+    }
+
+    /// Whether to include users being unbanned or having their restrictions lifted.
+    pub fn unban(mut self, val: bool) -> Self {
+        self.raw.unban = val;
+        self
+    }
+
+    /// Whether to include users being removed from the channel.
+    pub fn kick(mut self, val: bool) -> Self {
+        self.raw.kick = val;
+        self
+    }
+
+    /// Whether to include users being allowed to join the channel again.
+    pub fn unkick(mut self, val: bool) -> Self {
+        self.raw.unkick = val;
+        self
+    }
+
+    /// Whether to include users being promoted to administrator.
+    pub fn promote(mut self, val: bool) -> Self {
+        self.raw.promote = val;
+        self
+    }
+
+    /// Whether to include administrators being demoted.
+    pub fn demote(mut self, val: bool) -> Self {
+        self.raw.demote = val;
+        self
+    }
+
+    /// Whether to include changes to the title, description, photo or username.
+    pub fn info(mut self, val: bool) -> Self {
+        self.raw.info = val;
+        self
+    }
+
+    /// Whether to include changes to the settings of the channel.
+    pub fn settings(mut self, val: bool) -> Self {
+        self.raw.settings = val;
+        self
+    }
+
+    /// Whether to include messages being pinned or unpinned.
+    pub fn pinned(mut self, val: bool) -> Self {
+        self.raw.pinned = val;
+        self
+    }
+
+    /// Whether to include messages being edited.
+    pub fn edit(mut self, val: bool) -> Self {
+        self.raw.edit = val;
+        self
+    }
+
+    /// Whether to include messages being deleted.
+    pub fn delete(mut self, val: bool) -> Self {
+        self.raw.delete = val;
+        self
+    }
+
+    /// Whether to include group calls being started, ended or changed.
+    pub fn group_call(mut self, val: bool) -> Self {
+        self.raw.group_call = val;
+        self
+    }
+
+    /// Whether to include invite links being created, edited or revoked.
+    pub fn invites(mut self, val: bool) -> Self {
+        self.raw.invites = val;
+        self
+    }
+
+    /// Whether to include messages being sent.
+    pub fn send(mut self, val: bool) -> Self {
+        self.raw.send = val;
+        self
+    }
+
+    /// Whether to include forum topics being created, edited or deleted.
+    pub fn forums(mut self, val: bool) -> Self {
+        self.raw.forums = val;
+        self
+    }
+
+    pub(crate) fn to_raw(&self) -> Option<tl::enums::ChannelAdminLogEventsFilter> {
+        if *self == Self::new() {
+            None
+        } else {
+            Some(self.raw.clone().into())
+        }
+    }
+}
+
+fn participant(
+    chats: &ChatMap,
+    participant: &tl::enums::ChannelParticipant,
+) -> Option<Participant> {
+    use tl::enums::ChannelParticipant as P;
+
+    let peer = match participant {
+        P::Participant(p) => tl::types::PeerUser { user_id: p.user_id }.into(),
+        P::ParticipantSelf(p) => tl::types::PeerUser { user_id: p.user_id }.into(),
+        P::Creator(p) => tl::types::PeerUser { user_id: p.user_id }.into(),
+        P::Admin(p) => tl::types::PeerUser { user_id: p.user_id }.into(),
+        P::Banned(p) => p.peer.clone(),
+        P::Left(p) => p.peer.clone(),
+    };
+    // Converting a participant consumes its user from the map, but the same user may appear in
+    // several events, so give it a map of its own.
+    let mut map = ChatMap::single(match chats.get(&peer)? {
+        Chat::User(user) => Chat::User(user.clone()),
+        _ => return None,
+    });
+    Some(Participant::from_raw_channel(
+        Arc::get_mut(&mut map).unwrap(),
+        participant.clone(),
+    ))
+}
+
+impl AdminLogAction {
+    fn from_raw(
+        client: &Client,
+        action: tl::enums::ChannelAdminLogEventAction,
+        chats: &Arc<ChatMap>,
+    ) -> Self {
+        use tl::enums::ChannelAdminLogEventAction as A;
+
+        let message =
+            |message: &tl::enums::Message| Message::from_raw(client, message.clone(), chats);
+        let typed = match &action {
+            A::ParticipantJoin => Some(Self::Join),
+            A::ParticipantLeave => Some(Self::Leave),
+            A::ParticipantJoinByInvite(a) => {
+                Some(Self::JoinByInvite(InviteLink::from_raw(a.invite.clone())))
+            }
+            A::ParticipantInvite(a) => participant(chats, &a.participant).map(Self::Invite),
+            A::ChangeTitle(a) => Some(Self::ChangeTitle {
+                old: a.prev_value.clone(),
+                new: a.new_value.clone(),
+            }),
+            A::ChangeAbout(a) => Some(Self::ChangeAbout {
+                old: a.prev_value.clone(),
+                new: a.new_value.clone(),
+            }),
+            A::SendMessage(a) => message(&a.message).map(Self::Send),
+            A::EditMessage(a) => message(&a.prev_message)
+                .zip(message(&a.new_message))
+                .map(|(old, new)| Self::Edit { old, new }),
+            A::DeleteMessage(a) => message(&a.message).map(Self::Delete),
+            A::UpdatePinned(a) => message(&a.message).map(Self::Pin),
+            A::ParticipantToggleBan(a) => participant(chats, &a.prev_participant)
+                .zip(participant(chats, &a.new_participant))
+                .map(|(old, new)| Self::Ban { old, new }),
+            A::ParticipantToggleAdmin(a) => participant(chats, &a.prev_participant)
+                .zip(participant(chats, &a.new_participant))
+                .map(|(old, new)| Self::Promote { old, new }),
+            A::DefaultBannedRights(a) => Some(Self::DefaultPermissions {
+                old: Restrictions::from_raw(a.prev_banned_rights.clone().into()),
+                new: Restrictions::from_raw(a.new_banned_rights.clone().into()),
+            }),
+            _ => None,
+        };
+
+        typed.unwrap_or(Self::Other(action))
+    }
+}
+
+impl AdminLogEvent {
+    pub(crate) fn from_raw(
+        client: &Client,
+        event: tl::enums::ChannelAdminLogEvent,
+        chats: &Arc<ChatMap>,
+    ) -> Self {
+        let tl::enums::ChannelAdminLogEvent::Event(raw) = event;
+        let user = match chats.get(
+            &tl::types::PeerUser {
+                user_id: raw.user_id,
+            }
+            .into(),
+        ) {
+            Some(Chat::User(user)) => Some(user.clone()),
+            _ => None,
+        };
+        Self {
+            action: AdminLogAction::from_raw(client, raw.action.clone(), chats),
+            user,
+            raw,
+        }
+    }
+
+    /// Unique identifier of the event.
+    pub fn id(&self) -> i64 {
+        self.raw.id
+    }
+
+    /// The date when the action was performed.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// The user who performed the action, if it was included in the response.
+    pub fn user(&self) -> Option<&User> {
+        self.user.as_ref()
+    }
+
+    /// The action that was performed.
+    pub fn action(&self) -> &AdminLogAction {
+        &self.action
+    }
+}
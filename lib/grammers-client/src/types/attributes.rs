@@ -49,7 +49,7 @@ impl From<Attribute> for tl::enums::DocumentAttribute {
                 duration: duration.as_secs().try_into().unwrap(),
                 title: None,
                 performer: None,
-                waveform,
+                waveform: waveform.map(tl::byte_string),
             }),
             Video {
                 round_message,
@@ -0,0 +1,110 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::types::User;
+use crate::utils;
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+
+/// The boost level of a channel, and the progress towards the next one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoostStatus {
+    pub raw: tl::types::premium::BoostsStatus,
+}
+
+impl BoostStatus {
+    pub(crate) fn from_raw(status: tl::enums::premium::BoostsStatus) -> Self {
+        let tl::enums::premium::BoostsStatus::Status(raw) = status;
+        Self { raw }
+    }
+
+    /// The current boost level of the channel.
+    pub fn level(&self) -> i32 {
+        self.raw.level
+    }
+
+    /// The total number of boosts the channel has received.
+    pub fn boosts(&self) -> i32 {
+        self.raw.boosts
+    }
+
+    /// The number of boosts that were needed to reach the current level.
+    pub fn current_level_boosts(&self) -> i32 {
+        self.raw.current_level_boosts
+    }
+
+    /// The number of boosts needed to reach the next level, if there is one.
+    pub fn next_level_boosts(&self) -> Option<i32> {
+        self.raw.next_level_boosts
+    }
+
+    /// The link users can open to boost the channel.
+    pub fn boost_url(&self) -> &str {
+        &self.raw.boost_url
+    }
+
+    /// Whether the logged-in user is boosting the channel.
+    pub fn is_boosted_by_me(&self) -> bool {
+        self.raw.my_boost
+    }
+
+    /// The boost slots of the logged-in user which are used to boost the channel.
+    pub fn my_boost_slots(&self) -> &[i32] {
+        self.raw.my_boost_slots.as_deref().unwrap_or_default()
+    }
+}
+
+/// A boost applied to a channel, either by a Premium user or through a gift code.
+#[derive(Clone, Debug)]
+pub struct Boost {
+    pub raw: tl::types::Boost,
+    user: Option<User>,
+}
+
+impl Boost {
+    pub(crate) fn from_raw(boost: tl::enums::Boost, user: Option<User>) -> Self {
+        let tl::enums::Boost::Boost(raw) = boost;
+        Self { raw, user }
+    }
+
+    /// The identifier of the boost.
+    pub fn id(&self) -> &str {
+        &self.raw.id
+    }
+
+    /// The user who applied the boost, if known.
+    ///
+    /// Unclaimed boosts from gifts and giveaways have no user.
+    pub fn user(&self) -> Option<&User> {
+        self.user.as_ref()
+    }
+
+    /// Whether the boost comes from a gift code.
+    pub fn is_gift(&self) -> bool {
+        self.raw.gift
+    }
+
+    /// Whether the boost comes from a giveaway.
+    pub fn is_giveaway(&self) -> bool {
+        self.raw.giveaway
+    }
+
+    /// How many boosts this entry counts as.
+    pub fn multiplier(&self) -> i32 {
+        self.raw.multiplier.unwrap_or(1)
+    }
+
+    /// When the boost was applied.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// When the boost expires.
+    pub fn expires(&self) -> DateTime<Utc> {
+        utils::date(self.raw.expires)
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+/// A command shown in the menu of a bot, which users can select to send it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BotCommand {
+    /// Text of the command without the leading slash, such as `start`.
+    ///
+    /// At most 32 characters long, and can only contain lowercase letters, digits and
+    /// underscores.
+    pub command: String,
+    /// Description of the command, shown next to it in the menu.
+    pub description: String,
+}
+
+/// The users and chats for which a set of bot commands applies.
+///
+/// When looking up the commands to show, Telegram picks the most specific scope available,
+/// starting by [`BotCommandScope::ChatMember`] and ending with [`BotCommandScope::Default`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BotCommandScope {
+    /// Applies if no commands were set for a narrower scope.
+    #[default]
+    Default,
+    /// Applies to all private chats.
+    AllPrivateChats,
+    /// Applies to all group and supergroup chats.
+    AllGroupChats,
+    /// Applies to all administrators in group and supergroup chats.
+    AllChatAdministrators,
+    /// Applies to a specific chat.
+    Chat(PackedChat),
+    /// Applies to all administrators of a specific group or supergroup.
+    ChatAdministrators(PackedChat),
+    /// Applies to a specific member of a group or supergroup.
+    ChatMember(PackedChat, PackedChat),
+}
+
+impl BotCommand {
+    pub fn new<C: Into<String>, D: Into<String>>(command: C, description: D) -> Self {
+        Self {
+            command: command.into(),
+            description: description.into(),
+        }
+    }
+
+    pub fn from_raw(command: tl::enums::BotCommand) -> Self {
+        let tl::enums::BotCommand::Command(command) = command;
+        Self {
+            command: command.command,
+            description: command.description,
+        }
+    }
+
+    pub fn to_raw(&self) -> tl::enums::BotCommand {
+        tl::types::BotCommand {
+            command: self.command.clone(),
+            description: self.description.clone(),
+        }
+        .into()
+    }
+}
+
+impl BotCommandScope {
+    pub fn to_raw(&self) -> tl::enums::BotCommandScope {
+        use tl::enums::BotCommandScope as S;
+
+        match self {
+            Self::Default => S::Default,
+            Self::AllPrivateChats => S::Users,
+            Self::AllGroupChats => S::Chats,
+            Self::AllChatAdministrators => S::ChatAdmins,
+            Self::Chat(chat) => tl::types::BotCommandScopePeer {
+                peer: chat.to_input_peer(),
+            }
+            .into(),
+            Self::ChatAdministrators(chat) => tl::types::BotCommandScopePeerAdmins {
+                peer: chat.to_input_peer(),
+            }
+            .into(),
+            Self::ChatMember(chat, user) => tl::types::BotCommandScopePeerUser {
+                peer: chat.to_input_peer(),
+                user_id: user.to_input_user_lossy(),
+            }
+            .into(),
+        }
+    }
+}
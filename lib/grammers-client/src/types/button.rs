@@ -13,6 +13,9 @@ use grammers_tl_types as tl;
 /// * [`switch_inline`]
 /// * [`switch_inline_elsewhere`]
 /// * [`url`](url())
+/// * [`webview`]
+/// * [`game`]
+/// * [`buy`]
 pub struct Inline {
     pub raw: tl::enums::KeyboardButton,
 }
@@ -24,6 +27,7 @@ pub struct Inline {
 /// * [`request_geo`]
 /// * [`request_poll`]
 /// * [`request_quiz`]
+/// * [`simple_webview`]
 pub struct Keyboard {
     pub raw: tl::enums::KeyboardButton,
 }
@@ -41,7 +45,7 @@ pub fn inline<T: Into<String>, B: Into<Vec<u8>>>(text: T, bytes: B) -> Inline {
     Inline {
         raw: tl::types::KeyboardButtonCallback {
             text: text.into(),
-            data: bytes.into(),
+            data: tl::byte_string(bytes.into()),
             requires_password: false,
         }
         .into(),
@@ -106,6 +110,26 @@ pub fn webview<T: Into<String>, U: Into<String>>(text: T, url: U) -> Inline {
     }
 }
 
+/// An inline button that will launch the game the message was sent with.
+///
+/// This must always be the first button of the first row, and can only be used in messages
+/// containing a game.
+pub fn game<T: Into<String>>(text: T) -> Inline {
+    Inline {
+        raw: tl::types::KeyboardButtonGame { text: text.into() }.into(),
+    }
+}
+
+/// An inline button that will allow the user to pay for the invoice the message was sent with.
+///
+/// This must always be the first button of the first row, and can only be used in messages
+/// containing an invoice.
+pub fn buy<T: Into<String>>(text: T) -> Inline {
+    Inline {
+        raw: tl::types::KeyboardButtonBuy { text: text.into() }.into(),
+    }
+}
+
 /// A keyboard button in its simplest form.
 ///
 /// When pressed, the button's text will be sent as a normal message, as if the user had typed it.
@@ -155,11 +179,23 @@ pub fn request_quiz<T: Into<String>>(text: T) -> Keyboard {
     }
 }
 
+/// A keyboard button that when pressed will open the specified URL in an in-app browser.
+///
+/// Unlike [`webview`], the web app opened this way cannot send data back to the bot through
+/// the message.
+pub fn simple_webview<T: Into<String>, U: Into<String>>(text: T, url: U) -> Keyboard {
+    Keyboard {
+        raw: tl::types::KeyboardButtonSimpleWebView {
+            text: text.into(),
+            url: url.into(),
+        }
+        .into(),
+    }
+}
+
 /*
 TODO implement other buttons
 (with password) keyboardButtonCallback#35bbdb6b flags:# requires_password:flags.0?true text:string data:bytes = KeyboardButton;
 keyboardButtonUrlAuth#10b78d29 flags:# text:string fwd_text:flags.0?string url:string button_id:int = KeyboardButton;
-keyboardButtonGame#50f41ccf text:string = KeyboardButton;
-keyboardButtonBuy#afd93fbb text:string = KeyboardButton;
 inputKeyboardButtonUrlAuth#d02e7fd4 flags:# request_write_access:flags.0?true text:string fwd_text:flags.1?string url:string bot:InputUser = KeyboardButton;
 */
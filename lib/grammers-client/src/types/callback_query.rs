@@ -5,7 +5,7 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-use crate::{types, Client, InputMessage};
+use crate::{types, utils, Client, InputMessage};
 use grammers_mtsender::InvocationError;
 use grammers_tl_types as tl;
 use std::convert::TryInto;
@@ -97,8 +97,23 @@ impl CallbackQuery {
     ///
     /// > Trivia: it used to be possible to fake the callback data, but a server-side check was
     /// > added circa 2018 to prevent malicious clients from doing so.
+    ///
+    /// Callback queries produced by game buttons carry no data, in which case this slice will be
+    /// empty. Use [`CallbackQuery::game_short_name`] to tell those apart.
     pub fn data(&self) -> &[u8] {
-        self.raw.data.as_deref().unwrap()
+        self.raw.data.as_deref().unwrap_or_default()
+    }
+
+    /// The short name of the game, if the pressed button was a game button.
+    pub fn game_short_name(&self) -> Option<&str> {
+        self.raw.game_short_name.as_deref()
+    }
+
+    /// Whether the query comes from the button to play a game, rather than a data button.
+    ///
+    /// Such queries should be answered with the [`Answer::url`] of the game.
+    pub fn is_game(&self) -> bool {
+        self.raw.game_short_name.is_some()
     }
 
     /// Whether the callback query was generated from an inline message.
@@ -107,18 +122,27 @@ impl CallbackQuery {
     }
 
     /// Load the `Message` that contains the pressed inline button.
+    ///
+    /// Messages sent via inline mode do not belong to any chat the bot can fetch from, so this
+    /// will fail with [`InvocationError::InvalidInput`] if [`CallbackQuery::is_from_inline`].
+    /// If the message no longer exists, the error reports the empty message Telegram returned.
     pub async fn load_message(&self) -> Result<types::Message, InvocationError> {
-        Ok(self
-            .client
+        if self.is_from_inline() {
+            return Err(InvocationError::InvalidInput(
+                "messages sent via inline mode cannot be loaded",
+            ));
+        }
+
+        self.client
             .get_messages_by_id(self.chat(), &[self.raw.msg_id])
             .await?
             .pop()
-            .unwrap()
-            .unwrap())
+            .flatten()
+            .ok_or_else(utils::unexpected_response::<tl::types::MessageEmpty>)
     }
 
     /// Answer the callback query.
-    pub fn answer(&self) -> Answer {
+    pub fn answer(&self) -> Answer<'_> {
         Answer {
             request: tl::functions::messages::SetBotCallbackAnswer {
                 alert: false,
@@ -161,6 +185,15 @@ impl<'a> Answer<'a> {
         self
     }
 
+    /// Configure an URL to be opened by the user's client.
+    ///
+    /// This is only allowed for game buttons, in which case the URL should point to the game, or
+    /// for `t.me/your_bot?start=xyz` links that open a conversation with the bot.
+    pub fn url<U: Into<String>>(mut self, url: U) -> Self {
+        self.request.url = Some(url.into());
+        self
+    }
+
     /// Send the answer back to Telegram, and then relayed to the user who pressed the inline
     /// button.
     pub async fn send(self) -> Result<(), InvocationError> {
@@ -158,6 +158,35 @@ impl Channel {
         }
     }
 
+    /// Does this channel's photo have an animated video version?
+    pub fn photo_has_video(&self) -> bool {
+        self.photo().is_some_and(|photo| photo.has_video)
+    }
+
+    /// Return the accent color of this channel, if any.
+    ///
+    /// This is the color used for the name and the replies, along with the ID of the custom
+    /// emoji used as the background pattern.
+    pub fn color(&self) -> Option<&tl::types::PeerColor> {
+        self.raw
+            .color
+            .as_ref()
+            .map(|tl::enums::PeerColor::Color(color)| color)
+    }
+
+    /// Return the color and background emoji used in the profile page of this channel, if any.
+    pub fn profile_color(&self) -> Option<&tl::types::PeerColor> {
+        self.raw
+            .profile_color
+            .as_ref()
+            .map(|tl::enums::PeerColor::Color(color)| color)
+    }
+
+    /// Return the boost level of this channel, if known.
+    pub fn boost_level(&self) -> Option<i32> {
+        self.raw.level
+    }
+
     /// Return the permissions of the logged-in user in this channel.
     pub fn admin_rights(&self) -> Option<&tl::types::ChatAdminRights> {
         match &self.raw.admin_rights {
@@ -169,6 +169,63 @@ impl Group {
         }
     }
 
+    /// Does this group's photo have an animated video version?
+    pub fn photo_has_video(&self) -> bool {
+        self.photo().is_some_and(|photo| photo.has_video)
+    }
+
+    /// Return the accent color of this group, if any.
+    ///
+    /// Only megagroups can have an accent color.
+    pub fn color(&self) -> Option<&tl::types::PeerColor> {
+        match &self.raw {
+            tl::enums::Chat::Channel(channel) => channel
+                .color
+                .as_ref()
+                .map(|tl::enums::PeerColor::Color(color)| color),
+            _ => None,
+        }
+    }
+
+    /// Return the color and background emoji used in the profile page of this group, if any.
+    ///
+    /// Only megagroups can have a profile color.
+    pub fn profile_color(&self) -> Option<&tl::types::PeerColor> {
+        match &self.raw {
+            tl::enums::Chat::Channel(channel) => channel
+                .profile_color
+                .as_ref()
+                .map(|tl::enums::PeerColor::Color(color)| color),
+            _ => None,
+        }
+    }
+
+    /// Return the boost level of this group, if it is a megagroup and the level is known.
+    pub fn boost_level(&self) -> Option<i32> {
+        match &self.raw {
+            tl::enums::Chat::Channel(channel) => channel.level,
+            _ => None,
+        }
+    }
+
+    /// Return the supergroup this group was migrated to, if it was upgraded.
+    ///
+    /// Once a small group is migrated, it can no longer be used, and the returned supergroup
+    /// should be used instead.
+    pub fn migrated_to(&self) -> Option<PackedChat> {
+        match &self.raw {
+            tl::enums::Chat::Chat(chat) => match &chat.migrated_to {
+                Some(tl::enums::InputChannel::Channel(channel)) => Some(PackedChat {
+                    ty: PackedType::Megagroup,
+                    id: channel.channel_id,
+                    access_hash: Some(channel.access_hash),
+                }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     /// Returns true if this group is a megagroup (also known as supergroups).
     ///
     /// In case inner type of group is Channel, that means it's a megagroup.
@@ -172,6 +172,55 @@ impl Chat {
         }
     }
 
+    /// Return the accent color of this chat, if any.
+    ///
+    /// The color is used for the chat's name and replies, and the custom emoji (if any) as the
+    /// background pattern behind them.
+    pub fn color(&self) -> Option<&tl::types::PeerColor> {
+        match self {
+            Self::User(user) => user.color(),
+            Self::Group(group) => group.color(),
+            Self::Channel(channel) => channel.color(),
+        }
+    }
+
+    /// Return the color and background emoji used in the profile page of this chat, if any.
+    pub fn profile_color(&self) -> Option<&tl::types::PeerColor> {
+        match self {
+            Self::User(user) => user.profile_color(),
+            Self::Group(group) => group.profile_color(),
+            Self::Channel(channel) => channel.profile_color(),
+        }
+    }
+
+    /// Return the boost level of this chat, if it is a channel or megagroup and the level is
+    /// known.
+    pub fn boost_level(&self) -> Option<i32> {
+        match self {
+            Self::User(_) => None,
+            Self::Group(group) => group.boost_level(),
+            Self::Channel(channel) => channel.boost_level(),
+        }
+    }
+
+    /// Return the supergroup this chat was migrated to, if it was a small group that was
+    /// upgraded.
+    pub fn migrated_to(&self) -> Option<PackedChat> {
+        match self {
+            Self::Group(group) => group.migrated_to(),
+            Self::User(_) | Self::Channel(_) => None,
+        }
+    }
+
+    /// Does this chat's photo have an animated video version?
+    pub fn photo_has_video(&self) -> bool {
+        match self {
+            Self::User(user) => user.photo_has_video(),
+            Self::Group(group) => group.photo_has_video(),
+            Self::Channel(channel) => channel.photo_has_video(),
+        }
+    }
+
     // If `Self` has `min` `access_hash`, returns a mutable reference to both `min` and `access_hash`.
     //
     // This serves as a way of checking "is it min?" and "update the access hash" both in one.
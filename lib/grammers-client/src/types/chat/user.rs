@@ -236,6 +236,30 @@ impl User {
         }
     }
 
+    /// Does this user's profile photo have an animated video version?
+    pub fn photo_has_video(&self) -> bool {
+        self.photo().is_some_and(|photo| photo.has_video)
+    }
+
+    /// Return the accent color of this user, if any.
+    ///
+    /// This is the color used for the name and the replies, along with the ID of the custom
+    /// emoji used as the background pattern.
+    pub fn color(&self) -> Option<&tl::types::PeerColor> {
+        self.raw
+            .color
+            .as_ref()
+            .map(|tl::enums::PeerColor::Color(color)| color)
+    }
+
+    /// Return the color and background emoji used in the profile page of this user, if any.
+    pub fn profile_color(&self) -> Option<&tl::types::PeerColor> {
+        self.raw
+            .profile_color
+            .as_ref()
+            .map(|tl::enums::PeerColor::Color(color)| color)
+    }
+
     /// Does this user represent the account that's currently logged in?
     pub fn is_self(&self) -> bool {
         // TODO if is_self is false, check in chat cache if id == ourself
@@ -306,6 +330,11 @@ impl User {
         self.raw.support
     }
 
+    /// Does this user have a Telegram Premium subscription?
+    pub fn is_premium(&self) -> bool {
+        self.raw.premium
+    }
+
     /// Has this user been flagged for trying to scam other people?
     pub fn scam(&self) -> bool {
         self.raw.scam
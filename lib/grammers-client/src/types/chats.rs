@@ -6,7 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 use crate::types::Role;
-use crate::Client;
+use crate::{utils, Client};
+use chrono::{DateTime, Utc};
 use grammers_mtsender::{InvocationError, RpcError};
 use grammers_session::PackedChat;
 use grammers_tl_types as tl;
@@ -297,6 +298,13 @@ impl<F: Future<Output = BuilderRes>> AdminRightsBuilder<F> {
         self.inner_mut().rank = val.into();
         self
     }
+
+    pub(crate) fn rights(mut self, rights: &AdminRights) -> Self {
+        let s = self.inner_mut();
+        s.rights = rights.raw.clone();
+        s.rank = rights.rank.clone();
+        self
+    }
 }
 
 type BannedFutGen<F> = fn(BannedRightsBuilderInner) -> F;
@@ -531,6 +539,11 @@ impl<F: Future<Output = BuilderRes>> BannedRightsBuilder<F> {
         self
     }
 
+    pub(crate) fn rights(mut self, permissions: &ChatPermissions) -> Self {
+        self.inner_mut().rights = permissions.raw.clone();
+        self
+    }
+
     /// Apply the restrictions until the given epoch time.
     ///
     /// Note that this is absolute time (i.e current time is not added).
@@ -554,3 +567,290 @@ impl<F: Future<Output = BuilderRes>> BannedRightsBuilder<F> {
         self
     }
 }
+
+/// The rights of a non-admin user in a chat, to be applied with [`Client::restrict_member`].
+///
+/// Unlike [`BannedRightsBuilder`], this value does not perform any request on its own, so it can
+/// be built once and reused for many users. Every permission is granted by default.
+///
+/// # Examples
+///
+/// ```
+/// use grammers_client::types::ChatPermissions;
+///
+/// let read_only = ChatPermissions::new().send_messages(false).send_media(false);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatPermissions {
+    raw: tl::types::ChatBannedRights,
+}
+
+impl Default for ChatPermissions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChatPermissions {
+    /// Create a new set of permissions where everything is allowed.
+    pub fn new() -> Self {
+        Self {
+            raw: tl::types::ChatBannedRights {
+                view_messages: false,
+                send_messages: false,
+                send_media: false,
+                send_stickers: false,
+                send_gifs: false,
+                send_games: false,
+                send_inline: false,
+                embed_links: false,
+                send_polls: false,
+                change_info: false,
+                invite_users: false,
+                pin_messages: false,
+                manage_topics: false,
+                send_photos: false,
+                send_videos: false,
+                send_roundvideos: false,
+                send_audios: false,
+                send_voices: false,
+                send_docs: false,
+                send_plain: false,
+                until_date: 0,
+            },
+        }
+    }
+
+    /// Whether the user will be able to view messages. Taking this permission away effectively
+    /// bans them.
+    pub fn view_messages(mut self, val: bool) -> Self {
+        self.raw.view_messages = !val;
+        self
+    }
+
+    /// Whether the user will be able to send text messages.
+    pub fn send_messages(mut self, val: bool) -> Self {
+        self.raw.send_messages = !val;
+        self
+    }
+
+    /// Whether the user will be able to send any form of media, such as photos or voice notes.
+    pub fn send_media(mut self, val: bool) -> Self {
+        self.raw.send_media = !val;
+        self
+    }
+
+    /// Whether the user will be able to send stickers.
+    pub fn send_stickers(mut self, val: bool) -> Self {
+        self.raw.send_stickers = !val;
+        self
+    }
+
+    /// Whether the user will be able to send animated gifs.
+    pub fn send_gifs(mut self, val: bool) -> Self {
+        self.raw.send_gifs = !val;
+        self
+    }
+
+    /// Whether the user will be able to send games.
+    pub fn send_games(mut self, val: bool) -> Self {
+        self.raw.send_games = !val;
+        self
+    }
+
+    /// Whether the user will be able to use inline bots.
+    pub fn send_inline(mut self, val: bool) -> Self {
+        self.raw.send_inline = !val;
+        self
+    }
+
+    /// Whether the user will be able to enable the link preview in the messages they send.
+    pub fn embed_link_previews(mut self, val: bool) -> Self {
+        self.raw.embed_links = !val;
+        self
+    }
+
+    /// Whether the user will be able to send polls.
+    pub fn send_polls(mut self, val: bool) -> Self {
+        self.raw.send_polls = !val;
+        self
+    }
+
+    /// Whether the user will be able to change information about the chat.
+    pub fn change_info(mut self, val: bool) -> Self {
+        self.raw.change_info = !val;
+        self
+    }
+
+    /// Whether the user will be able to invite other users.
+    pub fn invite_users(mut self, val: bool) -> Self {
+        self.raw.invite_users = !val;
+        self
+    }
+
+    /// Whether the user will be able to pin messages.
+    pub fn pin_messages(mut self, val: bool) -> Self {
+        self.raw.pin_messages = !val;
+        self
+    }
+
+    /// Whether the user will be able to create forum topics.
+    pub fn manage_topics(mut self, val: bool) -> Self {
+        self.raw.manage_topics = !val;
+        self
+    }
+
+    /// Apply the restrictions until the given time. By default, they are permanent.
+    pub fn until(mut self, date: DateTime<Utc>) -> Self {
+        self.raw.until_date = utils::timestamp(date);
+        self
+    }
+}
+
+/// The rights of an administrator in a chat, to be granted with [`Client::promote_member`].
+///
+/// Unlike [`AdminRightsBuilder`], this value does not perform any request on its own, so it can
+/// be built once and reused for many users. No right is granted by default.
+///
+/// # Examples
+///
+/// ```
+/// use grammers_client::types::AdminRights;
+///
+/// let moderator = AdminRights::new()
+///     .delete_messages(true)
+///     .ban_users(true)
+///     .rank("Moderator");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdminRights {
+    raw: tl::types::ChatAdminRights,
+    rank: String,
+}
+
+impl Default for AdminRights {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AdminRights {
+    /// Create a new set of administrator rights where nothing is granted.
+    pub fn new() -> Self {
+        Self {
+            raw: tl::types::ChatAdminRights {
+                change_info: false,
+                post_messages: false,
+                edit_messages: false,
+                delete_messages: false,
+                ban_users: false,
+                invite_users: false,
+                pin_messages: false,
+                add_admins: false,
+                anonymous: false,
+                manage_call: false,
+                other: false,
+                manage_topics: false,
+                post_stories: false,
+                edit_stories: false,
+                delete_stories: false,
+            },
+            rank: String::new(),
+        }
+    }
+
+    /// Whether the user will be able to change information about the chat such as group
+    /// description or not.
+    pub fn change_info(mut self, val: bool) -> Self {
+        self.raw.change_info = val;
+        self
+    }
+
+    /// Whether the user will be able to post in the channel. This will only work in broadcast
+    /// channels, not groups.
+    pub fn post_messages(mut self, val: bool) -> Self {
+        self.raw.post_messages = val;
+        self
+    }
+
+    /// Whether the user will be able to edit messages in the channel. This will only work in
+    /// broadcast channels, not groups.
+    pub fn edit_messages(mut self, val: bool) -> Self {
+        self.raw.edit_messages = val;
+        self
+    }
+
+    /// Whether the user will be able to delete messages.
+    pub fn delete_messages(mut self, val: bool) -> Self {
+        self.raw.delete_messages = val;
+        self
+    }
+
+    /// Whether the user will be able to ban users.
+    pub fn ban_users(mut self, val: bool) -> Self {
+        self.raw.ban_users = val;
+        self
+    }
+
+    /// Whether the user will be able to invite users.
+    pub fn invite_users(mut self, val: bool) -> Self {
+        self.raw.invite_users = val;
+        self
+    }
+
+    /// Whether the user will be able to pin messages.
+    pub fn pin_messages(mut self, val: bool) -> Self {
+        self.raw.pin_messages = val;
+        self
+    }
+
+    /// Whether the user will be able to add other administrators with the same or less
+    /// permissions than the user itself.
+    pub fn add_admins(mut self, val: bool) -> Self {
+        self.raw.add_admins = val;
+        self
+    }
+
+    /// Whether the user will remain anonymous when sending messages.
+    pub fn anonymous(mut self, val: bool) -> Self {
+        self.raw.anonymous = val;
+        self
+    }
+
+    /// Whether the user will be able to manage group calls.
+    pub fn manage_call(mut self, val: bool) -> Self {
+        self.raw.manage_call = val;
+        self
+    }
+
+    /// Whether the user will be able to manage forum topics.
+    pub fn manage_topics(mut self, val: bool) -> Self {
+        self.raw.manage_topics = val;
+        self
+    }
+
+    /// Whether the user will be able to post stories on behalf of the channel.
+    pub fn post_stories(mut self, val: bool) -> Self {
+        self.raw.post_stories = val;
+        self
+    }
+
+    /// Whether the user will be able to edit stories posted by others on behalf of the channel.
+    pub fn edit_stories(mut self, val: bool) -> Self {
+        self.raw.edit_stories = val;
+        self
+    }
+
+    /// Whether the user will be able to delete stories posted by others on behalf of the
+    /// channel.
+    pub fn delete_stories(mut self, val: bool) -> Self {
+        self.raw.delete_stories = val;
+        self
+    }
+
+    /// The custom rank (also known as "admin title" or "badge") to show for this administrator.
+    pub fn rank<S: Into<String>>(mut self, val: S) -> Self {
+        self.rank = val.into();
+        self
+    }
+}
@@ -0,0 +1,49 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use grammers_tl_types as tl;
+
+/// A phone number to be imported into the contact list of the logged-in user.
+///
+/// Used by [`Client::import_contacts`].
+///
+/// [`Client::import_contacts`]: crate::Client::import_contacts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhoneContact {
+    pub phone: String,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+impl PhoneContact {
+    /// Create a new contact with the given phone number and name.
+    ///
+    /// The phone number should be in international format, although the leading `+` is optional.
+    pub fn new<P: Into<String>, F: Into<String>>(phone: P, first_name: F) -> Self {
+        Self {
+            phone: phone.into(),
+            first_name: first_name.into(),
+            last_name: String::new(),
+        }
+    }
+
+    /// Change the last name of the contact.
+    pub fn last_name<L: Into<String>>(mut self, last_name: L) -> Self {
+        self.last_name = last_name.into();
+        self
+    }
+
+    pub(crate) fn to_raw(&self, client_id: i64) -> tl::enums::InputContact {
+        tl::types::InputPhoneContact {
+            client_id,
+            phone: self.phone.clone(),
+            first_name: self.first_name.clone(),
+            last_name: self.last_name.clone(),
+        }
+        .into()
+    }
+}
@@ -6,10 +6,29 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use super::{Chat, ChatMap, Message, Peer};
+use super::{Chat, ChatMap, Draft, Message, Peer};
 use grammers_tl_types as tl;
 use std::collections::HashMap;
 
+/// The folders in which Telegram places dialogs, which are unrelated to the folders created by
+/// the user (see [`Folder`](super::Folder)).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeerFolder {
+    /// The main list of dialogs.
+    Main,
+    /// The archive, for dialogs moved out of the main list.
+    Archived,
+}
+
+impl PeerFolder {
+    pub(crate) fn id(self) -> i32 {
+        match self {
+            Self::Main => 0,
+            Self::Archived => 1,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Dialog {
     pub raw: tl::enums::Dialog,
@@ -42,4 +61,45 @@ impl Dialog {
     pub fn chat(&self) -> &Chat {
         &self.chat
     }
+
+    /// The last message sent to this dialog, if any.
+    pub fn last_message(&self) -> Option<&Message> {
+        self.last_message.as_ref()
+    }
+
+    /// How many messages in the dialog have not been read yet by the logged-in user.
+    ///
+    /// For the entry representing the archive, this is the count of all the dialogs in it.
+    pub fn unread_count(&self) -> usize {
+        let count = match &self.raw {
+            tl::enums::Dialog::Dialog(dialog) => dialog.unread_count,
+            tl::enums::Dialog::Folder(folder) => {
+                folder.unread_muted_messages_count + folder.unread_unmuted_messages_count
+            }
+        };
+        count.max(0) as usize
+    }
+
+    /// Whether the dialog is pinned to the top of its folder.
+    pub fn is_pinned(&self) -> bool {
+        match &self.raw {
+            tl::enums::Dialog::Dialog(dialog) => dialog.pinned,
+            tl::enums::Dialog::Folder(folder) => folder.pinned,
+        }
+    }
+
+    /// The unsent message the logged-in user was writing in this dialog, if any.
+    pub fn draft(&self) -> Option<Draft> {
+        match &self.raw {
+            tl::enums::Dialog::Dialog(tl::types::Dialog {
+                draft: Some(draft @ tl::enums::DraftMessage::Message(_)),
+                ..
+            }) => Some(Draft::from_raw(
+                draft.clone(),
+                Some(self.chat.clone()),
+                None,
+            )),
+            _ => None,
+        }
+    }
 }
@@ -47,6 +47,7 @@ impl ChatPhoto {
 
 #[derive(Clone, Debug, PartialEq)]
 #[non_exhaustive]
+#[allow(clippy::large_enum_variant)]
 pub enum Downloadable {
     Media(crate::types::Media),
     UserProfilePhoto(UserProfilePhoto),
@@ -66,3 +67,15 @@ impl Downloadable {
         }
     }
 }
+
+impl From<crate::types::Media> for Downloadable {
+    fn from(media: crate::types::Media) -> Self {
+        Self::Media(media)
+    }
+}
+
+impl From<super::photo_sizes::PhotoSize> for Downloadable {
+    fn from(photo_size: super::photo_sizes::PhotoSize) -> Self {
+        Self::PhotoSize(photo_size)
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::types::{Chat, ChatMap};
+use crate::utils;
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+
+/// A message that the logged-in user was writing in a chat, but has not sent yet.
+///
+/// Drafts are synchronized across all the devices of the user. They can be saved with
+/// [`Client::save_draft`].
+///
+/// [`Client::save_draft`]: crate::Client::save_draft
+#[derive(Clone, Debug)]
+pub struct Draft {
+    pub raw: tl::enums::DraftMessage,
+    chat: Option<Chat>,
+    topic_id: Option<i32>,
+}
+
+impl Draft {
+    pub(crate) fn from_raw(
+        draft: tl::enums::DraftMessage,
+        chat: Option<Chat>,
+        topic_id: Option<i32>,
+    ) -> Self {
+        Self {
+            raw: draft,
+            chat,
+            topic_id,
+        }
+    }
+
+    pub(crate) fn from_update(update: tl::types::UpdateDraftMessage, chats: &ChatMap) -> Self {
+        Self::from_raw(
+            update.draft,
+            chats.get(&update.peer).cloned(),
+            update.top_msg_id,
+        )
+    }
+
+    /// The chat where this draft was saved.
+    ///
+    /// This is only `None` if the chat was not known when the draft was received as an update.
+    pub fn chat(&self) -> Option<&Chat> {
+        self.chat.as_ref()
+    }
+
+    /// The forum topic where this draft was saved, if any.
+    pub fn topic_id(&self) -> Option<i32> {
+        self.topic_id
+    }
+
+    /// Whether the draft was cleared.
+    ///
+    /// Cleared drafts are only received as updates, when another device removes a draft.
+    pub fn is_empty(&self) -> bool {
+        match &self.raw {
+            tl::enums::DraftMessage::Empty(_) => true,
+            tl::enums::DraftMessage::Message(draft) => {
+                draft.message.is_empty() && draft.media.is_none()
+            }
+        }
+    }
+
+    /// The text of the draft, which is empty if the draft was cleared.
+    pub fn text(&self) -> &str {
+        match &self.raw {
+            tl::enums::DraftMessage::Empty(_) => "",
+            tl::enums::DraftMessage::Message(draft) => &draft.message,
+        }
+    }
+
+    /// The formatting entities within the text of the draft.
+    pub fn fmt_entities(&self) -> Option<&Vec<tl::enums::MessageEntity>> {
+        match &self.raw {
+            tl::enums::DraftMessage::Empty(_) => None,
+            tl::enums::DraftMessage::Message(draft) => draft.entities.as_ref(),
+        }
+    }
+
+    /// The identifier of the message the draft is replying to, if any.
+    pub fn reply_to_message_id(&self) -> Option<i32> {
+        match &self.raw {
+            tl::enums::DraftMessage::Message(tl::types::DraftMessage {
+                reply_to: Some(tl::enums::InputReplyTo::Message(reply)),
+                ..
+            }) => Some(reply.reply_to_msg_id),
+            _ => None,
+        }
+    }
+
+    /// When the draft was last modified.
+    pub fn date(&self) -> Option<DateTime<Utc>> {
+        match &self.raw {
+            tl::enums::DraftMessage::Empty(draft) => draft.date.map(utils::date),
+            tl::enums::DraftMessage::Message(draft) => Some(utils::date(draft.date)),
+        }
+    }
+}
@@ -0,0 +1,282 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use grammers_tl_types as tl;
+use std::fmt;
+use std::ops::{Add, AddAssign, Range};
+
+/// Sets the offset and length of any formatting entity.
+fn set_bounds(entity: &mut tl::enums::MessageEntity, offset: i32, length: i32) {
+    use tl::enums::MessageEntity as E;
+
+    macro_rules! set {
+        ( $e:ident ) => {{
+            $e.offset = offset;
+            $e.length = length;
+        }};
+    }
+
+    match entity {
+        E::Unknown(e) => set!(e),
+        E::Mention(e) => set!(e),
+        E::Hashtag(e) => set!(e),
+        E::BotCommand(e) => set!(e),
+        E::Url(e) => set!(e),
+        E::Email(e) => set!(e),
+        E::Bold(e) => set!(e),
+        E::Italic(e) => set!(e),
+        E::Code(e) => set!(e),
+        E::Pre(e) => set!(e),
+        E::TextUrl(e) => set!(e),
+        E::MentionName(e) => set!(e),
+        E::InputMessageEntityMentionName(e) => set!(e),
+        E::Phone(e) => set!(e),
+        E::Cashtag(e) => set!(e),
+        E::Underline(e) => set!(e),
+        E::Strike(e) => set!(e),
+        E::BankCard(e) => set!(e),
+        E::Spoiler(e) => set!(e),
+        E::CustomEmoji(e) => set!(e),
+        E::Blockquote(e) => set!(e),
+    }
+}
+
+/// Text along with its formatting entities.
+///
+/// Telegram measures the offset and length of formatting entities in UTF-16 code units, which
+/// makes it easy to corrupt them when manipulating the text as a Rust string. This type takes
+/// care of keeping both in sync, so that formatted text can be sliced and concatenated safely.
+///
+/// All positions used by this type are expressed in UTF-16 code units.
+///
+/// # Examples
+///
+/// ```
+/// # async fn f(message: grammers_client::types::Message) {
+/// use grammers_client::types::EntityText;
+///
+/// let text = message.entity_text();
+/// let preview = text.slice(0..text.len().min(100)) + EntityText::plain("…");
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EntityText {
+    text: String,
+    entities: Vec<tl::enums::MessageEntity>,
+}
+
+impl EntityText {
+    /// Create a new formatted text from its raw parts.
+    pub fn new(text: impl Into<String>, entities: Vec<tl::enums::MessageEntity>) -> Self {
+        Self {
+            text: text.into(),
+            entities,
+        }
+    }
+
+    /// Create a new text without any formatting entities.
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self::new(text, Vec::new())
+    }
+
+    /// Create a new formatted text from a markdown string.
+    #[cfg(feature = "markdown")]
+    pub fn markdown(text: &str) -> Self {
+        let (text, entities) = crate::parsers::parse_markdown_message(text);
+        Self::new(text, entities)
+    }
+
+    /// Create a new formatted text from a HTML string.
+    #[cfg(feature = "html")]
+    pub fn html(text: &str) -> Self {
+        let (text, entities) = crate::parsers::parse_html_message(text);
+        Self::new(text, entities)
+    }
+
+    /// The plain text, without any formatting.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The formatting entities applied to the text.
+    pub fn entities(&self) -> &[tl::enums::MessageEntity] {
+        &self.entities
+    }
+
+    /// Split the formatted text into its raw parts.
+    pub fn into_parts(self) -> (String, Vec<tl::enums::MessageEntity>) {
+        (self.text, self.entities)
+    }
+
+    /// The length of the text, in UTF-16 code units.
+    pub fn len(&self) -> usize {
+        self.text.encode_utf16().count()
+    }
+
+    /// Return `true` if the text is empty.
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Convert a position in UTF-16 code units into a byte index into the text.
+    ///
+    /// Positions in the middle of a surrogate pair are moved forward to the next character.
+    fn byte_index(&self, position: usize) -> usize {
+        let mut units = 0;
+        for (index, c) in self.text.char_indices() {
+            if units >= position {
+                return index;
+            }
+            units += c.len_utf16();
+        }
+        self.text.len()
+    }
+
+    /// Return the formatted text within the given range of UTF-16 code units.
+    ///
+    /// Entities are clipped to the range, and those that fall completely outside of it are
+    /// dropped. The range is clamped to the length of the text.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        let len = self.len();
+        let end = range.end.min(len);
+        let start = range.start.min(end);
+
+        let text = self.text[self.byte_index(start)..self.byte_index(end)].to_string();
+        let entities = self
+            .entities
+            .iter()
+            .filter_map(|entity| {
+                let entity_start = (entity.offset().max(0) as usize).max(start);
+                let entity_end = ((entity.offset() + entity.length()).max(0) as usize).min(end);
+                if entity_start >= entity_end {
+                    return None;
+                }
+
+                let mut entity = entity.clone();
+                set_bounds(
+                    &mut entity,
+                    (entity_start - start) as i32,
+                    (entity_end - entity_start) as i32,
+                );
+                Some(entity)
+            })
+            .collect();
+
+        Self { text, entities }
+    }
+
+    /// Append another formatted text at the end of this one, shifting its entities accordingly.
+    pub fn push(&mut self, other: EntityText) {
+        let shift = self.len() as i32;
+        self.text.push_str(&other.text);
+        self.entities
+            .extend(other.entities.into_iter().map(|mut entity| {
+                let (offset, length) = (entity.offset(), entity.length());
+                set_bounds(&mut entity, offset + shift, length);
+                entity
+            }));
+    }
+
+    /// Append plain text at the end of this one.
+    pub fn push_str(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    /// Convert the formatted text into a markdown string.
+    ///
+    /// See [`Message::markdown_text`](crate::types::Message::markdown_text) for the caveats.
+    #[cfg(feature = "markdown")]
+    pub fn to_markdown(&self) -> String {
+        crate::parsers::generate_markdown_message(&self.text, &self.entities)
+    }
+
+    /// Convert the formatted text into a HTML string.
+    ///
+    /// See [`Message::html_text`](crate::types::Message::html_text) for the caveats.
+    #[cfg(feature = "html")]
+    pub fn to_html(&self) -> String {
+        crate::parsers::generate_html_message(&self.text, &self.entities)
+    }
+}
+
+impl Add for EntityText {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self.push(other);
+        self
+    }
+}
+
+impl AddAssign for EntityText {
+    fn add_assign(&mut self, other: Self) {
+        self.push(other);
+    }
+}
+
+impl fmt::Display for EntityText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl From<&str> for EntityText {
+    fn from(text: &str) -> Self {
+        Self::plain(text)
+    }
+}
+
+impl From<String> for EntityText {
+    fn from(text: String) -> Self {
+        Self::plain(text)
+    }
+}
+
+impl From<&super::Message> for EntityText {
+    fn from(message: &super::Message) -> Self {
+        message.entity_text()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bold(offset: i32, length: i32) -> tl::enums::MessageEntity {
+        tl::types::MessageEntityBold { offset, length }.into()
+    }
+
+    #[test]
+    fn slice_clips_entities() {
+        let text = EntityText::new("Hello world", vec![bold(0, 5), bold(6, 5)]);
+        assert_eq!(
+            text.slice(3..8),
+            EntityText::new("lo wo", vec![bold(0, 2), bold(3, 2)])
+        );
+        assert_eq!(text.slice(0..5), EntityText::new("Hello", vec![bold(0, 5)]));
+        assert_eq!(text.slice(20..30), EntityText::plain(""));
+    }
+
+    #[test]
+    fn slice_uses_utf16_positions() {
+        let text = EntityText::new("🦀 crab", vec![bold(0, 2), bold(3, 4)]);
+        assert_eq!(text.len(), 7);
+        assert_eq!(text.slice(0..2), EntityText::new("🦀", vec![bold(0, 2)]));
+        assert_eq!(text.slice(3..7), EntityText::new("crab", vec![bold(0, 4)]));
+    }
+
+    #[test]
+    fn concatenation_shifts_entities() {
+        let text = EntityText::new("🦀", vec![bold(0, 2)])
+            + EntityText::plain(" and ")
+            + EntityText::new("🐍", vec![bold(0, 2)]);
+        assert_eq!(
+            text,
+            EntityText::new("🦀 and 🐍", vec![bold(0, 2), bold(7, 2)])
+        );
+    }
+}
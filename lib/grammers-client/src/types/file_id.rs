@@ -0,0 +1,852 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Conversion between media and the `file_id` strings used by the Bot API.
+//!
+//! Bot API file identifiers are URL-safe base64 strings wrapping a run-length encoded binary
+//! structure. They contain everything needed to download or resend a file through MTProto,
+//! which lets projects using both APIs exchange media references.
+use super::media::{Document, Photo};
+use super::photo_sizes::VecExt;
+use grammers_tl_types::{self as tl, Cursor, Deserializable, Serializable};
+
+const WEB_LOCATION_FLAG: i32 = 1 << 24;
+const FILE_REFERENCE_FLAG: i32 = 1 << 25;
+const VERSION: u8 = 4;
+// Photo file identifiers stopped including a volume and local identifier in this sub-version.
+const REMOVED_PHOTO_VOLUME_SUB_VERSION: u8 = 32;
+const SUB_VERSION: u8 = REMOVED_PHOTO_VOLUME_SUB_VERSION;
+
+/// The kind of file a [`FileId`] refers to, as understood by the Bot API.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(i32)]
+pub enum FileType {
+    Thumbnail = 0,
+    ProfilePhoto = 1,
+    Photo = 2,
+    Voice = 3,
+    Video = 4,
+    Document = 5,
+    Encrypted = 6,
+    Temp = 7,
+    Sticker = 8,
+    Audio = 9,
+    Animation = 10,
+    EncryptedThumbnail = 11,
+    Wallpaper = 12,
+    VideoNote = 13,
+    SecureRaw = 14,
+    Secure = 15,
+    Background = 16,
+    DocumentAsFile = 17,
+}
+
+impl FileType {
+    fn from_raw(value: i32) -> Option<Self> {
+        use FileType::*;
+        Some(match value {
+            0 => Thumbnail,
+            1 => ProfilePhoto,
+            2 => Photo,
+            3 => Voice,
+            4 => Video,
+            5 => Document,
+            6 => Encrypted,
+            7 => Temp,
+            8 => Sticker,
+            9 => Audio,
+            10 => Animation,
+            11 => EncryptedThumbnail,
+            12 => Wallpaper,
+            13 => VideoNote,
+            14 => SecureRaw,
+            15 => Secure,
+            16 => Background,
+            17 => DocumentAsFile,
+            _ => return None,
+        })
+    }
+
+    fn is_photo(self) -> bool {
+        matches!(
+            self,
+            Self::Thumbnail | Self::ProfilePhoto | Self::Photo | Self::EncryptedThumbnail
+        )
+    }
+}
+
+/// Where a photo-like file comes from, needed to build its location.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PhotoSource {
+    Legacy {
+        secret: i64,
+    },
+    Thumbnail {
+        file_type: FileType,
+        thumb_size: String,
+    },
+    ChatPhotoSmall {
+        chat_id: i64,
+        access_hash: i64,
+    },
+    ChatPhotoBig {
+        chat_id: i64,
+        access_hash: i64,
+    },
+    StickerSetThumbnail {
+        set_id: i64,
+        access_hash: i64,
+    },
+    FullLegacy {
+        volume_id: i64,
+        secret: i64,
+        local_id: i32,
+    },
+    ChatPhotoSmallLegacy {
+        chat_id: i64,
+        access_hash: i64,
+        volume_id: i64,
+        local_id: i32,
+    },
+    ChatPhotoBigLegacy {
+        chat_id: i64,
+        access_hash: i64,
+        volume_id: i64,
+        local_id: i32,
+    },
+    StickerSetThumbnailLegacy {
+        set_id: i64,
+        access_hash: i64,
+        volume_id: i64,
+        local_id: i32,
+    },
+    StickerSetThumbnailVersion {
+        set_id: i64,
+        access_hash: i64,
+        version: i32,
+    },
+}
+
+impl PhotoSource {
+    fn deserialize(buf: &mut Cursor) -> tl::deserialize::Result<Option<Self>> {
+        Ok(Some(match i32::deserialize(buf)? {
+            0 => Self::Legacy {
+                secret: i64::deserialize(buf)?,
+            },
+            1 => {
+                let file_type = match FileType::from_raw(i32::deserialize(buf)?) {
+                    Some(file_type) => file_type,
+                    None => return Ok(None),
+                };
+                let thumb_size = match char::from_u32(u32::deserialize(buf)?) {
+                    Some(c) => c.to_string(),
+                    None => return Ok(None),
+                };
+                Self::Thumbnail {
+                    file_type,
+                    thumb_size,
+                }
+            }
+            source @ (2 | 3) => {
+                let chat_id = i64::deserialize(buf)?;
+                let access_hash = i64::deserialize(buf)?;
+                if source == 2 {
+                    Self::ChatPhotoSmall {
+                        chat_id,
+                        access_hash,
+                    }
+                } else {
+                    Self::ChatPhotoBig {
+                        chat_id,
+                        access_hash,
+                    }
+                }
+            }
+            4 => Self::StickerSetThumbnail {
+                set_id: i64::deserialize(buf)?,
+                access_hash: i64::deserialize(buf)?,
+            },
+            5 => Self::FullLegacy {
+                volume_id: i64::deserialize(buf)?,
+                secret: i64::deserialize(buf)?,
+                local_id: i32::deserialize(buf)?,
+            },
+            source @ (6 | 7) => {
+                let chat_id = i64::deserialize(buf)?;
+                let access_hash = i64::deserialize(buf)?;
+                let volume_id = i64::deserialize(buf)?;
+                let local_id = i32::deserialize(buf)?;
+                if source == 6 {
+                    Self::ChatPhotoSmallLegacy {
+                        chat_id,
+                        access_hash,
+                        volume_id,
+                        local_id,
+                    }
+                } else {
+                    Self::ChatPhotoBigLegacy {
+                        chat_id,
+                        access_hash,
+                        volume_id,
+                        local_id,
+                    }
+                }
+            }
+            8 => Self::StickerSetThumbnailLegacy {
+                set_id: i64::deserialize(buf)?,
+                access_hash: i64::deserialize(buf)?,
+                volume_id: i64::deserialize(buf)?,
+                local_id: i32::deserialize(buf)?,
+            },
+            9 => Self::StickerSetThumbnailVersion {
+                set_id: i64::deserialize(buf)?,
+                access_hash: i64::deserialize(buf)?,
+                version: i32::deserialize(buf)?,
+            },
+            _ => return Ok(None),
+        }))
+    }
+
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::Legacy { secret } => {
+                0i32.serialize(buf);
+                secret.serialize(buf);
+            }
+            Self::Thumbnail {
+                file_type,
+                thumb_size,
+            } => {
+                1i32.serialize(buf);
+                (*file_type as i32).serialize(buf);
+                (thumb_size.chars().next().unwrap_or('\0') as u32).serialize(buf);
+            }
+            Self::ChatPhotoSmall {
+                chat_id,
+                access_hash,
+            }
+            | Self::ChatPhotoBig {
+                chat_id,
+                access_hash,
+            } => {
+                let source: i32 = if matches!(self, Self::ChatPhotoSmall { .. }) {
+                    2
+                } else {
+                    3
+                };
+                source.serialize(buf);
+                chat_id.serialize(buf);
+                access_hash.serialize(buf);
+            }
+            Self::StickerSetThumbnail {
+                set_id,
+                access_hash,
+            } => {
+                4i32.serialize(buf);
+                set_id.serialize(buf);
+                access_hash.serialize(buf);
+            }
+            Self::FullLegacy {
+                volume_id,
+                secret,
+                local_id,
+            } => {
+                5i32.serialize(buf);
+                volume_id.serialize(buf);
+                secret.serialize(buf);
+                local_id.serialize(buf);
+            }
+            Self::ChatPhotoSmallLegacy {
+                chat_id,
+                access_hash,
+                volume_id,
+                local_id,
+            }
+            | Self::ChatPhotoBigLegacy {
+                chat_id,
+                access_hash,
+                volume_id,
+                local_id,
+            } => {
+                let source: i32 = if matches!(self, Self::ChatPhotoSmallLegacy { .. }) {
+                    6
+                } else {
+                    7
+                };
+                source.serialize(buf);
+                chat_id.serialize(buf);
+                access_hash.serialize(buf);
+                volume_id.serialize(buf);
+                local_id.serialize(buf);
+            }
+            Self::StickerSetThumbnailLegacy {
+                set_id,
+                access_hash,
+                volume_id,
+                local_id,
+            } => {
+                8i32.serialize(buf);
+                set_id.serialize(buf);
+                access_hash.serialize(buf);
+                volume_id.serialize(buf);
+                local_id.serialize(buf);
+            }
+            Self::StickerSetThumbnailVersion {
+                set_id,
+                access_hash,
+                version,
+            } => {
+                9i32.serialize(buf);
+                set_id.serialize(buf);
+                access_hash.serialize(buf);
+                version.serialize(buf);
+            }
+        }
+    }
+}
+
+/// A decoded Bot API `file_id`.
+///
+/// # Examples
+///
+/// ```
+/// # fn f(file_id: &str) {
+/// use grammers_client::types::FileId;
+///
+/// let file_id = FileId::decode(file_id).expect("invalid file_id");
+/// println!("File of type {:?} in DC {}", file_id.file_type, file_id.dc_id);
+/// let location = file_id.to_raw_input_location();
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileId {
+    pub file_type: FileType,
+    pub dc_id: i32,
+    pub id: i64,
+    pub access_hash: i64,
+    pub file_reference: Vec<u8>,
+    /// The URL of the file, for files which are only stored on the web.
+    pub url: Option<String>,
+    /// The source of the file, for photo-like files.
+    pub photo_source: Option<PhotoSource>,
+}
+
+impl FileId {
+    /// Decode a Bot API `file_id` string.
+    ///
+    /// Returns `None` if the string is not a valid or supported file identifier.
+    pub fn decode(file_id: &str) -> Option<Self> {
+        let data = rle_decode(&base64_decode(file_id)?);
+        let (&version, data) = data.split_last()?;
+        let (sub_version, data) = if version >= 4 {
+            let (&sub_version, data) = data.split_last()?;
+            (sub_version, data)
+        } else {
+            (0, data)
+        };
+        if !(2..=VERSION).contains(&version) {
+            return None;
+        }
+
+        let buf = &mut Cursor::from_slice(data);
+        let raw_type = i32::deserialize(buf).ok()?;
+        let dc_id = i32::deserialize(buf).ok()?;
+        let file_type = FileType::from_raw(raw_type & !(WEB_LOCATION_FLAG | FILE_REFERENCE_FLAG))?;
+
+        let file_reference = if raw_type & FILE_REFERENCE_FLAG != 0 {
+            Vec::<u8>::deserialize(buf).ok()?
+        } else {
+            Vec::new()
+        };
+
+        if raw_type & WEB_LOCATION_FLAG != 0 {
+            let url = String::deserialize(buf).ok()?;
+            let access_hash = i64::deserialize(buf).ok()?;
+            return Some(Self {
+                file_type,
+                dc_id,
+                id: 0,
+                access_hash,
+                file_reference,
+                url: Some(url),
+                photo_source: None,
+            });
+        }
+
+        let id = i64::deserialize(buf).ok()?;
+        let access_hash = i64::deserialize(buf).ok()?;
+        let photo_source = if file_type.is_photo() {
+            if sub_version < REMOVED_PHOTO_VOLUME_SUB_VERSION {
+                i64::deserialize(buf).ok()?;
+            }
+            let source = if version >= 4 {
+                PhotoSource::deserialize(buf).ok()??
+            } else {
+                PhotoSource::Legacy {
+                    secret: i64::deserialize(buf).ok()?,
+                }
+            };
+            if sub_version < REMOVED_PHOTO_VOLUME_SUB_VERSION {
+                i32::deserialize(buf).ok()?;
+            }
+            Some(source)
+        } else {
+            None
+        };
+
+        Some(Self {
+            file_type,
+            dc_id,
+            id,
+            access_hash,
+            file_reference,
+            url: None,
+            photo_source,
+        })
+    }
+
+    /// Encode the file identifier into a string which the Bot API understands.
+    pub fn encode(&self) -> String {
+        let mut raw_type = self.file_type as i32;
+        if self.url.is_some() {
+            raw_type |= WEB_LOCATION_FLAG;
+        }
+        if !self.file_reference.is_empty() {
+            raw_type |= FILE_REFERENCE_FLAG;
+        }
+
+        let mut buf = Vec::new();
+        raw_type.serialize(&mut buf);
+        self.dc_id.serialize(&mut buf);
+        if !self.file_reference.is_empty() {
+            self.file_reference.serialize(&mut buf);
+        }
+        if let Some(url) = self.url.as_ref() {
+            url.serialize(&mut buf);
+            self.access_hash.serialize(&mut buf);
+        } else {
+            self.id.serialize(&mut buf);
+            self.access_hash.serialize(&mut buf);
+            if let Some(source) = self.photo_source.as_ref() {
+                source.serialize(&mut buf);
+            }
+        }
+        buf.push(SUB_VERSION);
+        buf.push(VERSION);
+
+        base64_encode(&rle_encode(&buf))
+    }
+
+    /// Encode the Bot API `file_unique_id` of the file.
+    ///
+    /// Unlike the `file_id`, this identifier is the same for every bot and cannot be used to
+    /// download the file. It is only computed for web files and documents.
+    pub fn unique_id(&self) -> Option<String> {
+        let mut buf = Vec::new();
+        if let Some(url) = self.url.as_ref() {
+            0i32.serialize(&mut buf);
+            url.serialize(&mut buf);
+        } else if self.file_type.is_photo() {
+            return None;
+        } else {
+            2i32.serialize(&mut buf);
+            self.id.serialize(&mut buf);
+        }
+        Some(base64_encode(&rle_encode(&buf)))
+    }
+
+    /// Build a file identifier from a photo, pointing to its largest size.
+    pub fn from_photo(photo: &Photo) -> Option<Self> {
+        let raw = match photo.raw.photo.as_ref()? {
+            tl::enums::Photo::Empty(_) => return None,
+            tl::enums::Photo::Photo(raw) => raw,
+        };
+        let thumb_size = photo
+            .thumbs()
+            .largest()
+            .map(|ps| ps.photo_type())
+            .unwrap_or(String::from("w"));
+
+        Some(Self {
+            file_type: FileType::Photo,
+            dc_id: raw.dc_id,
+            id: raw.id,
+            access_hash: raw.access_hash,
+            file_reference: raw.file_reference.to_vec(),
+            url: None,
+            photo_source: Some(PhotoSource::Thumbnail {
+                file_type: FileType::Photo,
+                thumb_size,
+            }),
+        })
+    }
+
+    /// Build a file identifier from a document, deriving its type from the attributes.
+    pub fn from_document(document: &Document) -> Option<Self> {
+        use tl::enums::DocumentAttribute as A;
+
+        let raw = match document.raw.document.as_ref()? {
+            tl::enums::Document::Empty(_) => return None,
+            tl::enums::Document::Document(raw) => raw,
+        };
+
+        let mut file_type = FileType::Document;
+        for attr in &raw.attributes {
+            file_type = match attr {
+                A::Sticker(_) => FileType::Sticker,
+                A::Audio(audio) if audio.voice => FileType::Voice,
+                A::Audio(_) => FileType::Audio,
+                A::Video(video) if video.round_message => FileType::VideoNote,
+                A::Video(_) if file_type == FileType::Document => FileType::Video,
+                A::Animated => FileType::Animation,
+                _ => continue,
+            };
+        }
+
+        Some(Self {
+            file_type,
+            dc_id: raw.dc_id,
+            id: raw.id,
+            access_hash: raw.access_hash,
+            file_reference: raw.file_reference.to_vec(),
+            url: None,
+            photo_source: None,
+        })
+    }
+
+    /// The location of the file, which can be used to download it.
+    ///
+    /// Returns `None` for web, encrypted and secure files.
+    pub fn to_raw_input_location(&self) -> Option<tl::enums::InputFileLocation> {
+        if self.url.is_some() {
+            return None;
+        }
+
+        Some(match self.photo_source.as_ref() {
+            None => match self.file_type {
+                FileType::Encrypted
+                | FileType::Secure
+                | FileType::SecureRaw
+                | FileType::EncryptedThumbnail => return None,
+                _ => tl::types::InputDocumentFileLocation {
+                    id: self.id,
+                    access_hash: self.access_hash,
+                    file_reference: tl::byte_string(self.file_reference.clone()),
+                    thumb_size: String::new(),
+                }
+                .into(),
+            },
+            Some(PhotoSource::Thumbnail {
+                file_type,
+                thumb_size,
+            }) => {
+                if matches!(file_type, FileType::Photo | FileType::ProfilePhoto) {
+                    tl::types::InputPhotoFileLocation {
+                        id: self.id,
+                        access_hash: self.access_hash,
+                        file_reference: tl::byte_string(self.file_reference.clone()),
+                        thumb_size: thumb_size.clone(),
+                    }
+                    .into()
+                } else {
+                    tl::types::InputDocumentFileLocation {
+                        id: self.id,
+                        access_hash: self.access_hash,
+                        file_reference: tl::byte_string(self.file_reference.clone()),
+                        thumb_size: thumb_size.clone(),
+                    }
+                    .into()
+                }
+            }
+            Some(
+                PhotoSource::ChatPhotoSmall {
+                    chat_id,
+                    access_hash,
+                }
+                | PhotoSource::ChatPhotoSmallLegacy {
+                    chat_id,
+                    access_hash,
+                    ..
+                },
+            ) => chat_photo_location(*chat_id, *access_hash, self.id, false),
+            Some(
+                PhotoSource::ChatPhotoBig {
+                    chat_id,
+                    access_hash,
+                }
+                | PhotoSource::ChatPhotoBigLegacy {
+                    chat_id,
+                    access_hash,
+                    ..
+                },
+            ) => chat_photo_location(*chat_id, *access_hash, self.id, true),
+            Some(
+                PhotoSource::StickerSetThumbnail {
+                    set_id,
+                    access_hash,
+                }
+                | PhotoSource::StickerSetThumbnailLegacy {
+                    set_id,
+                    access_hash,
+                    ..
+                },
+            ) => sticker_set_thumb_location(*set_id, *access_hash, 0),
+            Some(PhotoSource::StickerSetThumbnailVersion {
+                set_id,
+                access_hash,
+                version,
+            }) => sticker_set_thumb_location(*set_id, *access_hash, *version),
+            Some(PhotoSource::Legacy { .. } | PhotoSource::FullLegacy { .. }) => return None,
+        })
+    }
+
+    /// The file as input media, which can be used to send it again.
+    ///
+    /// Returns `None` for files which are not photos or documents, such as chat photos.
+    pub fn to_raw_input_media(&self) -> Option<tl::enums::InputMedia> {
+        if self.url.is_some() {
+            return None;
+        }
+
+        match self.photo_source {
+            Some(PhotoSource::Thumbnail {
+                file_type: FileType::Photo,
+                ..
+            }) => Some(
+                tl::types::InputMediaPhoto {
+                    spoiler: false,
+                    id: tl::types::InputPhoto {
+                        id: self.id,
+                        access_hash: self.access_hash,
+                        file_reference: tl::byte_string(self.file_reference.clone()),
+                    }
+                    .into(),
+                    ttl_seconds: None,
+                }
+                .into(),
+            ),
+            Some(_) => None,
+            None => Some(
+                tl::types::InputMediaDocument {
+                    spoiler: false,
+                    id: tl::types::InputDocument {
+                        id: self.id,
+                        access_hash: self.access_hash,
+                        file_reference: tl::byte_string(self.file_reference.clone()),
+                    }
+                    .into(),
+                    ttl_seconds: None,
+                    query: None,
+                }
+                .into(),
+            ),
+        }
+    }
+}
+
+/// Build the location of a chat photo, given the chat identifier in Bot API format.
+fn chat_photo_location(
+    chat_id: i64,
+    access_hash: i64,
+    photo_id: i64,
+    big: bool,
+) -> tl::enums::InputFileLocation {
+    const CHANNEL_OFFSET: i64 = -1_000_000_000_000;
+
+    let peer = if chat_id > 0 {
+        tl::types::InputPeerUser {
+            user_id: chat_id,
+            access_hash,
+        }
+        .into()
+    } else if chat_id > CHANNEL_OFFSET {
+        tl::types::InputPeerChat { chat_id: -chat_id }.into()
+    } else {
+        tl::types::InputPeerChannel {
+            channel_id: CHANNEL_OFFSET - chat_id,
+            access_hash,
+        }
+        .into()
+    };
+
+    tl::types::InputPeerPhotoFileLocation {
+        big,
+        peer,
+        photo_id,
+    }
+    .into()
+}
+
+fn sticker_set_thumb_location(
+    set_id: i64,
+    access_hash: i64,
+    thumb_version: i32,
+) -> tl::enums::InputFileLocation {
+    tl::types::InputStickerSetThumb {
+        stickerset: tl::types::InputStickerSetId {
+            id: set_id,
+            access_hash,
+        }
+        .into(),
+        thumb_version,
+    }
+    .into()
+}
+
+/// Decode run-length encoded data, where runs of zero bytes are stored as a zero and a count.
+fn rle_decode(data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(data.len());
+    let mut last_zero = false;
+    for &byte in data {
+        if last_zero {
+            result.extend(std::iter::repeat_n(0, byte as usize));
+            last_zero = false;
+        } else if byte == 0 {
+            last_zero = true;
+        } else {
+            result.push(byte);
+        }
+    }
+    result
+}
+
+fn rle_encode(data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(data.len());
+    let mut zeros = 0u8;
+    for &byte in data {
+        if byte == 0 {
+            if zeros == u8::MAX {
+                result.extend([0, zeros]);
+                zeros = 0;
+            }
+            zeros += 1;
+        } else {
+            if zeros != 0 {
+                result.extend([0, zeros]);
+                zeros = 0;
+            }
+            result.push(byte);
+        }
+    }
+    if zeros != 0 {
+        result.extend([0, zeros]);
+    }
+    result
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Decode URL-safe base64, with or without padding.
+fn base64_decode(data: &str) -> Option<Vec<u8>> {
+    let mut result = Vec::with_capacity(data.len() * 3 / 4);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for c in data.bytes().take_while(|&c| c != b'=') {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'-' | b'+' => 62,
+            b'_' | b'/' => 63,
+            _ => return None,
+        };
+        acc = (acc << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            result.push((acc >> bits) as u8);
+        }
+    }
+    Some(result)
+}
+
+/// Encode as URL-safe base64, without padding.
+fn base64_encode(data: &[u8]) -> String {
+    let mut result = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let acc = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, &b)| acc | (b as u32) << (16 - i * 8));
+        for i in 0..=chunk.len() {
+            result.push(BASE64_ALPHABET[(acc >> (18 - i * 6)) as usize & 0x3f] as char);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rle_roundtrip() {
+        let data = [1, 0, 0, 0, 2, 0, 3, 0, 0];
+        assert_eq!(rle_encode(&data), vec![1, 0, 3, 2, 0, 1, 3, 0, 2]);
+        assert_eq!(rle_decode(&rle_encode(&data)), data);
+    }
+
+    #[test]
+    fn base64_roundtrip() {
+        assert_eq!(base64_encode(b"grammers"), "Z3JhbW1lcnM");
+        assert_eq!(base64_decode("Z3JhbW1lcnM").unwrap(), b"grammers");
+        assert_eq!(base64_decode("Z3JhbW1lcnM=").unwrap(), b"grammers");
+        assert_eq!(base64_decode("Z3Jh!W1lcnM"), None);
+    }
+
+    #[test]
+    fn decode_file_id_header() {
+        // The header of a photo identifier sent from DC 4, with a file reference.
+        let data = rle_decode(&base64_decode("AgACAgQAAxkB").unwrap());
+        let buf = &mut Cursor::from_slice(&data);
+        let raw_type = i32::deserialize(buf).unwrap();
+        assert_eq!(raw_type & FILE_REFERENCE_FLAG, FILE_REFERENCE_FLAG);
+        assert_eq!(
+            FileType::from_raw(raw_type & !FILE_REFERENCE_FLAG),
+            Some(FileType::Photo)
+        );
+        assert_eq!(i32::deserialize(buf).unwrap(), 4);
+    }
+
+    #[test]
+    fn file_id_roundtrip() {
+        let photo = FileId {
+            file_type: FileType::Photo,
+            dc_id: 4,
+            id: 5_523_356_989_130_045_000,
+            access_hash: -3_034_478_207_047_491_000,
+            file_reference: vec![1, 0, 0, 2, 145, 103, 0, 0, 0, 7],
+            url: None,
+            photo_source: Some(PhotoSource::Thumbnail {
+                file_type: FileType::Photo,
+                thumb_size: "y".to_string(),
+            }),
+        };
+        assert!(photo.encode().starts_with("AgACAgQAA"));
+        assert_eq!(FileId::decode(&photo.encode()), Some(photo));
+
+        let document = FileId {
+            file_type: FileType::Sticker,
+            dc_id: 2,
+            id: 1_234_567_890,
+            access_hash: 42,
+            file_reference: Vec::new(),
+            url: None,
+            photo_source: None,
+        };
+        assert_eq!(FileId::decode(&document.encode()), Some(document.clone()));
+        assert_eq!(document.unique_id(), Some("AgAD0gKWSQAE".to_string()));
+    }
+
+    #[test]
+    fn decode_invalid_file_id() {
+        assert_eq!(FileId::decode(""), None);
+        assert_eq!(FileId::decode("not a file id"), None);
+        assert_eq!(FileId::decode("AgAD"), None);
+    }
+}
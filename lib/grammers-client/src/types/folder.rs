@@ -0,0 +1,242 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+
+/// A folder in which the logged-in user organizes their dialogs.
+///
+/// Folders are also known as "dialog filters" or "chat filters" in the Telegram API.
+#[derive(Clone, Debug)]
+pub struct Folder {
+    pub raw: tl::enums::DialogFilter,
+}
+
+/// A builder to configure the dialogs that belong to a folder.
+///
+/// Used by [`Client::create_folder`] and [`Client::edit_folder`]. A folder must end up with at
+/// least one included chat or type of chat for Telegram to accept it.
+///
+/// [`Client::create_folder`]: crate::Client::create_folder
+/// [`Client::edit_folder`]: crate::Client::edit_folder
+#[derive(Clone, Debug)]
+pub struct FolderBuilder {
+    raw: tl::types::DialogFilter,
+    // Set when editing a shared folder, so that it is not turned into a regular folder.
+    shared: Option<bool>,
+}
+
+impl Folder {
+    pub(crate) fn from_raw(filter: tl::enums::DialogFilter) -> Option<Self> {
+        match filter {
+            tl::enums::DialogFilter::Default => None,
+            raw => Some(Self { raw }),
+        }
+    }
+
+    /// Unique identifier of the folder.
+    pub fn id(&self) -> i32 {
+        match &self.raw {
+            tl::enums::DialogFilter::Filter(f) => f.id,
+            tl::enums::DialogFilter::Chatlist(f) => f.id,
+            tl::enums::DialogFilter::Default => 0,
+        }
+    }
+
+    /// The name of the folder.
+    pub fn title(&self) -> &str {
+        match &self.raw {
+            tl::enums::DialogFilter::Filter(f) => &f.title,
+            tl::enums::DialogFilter::Chatlist(f) => &f.title,
+            tl::enums::DialogFilter::Default => "",
+        }
+    }
+
+    /// The emoji used as the icon of the folder, if any.
+    pub fn emoticon(&self) -> Option<&str> {
+        match &self.raw {
+            tl::enums::DialogFilter::Filter(f) => f.emoticon.as_deref(),
+            tl::enums::DialogFilter::Chatlist(f) => f.emoticon.as_deref(),
+            tl::enums::DialogFilter::Default => None,
+        }
+    }
+
+    /// Whether the folder is a chat list shared through an invite link.
+    ///
+    /// Shared folders can only include specific chats.
+    pub fn is_shared(&self) -> bool {
+        matches!(self.raw, tl::enums::DialogFilter::Chatlist(_))
+    }
+
+    /// The chats pinned to the top of the folder.
+    pub fn pinned_chats(&self) -> &[tl::enums::InputPeer] {
+        match &self.raw {
+            tl::enums::DialogFilter::Filter(f) => &f.pinned_peers,
+            tl::enums::DialogFilter::Chatlist(f) => &f.pinned_peers,
+            tl::enums::DialogFilter::Default => &[],
+        }
+    }
+
+    /// The chats explicitly included in the folder, besides those pinned.
+    pub fn included_chats(&self) -> &[tl::enums::InputPeer] {
+        match &self.raw {
+            tl::enums::DialogFilter::Filter(f) => &f.include_peers,
+            tl::enums::DialogFilter::Chatlist(f) => &f.include_peers,
+            tl::enums::DialogFilter::Default => &[],
+        }
+    }
+
+    /// The chats explicitly excluded from the folder.
+    pub fn excluded_chats(&self) -> &[tl::enums::InputPeer] {
+        match &self.raw {
+            tl::enums::DialogFilter::Filter(f) => &f.exclude_peers,
+            tl::enums::DialogFilter::Chatlist(_) | tl::enums::DialogFilter::Default => &[],
+        }
+    }
+}
+
+impl FolderBuilder {
+    /// Start configuring a folder with the given name, which includes no chats.
+    pub fn new<T: Into<String>>(title: T) -> Self {
+        Self {
+            raw: tl::types::DialogFilter {
+                contacts: false,
+                non_contacts: false,
+                groups: false,
+                broadcasts: false,
+                bots: false,
+                exclude_muted: false,
+                exclude_read: false,
+                exclude_archived: false,
+                id: 0,
+                title: title.into(),
+                emoticon: None,
+                color: None,
+                pinned_peers: Vec::new(),
+                include_peers: Vec::new(),
+                exclude_peers: Vec::new(),
+            },
+            shared: None,
+        }
+    }
+
+    pub(crate) fn to_raw(&self, id: i32) -> tl::enums::DialogFilter {
+        let raw = self.raw.clone();
+        match self.shared {
+            Some(has_my_invites) => tl::types::DialogFilterChatlist {
+                has_my_invites,
+                id,
+                title: raw.title,
+                emoticon: raw.emoticon,
+                color: raw.color,
+                pinned_peers: raw.pinned_peers,
+                include_peers: raw.include_peers,
+            }
+            .into(),
+            None => tl::types::DialogFilter { id, ..raw }.into(),
+        }
+    }
+
+    /// Change the name of the folder.
+    pub fn title<T: Into<String>>(mut self, title: T) -> Self {
+        self.raw.title = title.into();
+        self
+    }
+
+    /// Change the emoji used as the icon of the folder.
+    pub fn emoticon<T: Into<String>>(mut self, emoticon: Option<T>) -> Self {
+        self.raw.emoticon = emoticon.map(Into::into);
+        self
+    }
+
+    /// Whether to include the private conversations with contacts.
+    ///
+    /// This and the rest of the types of chat, as well as the excluded chats, are ignored by
+    /// shared folders.
+    pub fn contacts(mut self, include: bool) -> Self {
+        self.raw.contacts = include;
+        self
+    }
+
+    /// Whether to include the private conversations with users who are not contacts.
+    pub fn non_contacts(mut self, include: bool) -> Self {
+        self.raw.non_contacts = include;
+        self
+    }
+
+    /// Whether to include groups.
+    pub fn groups(mut self, include: bool) -> Self {
+        self.raw.groups = include;
+        self
+    }
+
+    /// Whether to include broadcast channels.
+    pub fn broadcasts(mut self, include: bool) -> Self {
+        self.raw.broadcasts = include;
+        self
+    }
+
+    /// Whether to include the private conversations with bots.
+    pub fn bots(mut self, include: bool) -> Self {
+        self.raw.bots = include;
+        self
+    }
+
+    /// Whether to leave out the muted chats.
+    pub fn exclude_muted(mut self, exclude: bool) -> Self {
+        self.raw.exclude_muted = exclude;
+        self
+    }
+
+    /// Whether to leave out the chats without unread messages.
+    pub fn exclude_read(mut self, exclude: bool) -> Self {
+        self.raw.exclude_read = exclude;
+        self
+    }
+
+    /// Whether to leave out the archived chats.
+    pub fn exclude_archived(mut self, exclude: bool) -> Self {
+        self.raw.exclude_archived = exclude;
+        self
+    }
+
+    /// Include a specific chat in the folder, pinned to the top.
+    pub fn pin<C: Into<PackedChat>>(mut self, chat: C) -> Self {
+        self.raw.pinned_peers.push(chat.into().to_input_peer());
+        self
+    }
+
+    /// Include a specific chat in the folder, regardless of the types of chat included.
+    pub fn include<C: Into<PackedChat>>(mut self, chat: C) -> Self {
+        self.raw.include_peers.push(chat.into().to_input_peer());
+        self
+    }
+
+    /// Leave a specific chat out of the folder, regardless of the types of chat included.
+    pub fn exclude<C: Into<PackedChat>>(mut self, chat: C) -> Self {
+        self.raw.exclude_peers.push(chat.into().to_input_peer());
+        self
+    }
+}
+
+impl From<Folder> for FolderBuilder {
+    /// Start from the current configuration of an existing folder, to edit it.
+    fn from(folder: Folder) -> Self {
+        match folder.raw {
+            tl::enums::DialogFilter::Filter(raw) => Self { raw, shared: None },
+            tl::enums::DialogFilter::Chatlist(f) => {
+                let mut builder = Self::new(f.title).emoticon(f.emoticon);
+                builder.raw.color = f.color;
+                builder.raw.pinned_peers = f.pinned_peers;
+                builder.raw.include_peers = f.include_peers;
+                builder.shared = Some(f.has_my_invites);
+                builder
+            }
+            tl::enums::DialogFilter::Default => Self::new(String::new()),
+        }
+    }
+}
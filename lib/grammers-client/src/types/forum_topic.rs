@@ -0,0 +1,86 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::utils;
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+
+/// A topic inside of a supergroup with forum topics enabled.
+///
+/// Messages can be sent into a topic with [`InputMessage::topic`].
+///
+/// [`InputMessage::topic`]: crate::InputMessage::topic
+#[derive(Clone, Debug)]
+pub struct ForumTopic {
+    pub raw: tl::types::ForumTopic,
+}
+
+impl ForumTopic {
+    pub(crate) fn from_raw(topic: tl::enums::ForumTopic) -> Option<Self> {
+        match topic {
+            tl::enums::ForumTopic::Topic(raw) => Some(Self { raw }),
+            tl::enums::ForumTopic::Deleted(_) => None,
+        }
+    }
+
+    /// Unique identifier of the topic within its chat.
+    ///
+    /// This is also the identifier of the service message that created the topic.
+    pub fn id(&self) -> i32 {
+        self.raw.id
+    }
+
+    /// The title of the topic.
+    pub fn title(&self) -> &str {
+        &self.raw.title
+    }
+
+    /// The date when the topic was created.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// The color of the topic icon, in RGB format.
+    pub fn icon_color(&self) -> i32 {
+        self.raw.icon_color
+    }
+
+    /// The identifier of the custom emoji used as the topic icon, if any.
+    pub fn icon_emoji_id(&self) -> Option<i64> {
+        self.raw.icon_emoji_id
+    }
+
+    /// Whether the topic was created by the logged-in user.
+    pub fn is_mine(&self) -> bool {
+        self.raw.my
+    }
+
+    /// Whether the topic is closed, meaning only administrators can send messages to it.
+    pub fn closed(&self) -> bool {
+        self.raw.closed
+    }
+
+    /// Whether the topic is pinned.
+    pub fn pinned(&self) -> bool {
+        self.raw.pinned
+    }
+
+    /// Whether the topic is hidden. Only the "General" topic can be hidden.
+    pub fn hidden(&self) -> bool {
+        self.raw.hidden
+    }
+
+    /// The identifier of the last message sent to the topic.
+    pub fn top_message(&self) -> i32 {
+        self.raw.top_message
+    }
+
+    /// How many messages in the topic have not been read yet.
+    pub fn unread_count(&self) -> i32 {
+        self.raw.unread_count
+    }
+}
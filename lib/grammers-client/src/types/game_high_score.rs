@@ -0,0 +1,42 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::types::User;
+use grammers_tl_types as tl;
+
+/// An entry in the high score table of a game.
+#[derive(Clone, Debug)]
+pub struct GameHighScore {
+    pub raw: tl::types::HighScore,
+    user: Option<User>,
+}
+
+impl GameHighScore {
+    pub(crate) fn from_raw(raw: tl::types::HighScore, user: Option<User>) -> Self {
+        Self { raw, user }
+    }
+
+    /// The position of the entry in the table, starting at 1.
+    pub fn position(&self) -> i32 {
+        self.raw.pos
+    }
+
+    /// The score achieved by the user.
+    pub fn score(&self) -> i32 {
+        self.raw.score
+    }
+
+    /// The identifier of the user who achieved the score.
+    pub fn user_id(&self) -> i64 {
+        self.raw.user_id
+    }
+
+    /// The user who achieved the score, if Telegram included them.
+    pub fn user(&self) -> Option<&User> {
+        self.user.as_ref()
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::types::Chat;
+use crate::utils;
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+
+/// The connection to a group call (also known as voice chat) joined by the logged-in user.
+///
+/// The media must be sent by a WebRTC stack configured with the [`transport`](Self::transport)
+/// parameters returned by Telegram.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupCallConnection {
+    pub call: tl::enums::InputGroupCall,
+    pub(crate) params: String,
+}
+
+/// A participant in a group call.
+#[derive(Clone, Debug)]
+pub struct GroupCallParticipant {
+    pub raw: tl::types::GroupCallParticipant,
+    pub(crate) chat: Option<Chat>,
+}
+
+impl GroupCallConnection {
+    /// The JSON describing the WebRTC transport to use, such as the ICE candidates and
+    /// fingerprints of the server.
+    pub fn transport(&self) -> &str {
+        &self.params
+    }
+}
+
+impl GroupCallParticipant {
+    pub(crate) fn from_raw(
+        participant: tl::enums::GroupCallParticipant,
+        chat: Option<Chat>,
+    ) -> Self {
+        let tl::enums::GroupCallParticipant::Participant(raw) = participant;
+        Self { raw, chat }
+    }
+
+    /// The user or channel participating in the call, if it was sent by Telegram.
+    pub fn chat(&self) -> Option<&Chat> {
+        self.chat.as_ref()
+    }
+
+    /// The peer participating in the call.
+    pub fn peer(&self) -> &tl::enums::Peer {
+        &self.raw.peer
+    }
+
+    /// The date when the participant joined the call.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// The audio source identifier (SSRC) used by the participant.
+    pub fn source(&self) -> i32 {
+        self.raw.source
+    }
+
+    /// Whether the participant is muted.
+    pub fn is_muted(&self) -> bool {
+        self.raw.muted
+    }
+
+    /// Whether the participant is allowed to unmute themselves.
+    pub fn can_self_unmute(&self) -> bool {
+        self.raw.can_self_unmute
+    }
+
+    /// Whether this participant is the logged-in user.
+    pub fn is_self(&self) -> bool {
+        self.raw.is_self
+    }
+
+    /// The volume of the participant, where 10000 means 100%.
+    pub fn volume(&self) -> Option<i32> {
+        self.raw.volume
+    }
+
+    /// Whether the participant raised their hand to ask to speak.
+    pub fn has_raised_hand(&self) -> bool {
+        self.raw.raise_hand_rating.is_some()
+    }
+}
@@ -6,7 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use super::super::{Chat, ChatMap, User};
+use super::super::{media::Document, Chat, ChatMap, Photo, User};
 use crate::{client::Client, utils::generate_random_id, InputMessage};
 use grammers_mtsender::InvocationError;
 use grammers_tl_types as tl;
@@ -32,8 +32,67 @@ pub struct Answer {
 ///
 /// The following types implement [`Into<InlineResult>`]:
 /// - [`Article`]
+/// - [`PhotoResult`]
+/// - [`DocumentResult`]
+///
+/// Raw [`tl::enums::InputBotInlineResult`] values, and each of its [`tl::types`], can also be
+/// converted for results not covered by the builders above.
 pub struct InlineResult(tl::enums::InputBotInlineResult);
 
+impl InlineResult {
+    /// Build a new article result, which sends the given message when picked.
+    ///
+    /// See [`Article`] for further configuration options.
+    pub fn article<S: Into<String>, M: Into<InputMessage>>(title: S, input_message: M) -> Article {
+        Article::new(title, input_message)
+    }
+
+    /// Build a new result which sends a photo already stored in Telegram's servers when picked.
+    ///
+    /// See [`PhotoResult`] for further configuration options.
+    pub fn photo(photo: &Photo) -> PhotoResult {
+        PhotoResult::new(photo)
+    }
+
+    /// Build a new result which sends a document already stored in Telegram's servers when
+    /// picked.
+    ///
+    /// See [`DocumentResult`] for further configuration options.
+    pub fn document<S: Into<String>>(title: S, document: &Document) -> DocumentResult {
+        DocumentResult::new(title, document)
+    }
+}
+
+impl From<tl::enums::InputBotInlineResult> for InlineResult {
+    fn from(result: tl::enums::InputBotInlineResult) -> Self {
+        Self(result)
+    }
+}
+
+impl From<tl::types::InputBotInlineResult> for InlineResult {
+    fn from(result: tl::types::InputBotInlineResult) -> Self {
+        Self(result.into())
+    }
+}
+
+impl From<tl::types::InputBotInlineResultPhoto> for InlineResult {
+    fn from(result: tl::types::InputBotInlineResultPhoto) -> Self {
+        Self(result.into())
+    }
+}
+
+impl From<tl::types::InputBotInlineResultDocument> for InlineResult {
+    fn from(result: tl::types::InputBotInlineResultDocument) -> Self {
+        Self(result.into())
+    }
+}
+
+impl From<tl::types::InputBotInlineResultGame> for InlineResult {
+    fn from(result: tl::types::InputBotInlineResultGame) -> Self {
+        Self(result.into())
+    }
+}
+
 impl From<InlineResult> for tl::enums::InputBotInlineResult {
     fn from(result: InlineResult) -> Self {
         result.0
@@ -81,17 +140,35 @@ impl InlineQuery {
     }
 
     /// Answer the inline query.
-    // TODO: add example
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(query: grammers_client::types::InlineQuery) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::inline::query::InlineResult;
+    ///
+    /// query
+    ///     .answer([
+    ///         InlineResult::article("Hello", "Hello, world!"),
+    ///         InlineResult::article("Bye", "Goodbye, world!"),
+    ///     ])
+    ///     .cache_time(60)
+    ///     .switch_pm("Open a private chat", "inline")
+    ///     .send()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn answer<T>(&self, results: impl IntoIterator<Item = T>) -> Answer
     where
-        T: Into<tl::enums::InputBotInlineResult>,
+        T: Into<InlineResult>,
     {
         Answer {
             request: tl::functions::messages::SetInlineBotResults {
                 gallery: false,
                 private: false,
                 query_id: self.raw.query_id,
-                results: results.into_iter().map(Into::into).collect(),
+                results: results.into_iter().map(|r| r.into().0).collect(),
                 cache_time: 0,
                 next_offset: None,
                 switch_pm: None,
@@ -242,6 +319,127 @@ impl From<Article> for InlineResult {
     }
 }
 
+/// Inline result for a photo already stored in Telegram's servers.
+///
+/// See [`InlineResult::photo`] for usage.
+pub struct PhotoResult {
+    id: Option<String>,
+    photo: tl::enums::InputPhoto,
+    input_message: InputMessage,
+}
+
+impl PhotoResult {
+    pub fn new(photo: &Photo) -> Self {
+        Self {
+            id: None,
+            photo: photo.to_raw_input_media().id,
+            input_message: InputMessage::default(),
+        }
+    }
+
+    /// Unique identifier of the result.
+    ///
+    /// By default, a random string will be used.
+    pub fn id(mut self, result_id: impl Into<String>) -> Self {
+        self.id = Some(result_id.into());
+        self
+    }
+
+    /// The caption (along with its formatting entities and reply markup) to send with the photo.
+    ///
+    /// Any media in the message is ignored.
+    pub fn caption<M: Into<InputMessage>>(mut self, input_message: M) -> Self {
+        self.input_message = input_message.into();
+        self
+    }
+}
+
+impl From<PhotoResult> for InlineResult {
+    fn from(photo: PhotoResult) -> Self {
+        Self(tl::enums::InputBotInlineResult::Photo(
+            tl::types::InputBotInlineResultPhoto {
+                id: photo.id.unwrap_or_else(|| generate_random_id().to_string()),
+                r#type: "photo".into(),
+                photo: photo.photo,
+                send_message: media_auto(photo.input_message),
+            },
+        ))
+    }
+}
+
+/// Inline result for a document already stored in Telegram's servers.
+///
+/// See [`InlineResult::document`] for usage.
+pub struct DocumentResult {
+    id: Option<String>,
+    title: String,
+    description: Option<String>,
+    document: tl::enums::InputDocument,
+    input_message: InputMessage,
+}
+
+impl DocumentResult {
+    pub fn new<S: Into<String>>(title: S, document: &Document) -> Self {
+        Self {
+            id: None,
+            title: title.into(),
+            description: None,
+            document: document.to_raw_input_media().id,
+            input_message: InputMessage::default(),
+        }
+    }
+
+    /// Unique identifier of the result.
+    ///
+    /// By default, a random string will be used.
+    pub fn id(mut self, result_id: impl Into<String>) -> Self {
+        self.id = Some(result_id.into());
+        self
+    }
+
+    /// Short description of the result.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// The caption (along with its formatting entities and reply markup) to send with the
+    /// document.
+    ///
+    /// Any media in the message is ignored.
+    pub fn caption<M: Into<InputMessage>>(mut self, input_message: M) -> Self {
+        self.input_message = input_message.into();
+        self
+    }
+}
+
+impl From<DocumentResult> for InlineResult {
+    fn from(document: DocumentResult) -> Self {
+        Self(tl::enums::InputBotInlineResult::Document(
+            tl::types::InputBotInlineResultDocument {
+                id: document
+                    .id
+                    .unwrap_or_else(|| generate_random_id().to_string()),
+                r#type: "file".into(),
+                title: Some(document.title),
+                description: document.description,
+                document: document.document,
+                send_message: media_auto(document.input_message),
+            },
+        ))
+    }
+}
+
+/// Send the result's media using the input message as the caption.
+fn media_auto(input_message: InputMessage) -> tl::enums::InputBotInlineMessage {
+    tl::enums::InputBotInlineMessage::MediaAuto(tl::types::InputBotInlineMessageMediaAuto {
+        invert_media: input_message.invert_media,
+        message: input_message.text,
+        entities: Some(input_message.entities),
+        reply_markup: input_message.reply_markup,
+    })
+}
+
 impl fmt::Debug for InlineQuery {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("InlineQuery")
@@ -264,3 +462,23 @@ impl fmt::Debug for Article {
             .finish()
     }
 }
+
+impl fmt::Debug for PhotoResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PhotoResult")
+            .field("id", &self.id)
+            .field("photo", &self.photo)
+            .finish()
+    }
+}
+
+impl fmt::Debug for DocumentResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DocumentResult")
+            .field("id", &self.id)
+            .field("title", &self.title)
+            .field("description", &self.description)
+            .field("document", &self.document)
+            .finish()
+    }
+}
@@ -6,7 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 use super::attributes::Attribute;
-use crate::types::{Media, Uploaded};
+use crate::types::{InputMessage, Media, Uploaded};
 use grammers_tl_types as tl;
 
 /// Construct and send albums.
@@ -15,6 +15,7 @@ pub struct InputMedia {
     pub(crate) entities: Vec<tl::enums::MessageEntity>,
     pub(crate) reply_to: Option<i32>,
     pub(crate) caption: String,
+    pub(crate) topic: Option<i32>,
     pub(crate) media: Option<tl::enums::InputMedia>,
     media_ttl: Option<i32>,
     mime_type: Option<String>,
@@ -37,6 +38,14 @@ impl InputMedia {
         self
     }
 
+    /// The forum topic to which this album should be sent, if any.
+    ///
+    /// Only the topic from the first media is used.
+    pub fn topic(mut self, top_msg_id: Option<i32>) -> Self {
+        self.topic = top_msg_id;
+        self
+    }
+
     /// Include the uploaded file as a photo in the album.
     ///
     /// The Telegram server will compress the image and convert it to JPEG format if necessary.
@@ -283,3 +292,20 @@ impl InputMedia {
         }
     }
 }
+
+/// Use an [`InputMessage`] as part of an album.
+///
+/// Only the text (as the caption), formatting entities, media, message to reply to and topic
+/// are kept.
+impl From<InputMessage> for InputMedia {
+    fn from(message: InputMessage) -> Self {
+        Self {
+            entities: message.entities,
+            reply_to: message.reply_to,
+            caption: message.text,
+            topic: message.topic,
+            media: message.media,
+            ..Self::default()
+        }
+    }
+}
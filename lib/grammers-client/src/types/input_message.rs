@@ -7,8 +7,9 @@
 // except according to those terms.
 use super::attributes::Attribute;
 use crate::types::{Media, ReplyMarkup, Uploaded};
+use chrono::{DateTime, Utc};
 use grammers_tl_types as tl;
-use web_time::{SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
 // https://github.com/telegramdesktop/tdesktop/blob/e7fbcce9d9f0a8944eb2c34e74bd01b8776cb891/Telegram/SourceFiles/data/data_scheduled_messages.h#L52
 const SCHEDULE_ONCE_ONLINE: i32 = 0x7ffffffe;
@@ -26,6 +27,7 @@ pub struct InputMessage {
     pub(crate) schedule_date: Option<i32>,
     pub(crate) silent: bool,
     pub(crate) text: String,
+    pub(crate) topic: Option<i32>,
     pub(crate) media: Option<tl::enums::InputMedia>,
     media_ttl: Option<i32>,
     mime_type: Option<String>,
@@ -91,18 +93,41 @@ impl InputMessage {
         self
     }
 
+    /// The forum topic to which this message should be sent, if any.
+    ///
+    /// Topics are identified by the ID of the message that created them (see
+    /// [`ForumTopic::id`]). Otherwise, the message will be sent to the "General" topic of chats
+    /// with topics enabled.
+    ///
+    /// [`ForumTopic::id`]: crate::types::ForumTopic::id
+    pub fn topic(mut self, top_msg_id: Option<i32>) -> Self {
+        self.topic = top_msg_id;
+        self
+    }
+
     /// If set to a distant enough future time, the message won't be sent immediately,
     /// and instead it will be scheduled to be automatically sent at a later time.
     ///
     /// This scheduling is done server-side, and may not be accurate to the second.
     ///
+    /// Messages which are already scheduled can be managed with methods such as
+    /// [`Client::get_scheduled_messages`].
+    ///
     /// Bot accounts cannot schedule messages.
-    pub fn schedule_date(mut self, schedule_date: Option<SystemTime>) -> Self {
-        self.schedule_date = schedule_date.map(|t| {
-            t.duration_since(UNIX_EPOCH)
-                .map(|d| d.as_secs() as i32)
-                .unwrap_or(0)
-        });
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{Duration, Utc};
+    /// use grammers_client::InputMessage;
+    ///
+    /// let message = InputMessage::text("Good morning!")
+    ///     .schedule_date(Some(Utc::now() + Duration::hours(8)));
+    /// ```
+    ///
+    /// [`Client::get_scheduled_messages`]: crate::Client::get_scheduled_messages
+    pub fn schedule_date(mut self, schedule_date: Option<DateTime<Utc>>) -> Self {
+        self.schedule_date = schedule_date.map(|date| date.timestamp() as i32);
         self
     }
 
@@ -187,6 +212,24 @@ impl InputMessage {
         self
     }
 
+    /// Include a game of the logged-in bot in the message, given its short name.
+    ///
+    /// Games are created with [@BotFather](https://t.me/BotFather), and can only be sent by
+    /// their bot. The text of the message is ignored.
+    pub fn game<S: Into<String>>(mut self, short_name: S) -> Self {
+        self.media = Some(
+            (tl::types::InputMediaGame {
+                id: tl::types::InputGameShortName {
+                    bot_id: tl::enums::InputUser::UserSelf,
+                    short_name: short_name.into(),
+                }
+                .into(),
+            })
+            .into(),
+        );
+        self
+    }
+
     /// Include a media in the message using the raw TL types.
     ///
     /// You can use this to send any media using the raw TL types that don't have
@@ -205,6 +248,10 @@ impl InputMessage {
     ///
     /// The text will be the caption of the document, which may be empty for no caption.
     ///
+    /// In-memory thumbnails can be uploaded with [`Client::upload_thumbnail`].
+    ///
+    /// [`Client::upload_thumbnail`]: crate::Client::upload_thumbnail
+    ///
     /// # Examples
     ///
     /// ```
@@ -273,6 +320,232 @@ impl InputMessage {
         self
     }
 
+    /// Mark the file included so far as a voice note with the given duration.
+    ///
+    /// The waveform, if any, is the 5-bit encoded volume of the recording displayed by clients.
+    /// This must be called *after* setting a file, and replaces any previous audio attribute.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: &mut grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::time::Duration;
+    /// use grammers_client::InputMessage;
+    ///
+    /// let voice = client.upload_file("voice.ogg").await?;
+    /// let message = InputMessage::text("")
+    ///     .document(voice)
+    ///     .voice_note(Duration::from_secs(5), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn voice_note(self, duration: Duration, waveform: Option<Vec<u8>>) -> Self {
+        self.replace_attribute(Attribute::Voice { duration, waveform })
+    }
+
+    /// Mark the file included so far as a round video note with the given duration, and whose
+    /// width and height are both `size`.
+    ///
+    /// This must be called *after* setting a file, and replaces any previous video attribute.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(client: &mut grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use std::time::Duration;
+    /// use grammers_client::InputMessage;
+    ///
+    /// let video = client.upload_file("round.mp4").await?;
+    /// let message = InputMessage::text("")
+    ///     .document(video)
+    ///     .video_note(Duration::from_secs(10), 240);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn video_note(self, duration: Duration, size: i32) -> Self {
+        self.replace_attribute(Attribute::Video {
+            round_message: true,
+            supports_streaming: false,
+            duration,
+            w: size,
+            h: size,
+        })
+    }
+
+    /// Add the attribute to the file, removing any previous attribute of the same kind.
+    fn replace_attribute(mut self, attr: Attribute) -> Self {
+        if let Some(tl::enums::InputMedia::UploadedDocument(document)) = &mut self.media {
+            let attr = tl::enums::DocumentAttribute::from(attr);
+            document
+                .attributes
+                .retain(|a| std::mem::discriminant(a) != std::mem::discriminant(&attr));
+            document.attributes.push(attr);
+        }
+        self
+    }
+
+    /// Turn the media included so far into paid media, which users need to unlock by paying the
+    /// given amount of Telegram Stars.
+    ///
+    /// Paid media can only be sent to channels. This method should be called after setting the
+    /// media, else it won't have any effect.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(channel: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::InputMessage;
+    ///
+    /// let photo = client.upload_file("exclusive.jpg").await?;
+    /// let message = InputMessage::text("Unlock to see!").photo(photo).paid_media(25);
+    /// client.send_message(&channel, message).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn paid_media(mut self, stars_amount: i64) -> Self {
+        self.media = self.media.take().map(|media| match media {
+            tl::enums::InputMedia::PaidMedia(mut paid) => {
+                paid.stars_amount = stars_amount;
+                paid.into()
+            }
+            media => tl::types::InputMediaPaidMedia {
+                stars_amount,
+                extended_media: vec![media],
+            }
+            .into(),
+        });
+        self
+    }
+
+    /// Include a poll in the message with the given question and answer options.
+    ///
+    /// Polls allow a single answer and keep voters anonymous by default. Use
+    /// [`InputMessage::multiple_choice`], [`InputMessage::public_voters`] and
+    /// [`InputMessage::quiz`] *after* this method to change that.
+    ///
+    /// Text is ignored for polls, so the message text may be empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(chat: grammers_client::types::Chat, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::InputMessage;
+    ///
+    /// let message = InputMessage::text("")
+    ///     .poll("Which one is the largest?", ["Mercury", "Jupiter", "Mars"])
+    ///     .quiz(1)
+    ///     .quiz_solution("Jupiter is more than twice as massive as the rest combined.");
+    ///
+    /// client.send_message(&chat, message).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn poll<Q, O, I>(mut self, question: Q, options: I) -> Self
+    where
+        Q: Into<String>,
+        O: Into<String>,
+        I: IntoIterator<Item = O>,
+    {
+        let answers = options
+            .into_iter()
+            .enumerate()
+            .map(|(i, text)| {
+                tl::types::PollAnswer {
+                    text: tl::types::TextWithEntities {
+                        text: text.into(),
+                        entities: Vec::new(),
+                    }
+                    .into(),
+                    option: tl::byte_string(vec![i as u8]),
+                }
+                .into()
+            })
+            .collect();
+
+        self.media = Some(
+            (tl::types::InputMediaPoll {
+                poll: tl::types::Poll {
+                    id: 0,
+                    closed: false,
+                    public_voters: false,
+                    multiple_choice: false,
+                    quiz: false,
+                    question: tl::types::TextWithEntities {
+                        text: question.into(),
+                        entities: Vec::new(),
+                    }
+                    .into(),
+                    answers,
+                    close_period: None,
+                    close_date: None,
+                }
+                .into(),
+                correct_answers: None,
+                solution: None,
+                solution_entities: None,
+            })
+            .into(),
+        );
+        self
+    }
+
+    /// Whether the poll should allow voting for more than one option.
+    ///
+    /// This must be called *after* setting a poll, and cannot be used with quizzes.
+    pub fn multiple_choice(mut self, multiple_choice: bool) -> Self {
+        if let Some(poll) = self.raw_poll_mut() {
+            poll.multiple_choice = multiple_choice;
+        }
+        self
+    }
+
+    /// Whether the voters of the poll should be visible to everyone.
+    ///
+    /// This must be called *after* setting a poll, and cannot be used in channels.
+    pub fn public_voters(mut self, public_voters: bool) -> Self {
+        if let Some(poll) = self.raw_poll_mut() {
+            poll.public_voters = public_voters;
+        }
+        self
+    }
+
+    /// Turn the poll into a quiz, where the option at index `correct_option` is the right answer.
+    ///
+    /// This must be called *after* setting a poll.
+    pub fn quiz(mut self, correct_option: usize) -> Self {
+        if let Some(tl::enums::InputMedia::Poll(input)) = &mut self.media {
+            let tl::enums::Poll::Poll(poll) = &mut input.poll;
+            poll.quiz = true;
+            poll.multiple_choice = false;
+            input.correct_answers = poll.answers.get(correct_option).map(|answer| {
+                let tl::enums::PollAnswer::Answer(answer) = answer;
+                vec![answer.option.clone()]
+            });
+        }
+        self
+    }
+
+    /// The explanation shown to users who pick the wrong answer in a quiz.
+    ///
+    /// This must be called *after* setting a poll.
+    pub fn quiz_solution(mut self, solution: impl Into<String>) -> Self {
+        if let Some(tl::enums::InputMedia::Poll(input)) = &mut self.media {
+            input.solution = Some(solution.into());
+            input.solution_entities = Some(Vec::new());
+        }
+        self
+    }
+
+    fn raw_poll_mut(&mut self) -> Option<&mut tl::types::Poll> {
+        match &mut self.media {
+            Some(tl::enums::InputMedia::Poll(input)) => {
+                let tl::enums::Poll::Poll(poll) = &mut input.poll;
+                Some(poll)
+            }
+            _ => None,
+        }
+    }
+
     /// Copy media from an existing message.
     ///
     /// You can use this to send media from another message without re-uploading it.
@@ -402,6 +675,17 @@ impl From<String> for InputMessage {
     }
 }
 
+impl From<super::EntityText> for InputMessage {
+    fn from(text: super::EntityText) -> Self {
+        let (text, entities) = text.into_parts();
+        Self {
+            text,
+            entities,
+            ..Self::default()
+        }
+    }
+}
+
 impl From<&super::Message> for InputMessage {
     fn from(message: &super::Message) -> Self {
         Self {
@@ -0,0 +1,237 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::types::{Chat, User};
+use crate::{utils, ChatMap, Client};
+use chrono::{DateTime, Utc};
+use grammers_mtsender::InvocationError;
+use grammers_tl_types as tl;
+use std::fmt;
+use std::sync::Arc;
+
+/// An invite link to a group or channel.
+#[derive(Clone, Debug)]
+pub struct InviteLink {
+    pub raw: tl::types::ChatInviteExported,
+}
+
+/// An invite link builder, used to create new links with [`Client::create_invite_link`].
+///
+/// It will be executed once [`InviteLinkBuilder::send`] is called.
+pub struct InviteLinkBuilder {
+    client: Client,
+    request: tl::functions::messages::ExportChatInvite,
+}
+
+/// Represents a request by a user to join a chat through an invite link that requires approval.
+///
+/// Only bot accounts receive these as updates. You can [`JoinRequest::approve`] or
+/// [`JoinRequest::decline`] them.
+#[derive(Clone)]
+pub struct JoinRequest {
+    pub raw: tl::types::UpdateBotChatInviteRequester,
+    client: Client,
+    user: User,
+    chat: Chat,
+}
+
+impl InviteLink {
+    pub(crate) fn from_raw(invite: tl::enums::ExportedChatInvite) -> Option<Self> {
+        match invite {
+            tl::enums::ExportedChatInvite::ChatInviteExported(raw) => Some(Self { raw }),
+            tl::enums::ExportedChatInvite::ChatInvitePublicJoinRequests => None,
+        }
+    }
+
+    /// The link itself, in the form of `https://t.me/+hash`.
+    pub fn link(&self) -> &str {
+        &self.raw.link
+    }
+
+    /// The name given to the link, only visible to administrators.
+    pub fn title(&self) -> Option<&str> {
+        self.raw.title.as_deref()
+    }
+
+    /// The identifier of the administrator who created the link.
+    pub fn admin_id(&self) -> i64 {
+        self.raw.admin_id
+    }
+
+    /// The date when the link was created.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// The date when the link will stop working, if any.
+    pub fn expire_date(&self) -> Option<DateTime<Utc>> {
+        self.raw.expire_date.map(utils::date)
+    }
+
+    /// How many users can join through this link, if limited.
+    pub fn member_limit(&self) -> Option<i32> {
+        self.raw.usage_limit
+    }
+
+    /// How many users have joined through this link.
+    pub fn usage(&self) -> i32 {
+        self.raw.usage.unwrap_or(0)
+    }
+
+    /// How many users are waiting to be approved after requesting to join through this link.
+    pub fn pending_requests(&self) -> i32 {
+        self.raw.requested.unwrap_or(0)
+    }
+
+    /// Whether the link has been revoked and no longer works.
+    pub fn revoked(&self) -> bool {
+        self.raw.revoked
+    }
+
+    /// Whether this is the primary link of the chat, which never expires.
+    pub fn permanent(&self) -> bool {
+        self.raw.permanent
+    }
+
+    /// Whether users joining through this link must be approved by an administrator.
+    pub fn request_needed(&self) -> bool {
+        self.raw.request_needed
+    }
+}
+
+impl InviteLinkBuilder {
+    pub(crate) fn new(client: &Client, peer: tl::enums::InputPeer) -> Self {
+        Self {
+            client: client.clone(),
+            request: tl::functions::messages::ExportChatInvite {
+                legacy_revoke_permanent: false,
+                request_needed: false,
+                peer,
+                expire_date: None,
+                usage_limit: None,
+                title: None,
+                subscription_pricing: None,
+            },
+        }
+    }
+
+    /// The date when the link should stop working. By default, the link never expires.
+    pub fn expire(mut self, date: DateTime<Utc>) -> Self {
+        self.request.expire_date = Some(utils::timestamp(date));
+        self
+    }
+
+    /// How many users should be able to join through the link. By default, there is no limit.
+    ///
+    /// Cannot be used along with [`InviteLinkBuilder::request_needed`].
+    pub fn member_limit(mut self, limit: i32) -> Self {
+        self.request.usage_limit = Some(limit);
+        self
+    }
+
+    /// Whether users joining through the link must be approved by an administrator.
+    pub fn request_needed(mut self, request_needed: bool) -> Self {
+        self.request.request_needed = request_needed;
+        self
+    }
+
+    /// The name of the link, only visible to administrators.
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.request.title = Some(title.into());
+        self
+    }
+
+    /// Create the invite link.
+    pub async fn send(self) -> Result<InviteLink, InvocationError> {
+        let invite = self.client.invoke(&self.request).await?;
+        InviteLink::from_raw(invite)
+            .ok_or_else(utils::unexpected_response::<tl::types::ChatInvitePublicJoinRequests>)
+    }
+}
+
+impl JoinRequest {
+    /// Returns `None` if the user or the chat of the request are not in `chats`.
+    pub fn from_raw(
+        request: tl::types::UpdateBotChatInviteRequester,
+        client: &Client,
+        chats: &Arc<ChatMap>,
+    ) -> Option<Self> {
+        let user = match chats.get(
+            &tl::types::PeerUser {
+                user_id: request.user_id,
+            }
+            .into(),
+        )? {
+            Chat::User(user) => user.clone(),
+            _ => return None,
+        };
+        let chat = chats.get(&request.peer)?.clone();
+        Some(Self {
+            raw: request,
+            client: client.clone(),
+            user,
+            chat,
+        })
+    }
+
+    /// The user who wants to join.
+    pub fn user(&self) -> &User {
+        &self.user
+    }
+
+    /// The chat the user wants to join.
+    pub fn chat(&self) -> &Chat {
+        &self.chat
+    }
+
+    /// The bio of the user.
+    pub fn about(&self) -> &str {
+        &self.raw.about
+    }
+
+    /// The date when the request was made.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// The invite link that was used to make the request, if it's known.
+    pub fn invite(&self) -> Option<InviteLink> {
+        InviteLink::from_raw(self.raw.invite.clone())
+    }
+
+    /// Let the user join the chat.
+    pub async fn approve(&self) -> Result<(), InvocationError> {
+        self.hide(true).await
+    }
+
+    /// Refuse to let the user join the chat.
+    pub async fn decline(&self) -> Result<(), InvocationError> {
+        self.hide(false).await
+    }
+
+    async fn hide(&self, approved: bool) -> Result<(), InvocationError> {
+        self.client
+            .invoke(&tl::functions::messages::HideChatJoinRequest {
+                approved,
+                peer: self.chat().pack().to_input_peer(),
+                user_id: self.user().pack().to_input_user_lossy(),
+            })
+            .await
+            .map(drop)
+    }
+}
+
+impl fmt::Debug for JoinRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinRequest")
+            .field("user", &self.user())
+            .field("chat", &self.chat())
+            .field("about", &self.about())
+            .field("date", &self.date())
+            .finish()
+    }
+}
@@ -0,0 +1,111 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use grammers_tl_types as tl;
+
+/// The currency code used by invoices paid with Telegram Stars.
+pub const STARS_CURRENCY: &str = "XTR";
+
+/// An invoice which can be exported as a link through [`Client::export_invoice`].
+///
+/// [`Client::export_invoice`]: crate::Client::export_invoice
+#[derive(Clone, Debug)]
+pub struct Invoice {
+    raw: tl::types::InputMediaInvoice,
+}
+
+impl Invoice {
+    /// Create a new invoice for the given currency, paid through the given payment provider.
+    ///
+    /// The payload is not displayed to the user and can be used by the bot to identify the
+    /// invoice once it is paid. Prices should be added with [`Invoice::price`].
+    pub fn new<T: Into<String>, D: Into<String>, P: Into<Vec<u8>>, C: Into<String>>(
+        title: T,
+        description: D,
+        payload: P,
+        currency: C,
+        provider_token: Option<String>,
+    ) -> Self {
+        Self {
+            raw: tl::types::InputMediaInvoice {
+                title: title.into(),
+                description: description.into(),
+                photo: None,
+                invoice: tl::types::Invoice {
+                    test: false,
+                    name_requested: false,
+                    phone_requested: false,
+                    email_requested: false,
+                    shipping_address_requested: false,
+                    flexible: false,
+                    phone_to_provider: false,
+                    email_to_provider: false,
+                    recurring: false,
+                    currency: currency.into(),
+                    prices: Vec::new(),
+                    max_tip_amount: None,
+                    suggested_tip_amounts: None,
+                    terms_url: None,
+                }
+                .into(),
+                payload: tl::byte_string(payload.into()),
+                provider: provider_token,
+                provider_data: tl::types::DataJson {
+                    data: "{}".to_string(),
+                }
+                .into(),
+                start_param: None,
+                extended_media: None,
+            },
+        }
+    }
+
+    /// Create a new invoice to be paid with the given amount of Telegram Stars.
+    ///
+    /// No payment provider is needed for these invoices.
+    pub fn stars<T: Into<String>, D: Into<String>, P: Into<Vec<u8>>>(
+        title: T,
+        description: D,
+        payload: P,
+        amount: i64,
+    ) -> Self {
+        Self::new(title, description, payload, STARS_CURRENCY, None).price("Price", amount)
+    }
+
+    /// Add a price portion to the invoice, in the smallest units of the currency.
+    pub fn price<S: Into<String>>(mut self, label: S, amount: i64) -> Self {
+        let tl::enums::Invoice::Invoice(invoice) = &mut self.raw.invoice;
+        invoice.prices.push(
+            tl::types::LabeledPrice {
+                label: label.into(),
+                amount,
+            }
+            .into(),
+        );
+        self
+    }
+
+    /// Deep-linking parameter used when the invoice is forwarded and the user presses "Pay".
+    ///
+    /// When not set, forwarded copies of the invoice will show a "Pay" button which can be used
+    /// by anyone to pay for the same item.
+    pub fn start_param<S: Into<String>>(mut self, start_param: S) -> Self {
+        self.raw.start_param = Some(start_param.into());
+        self
+    }
+
+    /// Whether the invoice is for testing purposes.
+    pub fn test(mut self, test: bool) -> Self {
+        let tl::enums::Invoice::Invoice(invoice) = &mut self.raw.invoice;
+        invoice.test = test;
+        self
+    }
+
+    pub fn to_raw_input_media(&self) -> tl::enums::InputMedia {
+        self.raw.clone().into()
+    }
+}
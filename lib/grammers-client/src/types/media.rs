@@ -72,6 +72,7 @@ pub struct WebPage {
 
 #[derive(Clone, Debug, PartialEq)]
 #[non_exhaustive]
+#[allow(clippy::large_enum_variant)]
 pub enum Media {
     Photo(Photo),
     Document(Document),
@@ -443,6 +444,31 @@ impl Sticker {
     pub fn is_animated(&self) -> bool {
         self.animated
     }
+
+    /// Is this sticker a mask to be placed over photos?
+    pub fn is_mask(&self) -> bool {
+        self.raw_attrs.mask
+    }
+
+    /// The identifier of the sticker set this sticker belongs to, if any.
+    pub fn set_id(&self) -> Option<i64> {
+        match &self.raw_attrs.stickerset {
+            tl::enums::InputStickerSet::Id(set) => Some(set.id),
+            _ => None,
+        }
+    }
+
+    /// The short name of the sticker set this sticker belongs to, if it is known.
+    ///
+    /// Use [`Client::get_sticker_set_of`] to fetch the full set.
+    ///
+    /// [`Client::get_sticker_set_of`]: crate::Client::get_sticker_set_of
+    pub fn set_short_name(&self) -> Option<&str> {
+        match &self.raw_attrs.stickerset {
+            tl::enums::InputStickerSet::ShortName(set) => Some(&set.short_name),
+            _ => None,
+        }
+    }
 }
 
 impl Contact {
@@ -507,6 +533,11 @@ impl Poll {
         }
     }
 
+    /// Return the unique identifier of the poll
+    pub fn id(&self) -> i64 {
+        self.raw.id
+    }
+
     /// Return question of the poll
     pub fn question(&self) -> &grammers_tl_types::enums::TextWithEntities {
         &self.raw.question
@@ -522,6 +553,39 @@ impl Poll {
         self.raw.closed
     }
 
+    /// Whether voters may choose more than one option
+    pub fn multiple_choice(&self) -> bool {
+        self.raw.multiple_choice
+    }
+
+    /// Whether the list of voters is visible to everyone
+    pub fn public_voters(&self) -> bool {
+        self.raw.public_voters
+    }
+
+    /// Explanation of the correct answer to a quiz
+    ///
+    /// Only available once the results are known
+    pub fn solution(&self) -> Option<&str> {
+        self.raw_results.solution.as_deref()
+    }
+
+    /// Indices of the options chosen by the current user, if they voted
+    pub fn chosen_options(&self) -> Vec<usize> {
+        let chosen = match self.iter_voters_summary() {
+            Some(results) => results
+                .filter(|result| result.chosen)
+                .map(|result| &result.option[..])
+                .collect::<Vec<_>>(),
+            None => return Vec::new(),
+        };
+        self.iter_answers()
+            .enumerate()
+            .filter(|(_, answer)| chosen.contains(&&answer.option[..]))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
     /// Iterator over poll answer options
     pub fn iter_answers(&self) -> impl Iterator<Item = &tl::types::PollAnswer> {
         self.raw.answers.iter().map(|answer| match answer {
@@ -8,7 +8,10 @@
 #[cfg(any(feature = "markdown", feature = "html"))]
 use crate::parsers;
 use crate::types::reactions::InputReactions;
-use crate::types::{InputMessage, Media, Photo};
+use crate::types::{
+    media::{Poll, Sticker},
+    EntityText, InputMessage, Media, Photo,
+};
 use crate::ChatMap;
 use crate::{types, Client};
 use crate::{utils, InputMedia};
@@ -22,6 +25,7 @@ use types::Chat;
 
 #[cfg(feature = "fs")]
 use {
+    crate::client::files::MediaSource,
     crate::types::Downloadable,
     std::{io, path::Path},
 };
@@ -378,6 +382,15 @@ impl Message {
         }
     }
 
+    /// Like [`text`](Self::text), but along with the [`fmt_entities`](Self::fmt_entities),
+    /// so that the formatted text can be sliced or concatenated safely.
+    pub fn entity_text(&self) -> EntityText {
+        EntityText::new(
+            self.raw.message.clone(),
+            self.raw.entities.clone().unwrap_or_default(),
+        )
+    }
+
     /// The media displayed by this message, if any.
     ///
     /// This not only includes photos or videos, but also contacts, polls, documents, locations
@@ -467,6 +480,30 @@ impl Message {
         Ok(())
     }
 
+    /// Remove the reactions of the logged-in user from this message.
+    ///
+    /// Shorthand for reacting with [`InputReactions::remove`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message, client: grammers_client::Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// message.unreact().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn unreact(&self) -> Result<(), InvocationError> {
+        self.react(InputReactions::remove()).await
+    }
+
+    /// The reactions of this message, when applicable.
+    pub fn reactions(&self) -> Option<&tl::types::MessageReactions> {
+        self.raw
+            .reactions
+            .as_ref()
+            .map(|tl::enums::MessageReactions::Reactions(reactions)| reactions)
+    }
+
     /// How many reactions does this message have, when applicable.
     pub fn reaction_count(&self) -> Option<i32> {
         match &self.raw.reactions {
@@ -518,6 +555,17 @@ impl Message {
         self.raw_action.as_ref()
     }
 
+    /// If this message is a service message containing Telegram Passport data shared with the
+    /// bot, return said data.
+    pub fn passport_data(&self) -> Option<types::PassportData> {
+        match self.raw_action.as_ref() {
+            Some(tl::enums::MessageAction::SecureValuesSentMe(data)) => {
+                Some(types::PassportData::from_raw(data.clone()))
+            }
+            _ => None,
+        }
+    }
+
     /// If this message is replying to another message, return the replied message ID.
     pub fn reply_to_message_id(&self) -> Option<i32> {
         if let Some(tl::enums::MessageReplyHeader::Header(m)) = &self.raw.reply_to {
@@ -553,9 +601,9 @@ impl Message {
     /// replying to it.
     ///
     /// Shorthand for `Client::send_album`.
-    pub async fn respond_album(
+    pub async fn respond_album<M: Into<InputMedia>>(
         &self,
-        medias: Vec<InputMedia>,
+        medias: Vec<M>,
     ) -> Result<Vec<Option<Self>>, InvocationError> {
         self.client.send_album(&self.chat(), medias).await
     }
@@ -575,11 +623,17 @@ impl Message {
     /// it. This methods overrides the `reply_to` on the first `InputMedia` to point to `self`.
     ///
     /// Shorthand for `Client::send_album`.
-    pub async fn reply_album(
+    pub async fn reply_album<M: Into<InputMedia>>(
         &self,
-        mut medias: Vec<InputMedia>,
+        medias: Vec<M>,
     ) -> Result<Vec<Option<Self>>, InvocationError> {
-        medias.first_mut().unwrap().reply_to = Some(self.raw.id);
+        let mut medias = medias
+            .into_iter()
+            .map(Into::into)
+            .collect::<Vec<InputMedia>>();
+        if let Some(first) = medias.first_mut() {
+            first.reply_to = Some(self.raw.id);
+        }
         self.client.send_album(&self.chat(), medias).await
     }
 
@@ -597,6 +651,50 @@ impl Message {
             .map(|mut msgs| msgs.pop().unwrap().unwrap())
     }
 
+    /// Translate the text of this message into the given language, keeping its formatting.
+    ///
+    /// Shorthand for `Client::translate_messages`.
+    pub async fn translate(&self, to_lang: &str) -> Result<EntityText, InvocationError> {
+        self.client
+            .translate_messages(self.chat(), &[self.raw.id], to_lang)
+            .await
+            .map(|mut translated| translated.pop().unwrap_or_default())
+    }
+
+    /// Get which members of the group have read this message, along with the date when they did.
+    ///
+    /// Shorthand for `Client::get_message_read_participants`.
+    pub async fn get_read_participants(
+        &self,
+    ) -> Result<Vec<(i64, DateTime<Utc>)>, InvocationError> {
+        self.client
+            .get_message_read_participants(self.chat(), self.raw.id)
+            .await
+    }
+
+    /// Fetch the view, forward and reply counters of this message again, updating them in-place
+    /// and returning the new [`Message::view_count`].
+    ///
+    /// If `increment` is `true`, the message is also marked as viewed by the logged-in user.
+    ///
+    /// Shorthand for `Client::get_message_views`.
+    pub async fn refresh_view_count(
+        &mut self,
+        increment: bool,
+    ) -> Result<Option<i32>, InvocationError> {
+        if let Some(views) = self
+            .client
+            .get_message_views(self.chat(), &[self.raw.id], increment)
+            .await?
+            .pop()
+        {
+            self.raw.views = views.views;
+            self.raw.forwards = views.forwards;
+            self.raw.replies = views.replies;
+        }
+        Ok(self.raw.views)
+    }
+
     /// Edit this message to change its text or media.
     ///
     /// Shorthand for `Client::edit_message`.
@@ -644,9 +742,26 @@ impl Message {
 
     /// Pin this message in the chat.
     ///
-    /// Shorthand for `Client::pin_message`.
-    pub async fn pin(&self) -> Result<(), InvocationError> {
-        self.client.pin_message(&self.chat(), self.raw.id).await
+    /// By default, this will not notify any users. The returned builder can be used to change
+    /// that before calling [`Pin::send`].
+    ///
+    /// ```
+    /// # async fn f(message: grammers_client::types::Message) -> Result<(), Box<dyn std::error::Error>> {
+    /// message.pin().notify(true).send().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pin(&self) -> Pin<'_> {
+        Pin {
+            message: self,
+            request: tl::functions::messages::UpdatePinnedMessage {
+                silent: true,
+                unpin: false,
+                pm_oneside: false,
+                peer: self.chat().pack().to_input_peer(),
+                id: self.raw.id,
+            },
+        }
     }
 
     /// Unpin this message from the chat.
@@ -678,12 +793,19 @@ impl Message {
     /// Returns `true` if there was media to download, or `false` otherwise.
     ///
     /// Shorthand for `Client::download_media`.
+    ///
+    /// If the file reference of the media expires during the download, this message is fetched
+    /// again to refresh it, so that long downloads can complete.
     #[cfg(feature = "fs")]
     pub async fn download_media<P: AsRef<Path>>(&self, path: P) -> Result<bool, io::Error> {
         // TODO probably encode failed download in error
         if let Some(media) = self.media() {
+            let source = MediaSource::Message {
+                chat: self.chat().pack(),
+                message_id: self.id(),
+            };
             self.client
-                .download_media(&Downloadable::Media(media), path)
+                .download_media_from(&Downloadable::Media(media), path, Some(source))
                 .await
                 .map(|_| true)
         } else {
@@ -699,6 +821,53 @@ impl Message {
 
         None
     }
+
+    /// Returns the sticker inside the message, if it has one.
+    pub fn sticker(&self) -> Option<Sticker> {
+        if let Media::Sticker(sticker) = self.media()? {
+            return Some(sticker);
+        }
+
+        None
+    }
+
+    /// Returns the poll inside the message, if it has one, along with its current results.
+    pub fn poll(&self) -> Option<Poll> {
+        if let Media::Poll(poll) = self.media()? {
+            return Some(poll);
+        }
+
+        None
+    }
+}
+
+/// A pinned message builder.
+///
+/// It will be executed once [`Pin::send`] is called.
+pub struct Pin<'a> {
+    message: &'a Message,
+    request: tl::functions::messages::UpdatePinnedMessage,
+}
+
+impl Pin<'_> {
+    /// Whether the members of the chat should be notified about the pinned message.
+    pub fn notify(mut self, notify: bool) -> Self {
+        self.request.silent = !notify;
+        self
+    }
+
+    /// Whether the message should only be pinned for the logged-in user.
+    ///
+    /// This only has an effect in private conversations.
+    pub fn pm_oneside(mut self, oneside: bool) -> Self {
+        self.request.pm_oneside = oneside;
+        self
+    }
+
+    /// Pin the message.
+    pub async fn send(self) -> Result<(), InvocationError> {
+        self.message.client.invoke(&self.request).await.map(drop)
+    }
 }
 
 impl fmt::Debug for Message {
@@ -0,0 +1,82 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use grammers_tl_types as tl;
+
+/// The kind of messages a search should return.
+///
+/// Used by [`SearchIter::filter`] and [`GlobalSearchIter::filter`].
+///
+/// [`SearchIter::filter`]: crate::client::messages::SearchIter::filter
+/// [`GlobalSearchIter::filter`]: crate::client::messages::GlobalSearchIter::filter
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MessageFilter {
+    /// Every message, regardless of its media.
+    #[default]
+    Empty,
+    /// Only messages with a photo.
+    Photos,
+    /// Only messages with a video.
+    Videos,
+    /// Only messages with either a photo or a video.
+    PhotosAndVideos,
+    /// Only messages with a document.
+    Documents,
+    /// Only messages containing links.
+    Urls,
+    /// Only messages with an animation.
+    Gifs,
+    /// Only voice notes.
+    VoiceNotes,
+    /// Only messages with music.
+    Music,
+    /// Only service messages changing the chat photo.
+    ChatPhotos,
+    /// Only phone calls, optionally restricted to the missed ones.
+    PhoneCalls { missed: bool },
+    /// Only voice notes and round videos.
+    RoundVoice,
+    /// Only round videos.
+    RoundVideos,
+    /// Only messages mentioning the logged-in user.
+    Mentions,
+    /// Only messages with a location.
+    Geo,
+    /// Only messages with a shared contact.
+    Contacts,
+    /// Only pinned messages.
+    Pinned,
+}
+
+impl From<MessageFilter> for tl::enums::MessagesFilter {
+    fn from(filter: MessageFilter) -> Self {
+        use tl::enums::MessagesFilter as Filter;
+
+        match filter {
+            MessageFilter::Empty => Filter::InputMessagesFilterEmpty,
+            MessageFilter::Photos => Filter::InputMessagesFilterPhotos,
+            MessageFilter::Videos => Filter::InputMessagesFilterVideo,
+            MessageFilter::PhotosAndVideos => Filter::InputMessagesFilterPhotoVideo,
+            MessageFilter::Documents => Filter::InputMessagesFilterDocument,
+            MessageFilter::Urls => Filter::InputMessagesFilterUrl,
+            MessageFilter::Gifs => Filter::InputMessagesFilterGif,
+            MessageFilter::VoiceNotes => Filter::InputMessagesFilterVoice,
+            MessageFilter::Music => Filter::InputMessagesFilterMusic,
+            MessageFilter::ChatPhotos => Filter::InputMessagesFilterChatPhotos,
+            MessageFilter::PhoneCalls { missed } => {
+                tl::types::InputMessagesFilterPhoneCalls { missed }.into()
+            }
+            MessageFilter::RoundVoice => Filter::InputMessagesFilterRoundVoice,
+            MessageFilter::RoundVideos => Filter::InputMessagesFilterRoundVideo,
+            MessageFilter::Mentions => Filter::InputMessagesFilterMyMentions,
+            MessageFilter::Geo => Filter::InputMessagesFilterGeo,
+            MessageFilter::Contacts => Filter::InputMessagesFilterContacts,
+            MessageFilter::Pinned => Filter::InputMessagesFilterPinned,
+        }
+    }
+}
@@ -11,54 +11,109 @@
 //! A lot of fields in the types exported from this module are currently public even though
 //! they directly uses `grammers-tl-types`. This will probably change before the 1.0 release.
 pub mod action;
+pub mod admin_log;
 pub mod attributes;
+pub mod boost;
+pub mod bot_command;
 pub mod button;
 pub mod callback_query;
 pub mod chat;
 pub mod chat_map;
 pub mod chats;
+pub mod contact;
 pub mod dialog;
 pub mod downloadable;
+pub mod draft;
+pub mod entity_text;
+pub mod file_id;
+pub mod folder;
+pub mod forum_topic;
+pub mod game_high_score;
+pub mod group_call;
 pub mod inline;
 pub mod input_media;
 pub mod input_message;
+pub mod invite_link;
+pub mod invoice;
 pub mod iter_buffer;
 pub mod login_token;
 pub mod media;
 pub mod message;
 pub mod message_deletion;
+pub mod message_filter;
 pub mod participant;
+pub mod passport;
 pub mod password_token;
+pub mod payment_query;
 pub mod permissions;
+pub mod phone_call;
 pub mod photo_sizes;
 pub mod reactions;
 pub mod reply_markup;
+#[cfg(feature = "parse_invite_link")]
+pub mod resolved_link;
+pub mod secret_chat;
+pub mod sponsored_message;
+pub mod stars_revenue;
+pub mod stars_subscription;
+pub mod stats;
+pub mod sticker_set;
+pub mod story;
 pub mod terms_of_service;
 pub mod update;
+pub mod web_app;
 
-pub use action::ActionSender;
+pub use action::{ActionGuard, ActionSender, ChatAction};
+pub use admin_log::{AdminLogAction, AdminLogEvent, AdminLogFilter};
 pub use attributes::Attribute;
+pub use boost::{Boost, BoostStatus};
+pub use bot_command::{BotCommand, BotCommandScope};
 pub use callback_query::CallbackQuery;
 pub use chat::{Channel, Chat, Group, PackedChat, Platform, RestrictionReason, User};
 pub use chat_map::ChatMap;
 pub(crate) use chat_map::Peer;
-pub use chats::{AdminRightsBuilder, BannedRightsBuilder};
-pub use dialog::Dialog;
+pub use chats::{AdminRights, AdminRightsBuilder, BannedRightsBuilder, ChatPermissions};
+pub use contact::PhoneContact;
+pub use dialog::{Dialog, PeerFolder};
 pub use downloadable::{ChatPhoto, Downloadable, UserProfilePhoto};
+pub use draft::Draft;
+pub use entity_text::EntityText;
+pub use file_id::{FileId, FileType};
+pub use folder::{Folder, FolderBuilder};
+pub use forum_topic::ForumTopic;
+pub use game_high_score::GameHighScore;
+pub use grammers_mtproto::mtp::MsgState;
+pub use group_call::{GroupCallConnection, GroupCallParticipant};
 pub use inline::query::InlineQuery;
 pub use inline::send::InlineSend;
 pub use input_media::InputMedia;
 pub use input_message::InputMessage;
+pub use invite_link::{InviteLink, InviteLinkBuilder, JoinRequest};
+pub use invoice::Invoice;
 pub use iter_buffer::IterBuffer;
 pub use login_token::LoginToken;
 pub(crate) use media::Uploaded;
 pub use media::{Media, Photo};
-pub use message::Message;
+pub use message::{Message, Pin};
 pub use message_deletion::MessageDeletion;
-pub use participant::{Participant, Role};
+pub use message_filter::MessageFilter;
+pub use participant::{Participant, ParticipantFilter, Role};
+pub use passport::PassportData;
 pub use password_token::PasswordToken;
+pub use payment_query::{PreCheckoutQuery, ShippingOption, ShippingQuery};
 pub use permissions::{Permissions, Restrictions};
+pub use phone_call::{CallError, CallUpdate, IncomingCall, PhoneCall};
 pub use reactions::InputReactions;
 pub(crate) use reply_markup::ReplyMarkup;
+#[cfg(feature = "parse_invite_link")]
+pub use resolved_link::{InvitePreview, ResolvedLink};
+pub use secret_chat::{SecretChat, SecretChatError, SecretChatRequest, SecretChatUpdate};
+pub use sponsored_message::{SponsoredMessage, SponsoredMessages};
+pub use stars_revenue::StarsRevenue;
+pub use stars_subscription::StarsSubscription;
+pub use stats::{BroadcastStats, MegagroupStats, MessageStats, StatsGraph, StatsValue};
+pub use sticker_set::{InputSticker, StickerSet};
+pub use story::{Story, StoryPrivacy};
 pub use terms_of_service::TermsOfService;
 pub use update::Update;
+pub use web_app::WebAppData;
@@ -53,6 +53,50 @@ pub enum Role {
     Left(Left),
 }
 
+/// Which participants of a channel should be returned by [`ParticipantIter::filter`].
+///
+/// [`ParticipantIter::filter`]: crate::client::chats::ParticipantIter::filter
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum ParticipantFilter {
+    /// Every participant, most recently joined first.
+    #[default]
+    Recent,
+    /// Only the administrators, including the creator.
+    Admins,
+    /// Only the bot accounts.
+    Bots,
+    /// Only the users who were removed from the channel and cannot join again.
+    Kicked,
+    /// Only the users with restricted permissions.
+    Banned,
+    /// Only the participants in the contact list of the logged-in user.
+    Contacts,
+    /// Only the participants whose name or username matches the query.
+    Search(String),
+}
+
+impl From<ParticipantFilter> for tl::enums::ChannelParticipantsFilter {
+    fn from(filter: ParticipantFilter) -> Self {
+        use tl::enums::ChannelParticipantsFilter as Filter;
+
+        match filter {
+            ParticipantFilter::Recent => Filter::ChannelParticipantsRecent,
+            ParticipantFilter::Admins => Filter::ChannelParticipantsAdmins,
+            ParticipantFilter::Bots => Filter::ChannelParticipantsBots,
+            ParticipantFilter::Kicked => {
+                tl::types::ChannelParticipantsKicked { q: String::new() }.into()
+            }
+            ParticipantFilter::Banned => {
+                tl::types::ChannelParticipantsBanned { q: String::new() }.into()
+            }
+            ParticipantFilter::Contacts => {
+                tl::types::ChannelParticipantsContacts { q: String::new() }.into()
+            }
+            ParticipantFilter::Search(q) => tl::types::ChannelParticipantsSearch { q }.into(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 #[non_exhaustive]
 pub struct Participant {
@@ -0,0 +1,64 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use grammers_tl_types as tl;
+
+pub use grammers_crypto::passport::{decrypt_data, DecryptionError, PrivateKey};
+
+/// Telegram Passport data shared by a user with the bot.
+///
+/// The values are end-to-end encrypted, and can only be read after decrypting the credentials
+/// with the private key of the bot, which contain the secret needed to [`decrypt_data`] of each
+/// value and file.
+///
+/// <https://core.telegram.org/passport>
+#[derive(Clone, Debug, PartialEq)]
+pub struct PassportData {
+    pub raw: tl::types::MessageActionSecureValuesSentMe,
+}
+
+impl PassportData {
+    pub(crate) fn from_raw(raw: tl::types::MessageActionSecureValuesSentMe) -> Self {
+        Self { raw }
+    }
+
+    /// The encrypted values shared by the user, such as their identity document or address.
+    pub fn values(&self) -> impl Iterator<Item = &tl::types::SecureValue> {
+        self.raw.values.iter().map(|value| match value {
+            tl::enums::SecureValue::Value(value) => value,
+        })
+    }
+
+    /// Decrypt the credentials using the private key of the bot, returning the credentials JSON.
+    ///
+    /// The JSON must be validated to contain the `nonce` which was specified when requesting
+    /// the data, and contains the hashes and secrets to use for every value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn f(message: grammers_client::types::Message, pem: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// use grammers_client::types::passport::PrivateKey;
+    ///
+    /// let key = PrivateKey::from_pem(pem)?;
+    /// if let Some(data) = message.passport_data() {
+    ///     let credentials = data.decrypt_credentials(&key)?;
+    ///     println!("Credentials: {}", String::from_utf8_lossy(&credentials));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn decrypt_credentials(&self, key: &PrivateKey) -> Result<Vec<u8>, DecryptionError> {
+        let tl::enums::SecureCredentialsEncrypted::Encrypted(credentials) = &self.raw.credentials;
+        grammers_crypto::passport::decrypt_credentials(
+            &credentials.data,
+            &credentials.hash,
+            &credentials.secret,
+            key,
+        )
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::types::{Chat, User};
+use crate::{ChatMap, Client};
+use grammers_mtsender::InvocationError;
+use grammers_tl_types as tl;
+use std::fmt;
+use std::sync::Arc;
+
+/// Represents a query sent to the bot once the user has confirmed the payment and shipping
+/// details of an invoice, right before the payment is processed.
+///
+/// You must either [`PreCheckoutQuery::approve`] or [`PreCheckoutQuery::reject`] these queries
+/// within 10 seconds, or the payment will be canceled.
+#[derive(Clone)]
+pub struct PreCheckoutQuery {
+    pub raw: tl::types::UpdateBotPrecheckoutQuery,
+    client: Client,
+    chats: Arc<ChatMap>,
+}
+
+/// Represents a query sent to the bot when the user has specified a shipping address for an
+/// invoice that requested it, and the price is flexible.
+///
+/// You should [`ShippingQuery::answer`] these queries with the available shipping options, or
+/// [`ShippingQuery::reject`] them if delivery to the address is not possible.
+#[derive(Clone)]
+pub struct ShippingQuery {
+    pub raw: tl::types::UpdateBotShippingQuery,
+    client: Client,
+    chats: Arc<ChatMap>,
+}
+
+/// A shipping option, used to answer a [`ShippingQuery`].
+#[derive(Clone, Debug)]
+pub struct ShippingOption {
+    raw: tl::types::ShippingOption,
+}
+
+fn sender(chats: &ChatMap, user_id: i64) -> &User {
+    match chats.get(&tl::types::PeerUser { user_id }.into()).unwrap() {
+        Chat::User(user) => user,
+        _ => unreachable!(),
+    }
+}
+
+impl PreCheckoutQuery {
+    pub fn from_raw(
+        query: tl::types::UpdateBotPrecheckoutQuery,
+        client: &Client,
+        chats: &Arc<ChatMap>,
+    ) -> Self {
+        Self {
+            raw: query,
+            client: client.clone(),
+            chats: chats.clone(),
+        }
+    }
+
+    /// The user who is about to pay.
+    pub fn sender(&self) -> &User {
+        sender(&self.chats, self.raw.user_id)
+    }
+
+    /// The payload of the invoice, as specified by the bot when it was created.
+    pub fn payload(&self) -> &[u8] {
+        &self.raw.payload
+    }
+
+    /// Three-letter ISO 4217 currency code of the payment, or `XTR` for Telegram Stars.
+    pub fn currency(&self) -> &str {
+        &self.raw.currency
+    }
+
+    /// Total amount to be paid, in the smallest units of the currency.
+    pub fn total_amount(&self) -> i64 {
+        self.raw.total_amount
+    }
+
+    /// Identifier of the shipping option chosen by the user, if any.
+    pub fn shipping_option_id(&self) -> Option<&str> {
+        self.raw.shipping_option_id.as_deref()
+    }
+
+    /// The information provided by the user, such as their name or shipping address, if any was
+    /// requested by the invoice.
+    pub fn info(&self) -> Option<&tl::types::PaymentRequestedInfo> {
+        self.raw
+            .info
+            .as_ref()
+            .map(|tl::enums::PaymentRequestedInfo::Info(info)| info)
+    }
+
+    /// Approve the payment, letting Telegram process it.
+    pub async fn approve(&self) -> Result<(), InvocationError> {
+        self.client
+            .invoke(&tl::functions::messages::SetBotPrecheckoutResults {
+                success: true,
+                query_id: self.raw.query_id,
+                error: None,
+            })
+            .await
+            .map(drop)
+    }
+
+    /// Reject the payment, showing the given error message to the user.
+    pub async fn reject<S: Into<String>>(&self, error: S) -> Result<(), InvocationError> {
+        self.client
+            .invoke(&tl::functions::messages::SetBotPrecheckoutResults {
+                success: false,
+                query_id: self.raw.query_id,
+                error: Some(error.into()),
+            })
+            .await
+            .map(drop)
+    }
+}
+
+impl ShippingQuery {
+    pub fn from_raw(
+        query: tl::types::UpdateBotShippingQuery,
+        client: &Client,
+        chats: &Arc<ChatMap>,
+    ) -> Self {
+        Self {
+            raw: query,
+            client: client.clone(),
+            chats: chats.clone(),
+        }
+    }
+
+    /// The user who specified the shipping address.
+    pub fn sender(&self) -> &User {
+        sender(&self.chats, self.raw.user_id)
+    }
+
+    /// The payload of the invoice, as specified by the bot when it was created.
+    pub fn payload(&self) -> &[u8] {
+        &self.raw.payload
+    }
+
+    /// The shipping address specified by the user.
+    pub fn shipping_address(&self) -> &tl::types::PostAddress {
+        let tl::enums::PostAddress::Address(address) = &self.raw.shipping_address;
+        address
+    }
+
+    /// Answer the query with the shipping options available for the address.
+    pub async fn answer<I: IntoIterator<Item = ShippingOption>>(
+        &self,
+        options: I,
+    ) -> Result<(), InvocationError> {
+        self.client
+            .invoke(&tl::functions::messages::SetBotShippingResults {
+                query_id: self.raw.query_id,
+                error: None,
+                shipping_options: Some(options.into_iter().map(|o| o.raw.into()).collect()),
+            })
+            .await
+            .map(drop)
+    }
+
+    /// Reject the query, showing the given error message to the user.
+    pub async fn reject<S: Into<String>>(&self, error: S) -> Result<(), InvocationError> {
+        self.client
+            .invoke(&tl::functions::messages::SetBotShippingResults {
+                query_id: self.raw.query_id,
+                error: Some(error.into()),
+                shipping_options: None,
+            })
+            .await
+            .map(drop)
+    }
+}
+
+impl ShippingOption {
+    /// Create a new shipping option with the given identifier and title.
+    ///
+    /// Prices should be added with [`ShippingOption::price`].
+    pub fn new<I: Into<String>, T: Into<String>>(id: I, title: T) -> Self {
+        Self {
+            raw: tl::types::ShippingOption {
+                id: id.into(),
+                title: title.into(),
+                prices: Vec::new(),
+            },
+        }
+    }
+
+    /// Add a price portion to the shipping option, in the smallest units of the currency.
+    pub fn price<S: Into<String>>(mut self, label: S, amount: i64) -> Self {
+        self.raw.prices.push(
+            tl::types::LabeledPrice {
+                label: label.into(),
+                amount,
+            }
+            .into(),
+        );
+        self
+    }
+}
+
+impl fmt::Debug for PreCheckoutQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreCheckoutQuery")
+            .field("payload", &self.payload())
+            .field("sender", &self.sender())
+            .field("currency", &self.currency())
+            .field("total_amount", &self.total_amount())
+            .field("shipping_option_id", &self.shipping_option_id())
+            .finish()
+    }
+}
+
+impl fmt::Debug for ShippingQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShippingQuery")
+            .field("payload", &self.payload())
+            .field("sender", &self.sender())
+            .field("shipping_address", &self.shipping_address())
+            .finish()
+    }
+}
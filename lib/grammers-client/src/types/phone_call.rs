@@ -0,0 +1,639 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Types to work with [phone calls], whose signaling goes through Telegram while the media
+//! is sent by the library of your choice.
+//!
+//! [phone calls]: https://core.telegram.org/api/end-to-end/voice-calls
+use crate::types::secret_chat::DhParams;
+use crate::types::Update;
+use crate::utils;
+use crate::Client;
+use chrono::{DateTime, Utc};
+use grammers_crypto::calls;
+use grammers_crypto::secret_chat::{self as crypto, KEY_SIZE};
+use grammers_mtsender::InvocationError;
+use grammers_tl_types as tl;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Error produced while establishing or using a phone call.
+#[derive(Debug)]
+pub enum CallError {
+    /// The request to Telegram failed.
+    Invocation(InvocationError),
+    /// The Diffie-Hellman parameters or values received are not safe to use.
+    UnsafeParameters,
+    /// The key calculated by the other party does not match ours.
+    KeyMismatch,
+    /// The call was discarded and can no longer be used.
+    Discarded,
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invocation(e) => write!(f, "call error: {e}"),
+            Self::UnsafeParameters => write!(f, "call error: unsafe key exchange parameters"),
+            Self::KeyMismatch => write!(f, "call error: key mismatch"),
+            Self::Discarded => write!(f, "call error: call discarded"),
+        }
+    }
+}
+
+impl std::error::Error for CallError {}
+
+impl From<InvocationError> for CallError {
+    fn from(error: InvocationError) -> Self {
+        Self::Invocation(error)
+    }
+}
+
+/// An incoming phone call from another user.
+///
+/// The call can be [accepted](Self::accept) or [declined](Self::decline).
+#[derive(Clone)]
+pub struct IncomingCall {
+    pub raw: tl::types::PhoneCallRequested,
+    pub(crate) client: Client,
+}
+
+/// An event that occurred in a [`PhoneCall`].
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub enum CallUpdate {
+    /// The other party received the call, and their device is ringing.
+    Ringing,
+    /// The key exchange completed, and the media can now be connected using the
+    /// [`PhoneCall::key`] and [`PhoneCall::connections`].
+    Established,
+    /// The other party's media library sent signaling data, which should be fed to ours.
+    SignalingData(Vec<u8>),
+    /// The call was discarded by either party.
+    Discarded {
+        reason: Option<tl::enums::PhoneCallDiscardReason>,
+        duration: Option<Duration>,
+    },
+}
+
+/// A phone call with another user.
+///
+/// The library only takes care of the signaling and key exchange. The media must be handled
+/// by an external library, such as `libtgvoip` or `tgcalls`, configured with the [`key`],
+/// [`connections`] and the [`protocol`] given when requesting or accepting the call.
+///
+/// Updates for this call are only received while [`Client::next_update`] is being polled,
+/// which forwards them to the call. These are then processed by [`PhoneCall::next_update`].
+///
+/// [`key`]: Self::key
+/// [`connections`]: Self::connections
+/// [`protocol`]: tl::types::PhoneCallProtocol
+pub struct PhoneCall {
+    client: Client,
+    id: i64,
+    access_hash: i64,
+    user_id: i64,
+    video: bool,
+    state: Mutex<State>,
+    updates: mpsc::UnboundedReceiver<tl::enums::Update>,
+}
+
+pub(crate) struct State {
+    outgoing: bool,
+    dh: DhParams,
+    secret: [u8; KEY_SIZE],
+    protocol: tl::types::PhoneCallProtocol,
+    // The public value of the caller if we made the call, or its hash if we received it.
+    g_a: Vec<u8>,
+    key: Option<[u8; KEY_SIZE]>,
+    emojis: Option<[&'static str; 4]>,
+    raw: Option<tl::types::PhoneCall>,
+    ringing: bool,
+    discarded: bool,
+    updates: VecDeque<CallUpdate>,
+}
+
+fn input_call(id: i64, access_hash: i64) -> tl::enums::InputPhoneCall {
+    tl::types::InputPhoneCall { id, access_hash }.into()
+}
+
+impl IncomingCall {
+    pub(crate) fn from_raw(client: &Client, raw: tl::types::PhoneCallRequested) -> Self {
+        Self {
+            raw,
+            client: client.clone(),
+        }
+    }
+
+    /// The identifier of the call.
+    pub fn id(&self) -> i64 {
+        self.raw.id
+    }
+
+    /// The identifier of the user who is calling.
+    pub fn user_id(&self) -> i64 {
+        self.raw.admin_id
+    }
+
+    /// Whether the caller wants to start a video call.
+    pub fn is_video(&self) -> bool {
+        self.raw.video
+    }
+
+    /// The date when the call was made.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// The protocol supported by the caller.
+    pub fn protocol(&self) -> &tl::enums::PhoneCallProtocol {
+        &self.raw.protocol
+    }
+
+    /// Let the caller know the call was received, so that it can show it's ringing.
+    pub async fn received(&self) -> Result<(), InvocationError> {
+        self.client
+            .invoke(&tl::functions::phone::ReceivedCall {
+                peer: input_call(self.raw.id, self.raw.access_hash),
+            })
+            .await
+            .map(drop)
+    }
+
+    /// Accept the call using the given protocol, which must be supported by the media library.
+    ///
+    /// The call will be usable after [`CallUpdate::Established`] is received.
+    pub async fn accept(
+        &self,
+        protocol: tl::types::PhoneCallProtocol,
+    ) -> Result<PhoneCall, CallError> {
+        let (dh, random) = self
+            .client
+            .get_dh_params()
+            .await?
+            .ok_or(CallError::UnsafeParameters)?;
+        let secret = crypto::generate_secret(&random);
+        let g_b = crypto::calculate_g_a(dh.g, &dh.p, &secret).ok_or(CallError::UnsafeParameters)?;
+
+        let tl::enums::phone::PhoneCall::Call(call) = self
+            .client
+            .invoke(&tl::functions::phone::AcceptCall {
+                peer: input_call(self.raw.id, self.raw.access_hash),
+                g_b: tl::byte_string(g_b.to_vec()),
+                protocol: protocol.clone().into(),
+            })
+            .await?;
+
+        match call.phone_call {
+            tl::enums::PhoneCall::Empty(_) | tl::enums::PhoneCall::Discarded(_) => {
+                Err(CallError::Discarded)
+            }
+            _ => Ok(PhoneCall::new(
+                &self.client,
+                self.raw.id,
+                self.raw.access_hash,
+                self.raw.admin_id,
+                self.raw.video,
+                State::new(false, dh, secret, protocol, self.raw.g_a_hash.to_vec()),
+            )),
+        }
+    }
+
+    /// Decline the call.
+    pub async fn decline(&self) -> Result<(), InvocationError> {
+        self.client
+            .invoke(&tl::functions::phone::DiscardCall {
+                video: self.raw.video,
+                peer: input_call(self.raw.id, self.raw.access_hash),
+                duration: 0,
+                reason: tl::enums::PhoneCallDiscardReason::Hangup,
+                connection_id: 0,
+            })
+            .await
+            .map(drop)
+    }
+}
+
+impl fmt::Debug for IncomingCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IncomingCall")
+            .field("id", &self.id())
+            .field("user_id", &self.user_id())
+            .field("video", &self.is_video())
+            .field("date", &self.date())
+            .finish()
+    }
+}
+
+impl PhoneCall {
+    pub(crate) fn new(
+        client: &Client,
+        id: i64,
+        access_hash: i64,
+        user_id: i64,
+        video: bool,
+        state: State,
+    ) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        client.0.calls.lock().unwrap().insert(id, tx);
+        Self {
+            client: client.clone(),
+            id,
+            access_hash,
+            user_id,
+            video,
+            state: Mutex::new(state),
+            updates: rx,
+        }
+    }
+
+    /// The identifier of the call.
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    /// The identifier of the other user in the call.
+    pub fn user_id(&self) -> i64 {
+        self.user_id
+    }
+
+    /// Whether the logged-in user was the one who made the call.
+    pub fn is_outgoing(&self) -> bool {
+        self.state.lock().unwrap().outgoing
+    }
+
+    /// Whether the call was started as a video call.
+    pub fn is_video(&self) -> bool {
+        self.video
+    }
+
+    /// The key used to encrypt the media of the call, once it's established.
+    pub fn key(&self) -> Option<[u8; KEY_SIZE]> {
+        self.state.lock().unwrap().key
+    }
+
+    /// The fingerprint of the [`PhoneCall::key`], once the call is established.
+    pub fn key_fingerprint(&self) -> Option<i64> {
+        self.key().as_ref().map(crypto::key_fingerprint)
+    }
+
+    /// The emojis to display so both parties can verify the key, once the call is established.
+    pub fn emojis(&self) -> Option<[&'static str; 4]> {
+        self.state.lock().unwrap().emojis
+    }
+
+    /// Whether the media can be sent directly between both parties, once the call is established.
+    pub fn p2p_allowed(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.raw.as_ref().is_some_and(|call| call.p2p_allowed)
+    }
+
+    /// The servers which can relay the media of the call, once it's established.
+    pub fn connections(&self) -> Vec<tl::enums::PhoneConnection> {
+        let state = self.state.lock().unwrap();
+        state
+            .raw
+            .as_ref()
+            .map(|call| call.connections.clone())
+            .unwrap_or_default()
+    }
+
+    /// The raw call sent by Telegram once it's established, with all the negotiated parameters.
+    pub fn raw(&self) -> Option<tl::types::PhoneCall> {
+        self.state.lock().unwrap().raw.clone()
+    }
+
+    /// Wait for the next update in this call.
+    ///
+    /// The key exchange is completed while waiting for updates.
+    pub async fn next_update(&mut self) -> Result<CallUpdate, CallError> {
+        loop {
+            if let Some(update) = self.state.lock().unwrap().updates.pop_front() {
+                return Ok(update);
+            }
+
+            match self.updates.recv().await {
+                Some(update) => self.handle_update(update).await?,
+                None => return Err(CallError::Discarded),
+            }
+        }
+    }
+
+    /// Send the signaling data produced by the media library to the other party.
+    pub async fn send_signaling_data(&self, data: Vec<u8>) -> Result<(), InvocationError> {
+        self.client
+            .invoke(&tl::functions::phone::SendSignalingData {
+                peer: input_call(self.id, self.access_hash),
+                data: tl::byte_string(data),
+            })
+            .await
+            .map(drop)
+    }
+
+    /// Hang up the call, reporting how long it lasted and the identifier of the connection used.
+    pub async fn hang_up(
+        &self,
+        duration: Duration,
+        connection_id: i64,
+    ) -> Result<(), InvocationError> {
+        self.discard(
+            tl::enums::PhoneCallDiscardReason::Hangup,
+            duration,
+            connection_id,
+        )
+        .await
+    }
+
+    /// Discard the call for the given reason.
+    pub async fn discard(
+        &self,
+        reason: tl::enums::PhoneCallDiscardReason,
+        duration: Duration,
+        connection_id: i64,
+    ) -> Result<(), InvocationError> {
+        self.state.lock().unwrap().discarded = true;
+        self.client
+            .invoke(&tl::functions::phone::DiscardCall {
+                video: self.video,
+                peer: input_call(self.id, self.access_hash),
+                duration: duration.as_secs() as i32,
+                reason,
+                connection_id,
+            })
+            .await
+            .map(drop)
+    }
+
+    async fn handle_update(&self, update: tl::enums::Update) -> Result<(), CallError> {
+        let call = match update {
+            tl::enums::Update::PhoneCall(tl::types::UpdatePhoneCall { phone_call }) => phone_call,
+            tl::enums::Update::PhoneCallSignalingData(
+                tl::types::UpdatePhoneCallSignalingData { data, .. },
+            ) => {
+                self.state
+                    .lock()
+                    .unwrap()
+                    .updates
+                    .push_back(CallUpdate::SignalingData(data.to_vec()));
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+
+        match call {
+            tl::enums::PhoneCall::Waiting(call) => {
+                let state = &mut *self.state.lock().unwrap();
+                if state.outgoing && call.receive_date.is_some() && !state.ringing {
+                    state.ringing = true;
+                    state.updates.push_back(CallUpdate::Ringing);
+                }
+            }
+            tl::enums::PhoneCall::Accepted(call) => {
+                let confirm = self.state.lock().unwrap().accepted(&call.g_b)?;
+                if let Some((g_a, key_fingerprint, protocol)) = confirm {
+                    let tl::enums::phone::PhoneCall::Call(call) = self
+                        .client
+                        .invoke(&tl::functions::phone::ConfirmCall {
+                            peer: input_call(self.id, self.access_hash),
+                            g_a: tl::byte_string(g_a),
+                            key_fingerprint,
+                            protocol: protocol.into(),
+                        })
+                        .await?;
+                    if let tl::enums::PhoneCall::Call(call) = call.phone_call {
+                        self.state.lock().unwrap().established(call)?;
+                    }
+                }
+            }
+            tl::enums::PhoneCall::Call(call) => self.state.lock().unwrap().established(call)?,
+            tl::enums::PhoneCall::Discarded(call) => {
+                let state = &mut *self.state.lock().unwrap();
+                state.discarded = true;
+                state.updates.push_back(CallUpdate::Discarded {
+                    reason: call.reason,
+                    duration: call
+                        .duration
+                        .map(|secs| Duration::from_secs(secs.max(0) as u64)),
+                });
+            }
+            tl::enums::PhoneCall::Empty(_) | tl::enums::PhoneCall::Requested(_) => {}
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for PhoneCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PhoneCall")
+            .field("id", &self.id)
+            .field("user_id", &self.user_id)
+            .field("video", &self.video)
+            .field("emojis", &self.emojis())
+            .finish()
+    }
+}
+
+impl Drop for PhoneCall {
+    fn drop(&mut self) {
+        self.client.0.calls.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl State {
+    pub(crate) fn new(
+        outgoing: bool,
+        dh: DhParams,
+        secret: [u8; KEY_SIZE],
+        protocol: tl::types::PhoneCallProtocol,
+        g_a: Vec<u8>,
+    ) -> Self {
+        Self {
+            outgoing,
+            dh,
+            secret,
+            protocol,
+            g_a,
+            key: None,
+            emojis: None,
+            raw: None,
+            ringing: false,
+            discarded: false,
+            updates: VecDeque::new(),
+        }
+    }
+
+    // The callee accepted our call, so calculate the key and reveal our `g_a`.
+    #[allow(clippy::type_complexity)]
+    fn accepted(
+        &mut self,
+        g_b: &[u8],
+    ) -> Result<Option<(Vec<u8>, i64, tl::types::PhoneCallProtocol)>, CallError> {
+        if !self.outgoing || self.key.is_some() {
+            return Ok(None);
+        }
+
+        let key = crypto::calculate_key(g_b, &self.dh.p, &self.secret)
+            .ok_or(CallError::UnsafeParameters)?;
+        self.key = Some(key);
+        Ok(Some((
+            self.g_a.clone(),
+            crypto::key_fingerprint(&key),
+            self.protocol.clone(),
+        )))
+    }
+
+    fn established(&mut self, call: tl::types::PhoneCall) -> Result<(), CallError> {
+        if self.raw.is_some() {
+            return Ok(());
+        }
+
+        let (key, g_a) = if self.outgoing {
+            (self.key.ok_or(CallError::KeyMismatch)?, self.g_a.as_slice())
+        } else {
+            // The caller reveals its `g_a`, which must match the hash it sent before.
+            if !calls::check_g_a_hash(&call.g_a_or_b, &self.g_a) {
+                return Err(CallError::KeyMismatch);
+            }
+            let key = crypto::calculate_key(&call.g_a_or_b, &self.dh.p, &self.secret)
+                .ok_or(CallError::UnsafeParameters)?;
+            (key, &call.g_a_or_b[..])
+        };
+
+        if crypto::key_fingerprint(&key) != call.key_fingerprint {
+            return Err(CallError::KeyMismatch);
+        }
+
+        self.key = Some(key);
+        self.emojis = Some(calls::emoji_fingerprint(&key, g_a));
+        self.raw = Some(call);
+        self.updates.push_back(CallUpdate::Established);
+        Ok(())
+    }
+}
+
+pub(crate) fn route_update(client: &Client, update: tl::enums::Update) -> Option<Update> {
+    let call_id = match &update {
+        tl::enums::Update::PhoneCall(u) => u.phone_call.id(),
+        tl::enums::Update::PhoneCallSignalingData(u) => u.phone_call_id,
+        _ => return Some(Update::Raw(update)),
+    };
+
+    let update = match client.0.calls.lock().unwrap().get(&call_id) {
+        Some(tx) => match tx.send(update) {
+            Ok(()) => return None,
+            Err(mpsc::error::SendError(update)) => update,
+        },
+        None => update,
+    };
+
+    match update {
+        tl::enums::Update::PhoneCall(tl::types::UpdatePhoneCall {
+            phone_call: tl::enums::PhoneCall::Requested(raw),
+        }) => Some(Update::IncomingCall(IncomingCall::from_raw(client, raw))),
+        update => Some(Update::Raw(update)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grammers_crypto::hex;
+
+    const P: &str = "c71caeb9c6b1c9048e6c522f70f13f73980d40238e3e21c14934d037563d930f\
+        48198a0aa7c14058229493d22530f4dbfa336f6e0ac925139543aed44cce7c37\
+        20fd51f69458705ac68cd4fe6b6b13abdc9746512969328454f18faf8c595f64\
+        2477fe96bb2a941d5bcd1d4ac8cc49880708fa9b378e3c4f3a9060bee67cf9a4\
+        a4a695811051907e162753b56b0f6b410dba74d8a84b2a14b3144e0ef1284754\
+        fd17ed950d5965b4b9dd46582db1178d169c6bc465b0d6ff9ca3928fef5b9ae4\
+        e418fc15e83ebea0f87fa9ff5eed70050ded2849f47bf959d956850ce929851f\
+        0d8115f635b105ee2e4e15d04b2454bf6f4fadf034b10403119cd8e3b92fcc5b";
+
+    fn protocol() -> tl::types::PhoneCallProtocol {
+        tl::types::PhoneCallProtocol {
+            udp_p2p: true,
+            udp_reflector: true,
+            min_layer: 65,
+            max_layer: 92,
+            library_versions: vec!["2.4.4".to_string()],
+        }
+    }
+
+    fn established_call(g_a: Vec<u8>, key_fingerprint: i64) -> tl::types::PhoneCall {
+        tl::types::PhoneCall {
+            p2p_allowed: true,
+            video: false,
+            id: 1,
+            access_hash: 2,
+            date: 0,
+            admin_id: 3,
+            participant_id: 4,
+            g_a_or_b: tl::byte_string(g_a),
+            key_fingerprint,
+            protocol: protocol().into(),
+            connections: Vec::new(),
+            start_date: 0,
+            custom_parameters: None,
+        }
+    }
+
+    // Returns the caller and callee states after exchanging `g_a_hash` and `g_b`,
+    // along with the `g_a` and fingerprint the caller sends to confirm the call.
+    fn handshake() -> (State, State, Vec<u8>, i64) {
+        let dh = DhParams {
+            version: 1,
+            g: 3,
+            p: hex::from_hex(P),
+        };
+        let a = crypto::generate_secret(&[]);
+        let b = crypto::generate_secret(&[]);
+        let g_a = crypto::calculate_g_a(dh.g, &dh.p, &a).unwrap().to_vec();
+        let g_b = crypto::calculate_g_a(dh.g, &dh.p, &b).unwrap();
+
+        let mut caller = State::new(true, dh.clone(), a, protocol(), g_a.clone());
+        let callee = State::new(false, dh, b, protocol(), calls::g_a_hash(&g_a).to_vec());
+        let (confirm_g_a, key_fingerprint, _) = caller.accepted(&g_b).unwrap().unwrap();
+        assert_eq!(confirm_g_a, g_a);
+        (caller, callee, g_a, key_fingerprint)
+    }
+
+    #[test]
+    fn check_key_exchange() {
+        let (mut caller, mut callee, g_a, key_fingerprint) = handshake();
+        let call = established_call(g_a, key_fingerprint);
+        caller.established(call.clone()).unwrap();
+        callee.established(call).unwrap();
+
+        assert_eq!(caller.key, callee.key);
+        assert_eq!(caller.emojis, callee.emojis);
+        assert!(matches!(
+            callee.updates.pop_front(),
+            Some(CallUpdate::Established)
+        ));
+    }
+
+    #[test]
+    fn check_key_exchange_rejects_other_g_a() {
+        let (_, mut callee, mut g_a, key_fingerprint) = handshake();
+        g_a[255] ^= 1;
+        assert!(matches!(
+            callee.established(established_call(g_a, key_fingerprint)),
+            Err(CallError::KeyMismatch)
+        ));
+    }
+
+    #[test]
+    fn check_key_exchange_rejects_other_fingerprint() {
+        let (mut caller, _, g_a, key_fingerprint) = handshake();
+        assert!(matches!(
+            caller.established(established_call(g_a, key_fingerprint ^ 1)),
+            Err(CallError::KeyMismatch)
+        ));
+    }
+}
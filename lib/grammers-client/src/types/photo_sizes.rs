@@ -30,18 +30,18 @@ impl PhotoSize {
                 size: size.size,
                 id: photo.id,
                 access_hash: photo.access_hash,
-                file_reference: photo.file_reference.clone(),
+                file_reference: photo.file_reference.to_vec(),
                 from_document: false,
             }),
             tl::enums::PhotoSize::PhotoCachedSize(size) => PhotoSize::Cached(CachedSize {
                 photo_type: size.r#type.clone(),
                 width: size.w,
                 height: size.h,
-                bytes: size.bytes.clone(),
+                bytes: size.bytes.to_vec(),
             }),
             tl::enums::PhotoSize::PhotoStrippedSize(size) => PhotoSize::Stripped(StrippedSize {
                 photo_type: size.r#type.clone(),
-                bytes: size.bytes.clone(),
+                bytes: size.bytes.to_vec(),
             }),
             tl::enums::PhotoSize::Progressive(size) => PhotoSize::Progressive(ProgressiveSize {
                 photo_type: size.r#type.clone(),
@@ -50,12 +50,12 @@ impl PhotoSize {
                 sizes: size.sizes.clone(),
                 id: photo.id,
                 access_hash: photo.access_hash,
-                file_reference: photo.file_reference.clone(),
+                file_reference: photo.file_reference.to_vec(),
                 from_document: false,
             }),
             tl::enums::PhotoSize::PhotoPathSize(size) => PhotoSize::Path(PathSize {
                 photo_type: size.r#type.clone(),
-                bytes: size.bytes.clone(),
+                bytes: size.bytes.to_vec(),
             }),
         }
     }
@@ -75,18 +75,18 @@ impl PhotoSize {
                 size: size.size,
                 id: document.id,
                 access_hash: document.access_hash,
-                file_reference: document.file_reference.clone(),
+                file_reference: document.file_reference.to_vec(),
                 from_document: true,
             }),
             tl::enums::PhotoSize::PhotoCachedSize(size) => PhotoSize::Cached(CachedSize {
                 photo_type: size.r#type.clone(),
                 width: size.w,
                 height: size.h,
-                bytes: size.bytes.clone(),
+                bytes: size.bytes.to_vec(),
             }),
             tl::enums::PhotoSize::PhotoStrippedSize(size) => PhotoSize::Stripped(StrippedSize {
                 photo_type: size.r#type.clone(),
-                bytes: size.bytes.clone(),
+                bytes: size.bytes.to_vec(),
             }),
             tl::enums::PhotoSize::Progressive(size) => PhotoSize::Progressive(ProgressiveSize {
                 photo_type: size.r#type.clone(),
@@ -95,12 +95,12 @@ impl PhotoSize {
                 sizes: size.sizes.clone(),
                 id: document.id,
                 access_hash: document.access_hash,
-                file_reference: document.file_reference.clone(),
+                file_reference: document.file_reference.to_vec(),
                 from_document: true,
             }),
             tl::enums::PhotoSize::PhotoPathSize(size) => PhotoSize::Path(PathSize {
                 photo_type: size.r#type.clone(),
-                bytes: size.bytes.clone(),
+                bytes: size.bytes.to_vec(),
             }),
         }
     }
@@ -125,6 +125,18 @@ impl PhotoSize {
         }
     }
 
+    /// Width and height of the photo thumb, if known.
+    pub fn dimensions(&self) -> Option<(i32, i32)> {
+        match self {
+            PhotoSize::Empty(_) => None,
+            PhotoSize::Size(size) => Some((size.width, size.height)),
+            PhotoSize::Cached(size) => Some((size.width, size.height)),
+            PhotoSize::Stripped(_) => None,
+            PhotoSize::Progressive(size) => Some((size.width, size.height)),
+            PhotoSize::Path(_) => None,
+        }
+    }
+
     pub fn to_raw_input_location(&self) -> Option<tl::enums::InputFileLocation> {
         match self {
             PhotoSize::Size(size) => size.to_raw_input_location(),
@@ -186,14 +198,14 @@ impl Size {
             tl::enums::InputFileLocation::from(tl::types::InputPhotoFileLocation {
                 id: self.id,
                 access_hash: self.access_hash,
-                file_reference: self.file_reference.clone(),
+                file_reference: tl::byte_string(self.file_reference.clone()),
                 thumb_size: self.photo_type.clone(),
             })
         } else {
             tl::enums::InputFileLocation::from(tl::types::InputDocumentFileLocation {
                 id: self.id,
                 access_hash: self.access_hash,
-                file_reference: self.file_reference.clone(),
+                file_reference: tl::byte_string(self.file_reference.clone()),
                 thumb_size: self.photo_type.clone(),
             })
         };
@@ -316,14 +328,14 @@ impl ProgressiveSize {
             tl::enums::InputFileLocation::from(tl::types::InputPhotoFileLocation {
                 id: self.id,
                 access_hash: self.access_hash,
-                file_reference: self.file_reference.clone(),
+                file_reference: tl::byte_string(self.file_reference.clone()),
                 thumb_size: self.photo_type.clone(),
             })
         } else {
             tl::enums::InputFileLocation::from(tl::types::InputDocumentFileLocation {
                 id: self.id,
                 access_hash: self.access_hash,
-                file_reference: self.file_reference.clone(),
+                file_reference: tl::byte_string(self.file_reference.clone()),
                 thumb_size: self.photo_type.clone(),
             })
         };
@@ -375,10 +387,19 @@ viewBox="0 0 512 512" xml:space="preserve">
 pub trait VecExt {
     /// Helper method to get the largest photo thumb
     fn largest(&self) -> Option<&PhotoSize>;
+
+    /// Helper method to get the smallest photo thumb that is not empty
+    fn smallest(&self) -> Option<&PhotoSize>;
 }
 
 impl VecExt for Vec<PhotoSize> {
     fn largest(&self) -> Option<&PhotoSize> {
         self.iter().max_by_key(|x| x.size())
     }
+
+    fn smallest(&self) -> Option<&PhotoSize> {
+        self.iter()
+            .filter(|x| x.size() != 0)
+            .min_by_key(|x| x.size())
+    }
 }
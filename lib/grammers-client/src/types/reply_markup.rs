@@ -104,9 +104,10 @@ impl ReplyMarkup for ForceReply {
 /// use grammers_client::{InputMessage, reply_markup, button};
 ///
 /// let artist = "Krewella";
-/// client.send_message(chat, InputMessage::text("Select song").reply_markup(&reply_markup::keyboard(vec![
-///     vec![button::text(format!("Song by {}", artist))],
-///     vec![button::text("Previous"), button::text("Next")],
+/// client.send_message(chat, InputMessage::text("Select song").reply_markup(&reply_markup::inline(vec![
+///     vec![button::inline(format!("Song by {}", artist), b"play")],
+///     vec![button::inline("Previous", b"prev"), button::inline("Next", b"next")],
+///     vec![button::url("Lyrics", "https://example.com/lyrics")],
 /// ]))).await?;
 /// # Ok(())
 /// # }
@@ -136,7 +137,7 @@ pub fn inline<B: Into<Vec<Vec<button::Inline>>>>(buttons: B) -> Inline {
 /// You cannot add images to the buttons, but you can use emoji (simply copy-paste them into your
 /// code, or use the correct escape sequence, or using any other input methods you like).
 ///
-/// You will need to provide a matrix of [`button::Inline`], that is, a vector that contains the
+/// You will need to provide a matrix of [`button::Keyboard`], that is, a vector that contains the
 /// rows from top to bottom, where the rows consist of a vector of buttons from left to right.
 /// See the [`button`] module to learn what buttons are available.
 ///
@@ -252,6 +253,21 @@ impl Keyboard {
         self.raw.selective = true;
         self
     }
+
+    /// Requests clients to always show the keyboard, even after the user hides it by pressing
+    /// the special button in the input field.
+    pub fn persistent(mut self) -> Self {
+        self.raw.persistent = true;
+        self
+    }
+
+    /// The placeholder to be shown in the input field when the keyboard is active.
+    ///
+    /// At most 64 characters long.
+    pub fn placeholder<S: Into<String>>(mut self, placeholder: S) -> Self {
+        self.raw.placeholder = Some(placeholder.into());
+        self
+    }
 }
 
 impl Hide {
@@ -284,4 +300,12 @@ impl ForceReply {
         self.raw.selective = true;
         self
     }
+
+    /// The placeholder to be shown in the input field when the reply is active.
+    ///
+    /// At most 64 characters long.
+    pub fn placeholder<S: Into<String>>(mut self, placeholder: S) -> Self {
+        self.raw.placeholder = Some(placeholder.into());
+        self
+    }
 }
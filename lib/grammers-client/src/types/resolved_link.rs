@@ -0,0 +1,88 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::types::{Chat, Message};
+use crate::Client;
+use grammers_mtsender::InvocationError;
+use grammers_tl_types as tl;
+use std::fmt;
+
+/// What a Telegram link points to, as returned by [`Client::resolve_link`].
+///
+/// [`Client::resolve_link`]: crate::Client::resolve_link
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+#[allow(clippy::large_enum_variant)]
+pub enum ResolvedLink {
+    /// The link points to a user, bot, group or channel.
+    Chat(Chat),
+    /// The link points to a specific message inside of a chat.
+    Message(Message),
+    /// The link is an invite to a private chat the logged-in user is not a member of.
+    Invite(InvitePreview),
+}
+
+/// Information about a private chat shown before joining through its invite link.
+#[derive(Clone)]
+pub struct InvitePreview {
+    pub raw: tl::types::ChatInvite,
+    client: Client,
+    hash: String,
+}
+
+impl InvitePreview {
+    pub(crate) fn from_raw(client: &Client, raw: tl::types::ChatInvite, hash: String) -> Self {
+        Self {
+            raw,
+            client: client.clone(),
+            hash,
+        }
+    }
+
+    /// The title of the chat.
+    pub fn title(&self) -> &str {
+        &self.raw.title
+    }
+
+    /// The description of the chat, if any.
+    pub fn about(&self) -> Option<&str> {
+        self.raw.about.as_deref()
+    }
+
+    /// How many members the chat has.
+    pub fn participants_count(&self) -> i32 {
+        self.raw.participants_count
+    }
+
+    /// Whether the chat is a broadcast channel.
+    pub fn is_broadcast(&self) -> bool {
+        self.raw.broadcast
+    }
+
+    /// Whether an administrator must approve the request to join the chat.
+    ///
+    /// [`InvitePreview::join`] will fail with `INVITE_REQUEST_SENT` in that case.
+    pub fn request_needed(&self) -> bool {
+        self.raw.request_needed
+    }
+
+    /// Join the chat through the invite link.
+    pub async fn join(&self) -> Result<Option<Chat>, InvocationError> {
+        self.client.join_invite_hash(&self.hash).await
+    }
+}
+
+impl fmt::Debug for InvitePreview {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InvitePreview")
+            .field("title", &self.title())
+            .field("about", &self.about())
+            .field("participants_count", &self.participants_count())
+            .field("request_needed", &self.request_needed())
+            .finish()
+    }
+}
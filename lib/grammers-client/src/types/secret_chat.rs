@@ -0,0 +1,979 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Types to work with [secret chats], which are end-to-end encrypted between two users.
+//!
+//! [secret chats]: https://core.telegram.org/api/end-to-end
+use crate::types::Update;
+use crate::utils::{self, generate_random_id};
+use crate::Client;
+use chrono::{DateTime, Utc};
+use grammers_crypto::secret_chat::{self as crypto, KEY_SIZE};
+use grammers_mtsender::InvocationError;
+use grammers_tl_types::{self as tl, Deserializable, Serializable};
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+
+/// The layer of the end-to-end schema implemented by the library.
+pub const SECRET_LAYER: i32 = 73;
+
+// Layer assumed for the other party until they notify us of theirs.
+const DEFAULT_LAYER: i32 = 8;
+
+// The documentation recommends re-keying after a key has been used to
+// decrypt or encrypt more than 100 messages.
+const REKEY_AFTER_MESSAGES: u32 = 100;
+
+// How many of the sent messages are kept in case the other party asks to resend them.
+const MAX_HISTORY: usize = 100;
+
+/// Error produced while creating or using a secret chat.
+#[derive(Debug)]
+pub enum SecretChatError {
+    /// The request to Telegram failed.
+    Invocation(InvocationError),
+    /// The Diffie-Hellman parameters or values received are not safe to use.
+    UnsafeParameters,
+    /// The key calculated by the other party does not match ours.
+    KeyMismatch,
+    /// The other party has not accepted the chat yet.
+    NotAccepted,
+    /// The chat was discarded and can no longer be used.
+    Discarded,
+}
+
+impl fmt::Display for SecretChatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invocation(e) => write!(f, "secret chat error: {e}"),
+            Self::UnsafeParameters => {
+                write!(f, "secret chat error: unsafe key exchange parameters")
+            }
+            Self::KeyMismatch => write!(f, "secret chat error: key fingerprint mismatch"),
+            Self::NotAccepted => write!(f, "secret chat error: chat not accepted yet"),
+            Self::Discarded => write!(f, "secret chat error: chat discarded"),
+        }
+    }
+}
+
+impl std::error::Error for SecretChatError {}
+
+impl From<InvocationError> for SecretChatError {
+    fn from(error: InvocationError) -> Self {
+        Self::Invocation(error)
+    }
+}
+
+/// Diffie-Hellman parameters sent by the server, which have already been validated.
+#[derive(Clone)]
+pub(crate) struct DhParams {
+    pub(crate) version: i32,
+    pub(crate) g: i32,
+    pub(crate) p: Vec<u8>,
+}
+
+/// A request from another user to start a secret chat with the logged-in user.
+///
+/// The request can be [accepted](Self::accept) or [declined](Self::decline).
+#[derive(Clone)]
+pub struct SecretChatRequest {
+    pub raw: tl::types::EncryptedChatRequested,
+    pub(crate) client: Client,
+}
+
+/// An event that occurred in a [`SecretChat`].
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub enum SecretChatUpdate {
+    /// The other party accepted the chat requested by the logged-in user.
+    Accepted,
+    /// The other party sent a new message.
+    NewMessage {
+        message: tl::types::DecryptedMessage,
+        date: DateTime<Utc>,
+    },
+    /// The other party performed an action, such as reading or deleting messages.
+    ///
+    /// Actions used to manage the chat itself, like re-keying, are handled by the library.
+    Service {
+        action: tl::enums::DecryptedMessageAction,
+        date: DateTime<Utc>,
+    },
+    /// The chat was discarded by either party.
+    Discarded,
+}
+
+/// A secret chat with another user.
+///
+/// Updates for this chat are only received while [`Client::next_update`] is being polled,
+/// which forwards them to the chat. These are then processed by [`SecretChat::next_update`].
+///
+/// Dropping this value stops the updates for the chat from being forwarded, and they will
+/// be returned as [`Update::Raw`] instead.
+pub struct SecretChat {
+    client: Client,
+    id: i32,
+    access_hash: i64,
+    user_id: i64,
+    state: Mutex<State>,
+    updates: mpsc::UnboundedReceiver<tl::enums::Update>,
+}
+
+enum KeyExchange {
+    // We sent `RequestKey` and are waiting for the `AcceptKey`.
+    Requested { id: i64, secret: [u8; KEY_SIZE] },
+    // We sent `AcceptKey` and are waiting for the `CommitKey`.
+    Accepted { id: i64, key: [u8; KEY_SIZE] },
+}
+
+// An encrypted message ready to be sent.
+struct Outgoing {
+    random_id: i64,
+    service: bool,
+    data: Vec<u8>,
+}
+
+pub(crate) struct State {
+    originator: bool,
+    dh: DhParams,
+    // Our secret while waiting for the other party to accept the chat.
+    secret: Option<[u8; KEY_SIZE]>,
+    key: Option<[u8; KEY_SIZE]>,
+    // Used to decrypt messages that were in-flight while the key changed.
+    previous_key: Option<[u8; KEY_SIZE]>,
+    exchange: Option<KeyExchange>,
+    their_layer: i32,
+    sent: i32,
+    received: i32,
+    // Messages encrypted or decrypted with the current key.
+    key_usage: u32,
+    history: VecDeque<tl::types::DecryptedMessageLayer>,
+    // Messages which arrived before some of the ones they follow.
+    pending: BTreeMap<i32, (tl::types::DecryptedMessageLayer, i32)>,
+    resend_requested_until: i32,
+    discarded: bool,
+    updates: VecDeque<SecretChatUpdate>,
+}
+
+impl SecretChatRequest {
+    pub(crate) fn from_raw(client: &Client, raw: tl::types::EncryptedChatRequested) -> Self {
+        Self {
+            raw,
+            client: client.clone(),
+        }
+    }
+
+    /// The identifier of the secret chat.
+    pub fn id(&self) -> i32 {
+        self.raw.id
+    }
+
+    /// The identifier of the user who wants to start the secret chat.
+    pub fn user_id(&self) -> i64 {
+        self.raw.admin_id
+    }
+
+    /// The date when the chat was requested.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// Accept the request, completing the key exchange so that messages can be sent.
+    pub async fn accept(&self) -> Result<SecretChat, SecretChatError> {
+        let (dh, random) = self
+            .client
+            .get_dh_params()
+            .await?
+            .ok_or(SecretChatError::UnsafeParameters)?;
+        let secret = crypto::generate_secret(&random);
+        let g_b =
+            crypto::calculate_g_a(dh.g, &dh.p, &secret).ok_or(SecretChatError::UnsafeParameters)?;
+        let key = crypto::calculate_key(&self.raw.g_a, &dh.p, &secret)
+            .ok_or(SecretChatError::UnsafeParameters)?;
+        let key_fingerprint = crypto::key_fingerprint(&key);
+
+        let chat = self
+            .client
+            .invoke(&tl::functions::messages::AcceptEncryption {
+                peer: tl::types::InputEncryptedChat {
+                    chat_id: self.raw.id,
+                    access_hash: self.raw.access_hash,
+                }
+                .into(),
+                g_b: tl::byte_string(g_b.to_vec()),
+                key_fingerprint,
+            })
+            .await?;
+
+        match chat {
+            tl::enums::EncryptedChat::Chat(chat) if chat.key_fingerprint != key_fingerprint => {
+                Err(SecretChatError::KeyMismatch)
+            }
+            tl::enums::EncryptedChat::Empty(_) | tl::enums::EncryptedChat::Discarded(_) => {
+                Err(SecretChatError::Discarded)
+            }
+            _ => {
+                let mut state = State::new(false, dh);
+                state.key = Some(key);
+                let chat = SecretChat::new(
+                    &self.client,
+                    self.raw.id,
+                    self.raw.access_hash,
+                    self.raw.admin_id,
+                    state,
+                );
+                chat.notify_layer().await?;
+                Ok(chat)
+            }
+        }
+    }
+
+    /// Decline the request.
+    pub async fn decline(&self) -> Result<(), InvocationError> {
+        self.client
+            .invoke(&tl::functions::messages::DiscardEncryption {
+                delete_history: false,
+                chat_id: self.raw.id,
+            })
+            .await
+            .map(drop)
+    }
+}
+
+impl fmt::Debug for SecretChatRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretChatRequest")
+            .field("id", &self.id())
+            .field("user_id", &self.user_id())
+            .field("date", &self.date())
+            .finish()
+    }
+}
+
+impl SecretChat {
+    pub(crate) fn new(
+        client: &Client,
+        id: i32,
+        access_hash: i64,
+        user_id: i64,
+        state: State,
+    ) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        client.0.secret_chats.lock().unwrap().insert(id, tx);
+        Self {
+            client: client.clone(),
+            id,
+            access_hash,
+            user_id,
+            state: Mutex::new(state),
+            updates: rx,
+        }
+    }
+
+    /// The identifier of the secret chat.
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// The identifier of the other user in the chat.
+    pub fn user_id(&self) -> i64 {
+        self.user_id
+    }
+
+    /// Whether the logged-in user was the one who requested the chat.
+    pub fn is_originator(&self) -> bool {
+        self.state.lock().unwrap().originator
+    }
+
+    /// The layer negotiated with the other party, which may be lower than [`SECRET_LAYER`].
+    pub fn layer(&self) -> i32 {
+        self.state.lock().unwrap().their_layer.min(SECRET_LAYER)
+    }
+
+    /// The fingerprint of the key currently in use, or `None` if the chat was not accepted yet.
+    ///
+    /// Both parties can compare this value to make sure nobody is intercepting the chat.
+    pub fn key_fingerprint(&self) -> Option<i64> {
+        self.state
+            .lock()
+            .unwrap()
+            .key
+            .as_ref()
+            .map(crypto::key_fingerprint)
+    }
+
+    /// Wait for the next update in this secret chat.
+    ///
+    /// Messages are returned in the order they were sent, even if they were received
+    /// otherwise. Managing the chat, such as re-keying or resending lost messages, is
+    /// handled while waiting for updates.
+    pub async fn next_update(&mut self) -> Result<SecretChatUpdate, SecretChatError> {
+        loop {
+            if let Some(update) = self.state.lock().unwrap().updates.pop_front() {
+                return Ok(update);
+            }
+
+            match self.updates.recv().await {
+                Some(update) => self.handle_update(update).await?,
+                None => return Err(SecretChatError::Discarded),
+            }
+        }
+    }
+
+    /// Send a text message to the other party, returning its random identifier.
+    pub async fn send_message<S: Into<String>>(&self, text: S) -> Result<i64, SecretChatError> {
+        let random_id = generate_random_id();
+        let outgoing = {
+            let state = &mut *self.state.lock().unwrap();
+            if state.discarded {
+                return Err(SecretChatError::Discarded);
+            }
+            if state.key.is_none() {
+                return Err(SecretChatError::NotAccepted);
+            }
+
+            let mut outgoing = vec![state.prepare(
+                tl::types::DecryptedMessage {
+                    no_webpage: false,
+                    silent: false,
+                    random_id,
+                    ttl: 0,
+                    message: text.into(),
+                    media: None,
+                    entities: None,
+                    via_bot_name: None,
+                    reply_to_random_id: None,
+                    grouped_id: None,
+                }
+                .into(),
+            )];
+            state.maybe_rekey(&mut outgoing);
+            outgoing
+        };
+
+        self.send_all(outgoing).await?;
+        Ok(random_id)
+    }
+
+    /// Discard the secret chat for both parties, optionally deleting its history.
+    pub async fn discard(&self, delete_history: bool) -> Result<(), InvocationError> {
+        self.state.lock().unwrap().discarded = true;
+        self.client
+            .invoke(&tl::functions::messages::DiscardEncryption {
+                delete_history,
+                chat_id: self.id,
+            })
+            .await
+            .map(drop)
+    }
+
+    async fn notify_layer(&self) -> Result<(), InvocationError> {
+        let outgoing = self.state.lock().unwrap().prepare_service(
+            tl::types::DecryptedMessageActionNotifyLayer {
+                layer: SECRET_LAYER,
+            }
+            .into(),
+        );
+        self.send_all(vec![outgoing]).await
+    }
+
+    async fn handle_update(&self, update: tl::enums::Update) -> Result<(), SecretChatError> {
+        match update {
+            tl::enums::Update::NewEncryptedMessage(tl::types::UpdateNewEncryptedMessage {
+                message,
+                qts,
+            }) => {
+                let (data, date) = match message {
+                    tl::enums::EncryptedMessage::Message(m) => (m.bytes, m.date),
+                    tl::enums::EncryptedMessage::Service(m) => (m.bytes, m.date),
+                };
+                let outgoing = self.state.lock().unwrap().receive(&data, date);
+                self.send_all(outgoing).await?;
+                self.client
+                    .invoke(&tl::functions::messages::ReceivedQueue { max_qts: qts })
+                    .await?;
+            }
+            tl::enums::Update::Encryption(tl::types::UpdateEncryption { chat, .. }) => match chat {
+                tl::enums::EncryptedChat::Chat(chat) => {
+                    let accepted = self
+                        .state
+                        .lock()
+                        .unwrap()
+                        .accepted(&chat.g_a_or_b, chat.key_fingerprint)?;
+                    if accepted {
+                        self.notify_layer().await?;
+                    }
+                }
+                tl::enums::EncryptedChat::Discarded(_) | tl::enums::EncryptedChat::Empty(_) => {
+                    let state = &mut *self.state.lock().unwrap();
+                    if !state.discarded {
+                        state.discarded = true;
+                        state.updates.push_back(SecretChatUpdate::Discarded);
+                    }
+                }
+                tl::enums::EncryptedChat::Waiting(_) | tl::enums::EncryptedChat::Requested(_) => {}
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn send_all(&self, outgoing: Vec<Outgoing>) -> Result<(), InvocationError> {
+        let peer: tl::enums::InputEncryptedChat = tl::types::InputEncryptedChat {
+            chat_id: self.id,
+            access_hash: self.access_hash,
+        }
+        .into();
+
+        for Outgoing {
+            random_id,
+            service,
+            data,
+        } in outgoing
+        {
+            if service {
+                self.client
+                    .invoke(&tl::functions::messages::SendEncryptedService {
+                        peer: peer.clone(),
+                        random_id,
+                        data: tl::byte_string(data),
+                    })
+                    .await?;
+            } else {
+                self.client
+                    .invoke(&tl::functions::messages::SendEncrypted {
+                        silent: false,
+                        peer: peer.clone(),
+                        random_id,
+                        data: tl::byte_string(data),
+                    })
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SecretChat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretChat")
+            .field("id", &self.id)
+            .field("user_id", &self.user_id)
+            .field("layer", &self.layer())
+            .finish()
+    }
+}
+
+impl Drop for SecretChat {
+    fn drop(&mut self) {
+        self.client.0.secret_chats.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl State {
+    pub(crate) fn new(originator: bool, dh: DhParams) -> Self {
+        Self {
+            originator,
+            dh,
+            secret: None,
+            key: None,
+            previous_key: None,
+            exchange: None,
+            their_layer: DEFAULT_LAYER,
+            sent: 0,
+            received: 0,
+            key_usage: 0,
+            history: VecDeque::new(),
+            pending: BTreeMap::new(),
+            resend_requested_until: -1,
+            discarded: false,
+            updates: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn waiting(dh: DhParams, secret: [u8; KEY_SIZE]) -> Self {
+        let mut state = Self::new(true, dh);
+        state.secret = Some(secret);
+        state
+    }
+
+    // The originator of the chat uses odd `out_seq_no`, and the other party even ones.
+    fn out_seq_no(&self) -> i32 {
+        2 * self.sent + i32::from(self.originator)
+    }
+
+    fn in_seq_no(&self) -> i32 {
+        2 * self.received + i32::from(!self.originator)
+    }
+
+    // Complete the key exchange started by us, returning `true` if the chat was waiting on it.
+    fn accepted(&mut self, g_b: &[u8], key_fingerprint: i64) -> Result<bool, SecretChatError> {
+        let Some(secret) = self.secret.take() else {
+            return Ok(false);
+        };
+
+        let key = crypto::calculate_key(g_b, &self.dh.p, &secret)
+            .ok_or(SecretChatError::UnsafeParameters)?;
+        if crypto::key_fingerprint(&key) != key_fingerprint {
+            return Err(SecretChatError::KeyMismatch);
+        }
+
+        self.key = Some(key);
+        self.updates.push_back(SecretChatUpdate::Accepted);
+        Ok(true)
+    }
+
+    fn key_for(&self, fingerprint: &[u8]) -> Option<[u8; KEY_SIZE]> {
+        let pending = match &self.exchange {
+            Some(KeyExchange::Accepted { key, .. }) => Some(key),
+            _ => None,
+        };
+
+        self.key
+            .iter()
+            .chain(self.previous_key.iter())
+            .chain(pending)
+            .find(|key| crypto::key_fingerprint(key).to_le_bytes() == fingerprint)
+            .copied()
+    }
+
+    fn receive(&mut self, data: &[u8], date: i32) -> Vec<Outgoing> {
+        let mut outgoing = Vec::new();
+        let Some(key) = data
+            .get(..8)
+            .and_then(|fingerprint| self.key_for(fingerprint))
+        else {
+            log::warn!("dropping secret chat message encrypted with an unknown key");
+            return outgoing;
+        };
+
+        let layer = match crypto::decrypt_message(data, &key, self.originator)
+            .map_err(|e| e.to_string())
+            .and_then(|plaintext| {
+                tl::enums::DecryptedMessageLayer::from_bytes(&plaintext).map_err(|e| e.to_string())
+            }) {
+            Ok(tl::enums::DecryptedMessageLayer::Layer(layer)) => layer,
+            Err(e) => {
+                log::warn!("dropping secret chat message that failed to decrypt: {e}");
+                return outgoing;
+            }
+        };
+
+        let expected = self.in_seq_no();
+        if layer.out_seq_no % 2 != expected % 2 {
+            log::warn!("dropping secret chat message with a bad seq_no parity");
+            return outgoing;
+        }
+        if layer.out_seq_no < expected {
+            // Already processed.
+            return outgoing;
+        }
+        if layer.out_seq_no > expected {
+            // Ask for the missing messages, unless they were already requested.
+            let start_seq_no = expected.max(self.resend_requested_until + 2);
+            let end_seq_no = layer.out_seq_no - 2;
+            if start_seq_no <= end_seq_no {
+                self.resend_requested_until = end_seq_no;
+                outgoing.push(
+                    self.prepare_service(
+                        tl::types::DecryptedMessageActionResend {
+                            start_seq_no,
+                            end_seq_no,
+                        }
+                        .into(),
+                    ),
+                );
+            }
+            self.pending.insert(layer.out_seq_no, (layer, date));
+            return outgoing;
+        }
+
+        self.process(layer, date, &mut outgoing);
+        while let Some((layer, date)) = self.pending.remove(&self.in_seq_no()) {
+            self.process(layer, date, &mut outgoing);
+        }
+        self.maybe_rekey(&mut outgoing);
+        outgoing
+    }
+
+    fn process(
+        &mut self,
+        layer: tl::types::DecryptedMessageLayer,
+        date: i32,
+        outgoing: &mut Vec<Outgoing>,
+    ) {
+        self.received += 1;
+        self.key_usage += 1;
+        self.their_layer = layer.layer;
+
+        let date = utils::date(date);
+        let action = match layer.message {
+            tl::enums::DecryptedMessage::Message(message) => {
+                self.updates
+                    .push_back(SecretChatUpdate::NewMessage { message, date });
+                return;
+            }
+            tl::enums::DecryptedMessage::Service(service) => service.action,
+        };
+
+        use tl::enums::DecryptedMessageAction as A;
+        match action {
+            A::NotifyLayer(notify) => self.their_layer = notify.layer,
+            A::Noop => {}
+            A::Resend(resend) => {
+                let resent = self
+                    .history
+                    .iter()
+                    .filter(|sent| {
+                        (resend.start_seq_no..=resend.end_seq_no).contains(&sent.out_seq_no)
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+                outgoing.extend(resent.into_iter().filter_map(|layer| self.encrypt(layer)));
+            }
+            A::RequestKey(request) => self.key_requested(request, outgoing),
+            A::AcceptKey(accept) => self.key_accepted(accept, outgoing),
+            A::CommitKey(commit) => match self.exchange.take() {
+                Some(KeyExchange::Accepted { id, key })
+                    if id == commit.exchange_id
+                        && crypto::key_fingerprint(&key) == commit.key_fingerprint =>
+                {
+                    self.previous_key = self.key.replace(key);
+                    self.key_usage = 0;
+                }
+                exchange => {
+                    self.exchange = exchange;
+                    self.abort_key(commit.exchange_id, outgoing);
+                }
+            },
+            A::AbortKey(abort) => {
+                if self.exchange_id() == Some(abort.exchange_id) {
+                    self.exchange = None;
+                }
+            }
+            action => self
+                .updates
+                .push_back(SecretChatUpdate::Service { action, date }),
+        }
+    }
+
+    fn exchange_id(&self) -> Option<i64> {
+        match &self.exchange {
+            Some(KeyExchange::Requested { id, .. } | KeyExchange::Accepted { id, .. }) => Some(*id),
+            None => None,
+        }
+    }
+
+    fn key_requested(
+        &mut self,
+        request: tl::types::DecryptedMessageActionRequestKey,
+        outgoing: &mut Vec<Outgoing>,
+    ) {
+        // "If both clients request a key at the same time, the one with the larger
+        // exchange_id proceeds, and the other request is discarded."
+        if let Some(KeyExchange::Requested { id, .. }) = &self.exchange {
+            if *id > request.exchange_id {
+                return;
+            }
+        }
+
+        let secret = crypto::generate_secret(&[]);
+        let values = crypto::calculate_g_a(self.dh.g, &self.dh.p, &secret)
+            .zip(crypto::calculate_key(&request.g_a, &self.dh.p, &secret));
+        let Some((g_b, key)) = values else {
+            self.exchange = None;
+            self.abort_key(request.exchange_id, outgoing);
+            return;
+        };
+
+        self.exchange = Some(KeyExchange::Accepted {
+            id: request.exchange_id,
+            key,
+        });
+        outgoing.push(
+            self.prepare_service(
+                tl::types::DecryptedMessageActionAcceptKey {
+                    exchange_id: request.exchange_id,
+                    g_b: tl::byte_string(g_b.to_vec()),
+                    key_fingerprint: crypto::key_fingerprint(&key),
+                }
+                .into(),
+            ),
+        );
+    }
+
+    fn key_accepted(
+        &mut self,
+        accept: tl::types::DecryptedMessageActionAcceptKey,
+        outgoing: &mut Vec<Outgoing>,
+    ) {
+        let key = match &self.exchange {
+            Some(KeyExchange::Requested { id, secret }) if *id == accept.exchange_id => {
+                crypto::calculate_key(&accept.g_b, &self.dh.p, secret)
+                    .filter(|key| crypto::key_fingerprint(key) == accept.key_fingerprint)
+            }
+            _ => return,
+        };
+
+        self.exchange = None;
+        let Some(key) = key else {
+            self.abort_key(accept.exchange_id, outgoing);
+            return;
+        };
+
+        // The commit is the last message sent with the old key.
+        outgoing.push(
+            self.prepare_service(
+                tl::types::DecryptedMessageActionCommitKey {
+                    exchange_id: accept.exchange_id,
+                    key_fingerprint: accept.key_fingerprint,
+                }
+                .into(),
+            ),
+        );
+        self.previous_key = self.key.replace(key);
+        self.key_usage = 0;
+    }
+
+    fn abort_key(&mut self, exchange_id: i64, outgoing: &mut Vec<Outgoing>) {
+        outgoing.push(
+            self.prepare_service(tl::types::DecryptedMessageActionAbortKey { exchange_id }.into()),
+        );
+    }
+
+    fn maybe_rekey(&mut self, outgoing: &mut Vec<Outgoing>) {
+        if self.key_usage < REKEY_AFTER_MESSAGES || self.exchange.is_some() || self.key.is_none() {
+            return;
+        }
+
+        let secret = crypto::generate_secret(&[]);
+        let Some(g_a) = crypto::calculate_g_a(self.dh.g, &self.dh.p, &secret) else {
+            return;
+        };
+
+        let id = generate_random_id();
+        self.exchange = Some(KeyExchange::Requested { id, secret });
+        outgoing.push(
+            self.prepare_service(
+                tl::types::DecryptedMessageActionRequestKey {
+                    exchange_id: id,
+                    g_a: tl::byte_string(g_a.to_vec()),
+                }
+                .into(),
+            ),
+        );
+    }
+
+    fn prepare_service(&mut self, action: tl::enums::DecryptedMessageAction) -> Outgoing {
+        self.prepare(
+            tl::types::DecryptedMessageService {
+                random_id: generate_random_id(),
+                action,
+            }
+            .into(),
+        )
+    }
+
+    // Wrap the message in a layer with the next sequence numbers and encrypt it.
+    //
+    // The key must be known by the time messages are sent.
+    fn prepare(&mut self, message: tl::enums::DecryptedMessage) -> Outgoing {
+        let layer = tl::types::DecryptedMessageLayer {
+            random_bytes: tl::byte_string(crypto::generate_layer_random_bytes()),
+            layer: SECRET_LAYER,
+            in_seq_no: self.in_seq_no(),
+            out_seq_no: self.out_seq_no(),
+            message,
+        };
+        self.sent += 1;
+        self.key_usage += 1;
+
+        if self.history.len() == MAX_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(layer.clone());
+
+        self.encrypt(layer)
+            .expect("key must be known to send messages")
+    }
+
+    fn encrypt(&self, layer: tl::types::DecryptedMessageLayer) -> Option<Outgoing> {
+        let key = self.key.as_ref()?;
+        let random_id = layer.message.random_id();
+        let service = matches!(layer.message, tl::enums::DecryptedMessage::Service(_));
+        let plaintext = tl::enums::DecryptedMessageLayer::Layer(layer).to_bytes();
+        Some(Outgoing {
+            random_id,
+            service,
+            data: crypto::encrypt_message(&plaintext, key, self.originator),
+        })
+    }
+}
+
+pub(crate) fn route_update(client: &Client, update: tl::enums::Update) -> Option<Update> {
+    let chat_id = match &update {
+        tl::enums::Update::NewEncryptedMessage(u) => match &u.message {
+            tl::enums::EncryptedMessage::Message(m) => m.chat_id,
+            tl::enums::EncryptedMessage::Service(m) => m.chat_id,
+        },
+        tl::enums::Update::Encryption(u) => u.chat.id(),
+        _ => return Some(Update::Raw(update)),
+    };
+
+    let update = match client.0.secret_chats.lock().unwrap().get(&chat_id) {
+        Some(tx) => match tx.send(update) {
+            Ok(()) => return None,
+            Err(mpsc::error::SendError(update)) => update,
+        },
+        None => update,
+    };
+
+    match update {
+        tl::enums::Update::Encryption(tl::types::UpdateEncryption {
+            chat: tl::enums::EncryptedChat::Requested(raw),
+            ..
+        }) => Some(Update::SecretChatRequest(SecretChatRequest::from_raw(
+            client, raw,
+        ))),
+        update => Some(Update::Raw(update)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grammers_crypto::hex;
+
+    const P: &str = "c71caeb9c6b1c9048e6c522f70f13f73980d40238e3e21c14934d037563d930f\
+        48198a0aa7c14058229493d22530f4dbfa336f6e0ac925139543aed44cce7c37\
+        20fd51f69458705ac68cd4fe6b6b13abdc9746512969328454f18faf8c595f64\
+        2477fe96bb2a941d5bcd1d4ac8cc49880708fa9b378e3c4f3a9060bee67cf9a4\
+        a4a695811051907e162753b56b0f6b410dba74d8a84b2a14b3144e0ef1284754\
+        fd17ed950d5965b4b9dd46582db1178d169c6bc465b0d6ff9ca3928fef5b9ae4\
+        e418fc15e83ebea0f87fa9ff5eed70050ded2849f47bf959d956850ce929851f\
+        0d8115f635b105ee2e4e15d04b2454bf6f4fadf034b10403119cd8e3b92fcc5b";
+
+    fn get_states() -> (State, State) {
+        let dh = DhParams {
+            version: 1,
+            g: 3,
+            p: hex::from_hex(P),
+        };
+        let key = crypto::generate_secret(&[]);
+        let mut originator = State::new(true, dh.clone());
+        let mut responder = State::new(false, dh);
+        originator.key = Some(key);
+        responder.key = Some(key);
+        (originator, responder)
+    }
+
+    fn text(message: &str) -> tl::enums::DecryptedMessage {
+        tl::types::DecryptedMessage {
+            no_webpage: false,
+            silent: false,
+            random_id: generate_random_id(),
+            ttl: 0,
+            message: message.to_string(),
+            media: None,
+            entities: None,
+            via_bot_name: None,
+            reply_to_random_id: None,
+            grouped_id: None,
+        }
+        .into()
+    }
+
+    fn deliver(to: &mut State, outgoing: Vec<Outgoing>) -> Vec<Outgoing> {
+        outgoing
+            .into_iter()
+            .flat_map(|o| to.receive(&o.data, 0))
+            .collect()
+    }
+
+    fn received_texts(state: &mut State) -> Vec<String> {
+        state
+            .updates
+            .drain(..)
+            .filter_map(|update| match update {
+                SecretChatUpdate::NewMessage { message, .. } => Some(message.message),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn check_seq_no_parity() {
+        let (mut originator, mut responder) = get_states();
+        assert_eq!((originator.out_seq_no(), originator.in_seq_no()), (1, 0));
+        assert_eq!((responder.out_seq_no(), responder.in_seq_no()), (0, 1));
+
+        let outgoing = vec![originator.prepare(text("hi"))];
+        assert!(deliver(&mut responder, outgoing).is_empty());
+        assert_eq!((originator.out_seq_no(), responder.in_seq_no()), (3, 3));
+        assert_eq!(received_texts(&mut responder), ["hi"]);
+    }
+
+    #[test]
+    fn check_out_of_order_delivery() {
+        let (mut originator, mut responder) = get_states();
+        let first = originator.prepare(text("first"));
+        let second = originator.prepare(text("second"));
+
+        let resend = deliver(&mut responder, vec![second]);
+        assert_eq!(resend.len(), 1);
+        assert!(received_texts(&mut responder).is_empty());
+
+        // The originator would resend the missing message, so the duplicate is ignored.
+        let resent = deliver(&mut originator, resend);
+        assert_eq!(resent.len(), 1);
+        assert!(deliver(&mut responder, vec![first]).is_empty());
+        assert!(deliver(&mut responder, resent).is_empty());
+        assert_eq!(received_texts(&mut responder), ["first", "second"]);
+    }
+
+    #[test]
+    fn check_rekey() {
+        let (mut originator, mut responder) = get_states();
+        let old_key = originator.key;
+
+        let mut request = Vec::new();
+        originator.key_usage = REKEY_AFTER_MESSAGES;
+        originator.maybe_rekey(&mut request);
+        assert_eq!(request.len(), 1);
+
+        let accept = deliver(&mut responder, request);
+        let commit = deliver(&mut originator, accept);
+        assert_ne!(originator.key, old_key);
+        assert!(deliver(&mut responder, commit).is_empty());
+        assert_eq!(originator.key, responder.key);
+        assert!(originator.exchange.is_none() && responder.exchange.is_none());
+
+        let outgoing = vec![responder.prepare(text("rekeyed"))];
+        deliver(&mut originator, outgoing);
+        assert_eq!(received_texts(&mut originator), ["rekeyed"]);
+    }
+
+    #[test]
+    fn check_rekey_collision() {
+        let (mut originator, mut responder) = get_states();
+        let mut requests = Vec::new();
+        originator.key_usage = REKEY_AFTER_MESSAGES;
+        responder.key_usage = REKEY_AFTER_MESSAGES;
+        originator.maybe_rekey(&mut requests);
+        responder.maybe_rekey(&mut requests);
+        let responder_request = requests.pop().unwrap();
+        let originator_request = requests.pop().unwrap();
+
+        let winner_is_originator = originator.exchange_id() > responder.exchange_id();
+        let from_responder = deliver(&mut responder, vec![originator_request]);
+        let from_originator = deliver(&mut originator, vec![responder_request]);
+        assert_eq!(from_responder.len(), usize::from(winner_is_originator));
+        assert_eq!(from_originator.len(), usize::from(!winner_is_originator));
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::types::{EntityText, Media, Photo};
+use grammers_tl_types as tl;
+
+/// A sponsored message shown in a broadcast channel.
+///
+/// Clients are expected to display these after the last message of the channel, and to report
+/// both views and clicks through [`Client::mark_sponsored_viewed`] and
+/// [`Client::mark_sponsored_clicked`].
+///
+/// [`Client::mark_sponsored_viewed`]: crate::Client::mark_sponsored_viewed
+/// [`Client::mark_sponsored_clicked`]: crate::Client::mark_sponsored_clicked
+#[derive(Clone, Debug, PartialEq)]
+pub struct SponsoredMessage {
+    pub raw: tl::types::SponsoredMessage,
+}
+
+impl SponsoredMessage {
+    pub(crate) fn from_raw(message: tl::enums::SponsoredMessage) -> Self {
+        let tl::enums::SponsoredMessage::Message(raw) = message;
+        Self { raw }
+    }
+
+    /// The opaque identifier used to report views and clicks of this message.
+    pub fn random_id(&self) -> &[u8] {
+        &self.raw.random_id
+    }
+
+    /// The title of the sponsored message, commonly the name of the advertiser.
+    pub fn title(&self) -> &str {
+        &self.raw.title
+    }
+
+    /// The text of the sponsored message.
+    pub fn text(&self) -> &str {
+        &self.raw.message
+    }
+
+    /// Like [`text`](Self::text), but along with its formatting entities.
+    pub fn entity_text(&self) -> EntityText {
+        EntityText::new(
+            self.raw.message.clone(),
+            self.raw.entities.clone().unwrap_or_default(),
+        )
+    }
+
+    /// The URL the user should be taken to when the message is clicked.
+    pub fn url(&self) -> &str {
+        &self.raw.url
+    }
+
+    /// The text of the button that opens the [`url`](Self::url).
+    pub fn button_text(&self) -> &str {
+        &self.raw.button_text
+    }
+
+    /// The photo to display next to the message, if any.
+    pub fn photo(&self) -> Option<Photo> {
+        self.raw.photo.clone().map(Photo::from_raw)
+    }
+
+    /// The media to display inside the message, if any.
+    pub fn media(&self) -> Option<Media> {
+        self.raw.media.clone().and_then(Media::from_raw)
+    }
+
+    /// Information about the sponsor, which must be shown to the user on request.
+    pub fn sponsor_info(&self) -> Option<&str> {
+        self.raw.sponsor_info.as_deref()
+    }
+
+    /// Additional information about the sponsored message, which must be shown to the user on
+    /// request.
+    pub fn additional_info(&self) -> Option<&str> {
+        self.raw.additional_info.as_deref()
+    }
+
+    /// Whether the message should be labeled as "recommended" instead of "sponsored".
+    pub fn is_recommended(&self) -> bool {
+        self.raw.recommended
+    }
+
+    /// Whether the message can be reported.
+    pub fn can_report(&self) -> bool {
+        self.raw.can_report
+    }
+}
+
+/// The sponsored messages to display in a broadcast channel.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SponsoredMessages {
+    messages: Vec<SponsoredMessage>,
+    posts_between: Option<i32>,
+}
+
+impl SponsoredMessages {
+    pub(crate) fn from_raw(messages: tl::enums::messages::SponsoredMessages) -> Self {
+        match messages {
+            tl::enums::messages::SponsoredMessages::Messages(messages) => Self {
+                messages: messages
+                    .messages
+                    .into_iter()
+                    .map(SponsoredMessage::from_raw)
+                    .collect(),
+                posts_between: messages.posts_between,
+            },
+            tl::enums::messages::SponsoredMessages::Empty => Self::default(),
+        }
+    }
+
+    /// The sponsored messages to display, which may be empty.
+    pub fn messages(&self) -> &[SponsoredMessage] {
+        &self.messages
+    }
+
+    /// If present, sponsored messages should be shown repeatedly, every this many posts, instead
+    /// of only once after the last post.
+    pub fn posts_between(&self) -> Option<i32> {
+        self.posts_between
+    }
+
+    /// Consume the list, returning the sponsored messages it contains.
+    pub fn into_messages(self) -> Vec<SponsoredMessage> {
+        self.messages
+    }
+}
@@ -0,0 +1,59 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::utils;
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+
+/// Telegram Stars earned by a channel or bot, for example, through paid media or subscriptions.
+#[derive(Clone, Debug)]
+pub struct StarsRevenue {
+    pub raw: tl::types::StarsRevenueStatus,
+    usd_rate: f64,
+}
+
+impl StarsRevenue {
+    pub(crate) fn from_raw(stats: tl::enums::payments::StarsRevenueStats) -> Self {
+        let tl::enums::payments::StarsRevenueStats::Stats(stats) = stats;
+        let tl::enums::StarsRevenueStatus::Status(raw) = stats.status;
+        Self {
+            raw,
+            usd_rate: stats.usd_rate,
+        }
+    }
+
+    /// Amount of Stars not yet withdrawn.
+    pub fn current_balance(&self) -> i64 {
+        self.raw.current_balance
+    }
+
+    /// Amount of Stars which can already be withdrawn.
+    pub fn available_balance(&self) -> i64 {
+        self.raw.available_balance
+    }
+
+    /// Total amount of Stars ever earned.
+    pub fn overall_revenue(&self) -> i64 {
+        self.raw.overall_revenue
+    }
+
+    /// Whether the earned Stars can currently be withdrawn.
+    pub fn withdrawal_enabled(&self) -> bool {
+        self.raw.withdrawal_enabled
+    }
+
+    /// The date after which the next withdrawal will be possible, if withdrawals are temporarily
+    /// unavailable.
+    pub fn next_withdrawal_date(&self) -> Option<DateTime<Utc>> {
+        self.raw.next_withdrawal_at.map(utils::date)
+    }
+
+    /// Value of a single Star in US dollars.
+    pub fn usd_rate(&self) -> f64 {
+        self.usd_rate
+    }
+}
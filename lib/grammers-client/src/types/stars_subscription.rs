@@ -0,0 +1,80 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{Chat, ChatMap};
+use crate::utils;
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+use std::sync::Arc;
+
+/// A subscription paid with Telegram Stars, such as the one used to join a channel through a
+/// subscription invite link.
+#[derive(Clone, Debug)]
+pub struct StarsSubscription {
+    pub raw: tl::types::StarsSubscription,
+    chat: Option<Chat>,
+}
+
+impl StarsSubscription {
+    pub(crate) fn from_raw(
+        subscription: tl::enums::StarsSubscription,
+        chats: &Arc<ChatMap>,
+    ) -> Self {
+        let tl::enums::StarsSubscription::Subscription(raw) = subscription;
+        Self {
+            chat: chats.get(&raw.peer).cloned(),
+            raw,
+        }
+    }
+
+    /// Unique identifier of the subscription.
+    pub fn id(&self) -> &str {
+        &self.raw.id
+    }
+
+    /// The chat the subscription is for, if it was included in the response.
+    pub fn chat(&self) -> Option<&Chat> {
+        self.chat.as_ref()
+    }
+
+    /// Date until which the subscription is paid for.
+    pub fn until_date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.until_date)
+    }
+
+    /// Duration of each billing period, in seconds.
+    pub fn period(&self) -> i32 {
+        let tl::enums::StarsSubscriptionPricing::Pricing(pricing) = &self.raw.pricing;
+        pricing.period
+    }
+
+    /// Amount of Telegram Stars charged every period.
+    pub fn amount(&self) -> i64 {
+        let tl::enums::StarsSubscriptionPricing::Pricing(pricing) = &self.raw.pricing;
+        pricing.amount
+    }
+
+    /// Whether the subscription was canceled and will not be renewed.
+    pub fn canceled(&self) -> bool {
+        self.raw.canceled
+    }
+
+    /// Whether the subscription expired but can still be renewed.
+    pub fn can_refulfill(&self) -> bool {
+        self.raw.can_refulfill
+    }
+
+    /// Whether the subscription could not be renewed due to an insufficient Stars balance.
+    pub fn missing_balance(&self) -> bool {
+        self.raw.missing_balance
+    }
+
+    /// Hash of the invite link used to join the chat, if the subscription was made that way.
+    pub fn chat_invite_hash(&self) -> Option<&str> {
+        self.raw.chat_invite_hash.as_deref()
+    }
+}
@@ -0,0 +1,314 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::utils;
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+
+/// A statistic value for the current period, along with its value for the previous period.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatsValue {
+    pub current: f64,
+    pub previous: f64,
+}
+
+impl StatsValue {
+    fn from_raw(value: tl::enums::StatsAbsValueAndPrev) -> Self {
+        let tl::enums::StatsAbsValueAndPrev::Prev(value) = value;
+        Self {
+            current: value.current,
+            previous: value.previous,
+        }
+    }
+
+    /// How much the value changed since the previous period.
+    pub fn change(&self) -> f64 {
+        self.current - self.previous
+    }
+}
+
+/// A statistics graph, which may need to be loaded with [`Client::load_stats_graph`] before
+/// its data can be used.
+///
+/// The data is a JSON object meant to be rendered by a charting library.
+///
+/// [`Client::load_stats_graph`]: crate::Client::load_stats_graph
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatsGraph {
+    pub raw: tl::enums::StatsGraph,
+    pub(crate) dc_id: Option<i32>,
+}
+
+impl StatsGraph {
+    pub(crate) fn from_raw(raw: tl::enums::StatsGraph, dc_id: Option<i32>) -> Self {
+        Self { raw, dc_id }
+    }
+
+    /// Whether the graph still needs to be loaded before its data can be accessed.
+    pub fn needs_loading(&self) -> bool {
+        matches!(self.raw, tl::enums::StatsGraph::Async(_))
+    }
+
+    /// The JSON data of the graph, if it is loaded.
+    pub fn json(&self) -> Option<&str> {
+        match &self.raw {
+            tl::enums::StatsGraph::Graph(graph) => {
+                let tl::enums::DataJson::Json(json) = &graph.json;
+                Some(&json.data)
+            }
+            _ => None,
+        }
+    }
+
+    /// The token that can be used to load a zoomed-in version of this graph, if any.
+    pub fn zoom_token(&self) -> Option<&str> {
+        match &self.raw {
+            tl::enums::StatsGraph::Graph(graph) => graph.zoom_token.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// The error Telegram returned instead of the graph, if it could not be generated.
+    pub fn error(&self) -> Option<&str> {
+        match &self.raw {
+            tl::enums::StatsGraph::Error(error) => Some(&error.error),
+            _ => None,
+        }
+    }
+}
+
+/// Statistics of a broadcast channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BroadcastStats {
+    pub raw: tl::types::stats::BroadcastStats,
+    dc_id: Option<i32>,
+}
+
+impl BroadcastStats {
+    pub(crate) fn from_raw(stats: tl::enums::stats::BroadcastStats, dc_id: Option<i32>) -> Self {
+        let tl::enums::stats::BroadcastStats::Stats(raw) = stats;
+        Self { raw, dc_id }
+    }
+
+    /// The period of time covered by these statistics.
+    pub fn period(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        let tl::enums::StatsDateRangeDays::Days(period) = &self.raw.period;
+        (utils::date(period.min_date), utils::date(period.max_date))
+    }
+
+    /// Number of followers of the channel.
+    pub fn followers(&self) -> StatsValue {
+        StatsValue::from_raw(self.raw.followers.clone())
+    }
+
+    /// Average number of views per post.
+    pub fn views_per_post(&self) -> StatsValue {
+        StatsValue::from_raw(self.raw.views_per_post.clone())
+    }
+
+    /// Average number of shares per post.
+    pub fn shares_per_post(&self) -> StatsValue {
+        StatsValue::from_raw(self.raw.shares_per_post.clone())
+    }
+
+    /// Average number of reactions per post.
+    pub fn reactions_per_post(&self) -> StatsValue {
+        StatsValue::from_raw(self.raw.reactions_per_post.clone())
+    }
+
+    /// Average number of views per story.
+    pub fn views_per_story(&self) -> StatsValue {
+        StatsValue::from_raw(self.raw.views_per_story.clone())
+    }
+
+    /// Average number of shares per story.
+    pub fn shares_per_story(&self) -> StatsValue {
+        StatsValue::from_raw(self.raw.shares_per_story.clone())
+    }
+
+    /// Average number of reactions per story.
+    pub fn reactions_per_story(&self) -> StatsValue {
+        StatsValue::from_raw(self.raw.reactions_per_story.clone())
+    }
+
+    /// Percentage of followers with notifications enabled, from 0 to 100.
+    pub fn enabled_notifications(&self) -> f64 {
+        let tl::enums::StatsPercentValue::Value(value) = &self.raw.enabled_notifications;
+        if value.total == 0.0 {
+            0.0
+        } else {
+            value.part * 100.0 / value.total
+        }
+    }
+
+    /// Follower growth over time.
+    pub fn growth_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.growth_graph.clone(), self.dc_id)
+    }
+
+    /// Followers joining and leaving over time.
+    pub fn followers_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.followers_graph.clone(), self.dc_id)
+    }
+
+    /// Followers muting and unmuting the channel over time.
+    pub fn mute_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.mute_graph.clone(), self.dc_id)
+    }
+
+    /// Views by hour of the day.
+    pub fn top_hours_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.top_hours_graph.clone(), self.dc_id)
+    }
+
+    /// Views and shares of posts over time.
+    pub fn interactions_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.interactions_graph.clone(), self.dc_id)
+    }
+
+    /// Instant View interactions over time.
+    pub fn iv_interactions_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.iv_interactions_graph.clone(), self.dc_id)
+    }
+
+    /// Views by source.
+    pub fn views_by_source_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.views_by_source_graph.clone(), self.dc_id)
+    }
+
+    /// New followers by source.
+    pub fn new_followers_by_source_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.new_followers_by_source_graph.clone(), self.dc_id)
+    }
+
+    /// Followers by language.
+    pub fn languages_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.languages_graph.clone(), self.dc_id)
+    }
+
+    /// Reactions to posts by emotion.
+    pub fn reactions_by_emotion_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.reactions_by_emotion_graph.clone(), self.dc_id)
+    }
+
+    /// Views and shares of stories over time.
+    pub fn story_interactions_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.story_interactions_graph.clone(), self.dc_id)
+    }
+
+    /// Reactions to stories by emotion.
+    pub fn story_reactions_by_emotion_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(
+            self.raw.story_reactions_by_emotion_graph.clone(),
+            self.dc_id,
+        )
+    }
+}
+
+/// Statistics of a megagroup.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MegagroupStats {
+    pub raw: tl::types::stats::MegagroupStats,
+    dc_id: Option<i32>,
+}
+
+impl MegagroupStats {
+    pub(crate) fn from_raw(stats: tl::enums::stats::MegagroupStats, dc_id: Option<i32>) -> Self {
+        let tl::enums::stats::MegagroupStats::Stats(raw) = stats;
+        Self { raw, dc_id }
+    }
+
+    /// The period of time covered by these statistics.
+    pub fn period(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        let tl::enums::StatsDateRangeDays::Days(period) = &self.raw.period;
+        (utils::date(period.min_date), utils::date(period.max_date))
+    }
+
+    /// Number of members of the group.
+    pub fn members(&self) -> StatsValue {
+        StatsValue::from_raw(self.raw.members.clone())
+    }
+
+    /// Number of messages sent in the group.
+    pub fn messages(&self) -> StatsValue {
+        StatsValue::from_raw(self.raw.messages.clone())
+    }
+
+    /// Number of members who viewed messages.
+    pub fn viewers(&self) -> StatsValue {
+        StatsValue::from_raw(self.raw.viewers.clone())
+    }
+
+    /// Number of members who sent messages.
+    pub fn posters(&self) -> StatsValue {
+        StatsValue::from_raw(self.raw.posters.clone())
+    }
+
+    /// Member growth over time.
+    pub fn growth_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.growth_graph.clone(), self.dc_id)
+    }
+
+    /// Members joining and leaving over time.
+    pub fn members_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.members_graph.clone(), self.dc_id)
+    }
+
+    /// New members by source.
+    pub fn new_members_by_source_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.new_members_by_source_graph.clone(), self.dc_id)
+    }
+
+    /// Members by language.
+    pub fn languages_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.languages_graph.clone(), self.dc_id)
+    }
+
+    /// Messages sent over time.
+    pub fn messages_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.messages_graph.clone(), self.dc_id)
+    }
+
+    /// Member actions over time.
+    pub fn actions_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.actions_graph.clone(), self.dc_id)
+    }
+
+    /// Activity by hour of the day.
+    pub fn top_hours_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.top_hours_graph.clone(), self.dc_id)
+    }
+
+    /// Activity by day of the week.
+    pub fn weekdays_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.weekdays_graph.clone(), self.dc_id)
+    }
+}
+
+/// Statistics of a single message in a channel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MessageStats {
+    pub raw: tl::types::stats::MessageStats,
+    dc_id: Option<i32>,
+}
+
+impl MessageStats {
+    pub(crate) fn from_raw(stats: tl::enums::stats::MessageStats, dc_id: Option<i32>) -> Self {
+        let tl::enums::stats::MessageStats::Stats(raw) = stats;
+        Self { raw, dc_id }
+    }
+
+    /// Views and shares of the message over time.
+    pub fn views_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.views_graph.clone(), self.dc_id)
+    }
+
+    /// Reactions to the message by emotion.
+    pub fn reactions_by_emotion_graph(&self) -> StatsGraph {
+        StatsGraph::from_raw(self.raw.reactions_by_emotion_graph.clone(), self.dc_id)
+    }
+}
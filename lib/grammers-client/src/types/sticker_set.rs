@@ -0,0 +1,155 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::types::media::{Document, Sticker, Uploaded};
+use crate::utils;
+use chrono::{DateTime, Utc};
+use grammers_tl_types as tl;
+
+/// A collection of stickers, also known as a sticker pack.
+#[derive(Clone, Debug)]
+pub struct StickerSet {
+    pub raw: tl::types::StickerSet,
+    stickers: Vec<Sticker>,
+}
+
+/// A sticker to be uploaded into a sticker set.
+///
+/// Used by [`Client::create_sticker_set`] and [`Client::add_sticker_to_set`].
+///
+/// [`Client::create_sticker_set`]: crate::Client::create_sticker_set
+/// [`Client::add_sticker_to_set`]: crate::Client::add_sticker_to_set
+#[derive(Clone, Debug)]
+pub struct InputSticker {
+    pub(crate) file: Uploaded,
+    pub(crate) emoji: String,
+    pub(crate) mime_type: String,
+    pub(crate) keywords: Option<String>,
+}
+
+impl StickerSet {
+    pub(crate) fn from_raw(set: tl::enums::StickerSet) -> Self {
+        let tl::enums::StickerSet::Set(raw) = set;
+        Self {
+            raw,
+            stickers: Vec::new(),
+        }
+    }
+
+    pub(crate) fn from_full(set: tl::types::messages::StickerSet) -> Self {
+        let mut result = Self::from_raw(set.set);
+        result.stickers = set
+            .documents
+            .into_iter()
+            .filter_map(|document| {
+                Sticker::from_document(&Document::from_raw_media(tl::types::MessageMediaDocument {
+                    nopremium: false,
+                    spoiler: false,
+                    video: false,
+                    round: false,
+                    voice: false,
+                    document: Some(document),
+                    alt_document: None,
+                    ttl_seconds: None,
+                }))
+            })
+            .collect();
+        result
+    }
+
+    pub(crate) fn to_input(&self) -> tl::enums::InputStickerSet {
+        tl::types::InputStickerSetId {
+            id: self.raw.id,
+            access_hash: self.raw.access_hash,
+        }
+        .into()
+    }
+
+    /// Unique identifier of the set.
+    pub fn id(&self) -> i64 {
+        self.raw.id
+    }
+
+    /// The title of the set.
+    pub fn title(&self) -> &str {
+        &self.raw.title
+    }
+
+    /// The short name of the set, used in links such as `t.me/addstickers/short_name`.
+    pub fn short_name(&self) -> &str {
+        &self.raw.short_name
+    }
+
+    /// How many stickers the set contains.
+    pub fn count(&self) -> usize {
+        self.raw.count.max(0) as usize
+    }
+
+    /// Whether the set is an official set made by Telegram.
+    pub fn is_official(&self) -> bool {
+        self.raw.official
+    }
+
+    /// Whether the set contains masks rather than stickers.
+    pub fn is_masks(&self) -> bool {
+        self.raw.masks
+    }
+
+    /// Whether the set contains custom emoji rather than stickers.
+    pub fn is_emojis(&self) -> bool {
+        self.raw.emojis
+    }
+
+    /// Whether the set was archived by the logged-in user.
+    pub fn is_archived(&self) -> bool {
+        self.raw.archived
+    }
+
+    /// When the set was installed by the logged-in user, if it is installed.
+    pub fn installed_date(&self) -> Option<DateTime<Utc>> {
+        self.raw.installed_date.map(utils::date)
+    }
+
+    /// The stickers in the set.
+    ///
+    /// This is empty for sets obtained from [`Client::iter_installed_sticker_sets`], which only
+    /// include the information about the set. Use [`Client::get_sticker_set`] to fetch them.
+    ///
+    /// [`Client::iter_installed_sticker_sets`]: crate::Client::iter_installed_sticker_sets
+    /// [`Client::get_sticker_set`]: crate::Client::get_sticker_set
+    pub fn stickers(&self) -> &[Sticker] {
+        &self.stickers
+    }
+}
+
+impl InputSticker {
+    /// Create a new sticker from an uploaded file, associated with the given emoji.
+    ///
+    /// The file is expected to be a static WEBP or PNG image. Use [`InputSticker::mime_type`]
+    /// for animated or video stickers.
+    pub fn new<E: Into<String>>(file: Uploaded, emoji: E) -> Self {
+        Self {
+            file,
+            emoji: emoji.into(),
+            mime_type: "image/png".to_string(),
+            keywords: None,
+        }
+    }
+
+    /// Change the mime type of the file, such as `application/x-tgsticker` for animated stickers
+    /// or `video/webm` for video stickers.
+    pub fn mime_type<M: Into<String>>(mut self, mime_type: M) -> Self {
+        self.mime_type = mime_type.into();
+        self
+    }
+
+    /// Comma-separated keywords to also find the sticker by, besides its emoji.
+    pub fn keywords<K: Into<String>>(mut self, keywords: K) -> Self {
+        self.keywords = Some(keywords.into());
+        self
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{Chat, ChatMap, Media};
+use crate::utils;
+use chrono::{DateTime, Utc};
+use grammers_session::PackedChat;
+use grammers_tl_types as tl;
+use std::sync::Arc;
+
+/// A story posted by a user or channel, which will typically expire after a day.
+#[derive(Clone, Debug)]
+pub struct Story {
+    pub raw: tl::types::StoryItem,
+    chat: Option<Chat>,
+}
+
+/// Who can see a story sent with [`Client::send_story`].
+///
+/// [`Client::send_story`]: crate::Client::send_story
+#[derive(Clone, Debug, Default)]
+pub enum StoryPrivacy {
+    /// Everyone can see the story.
+    #[default]
+    Everyone,
+    /// Only contacts can see the story.
+    Contacts,
+    /// Only users in the close friends list can see the story.
+    CloseFriends,
+    /// Only the given users can see the story.
+    Users(Vec<PackedChat>),
+}
+
+impl Story {
+    pub(crate) fn from_raw(
+        story: tl::enums::StoryItem,
+        peer: &tl::enums::Peer,
+        chats: &Arc<ChatMap>,
+    ) -> Option<Self> {
+        match story {
+            tl::enums::StoryItem::Item(raw) => Some(Self {
+                chat: chats.get(raw.from_id.as_ref().unwrap_or(peer)).cloned(),
+                raw: *raw,
+            }),
+            tl::enums::StoryItem::Deleted(_) | tl::enums::StoryItem::Skipped(_) => None,
+        }
+    }
+
+    /// Unique identifier of the story among the stories posted by the same chat.
+    pub fn id(&self) -> i32 {
+        self.raw.id
+    }
+
+    /// The chat that posted the story, if it was included in the response.
+    pub fn chat(&self) -> Option<&Chat> {
+        self.chat.as_ref()
+    }
+
+    /// The date when the story was posted.
+    pub fn date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.date)
+    }
+
+    /// The date when the story will no longer be visible, unless it's pinned to the profile.
+    pub fn expire_date(&self) -> DateTime<Utc> {
+        utils::date(self.raw.expire_date)
+    }
+
+    /// The caption of the story, which may be empty.
+    pub fn caption(&self) -> &str {
+        self.raw.caption.as_deref().unwrap_or_default()
+    }
+
+    /// The photo or video shown in the story, if it's supported by the library.
+    pub fn media(&self) -> Option<Media> {
+        Media::from_raw(self.raw.media.clone())
+    }
+
+    /// Whether the story is pinned to the profile of the chat that posted it.
+    pub fn pinned(&self) -> bool {
+        self.raw.pinned
+    }
+
+    /// Whether the story is visible to everyone.
+    pub fn public(&self) -> bool {
+        self.raw.public
+    }
+
+    /// Whether the story was posted by the logged-in user.
+    pub fn outgoing(&self) -> bool {
+        self.raw.out
+    }
+}
+
+impl StoryPrivacy {
+    pub(crate) fn to_raw(&self) -> Vec<tl::enums::InputPrivacyRule> {
+        use tl::enums::InputPrivacyRule as Rule;
+
+        vec![match self {
+            Self::Everyone => Rule::InputPrivacyValueAllowAll,
+            Self::Contacts => Rule::InputPrivacyValueAllowContacts,
+            Self::CloseFriends => Rule::InputPrivacyValueAllowCloseFriends,
+            Self::Users(users) => tl::types::InputPrivacyValueAllowUsers {
+                users: users
+                    .iter()
+                    .map(|user| user.to_input_user_lossy())
+                    .collect(),
+            }
+            .into(),
+        }]
+    }
+}
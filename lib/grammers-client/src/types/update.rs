@@ -8,12 +8,18 @@
 
 use std::sync::Arc;
 
-use super::{CallbackQuery, ChatMap, InlineQuery, InlineSend, Message};
-use crate::{types::MessageDeletion, Client};
+use super::{
+    CallbackQuery, ChatMap, Draft, IncomingCall, InlineQuery, InlineSend, JoinRequest, Message,
+    PreCheckoutQuery, SecretChatRequest, ShippingQuery, Story, WebAppData,
+};
+use crate::types::{phone_call, secret_chat, MessageDeletion};
+use crate::Client;
+use grammers_session::{PackedChat, PackedType};
 use grammers_tl_types as tl;
 
 #[non_exhaustive]
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum Update {
     /// Occurs whenever a new text message or a message with media is produced.
     NewMessage(Message),
@@ -29,6 +35,39 @@ pub enum Update {
     InlineQuery(InlineQuery),
     /// Represents an update of user choosing the result of inline query and sending it to their chat partner.
     InlineSend(InlineSend),
+    /// Occurs when a user has confirmed the payment of an invoice sent by the bot, which must be
+    /// approved or rejected before the payment is processed.
+    PreCheckoutQuery(PreCheckoutQuery),
+    /// Occurs when a user has specified a shipping address for an invoice with a flexible price
+    /// sent by the bot.
+    ShippingQuery(ShippingQuery),
+    /// Occurs when a chat the logged-in user follows posts or edits a story.
+    NewStory(Story),
+    /// Occurs when a user requests to join a chat managed by the bot through an invite link that
+    /// requires approval.
+    ChatJoinRequest(JoinRequest),
+    /// Occurs when a basic group is upgraded into a megagroup, which has a different identifier.
+    ///
+    /// This replaces the [`Update::NewMessage`] for the service message in the old group. Sending
+    /// messages to the old group is retried in the new one automatically, but the chats stored by
+    /// your application should be updated.
+    ChatMigrated { from: PackedChat, to: PackedChat },
+    /// Occurs when a user sends data to the bot from a Web App opened with a keyboard button.
+    ///
+    /// This replaces the [`Update::NewMessage`] for the service message containing the data.
+    WebAppData(WebAppData),
+    /// Occurs when the logged-in user saves or clears a draft, possibly from a different device.
+    DraftUpdated(Draft),
+    /// Occurs when another user wants to start a secret chat with the logged-in user.
+    ///
+    /// Updates for secret chats which have a [`SecretChat`](super::SecretChat) alive are
+    /// delivered to it instead, and do not appear here.
+    SecretChatRequest(SecretChatRequest),
+    /// Occurs when another user calls the logged-in user.
+    ///
+    /// Updates for calls which have a [`PhoneCall`](super::PhoneCall) alive are delivered to
+    /// it instead, and do not appear here.
+    IncomingCall(IncomingCall),
     /// Raw events are not actual events.
     /// Instead, they are the raw Update object that Telegram sends. You
     /// normally shouldn’t need these.
@@ -43,9 +82,32 @@ impl Update {
     /// Create new friendly to use Update from its raw version and chat map
     pub fn new(client: &Client, update: tl::enums::Update, chats: &Arc<ChatMap>) -> Option<Self> {
         match update {
-            // NewMessage
+            // NewMessage, ChatMigrated or WebAppData
             tl::enums::Update::NewMessage(tl::types::UpdateNewMessage { message, .. }) => {
-                Message::from_raw(client, message, chats).map(Self::NewMessage)
+                Message::from_raw(client, message, chats).map(|message| match message.action() {
+                    Some(tl::enums::MessageAction::ChatMigrateTo(
+                        tl::types::MessageActionChatMigrateTo { channel_id },
+                    )) => Self::ChatMigrated {
+                        from: message.chat().pack(),
+                        to: chats
+                            .get(
+                                &tl::types::PeerChannel {
+                                    channel_id: *channel_id,
+                                }
+                                .into(),
+                            )
+                            .map(|chat| chat.pack())
+                            .unwrap_or(PackedChat {
+                                ty: PackedType::Megagroup,
+                                id: *channel_id,
+                                access_hash: None,
+                            }),
+                    },
+                    Some(tl::enums::MessageAction::WebViewDataSentMe(raw)) => {
+                        Self::WebAppData(WebAppData::from_raw(raw.clone(), message))
+                    }
+                    _ => Self::NewMessage(message),
+                })
             }
             tl::enums::Update::NewChannelMessage(tl::types::UpdateNewChannelMessage {
                 message,
@@ -93,6 +155,41 @@ impl Update {
                 Some(Self::InlineSend(InlineSend::from_raw(query, client, chats)))
             }
 
+            // PreCheckoutQuery
+            tl::enums::Update::BotPrecheckoutQuery(query) => Some(Self::PreCheckoutQuery(
+                PreCheckoutQuery::from_raw(query, client, chats),
+            )),
+
+            // ShippingQuery
+            tl::enums::Update::BotShippingQuery(query) => Some(Self::ShippingQuery(
+                ShippingQuery::from_raw(query, client, chats),
+            )),
+
+            // NewStory
+            tl::enums::Update::Story(tl::types::UpdateStory { peer, story }) => {
+                Story::from_raw(story, &peer, chats).map(Self::NewStory)
+            }
+
+            // ChatJoinRequest
+            tl::enums::Update::BotChatInviteRequester(request) => {
+                JoinRequest::from_raw(request, client, chats).map(Self::ChatJoinRequest)
+            }
+
+            // DraftUpdated
+            tl::enums::Update::DraftMessage(update) => {
+                Some(Self::DraftUpdated(Draft::from_update(update, chats)))
+            }
+
+            // SecretChatRequest, or routed to the corresponding SecretChat
+            update @ (tl::enums::Update::NewEncryptedMessage(_)
+            | tl::enums::Update::Encryption(_)) => secret_chat::route_update(client, update),
+
+            // IncomingCall, or routed to the corresponding PhoneCall
+            update @ (tl::enums::Update::PhoneCall(_)
+            | tl::enums::Update::PhoneCallSignalingData(_)) => {
+                phone_call::route_update(client, update)
+            }
+
             // Raw
             update => Some(Self::Raw(update)),
         }
@@ -0,0 +1,180 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Helpers for bots hosting Web Apps (also known as Mini Apps).
+//!
+//! <https://core.telegram.org/bots/webapps>
+use crate::types::{Chat, Message};
+use grammers_crypto::{hex, sha::hmac_sha256};
+use grammers_tl_types as tl;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+
+/// Data sent to the bot by a Web App opened from a keyboard button.
+///
+/// This is received as the service message the user sends to the bot upon calling
+/// `Telegram.WebApp.sendData` from within the Web App.
+#[derive(Clone, Debug)]
+pub struct WebAppData {
+    pub raw: tl::types::MessageActionWebViewDataSentMe,
+    message: Message,
+}
+
+impl WebAppData {
+    pub(crate) fn from_raw(
+        raw: tl::types::MessageActionWebViewDataSentMe,
+        message: Message,
+    ) -> Self {
+        Self { raw, message }
+    }
+
+    /// The text of the keyboard button which opened the Web App.
+    pub fn button_text(&self) -> &str {
+        &self.raw.text
+    }
+
+    /// The data sent by the Web App.
+    pub fn data(&self) -> &str {
+        &self.raw.data
+    }
+
+    /// The user who used the Web App.
+    pub fn sender(&self) -> Option<Chat> {
+        self.message.sender()
+    }
+
+    /// The chat where the data was sent.
+    pub fn chat(&self) -> Chat {
+        self.message.chat()
+    }
+
+    /// The service message containing the data.
+    pub fn message(&self) -> &Message {
+        &self.message
+    }
+}
+
+/// Decode a percent-encoded query string component.
+fn percent_decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut iter = value.bytes();
+    while let Some(b) = iter.next() {
+        bytes.push(match b {
+            b'+' => b' ',
+            b'%' => {
+                let hex = [iter.next()?, iter.next()?];
+                u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?
+            }
+            b => b,
+        });
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Validate the `initData` a Web App received from Telegram, using the token of the bot.
+///
+/// Web Apps should send the raw `Telegram.WebApp.initData` query string to their backend, which
+/// must check its signature before trusting any of the fields in it. If the signature is valid,
+/// the decoded fields are returned (without the `hash`).
+///
+/// Note that this does not check the `auth_date` field, so old data will still be considered
+/// valid. Applications should reject data that is older than they would accept.
+///
+/// # Examples
+///
+/// ```
+/// use grammers_client::types::web_app::validate_init_data;
+///
+/// # fn f(init_data: &str, bot_token: &str) {
+/// match validate_init_data(init_data, bot_token) {
+///     Some(fields) => println!("Valid data for user {:?}", fields.get("user")),
+///     None => println!("Invalid or forged data!"),
+/// }
+/// # }
+/// ```
+pub fn validate_init_data(init_data: &str, bot_token: &str) -> Option<HashMap<String, String>> {
+    let mut fields = init_data
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Some((percent_decode(key)?, percent_decode(value)?))
+        })
+        .collect::<Option<HashMap<_, _>>>()?;
+
+    let hash = hex::opt_from_hex(&fields.remove("hash")?)?;
+    if hash.len() != 32 {
+        return None;
+    }
+
+    let mut pairs = fields
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>();
+    pairs.sort();
+    let data_check_string = pairs.join("\n");
+
+    let secret_key = hmac_sha256(b"WebAppData", bot_token.as_bytes());
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(&secret_key).expect("HMAC can take key of any size");
+    mac.update(data_check_string.as_bytes());
+
+    // Compared in constant time, so that how long it takes reveals nothing about the valid hash.
+    mac.verify_slice(&hash).ok()?;
+    Some(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOT_TOKEN: &str = "123456:ABC-DEF";
+
+    fn sign(data_check_string: &str) -> String {
+        let secret_key = hmac_sha256(b"WebAppData", BOT_TOKEN.as_bytes());
+        hex::to_hex(&hmac_sha256(&secret_key, data_check_string.as_bytes()))
+    }
+
+    #[test]
+    fn valid_init_data() {
+        let hash = "ec700921dc8c0720876ec8cda29c489fd7e655516d0237d96053b7c1eedb0d6e";
+        assert_eq!(
+            sign("auth_date=1700000000\nquery_id=AAE\nuser={\"id\":1}"),
+            hash
+        );
+        let init_data =
+            format!("user=%7B%22id%22%3A1%7D&auth_date=1700000000&query_id=AAE&hash={hash}");
+
+        let fields = validate_init_data(&init_data, BOT_TOKEN).unwrap();
+        assert_eq!(fields.get("user").map(String::as_str), Some("{\"id\":1}"));
+        assert_eq!(fields.get("hash"), None);
+    }
+
+    #[test]
+    fn invalid_init_data() {
+        let hash = sign("auth_date=1700000000\nquery_id=AAE");
+        let tampered = format!("auth_date=1700000001&query_id=AAE&hash={hash}");
+        assert_eq!(validate_init_data(&tampered, BOT_TOKEN), None);
+        assert_eq!(validate_init_data("auth_date=1", BOT_TOKEN), None);
+        assert_eq!(validate_init_data("hash=%zz", BOT_TOKEN), None);
+    }
+
+    #[test]
+    fn init_data_hash_must_be_hex_of_the_right_length() {
+        let hash = sign("auth_date=1700000000");
+        let upper = format!("auth_date=1700000000&hash={}", hash.to_uppercase());
+        assert!(validate_init_data(&upper, BOT_TOKEN).is_some());
+
+        let short = format!("auth_date=1700000000&hash={}", &hash[..62]);
+        assert_eq!(validate_init_data(&short, BOT_TOKEN), None);
+        let long = format!("auth_date=1700000000&hash={hash}00");
+        assert_eq!(validate_init_data(&long, BOT_TOKEN), None);
+        let not_hex = format!("auth_date=1700000000&hash={}zz", &hash[..62]);
+        assert_eq!(validate_init_data(&not_hex, BOT_TOKEN), None);
+    }
+}
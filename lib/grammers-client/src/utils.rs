@@ -8,6 +8,8 @@
 
 use crate::types;
 use chrono::{DateTime, Utc};
+use grammers_mtproto::mtp;
+use grammers_mtsender::{InvocationError, ReadError};
 use grammers_session::{PackedChat, PackedType};
 use grammers_tl_types as tl;
 use std::sync::atomic::{AtomicI64, Ordering};
@@ -46,9 +48,23 @@ pub(crate) fn date(date: i32) -> DateTime<Utc> {
     DateTime::<Utc>::from_timestamp(date as i64, 0).expect("date out of range")
 }
 
+/// Error for a response of type `T` that Telegram should never return for the request made.
+pub(crate) fn unexpected_response<T: tl::Identifiable>() -> InvocationError {
+    InvocationError::Read(ReadError::Deserialize(
+        mtp::DeserializeError::UnexpectedConstructor {
+            id: T::CONSTRUCTOR_ID,
+        },
+    ))
+}
+
+/// Convert a date into the timestamp used by Telegram, clamped to the range it can represent.
+pub(crate) fn timestamp(date: DateTime<Utc>) -> i32 {
+    date.timestamp().clamp(0, i32::MAX as i64) as i32
+}
+
 pub(crate) fn extract_password_parameters(
     current_algo: &tl::enums::PasswordKdfAlgo,
-) -> (&Vec<u8>, &Vec<u8>, &Vec<u8>, &i32) {
+) -> (&[u8], &[u8], &[u8], &i32) {
     let tl::types::PasswordKdfAlgoSha256Sha256Pbkdf2Hmacsha512iter100000Sha256ModPow { salt1, salt2, p, g } = match current_algo {
         tl::enums::PasswordKdfAlgo::Unknown => panic!("Unknown KDF (most likely, the client is outdated and does not support the specified KDF algorithm)"),
         tl::enums::PasswordKdfAlgo::Sha256Sha256Pbkdf2Hmacsha512iter100000Sha256ModPow(alg) => alg,
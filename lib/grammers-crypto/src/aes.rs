@@ -7,11 +7,12 @@
 // except according to those terms.
 use aes::cipher::generic_array::GenericArray;
 use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit};
-use std::mem;
+use alloc::vec::Vec;
+use core::mem;
 
 /// Encrypt the input plaintext in-place using the AES-IGE mode.
 pub fn ige_encrypt(buffer: &mut [u8], key: &[u8; 32], iv: &[u8; 32]) {
-    assert!(buffer.len() % 16 == 0);
+    assert!(buffer.len().is_multiple_of(16));
 
     let key = GenericArray::from_slice(key);
     let cipher = aes::Aes256::new(key);
@@ -50,22 +51,29 @@ pub fn ige_encrypt(buffer: &mut [u8], key: &[u8; 32], iv: &[u8; 32]) {
 
 /// Decrypt the input ciphertext using the AES-IGE mode.
 pub fn ige_decrypt(ciphertext: &[u8], key: &[u8; 32], iv: &[u8; 32]) -> Vec<u8> {
-    let size = ciphertext.len();
-    assert!(size % 16 == 0);
-    let mut plaintext = vec![0; size];
+    let mut plaintext = ciphertext.to_vec();
+    ige_decrypt_in_place(&mut plaintext, key, iv);
+    plaintext
+}
+
+/// Decrypt the input ciphertext in-place using the AES-IGE mode.
+pub fn ige_decrypt_in_place(buffer: &mut [u8], key: &[u8; 32], iv: &[u8; 32]) {
+    assert!(buffer.len().is_multiple_of(16));
 
     let key = GenericArray::from_slice(key);
     let cipher = aes::Aes256::new(key);
+    let mut ciphertext_block = [0; 16];
     let mut iv = *iv;
     let (iv1, iv2) = iv.split_at_mut(16);
 
-    for (ciphertext_block, plaintext_block) in ciphertext.chunks(16).zip(plaintext.chunks_mut(16)) {
+    for plaintext_block in buffer.chunks_mut(16) {
+        ciphertext_block.copy_from_slice(plaintext_block);
+
         // block = block XOR iv2
         plaintext_block
             .iter_mut()
-            .zip(ciphertext_block)
             .zip(iv2.as_ref())
-            .for_each(|((a, x), b)| *a = x ^ b);
+            .for_each(|(a, b)| *a ^= b);
 
         // block = decrypt(block);
         let plaintext_block = GenericArray::from_mut_slice(plaintext_block);
@@ -78,9 +86,34 @@ pub fn ige_decrypt(ciphertext: &[u8], key: &[u8; 32], iv: &[u8; 32]) -> Vec<u8>
             .for_each(|(a, b)| *a ^= b);
 
         // save plaintext and adjust iv
-        iv1.copy_from_slice(ciphertext_block);
+        iv1.copy_from_slice(&ciphertext_block);
         iv2.copy_from_slice(plaintext_block);
     }
+}
+
+/// Decrypt the input ciphertext using the AES-CBC mode.
+pub fn cbc_decrypt(ciphertext: &[u8], key: &[u8; 32], iv: &[u8; 16]) -> Vec<u8> {
+    assert!(ciphertext.len().is_multiple_of(16));
+    let mut plaintext = ciphertext.to_vec();
+
+    let key = GenericArray::from_slice(key);
+    let cipher = aes::Aes256::new(key);
+    let mut iv = *iv;
+
+    for (ciphertext_block, plaintext_block) in ciphertext.chunks(16).zip(plaintext.chunks_mut(16)) {
+        // block = decrypt(block);
+        let plaintext_block = GenericArray::from_mut_slice(plaintext_block);
+        cipher.decrypt_block(plaintext_block);
+
+        // block = block XOR iv
+        plaintext_block
+            .iter_mut()
+            .zip(iv.as_ref())
+            .for_each(|(a, b)| *a ^= b);
+
+        // adjust iv
+        iv.copy_from_slice(ciphertext_block);
+    }
 
     plaintext
 }
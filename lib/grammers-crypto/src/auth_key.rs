@@ -6,7 +6,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 use crate::sha1;
-use std::fmt;
+use alloc::vec::Vec;
+use core::fmt;
 
 #[derive(Clone)]
 pub struct AuthKey {
@@ -56,6 +57,11 @@ impl AuthKey {
         self.data
     }
 
+    /// The identifier of the authorization key, as sent along encrypted messages.
+    pub fn key_id(&self) -> [u8; 8] {
+        self.key_id
+    }
+
     /// Calculates the new nonce hash based on the current attributes.
     pub fn calc_new_nonce_hash(&self, new_nonce: &[u8; 32], number: u8) -> [u8; 16] {
         let data = {
@@ -0,0 +1,184 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::DequeBuffer;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+/// How many buffers are kept around by default once they're returned to the pool.
+pub const DEFAULT_MAX_RETAINED_BUFFERS: usize = 8;
+
+/// A pool of [`DequeBuffer`]s, so that they can be reused instead of reallocated.
+///
+/// Buffers are taken out of the pool with [`BufferPool::get`], and go back into it as soon as
+/// the returned [`PooledBuffer`] is dropped. Buffers that grew past the maximum retained capacity
+/// are shrunk before being stored, so that a single large message doesn't keep its memory around
+/// forever.
+///
+/// Cloning the pool is cheap, and all clones share the same buffers.
+#[derive(Clone, Debug)]
+pub struct BufferPool {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    buffers: Vec<DequeBuffer<u8>>,
+    back_capacity: usize,
+    front_capacity: usize,
+    max_retained_capacity: usize,
+    max_retained_buffers: usize,
+}
+
+/// A buffer taken out of a [`BufferPool`], which is returned to it once dropped.
+#[derive(Debug)]
+pub struct PooledBuffer {
+    buffer: Option<DequeBuffer<u8>>,
+    pool: BufferPool,
+}
+
+impl BufferPool {
+    /// Creates an empty pool of buffers, which will have space for at least `back_capacity`
+    /// elements in the back, and exactly `front_capacity` elements in the front.
+    ///
+    /// By default, the retained capacity is limited to the capacity of a new buffer.
+    pub fn new(back_capacity: usize, front_capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                buffers: Vec::new(),
+                back_capacity,
+                front_capacity,
+                max_retained_capacity: back_capacity + front_capacity,
+                max_retained_buffers: DEFAULT_MAX_RETAINED_BUFFERS,
+            })),
+        }
+    }
+
+    /// Changes the maximum capacity a buffer can keep while stored in the pool.
+    ///
+    /// Buffers with a larger capacity are shrunk when they're returned.
+    pub fn set_max_retained_capacity(&self, capacity: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.max_retained_capacity = capacity;
+        inner.buffers.iter_mut().for_each(|b| b.shrink_to(capacity));
+    }
+
+    /// Changes the maximum amount of buffers kept in the pool.
+    ///
+    /// Buffers returned while the pool is full are dropped.
+    pub fn set_max_retained_buffers(&self, count: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.max_retained_buffers = count;
+        inner.buffers.truncate(count);
+    }
+
+    /// Takes an empty buffer out of the pool, or allocates a new one if there are none left.
+    pub fn get(&self) -> PooledBuffer {
+        let mut inner = self.inner.lock().unwrap();
+        let buffer = inner.buffers.pop().unwrap_or_else(|| {
+            DequeBuffer::with_capacity(inner.back_capacity, inner.front_capacity)
+        });
+        PooledBuffer {
+            buffer: Some(buffer),
+            pool: self.clone(),
+        }
+    }
+
+    /// Returns the number of buffers currently stored in the pool.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().buffers.len()
+    }
+
+    /// Returns `true` if there are no buffers stored in the pool.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn put(&self, mut buffer: DequeBuffer<u8>) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.buffers.len() >= inner.max_retained_buffers {
+            return;
+        }
+        buffer.clear();
+        if buffer.capacity() > inner.max_retained_capacity {
+            buffer.shrink_to(inner.max_retained_capacity);
+        }
+        inner.buffers.push(buffer);
+    }
+}
+
+impl Deref for PooledBuffer {
+    type Target = DequeBuffer<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        self.buffer.as_ref().expect("buffer is only taken on drop")
+    }
+}
+
+impl DerefMut for PooledBuffer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.buffer.as_mut().expect("buffer is only taken on drop")
+    }
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        if let Some(buffer) = self.buffer.take() {
+            // Avoid panicking while dropping if another thread poisoned the lock.
+            if !self.pool.inner.is_poisoned() {
+                self.pool.put(buffer);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffers_are_reused() {
+        let pool = BufferPool::new(16, 4);
+        let mut buffer = pool.get();
+        buffer.extend(1..=3);
+        let ptr = buffer.as_ref().as_ptr();
+        drop(buffer);
+        assert_eq!(pool.len(), 1);
+
+        let buffer = pool.get();
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.as_ref().as_ptr(), ptr);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn large_buffers_are_shrunk() {
+        let pool = BufferPool::new(16, 4);
+        let mut buffer = pool.get();
+        buffer.extend(&[0; 1024]);
+        assert!(buffer.capacity() >= 1024);
+        drop(buffer);
+
+        let buffer = pool.get();
+        assert!(buffer.capacity() < 1024);
+        assert_eq!(buffer.len(), 0);
+    }
+
+    #[test]
+    fn max_retained_buffers_is_respected() {
+        let pool = BufferPool::new(16, 4);
+        pool.set_max_retained_buffers(1);
+        let a = pool.get();
+        let b = pool.get();
+        drop(a);
+        drop(b);
+        assert_eq!(pool.len(), 1);
+
+        pool.set_max_retained_buffers(0);
+        assert!(pool.is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Cryptographic primitives for [phone calls], whose media is end-to-end encrypted.
+//!
+//! The key exchange is the same Diffie-Hellman used by [secret chats](crate::secret_chat),
+//! except that the caller first commits to its `g_a` by sending only its hash.
+//!
+//! [phone calls]: https://core.telegram.org/api/end-to-end/voice-calls
+use crate::secret_chat::KEY_SIZE;
+use crate::sha256;
+
+/// Emojis used to display the key fingerprint of a call, in the same order as other clients.
+static EMOJIS: [&str; 333] = [
+    "😉", "😍", "😛", "😭", "😱", "😡", "😎", "😴", "😵", "😈", "😬", "😇", "😏", "👮", "👷", "💂",
+    "👶", "👨", "👩", "👴", "👵", "😻", "😽", "🙀", "👺", "🙈", "🙉", "🙊", "💀", "👽", "💩", "🔥",
+    "💥", "💤", "👂", "👀", "👃", "👅", "👄", "👍", "👎", "👌", "👊", "✌️", "✋", "👐", "👆", "👇",
+    "👉", "👈", "🙏", "👏", "💪", "🚶", "🏃", "💃", "👫", "👪", "👬", "👭", "💅", "🎩", "👑", "👒",
+    "👟", "👞", "👠", "👕", "👗", "👖", "👙", "👜", "👓", "🎀", "💄", "💛", "💙", "💜", "💚", "💍",
+    "💎", "🐶", "🐺", "🐱", "🐭", "🐹", "🐰", "🐸", "🐯", "🐨", "🐻", "🐷", "🐮", "🐗", "🐴", "🐑",
+    "🐘", "🐼", "🐧", "🐥", "🐔", "🐍", "🐢", "🐛", "🐝", "🐜", "🐞", "🐌", "🐙", "🐚", "🐟", "🐬",
+    "🐋", "🐐", "🐊", "🐫", "🍀", "🌹", "🌻", "🍁", "🌾", "🍄", "🌵", "🌴", "🌳", "🌞", "🌚", "🌙",
+    "🌎", "🌋", "⚡", "☔", "❄️", "⛄", "🌀", "🌈", "🌊", "🎓", "🎆", "🎃", "👻", "🎅", "🎄", "🎁",
+    "🎈", "🔮", "🎥", "📷", "💿", "💻", "☎️", "📡", "📺", "📻", "🔉", "🔔", "⏳", "⏰", "⌚", "🔒",
+    "🔑", "🔎", "💡", "🔦", "🔌", "🔋", "🚿", "🚽", "🔧", "🔨", "🚪", "🚬", "💣", "🔫", "🔪", "💊",
+    "💉", "💰", "💵", "💳", "✉️", "📫", "📦", "📅", "📁", "✂️", "📌", "📎", "✒️", "✏️", "📐", "📚",
+    "🔬", "🔭", "🎨", "🎬", "🎤", "🎧", "🎵", "🎹", "🎻", "🎺", "🎸", "👾", "🎮", "🃏", "🎲", "🎯",
+    "🏈", "🏀", "⚽", "⚾", "🎾", "🎱", "🏉", "🎳", "🏁", "🏇", "🏆", "🏊", "🏄", "☕", "🍼", "🍺",
+    "🍷", "🍴", "🍕", "🍔", "🍟", "🍗", "🍱", "🍚", "🍜", "🍡", "🍳", "🍞", "🍩", "🍦", "🎂", "🍰",
+    "🍪", "🍫", "🍭", "🍯", "🍎", "🍏", "🍊", "🍋", "🍒", "🍇", "🍉", "🍓", "🍑", "🍌", "🍐", "🍍",
+    "🍆", "🍅", "🌽", "🏡", "🏥", "🏦", "⛪", "🏰", "⛺", "🏭", "🗻", "🗽", "🎠", "🎡", "⛲", "🎢",
+    "🚢", "🚤", "⚓", "🚀", "✈️", "🚁", "🚂", "🚋", "🚎", "🚌", "🚙", "🚗", "🚕", "🚛", "🚨", "🚔",
+    "🚒", "🚑", "🚲", "🚠", "🚜", "🚦", "⚠️", "🚧", "⛽", "🎰", "🗿", "🎪", "🎭", "🇯🇵", "🇰🇷", "🇩🇪",
+    "🇨🇳", "🇺🇸", "🇫🇷", "🇪🇸", "🇮🇹", "🇷🇺", "🇬🇧", "1️⃣", "2️⃣", "3️⃣", "4️⃣", "5️⃣", "6️⃣", "7️⃣", "8️⃣", "9️⃣",
+    "0️⃣", "🔟", "❗", "❓", "♥️", "♦️", "💯", "🔗", "🔱", "🔴", "🔵", "🔶", "🔷",
+];
+
+/// Calculates the `g_a_hash` the caller sends in `phone.requestCall`.
+pub fn g_a_hash(g_a: &[u8]) -> [u8; 32] {
+    sha256!(g_a)
+}
+
+/// Checks that the `g_a` revealed by the caller in `phone.confirmCall` matches the hash
+/// it committed to when requesting the call.
+pub fn check_g_a_hash(g_a: &[u8], g_a_hash: &[u8]) -> bool {
+    self::g_a_hash(g_a) == g_a_hash
+}
+
+/// Derives the four emojis both parties of a call should see, as a visual representation of
+/// the key. If they see the same emojis, nobody is intercepting the call.
+///
+/// `g_a` is always the public value of the caller.
+pub fn emoji_fingerprint(key: &[u8; KEY_SIZE], g_a: &[u8]) -> [&'static str; 4] {
+    let hash = sha256!(key, g_a);
+    let mut emojis = [""; 4];
+    emojis.iter_mut().enumerate().for_each(|(i, emoji)| {
+        let mut chunk = [0; 8];
+        chunk.copy_from_slice(&hash[8 * i..8 * i + 8]);
+        let num = u64::from_be_bytes(chunk) & 0x7fff_ffff_ffff_ffff;
+        *emoji = EMOJIS[(num % EMOJIS.len() as u64) as usize];
+    });
+    emojis
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_g_a_hash_matches() {
+        let g_a = [7; KEY_SIZE];
+        let hash = g_a_hash(&g_a);
+        assert!(check_g_a_hash(&g_a, &hash));
+        assert!(!check_g_a_hash(&g_a[1..], &hash));
+    }
+
+    #[test]
+    fn check_emoji_fingerprint() {
+        let mut key = [0; KEY_SIZE];
+        let mut g_a = [0; KEY_SIZE];
+        for (i, (k, g)) in key.iter_mut().zip(g_a.iter_mut()).enumerate() {
+            *k = i as u8;
+            *g = 255 - i as u8;
+        }
+
+        // Emojis 122, 225, 65 and 23.
+        assert_eq!(emoji_fingerprint(&key, &g_a), ["🌵", "🍴", "👞", "🙀"]);
+    }
+}
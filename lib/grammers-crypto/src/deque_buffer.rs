@@ -5,7 +5,8 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-use std::{
+use alloc::vec::Vec;
+use core::{
     ops::{Index, IndexMut},
     slice::SliceIndex,
 };
@@ -69,6 +70,19 @@ impl<T: Copy + Default> DequeBuffer<T> {
     pub fn len(&self) -> usize {
         self.buffer.len() - self.head
     }
+
+    /// Returns the total number of elements the buffer can hold without reallocating,
+    /// including the space reserved in the front.
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+
+    /// Shrinks the capacity of the buffer with a lower bound.
+    ///
+    /// The capacity will remain at least as large as both the length and the supplied value.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.buffer.shrink_to(min_capacity)
+    }
 }
 
 impl<T: Copy + Default> AsRef<[T]> for DequeBuffer<T> {
@@ -211,4 +225,14 @@ mod tests {
         buffer.clear();
         assert_eq!(repr(&buffer), "[ 0 0 0 0|? ? ? ? ? ? ]");
     }
+
+    #[test]
+    fn shrink_keeps_contents() {
+        let mut buffer = DequeBuffer::<u8>::with_capacity(64, 4);
+        buffer.extend(1..=3);
+        buffer.shrink_to(0);
+        sanity_checks(&buffer);
+        assert_eq!(repr(&buffer), "[ 0 0 0 0|1 2 3 ]");
+        assert!(buffer.capacity() < 64);
+    }
 }
@@ -6,7 +6,9 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::fmt::Write;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
 
 /// Represent a sequence of bytes as an hexadecimal string.
 pub fn to_hex(bytes: &[u8]) -> String {
@@ -28,7 +30,7 @@ pub fn opt_from_hex(hex: &str) -> Option<Vec<u8>> {
         })
     }
 
-    if hex.len() % 2 != 0 {
+    if !hex.len().is_multiple_of(2) {
         return None;
     }
 
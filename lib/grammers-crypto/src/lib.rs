@@ -6,22 +6,34 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(unsafe_code)]
 
+extern crate alloc;
+
 pub mod aes;
 mod auth_key;
+#[cfg(feature = "std")]
+pub mod buffer_pool;
+pub mod calls;
 pub mod deque_buffer;
 pub mod factorize;
 pub mod hex;
 pub mod obfuscated;
+pub mod passport;
 pub mod rsa;
+pub mod secret_chat;
 pub mod sha;
 pub mod two_factor_auth;
 
+use alloc::vec::Vec;
 pub use auth_key::AuthKey;
+#[cfg(feature = "std")]
+pub use buffer_pool::{BufferPool, PooledBuffer};
+use core::fmt;
 pub use deque_buffer::DequeBuffer;
+#[cfg(feature = "std")]
 use getrandom::getrandom;
-use std::fmt;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Error {
@@ -35,6 +47,7 @@ pub enum Error {
     MessageKeyMismatch,
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {}
 
 impl fmt::Display for Error {
@@ -105,8 +118,15 @@ fn determine_padding_v2_length(len: usize) -> usize {
     16 + (16 - (len % 16))
 }
 
-// Inner body of `encrypt_data_v2`, separated for testing purposes.
-fn do_encrypt_data_v2(buffer: &mut DequeBuffer<u8>, auth_key: &AuthKey, random_padding: &[u8; 32]) {
+/// Like [`encrypt_data_v2`], but using the given random padding instead of generating it.
+///
+/// This is useful in environments where the operating system can't provide secure random
+/// data. Only as much padding as needed is used, and it must never be reused.
+pub fn encrypt_data_v2_with_padding(
+    buffer: &mut DequeBuffer<u8>,
+    auth_key: &AuthKey,
+    random_padding: &[u8; 32],
+) -> u32 {
     // "Note that MTProto 2.0 requires from 12 to 1024 bytes of padding"
     // "[...] the resulting message length be divisible by 16 bytes"
     let padding_len = determine_padding_v2_length(buffer.len());
@@ -133,29 +153,49 @@ fn do_encrypt_data_v2(buffer: &mut DequeBuffer<u8>, auth_key: &AuthKey, random_p
 
     buffer.extend_front(&msg_key);
     buffer.extend_front(&auth_key.key_id);
+
+    // The quick acknowledgement is the first 32 bits of `msg_key_large` with the highest bit set.
+    u32::from_le_bytes(msg_key_large[..4].try_into().unwrap()) | 0x8000_0000
 }
 
 /// This function implements the [MTProto 2.0 algorithm] for computing
 /// `aes_key` and `aes_iv` from `auth_key` and `msg_key` as specified
 ///
+/// Returns the token that the server will send back if a quick acknowledgement is requested.
+///
 /// [MTProto 2.0 algorithm]: https://core.telegram.org/mtproto/description#defining-aes-key-and-initialization-vector
-pub fn encrypt_data_v2(buffer: &mut DequeBuffer<u8>, auth_key: &AuthKey) {
+#[cfg(feature = "std")]
+pub fn encrypt_data_v2(buffer: &mut DequeBuffer<u8>, auth_key: &AuthKey) -> u32 {
     let random_padding = {
         let mut rnd = [0; 32];
         getrandom(&mut rnd).expect("failed to generate a secure padding");
         rnd
     };
 
-    do_encrypt_data_v2(buffer, auth_key, &random_padding)
+    encrypt_data_v2_with_padding(buffer, auth_key, &random_padding)
 }
 
 /// This method is the inverse of `encrypt_data_v2`.
 pub fn decrypt_data_v2(ciphertext: &[u8], auth_key: &AuthKey) -> Result<Vec<u8>, Error> {
+    let mut plaintext = DequeBuffer::with_capacity(ciphertext.len(), 0);
+    decrypt_data_v2_into(ciphertext, auth_key, &mut plaintext)?;
+    Ok(plaintext.as_ref().to_vec())
+}
+
+/// Like `decrypt_data_v2`, but the plaintext is written into the given `buffer`, so that its
+/// memory can be reused across messages.
+///
+/// The buffer is cleared before use. Its contents are unspecified if decryption fails.
+pub fn decrypt_data_v2_into(
+    ciphertext: &[u8],
+    auth_key: &AuthKey,
+    buffer: &mut DequeBuffer<u8>,
+) -> Result<(), Error> {
     // Decryption is done from the server
     let side = Side::Server;
     let x = side.x();
 
-    if ciphertext.len() < 24 || (ciphertext.len() - 24) % 16 != 0 {
+    if ciphertext.len() < 24 || !(ciphertext.len() - 24).is_multiple_of(16) {
         return Err(Error::InvalidBuffer);
     }
 
@@ -172,16 +212,110 @@ pub fn decrypt_data_v2(ciphertext: &[u8], auth_key: &AuthKey) -> Result<Vec<u8>,
     };
 
     let (key, iv) = calc_key(auth_key, &msg_key, Side::Server);
-    let plaintext = decrypt_ige(&ciphertext[24..], &key, &iv);
+    buffer.clear();
+    buffer.extend(&ciphertext[24..]);
+    aes::ige_decrypt_in_place(buffer.as_mut(), &key, &iv);
 
     // https://core.telegram.org/mtproto/security_guidelines#mtproto-encrypted-messages
-    let our_key = sha256!(&auth_key.data[88 + x..88 + x + 32], &plaintext);
+    let our_key = sha256!(&auth_key.data[88 + x..88 + x + 32], &buffer[..]);
 
     if msg_key != our_key[8..8 + 16] {
         return Err(Error::MessageKeyMismatch);
     }
 
-    Ok(plaintext)
+    Ok(())
+}
+
+/// Calculate the key based on Telegram [guidelines for MTProto 1],
+/// returning the pair `(key, iv)` for use in AES-IGE mode.
+///
+/// MTProto 1.0 is obsolete, but it is still needed to encrypt the inner
+/// message when binding a temporary authorization key.
+///
+/// [guidelines for MTProto 1]: https://core.telegram.org/mtproto_v1#defining-aes-key-and-initialization-vector
+fn calc_key_v1(auth_key: &AuthKey, msg_key: &[u8; 16], side: Side) -> ([u8; 32], [u8; 32]) {
+    let x = side.x();
+
+    // sha1_a = SHA1 (msg_key + substr (auth_key, x, 32));
+    let sha1_a = sha1!(msg_key, &auth_key.data[x..x + 32]);
+
+    // sha1_b = SHA1 (substr (auth_key, 32+x, 16) + msg_key + substr (auth_key, 48+x, 16));
+    let sha1_b = sha1!(
+        &auth_key.data[32 + x..32 + x + 16],
+        msg_key,
+        &auth_key.data[48 + x..48 + x + 16]
+    );
+
+    // sha1_c = SHA1 (substr (auth_key, 64+x, 32) + msg_key);
+    let sha1_c = sha1!(&auth_key.data[64 + x..64 + x + 32], msg_key);
+
+    // sha1_d = SHA1 (msg_key + substr (auth_key, 96+x, 32));
+    let sha1_d = sha1!(msg_key, &auth_key.data[96 + x..96 + x + 32]);
+
+    // aes_key = substr (sha1_a, 0, 8) + substr (sha1_b, 8, 12) + substr (sha1_c, 4, 12);
+    let aes_key = {
+        let mut buffer = [0; 32];
+        buffer[0..8].copy_from_slice(&sha1_a[0..8]);
+        buffer[8..8 + 12].copy_from_slice(&sha1_b[8..8 + 12]);
+        buffer[20..20 + 12].copy_from_slice(&sha1_c[4..4 + 12]);
+        buffer
+    };
+
+    // aes_iv = substr (sha1_a, 8, 12) + substr (sha1_b, 0, 8) + substr (sha1_c, 16, 4) + substr (sha1_d, 0, 8);
+    let aes_iv = {
+        let mut buffer = [0; 32];
+        buffer[0..12].copy_from_slice(&sha1_a[8..8 + 12]);
+        buffer[12..12 + 8].copy_from_slice(&sha1_b[0..8]);
+        buffer[20..20 + 4].copy_from_slice(&sha1_c[16..16 + 4]);
+        buffer[24..24 + 8].copy_from_slice(&sha1_d[0..8]);
+        buffer
+    };
+
+    (aes_key, aes_iv)
+}
+
+/// Like [`encrypt_data_v1`], but using the given random padding instead of generating it.
+pub fn encrypt_data_v1_with_padding(
+    plaintext: &[u8],
+    auth_key: &AuthKey,
+    random_padding: &[u8; 16],
+) -> Vec<u8> {
+    // msg_key = substr (SHA1 (plaintext), 4, 16);
+    let msg_key = {
+        let sha = sha1!(plaintext);
+        let mut buffer = [0; 16];
+        buffer.copy_from_slice(&sha[4..4 + 16]);
+        buffer
+    };
+
+    let (key, iv) = calc_key_v1(auth_key, &msg_key, Side::Client);
+
+    // "Padding is not included in the SHA1 computation"
+    let mut buffer = Vec::with_capacity(8 + 16 + plaintext.len() + 16);
+    buffer.extend(&auth_key.key_id);
+    buffer.extend(&msg_key);
+    let start = buffer.len();
+    buffer.extend(plaintext);
+    let padding_len = (16 - (plaintext.len() % 16)) % 16;
+    buffer.extend(&random_padding[..padding_len]);
+
+    aes::ige_encrypt(&mut buffer[start..], &key, &iv);
+    buffer
+}
+
+/// This function implements the [MTProto 1.0 algorithm] for encrypting data
+/// sent by the client, which is only used to bind temporary authorization keys.
+///
+/// [MTProto 1.0 algorithm]: https://core.telegram.org/mtproto_v1
+#[cfg(feature = "std")]
+pub fn encrypt_data_v1(plaintext: &[u8], auth_key: &AuthKey) -> Vec<u8> {
+    let random_padding = {
+        let mut rnd = [0; 16];
+        getrandom(&mut rnd).expect("failed to generate a secure padding");
+        rnd
+    };
+
+    encrypt_data_v1_with_padding(plaintext, auth_key, &random_padding)
 }
 
 /// Generate the AES key and initialization vector from the server nonce
@@ -215,8 +349,12 @@ pub fn generate_key_data_from_nonce(
 }
 
 /// Encrypt data using AES-IGE.
+///
+/// The plaintext is padded with random bytes if its length is not a multiple of 16. Without the
+/// `std` feature, pad it beforehand and use [`aes::ige_encrypt`] instead.
+#[cfg(feature = "std")]
 pub fn encrypt_ige(plaintext: &[u8], key: &[u8; 32], iv: &[u8; 32]) -> Vec<u8> {
-    let mut padded = if plaintext.len() % 16 == 0 {
+    let mut padded = if plaintext.len().is_multiple_of(16) {
         plaintext.to_vec()
     } else {
         let pad_len = (16 - (plaintext.len() % 16)) % 16;
@@ -321,8 +459,27 @@ mod tests {
             36, 61, 86, 62, 161, 128, 210, 24, 238, 117, 124, 154,
         ];
 
-        do_encrypt_data_v2(&mut buffer, &auth_key, &random_padding);
+        let quick_ack = encrypt_data_v2_with_padding(&mut buffer, &auth_key, &random_padding);
         assert_eq!(&buffer[..], expected);
+        assert_eq!(quick_ack, 0xcd57_1d02);
+    }
+
+    #[test]
+    fn encrypt_client_data_v1() {
+        let plaintext = b"Hello, world! This data should remain secure!";
+        let auth_key = get_test_auth_key();
+        let random_padding = [0; 16];
+        let expected = vec![
+            50, 209, 88, 110, 164, 87, 223, 200, 200, 130, 39, 170, 72, 144, 14, 82, 149, 202, 203,
+            166, 167, 177, 152, 142, 94, 205, 2, 62, 218, 159, 151, 93, 42, 140, 133, 195, 37, 141,
+            127, 222, 221, 235, 127, 237, 97, 83, 16, 66, 108, 78, 65, 76, 40, 0, 172, 28, 206,
+            194, 236, 25, 117, 229, 123, 165, 74, 184, 223, 132, 148, 113, 164, 144,
+        ];
+
+        assert_eq!(
+            encrypt_data_v1_with_padding(plaintext, &auth_key, &random_padding),
+            expected
+        );
     }
 
     #[test]
@@ -365,6 +522,11 @@ mod tests {
         ];
 
         assert_eq!(decrypt_data_v2(&ciphertext, &auth_key).unwrap(), expected);
+
+        let mut buffer = DequeBuffer::with_capacity(0, 0);
+        buffer.extend(b"stale");
+        decrypt_data_v2_into(&ciphertext, &auth_key, &mut buffer).unwrap();
+        assert_eq!(&buffer[..], &expected[..]);
     }
 
     #[test]
@@ -7,6 +7,7 @@
 // except according to those terms.
 
 use aes::cipher::{generic_array::GenericArray, KeyIvInit, StreamCipher};
+use alloc::vec::Vec;
 
 /// This implements the AES-256-CTR cipher used by Telegram to encrypt data
 /// when using the obfuscated transport.
@@ -33,6 +34,16 @@ impl ObfuscatedCipher {
         }
     }
 
+    /// Creates the cipher used by the server on a connection, from the
+    /// initialization payload sent by the client.
+    ///
+    /// The keys are the same as those of the client, but swapped, so that
+    /// what one side encrypts can be decrypted by the other.
+    pub fn new_server(init: &[u8; 64]) -> Self {
+        let Self { rx, tx } = Self::new(init);
+        Self { rx: tx, tx: rx }
+    }
+
     pub fn encrypt(&mut self, buffer: &mut [u8]) {
         self.tx.apply_keystream(buffer);
     }
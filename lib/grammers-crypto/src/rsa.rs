@@ -5,6 +5,7 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
+use alloc::vec::Vec;
 use num_bigint::BigUint;
 
 use crate::{aes::ige_encrypt, sha256};
@@ -0,0 +1,320 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Cryptographic primitives for [secret chats], which are end-to-end
+//! encrypted between two users and never readable by the server.
+//!
+//! [secret chats]: https://core.telegram.org/api/end-to-end
+#[cfg(feature = "std")]
+use crate::two_factor_auth;
+use crate::{calc_key, sha256, AuthKey, Error, Side};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use getrandom::getrandom;
+use num_bigint::BigUint;
+
+/// Size, in bytes, of the keys used by secret chats.
+pub const KEY_SIZE: usize = 256;
+
+/// Generates the secret exponent used during a key exchange, mixed with the
+/// `random` bytes sent by the server as suggested by the documentation.
+#[cfg(feature = "std")]
+pub fn generate_secret(server_random: &[u8]) -> [u8; KEY_SIZE] {
+    let mut secret = [0; KEY_SIZE];
+    getrandom(&mut secret).expect("failed to generate a secure secret");
+    secret
+        .iter_mut()
+        .zip(server_random)
+        .for_each(|(x, r)| *x ^= r);
+    secret
+}
+
+/// Generates the `random_bytes` of a `decryptedMessageLayer`, which must be at least 15 bytes long.
+#[cfg(feature = "std")]
+pub fn generate_layer_random_bytes() -> Vec<u8> {
+    let mut rnd = vec![0; 16];
+    getrandom(&mut rnd).expect("failed to generate secure random bytes");
+    rnd
+}
+
+/// Validates the Diffie-Hellman parameters sent by the server in `messages.dhConfig`.
+///
+/// This check is expensive, so its result should be cached for as long as `p` does not change.
+#[cfg(feature = "std")]
+pub fn check_dh_params(g: i32, p: &[u8]) -> bool {
+    (2..=7).contains(&g) && two_factor_auth::check_p_and_g(p, &g)
+}
+
+/// Calculates the public `g_a = pow(g, a) mod p` using the secret `a`.
+///
+/// `g` and `p` must have been validated beforehand with [`check_dh_params`].
+///
+/// Returns `None` if the resulting value is not safe to use.
+pub fn calculate_g_a(g: i32, p: &[u8], a: &[u8; KEY_SIZE]) -> Option<[u8; KEY_SIZE]> {
+    let p = BigUint::from_bytes_be(p);
+    let g_a = BigUint::from(g as u32).modpow(&BigUint::from_bytes_be(a), &p);
+    if !check_public_value(&g_a, &p) {
+        return None;
+    }
+
+    Some(pad_to_key(&g_a))
+}
+
+/// Calculates the shared key `pow(g_b, a) mod p` from the other party's public value.
+///
+/// `p` must have been validated beforehand with [`check_dh_params`].
+///
+/// Returns `None` if `g_b` is not within the safe range.
+pub fn calculate_key(g_b: &[u8], p: &[u8], a: &[u8; KEY_SIZE]) -> Option<[u8; KEY_SIZE]> {
+    let p = BigUint::from_bytes_be(p);
+    let g_b = BigUint::from_bytes_be(g_b);
+    if !check_public_value(&g_b, &p) {
+        return None;
+    }
+
+    Some(pad_to_key(&g_b.modpow(&BigUint::from_bytes_be(a), &p)))
+}
+
+/// Calculates the fingerprint of a key, which both parties use to make sure they agree on it.
+pub fn key_fingerprint(key: &[u8; KEY_SIZE]) -> i64 {
+    i64::from_le_bytes(AuthKey::from_bytes(*key).key_id)
+}
+
+/// Encrypts the serialized `decryptedMessageLayer` for the other party of the chat,
+/// returning the bytes to use as the `data` in `messages.sendEncrypted`.
+///
+/// `originator` must be `true` when the chat was requested by us.
+#[cfg(feature = "std")]
+pub fn encrypt_message(plaintext: &[u8], key: &[u8; KEY_SIZE], originator: bool) -> Vec<u8> {
+    let random_padding = {
+        let mut rnd = [0; 32];
+        getrandom(&mut rnd).expect("failed to generate a secure padding");
+        rnd
+    };
+
+    encrypt_message_with_padding(plaintext, key, originator, &random_padding)
+}
+
+/// Like [`encrypt_message`], but using the given random padding instead of generating it.
+pub fn encrypt_message_with_padding(
+    plaintext: &[u8],
+    key: &[u8; KEY_SIZE],
+    originator: bool,
+    random_padding: &[u8; 32],
+) -> Vec<u8> {
+    let key = AuthKey::from_bytes(*key);
+    let side = sender_side(originator);
+    let x = side.x();
+
+    // "the message is prefixed with its length as a 32-bit integer and padded
+    // with 12 to 1024 random bytes so that its length is divisible by 16"
+    let mut data = Vec::with_capacity(4 + plaintext.len() + 32);
+    data.extend((plaintext.len() as i32).to_le_bytes());
+    data.extend(plaintext);
+    let padding_len = crate::determine_padding_v2_length(data.len());
+    data.extend(&random_padding[..padding_len]);
+
+    // msg_key_large = SHA256 (substr (key, 88+x, 32) + plaintext + random_padding);
+    let msg_key_large = sha256!(&key.data[88 + x..88 + x + 32], &data);
+
+    // msg_key = substr (msg_key_large, 8, 16);
+    let msg_key = {
+        let mut buffer = [0; 16];
+        buffer.copy_from_slice(&msg_key_large[8..8 + 16]);
+        buffer
+    };
+
+    let (aes_key, aes_iv) = calc_key(&key, &msg_key, side);
+    crate::aes::ige_encrypt(&mut data, &aes_key, &aes_iv);
+
+    let mut result = Vec::with_capacity(8 + 16 + data.len());
+    result.extend(key.key_id);
+    result.extend(msg_key);
+    result.extend(data);
+    result
+}
+
+/// Decrypts the `bytes` of an `encryptedMessage` (or `encryptedMessageService`) sent by
+/// the other party of the chat, returning the serialized `decryptedMessageLayer`.
+///
+/// `originator` must be `true` when the chat was requested by us.
+pub fn decrypt_message(
+    ciphertext: &[u8],
+    key: &[u8; KEY_SIZE],
+    originator: bool,
+) -> Result<Vec<u8>, Error> {
+    // key_fingerprint, msg_key, and at least one block of encrypted data.
+    if ciphertext.len() < 8 + 16 + 16 || !(ciphertext.len() - 8 - 16).is_multiple_of(16) {
+        return Err(Error::InvalidBuffer);
+    }
+
+    let key = AuthKey::from_bytes(*key);
+    if ciphertext[..8] != key.key_id {
+        return Err(Error::AuthKeyMismatch);
+    }
+
+    // Messages we receive were sent by the other side.
+    let side = sender_side(!originator);
+    let x = side.x();
+
+    let msg_key = {
+        let mut buffer = [0; 16];
+        buffer.copy_from_slice(&ciphertext[8..8 + 16]);
+        buffer
+    };
+
+    let (aes_key, aes_iv) = calc_key(&key, &msg_key, side);
+    let plaintext = crate::decrypt_ige(&ciphertext[24..], &aes_key, &aes_iv);
+
+    let our_key = sha256!(&key.data[88 + x..88 + x + 32], &plaintext);
+    if msg_key != our_key[8..8 + 16] {
+        return Err(Error::MessageKeyMismatch);
+    }
+
+    let len = i32::from_le_bytes([plaintext[0], plaintext[1], plaintext[2], plaintext[3]]);
+    let padding = (plaintext.len() - 4) as isize - len as isize;
+    if len < 0 || !(12..=1024).contains(&padding) {
+        return Err(Error::InvalidBuffer);
+    }
+
+    Ok(plaintext[4..4 + len as usize].to_vec())
+}
+
+// "x = 0 for messages sent by the chat originator and x = 8 for those sent by the responder."
+fn sender_side(originator: bool) -> Side {
+    if originator {
+        Side::Client
+    } else {
+        Side::Server
+    }
+}
+
+// "1 < g_a < p-1" and "2^{2048-64} <= g_a <= p - 2^{2048-64}"
+fn check_public_value(value: &BigUint, p: &BigUint) -> bool {
+    let min = BigUint::from(1u8) << (2048 - 64);
+    if p <= &(&min << 1) {
+        return false;
+    }
+    value >= &min && value <= &(p - &min)
+}
+
+fn pad_to_key(value: &BigUint) -> [u8; KEY_SIZE] {
+    let bytes = value.to_bytes_be();
+    let mut out = [0; KEY_SIZE];
+    out[KEY_SIZE - bytes.len()..].copy_from_slice(&bytes);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    const G: i32 = 3;
+    const P: &str = "c71caeb9c6b1c9048e6c522f70f13f73980d40238e3e21c14934d037563d930f\
+        48198a0aa7c14058229493d22530f4dbfa336f6e0ac925139543aed44cce7c37\
+        20fd51f69458705ac68cd4fe6b6b13abdc9746512969328454f18faf8c595f64\
+        2477fe96bb2a941d5bcd1d4ac8cc49880708fa9b378e3c4f3a9060bee67cf9a4\
+        a4a695811051907e162753b56b0f6b410dba74d8a84b2a14b3144e0ef1284754\
+        fd17ed950d5965b4b9dd46582db1178d169c6bc465b0d6ff9ca3928fef5b9ae4\
+        e418fc15e83ebea0f87fa9ff5eed70050ded2849f47bf959d956850ce929851f\
+        0d8115f635b105ee2e4e15d04b2454bf6f4fadf034b10403119cd8e3b92fcc5b";
+
+    fn get_test_key() -> [u8; KEY_SIZE] {
+        let mut buffer = [0u8; KEY_SIZE];
+        buffer
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, x)| *x = i as u8);
+        buffer
+    }
+
+    #[test]
+    fn key_exchange_agrees() {
+        let p = hex::from_hex(P);
+        let a = [0x12; KEY_SIZE];
+        let b = [0x34; KEY_SIZE];
+        assert!(check_dh_params(G, &p));
+
+        let g_a = calculate_g_a(G, &p, &a).unwrap();
+        let g_b = calculate_g_a(G, &p, &b).unwrap();
+
+        let key_a = calculate_key(&g_b, &p, &a).unwrap();
+        let key_b = calculate_key(&g_a, &p, &b).unwrap();
+        assert_eq!(key_a, key_b);
+        assert_eq!(key_fingerprint(&key_a), key_fingerprint(&key_b));
+    }
+
+    #[test]
+    fn key_exchange_rejects_unsafe_values() {
+        let p = hex::from_hex(P);
+        let a = [0x12; KEY_SIZE];
+
+        assert!(!check_dh_params(8, &p));
+        assert!(!check_dh_params(G, &p[1..]));
+        assert_eq!(calculate_g_a(G, &p, &[0; KEY_SIZE]), None);
+        assert_eq!(calculate_key(&[1], &p, &a), None);
+        assert_eq!(calculate_key(&p, &p, &a), None);
+    }
+
+    #[test]
+    fn encrypt_message_layout() {
+        let key = get_test_key();
+        let plaintext = b"Hello, world! This data should remain secure!";
+        let random_padding = [0; 32];
+
+        let ciphertext = encrypt_message_with_padding(plaintext, &key, true, &random_padding);
+        assert_eq!(&ciphertext[..8], &AuthKey::from_bytes(key).key_id);
+        assert_eq!((ciphertext.len() - 24) % 16, 0);
+        assert!(ciphertext.len() - 24 - 4 - plaintext.len() >= 12);
+    }
+
+    #[test]
+    fn decrypt_message_roundtrip() {
+        let key = get_test_key();
+        let plaintext = b"Hello, world! This data should remain secure!";
+
+        for originator in [true, false] {
+            let ciphertext = encrypt_message(plaintext, &key, originator);
+            assert_eq!(
+                decrypt_message(&ciphertext, &key, !originator),
+                Ok(plaintext.to_vec())
+            );
+            // The same side cannot decrypt its own messages as if they came from the other.
+            assert_eq!(
+                decrypt_message(&ciphertext, &key, originator),
+                Err(Error::MessageKeyMismatch)
+            );
+        }
+    }
+
+    #[test]
+    fn decrypt_message_rejects_tampering() {
+        let key = get_test_key();
+        let mut ciphertext = encrypt_message(b"Hello!", &key, true);
+
+        assert_eq!(
+            decrypt_message(&ciphertext[..30], &key, false),
+            Err(Error::InvalidBuffer)
+        );
+
+        let mut other_key = key;
+        other_key[0] ^= 1;
+        assert_eq!(
+            decrypt_message(&ciphertext, &other_key, false),
+            Err(Error::AuthKeyMismatch)
+        );
+
+        let last = ciphertext.len() - 1;
+        ciphertext[last] ^= 1;
+        assert_eq!(
+            decrypt_message(&ciphertext, &key, false),
+            Err(Error::MessageKeyMismatch)
+        );
+    }
+}
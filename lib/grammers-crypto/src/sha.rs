@@ -31,3 +31,13 @@ macro_rules! sha256 (
         sha
     })
 );
+
+/// Compute the HMAC-SHA256 of the message using the given key.
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
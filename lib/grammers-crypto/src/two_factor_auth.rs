@@ -5,9 +5,14 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use glass_pumpkin::safe_prime;
 use hmac::Hmac;
-use num_bigint::{BigInt, BigUint, Sign};
+#[cfg(feature = "std")]
+use num_bigint::BigUint;
+use num_bigint::{BigInt, Sign};
 use num_traits::ops::euclid::Euclid;
 use sha2::Sha512;
 
@@ -87,6 +92,7 @@ pub fn calculate_2fa(
 }
 
 /// Validation for parameters required for two-factor authentication
+#[cfg(feature = "std")]
 pub fn check_p_and_g(p: &[u8], g: &i32) -> bool {
     if !check_p_len(p) {
         return false;
@@ -95,6 +101,7 @@ pub fn check_p_and_g(p: &[u8], g: &i32) -> bool {
     check_p_prime_and_subgroup(p, g)
 }
 
+#[cfg(feature = "std")]
 fn check_p_prime_and_subgroup(p: &[u8], g: &i32) -> bool {
     let p = &BigUint::from_bytes_be(p);
 
@@ -124,6 +131,7 @@ fn check_p_prime_and_subgroup(p: &[u8], g: &i32) -> bool {
     }
 }
 
+#[cfg(feature = "std")]
 fn check_p_len(p: &[u8]) -> bool {
     p.len() == 256
 }
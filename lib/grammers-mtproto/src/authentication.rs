@@ -130,6 +130,10 @@ pub enum Error {
         /// The expected nonce.
         expected: [u8; 16],
     },
+
+    /// The server refused to bind the temporary key to the permanent one (see
+    /// [`bind_temp_auth_key`]).
+    TempKeyNotBound,
 }
 
 impl std::error::Error for Error {}
@@ -170,6 +174,7 @@ impl fmt::Display for Error {
                 f,
                 "invalid new nonce hash: got {got:?}, expected {expected:?}"
             ),
+            Self::TempKeyNotBound => write!(f, "the temporary key could not be bound"),
         }
     }
 }
@@ -243,6 +248,32 @@ fn do_step1(random_bytes: &[u8; 16]) -> Result<(Vec<u8>, Step1), Error> {
 
 /// The second step of the process to generate an authorization key.
 pub fn step2(data: Step1, response: &[u8]) -> Result<(Vec<u8>, Step2), Error> {
+    step2_impl(data, response, None)
+}
+
+/// Like [`step2`], but generates a temporary authorization key for the datacenter `dc_id`,
+/// which will expire after `expires_in` seconds.
+///
+/// When connecting to the test servers, `10000` must be added to the `dc_id`.
+///
+/// Temporary keys are needed for [Perfect Forward Secrecy], and must be bound to the
+/// permanent key with [`bind_temp_auth_key`] before they can be used.
+///
+/// [Perfect Forward Secrecy]: https://core.telegram.org/api/pfs
+pub fn step2_temp(
+    data: Step1,
+    response: &[u8],
+    dc_id: i32,
+    expires_in: i32,
+) -> Result<(Vec<u8>, Step2), Error> {
+    step2_impl(data, response, Some((dc_id, expires_in)))
+}
+
+fn step2_impl(
+    data: Step1,
+    response: &[u8],
+    temp: Option<(i32, i32)>,
+) -> Result<(Vec<u8>, Step2), Error> {
     if TRACE_AUTH_GEN {
         println!("< {}", hex::to_hex(response));
     }
@@ -257,7 +288,7 @@ pub fn step2(data: Step1, response: &[u8]) -> Result<(Vec<u8>, Step2), Error> {
         println!("r {}", hex::to_hex(&random_bytes));
     }
 
-    let res = do_step2(data, response, &random_bytes);
+    let res = do_step2(data, response, &random_bytes, temp);
     if TRACE_AUTH_GEN {
         if let Ok((x, _)) = &res {
             println!("> {}", hex::to_hex(x));
@@ -270,6 +301,7 @@ fn do_step2(
     data: Step1,
     response: &[u8],
     random_bytes: &[u8; 32 + 224],
+    temp: Option<(i32, i32)>,
 ) -> Result<(Vec<u8>, Step2), Error> {
     // Step 2. Validate the PQ response. Return `(p, q)` if it's valid.
     let Step1 { nonce } = data;
@@ -311,26 +343,38 @@ fn do_step2(
         if let Some(pos) = buffer.iter().position(|&b| b != 0) {
             buffer = buffer[pos..].to_vec();
         }
-        buffer
+        tl::byte_string(buffer)
     };
     let q_bytes = {
         let mut buffer = q.to_be_bytes().to_vec();
         if let Some(pos) = buffer.iter().position(|&b| b != 0) {
             buffer = buffer[pos..].to_vec();
         }
-        buffer
+        tl::byte_string(buffer)
     };
 
     // "pq is a representation of a natural number (in binary big endian format)"
     // https://core.telegram.org/mtproto/auth_key#dh-exchange-initiation
-    let pq_inner_data = tl::enums::PQInnerData::Data(tl::types::PQInnerData {
-        pq: pq.to_be_bytes().to_vec(),
-        p: p_bytes.clone(),
-        q: q_bytes.clone(),
-        nonce,
-        server_nonce: res_pq.server_nonce,
-        new_nonce,
-    })
+    let pq_inner_data = match temp {
+        None => tl::enums::PQInnerData::Data(tl::types::PQInnerData {
+            pq: tl::byte_string(pq.to_be_bytes().to_vec()),
+            p: p_bytes.clone(),
+            q: q_bytes.clone(),
+            nonce,
+            server_nonce: res_pq.server_nonce,
+            new_nonce,
+        }),
+        Some((dc, expires_in)) => tl::enums::PQInnerData::TempDc(tl::types::PQInnerDataTempDc {
+            pq: tl::byte_string(pq.to_be_bytes().to_vec()),
+            p: p_bytes.clone(),
+            q: q_bytes.clone(),
+            nonce,
+            server_nonce: res_pq.server_nonce,
+            new_nonce,
+            dc,
+            expires_in,
+        }),
+    }
     .to_bytes();
 
     // sha_digest + data + random_bytes
@@ -359,7 +403,7 @@ fn do_step2(
             p: p_bytes,
             q: q_bytes,
             public_key_fingerprint: fingerprint,
-            encrypted_data: ciphertext,
+            encrypted_data: tl::byte_string(ciphertext),
         }
         .to_bytes(),
         Step2 {
@@ -523,7 +567,7 @@ fn do_step3(
         nonce,
         server_nonce,
         retry_id: 0, // TODO use an actual retry_id
-        g_b: g_b.to_bytes_be(),
+        g_b: tl::byte_string(g_b.to_bytes_be()),
     })
     .to_bytes();
 
@@ -554,7 +598,7 @@ fn do_step3(
         tl::functions::SetClientDhParams {
             nonce,
             server_nonce,
-            encrypted_data: client_dh_encrypted,
+            encrypted_data: tl::byte_string(client_dh_encrypted),
         }
         .to_bytes(),
         Step3 {
@@ -662,6 +706,63 @@ pub fn create_key(data: Step3, response: &[u8]) -> Result<Finished, Error> {
     }
 }
 
+/// Build the request to bind a temporary authorization key to the permanent one.
+///
+/// The request must be sent encrypted with the temporary key, using the given `msg_id`
+/// (which is also embedded in the encrypted inner message) and from the session with
+/// identifier `temp_session_id`. `expires_at` should match the expiration date of the
+/// temporary key, as a Unix timestamp in the server's time.
+pub fn bind_temp_auth_key(
+    perm_auth_key: &[u8; 256],
+    temp_auth_key: &[u8; 256],
+    temp_session_id: i64,
+    msg_id: i64,
+    expires_at: i32,
+) -> tl::functions::auth::BindTempAuthKey {
+    let random_bytes = {
+        let mut buffer = [0; 8 + 16];
+        getrandom(&mut buffer).expect("failed to generate secure data for auth key binding");
+        buffer
+    };
+
+    let perm_auth_key = AuthKey::from_bytes(*perm_auth_key);
+    let perm_auth_key_id = i64::from_le_bytes(perm_auth_key.key_id());
+    let temp_auth_key_id = i64::from_le_bytes(AuthKey::from_bytes(*temp_auth_key).key_id());
+    let nonce = {
+        let mut buffer = [0; 8];
+        buffer.copy_from_slice(&random_bytes[..8]);
+        i64::from_le_bytes(buffer)
+    };
+
+    let inner = tl::enums::BindAuthKeyInner::Inner(tl::types::BindAuthKeyInner {
+        nonce,
+        temp_auth_key_id,
+        perm_auth_key_id,
+        temp_session_id,
+        expires_at,
+    })
+    .to_bytes();
+
+    // "random:int128" takes the place of the salt and session ID, followed by a message
+    // with the same `msg_id` as the outer request and a sequence number of zero.
+    let mut plaintext = Vec::with_capacity(16 + 8 + 4 + 4 + inner.len());
+    plaintext.extend(&random_bytes[8..]);
+    msg_id.serialize(&mut plaintext);
+    0i32.serialize(&mut plaintext);
+    (inner.len() as i32).serialize(&mut plaintext);
+    plaintext.extend(inner);
+
+    tl::functions::auth::BindTempAuthKey {
+        perm_auth_key_id,
+        nonce,
+        expires_at,
+        encrypted_message: tl::byte_string(grammers_crypto::encrypt_data_v1(
+            &plaintext,
+            &perm_auth_key,
+        )),
+    }
+}
+
 /// Helper function to avoid the boilerplate of checking for invalid nonce.
 fn check_nonce(got: &[u8; 16], expected: &[u8; 16]) -> Result<(), Error> {
     if got == expected {
@@ -753,7 +854,7 @@ mod tests {
         assert_eq!(request, step1_request.to_vec());
         let response = step1_response;
 
-        let (request, data) = do_step2(data, &response, &step2_random)?;
+        let (request, data) = do_step2(data, &response, &step2_random, None)?;
         assert_eq!(request, step2_request.to_vec());
         let response = step2_response;
 
@@ -0,0 +1,51 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Sources of randomness and time needed by the protocol.
+//!
+//! With the `std` feature, the operating system provides both. Without it, they must be
+//! supplied by the user, for example, from a hardware random number generator and a
+//! real-time clock on embedded devices.
+use core::time::Duration;
+
+/// The sources of secure random data and the current time used by the protocol.
+///
+/// With the `std` feature, [`Environment::default`] uses the random number generator and the
+/// clock of the operating system.
+#[derive(Clone, Copy, Debug)]
+pub struct Environment {
+    /// Fills the buffer with cryptographically secure random bytes.
+    pub random: fn(&mut [u8]),
+    /// Returns the time elapsed since the UNIX epoch.
+    pub now: fn() -> Duration,
+}
+
+impl Environment {
+    pub(crate) fn random_bytes<const N: usize>(&self) -> [u8; N] {
+        let mut buffer = [0; N];
+        (self.random)(&mut buffer);
+        buffer
+    }
+
+    pub(crate) fn now(&self) -> Duration {
+        (self.now)()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for Environment {
+    fn default() -> Self {
+        Self {
+            random: |buffer| getrandom::getrandom(buffer).expect("failed to generate secure data"),
+            now: || {
+                web_time::SystemTime::now()
+                    .duration_since(web_time::UNIX_EPOCH)
+                    .expect("system time is before epoch")
+            },
+        }
+    }
+}
@@ -6,6 +6,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(unsafe_code)]
 
 //! This library is an implementation of the [Mobile Transport Protocol].
@@ -14,13 +15,23 @@
 //! containers to later be encrypted and transmitted, and processing the
 //! server responses to maintain a correct state.
 //!
+//! Without the default `std` feature, the library only depends on `alloc`.
+//! Generating authorization keys and gzip compression are not available
+//! then, and the randomness and clock must be provided in an [`Environment`].
+//!
 //! [Mobile Transport Protocol]: https://core.telegram.org/mtproto
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub mod authentication;
+mod environment;
 mod manual_tl;
 pub mod mtp;
 pub mod transport;
 mod utils;
 
+pub use environment::Environment;
+
 /// The default compression threshold to be used.
 pub const DEFAULT_COMPRESSION_THRESHOLD: Option<usize> = Some(512);
 
@@ -33,3 +44,9 @@ pub const DEFAULT_COMPRESSION_THRESHOLD: Option<usize> = Some(512);
 /// you will know the response corresponds to it.
 #[derive(Copy, Clone, Debug, Hash, PartialEq, PartialOrd, Eq, Ord)]
 pub struct MsgId(i64);
+
+impl From<MsgId> for i64 {
+    fn from(msg_id: MsgId) -> Self {
+        msg_id.0
+    }
+}
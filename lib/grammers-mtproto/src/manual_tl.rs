@@ -8,9 +8,14 @@
 
 //! This module contains additional, manual structures for some TL types.
 use crate::mtp;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use flate2::write::{GzDecoder, GzEncoder};
+#[cfg(feature = "std")]
 use flate2::Compression;
 use grammers_tl_types::{self as tl, Cursor, Deserializable, Identifiable, Serializable};
+#[cfg(feature = "std")]
 use std::io::Write;
 
 /// This struct represents the following TL definition:
@@ -136,15 +141,13 @@ impl MessageContainer {
     /// The overhead of the container itself is subtracted.
     pub const MAXIMUM_SIZE: usize = 1_044_456 - Self::SIZE_OVERHEAD;
 
-    /// Maximum amount of messages that can't be sent inside a single
+    /// Maximum amount of messages that can be sent inside a single
     /// container, inclusive. Beyond this limit Telegram will respond
     /// with `BAD_MESSAGE` `64` (invalid container).
     ///
-    /// This limit is not 100% accurate and may in some cases be higher.
-    /// However, sending up to 100 requests at once in a single container
-    /// is a reasonable conservative value, since it could also depend on
-    /// other factors like size per request, but we cannot know this.
-    pub const MAXIMUM_LENGTH: usize = 100;
+    /// The size limit is usually reached first by large requests, but
+    /// many small ones (such as acknowledgements) can hit this one.
+    pub const MAXIMUM_LENGTH: usize = 1024;
 }
 
 impl Identifiable for MessageContainer {
@@ -199,6 +202,7 @@ pub(crate) struct GzipPacked {
 }
 
 impl GzipPacked {
+    #[cfg(feature = "std")]
     pub fn new(unpacked_data: &[u8]) -> Self {
         let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
         // Safe to unwrap, in-memory data should not fail
@@ -207,6 +211,7 @@ impl GzipPacked {
         Self { packed_data }
     }
 
+    #[cfg(feature = "std")]
     pub fn decompress(&self) -> Result<Vec<u8>, mtp::DeserializeError> {
         let writer = Vec::new();
         let mut decoder = GzDecoder::new(writer);
@@ -217,6 +222,12 @@ impl GzipPacked {
             .finish()
             .map_err(|_| mtp::DeserializeError::DecompressionFailed)
     }
+
+    // gzip is only supported with `std`, but the server rarely compresses responses.
+    #[cfg(not(feature = "std"))]
+    pub fn decompress(&self) -> Result<Vec<u8>, mtp::DeserializeError> {
+        Err(mtp::DeserializeError::DecompressionFailed)
+    }
 }
 
 impl Identifiable for GzipPacked {
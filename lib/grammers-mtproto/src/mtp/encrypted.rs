@@ -6,16 +6,18 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 use super::{
-    Deserialization, DeserializationFailure, DeserializeError, Mtp, RpcResult, RpcResultError,
+    Deserialization, DeserializationFailure, DeserializeError, MsgState, MsgStateReport, Mtp,
+    RpcResult, RpcResultError, UnackedAnswer,
 };
 use crate::utils::StackBuffer;
-use crate::{manual_tl, MsgId};
-use getrandom::getrandom;
-use grammers_crypto::{decrypt_data_v2, encrypt_data_v2, AuthKey, DequeBuffer};
+use crate::{manual_tl, Environment, MsgId};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+use core::time::Duration;
+use grammers_crypto::{decrypt_data_v2_into, encrypt_data_v2_with_padding, AuthKey, DequeBuffer};
 use grammers_tl_types::{self as tl, Cursor, Deserializable, Identifiable, Serializable};
 use log::info;
-use std::mem;
-use web_time::{Instant, SystemTime, UNIX_EPOCH};
 
 /// How many future salts to fetch or have stored at a given time.
 ///
@@ -28,6 +30,11 @@ const NUM_FUTURE_SALTS: i32 = 64;
 /// Used to prevent small fluctuations in the system clock.
 const SALT_USE_DELAY: i32 = 60;
 
+/// The maximum amount of message identifiers a single `msgs_ack` may contain.
+///
+/// See [Acknowledgment of Receipt](https://core.telegram.org/mtproto/service_messages_about_messages#acknowledgment-of-receipt).
+const MAX_ACKS_PER_MESSAGE: usize = 8192;
+
 static UPDATE_IDS: [u32; 8] = [
     tl::types::UpdateShortMessage::CONSTRUCTOR_ID,
     tl::types::UpdateShortChatMessage::CONSTRUCTOR_ID,
@@ -49,6 +56,7 @@ pub struct Builder {
     time_offset: i32,
     first_salt: i64,
     compression_threshold: Option<usize>,
+    environment: Environment,
 }
 
 /// An implementation of the [Mobile Transport Protocol] for ciphertext
@@ -59,6 +67,9 @@ pub struct Encrypted {
     /// The authorization key to use to encrypt payload.
     auth_key: AuthKey,
 
+    /// The source of random data and time.
+    environment: Environment,
+
     /// The time offset from the server's time, in seconds.
     time_offset: i32,
 
@@ -66,14 +77,20 @@ pub struct Encrypted {
     /// that can be used now).
     salts: Vec<tl::types::FutureSalt>,
 
-    /// The `now` received from future salts along with the instant when it occured.
+    /// The `now` received from future salts along with the local time when it occured.
     ///
     /// Used to accurately determine when salts become valid.
-    start_salt_time: Option<(i32, Instant)>,
+    start_salt_time: Option<(i32, Duration)>,
 
     /// Internal request for salts which should not be propagated.
     salt_request_msg_id: Option<MsgId>,
 
+    /// The container the internal request for salts was sent in, if it was not sent alone.
+    ///
+    /// Notifications about the container also affect the request for salts, which would
+    /// otherwise never be answered, and no other request would be sent while waiting for it.
+    salt_request_container_msg_id: Option<MsgId>,
+
     /// The secure, random identifier for this instance.
     client_id: i64,
 
@@ -83,6 +100,12 @@ pub struct Encrypted {
     /// The ID of the last message.
     last_msg_id: i64,
 
+    /// The ID reserved for the next request pushed, if any.
+    reserved_msg_id: Option<i64>,
+
+    /// The quick acknowledgement token of the last finalized buffer, if any.
+    quick_ack_token: Option<u32>,
+
     /// Identifiers that need to be acknowledged to the server.
     ///
     /// A [Content-related Message] is "a message requiring an explicit
@@ -100,6 +123,9 @@ pub struct Encrypted {
     /// Temporary deserialization results.
     deserialization: Vec<Deserialization>,
 
+    /// Buffer where incoming messages are decrypted, reused across messages.
+    plaintext: DequeBuffer<u8>,
+
     /// How many messages are there in the buffer.
     msg_count: usize,
 }
@@ -117,6 +143,8 @@ impl Builder {
     }
 
     /// Configures the compression threshold for outgoing messages.
+    ///
+    /// Without the `std` feature, messages are never compressed.
     pub fn compression_threshold(mut self, threshold: Option<usize>) -> Self {
         self.compression_threshold = threshold;
         self
@@ -127,6 +155,7 @@ impl Builder {
     pub fn finish(self, auth_key: [u8; 256]) -> Encrypted {
         Encrypted {
             auth_key: AuthKey::from_bytes(auth_key),
+            environment: self.environment,
             time_offset: self.time_offset,
             salts: vec![tl::types::FutureSalt {
                 valid_since: 0,
@@ -135,16 +164,16 @@ impl Builder {
             }],
             start_salt_time: None,
             salt_request_msg_id: None,
-            client_id: {
-                let mut buffer = [0u8; 8];
-                getrandom(&mut buffer).expect("failed to generate a secure client_id");
-                i64::from_le_bytes(buffer)
-            },
+            salt_request_container_msg_id: None,
+            client_id: i64::from_le_bytes(self.environment.random_bytes()),
             sequence: 0,
             last_msg_id: 0,
+            reserved_msg_id: None,
+            quick_ack_token: None,
             pending_ack: vec![],
             compression_threshold: self.compression_threshold,
             deserialization: Vec::new(),
+            plaintext: DequeBuffer::with_capacity(0, 0),
             msg_count: 0,
         }
     }
@@ -152,11 +181,18 @@ impl Builder {
 
 impl Encrypted {
     /// Start building a new encrypted MTP.
+    #[cfg(feature = "std")]
     pub fn build() -> Builder {
+        Self::build_with_environment(Environment::default())
+    }
+
+    /// Start building a new encrypted MTP which uses the given sources of randomness and time.
+    pub fn build_with_environment(environment: Environment) -> Builder {
         Builder {
             time_offset: 0,
             compression_threshold: crate::DEFAULT_COMPRESSION_THRESHOLD,
             first_salt: 0,
+            environment,
         }
     }
 
@@ -165,23 +201,58 @@ impl Encrypted {
         self.auth_key.to_bytes()
     }
 
+    /// Changes the compression threshold for outgoing messages.
+    ///
+    /// Content-related messages at least this large are compressed before
+    /// being sent, unless compressing them would not make them smaller.
+    /// If `None`, messages are never compressed.
+    pub fn set_compression_threshold(&mut self, threshold: Option<usize>) {
+        self.compression_threshold = threshold;
+    }
+
+    /// The secure, random identifier of the current session.
+    pub fn client_id(&self) -> i64 {
+        self.client_id
+    }
+
+    /// The time offset from the server's time, in seconds.
+    pub fn time_offset(&self) -> i32 {
+        self.time_offset
+    }
+
+    /// Override the time offset from the server's time, in seconds.
+    ///
+    /// This can be used to reuse the offset known from a previous connection, so that the
+    /// first messages are not rejected for having a wrong message identifier.
+    pub fn set_time_offset(&mut self, offset: i32) {
+        self.time_offset = offset;
+    }
+
+    /// Generate the message ID that will be given to the next request pushed.
+    ///
+    /// This is needed by requests which must know their own message ID before being
+    /// serialized, such as `auth.bindTempAuthKey`. The request will be the first message
+    /// in the next container.
+    pub fn reserve_msg_id(&mut self) -> i64 {
+        let msg_id = self.get_new_msg_id();
+        self.reserved_msg_id = Some(msg_id);
+        msg_id
+    }
+
     /// Correct our time offset based on a known valid message ID.
     fn correct_time_offset(&mut self, msg_id: i64) {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("system time is before epoch")
-            .as_secs() as i32;
+        let now = self.environment.now().as_secs() as i32;
 
         let correct = (msg_id >> 32) as i32;
         self.time_offset = correct - now;
+        // Otherwise, the new identifiers would still be based on the last (possibly too high) one.
+        self.last_msg_id = 0;
     }
 
     /// Generates a new unique message ID based on the current
     /// time (in ms) since epoch, applying a known time offset.
     fn get_new_msg_id(&mut self) -> i64 {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("system time is before epoch");
+        let now = self.environment.now();
 
         let seconds = (now.as_secs() as i32 + self.time_offset) as u64;
         let nanoseconds = now.subsec_nanos() as u64;
@@ -223,6 +294,34 @@ impl Encrypted {
         MsgId(msg_id)
     }
 
+    /// Serialize as many of the pending acknowledgements as fit in the current container.
+    ///
+    /// They are split in several `msgs_ack` if there are too many for a single one. Those that
+    /// don't fit are kept for the next container.
+    fn serialize_pending_acks(&mut self, buffer: &mut DequeBuffer<u8>) {
+        // constructor id (4 bytes), vector constructor id (4 bytes), vector len (4 bytes)
+        const MSGS_ACK_OVERHEAD: usize = manual_tl::Message::SIZE_OVERHEAD + 4 + 4 + 4;
+
+        while !self.pending_ack.is_empty()
+            && self.msg_count < manual_tl::MessageContainer::MAXIMUM_LENGTH
+        {
+            let room = manual_tl::MessageContainer::MAXIMUM_SIZE
+                .saturating_sub(buffer.len() + MSGS_ACK_OVERHEAD)
+                / 8;
+            let count = self.pending_ack.len().min(MAX_ACKS_PER_MESSAGE).min(room);
+            if count == 0 {
+                break;
+            }
+
+            // TODO avoid to_bytes here, serialize it in-place
+            let body = tl::enums::MsgsAck::Ack(tl::types::MsgsAck {
+                msg_ids: self.pending_ack.drain(..count).collect(),
+            })
+            .to_bytes();
+            self.serialize_msg(buffer, &body, false);
+        }
+    }
+
     fn get_current_salt(&self) -> i64 {
         self.salts.last().map(|s| s.salt).unwrap_or(0)
     }
@@ -248,10 +347,39 @@ impl Encrypted {
         }
     }
 
+    /// `deserialize`, decrypting the `payload` into the given `plaintext` buffer.
+    fn deserialize_plaintext(
+        &mut self,
+        payload: &[u8],
+        plaintext: &mut DequeBuffer<u8>,
+    ) -> Result<Vec<Deserialization>, DeserializeError> {
+        decrypt_data_v2_into(payload, &self.auth_key, plaintext)?;
+        let mut buffer = Cursor::from_slice(&plaintext[..]);
+
+        let _salt = i64::deserialize(&mut buffer)?;
+        let client_id = i64::deserialize(&mut buffer)?;
+        if client_id != self.client_id {
+            panic!("wrong session id");
+        }
+
+        self.process_message(manual_tl::Message::deserialize(&mut buffer)?)?;
+
+        // For simplicity, and to avoid passing too much stuff around (RPC results, updates),
+        // the processing result is stored in self. After processing is done, that temporary
+        // state is cleaned and returned with `mem::take`.
+        Ok(mem::take(&mut self.deserialization))
+    }
+
     /// `finalize`, but without encryption.
     ///
     /// The buffer is *not* cleared, but is instead returned.
     fn finalize_plain(&mut self, buffer: &mut DequeBuffer<u8>) {
+        // Acknowledgements don't need to wait for a request to be sent along with them, unless
+        // the reserved message ID has to be used first.
+        if self.reserved_msg_id.is_none() {
+            self.serialize_pending_acks(buffer);
+        }
+
         if self.msg_count == 0 {
             return;
         }
@@ -263,7 +391,11 @@ impl Encrypted {
             let mut header = StackBuffer::<MESSAGE_CONTAINER_HEADER_LEN>::new();
 
             // Manually `serialize_msg` because the container body was already written.
-            self.get_new_msg_id().serialize(&mut header);
+            let container_msg_id = self.get_new_msg_id();
+            if self.salt_request_msg_id.is_some() && self.salt_request_container_msg_id.is_none() {
+                self.salt_request_container_msg_id = Some(MsgId(container_msg_id));
+            }
+            container_msg_id.serialize(&mut header);
             self.get_seq_no(false).serialize(&mut header);
 
             len.serialize(&mut header);
@@ -521,7 +653,7 @@ impl Encrypted {
     fn store_own_updates(&mut self, body: &[u8]) {
         match u32::from_bytes(body) {
             Ok(body_id) => {
-                if UPDATE_IDS.iter().any(|&id| body_id == id) {
+                if UPDATE_IDS.contains(&body_id) {
                     // TODO somehow signal that this updates is our own, to avoid getting into nasty loops
                     self.deserialization
                         .push(Deserialization::Update(body.to_vec()));
@@ -564,7 +696,8 @@ impl Encrypted {
     /// [Acknowledgment of Receipt]: https://core.telegram.org/mtproto/service_messages_about_messages#acknowledgment-of-receipt
     fn handle_ack(&self, message: manual_tl::Message) -> Result<(), DeserializeError> {
         // TODO notify about this somehow
-        let _ack = tl::enums::MsgsAck::from_bytes(&message.body)?;
+        let tl::enums::MsgsAck::Ack(ack) = tl::enums::MsgsAck::from_bytes(&message.body)?;
+        log::trace!("server acknowledged msg_ids {:?}", ack.msg_ids);
         Ok(())
     }
 
@@ -639,17 +772,21 @@ impl Encrypted {
         message: manual_tl::Message,
     ) -> Result<(), DeserializeError> {
         let bad_msg = tl::enums::BadMsgNotification::from_bytes(&message.body)?;
+        let bad_msg_id = MsgId(bad_msg.bad_msg_id());
 
-        if self
-            .salt_request_msg_id
-            .is_some_and(|msg_id| msg_id == MsgId(bad_msg.bad_msg_id()))
-        {
+        if self.salt_request_msg_id == Some(bad_msg_id) {
             // Response to internal request, do not propagate.
             self.salt_request_msg_id = None;
+            self.salt_request_container_msg_id = None;
         } else {
+            if self.salt_request_container_msg_id == Some(bad_msg_id) {
+                // The request for salts needs to be sent again, along with the rest.
+                self.salt_request_msg_id = None;
+                self.salt_request_container_msg_id = None;
+            }
             self.deserialization
                 .push(Deserialization::BadMessage(super::BadMessage {
-                    msg_id: MsgId(bad_msg.bad_msg_id()),
+                    msg_id: bad_msg_id,
                     code: bad_msg.error_code(),
                 }));
         }
@@ -664,6 +801,7 @@ impl Encrypted {
                     salt: x.new_server_salt,
                 });
                 self.salt_request_msg_id = None;
+                self.salt_request_container_msg_id = None;
 
                 info!("got bad salt; salts have been reset down to a single one");
                 return Ok(());
@@ -749,8 +887,16 @@ impl Encrypted {
     /// valid, the message is to be wrapped in `msg_copy`).
     ///
     /// [Informational Message regarding Status of Messages]: https://core.telegram.org/mtproto/service_messages_about_messages#informational-message-regarding-status-of-messages
-    fn handle_state_info(&mut self, _message: manual_tl::Message) -> Result<(), DeserializeError> {
-        // TODO implement
+    fn handle_state_info(&mut self, message: manual_tl::Message) -> Result<(), DeserializeError> {
+        // The state info is the answer to a `msgs_state_req` sent by us, so it is treated just
+        // like the result of any other request for the caller to decode it.
+        let tl::enums::MsgsStateInfo::Info(info) =
+            tl::enums::MsgsStateInfo::from_bytes(&message.body)?;
+        self.deserialization
+            .push(Deserialization::RpcResult(RpcResult {
+                msg_id: MsgId(info.req_msg_id),
+                body: message.body,
+            }));
         Ok(())
     }
 
@@ -771,8 +917,17 @@ impl Encrypted {
     /// This message does not require an acknowledgment.
     ///
     /// [Voluntary Communication of Status of Messages]: https://core.telegram.org/mtproto/service_messages_about_messages#voluntary-communication-of-status-of-messages
-    fn handle_msg_all(&mut self, _message: manual_tl::Message) -> Result<(), DeserializeError> {
-        // TODO implement
+    fn handle_msg_all(&mut self, message: manual_tl::Message) -> Result<(), DeserializeError> {
+        let tl::enums::MsgsAllInfo::Info(info) = tl::enums::MsgsAllInfo::from_bytes(&message.body)?;
+        for (msg_id, state) in info.msg_ids.iter().zip(info.info.iter().copied()) {
+            let state = MsgState::new(state);
+            log::debug!("server reports {:?} for msg_id {}", state, msg_id);
+            self.deserialization
+                .push(Deserialization::MsgStateReport(MsgStateReport {
+                    msg_id: MsgId(*msg_id),
+                    state,
+                }));
+        }
         Ok(())
     }
 
@@ -808,10 +963,28 @@ impl Encrypted {
         let msg_detailed = tl::enums::MsgDetailedInfo::from_bytes(&message.body)?;
         match msg_detailed {
             tl::enums::MsgDetailedInfo::Info(x) => {
+                info!(
+                    "server reports answer {} to msg_id {} was never acknowledged",
+                    x.answer_msg_id, x.msg_id
+                );
                 self.pending_ack.push(x.answer_msg_id);
+                self.deserialization
+                    .push(Deserialization::UnackedAnswer(UnackedAnswer {
+                        msg_id: Some(MsgId(x.msg_id)),
+                        answer_msg_id: x.answer_msg_id,
+                    }));
             }
             tl::enums::MsgDetailedInfo::MsgNewDetailedInfo(x) => {
+                info!(
+                    "server reports message {} was never acknowledged",
+                    x.answer_msg_id
+                );
                 self.pending_ack.push(x.answer_msg_id);
+                self.deserialization
+                    .push(Deserialization::UnackedAnswer(UnackedAnswer {
+                        msg_id: None,
+                        answer_msg_id: x.answer_msg_id,
+                    }));
             }
         }
         Ok(())
@@ -883,6 +1056,7 @@ impl Encrypted {
         {
             // Response to internal request, do not propagate.
             self.salt_request_msg_id = None;
+            self.salt_request_container_msg_id = None;
         } else {
             self.deserialization
                 .push(Deserialization::RpcResult(RpcResult {
@@ -891,7 +1065,7 @@ impl Encrypted {
                 }));
         }
 
-        self.start_salt_time = Some((salts.now, Instant::now()));
+        self.start_salt_time = Some((salts.now, self.environment.now()));
         self.salts = salts.salts.0;
         self.salts.sort_by_key(|salt| -salt.valid_since);
         info!("got {} future salts", self.salts.len());
@@ -1194,12 +1368,28 @@ impl Mtp for Encrypted {
     fn push(&mut self, buffer: &mut DequeBuffer<u8>, request: &[u8]) -> Option<MsgId> {
         // TODO rather than taking in bytes, take requests, serialize them in place, and if too large drop the last part of the buffer
 
+        // A request with a reserved message ID goes first, so every other message gets a newer ID.
+        if let Some(msg_id) = self.reserved_msg_id {
+            if self.msg_count != 0 {
+                return None;
+            }
+            self.reserved_msg_id = None;
+
+            msg_id.serialize(buffer);
+            self.get_seq_no(true).serialize(buffer);
+            (request.len() as i32).serialize(buffer);
+            buffer.extend(request);
+            self.msg_count += 1;
+            return Some(MsgId(msg_id));
+        }
+
         // Check to see if the next salt can be used already. If it can, drop the current one and,
         // if the next salt is the last one, fetch more.
-        if let Some((start_secs, start_instant)) = self.start_salt_time {
+        if let Some((start_secs, start_time)) = self.start_salt_time {
             if self.salts.len() > 1 {
                 let salt = &self.salts[self.salts.len() - 2];
-                let now = start_secs + start_instant.elapsed().as_secs() as i32;
+                let elapsed = self.environment.now().saturating_sub(start_time);
+                let now = start_secs + elapsed.as_secs() as i32;
                 if now >= salt.valid_since + SALT_USE_DELAY {
                     self.salts.pop();
                 }
@@ -1215,34 +1405,23 @@ impl Mtp for Encrypted {
         // If we need to acknowledge messages, this notification goes in with the rest of requests
         // so that we can also include it. It has priority over user requests because these should
         // be sent out as soon as possible.
-        if !self.pending_ack.is_empty() {
-            // TODO avoid to_bytes here, serialize it in-place
-            let body = tl::enums::MsgsAck::Ack(tl::types::MsgsAck {
-                msg_ids: mem::take(&mut self.pending_ack),
-            })
-            .to_bytes();
-            self.serialize_msg(buffer, &body, false);
-        }
+        self.serialize_pending_acks(buffer);
 
         // Serialize `MAXIMUM_LENGTH` requests at most.
         if self.msg_count == manual_tl::MessageContainer::MAXIMUM_LENGTH {
             return None;
         }
 
-        // Requests that are too large would cause Telegram to close the
-        // connection but are so uncommon it's not worth returning `Err`.
-        assert!(
-            request.len() + manual_tl::Message::SIZE_OVERHEAD
-                <= manual_tl::MessageContainer::MAXIMUM_SIZE
-        );
-
         // Serialized requests will always be correctly padded.
-        assert!(request.len() % 4 == 0);
+        assert!(request.len().is_multiple_of(4));
 
         // Payload provided by the user is always considered to be
         // content-related, which means we can apply compression.
+        #[cfg_attr(not(feature = "std"), allow(unused_mut))]
         let mut body = request;
+        #[cfg(feature = "std")]
         let compressed;
+        #[cfg(feature = "std")]
         if let Some(threshold) = self.compression_threshold {
             if request.len() >= threshold {
                 compressed = manual_tl::GzipPacked::new(request).to_bytes();
@@ -1252,6 +1431,14 @@ impl Mtp for Encrypted {
             }
         }
 
+        // Requests that are too large would cause Telegram to close the
+        // connection but are so uncommon it's not worth returning `Err`.
+        // Only the size after compression matters, as that's what is sent.
+        assert!(
+            body.len() + manual_tl::Message::SIZE_OVERHEAD
+                <= manual_tl::MessageContainer::MAXIMUM_SIZE
+        );
+
         let new_size = buffer.len() + body.len() + manual_tl::Message::SIZE_OVERHEAD;
         if new_size >= manual_tl::MessageContainer::MAXIMUM_SIZE {
             // No more messages fit in this container.
@@ -1265,52 +1452,52 @@ impl Mtp for Encrypted {
     fn finalize(&mut self, buffer: &mut DequeBuffer<u8>) -> Option<MsgId> {
         self.finalize_plain(buffer);
         if buffer.is_empty() {
+            self.quick_ack_token = None;
             None
         } else {
-            encrypt_data_v2(buffer, &self.auth_key);
+            let random_padding = self.environment.random_bytes();
+            self.quick_ack_token = Some(encrypt_data_v2_with_padding(
+                buffer,
+                &self.auth_key,
+                &random_padding,
+            ));
             Some(MsgId(self.last_msg_id))
         }
     }
 
+    fn quick_ack_token(&self) -> Option<u32> {
+        self.quick_ack_token
+    }
+
     /// Processes an encrypted response from the server.
     fn deserialize(&mut self, payload: &[u8]) -> Result<Vec<Deserialization>, DeserializeError> {
         crate::utils::check_message_buffer(payload)?;
 
-        let plaintext = decrypt_data_v2(payload, &self.auth_key)?;
-        let mut buffer = Cursor::from_slice(&plaintext[..]);
-
-        let _salt = i64::deserialize(&mut buffer)?;
-        let client_id = i64::deserialize(&mut buffer)?;
-        if client_id != self.client_id {
-            panic!("wrong session id");
-        }
-
-        self.process_message(manual_tl::Message::deserialize(&mut buffer)?)?;
-
-        // For simplicity, and to avoid passing too much stuff around (RPC results, updates),
-        // the processing result is stored in self. After processing is done, that temporary
-        // state is cleaned and returned with `mem::take`.
-        Ok(mem::take(&mut self.deserialization))
+        // The buffer is taken out while processing so that `self` can still be borrowed mutably.
+        let mut plaintext = mem::replace(&mut self.plaintext, DequeBuffer::with_capacity(0, 0));
+        let result = self.deserialize_plaintext(payload, &mut plaintext);
+        self.plaintext = plaintext;
+        result
     }
 
     fn reset(&mut self) {
         log::info!("resetting mtp client id and related state");
-        self.client_id = {
-            let mut buffer = [0u8; 8];
-            getrandom(&mut buffer).expect("failed to generate a secure client_id");
-            i64::from_le_bytes(buffer)
-        };
+        self.client_id = i64::from_le_bytes(self.environment.random_bytes());
         self.sequence = 0;
         self.last_msg_id = 0;
+        self.reserved_msg_id = None;
+        self.quick_ack_token = None;
         self.pending_ack.clear();
         self.msg_count = 0;
         self.salt_request_msg_id = None;
+        self.salt_request_container_msg_id = None;
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mtp::BadMessage;
 
     // salt + client_id
     const MESSAGE_PREFIX_LEN: usize = 8 + 8;
@@ -1357,6 +1544,18 @@ mod tests {
         ensure_buffer_is_message(&buffer[MESSAGE_PREFIX_LEN..], REQUEST, 1);
     }
 
+    #[test]
+    fn ensure_environment_is_used() {
+        let environment = Environment {
+            random: |buffer| buffer.fill(7),
+            now: || Duration::from_secs(1000),
+        };
+        let mut mtproto = Encrypted::build_with_environment(environment).finish(auth_key());
+
+        assert_eq!(mtproto.client_id(), i64::from_le_bytes([7; 8]));
+        assert_eq!(mtproto.reserve_msg_id(), 1000 << 32);
+    }
+
     #[test]
     fn ensure_correct_single_serialization() {
         let mut buffer = DequeBuffer::with_capacity(0, 0);
@@ -1369,6 +1568,22 @@ mod tests {
         ensure_buffer_is_message(buffer, REQUEST, 1);
     }
 
+    #[test]
+    fn ensure_reserved_msg_id_is_used_first() {
+        let mut buffer = DequeBuffer::with_capacity(0, 0);
+        let mut mtproto = Encrypted::build().finish(auth_key());
+
+        let msg_id = mtproto.reserve_msg_id();
+        assert_eq!(mtproto.push(&mut buffer, REQUEST), Some(MsgId(msg_id)));
+        assert!(mtproto.push(&mut buffer, REQUEST_B).is_some());
+        mtproto.finalize_plain(&mut buffer);
+
+        // The second request gets a newer ID, so both go in a container.
+        let buffer = &buffer[MESSAGE_PREFIX_LEN..];
+        assert_eq!(&buffer[16..20], MSG_CONTAINER_HEADER);
+        assert_eq!(&buffer[24..32], msg_id.to_le_bytes());
+    }
+
     #[test]
     fn ensure_correct_multi_serialization() {
         let mut buffer = DequeBuffer::with_capacity(0, 0);
@@ -1436,8 +1651,11 @@ mod tests {
     #[test]
     #[should_panic]
     fn ensure_large_payload_panics() {
+        // Without compression, as null bytes would otherwise shrink enough to fit
         let mut buffer = DequeBuffer::with_capacity(0, 0);
-        let mut mtproto = Encrypted::build().finish(auth_key());
+        let mut mtproto = Encrypted::build()
+            .compression_threshold(None)
+            .finish(auth_key());
 
         mtproto.push(&mut buffer, &vec![0; 2 * 1024 * 1024]);
     }
@@ -1496,4 +1714,231 @@ mod tests {
             assert!(buffer.as_ref().windows(4).any(|w| w == GZIP_PACKED_HEADER));
         }
     }
+
+    #[test]
+    fn ensure_compressed_size_is_checked() {
+        // Too large to be sent as-is, but fine once compressed
+        let mut buffer = DequeBuffer::with_capacity(0, 0);
+        let mut mtproto = Encrypted::build()
+            .compression_threshold(Some(512))
+            .finish(auth_key());
+        assert!(mtproto
+            .push(
+                &mut buffer,
+                &vec![0; manual_tl::MessageContainer::MAXIMUM_SIZE]
+            )
+            .is_some());
+        mtproto.finalize_plain(&mut buffer);
+        assert!(buffer.as_ref().windows(4).any(|w| w == GZIP_PACKED_HEADER));
+    }
+
+    #[test]
+    fn ensure_compression_threshold_can_change() {
+        let mut buffer = DequeBuffer::with_capacity(0, 0);
+        let mut mtproto = Encrypted::build().finish(auth_key());
+        mtproto.set_compression_threshold(None);
+        mtproto.push(&mut buffer, &vec![0; 512 * 1024]);
+        mtproto.finalize_plain(&mut buffer);
+        assert!(!buffer.as_ref().windows(4).any(|w| w == GZIP_PACKED_HEADER));
+    }
+
+    #[test]
+    fn ensure_acks_are_sent_alone() {
+        let mut buffer = DequeBuffer::with_capacity(0, 0);
+        let mut mtproto = Encrypted::build().finish(auth_key());
+        mtproto.pending_ack.extend([1, 2, 3]);
+
+        mtproto.finalize_plain(&mut buffer);
+        let buffer = &buffer[MESSAGE_PREFIX_LEN..];
+        let body = tl::enums::MsgsAck::Ack(tl::types::MsgsAck {
+            msg_ids: vec![1, 2, 3],
+        })
+        .to_bytes();
+        ensure_buffer_is_message(buffer, &body, 0);
+        assert!(mtproto.pending_ack.is_empty());
+    }
+
+    #[test]
+    fn ensure_acks_are_batched_with_requests() {
+        let mut buffer = DequeBuffer::with_capacity(0, 0);
+        let mut mtproto = Encrypted::build().finish(auth_key());
+        mtproto
+            .pending_ack
+            .extend(0..MAX_ACKS_PER_MESSAGE as i64 + 1);
+
+        assert!(mtproto.push(&mut buffer, REQUEST).is_some());
+        assert!(mtproto.push(&mut buffer, REQUEST_B).is_some());
+        mtproto.finalize_plain(&mut buffer);
+
+        // Two acks (one full, one with the remaining ID) and the two requests.
+        let buffer = &buffer[MESSAGE_PREFIX_LEN..];
+        assert_eq!(&buffer[16..20], MSG_CONTAINER_HEADER);
+        assert_eq!(&buffer[20..24], [4, 0, 0, 0]);
+        assert!(mtproto.pending_ack.is_empty());
+    }
+
+    #[test]
+    fn ensure_container_length_is_limited() {
+        let mut buffer = DequeBuffer::with_capacity(0, 0);
+        let mut mtproto = Encrypted::build().finish(auth_key());
+        mtproto.pending_ack.push(1);
+
+        for _ in 1..manual_tl::MessageContainer::MAXIMUM_LENGTH {
+            assert!(mtproto.push(&mut buffer, REQUEST).is_some());
+        }
+        assert!(mtproto.push(&mut buffer, REQUEST).is_none());
+    }
+
+    #[test]
+    fn ensure_acks_that_dont_fit_wait() {
+        let mut buffer = DequeBuffer::with_capacity(0, 0);
+        let mut mtproto = Encrypted::build()
+            .compression_threshold(None)
+            .finish(auth_key());
+        let data = vec![0x7f; manual_tl::MessageContainer::MAXIMUM_SIZE - 1024];
+
+        assert!(mtproto.push(&mut buffer, &data).is_some());
+        mtproto.pending_ack.extend(0..1024);
+        mtproto.finalize_plain(&mut buffer);
+
+        // Only some of the acknowledgements fit, in a container with the request.
+        let buffer = &buffer[MESSAGE_PREFIX_LEN..];
+        assert_eq!(&buffer[16..20], MSG_CONTAINER_HEADER);
+        assert!(buffer.len() - 24 <= manual_tl::MessageContainer::MAXIMUM_SIZE);
+        assert!(!mtproto.pending_ack.is_empty());
+    }
+
+    fn bad_msg_notification(msg_id: i64, bad_msg_id: MsgId, error_code: i32) -> manual_tl::Message {
+        manual_tl::Message {
+            msg_id,
+            seq_no: 0,
+            body: tl::enums::BadMsgNotification::Notification(tl::types::BadMsgNotification {
+                bad_msg_id: bad_msg_id.0,
+                bad_msg_seqno: 0,
+                error_code,
+            })
+            .to_bytes(),
+        }
+    }
+
+    #[test]
+    fn ensure_time_offset_is_corrected() {
+        let environment = Environment {
+            random: |buffer| buffer.fill(7),
+            now: || Duration::from_secs(1000),
+        };
+        let mut mtproto = Encrypted::build_with_environment(environment).finish(auth_key());
+
+        // Our clock is ahead, so the message identifiers were too high.
+        let msg_id = MsgId(mtproto.reserve_msg_id());
+        mtproto
+            .process_message(bad_msg_notification(500 << 32, msg_id, 17))
+            .unwrap();
+
+        assert_eq!(mtproto.time_offset(), -500);
+        assert_eq!(mtproto.reserve_msg_id(), 500 << 32);
+        assert!(matches!(
+            mem::take(&mut mtproto.deserialization).as_slice(),
+            [Deserialization::BadMessage(BadMessage { code: 17, .. })]
+        ));
+    }
+
+    #[test]
+    fn ensure_salt_request_in_bad_container_is_sent_again() {
+        let mut buffer = DequeBuffer::with_capacity(0, 0);
+        let mut mtproto = Encrypted::build().first_salt(1).finish(auth_key());
+
+        // Only one salt is known, so more are requested first, with acknowledgements alongside.
+        mtproto.pending_ack.push(1);
+        assert!(mtproto.push(&mut buffer, REQUEST).is_none());
+        mtproto.finalize_plain(&mut buffer);
+        let container_msg_id = mtproto.salt_request_container_msg_id.unwrap();
+
+        mtproto
+            .process_message(bad_msg_notification(
+                mtproto.last_msg_id,
+                container_msg_id,
+                16,
+            ))
+            .unwrap();
+
+        // The notification is propagated, and the salts are requested again.
+        assert!(matches!(
+            mem::take(&mut mtproto.deserialization).as_slice(),
+            [Deserialization::BadMessage(BadMessage { code: 16, .. })]
+        ));
+        assert!(mtproto.salt_request_msg_id.is_none());
+        buffer.clear();
+        assert!(mtproto.push(&mut buffer, REQUEST).is_none());
+        assert!(mtproto.salt_request_msg_id.is_some());
+    }
+
+    #[test]
+    fn ensure_unacked_answers_are_reported() {
+        let mut mtproto = Encrypted::build().finish(auth_key());
+        for body in [
+            tl::enums::MsgDetailedInfo::Info(tl::types::MsgDetailedInfo {
+                msg_id: 1,
+                answer_msg_id: 2,
+                bytes: 0,
+                status: 0,
+            }),
+            tl::types::MsgNewDetailedInfo {
+                answer_msg_id: 3,
+                bytes: 0,
+                status: 0,
+            }
+            .into(),
+        ] {
+            mtproto
+                .process_message(manual_tl::Message {
+                    msg_id: 5,
+                    seq_no: 0,
+                    body: body.to_bytes(),
+                })
+                .unwrap();
+        }
+
+        // The answers are acknowledged, and reported so that they can be diagnosed.
+        assert_eq!(mtproto.pending_ack, [2, 3]);
+        assert!(matches!(
+            mem::take(&mut mtproto.deserialization).as_slice(),
+            [
+                Deserialization::UnackedAnswer(UnackedAnswer {
+                    msg_id: Some(MsgId(1)),
+                    answer_msg_id: 2,
+                }),
+                Deserialization::UnackedAnswer(UnackedAnswer {
+                    msg_id: None,
+                    answer_msg_id: 3,
+                }),
+            ]
+        ));
+    }
+
+    #[test]
+    fn ensure_msg_states_are_reported() {
+        let mut mtproto = Encrypted::build().finish(auth_key());
+        mtproto
+            .process_message(manual_tl::Message {
+                msg_id: 5,
+                seq_no: 0,
+                body: tl::enums::MsgsAllInfo::Info(tl::types::MsgsAllInfo {
+                    msg_ids: vec![1, 2],
+                    info: tl::byte_string(vec![2, 4]),
+                })
+                .to_bytes(),
+            })
+            .unwrap();
+
+        match mem::take(&mut mtproto.deserialization).as_slice() {
+            [Deserialization::MsgStateReport(first), Deserialization::MsgStateReport(second)] => {
+                assert_eq!(first.msg_id, MsgId(1));
+                assert!(first.state.not_received());
+                assert_eq!(second.msg_id, MsgId(2));
+                assert!(second.state.received());
+            }
+            _ => panic!("msg states were not reported"),
+        }
+    }
 }
@@ -20,6 +20,8 @@ mod encrypted;
 mod plain;
 
 use crate::MsgId;
+use alloc::vec::Vec;
+use core::fmt;
 use crypto::DequeBuffer;
 pub use encrypted::{
     Encrypted, ENCRYPTED_PACKET_HEADER_LEN, MAX_TRANSPORT_HEADER_LEN, MESSAGE_CONTAINER_HEADER_LEN,
@@ -28,7 +30,6 @@ pub use encrypted::{
 use grammers_crypto as crypto;
 use grammers_tl_types as tl;
 pub use plain::Plain;
-use std::fmt;
 
 pub struct RpcResult {
     pub msg_id: MsgId,
@@ -45,6 +46,39 @@ pub struct BadMessage {
     pub code: i32,
 }
 
+/// The server reports that a message it sent was never acknowledged.
+///
+/// See [Extended Voluntary Communication of Status of One Message] for details.
+///
+/// [Extended Voluntary Communication of Status of One Message]: https://core.telegram.org/mtproto/service_messages_about_messages#extended-voluntary-communication-of-status-of-one-message
+pub struct UnackedAnswer {
+    /// The request the message answered, or `None` if it was not sent in response to one.
+    pub msg_id: Option<MsgId>,
+    /// The identifier of the message sent by the server.
+    pub answer_msg_id: i64,
+}
+
+/// The server voluntarily reports the status of a message sent to it.
+///
+/// See [Voluntary Communication of Status of Messages] for details.
+///
+/// [Voluntary Communication of Status of Messages]: https://core.telegram.org/mtproto/service_messages_about_messages#voluntary-communication-of-status-of-messages
+pub struct MsgStateReport {
+    /// The message whose status is reported.
+    pub msg_id: MsgId,
+    /// The status of the message, as known by the server.
+    pub state: MsgState,
+}
+
+/// The status of a message, as known by the other party (normally, the server).
+///
+/// Obtained by sending a `msgs_state_req`, or voluntarily reported by the server.
+/// See [Informational Message regarding Status of Messages] for details.
+///
+/// [Informational Message regarding Status of Messages]: https://core.telegram.org/mtproto/service_messages_about_messages#informational-message-regarding-status-of-messages
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MsgState(u8);
+
 pub struct DeserializationFailure {
     pub msg_id: MsgId,
     pub error: DeserializeError,
@@ -56,6 +90,8 @@ pub enum Deserialization {
     RpcResult(RpcResult),
     RpcError(RpcResultError),
     BadMessage(BadMessage),
+    UnackedAnswer(UnackedAnswer),
+    MsgStateReport(MsgStateReport),
     Failure(DeserializationFailure),
 }
 
@@ -87,6 +123,58 @@ impl BadMessage {
     }
 }
 
+impl MsgState {
+    pub fn new(state: u8) -> Self {
+        Self(state)
+    }
+
+    /// The raw status byte sent by the server.
+    pub fn raw(&self) -> u8 {
+        self.0
+    }
+
+    /// Nothing is known about the message (its identifier is too low, and the other party may
+    /// have forgotten it).
+    pub fn unknown(&self) -> bool {
+        self.0 & 7 == 1
+    }
+
+    /// The message was certainly not received by the other party.
+    pub fn not_received(&self) -> bool {
+        matches!(self.0 & 7, 2 | 3)
+    }
+
+    /// The message was received by the other party.
+    pub fn received(&self) -> bool {
+        self.0 & 7 == 4
+    }
+
+    /// The message was already acknowledged.
+    pub fn acknowledged(&self) -> bool {
+        self.0 & 8 != 0
+    }
+
+    /// The message does not require acknowledgment.
+    pub fn ack_not_required(&self) -> bool {
+        self.0 & 16 != 0
+    }
+
+    /// The RPC query contained in the message is being processed, or processing is complete.
+    pub fn processing(&self) -> bool {
+        self.0 & 32 != 0
+    }
+
+    /// A content-related response to the message was already generated.
+    pub fn answered(&self) -> bool {
+        self.0 & 64 != 0
+    }
+
+    /// The other party knows for a fact that the message was already received.
+    pub fn known_received(&self) -> bool {
+        self.0 & 128 != 0
+    }
+}
+
 /// The error type for the deserialization of server messages.
 #[derive(Clone, Debug, PartialEq)]
 pub enum DeserializeError {
@@ -119,6 +207,7 @@ pub enum DeserializeError {
     DecryptionError(crypto::Error),
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for DeserializeError {}
 
 impl fmt::Display for DeserializeError {
@@ -201,6 +290,12 @@ pub trait Mtp {
     /// This will either belong to the container (if used) or the last serialized message.
     fn finalize(&mut self, buffer: &mut DequeBuffer<u8>) -> Option<MsgId>;
 
+    /// The token the server will send back as a quick acknowledgement of the buffer produced by
+    /// the last call to `finalize`, if the protocol supports them and one was produced.
+    fn quick_ack_token(&self) -> Option<u32> {
+        None
+    }
+
     /// Deserializes a single incoming message payload into zero or more responses.
     fn deserialize(&mut self, payload: &[u8]) -> Result<Vec<Deserialization>, DeserializeError>;
 
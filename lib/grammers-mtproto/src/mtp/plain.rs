@@ -7,6 +7,8 @@
 // except according to those terms.
 use super::{Deserialization, DeserializeError, Mtp, RpcResult};
 use crate::MsgId;
+use alloc::vec;
+use alloc::vec::Vec;
 use grammers_crypto::DequeBuffer;
 use grammers_tl_types::{Cursor, Deserializable, Serializable};
 
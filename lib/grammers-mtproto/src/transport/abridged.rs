@@ -36,12 +36,27 @@ use grammers_crypto::DequeBuffer;
 /// [abridged transport]: https://core.telegram.org/mtproto/mtproto-transports#abridged
 pub struct Abridged {
     init: bool,
+    quick_ack: bool,
+    server: bool,
 }
 
 #[allow(clippy::new_without_default)]
 impl Abridged {
     pub fn new() -> Self {
-        Self { init: false }
+        Self {
+            init: false,
+            quick_ack: false,
+            server: false,
+        }
+    }
+
+    /// Creates the server side of the transport, which never sends the tag.
+    pub(crate) fn server() -> Self {
+        Self {
+            init: true,
+            quick_ack: false,
+            server: true,
+        }
     }
 }
 
@@ -50,11 +65,15 @@ impl Transport for Abridged {
         let len = buffer.len();
         assert_eq!(len % 4, 0);
 
+        // The highest bit of the first byte requests a quick acknowledgement.
+        let quick_ack = if self.quick_ack { 0x80 } else { 0 };
+        self.quick_ack = false;
+
         let len = len / 4;
         if len < 127 {
-            buffer.extend_front(&[len as u8]);
+            buffer.extend_front(&[len as u8 | quick_ack]);
         } else {
-            buffer.extend_front(&(0x7f | ((len as u32) << 8)).to_le_bytes());
+            buffer.extend_front(&(0x7f | quick_ack as u32 | ((len as u32) << 8)).to_le_bytes());
         }
 
         if !self.init {
@@ -68,8 +87,26 @@ impl Transport for Abridged {
             return Err(Error::MissingBytes);
         }
 
+        // Quick acknowledgements are sent in big-endian with the highest bit set.
+        if buffer[0] & 0x80 != 0 && !self.server {
+            if buffer.len() < 4 {
+                return Err(Error::MissingBytes);
+            }
+
+            return Ok(UnpackedOffset {
+                data_start: 4,
+                data_end: 4,
+                next_offset: 4,
+                quick_ack: Some(u32::from_be_bytes(buffer[0..4].try_into().unwrap())),
+                quick_ack_requested: false,
+            });
+        }
+
+        // Clients instead use the highest bit of the length to request one.
+        let quick_ack_requested = buffer[0] & 0x80 != 0;
+
         let header_len;
-        let len = buffer[0];
+        let len = buffer[0] & 0x7f;
         let len = if len < 127 {
             header_len = 1;
             len as i32
@@ -87,12 +124,10 @@ impl Transport for Abridged {
             return Err(Error::MissingBytes);
         }
 
-        if header_len == 1 && len >= 4 {
+        if header_len == 1 && len >= 4 && !self.server {
             let data = i32::from_le_bytes(buffer[1..5].try_into().unwrap());
             if data < 0 {
-                return Err(Error::BadStatus {
-                    status: (-data) as u32,
-                });
+                return Err(Error::TransportCode(data));
             }
         }
 
@@ -103,12 +138,20 @@ impl Transport for Abridged {
             data_start: header_len,
             data_end: header_len + len,
             next_offset: header_len + len,
+            quick_ack: None,
+            quick_ack_requested,
         })
     }
 
+    fn request_quick_ack(&mut self) -> bool {
+        self.quick_ack = !self.server;
+        self.quick_ack
+    }
+
     fn reset(&mut self) {
         log::info!("resetting sending of header in abridged transport");
-        self.init = false;
+        self.init = self.server;
+        self.quick_ack = false;
     }
 }
 
@@ -162,6 +205,38 @@ mod tests {
         assert_eq!(&buffer[5..], &orig[..]);
     }
 
+    #[test]
+    fn pack_quick_ack() {
+        let (mut transport, mut buffer) = setup_pack(128);
+        assert!(transport.request_quick_ack());
+        transport.pack(&mut buffer);
+        assert_eq!(&buffer[..2], &[0xef, 0x80 | 32]);
+
+        let (_, mut buffer) = setup_pack(128);
+        transport.pack(&mut buffer);
+        assert_eq!(&buffer[..1], &[32]);
+    }
+
+    #[test]
+    fn pack_large_quick_ack() {
+        let (mut transport, mut buffer) = setup_pack(1024);
+        transport.request_quick_ack();
+        transport.pack(&mut buffer);
+        assert_eq!(&buffer[..5], &[0xef, 0xff, 0, 1, 0]);
+    }
+
+    #[test]
+    fn unpack_quick_ack() {
+        let mut transport = Abridged::new();
+        let mut buffer = DequeBuffer::with_capacity(4, 0);
+        buffer.extend(&0x8123_4567_u32.to_be_bytes());
+
+        let offset = transport.unpack(&mut buffer[..]).unwrap();
+        assert_eq!(offset.quick_ack, Some(0x8123_4567));
+        assert_eq!(offset.data_start, offset.data_end);
+        assert_eq!(offset.next_offset, 4);
+    }
+
     #[test]
     fn unpack_small() {
         let mut transport = Abridged::new();
@@ -222,7 +297,7 @@ mod tests {
 
         assert_eq!(
             transport.unpack(&mut buffer[..]),
-            Err(Error::BadStatus { status: 404 })
+            Err(Error::TransportCode(-404))
         );
     }
 }
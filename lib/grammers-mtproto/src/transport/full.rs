@@ -74,9 +74,7 @@ impl Transport for Full {
         let len = i32::from_le_bytes(buffer[0..4].try_into().unwrap());
         if len < 12 {
             if len < 0 {
-                return Err(Error::BadStatus {
-                    status: (-len) as u32,
-                });
+                return Err(Error::TransportCode(len));
             }
             return Err(Error::BadLen { got: len });
         }
@@ -112,10 +110,21 @@ impl Transport for Full {
         }
 
         self.recv_seq += 1;
+
+        // The code may also be sent as the only payload of a regular packet.
+        if len == 16 {
+            let data = i32::from_le_bytes(buffer[8..12].try_into().unwrap());
+            if data < 0 {
+                return Err(Error::TransportCode(data));
+            }
+        }
+
         Ok(UnpackedOffset {
             data_start: 8,
             data_end: len - 4,
             next_offset: len,
+            quick_ack: None,
+            quick_ack_requested: false,
         })
     }
 
@@ -262,7 +271,20 @@ mod tests {
 
         assert_eq!(
             transport.unpack(&mut buffer[..]),
-            Err(Error::BadStatus { status: 404 })
+            Err(Error::TransportCode(-404))
+        );
+    }
+
+    #[test]
+    fn unpack_framed_transport_code() {
+        let mut transport = Full::new();
+        let mut buffer = DequeBuffer::with_capacity(4, 0);
+        buffer.extend(&(-429_i32).to_le_bytes());
+        transport.pack(&mut buffer);
+
+        assert_eq!(
+            transport.unpack(&mut buffer[..]),
+            Err(Error::TransportCode(-429))
         );
     }
 }
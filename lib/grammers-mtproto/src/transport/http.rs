@@ -0,0 +1,272 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{Error, Transport, UnpackedOffset};
+use alloc::format;
+use alloc::string::{String, ToString};
+use grammers_crypto::DequeBuffer;
+
+/// The maximum length of the status line and headers of a response we're willing to parse.
+const MAX_HEADER_LEN: usize = 8 * 1024;
+
+/// A MTProto transport protocol on top of HTTP/1.1. This is an implementation
+/// of the [HTTP transport].
+///
+/// * Overhead: large.
+/// * Minimum envelope length: ~90 bytes.
+/// * Maximum envelope length: ~110 bytes.
+///
+/// Every payload is sent as the body of a `POST /api` request over a
+/// keep-alive connection, and received as the body of the response:
+///
+/// ```text
+/// POST /api HTTP/1.1\r\n
+/// Host: ...\r\n
+/// Connection: keep-alive\r\n
+/// Content-Length: ...\r\n
+/// \r\n
+/// payload
+/// ```
+///
+/// The server can only send data in the response to a request, so in order to
+/// receive updates while idle, a request must be kept open at all times (see
+/// [`Transport::needs_poll`]). The sender does so by sending [`http_wait`].
+///
+/// [HTTP transport]: https://core.telegram.org/mtproto/mtproto-transports#http
+/// [`http_wait`]: https://core.telegram.org/mtproto/service_messages#long-poll
+pub struct Http {
+    host: String,
+    // Number of requests sent whose response has not been received yet
+    pending_responses: usize,
+}
+
+impl Http {
+    /// Creates a new HTTP transport, which will identify the server as `host` in its requests.
+    pub fn new(host: &str) -> Self {
+        Self {
+            host: host.to_string(),
+            pending_responses: 0,
+        }
+    }
+}
+
+/// Returns the value of the header `name` by looking in the `headers` lines.
+fn find_header<'a>(headers: &'a str, name: &str) -> Option<&'a str> {
+    headers.split("\r\n").find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then_some(value.trim())
+    })
+}
+
+impl Transport for Http {
+    fn pack(&mut self, buffer: &mut DequeBuffer<u8>) {
+        let len = buffer.len();
+        assert_eq!(len % 4, 0);
+
+        let header = format!(
+            "POST /api HTTP/1.1\r\nHost: {}\r\nConnection: keep-alive\r\nContent-Length: {}\r\n\r\n",
+            self.host, len
+        );
+        buffer.extend_front(header.as_bytes());
+        self.pending_responses += 1;
+    }
+
+    fn unpack(&mut self, buffer: &mut [u8]) -> Result<UnpackedOffset, Error> {
+        let header_end = match buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            Some(pos) => pos + 4,
+            None if buffer.len() > MAX_HEADER_LEN => return Err(Error::BadHttp),
+            None => return Err(Error::MissingBytes),
+        };
+
+        let header = core::str::from_utf8(&buffer[..header_end]).map_err(|_| Error::BadHttp)?;
+        let (status_line, headers) = header.split_once("\r\n").ok_or(Error::BadHttp)?;
+
+        let mut status_parts = status_line.split(' ');
+        if !status_parts
+            .next()
+            .is_some_and(|version| version.starts_with("HTTP/1."))
+        {
+            return Err(Error::BadHttp);
+        }
+        let status = status_parts
+            .next()
+            .and_then(|status| status.parse::<i32>().ok())
+            .ok_or(Error::BadHttp)?;
+
+        // The server reports transport errors as the HTTP status itself.
+        if status != 200 {
+            return Err(Error::TransportCode(-status));
+        }
+
+        let len = find_header(headers, "Content-Length")
+            .and_then(|len| len.parse::<usize>().ok())
+            .ok_or(Error::BadHttp)?;
+
+        if buffer.len() < header_end + len {
+            return Err(Error::MissingBytes);
+        }
+
+        if len <= 4 {
+            if len == 4 {
+                let data =
+                    i32::from_le_bytes(buffer[header_end..header_end + 4].try_into().unwrap());
+                if data < 0 {
+                    return Err(Error::TransportCode(data));
+                }
+            }
+            return Err(Error::BadLen { got: len as i32 });
+        }
+
+        self.pending_responses = self.pending_responses.saturating_sub(1);
+
+        Ok(UnpackedOffset {
+            data_start: header_end,
+            data_end: header_end + len,
+            next_offset: header_end + len,
+            quick_ack: None,
+            quick_ack_requested: false,
+        })
+    }
+
+    fn needs_poll(&self) -> bool {
+        self.pending_responses == 0
+    }
+
+    fn reset(&mut self) {
+        log::info!("resetting pending responses in http transport");
+        self.pending_responses = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a new HTTP transport, and `n` bytes of input data for it.
+    fn setup_pack(n: u32) -> (Http, DequeBuffer<u8>) {
+        let mut buffer = DequeBuffer::with_capacity(n as usize, 0);
+        buffer.extend((0..n).map(|x| (x & 0xff) as u8));
+        (Http::new("149.154.167.51"), buffer)
+    }
+
+    /// Returns a response with the given status and `body`.
+    fn response(status: &str, body: &[u8]) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 {}\r\nConnection: keep-alive\r\ncontent-length: {}\r\n\r\n",
+            status,
+            body.len()
+        )
+        .into_bytes();
+        response.extend(body);
+        response
+    }
+
+    #[test]
+    fn pack_normal() {
+        let (mut transport, mut buffer) = setup_pack(128);
+        let orig = buffer.clone();
+        transport.pack(&mut buffer);
+        let header = b"POST /api HTTP/1.1\r\nHost: 149.154.167.51\r\nConnection: keep-alive\r\nContent-Length: 128\r\n\r\n";
+        assert_eq!(&buffer[..header.len()], header);
+        assert_eq!(&buffer[header.len()..], &orig[..]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn pack_non_padded() {
+        let (mut transport, mut buffer) = setup_pack(7);
+        transport.pack(&mut buffer);
+    }
+
+    #[test]
+    fn unpack_small() {
+        let mut transport = Http::new("localhost");
+        let mut buffer = b"HTTP/1.1 200 OK\r\n".to_vec();
+        assert_eq!(transport.unpack(&mut buffer[..]), Err(Error::MissingBytes));
+    }
+
+    #[test]
+    fn unpack_partial_body() {
+        let mut transport = Http::new("localhost");
+        let mut buffer = response("200 OK", &[1; 16]);
+        buffer.truncate(buffer.len() - 1);
+        assert_eq!(transport.unpack(&mut buffer[..]), Err(Error::MissingBytes));
+    }
+
+    #[test]
+    fn unpack_normal() {
+        let (mut transport, _) = setup_pack(0);
+        let body = (0..128).collect::<Vec<u8>>();
+        let mut buffer = response("200 OK", &body);
+        let offset = transport.unpack(&mut buffer[..]).unwrap();
+        assert_eq!(&buffer[offset.data_start..offset.data_end], &body[..]);
+        assert_eq!(offset.next_offset, buffer.len());
+    }
+
+    #[test]
+    fn unpack_two_at_once() {
+        let (mut transport, _) = setup_pack(0);
+        let body = (0..128).collect::<Vec<u8>>();
+        let mut buffer = response("200 OK", &body);
+        let single_size = buffer.len();
+        buffer.extend(response("200 OK", &body));
+
+        let offset = transport.unpack(&mut buffer[..]).unwrap();
+        assert_eq!(&buffer[offset.data_start..offset.data_end], &body[..]);
+        assert_eq!(offset.next_offset, single_size);
+
+        let offset = transport.unpack(&mut buffer[single_size..]).unwrap();
+        assert_eq!(
+            &buffer[single_size..][offset.data_start..offset.data_end],
+            &body[..]
+        );
+    }
+
+    #[test]
+    fn unpack_bad_status() {
+        let mut transport = Http::new("localhost");
+        let mut buffer = response("404 Not Found", &[]);
+        assert_eq!(
+            transport.unpack(&mut buffer[..]),
+            Err(Error::TransportCode(-404))
+        );
+    }
+
+    #[test]
+    fn unpack_transport_code() {
+        let mut transport = Http::new("localhost");
+        let mut buffer = response("200 OK", &(-429_i32).to_le_bytes());
+        assert_eq!(
+            transport.unpack(&mut buffer[..]),
+            Err(Error::TransportCode(-429))
+        );
+    }
+
+    #[test]
+    fn unpack_bad_response() {
+        let mut transport = Http::new("localhost");
+        let mut buffer = b"garbage\r\n\r\n".to_vec();
+        assert_eq!(transport.unpack(&mut buffer[..]), Err(Error::BadHttp));
+
+        let mut buffer = b"HTTP/1.1 200 OK\r\n\r\n".to_vec();
+        assert_eq!(transport.unpack(&mut buffer[..]), Err(Error::BadHttp));
+    }
+
+    #[test]
+    fn needs_poll_until_response() {
+        let (mut transport, mut buffer) = setup_pack(128);
+        assert!(transport.needs_poll());
+        transport.pack(&mut buffer);
+        assert!(!transport.needs_poll());
+
+        let mut buffer = response("200 OK", &[0; 128]);
+        transport.unpack(&mut buffer[..]).unwrap();
+        assert!(transport.needs_poll());
+    }
+}
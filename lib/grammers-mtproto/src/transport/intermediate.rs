@@ -27,6 +27,8 @@ use grammers_crypto::DequeBuffer;
 /// [intermediate transport]: https://core.telegram.org/mtproto/mtproto-transports#intermediate
 pub struct Intermediate {
     init: bool,
+    quick_ack: bool,
+    server: bool,
 }
 
 #[allow(clippy::new_without_default)]
@@ -34,7 +36,20 @@ impl Intermediate {
     const TAG: [u8; 4] = 0xee_ee_ee_ee_u32.to_le_bytes();
 
     pub fn new() -> Self {
-        Self { init: false }
+        Self {
+            init: false,
+            quick_ack: false,
+            server: false,
+        }
+    }
+
+    /// Creates the server side of the transport, which never sends the tag.
+    pub(crate) fn server() -> Self {
+        Self {
+            init: true,
+            quick_ack: false,
+            server: true,
+        }
     }
 }
 
@@ -43,7 +58,11 @@ impl Transport for Intermediate {
         let len = buffer.len();
         assert_eq!(len % 4, 0);
 
-        buffer.extend_front(&(len as i32).to_le_bytes());
+        // The highest bit of the length requests a quick acknowledgement.
+        let quick_ack = if self.quick_ack { 0x8000_0000 } else { 0 };
+        self.quick_ack = false;
+
+        buffer.extend_front(&(len as u32 | quick_ack).to_le_bytes());
 
         if !self.init {
             buffer.extend_front(&Self::TAG);
@@ -57,16 +76,32 @@ impl Transport for Intermediate {
         }
 
         let len = i32::from_le_bytes(buffer[0..4].try_into().unwrap());
-        if (buffer.len() as i32) < len {
+
+        // Quick acknowledgements are sent instead of the length with the highest bit set.
+        if len < 0 && !self.server {
+            return Ok(UnpackedOffset {
+                data_start: 4,
+                data_end: 4,
+                next_offset: 4,
+                quick_ack: Some(len as u32),
+                quick_ack_requested: false,
+            });
+        }
+
+        // Clients instead use the highest bit of the length to request one.
+        let quick_ack_requested = len < 0;
+        let len = len & 0x7fff_ffff;
+
+        if (buffer.len() as i32) < 4 + len {
             return Err(Error::MissingBytes);
         }
 
         if len <= 4 {
-            if len >= 4 {
+            if len == 4 && !self.server {
                 let data = i32::from_le_bytes(buffer[4..8].try_into().unwrap());
-                return Err(Error::BadStatus {
-                    status: (-data) as u32,
-                });
+                if data < 0 {
+                    return Err(Error::TransportCode(data));
+                }
             }
             return Err(Error::BadLen { got: len });
         }
@@ -77,12 +112,20 @@ impl Transport for Intermediate {
             data_start: 4,
             data_end: 4 + len,
             next_offset: 4 + len,
+            quick_ack: None,
+            quick_ack_requested,
         })
     }
 
+    fn request_quick_ack(&mut self) -> bool {
+        self.quick_ack = !self.server;
+        self.quick_ack
+    }
+
     fn reset(&mut self) {
         log::info!("resetting sending of header in intermediate transport");
-        self.init = false;
+        self.init = self.server;
+        self.quick_ack = false;
     }
 }
 
@@ -127,6 +170,30 @@ mod tests {
         assert_eq!(&buffer[8..buffer.len()], &orig[..]);
     }
 
+    #[test]
+    fn pack_quick_ack() {
+        let (mut transport, mut buffer) = setup_pack(128);
+        assert!(transport.request_quick_ack());
+        transport.pack(&mut buffer);
+        assert_eq!(&buffer[..8], &[0xee, 0xee, 0xee, 0xee, 128, 0, 0, 0x80]);
+
+        let (_, mut buffer) = setup_pack(128);
+        transport.pack(&mut buffer);
+        assert_eq!(&buffer[..4], &[128, 0, 0, 0]);
+    }
+
+    #[test]
+    fn unpack_quick_ack() {
+        let mut transport = Intermediate::new();
+        let mut buffer = DequeBuffer::with_capacity(4, 0);
+        buffer.extend(&0x8123_4567_u32.to_le_bytes());
+
+        let offset = transport.unpack(&mut buffer[..]).unwrap();
+        assert_eq!(offset.quick_ack, Some(0x8123_4567));
+        assert_eq!(offset.data_start, offset.data_end);
+        assert_eq!(offset.next_offset, 4);
+    }
+
     #[test]
     fn unpack_small() {
         let mut transport = Intermediate::new();
@@ -177,7 +244,7 @@ mod tests {
 
         assert_eq!(
             transport.unpack(&mut buffer[..]),
-            Err(Error::BadStatus { status: 404 })
+            Err(Error::TransportCode(-404))
         );
     }
 }
@@ -13,15 +13,22 @@
 //! [MTProto transports]: https://core.telegram.org/mtproto#mtproto-transport
 mod abridged;
 mod full;
+mod http;
 mod intermediate;
 mod obfuscated;
+mod padded_intermediate;
+mod server;
 
 pub use abridged::Abridged;
+use alloc::boxed::Box;
+use core::fmt;
 pub use full::Full;
 use grammers_crypto::DequeBuffer;
+pub use http::Http;
 pub use intermediate::Intermediate;
 pub use obfuscated::Obfuscated;
-use std::fmt;
+pub use padded_intermediate::PaddedIntermediate;
+pub use server::{accept, Handshake, Protocol};
 
 /// The error type reported by the different transports when something is wrong.
 ///
@@ -42,19 +49,28 @@ pub enum Error {
     /// The checksum of the packet does not match its expected value.
     BadCrc { expected: u32, got: u32 },
 
-    /// A negative length was received, indicating a [transport-level error].
-    /// The absolute value of this length behaves like an [HTTP status code]:
+    /// A negative 4-byte code was received instead of a packet, indicating a
+    /// [transport-level error]. The absolute value of this code behaves like an
+    /// [HTTP status code]:
     ///
-    /// * 404, if the authorization key used was not found, meaning that the
+    /// * -404, if the authorization key used was not found, meaning that the
     ///   server is not aware of the key used by the client, so it cannot be
     ///   used to securely communicate with it.
     ///
-    /// * 429, if too many transport connections are established to the same
+    /// * -429, if too many transport connections are established to the same
     ///   IP address in a too-short lapse of time.
     ///
+    /// * -444, if an invalid datacenter was specified while connecting.
+    ///
     /// [transport-level error]: https://core.telegram.org/mtproto/mtproto-transports#transport-errors
     /// [HTTP status code]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Status
-    BadStatus { status: u32 },
+    TransportCode(i32),
+
+    /// The response is not valid HTTP, or lacks the headers needed to read its body.
+    BadHttp,
+
+    /// The client announced a transport that is not known.
+    BadTag { got: [u8; 4] },
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -62,8 +78,21 @@ pub struct UnpackedOffset {
     pub data_start: usize,
     pub data_end: usize,
     pub next_offset: usize,
+    /// The token sent by the server as a [quick acknowledgement], if this packet was one.
+    ///
+    /// Quick acknowledgements carry no data, so the data range will be empty.
+    ///
+    /// [quick acknowledgement]: https://core.telegram.org/mtproto#tcp-transport
+    pub quick_ack: Option<u32>,
+    /// Whether the client requested a [quick acknowledgement] for this packet.
+    ///
+    /// Only transports created for the server side of a connection set this.
+    ///
+    /// [quick acknowledgement]: https://core.telegram.org/mtproto#tcp-transport
+    pub quick_ack_requested: bool,
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {}
 
 impl fmt::Display for Error {
@@ -78,9 +107,9 @@ impl fmt::Display for Error {
             Error::BadCrc { expected, got } => {
                 write!(f, "bad crc (expected {expected}, got {got})")
             }
-            Error::BadStatus { status } => {
-                write!(f, "bad status (negative length -{status})")
-            }
+            Error::TransportCode(code) => write!(f, "error code {code}"),
+            Error::BadHttp => write!(f, "bad http response"),
+            Error::BadTag { got } => write!(f, "bad tag (got {got:02x?})"),
         }
     }
 }
@@ -97,6 +126,22 @@ pub trait Transport {
     /// with the data on the ranges from previous `UnpackedOffset` removed.
     fn unpack(&mut self, buffer: &mut [u8]) -> Result<UnpackedOffset, Error>;
 
+    /// Request a quick acknowledgement from the server for the next packet packed.
+    ///
+    /// Returns `false` if the transport does not support quick acknowledgements, in which case
+    /// the server will not send one.
+    fn request_quick_ack(&mut self) -> bool {
+        false
+    }
+
+    /// Whether the server is unable to send anything until a new packet is sent.
+    ///
+    /// When this is `true`, a packet should be sent even if there is nothing to send, so that
+    /// the server can use its response to deliver pending messages, such as updates.
+    fn needs_poll(&self) -> bool {
+        false
+    }
+
     /// Reset the state, as if a new instance was just created.
     fn reset(&mut self);
 }
@@ -107,3 +152,25 @@ pub trait Tagged {
     /// changes the internal state to avoid sending the tag again.
     fn init_tag(&mut self) -> [u8; 4];
 }
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn pack(&mut self, buffer: &mut DequeBuffer<u8>) {
+        (**self).pack(buffer)
+    }
+
+    fn unpack(&mut self, buffer: &mut [u8]) -> Result<UnpackedOffset, Error> {
+        (**self).unpack(buffer)
+    }
+
+    fn request_quick_ack(&mut self) -> bool {
+        (**self).request_quick_ack()
+    }
+
+    fn needs_poll(&self) -> bool {
+        (**self).needs_poll()
+    }
+
+    fn reset(&mut self) {
+        (**self).reset()
+    }
+}
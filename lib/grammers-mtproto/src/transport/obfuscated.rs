@@ -9,6 +9,7 @@ use grammers_crypto::{obfuscated::ObfuscatedCipher, DequeBuffer};
 use log::debug;
 
 use super::{Error, Tagged, Transport, UnpackedOffset};
+use crate::Environment;
 
 /// An obfuscation protocol made by telegram to avoid ISP blocks.
 /// This is needed to connect to the Telegram servers using websockets or
@@ -28,6 +29,8 @@ pub struct Obfuscated<T: Transport + Tagged> {
     head: Option<[u8; 64]>,
     decrypt_tail: usize,
     cipher: ObfuscatedCipher,
+    // `None` on the server side, which uses the keys generated by the client.
+    environment: Option<Environment>,
 }
 
 const FORBIDDEN_FIRST_INTS: [[u8; 4]; 7] = [
@@ -41,35 +44,55 @@ const FORBIDDEN_FIRST_INTS: [[u8; 4]; 7] = [
 ];
 
 impl<T: Transport + Tagged> Obfuscated<T> {
-    fn generate_keys(inner: &mut T) -> ([u8; 64], ObfuscatedCipher) {
+    fn generate_keys(inner: &mut T, environment: Environment) -> ([u8; 64], ObfuscatedCipher) {
         let mut init = [0; 64];
 
         while init[4..8] == [0; 4] // Full
             || init[0] == 0xef // Abridged
             || FORBIDDEN_FIRST_INTS.iter().any(|start| start == &init[..4])
         {
-            getrandom::getrandom(&mut init).unwrap();
+            (environment.random)(&mut init);
         }
 
         init[56..60].copy_from_slice(&inner.init_tag());
 
         let mut cipher = ObfuscatedCipher::new(&init);
 
-        let mut encrypted_init = init.to_vec();
+        let mut encrypted_init = init;
         cipher.encrypt(&mut encrypted_init);
         init[56..64].copy_from_slice(&encrypted_init[56..64]);
 
         (init, cipher)
     }
 
-    pub fn new(mut inner: T) -> Self {
-        let (init, cipher) = Self::generate_keys(&mut inner);
+    #[cfg(feature = "std")]
+    pub fn new(inner: T) -> Self {
+        Self::with_environment(inner, Environment::default())
+    }
+
+    /// Like [`Obfuscated::new`], but uses the given [`Environment`] to generate
+    /// the random initialization payload.
+    pub fn with_environment(mut inner: T, environment: Environment) -> Self {
+        let (init, cipher) = Self::generate_keys(&mut inner, environment);
 
         Self {
             inner,
             head: Some(init),
             decrypt_tail: 0,
             cipher,
+            environment: Some(environment),
+        }
+    }
+
+    /// Creates the server side of the transport, after the client's
+    /// initialization payload has been used to create the `cipher`.
+    pub(crate) fn server(inner: T, cipher: ObfuscatedCipher) -> Self {
+        Self {
+            inner,
+            head: None,
+            decrypt_tail: 0,
+            cipher,
+            environment: None,
         }
     }
 }
@@ -100,12 +123,19 @@ impl<T: Transport + Tagged> Transport for Obfuscated<T> {
         }
     }
 
+    fn request_quick_ack(&mut self) -> bool {
+        self.inner.request_quick_ack()
+    }
+
     fn reset(&mut self) {
         self.inner.reset();
-        debug!("regenerating keys for obfuscated transport");
 
-        let (init, cipher) = Self::generate_keys(&mut self.inner);
-        self.head = Some(init);
-        self.cipher = cipher;
+        if let Some(environment) = self.environment {
+            debug!("regenerating keys for obfuscated transport");
+
+            let (init, cipher) = Self::generate_keys(&mut self.inner, environment);
+            self.head = Some(init);
+            self.cipher = cipher;
+        }
     }
 }
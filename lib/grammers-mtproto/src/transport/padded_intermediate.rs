@@ -0,0 +1,286 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{Error, Tagged, Transport, UnpackedOffset};
+use crate::Environment;
+use grammers_crypto::DequeBuffer;
+
+/// A variant of the intermediate transport which appends a random amount of
+/// padding to every packet, to make traffic harder to detect. This is an
+/// implementation of the [padded intermediate transport].
+///
+/// It's required by MTProto proxies working in secure mode, and is meant to be
+/// wrapped by the [`Obfuscated`] transport.
+///
+/// * Overhead: small-medium.
+/// * Minimum envelope length: 4 bytes.
+/// * Maximum envelope length: 19 bytes.
+///
+/// It serializes the input payload as follows:
+///
+/// ```text
+/// +----+----...----+----...----+
+/// | len|  payload  |  padding  |
+/// +----+----...----+----...----+
+///  ^^^^ 4 bytes     ^^^^^^^^^^^ 0 to 15 bytes
+/// ```
+///
+/// [padded intermediate transport]: https://core.telegram.org/mtproto/mtproto-transports#padded-intermediate
+/// [`Obfuscated`]: super::Obfuscated
+pub struct PaddedIntermediate {
+    init: bool,
+    quick_ack: bool,
+    server: bool,
+    environment: Environment,
+}
+
+#[allow(clippy::new_without_default)]
+impl PaddedIntermediate {
+    const TAG: [u8; 4] = 0xdd_dd_dd_dd_u32.to_le_bytes();
+
+    #[cfg(feature = "std")]
+    pub fn new() -> Self {
+        Self::with_environment(Environment::default())
+    }
+
+    /// Like [`PaddedIntermediate::new`], but uses the given [`Environment`] to
+    /// generate the random padding.
+    pub fn with_environment(environment: Environment) -> Self {
+        Self {
+            init: false,
+            quick_ack: false,
+            server: false,
+            environment,
+        }
+    }
+
+    /// Creates the server side of the transport, which never sends the tag.
+    pub(crate) fn server(environment: Environment) -> Self {
+        Self {
+            init: true,
+            quick_ack: false,
+            server: true,
+            environment,
+        }
+    }
+
+    // Inner body of `pack`, separated for testing purposes.
+    fn do_pack(&mut self, buffer: &mut DequeBuffer<u8>, padding: &[u8]) {
+        let len = buffer.len();
+        assert_eq!(len % 4, 0);
+
+        buffer.extend(padding.iter().copied());
+
+        // The highest bit of the length requests a quick acknowledgement.
+        let quick_ack = if self.quick_ack { 0x8000_0000 } else { 0 };
+        self.quick_ack = false;
+
+        buffer.extend_front(&((len + padding.len()) as u32 | quick_ack).to_le_bytes());
+
+        if !self.init {
+            buffer.extend_front(&Self::TAG);
+            self.init = true;
+        }
+    }
+}
+
+impl Transport for PaddedIntermediate {
+    fn pack(&mut self, buffer: &mut DequeBuffer<u8>) {
+        let padding = self.environment.random_bytes::<16>();
+        let padding_len = (padding[0] % 16) as usize;
+
+        self.do_pack(buffer, &padding[..padding_len])
+    }
+
+    fn unpack(&mut self, buffer: &mut [u8]) -> Result<UnpackedOffset, Error> {
+        if buffer.len() < 4 {
+            return Err(Error::MissingBytes);
+        }
+
+        let len = i32::from_le_bytes(buffer[0..4].try_into().unwrap());
+
+        // Quick acknowledgements are sent instead of the length with the highest bit set.
+        if len < 0 && !self.server {
+            return Ok(UnpackedOffset {
+                data_start: 4,
+                data_end: 4,
+                next_offset: 4,
+                quick_ack: Some(len as u32),
+                quick_ack_requested: false,
+            });
+        }
+
+        // Clients instead use the highest bit of the length to request one.
+        let quick_ack_requested = len < 0;
+        let len = len & 0x7fff_ffff;
+
+        if (buffer.len() as i32) < 4 + len {
+            return Err(Error::MissingBytes);
+        }
+
+        if len <= 4 {
+            if len == 4 && !self.server {
+                let data = i32::from_le_bytes(buffer[4..8].try_into().unwrap());
+                if data < 0 {
+                    return Err(Error::TransportCode(data));
+                }
+            }
+            return Err(Error::BadLen { got: len });
+        }
+
+        let len = len as usize;
+
+        // The padding is not part of the payload. Plain messages know their own length, but
+        // encrypted ones (with a non-zero auth key ID) must be 24 + 16 * n bytes long to be
+        // decrypted, so anything after that can be dropped.
+        let data_len = if len >= 24 && buffer[4..12] != [0; 8] {
+            len - (len - 24) % 16
+        } else {
+            len
+        };
+
+        Ok(UnpackedOffset {
+            data_start: 4,
+            data_end: 4 + data_len,
+            next_offset: 4 + len,
+            quick_ack: None,
+            quick_ack_requested,
+        })
+    }
+
+    fn request_quick_ack(&mut self) -> bool {
+        self.quick_ack = !self.server;
+        self.quick_ack
+    }
+
+    fn reset(&mut self) {
+        log::info!("resetting sending of header in padded intermediate transport");
+        self.init = self.server;
+        self.quick_ack = false;
+    }
+}
+
+impl Tagged for PaddedIntermediate {
+    fn init_tag(&mut self) -> [u8; 4] {
+        self.init = true;
+        Self::TAG
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a new padded intermediate transport, and `n` bytes of input data for it.
+    fn setup_pack(n: usize) -> (PaddedIntermediate, DequeBuffer<u8>) {
+        let mut buffer = DequeBuffer::with_capacity(n, 0);
+        buffer.extend((0..n).map(|x| (x & 0xff) as u8));
+        (PaddedIntermediate::new(), buffer)
+    }
+
+    #[test]
+    fn pack_empty() {
+        let (mut transport, mut buffer) = setup_pack(0);
+        transport.do_pack(&mut buffer, &[]);
+        assert_eq!(&buffer[..], &[0xdd, 0xdd, 0xdd, 0xdd, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn pack_non_padded() {
+        let (mut transport, mut buffer) = setup_pack(7);
+        transport.pack(&mut buffer);
+    }
+
+    #[test]
+    fn pack_normal() {
+        let (mut transport, mut buffer) = setup_pack(128);
+        let orig = buffer.clone();
+        transport.do_pack(&mut buffer, &[0xff; 3]);
+        assert_eq!(&buffer[..8], &[0xdd, 0xdd, 0xdd, 0xdd, 131, 0, 0, 0]);
+        assert_eq!(&buffer[8..136], &orig[..]);
+        assert_eq!(&buffer[136..], &[0xff; 3]);
+    }
+
+    #[test]
+    fn pack_random_padding() {
+        let (mut transport, mut buffer) = setup_pack(128);
+        transport.pack(&mut buffer);
+        let len = u32::from_le_bytes(buffer[4..8].try_into().unwrap()) as usize;
+        assert!((128..128 + 16).contains(&len));
+        assert_eq!(buffer.len(), 8 + len);
+    }
+
+    #[test]
+    fn unpack_small() {
+        let mut transport = PaddedIntermediate::new();
+        let mut buffer = DequeBuffer::with_capacity(1, 0);
+        buffer.extend([1]);
+        assert_eq!(transport.unpack(&mut buffer[..]), Err(Error::MissingBytes));
+    }
+
+    #[test]
+    fn unpack_plain() {
+        let (mut transport, mut buffer) = setup_pack(128);
+        buffer[..8].fill(0); // plain auth key ID
+        let orig = buffer.clone();
+        transport.do_pack(&mut buffer, &[0xff; 5]);
+        let n = 4; // init bytes
+        let offset = transport.unpack(&mut buffer[n..]).unwrap();
+        assert_eq!(
+            &buffer[n..][offset.data_start..offset.data_end][..128],
+            &orig[..]
+        );
+        assert_eq!(offset.next_offset, 4 + 128 + 5);
+    }
+
+    #[test]
+    fn unpack_encrypted_strips_padding() {
+        let (mut transport, mut buffer) = setup_pack(24 + 16 * 4);
+        let orig = buffer.clone();
+        transport.do_pack(&mut buffer, &[0xff; 15]);
+        let n = 4; // init bytes
+        let offset = transport.unpack(&mut buffer[n..]).unwrap();
+        assert_eq!(&buffer[n..][offset.data_start..offset.data_end], &orig[..]);
+        assert_eq!(offset.next_offset, 4 + 24 + 16 * 4 + 15);
+    }
+
+    #[test]
+    fn unpack_quick_ack() {
+        let mut transport = PaddedIntermediate::new();
+        let mut buffer = DequeBuffer::with_capacity(4, 0);
+        buffer.extend(&0x8123_4567_u32.to_le_bytes());
+
+        let offset = transport.unpack(&mut buffer[..]).unwrap();
+        assert_eq!(offset.quick_ack, Some(0x8123_4567));
+        assert_eq!(offset.data_start, offset.data_end);
+    }
+
+    #[test]
+    fn unpack_transport_code() {
+        let mut transport = PaddedIntermediate::new();
+        let mut buffer = DequeBuffer::with_capacity(8, 0);
+        buffer.extend(&(4_i32).to_le_bytes());
+        buffer.extend(&(-404_i32).to_le_bytes());
+
+        assert_eq!(
+            transport.unpack(&mut buffer[..]),
+            Err(Error::TransportCode(-404))
+        );
+    }
+
+    #[test]
+    fn obfuscated_roundtrip() {
+        use crate::transport::Obfuscated;
+
+        let (_, mut buffer) = setup_pack(24 + 16);
+        let orig = buffer.clone();
+        let mut transport = Obfuscated::new(PaddedIntermediate::new());
+        transport.pack(&mut buffer);
+        assert!(buffer.len() >= 64 + 4 + orig.len());
+    }
+}
@@ -0,0 +1,267 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use super::{
+    Abridged, Error, Full, Intermediate, Obfuscated, PaddedIntermediate, Tagged, Transport,
+};
+use crate::Environment;
+use alloc::boxed::Box;
+use grammers_crypto::obfuscated::ObfuscatedCipher;
+
+const ABRIDGED_TAG: [u8; 4] = [0xef, 0xef, 0xef, 0xef];
+const INTERMEDIATE_TAG: [u8; 4] = [0xee, 0xee, 0xee, 0xee];
+const PADDED_INTERMEDIATE_TAG: [u8; 4] = [0xdd, 0xdd, 0xdd, 0xdd];
+
+/// The transport protocol chosen by a client, as announced by the first bytes it sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Protocol {
+    Abridged,
+    Intermediate,
+    PaddedIntermediate,
+    Full,
+}
+
+/// The start of a connection made by a client, as seen by the server.
+///
+/// Created by [`accept`].
+pub struct Handshake {
+    protocol: Protocol,
+    len: usize,
+    obfuscation: Option<(ObfuscatedCipher, i16)>,
+}
+
+/// Reads the first bytes sent by a client to detect which transport it is using.
+///
+/// This is meant to be used when implementing the server side of a connection, such as in
+/// relays or mock servers. Both plain and [obfuscated] connections are supported, although
+/// proxy secrets are not.
+///
+/// Returns `Error::MissingBytes` if more bytes are needed to tell which transport is used,
+/// and `Error::BadTag` if the transport is not known.
+///
+/// The first [`Handshake::head_len`] bytes of the input belong to the handshake, and should
+/// not be given to the [`Transport`] returned by [`Handshake::into_transport`].
+///
+/// [obfuscated]: https://core.telegram.org/mtproto/mtproto-transports#transport-obfuscation
+pub fn accept(buffer: &[u8]) -> Result<Handshake, Error> {
+    if buffer.first() == Some(&0xef) {
+        return Ok(Handshake::plain(Protocol::Abridged, 1));
+    }
+    if buffer.len() < 4 {
+        return Err(Error::MissingBytes);
+    }
+    match buffer[..4].try_into().unwrap() {
+        INTERMEDIATE_TAG => return Ok(Handshake::plain(Protocol::Intermediate, 4)),
+        PADDED_INTERMEDIATE_TAG => {
+            return Ok(Handshake::plain(Protocol::PaddedIntermediate, 4));
+        }
+        _ => {}
+    }
+
+    // The full transport has no tag, but starts with a zero sequence number,
+    // which the obfuscated transport is not allowed to have in its place.
+    if buffer.len() < 8 {
+        return Err(Error::MissingBytes);
+    }
+    if buffer[4..8] == [0; 4] {
+        return Ok(Handshake::plain(Protocol::Full, 0));
+    }
+
+    let Some(init) = buffer.get(..64) else {
+        return Err(Error::MissingBytes);
+    };
+    let init: &[u8; 64] = init.try_into().unwrap();
+
+    let mut cipher = ObfuscatedCipher::new_server(init);
+    let mut decrypted = *init;
+    cipher.decrypt(&mut decrypted);
+
+    let protocol = match decrypted[56..60].try_into().unwrap() {
+        ABRIDGED_TAG => Protocol::Abridged,
+        INTERMEDIATE_TAG => Protocol::Intermediate,
+        PADDED_INTERMEDIATE_TAG => Protocol::PaddedIntermediate,
+        got => return Err(Error::BadTag { got }),
+    };
+    let dc_id = i16::from_le_bytes(decrypted[60..62].try_into().unwrap());
+
+    Ok(Handshake {
+        protocol,
+        len: 64,
+        obfuscation: Some((cipher, dc_id)),
+    })
+}
+
+impl Handshake {
+    fn plain(protocol: Protocol, len: usize) -> Self {
+        Self {
+            protocol,
+            len,
+            obfuscation: None,
+        }
+    }
+
+    /// The transport protocol chosen by the client.
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// How many bytes from the start of the connection belong to the handshake.
+    pub fn head_len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the connection uses the obfuscated transport.
+    pub fn is_obfuscated(&self) -> bool {
+        self.obfuscation.is_some()
+    }
+
+    /// The datacenter the client wants to reach, if the connection is obfuscated.
+    ///
+    /// Negative values identify media datacenters. Not all clients fill this in,
+    /// so the value may be meaningless.
+    pub fn dc_id(&self) -> Option<i16> {
+        self.obfuscation.as_ref().map(|(_, dc_id)| *dc_id)
+    }
+
+    /// Creates the server side of the transport chosen by the client.
+    ///
+    /// The returned transport unpacks the packets sent by the client, and packs the packets
+    /// sent to it. The `environment` is only used to generate the padded intermediate padding.
+    pub fn into_transport(self, environment: Environment) -> Box<dyn Transport + Send> {
+        match self.obfuscation {
+            None => match self.protocol {
+                Protocol::Abridged => Box::new(Abridged::server()),
+                Protocol::Intermediate => Box::new(Intermediate::server()),
+                Protocol::PaddedIntermediate => Box::new(PaddedIntermediate::server(environment)),
+                Protocol::Full => Box::new(Full::new()),
+            },
+            Some((cipher, _)) => match self.protocol {
+                Protocol::Abridged => obfuscated(Abridged::server(), cipher),
+                Protocol::Intermediate => obfuscated(Intermediate::server(), cipher),
+                Protocol::PaddedIntermediate => {
+                    obfuscated(PaddedIntermediate::server(environment), cipher)
+                }
+                Protocol::Full => unreachable!("full transport cannot be obfuscated"),
+            },
+        }
+    }
+}
+
+fn obfuscated<T: Transport + Tagged + Send + 'static>(
+    inner: T,
+    cipher: ObfuscatedCipher,
+) -> Box<dyn Transport + Send> {
+    Box::new(Obfuscated::server(inner, cipher))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use grammers_crypto::DequeBuffer;
+
+    /// Returns `n` bytes of input data which look like an encrypted message.
+    fn payload(n: usize) -> DequeBuffer<u8> {
+        let mut buffer = DequeBuffer::with_capacity(n, 0);
+        buffer.extend((0..n).map(|x| (x & 0xff) as u8 | 1));
+        buffer
+    }
+
+    /// Packs a message using the `client`, accepts it on the server, and makes sure both
+    /// sides can read what the other sent.
+    fn roundtrip(mut client: impl Transport, protocol: Protocol, obfuscated: bool) {
+        let orig = payload(24 + 16 * 4);
+
+        let mut buffer = orig.clone();
+        client.pack(&mut buffer);
+        let mut received = buffer[..].to_vec();
+
+        let handshake = accept(&received).unwrap();
+        assert_eq!(handshake.protocol(), protocol);
+        assert_eq!(handshake.is_obfuscated(), obfuscated);
+        assert_eq!(handshake.dc_id().is_some(), obfuscated);
+
+        let n = handshake.head_len();
+        let mut server = handshake.into_transport(Environment::default());
+        let offset = server.unpack(&mut received[n..]).unwrap();
+        assert_eq!(
+            &received[n..][offset.data_start..offset.data_end],
+            &orig[..]
+        );
+        assert_eq!(n + offset.next_offset, received.len());
+        assert!(!offset.quick_ack_requested);
+
+        let mut buffer = orig.clone();
+        server.pack(&mut buffer);
+        let mut sent = buffer[..].to_vec();
+        let offset = client.unpack(&mut sent).unwrap();
+        assert_eq!(&sent[offset.data_start..offset.data_end], &orig[..]);
+        assert_eq!(offset.next_offset, sent.len());
+    }
+
+    #[test]
+    fn accept_plain() {
+        roundtrip(Abridged::new(), Protocol::Abridged, false);
+        roundtrip(Intermediate::new(), Protocol::Intermediate, false);
+        roundtrip(
+            PaddedIntermediate::new(),
+            Protocol::PaddedIntermediate,
+            false,
+        );
+        roundtrip(Full::new(), Protocol::Full, false);
+    }
+
+    #[test]
+    fn accept_obfuscated() {
+        roundtrip(Obfuscated::new(Abridged::new()), Protocol::Abridged, true);
+        roundtrip(
+            Obfuscated::new(Intermediate::new()),
+            Protocol::Intermediate,
+            true,
+        );
+        roundtrip(
+            Obfuscated::new(PaddedIntermediate::new()),
+            Protocol::PaddedIntermediate,
+            true,
+        );
+    }
+
+    #[test]
+    fn accept_missing_bytes() {
+        assert!(matches!(accept(&[]), Err(Error::MissingBytes)));
+        assert!(matches!(accept(&[0xee, 0xee]), Err(Error::MissingBytes)));
+        assert!(matches!(accept(&[1; 32]), Err(Error::MissingBytes)));
+    }
+
+    #[test]
+    fn accept_bad_tag() {
+        assert!(matches!(accept(&[1; 64]), Err(Error::BadTag { .. })));
+    }
+
+    #[test]
+    fn unpack_quick_ack_request() {
+        for mut client in [
+            Box::new(Abridged::new()) as Box<dyn Transport>,
+            Box::new(Intermediate::new()),
+            Box::new(PaddedIntermediate::new()),
+        ] {
+            let mut buffer = payload(24 + 16);
+            assert!(client.request_quick_ack());
+            client.pack(&mut buffer);
+            let mut received = buffer[..].to_vec();
+
+            let handshake = accept(&received).unwrap();
+            let n = handshake.head_len();
+            let mut server = handshake.into_transport(Environment::default());
+            assert!(!server.request_quick_ack());
+
+            let offset = server.unpack(&mut received[n..]).unwrap();
+            assert!(offset.quick_ack_requested);
+            assert_eq!(offset.quick_ack, None);
+            assert_eq!(offset.data_end - offset.data_start, 24 + 16);
+        }
+    }
+}
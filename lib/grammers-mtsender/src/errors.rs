@@ -5,6 +5,7 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
+use crate::RpcErrorKind;
 use grammers_mtproto::{authentication, mtp, transport};
 use grammers_tl_types as tl;
 use std::fmt;
@@ -156,6 +157,27 @@ impl RpcError {
         }
     }
 
+    /// The kind of the RPC error, which can be matched on instead of comparing its name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grammers_mtsender::RpcErrorKind;
+    ///
+    /// # let rpc_err = grammers_mtsender::RpcError {
+    /// #     code: 420, name: "SLOWMODE_WAIT".to_string(), value: Some(10), caused_by: None };
+    /// #
+    /// match rpc_err.kind() {
+    ///     RpcErrorKind::FloodWait(seconds) | RpcErrorKind::SlowmodeWait(seconds) => {
+    ///         assert_eq!(seconds, 10);
+    ///     }
+    ///     _ => panic!(),
+    /// }
+    /// ```
+    pub fn kind(&self) -> RpcErrorKind {
+        RpcErrorKind::parse(&self.name, self.value)
+    }
+
     pub fn with_caused_by(mut self, constructor_id: u32) -> Self {
         self.caused_by = Some(constructor_id);
         self
@@ -167,6 +189,7 @@ impl RpcError {
 /// The request should be retransmited when this happens, unless the
 /// variant is `InvalidParameters`.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum InvocationError {
     /// The request invocation failed because it was invalid or the server
     /// could not process it successfully.
@@ -177,6 +200,13 @@ pub enum InvocationError {
 
     /// The error occured while reading the response.
     Read(ReadError),
+
+    /// A new authorization key was needed to send the request, but generating it failed.
+    Authentication(authentication::Error),
+
+    /// The request was not sent because the input used to build it was invalid. For example,
+    /// a small group chat was given to a method which only works with channels.
+    InvalidInput(&'static str),
 }
 
 impl std::error::Error for InvocationError {}
@@ -187,6 +217,8 @@ impl fmt::Display for InvocationError {
             Self::Rpc(err) => write!(f, "request error: {err}"),
             Self::Dropped => write!(f, "request error: dropped (cancelled)"),
             Self::Read(err) => write!(f, "request error: {err}"),
+            Self::Authentication(err) => write!(f, "request error: {err}"),
+            Self::InvalidInput(reason) => write!(f, "request error: invalid input: {reason}"),
         }
     }
 }
@@ -197,6 +229,15 @@ impl From<ReadError> for InvocationError {
     }
 }
 
+impl From<AuthorizationError> for InvocationError {
+    fn from(error: AuthorizationError) -> Self {
+        match error {
+            AuthorizationError::Gen(err) => Self::Authentication(err),
+            AuthorizationError::Invoke(err) => err,
+        }
+    }
+}
+
 impl From<mtp::DeserializeError> for InvocationError {
     fn from(error: mtp::DeserializeError) -> Self {
         Self::from(ReadError::from(error))
@@ -236,6 +277,31 @@ impl InvocationError {
             _ => false,
         }
     }
+
+    /// The kind of the RPC error, if this is a RPC error.
+    ///
+    /// See [`RpcError::kind`] for more details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grammers_mtsender::RpcErrorKind;
+    ///
+    /// # let request_result = Result::<(), _>::Err(grammers_mtsender::InvocationError::Rpc(
+    /// #     grammers_mtsender::RpcError { code: 403, name: "CHAT_WRITE_FORBIDDEN".to_string(), value: None, caused_by: None }));
+    /// #
+    /// match request_result {
+    ///     Err(err) if err.kind() == Some(RpcErrorKind::ChatWriteForbidden) => {},
+    ///     _ => panic!()
+    /// }
+    /// ```
+    #[inline]
+    pub fn kind(&self) -> Option<RpcErrorKind> {
+        match self {
+            Self::Rpc(rpc) => Some(rpc.kind()),
+            _ => None,
+        }
+    }
 }
 
 /// This error occurs when the process to generate an authorization key fails.
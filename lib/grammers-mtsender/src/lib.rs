@@ -9,28 +9,37 @@
 #![deny(unsafe_code)]
 
 mod errors;
+mod negotiation;
 mod net;
+mod observer;
 mod reconnection;
+mod rpc_errors;
 pub mod utils;
 
+pub use crate::negotiation::*;
+pub use crate::observer::*;
 pub use crate::reconnection::*;
 pub use errors::{AuthorizationError, InvocationError, ReadError, RpcError};
 use futures_util::future::{pending, select, Either};
-use grammers_crypto::DequeBuffer;
+pub use grammers_crypto::BufferPool;
+use grammers_crypto::PooledBuffer;
 use grammers_mtproto::mtp::{
-    self, BadMessage, Deserialization, DeserializationFailure, Mtp, RpcResult, RpcResultError,
+    self, BadMessage, Deserialization, DeserializationFailure, MsgStateReport, Mtp, RpcResult,
+    RpcResultError, UnackedAnswer,
 };
-use grammers_mtproto::transport::{self, Transport};
+use grammers_mtproto::transport::{self, Transport, UnpackedOffset};
 use grammers_mtproto::{authentication, MsgId};
 use grammers_tl_types::{self as tl, Deserializable, RemoteCall};
 use log::{debug, error, info, trace, warn};
 use net::NetStream;
 pub use net::ServerAddr;
+pub use rpc_errors::RpcErrorKind;
 use std::io;
 use std::io::Error;
 use std::ops::ControlFlow;
 use std::pin::pin;
-use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::OnceLock;
 use std::time::Duration;
 use tl::Serializable;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -57,17 +66,79 @@ const LEADING_BUFFER_SPACE: usize = mtp::MAX_TRANSPORT_HEADER_LEN
     + mtp::PLAIN_PACKET_HEADER_LEN
     + mtp::MESSAGE_CONTAINER_HEADER_LEN;
 
-/// Every how often are pings sent?
-const PING_DELAY: Duration = Duration::from_secs(60);
+/// Every how often are pings sent, by default?
+pub const PING_DELAY: Duration = Duration::from_secs(60);
 
-/// After how many seconds should the server close the connection when we send a ping?
+/// After how many seconds should the server close the connection when we send a ping, by default?
 ///
 /// What this value essentially means is that we have `NO_PING_DISCONNECT - PING_DELAY` seconds
 /// to keep sending pings, or the server will close the connection.
 ///
 /// Pings ensure the connection is kept active, and the delayed disconnect ensures the messages
 /// are getting through consistently enough.
-const NO_PING_DISCONNECT: i32 = 75;
+pub const NO_PING_DISCONNECT: Duration = Duration::from_secs(75);
+
+/// For how long should the server hold a poll open when there is nothing to send, for transports
+/// which need polling.
+const HTTP_WAIT: Duration = Duration::from_secs(25);
+
+/// Returns the pool from which every sender takes its read and write buffers.
+///
+/// The buffers are returned to the pool when the sender is dropped, so that connecting again
+/// (or connecting to a different datacenter for a file transfer) can reuse the memory. The pool
+/// can be configured to limit how many buffers and how much memory it keeps around.
+pub fn buffer_pool() -> &'static BufferPool {
+    static POOL: OnceLock<BufferPool> = OnceLock::new();
+    POOL.get_or_init(|| BufferPool::new(MAXIMUM_DATA, LEADING_BUFFER_SPACE))
+}
+
+/// Returns the pool from which the serialized body of every request is taken.
+///
+/// Bodies start small, but may keep up to [`MAXIMUM_DATA`] bytes around once returned, so that
+/// the chunks of a large file transfer don't need to be reallocated for every request.
+pub fn request_buffer_pool() -> &'static BufferPool {
+    static POOL: OnceLock<BufferPool> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let pool = BufferPool::new(REQUEST_BUFFER_CAPACITY, 0);
+        pool.set_max_retained_capacity(MAXIMUM_DATA);
+        pool
+    })
+}
+
+/// How much space a request body taken out of a new buffer has, before needing to grow.
+const REQUEST_BUFFER_CAPACITY: usize = 1024;
+
+/// Serialize the `request` into a buffer taken from the [`request_buffer_pool`].
+fn serialize_request<S: Serializable>(request: &S) -> PooledBuffer {
+    let mut body = request_buffer_pool().get();
+    request.serialize(&mut *body);
+    body
+}
+
+/// Take a buffer of [`MAXIMUM_DATA`] bytes from the [`buffer_pool`] to read incoming data into.
+fn read_buffer() -> PooledBuffer {
+    let mut buffer = buffer_pool().get();
+    buffer.extend(std::iter::repeat_n(0, MAXIMUM_DATA));
+    buffer
+}
+
+/// Transport error code sent by the server when it does not know the auth key used.
+const AUTH_KEY_NOT_FOUND: i32 = -404;
+
+/// Transport error code sent by the server when too many connections are made.
+const TRANSPORT_FLOOD: i32 = -429;
+
+/// Wrap the serialized `body` of a request in `invokeAfterMsg`.
+fn invoke_after_msg(msg_id: MsgId, body: &[u8]) -> PooledBuffer {
+    let mut wrapped = request_buffer_pool().get();
+    wrapped.extend(
+        <tl::functions::InvokeAfterMsg<tl::functions::Ping> as tl::Identifiable>::CONSTRUCTOR_ID
+            .to_le_bytes(),
+    );
+    wrapped.extend(i64::from(msg_id).to_le_bytes());
+    wrapped.extend(body);
+    wrapped
+}
 
 /// Generate a "random" ping ID.
 pub(crate) fn generate_random_id() -> i64 {
@@ -96,19 +167,65 @@ pub struct Sender<T: Transport, M: Mtp> {
     requests: Vec<Request>,
     request_rx: mpsc::UnboundedReceiver<Request>,
     next_ping: Instant,
+    ping_interval: Duration,
+    ping_disconnect_delay: Duration,
+    // Identifier and send time of the last keepalive ping which has not been answered yet
+    pending_ping: Option<(i64, Instant)>,
+    last_rtt: Option<Duration>,
+    // Quick acknowledgement tokens expected from the server, with the message ID of their packet
+    pending_quick_acks: Vec<(u32, MsgId)>,
+    // When the temporary authorization key used by this sender expires, if it uses one
+    temp_auth_key_expiry: Option<Instant>,
     reconnection_policy: &'static dyn ReconnectionPolicy,
+    observer: &'static dyn SenderObserver,
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
 
     // Transport-level buffers and positions
-    read_buffer: Vec<u8>,
+    read_buffer: PooledBuffer,
     read_tail: usize,
-    write_buffer: DequeBuffer<u8>,
+    write_buffer: PooledBuffer,
     write_head: usize,
 }
 
 struct Request {
-    body: Vec<u8>,
+    body: PooledBuffer,
     state: RequestState,
+    // Set if other requests may depend on this one
+    handle: Option<RequestHandle>,
+    // Request which must be processed by the server before this one
+    after: Option<RequestHandle>,
     result: oneshot::Sender<Result<Vec<u8>, InvocationError>>,
+    // Notified when the server sends a quick acknowledgement for the packet with this request
+    quick_ack: Option<oneshot::Sender<()>>,
+    // Lives as long as the request, so its duration is how long the request took to complete
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl Request {
+    fn new(body: PooledBuffer, result: oneshot::Sender<Result<Vec<u8>, InvocationError>>) -> Self {
+        #[cfg(feature = "tracing")]
+        let span = {
+            let req_id = u32::from_le_bytes([body[0], body[1], body[2], body[3]]);
+            tracing::debug_span!(
+                "request",
+                name = tl::name_for_id(req_id),
+                msg_id = tracing::field::Empty
+            )
+        };
+
+        Self {
+            body,
+            state: RequestState::NotSerialized,
+            handle: None,
+            after: None,
+            result,
+            quick_ack: None,
+            #[cfg(feature = "tracing")]
+            span,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -125,6 +242,18 @@ enum RequestState {
 
 pub struct Enqueuer(mpsc::UnboundedSender<Request>);
 
+/// Identifies a request enqueued with [`Enqueuer::enqueue_after`], so that later requests can
+/// be made to depend on it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RequestHandle(u64);
+
+impl RequestHandle {
+    fn next() -> Self {
+        static LAST_HANDLE: AtomicU64 = AtomicU64::new(0);
+        Self(LAST_HANDLE.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
 impl MsgIdPair {
     fn new(msg_id: MsgId) -> Self {
         Self {
@@ -141,7 +270,7 @@ impl Enqueuer {
         request: &R,
     ) -> oneshot::Receiver<Result<Vec<u8>, InvocationError>> {
         // TODO we probably want a bound here (to not enqueue more than N at once)
-        let body = request.to_bytes();
+        let body = serialize_request(request);
         assert!(body.len() >= 4);
         let req_id = u32::from_le_bytes([body[0], body[1], body[2], body[3]]);
         debug!(
@@ -150,15 +279,119 @@ impl Enqueuer {
         );
 
         let (tx, rx) = oneshot::channel();
-        if let Err(err) = self.0.send(Request {
-            body,
-            state: RequestState::NotSerialized,
-            result: tx,
-        }) {
+        if let Err(err) = self.0.send(Request::new(body, tx)) {
             err.0.result.send(Err(InvocationError::Dropped)).unwrap();
         }
         rx
     }
+
+    /// Like `enqueue`, but the server will only process the request once it's done processing
+    /// the request identified by `after`, by wrapping it in `invokeAfterMsg`.
+    ///
+    /// The returned handle can be used to make more requests depend on this one. If the request
+    /// `after` has already completed, or was enqueued in a different sender, there is nothing to
+    /// wait on and the request is sent as-is.
+    pub fn enqueue_after<R: RemoteCall>(
+        &self,
+        request: &R,
+        after: Option<RequestHandle>,
+    ) -> (
+        RequestHandle,
+        oneshot::Receiver<Result<Vec<u8>, InvocationError>>,
+    ) {
+        let body = serialize_request(request);
+        assert!(body.len() >= 4);
+        let req_id = u32::from_le_bytes([body[0], body[1], body[2], body[3]]);
+        debug!(
+            "enqueueing request {} to be serialized after {:?}",
+            tl::name_for_id(req_id),
+            after
+        );
+
+        let handle = RequestHandle::next();
+        let (tx, rx) = oneshot::channel();
+        let mut request = Request::new(body, tx);
+        request.handle = Some(handle);
+        request.after = after;
+        if let Err(err) = self.0.send(request) {
+            err.0.result.send(Err(InvocationError::Dropped)).unwrap();
+        }
+        (handle, rx)
+    }
+
+    /// Like `enqueue`, but also request a quick acknowledgement from the server.
+    ///
+    /// The first receiver completes as soon as the server confirms that the request arrived,
+    /// which can happen long before its result is available. If the transport does not support
+    /// quick acknowledgements, or the server does not send one, it is closed without completing
+    /// once the request is done.
+    pub fn enqueue_with_quick_ack<R: RemoteCall>(
+        &self,
+        request: &R,
+    ) -> (
+        oneshot::Receiver<()>,
+        oneshot::Receiver<Result<Vec<u8>, InvocationError>>,
+    ) {
+        let body = serialize_request(request);
+        assert!(body.len() >= 4);
+        let req_id = u32::from_le_bytes([body[0], body[1], body[2], body[3]]);
+        debug!(
+            "enqueueing request {} to be serialized with quick ack",
+            tl::name_for_id(req_id)
+        );
+
+        let (ack_tx, ack_rx) = oneshot::channel();
+        let (tx, rx) = oneshot::channel();
+        let mut request = Request::new(body, tx);
+        request.quick_ack = Some(ack_tx);
+        if let Err(err) = self.0.send(request) {
+            err.0.result.send(Err(InvocationError::Dropped)).unwrap();
+        }
+        (ack_rx, rx)
+    }
+}
+
+/// The receiving end of an [`Enqueuer`] created with [`enqueuer_channel`].
+pub struct RequestReceiver(mpsc::UnboundedReceiver<Request>);
+
+/// A request enqueued through an [`Enqueuer`] created with [`enqueuer_channel`], which is
+/// waiting to be answered.
+pub struct PendingRequest(Request);
+
+/// Creates an [`Enqueuer`] which is not connected to any [`Sender`], along with the receiving
+/// end of the requests enqueued through it.
+///
+/// This can be used to answer requests without a network connection, such as in tests.
+/// Requests depending on others or asking for a quick acknowledgement are received as-is.
+pub fn enqueuer_channel() -> (Enqueuer, RequestReceiver) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    (Enqueuer(tx), RequestReceiver(rx))
+}
+
+impl RequestReceiver {
+    /// Waits for the next enqueued request.
+    ///
+    /// Returns `None` once every [`Enqueuer`] for this receiver has been dropped.
+    pub async fn recv(&mut self) -> Option<PendingRequest> {
+        self.0.recv().await.map(PendingRequest)
+    }
+
+    /// Returns the next enqueued request, if there is one already.
+    pub fn try_recv(&mut self) -> Option<PendingRequest> {
+        self.0.try_recv().ok().map(PendingRequest)
+    }
+}
+
+impl PendingRequest {
+    /// The serialized request.
+    pub fn body(&self) -> &[u8] {
+        &self.0.body[..]
+    }
+
+    /// Completes the request with the given result.
+    pub fn respond(self, result: Result<Vec<u8>, InvocationError>) {
+        drop(self.0.result.send(result));
+    }
 }
 
 impl<T: Transport, M: Mtp> Sender<T, M> {
@@ -170,6 +403,8 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
     ) -> Result<(Self, Enqueuer), io::Error> {
         let stream = NetStream::connect(&addr).await?;
         let (tx, rx) = mpsc::unbounded_channel();
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!("connection", addr = ?addr, dc_id = tracing::field::Empty);
         Ok((
             Self {
                 stream,
@@ -179,11 +414,20 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
                 requests: vec![],
                 request_rx: rx,
                 next_ping: Instant::now() + PING_DELAY,
+                ping_interval: PING_DELAY,
+                ping_disconnect_delay: NO_PING_DISCONNECT,
+                pending_ping: None,
+                last_rtt: None,
+                pending_quick_acks: Vec::new(),
+                temp_auth_key_expiry: None,
                 reconnection_policy,
+                observer: &NoObserver,
+                #[cfg(feature = "tracing")]
+                span,
 
-                read_buffer: vec![0; MAXIMUM_DATA],
+                read_buffer: read_buffer(),
                 read_tail: 0,
-                write_buffer: DequeBuffer::with_capacity(MAXIMUM_DATA, LEADING_BUFFER_SPACE),
+                write_buffer: buffer_pool().get(),
                 write_head: 0,
             },
             Enqueuer(tx),
@@ -191,19 +435,21 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
     }
 
     pub async fn invoke<R: RemoteCall>(&mut self, request: &R) -> Result<Vec<u8>, InvocationError> {
-        let rx = self.enqueue_body(request.to_bytes());
+        let rx = self.enqueue_body(serialize_request(request));
         self.step_until_receive(rx).await
     }
 
     /// Like `invoke` but raw data.
     async fn send(&mut self, body: Vec<u8>) -> Result<Vec<u8>, InvocationError> {
-        let rx = self.enqueue_body(body);
+        let mut pooled = request_buffer_pool().get();
+        pooled.extend(&body);
+        let rx = self.enqueue_body(pooled);
         self.step_until_receive(rx).await
     }
 
     fn enqueue_body(
         &mut self,
-        body: Vec<u8>,
+        body: PooledBuffer,
     ) -> oneshot::Receiver<Result<Vec<u8>, InvocationError>> {
         assert!(body.len() >= 4);
         let req_id = u32::from_le_bytes([body[0], body[1], body[2], body[3]]);
@@ -213,11 +459,7 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
         );
 
         let (tx, rx) = oneshot::channel();
-        self.requests.push(Request {
-            body,
-            state: RequestState::NotSerialized,
-            result: tx,
-        });
+        self.requests.push(Request::new(body, tx));
         rx
     }
 
@@ -230,9 +472,7 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
             match rx.try_recv() {
                 Ok(x) => break x,
                 Err(TryRecvError::Empty) => continue,
-                Err(TryRecvError::Closed) => {
-                    panic!("request channel dropped before receiving a result")
-                }
+                Err(TryRecvError::Closed) => break Err(InvocationError::Dropped),
             }
         }
     }
@@ -241,6 +481,18 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
     ///
     /// Updates received during this step, if any, are returned.
     pub async fn step(&mut self) -> Result<Vec<tl::enums::Updates>, ReadError> {
+        #[cfg(feature = "tracing")]
+        {
+            use tracing::Instrument;
+            let span = self.span.clone();
+            self.do_step().instrument(span).await
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        self.do_step().await
+    }
+
+    async fn do_step(&mut self) -> Result<Vec<tl::enums::Updates>, ReadError> {
         enum Sel {
             Sleep,
             Request(Option<Request>),
@@ -248,6 +500,7 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
             Write(io::Result<usize>),
         }
 
+        let in_flight = self.requests.len();
         self.try_fill_write();
         let write_len = self.write_buffer.len() - self.write_head;
         trace!(
@@ -293,10 +546,16 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
             }
         };
 
-        match res {
+        let res = match res {
             Ok(ok) => Ok(ok),
             Err(err) => self.on_error(err).await,
+        };
+
+        if self.requests.len() != in_flight {
+            self.observer.on_requests_in_flight(self.requests.len());
         }
+
+        res
     }
 
     #[allow(unused_variables)]
@@ -309,13 +568,13 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
                         "auto-reconnect success after {} failed attempt(s)",
                         attempts
                     );
+                    self.observer.on_reconnect(attempts);
                     self.stream = result;
                     return Ok(());
                 }
                 Err(e) => {
                     attempts += 1;
                     log::warn!("auto-reconnect failed {} time(s): {}", attempts, e);
-                    sleep(Duration::from_secs(1)).await;
 
                     match self.reconnection_policy.should_retry(attempts) {
                         ControlFlow::Break(_) => {
@@ -340,37 +599,116 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
             return;
         }
 
-        // TODO add a test to make sure we only ever send the same request once
-        for request in self
-            .requests
-            .iter_mut()
-            .filter(|r| matches!(r.state, RequestState::NotSerialized))
-        {
-            // TODO make mtp itself use BytesMut to avoid copies
-            if let Some(msg_id) = self.mtp.push(&mut self.write_buffer, &request.body) {
-                assert!(request.body.len() >= 4);
-                let req_id = u32::from_le_bytes([
-                    request.body[0],
-                    request.body[1],
-                    request.body[2],
-                    request.body[3],
-                ]);
-                debug!(
-                    "serialized request {:x} ({}) with {:?}",
-                    req_id,
-                    tl::name_for_id(req_id),
-                    msg_id
-                );
-                // Note how only NotSerialized become Serialized.
-                // Nasty bugs that take ~2h to find occur otherwise!
-                // (e.g. infinite loops leading to transport flood.)
-                request.state = RequestState::Serialized(MsgIdPair::new(msg_id));
-            } else {
+        let mut packed = 0;
+        // Requests are removed with `swap_remove`, so one may come before the request it depends
+        // on. Those are skipped until the dependency has a message ID, in another pass.
+        let mut full = false;
+        loop {
+            let packed_before = packed;
+            let mut waiting = false;
+            // TODO add a test to make sure we only ever send the same request once
+            for i in 0..self.requests.len() {
+                if !matches!(self.requests[i].state, RequestState::NotSerialized) {
+                    continue;
+                }
+
+                // Requests that depend on another use its current message ID, which may change if
+                // it's sent again, so the wrapper is only added when serializing.
+                let after_msg_id = match self.requests[i].after {
+                    Some(after) => match self
+                        .requests
+                        .iter()
+                        .find(|r| r.handle == Some(after))
+                        .map(|r| &r.state)
+                    {
+                        Some(RequestState::Serialized(pair) | RequestState::Sent(pair)) => {
+                            Some(pair.msg_id)
+                        }
+                        // It must get a message ID first, in this pass or a later one.
+                        Some(RequestState::NotSerialized) => {
+                            waiting = true;
+                            continue;
+                        }
+                        // Already done, so there's nothing to wait for.
+                        None => None,
+                    },
+                    None => None,
+                };
+
+                let request = &mut self.requests[i];
+                let wrapped;
+                let body = match after_msg_id {
+                    Some(after_msg_id) => {
+                        wrapped = invoke_after_msg(after_msg_id, &request.body[..]);
+                        &wrapped[..]
+                    }
+                    None => &request.body[..],
+                };
+
+                // TODO make mtp itself use BytesMut to avoid copies
+                if let Some(msg_id) = self.mtp.push(&mut self.write_buffer, body) {
+                    assert!(request.body.len() >= 4);
+                    let req_id = u32::from_le_bytes([
+                        request.body[0],
+                        request.body[1],
+                        request.body[2],
+                        request.body[3],
+                    ]);
+                    debug!(
+                        "serialized request {:x} ({}) with {:?}",
+                        req_id,
+                        tl::name_for_id(req_id),
+                        msg_id
+                    );
+                    // Note how only NotSerialized become Serialized.
+                    // Nasty bugs that take ~2h to find occur otherwise!
+                    // (e.g. infinite loops leading to transport flood.)
+                    #[cfg(feature = "tracing")]
+                    {
+                        request.span.record("msg_id", tracing::field::debug(msg_id));
+                        tracing::debug!(parent: &request.span, "serialized request");
+                    }
+                    request.state = RequestState::Serialized(MsgIdPair::new(msg_id));
+                    packed += 1;
+                } else {
+                    full = true;
+                    break;
+                }
+            }
+
+            if full || !waiting || packed == packed_before {
                 break;
             }
         }
 
+        if packed == 0 && self.transport.needs_poll() {
+            // Give the server a chance to send whatever it has for us, such as updates.
+            debug!("enqueueing http_wait to keep a poll open");
+            self.mtp.push(
+                &mut self.write_buffer,
+                &tl::types::HttpWait {
+                    max_delay: 0,
+                    wait_after: 0,
+                    max_wait: HTTP_WAIT.as_millis() as i32,
+                }
+                .to_bytes(),
+            );
+        }
+
         if let Some(container_msg_id) = self.mtp.finalize(&mut self.write_buffer) {
+            if packed > 1 {
+                self.observer.on_container_sent(packed);
+            }
+            let wants_quick_ack = self
+                .requests
+                .iter()
+                .any(|r| r.quick_ack.is_some() && matches!(r.state, RequestState::Serialized(_)));
+            if wants_quick_ack && self.transport.request_quick_ack() {
+                if let Some(token) = self.mtp.quick_ack_token() {
+                    self.forget_done_quick_acks();
+                    self.pending_quick_acks.push((token, container_msg_id));
+                }
+            }
             for request in self.requests.iter_mut() {
                 match request.state {
                     RequestState::Serialized(ref mut pair) => {
@@ -395,6 +733,7 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
         }
 
         self.read_tail += n;
+        self.observer.on_bytes_received(n);
         trace!("read {} bytes from the network", n);
         trace!("trying to unpack buffer of {} bytes...", self.read_tail);
 
@@ -407,6 +746,14 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
                 .transport
                 .unpack(&mut self.read_buffer[next_offset..self.read_tail])
             {
+                Ok(UnpackedOffset {
+                    quick_ack: Some(token),
+                    next_offset: offset,
+                    ..
+                }) => {
+                    self.on_quick_ack(token);
+                    next_offset += offset;
+                }
                 Ok(offset) => {
                     debug!("deserializing valid transport packet...");
                     let result = self.mtp.deserialize(
@@ -421,7 +768,9 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
             }
         }
 
-        self.read_buffer.copy_within(next_offset..self.read_tail, 0);
+        self.read_buffer
+            .as_mut()
+            .copy_within(next_offset..self.read_tail, 0);
         self.read_tail -= next_offset;
 
         Ok(updates)
@@ -430,6 +779,7 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
     /// Handle `n` more written bytes being ready to process by the transport.
     fn on_net_write(&mut self, n: usize) {
         self.write_head += n;
+        self.observer.on_bytes_sent(n);
         trace!(
             "written {} bytes to the network ({}/{})",
             n,
@@ -448,26 +798,144 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
                 RequestState::NotSerialized | RequestState::Sent(_) => {}
                 RequestState::Serialized(pair) => {
                     debug!("sent request with {:?}", pair);
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(parent: &req.span, container_msg_id = ?pair.container_msg_id, "sent request");
                     req.state = RequestState::Sent(pair.clone());
                 }
             }
         }
     }
 
+    /// Handle a quick acknowledgement, notifying the requests sent in the acknowledged packet.
+    fn on_quick_ack(&mut self, token: u32) {
+        let Some(pos) = self
+            .pending_quick_acks
+            .iter()
+            .position(|(t, _)| *t == token)
+        else {
+            info!("got quick ack {:x} but no such packet is saved", token);
+            return;
+        };
+
+        let (_, container_msg_id) = self.pending_quick_acks.remove(pos);
+        debug!("got quick ack for {:?}", container_msg_id);
+        for request in self.requests.iter_mut() {
+            match &request.state {
+                RequestState::Serialized(pair) | RequestState::Sent(pair)
+                    if pair.container_msg_id == container_msg_id =>
+                {
+                    if let Some(ack) = request.quick_ack.take() {
+                        let _ = ack.send(());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        self.forget_done_quick_acks();
+    }
+
+    /// Forget the quick acknowledgements expected for packets whose requests are all done.
+    fn forget_done_quick_acks(&mut self) {
+        let requests = &self.requests;
+        self.pending_quick_acks.retain(|(_, msg_id)| {
+            requests.iter().any(|r| match &r.state {
+                RequestState::Serialized(pair) | RequestState::Sent(pair) => {
+                    pair.container_msg_id == *msg_id
+                }
+                RequestState::NotSerialized => false,
+            })
+        });
+    }
+
     /// Handle a ping timeout, meaning we need to enqueue a new ping request.
     fn on_ping_timeout(&mut self) {
         let ping_id = generate_random_id();
         debug!("enqueueing keepalive ping {}", ping_id);
         drop(
-            self.enqueue_body(
-                tl::functions::PingDelayDisconnect {
-                    ping_id,
-                    disconnect_delay: NO_PING_DISCONNECT,
+            self.enqueue_body(serialize_request(&tl::functions::PingDelayDisconnect {
+                ping_id,
+                disconnect_delay: self.ping_disconnect_delay.as_secs() as i32,
+            })),
+        );
+        let now = Instant::now();
+        self.pending_ping = Some((ping_id, now));
+        self.next_ping = now + self.ping_interval;
+    }
+
+    /// Measure the round-trip time if the pong belongs to the last keepalive ping.
+    fn on_pong(&mut self, body: &[u8]) {
+        if let Ok(tl::enums::Pong::Pong(pong)) = tl::enums::Pong::from_bytes(body) {
+            match self.pending_ping {
+                Some((ping_id, sent)) if ping_id == pong.ping_id => {
+                    let rtt = sent.elapsed();
+                    debug!("got keepalive pong {} after {:?}", ping_id, rtt);
+                    self.last_rtt = Some(rtt);
+                    self.pending_ping = None;
                 }
-                .to_bytes(),
-            ),
+                _ => {}
+            }
+        }
+    }
+
+    /// Change every how often keepalive pings are sent, and after how long without receiving
+    /// any should the server close the connection.
+    ///
+    /// The `disconnect_delay` should be larger than the `interval`, or the server will close the
+    /// connection before the next ping is sent. The delay is sent with second precision.
+    pub fn set_ping_delays(&mut self, interval: Duration, disconnect_delay: Duration) {
+        self.ping_interval = interval;
+        self.ping_disconnect_delay = disconnect_delay;
+        self.next_ping = Instant::now() + interval;
+    }
+
+    /// The span in which the events of this connection are recorded.
+    ///
+    /// It has an empty `dc_id` field, which can be recorded by the owner of the sender.
+    #[cfg(feature = "tracing")]
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    /// Change the observer notified of the events happening in this sender.
+    ///
+    /// By default, a [`NoObserver`] is used, which ignores every event.
+    pub fn set_observer(&mut self, observer: &'static dyn SenderObserver) {
+        self.observer = observer;
+    }
+
+    /// Take the round-trip time measured with the last keepalive ping that was answered, if
+    /// there has been a new measurement since the previous call.
+    pub fn take_rtt(&mut self) -> Option<Duration> {
+        self.last_rtt.take()
+    }
+
+    /// Take over the requests still pending in `old`, along with the channel used to enqueue
+    /// them, so that this sender can replace it without failing the requests in flight.
+    ///
+    /// The requests are sent again through this sender. The [`Enqueuer`] of `old` keeps working,
+    /// while the one created along with this sender should no longer be used.
+    pub fn take_over<U: Transport, N: Mtp>(&mut self, mut old: Sender<U, N>) {
+        while let Ok(request) = self.request_rx.try_recv() {
+            self.requests.push(request);
+        }
+        while let Ok(request) = old.request_rx.try_recv() {
+            old.requests.push(request);
+        }
+
+        info!(
+            "taking over {} request(s) from the previous sender",
+            old.requests.len()
         );
-        self.next_ping = Instant::now() + PING_DELAY;
+        for request in old.requests.iter_mut() {
+            if !matches!(request.state, RequestState::NotSerialized) {
+                self.observer.on_request_retry();
+            }
+            request.state = RequestState::NotSerialized;
+        }
+        old.requests.append(&mut self.requests);
+        self.requests = old.requests;
+        self.request_rx = old.request_rx;
     }
 
     /// Handle errors that occured while performing I/O.
@@ -475,6 +943,8 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
         log::info!("handling error: {error}");
         self.transport.reset();
         self.mtp.reset();
+        self.pending_ping = None;
+        self.pending_quick_acks.clear();
         log::info!(
             "resetting sender state from read_buffer {}/{}, write_buffer {}/{}",
             self.read_tail,
@@ -483,23 +953,38 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
             self.write_buffer.len(),
         );
         self.read_tail = 0;
-        self.read_buffer.fill(0);
+        self.read_buffer.as_mut().fill(0);
         self.write_head = 0;
         self.write_buffer.clear();
 
         let error = match error {
             ReadError::Io(_)
+            | ReadError::Transport(transport::Error::TransportCode(TRANSPORT_FLOOD))
                 if matches!(
                     self.reconnection_policy.should_retry(0),
                     ControlFlow::Continue(_)
                 ) =>
             {
+                if matches!(error, ReadError::Transport(_)) {
+                    // Connecting again right away would only make the server more upset.
+                    if let ControlFlow::Continue(delay) = self.reconnection_policy.should_retry(1) {
+                        warn!(
+                            "too many connections, waiting {:?} before reconnecting",
+                            delay
+                        );
+                        sleep(delay).await;
+                    }
+                }
+
                 match self.try_connect().await {
                     Ok(_) => {
                         // Reconnect success means everything can be retried.
-                        self.requests
-                            .iter_mut()
-                            .for_each(|r| r.state = RequestState::NotSerialized);
+                        for request in self.requests.iter_mut() {
+                            if !matches!(request.state, RequestState::NotSerialized) {
+                                self.observer.on_request_retry();
+                            }
+                            request.state = RequestState::NotSerialized;
+                        }
 
                         // We'll return a TooLong update to signal to the client
                         // that it needs to call getDifference and query the server
@@ -509,6 +994,15 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
                     Err(e) => ReadError::from(e),
                 }
             }
+            // The server no longer knows the key, so the requests can only be sent again with a
+            // different one. They're kept so that a new sender can take over them.
+            ReadError::Transport(transport::Error::TransportCode(AUTH_KEY_NOT_FOUND)) => {
+                warn!("server does not know the auth key; keeping requests for a new sender");
+                for request in self.requests.iter_mut() {
+                    request.state = RequestState::NotSerialized;
+                }
+                return Err(error);
+            }
             e => e,
         };
 
@@ -537,6 +1031,8 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
                 Deserialization::RpcResult(result) => self.process_result(result),
                 Deserialization::RpcError(error) => self.process_error(error),
                 Deserialization::BadMessage(bad_msg) => self.process_bad_message(bad_msg),
+                Deserialization::UnackedAnswer(unacked) => self.process_unacked_answer(unacked),
+                Deserialization::MsgStateReport(report) => self.process_msg_state_report(report),
                 Deserialization::Failure(failure) => self.process_deserialize_error(failure),
             }
         }
@@ -594,6 +1090,11 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
                 tl::name_for_id(res_id),
                 result.msg_id
             );
+            #[cfg(feature = "tracing")]
+            tracing::debug!(parent: &req.span, result = tl::name_for_id(res_id), "got result");
+            if res_id == <tl::types::Pong as tl::Identifiable>::CONSTRUCTOR_ID {
+                self.on_pong(&x);
+            }
             drop(req.result.send(Ok(x)));
         } else {
             info!(
@@ -606,7 +1107,9 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
     fn process_error(&mut self, error: RpcResultError) {
         if let Some(req) = self.pop_request(error.msg_id) {
             debug!("got rpc error {:?}", error.error);
-            let x = req.body.as_slice();
+            #[cfg(feature = "tracing")]
+            tracing::debug!(parent: &req.span, error = ?error.error, "got rpc error");
+            let x = &req.body[..];
             drop(
                 req.result.send(Err(InvocationError::Rpc(
                     RpcError::from(error.error)
@@ -621,22 +1124,29 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
         }
     }
 
+    fn process_unacked_answer(&mut self, unacked: UnackedAnswer) {
+        let msg_id = unacked.msg_id.map(i64::from);
+        debug!(
+            "server reports answer {} to {:?} was never acknowledged",
+            unacked.answer_msg_id, msg_id
+        );
+        self.observer
+            .on_unacked_answer(msg_id, unacked.answer_msg_id);
+    }
+
+    fn process_msg_state_report(&mut self, report: MsgStateReport) {
+        self.observer
+            .on_msg_state(report.msg_id.into(), report.state);
+    }
+
     fn process_bad_message(&mut self, bad_msg: BadMessage) {
         for i in (0..self.requests.len()).rev() {
             match &self.requests[i].state {
-                RequestState::Serialized(pair)
-                    if pair.msg_id == bad_msg.msg_id || pair.container_msg_id == bad_msg.msg_id =>
-                {
-                    panic!(
-                        "bad msg for unsent request {:?}: {}",
-                        bad_msg.msg_id,
-                        bad_msg.description()
-                    );
-                }
-                RequestState::Sent(pair)
+                // The notification may arrive before the write of the container is done. Since
+                // nothing new is serialized until then, the request can be sent again all the same.
+                RequestState::Serialized(pair) | RequestState::Sent(pair)
                     if pair.msg_id == bad_msg.msg_id || pair.container_msg_id == bad_msg.msg_id =>
                 {
-                    // TODO add a test to make sure we resend the request
                     if bad_msg.retryable() {
                         info!(
                             "{}; re-sending request {:?}",
@@ -646,6 +1156,12 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
 
                         // TODO check if actually retryable first!
                         self.requests[i].state = RequestState::NotSerialized;
+                        self.observer.on_request_retry();
+                        #[cfg(feature = "tracing")]
+                        tracing::debug!(parent: &self.requests[i].span, "retrying after bad message");
+                        if let Some(handle) = self.requests[i].handle {
+                            self.reset_dependents(handle);
+                        }
                     } else {
                         if bad_msg.fatal() {
                             error!(
@@ -669,6 +1185,25 @@ impl<T: Transport, M: Mtp> Sender<T, M> {
         }
     }
 
+    /// Send again every request that was serialized to run after the request identified by
+    /// `handle`, since the message ID they refer to in `invokeAfterMsg` is no longer valid.
+    fn reset_dependents(&mut self, handle: RequestHandle) {
+        for i in 0..self.requests.len() {
+            if self.requests[i].after != Some(handle)
+                || matches!(self.requests[i].state, RequestState::NotSerialized)
+            {
+                continue;
+            }
+
+            info!("re-sending request depending on {:?}", handle);
+            self.requests[i].state = RequestState::NotSerialized;
+            self.observer.on_request_retry();
+            if let Some(dependent) = self.requests[i].handle {
+                self.reset_dependents(dependent);
+            }
+        }
+    }
+
     fn process_deserialize_error(&mut self, failure: DeserializationFailure) {
         if let Some(req) = self.pop_request(failure.msg_id) {
             debug!("got deserialization failure {:?}", failure.error);
@@ -705,6 +1240,36 @@ impl<T: Transport> Sender<T, mtp::Encrypted> {
     pub fn auth_key(&self) -> [u8; 256] {
         self.mtp.auth_key()
     }
+
+    /// When the temporary authorization key used by this sender expires, if it was connected
+    /// with [`connect_with_temp_auth`].
+    ///
+    /// The server stops accepting the key after this, so a new sender should take over before.
+    pub fn temp_auth_key_expiry(&self) -> Option<Instant> {
+        self.temp_auth_key_expiry
+    }
+
+    /// Change the size from which requests are compressed before being sent.
+    ///
+    /// If `None`, requests are never compressed.
+    pub fn set_compression_threshold(&mut self, threshold: Option<usize>) {
+        self.mtp.set_compression_threshold(threshold);
+    }
+
+    /// The difference between the server's clock and ours, in seconds.
+    ///
+    /// It is corrected automatically whenever the server reports that the clock is wrong.
+    pub fn time_offset(&self) -> i32 {
+        self.mtp.time_offset()
+    }
+
+    /// Override the difference between the server's clock and ours, in seconds.
+    ///
+    /// Should be used before any request is sent, with the offset known from a previous
+    /// connection, so that the first requests are not rejected.
+    pub fn set_time_offset(&mut self, offset: i32) {
+        self.mtp.set_time_offset(offset);
+    }
 }
 
 pub async fn connect<T: Transport>(
@@ -717,15 +1282,80 @@ pub async fn connect<T: Transport>(
 }
 
 pub async fn generate_auth_key<T: Transport>(
+    sender: Sender<T, mtp::Plain>,
+    enqueuer: Enqueuer,
+) -> Result<(Sender<T, mtp::Encrypted>, Enqueuer), AuthorizationError> {
+    do_generate_auth_key(sender, enqueuer, None).await
+}
+
+/// Connect to the server and generate a new temporary authorization key for [Perfect Forward
+/// Secrecy], bound to the permanent `auth_key` of the datacenter `dc_id`.
+///
+/// The temporary key will expire after `expires_in`, after which a new connection will have to
+/// be made (see [`Sender::temp_auth_key_expiry`] and [`Sender::take_over`]). The permanent key is
+/// never used to encrypt messages sent through this connection.
+///
+/// [Perfect Forward Secrecy]: https://core.telegram.org/api/pfs
+pub async fn connect_with_temp_auth<T: Transport>(
+    transport: T,
+    addr: ServerAddr,
+    auth_key: [u8; 256],
+    dc_id: i32,
+    expires_in: Duration,
+    rc_policy: &'static dyn ReconnectionPolicy,
+) -> Result<(Sender<T, mtp::Encrypted>, Enqueuer), AuthorizationError> {
+    let expires_in = expires_in.as_secs().min(i32::MAX as u64) as i32;
+    let (sender, enqueuer) = Sender::connect(transport, mtp::Plain::new(), addr, rc_policy).await?;
+    let (mut sender, enqueuer) =
+        do_generate_auth_key(sender, enqueuer, Some((dc_id, expires_in))).await?;
+
+    debug!("binding temporary auth key to the permanent one");
+    let now = match SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    };
+    let expires_at = (now + sender.mtp.time_offset() as i64 + expires_in as i64)
+        .clamp(0, i32::MAX as i64) as i32;
+    let msg_id = sender.mtp.reserve_msg_id();
+    let request = authentication::bind_temp_auth_key(
+        &auth_key,
+        &sender.mtp.auth_key(),
+        sender.mtp.client_id(),
+        msg_id,
+        expires_at,
+    );
+
+    // The request with the reserved message ID must be the first one to be serialized.
+    let (tx, rx) = oneshot::channel();
+    sender
+        .requests
+        .insert(0, Request::new(serialize_request(&request), tx));
+    let response = sender.step_until_receive(rx).await?;
+    if bool::from_bytes(&response).map_err(InvocationError::from)? {
+        info!("temporary authorization key bound successfully");
+        sender.temp_auth_key_expiry = Some(Instant::now() + Duration::from_secs(expires_in as u64));
+        Ok((sender, enqueuer))
+    } else {
+        Err(authentication::Error::TempKeyNotBound.into())
+    }
+}
+
+async fn do_generate_auth_key<T: Transport>(
     mut sender: Sender<T, mtp::Plain>,
     enqueuer: Enqueuer,
+    temp: Option<(i32, i32)>,
 ) -> Result<(Sender<T, mtp::Encrypted>, Enqueuer), AuthorizationError> {
     info!("generating new authorization key...");
     let (request, data) = authentication::step1()?;
     debug!("gen auth key: sending step 1");
     let response = sender.send(request).await?;
     debug!("gen auth key: starting step 2");
-    let (request, data) = authentication::step2(data, &response)?;
+    let (request, data) = match temp {
+        None => authentication::step2(data, &response)?,
+        Some((dc_id, expires_in)) => {
+            authentication::step2_temp(data, &response, dc_id, expires_in)?
+        }
+    };
     debug!("gen auth key: sending step 2");
     let response = sender.send(request).await?;
     debug!("gen auth key: starting step 3");
@@ -750,13 +1380,22 @@ pub async fn generate_auth_key<T: Transport>(
                 .finish(auth_key),
             requests: sender.requests,
             request_rx: sender.request_rx,
-            next_ping: Instant::now() + PING_DELAY,
+            next_ping: Instant::now() + sender.ping_interval,
+            ping_interval: sender.ping_interval,
+            ping_disconnect_delay: sender.ping_disconnect_delay,
+            pending_ping: None,
+            last_rtt: sender.last_rtt,
+            pending_quick_acks: Vec::new(),
+            temp_auth_key_expiry: None,
             read_buffer: sender.read_buffer,
             read_tail: sender.read_tail,
             write_buffer: sender.write_buffer,
             write_head: sender.write_head,
             addr: sender.addr,
             reconnection_policy: sender.reconnection_policy,
+            observer: sender.observer,
+            #[cfg(feature = "tracing")]
+            span: sender.span,
         },
         enqueuer,
     ))
@@ -776,3 +1415,228 @@ pub async fn connect_with_auth<T: Transport>(
     )
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::Future;
+    use tokio::net::TcpListener;
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    async fn local_sender(
+        listener: &TcpListener,
+    ) -> (Sender<transport::Full, mtp::Plain>, Enqueuer) {
+        let addr = ServerAddr::Tcp {
+            address: listener.local_addr().unwrap(),
+        };
+        Sender::connect(
+            transport::Full::new(),
+            mtp::Plain::new(),
+            addr,
+            &NoReconnect,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn request_bodies_are_serialized_into_pooled_buffers() {
+        let request = tl::functions::Ping { ping_id: 1 };
+        let body = serialize_request(&request);
+        assert_eq!(&body[..], &request.to_bytes()[..]);
+    }
+
+    #[test]
+    fn unacked_answers_are_reported_to_the_observer() {
+        struct Recorder(std::sync::Mutex<Vec<(Option<i64>, i64)>>);
+
+        impl SenderObserver for Recorder {
+            fn on_unacked_answer(&self, msg_id: Option<i64>, answer_msg_id: i64) {
+                self.0.lock().unwrap().push((msg_id, answer_msg_id));
+            }
+        }
+
+        static RECORDER: Recorder = Recorder(std::sync::Mutex::new(Vec::new()));
+        block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let (mut sender, _) = local_sender(&listener).await;
+            sender.set_observer(&RECORDER);
+
+            let mut updates = Vec::new();
+            sender.process_mtp_buffer(
+                vec![Deserialization::UnackedAnswer(UnackedAnswer {
+                    msg_id: None,
+                    answer_msg_id: 7,
+                })],
+                &mut updates,
+            );
+            assert!(updates.is_empty());
+        });
+        assert_eq!(*RECORDER.0.lock().unwrap(), [(None, 7)]);
+    }
+
+    #[test]
+    fn msg_states_are_reported_to_the_observer() {
+        struct Recorder(std::sync::Mutex<Vec<(i64, u8)>>);
+
+        impl SenderObserver for Recorder {
+            fn on_msg_state(&self, msg_id: i64, state: mtp::MsgState) {
+                self.0.lock().unwrap().push((msg_id, state.raw()));
+            }
+        }
+
+        static RECORDER: Recorder = Recorder(std::sync::Mutex::new(Vec::new()));
+        let msg_id = block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let (mut sender, _) = local_sender(&listener).await;
+            sender.set_observer(&RECORDER);
+
+            let msg_id = sender
+                .mtp
+                .push(
+                    &mut sender.write_buffer,
+                    &tl::functions::Ping { ping_id: 0 }.to_bytes(),
+                )
+                .unwrap();
+            let mut updates = Vec::new();
+            sender.process_mtp_buffer(
+                vec![Deserialization::MsgStateReport(MsgStateReport {
+                    msg_id,
+                    state: mtp::MsgState::new(2),
+                })],
+                &mut updates,
+            );
+            assert!(updates.is_empty());
+            i64::from(msg_id)
+        });
+        assert_eq!(*RECORDER.0.lock().unwrap(), [(msg_id, 2)]);
+    }
+
+    /// Enqueue two requests, the second to run after the first, and place the dependent first, as
+    /// `swap_remove` may leave it.
+    fn enqueue_dependent_first(
+        sender: &mut Sender<transport::Full, mtp::Plain>,
+        enqueuer: &Enqueuer,
+    ) {
+        let (first, _) = enqueuer.enqueue_after(&tl::functions::Ping { ping_id: 1 }, None);
+        enqueuer.enqueue_after(&tl::functions::Ping { ping_id: 2 }, Some(first));
+        sender.requests.push(sender.request_rx.try_recv().unwrap());
+        sender.requests.push(sender.request_rx.try_recv().unwrap());
+        sender.requests.swap(0, 1);
+    }
+
+    /// Serialize one plain message at a time, as if each previous write had completed.
+    fn fill_write(sender: &mut Sender<transport::Full, mtp::Plain>) {
+        sender.write_buffer.clear();
+        sender.try_fill_write();
+    }
+
+    fn serialized(sender: &Sender<transport::Full, mtp::Plain>) -> [bool; 2] {
+        [0, 1].map(|i| matches!(sender.requests[i].state, RequestState::Serialized(_)))
+    }
+
+    #[test]
+    fn dependents_before_their_dependency_are_sent() {
+        block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let (mut sender, enqueuer) = local_sender(&listener).await;
+            enqueue_dependent_first(&mut sender, &enqueuer);
+
+            fill_write(&mut sender);
+            assert_eq!(serialized(&sender), [false, true]);
+            fill_write(&mut sender);
+            assert_eq!(serialized(&sender), [true, true]);
+        });
+    }
+
+    #[test]
+    fn dependents_are_sent_again_after_bad_messages() {
+        block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let (mut sender, enqueuer) = local_sender(&listener).await;
+            enqueue_dependent_first(&mut sender, &enqueuer);
+            fill_write(&mut sender);
+            fill_write(&mut sender);
+
+            let msg_id = match &sender.requests[1].state {
+                RequestState::Serialized(pair) => pair.msg_id,
+                _ => unreachable!(),
+            };
+            let mut updates = Vec::new();
+            sender.process_mtp_buffer(
+                vec![Deserialization::BadMessage(BadMessage { msg_id, code: 16 })],
+                &mut updates,
+            );
+            assert_eq!(serialized(&sender), [false, false]);
+
+            fill_write(&mut sender);
+            assert_eq!(serialized(&sender), [false, true]);
+            fill_write(&mut sender);
+            assert_eq!(serialized(&sender), [true, true]);
+        });
+    }
+
+    #[test]
+    fn dependents_are_sent_again_after_reconnecting() {
+        static RECONNECT: FixedReconnect = FixedReconnect {
+            attempts: 1,
+            delay: Duration::ZERO,
+        };
+
+        block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let (mut sender, enqueuer) = local_sender(&listener).await;
+            sender.reconnection_policy = &RECONNECT;
+            enqueue_dependent_first(&mut sender, &enqueuer);
+            fill_write(&mut sender);
+            fill_write(&mut sender);
+
+            let error = io::Error::new(io::ErrorKind::ConnectionReset, "reset");
+            assert!(sender.on_error(ReadError::Io(error)).await.is_ok());
+            assert_eq!(serialized(&sender), [false, false]);
+
+            fill_write(&mut sender);
+            assert_eq!(serialized(&sender), [false, true]);
+            fill_write(&mut sender);
+            assert_eq!(serialized(&sender), [true, true]);
+        });
+    }
+
+    #[test]
+    fn read_buffers_are_full_size() {
+        let mut buffer = read_buffer();
+        assert_eq!(buffer.len(), MAXIMUM_DATA);
+        buffer.as_mut().fill(1);
+        drop(buffer);
+        assert!(read_buffer().as_ref().iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn take_over_keeps_pending_requests() {
+        block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let (mut old, old_enqueuer) = local_sender(&listener).await;
+            let (mut new, _) = local_sender(&listener).await;
+
+            let mut first = old_enqueuer.enqueue(&tl::functions::Ping { ping_id: 1 });
+            old.requests.push(old.request_rx.try_recv().unwrap());
+            let mut second = old_enqueuer.enqueue(&tl::functions::Ping { ping_id: 2 });
+
+            new.take_over(old);
+            assert_eq!(new.requests.len(), 2);
+            assert!(matches!(first.try_recv(), Err(TryRecvError::Empty)));
+            assert!(matches!(second.try_recv(), Err(TryRecvError::Empty)));
+
+            // Requests enqueued through the old sender now reach the new one.
+            let _third = old_enqueuer.enqueue(&tl::functions::Ping { ping_id: 3 });
+            assert!(new.request_rx.try_recv().is_ok());
+        });
+    }
+}
@@ -0,0 +1,396 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use crate::utils::sleep;
+use crate::{
+    AuthorizationError, InvocationError, ReadError, ServerAddr, AUTH_KEY_NOT_FOUND, TRANSPORT_FLOOD,
+};
+use grammers_mtproto::transport::{self, Transport};
+use log::warn;
+use std::future::Future;
+use std::time::Duration;
+
+/// A transport chosen at runtime, such as the ones created by [`TransportKind`].
+pub type DynTransport = Box<dyn Transport + Send>;
+
+/// Port used by the obfuscated transport, which looks like TLS traffic to most middleboxes.
+const OBFUSCATED_PORT: u16 = 443;
+
+/// Port used by the HTTP transport.
+const HTTP_PORT: u16 = 80;
+
+/// How many times to connect again with the same transport after the server reports a flood.
+const TRANSPORT_FLOOD_RETRIES: u32 = 3;
+
+/// How long to wait before the first retry after a flood. It doubles on every retry.
+const TRANSPORT_FLOOD_DELAY: Duration = Duration::from_secs(1);
+
+/// The transports which can be negotiated with [`connect_with_fallback`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransportKind {
+    /// The [`transport::Full`] transport.
+    Full,
+    /// The [`transport::Intermediate`] transport.
+    Intermediate,
+    /// The [`transport::Abridged`] transport.
+    Abridged,
+    /// The [`transport::Obfuscated`] transport wrapping the intermediate one, on port 443.
+    Obfuscated,
+    /// The [`transport::Http`] transport, on port 80.
+    Http,
+}
+
+impl TransportKind {
+    /// The order in which transports should be tried when the handshake fails, from the
+    /// lightest one to the one most likely to get through restrictive networks.
+    pub const FALLBACK_ORDER: [TransportKind; 4] = [
+        TransportKind::Intermediate,
+        TransportKind::Abridged,
+        TransportKind::Obfuscated,
+        TransportKind::Http,
+    ];
+
+    /// Stable identifier of the transport, suitable to be persisted.
+    pub fn id(self) -> i32 {
+        match self {
+            TransportKind::Full => 0,
+            TransportKind::Intermediate => 1,
+            TransportKind::Abridged => 2,
+            TransportKind::Obfuscated => 3,
+            TransportKind::Http => 4,
+        }
+    }
+
+    /// Reverse of [`TransportKind::id`]. Returns `None` if the identifier is unknown.
+    pub fn from_id(id: i32) -> Option<Self> {
+        Some(match id {
+            0 => TransportKind::Full,
+            1 => TransportKind::Intermediate,
+            2 => TransportKind::Abridged,
+            3 => TransportKind::Obfuscated,
+            4 => TransportKind::Http,
+            _ => return None,
+        })
+    }
+
+    /// The address to use in order to reach `addr` with this transport.
+    ///
+    /// Some transports are meant to be used on a specific port, so the port of `addr` is
+    /// replaced with it if needed.
+    pub fn server_addr(self, addr: &ServerAddr) -> ServerAddr {
+        let port = match self {
+            TransportKind::Obfuscated => OBFUSCATED_PORT,
+            TransportKind::Http => HTTP_PORT,
+            TransportKind::Full | TransportKind::Intermediate | TransportKind::Abridged => {
+                return addr.clone()
+            }
+        };
+
+        let mut addr = addr.clone();
+        match &mut addr {
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            ServerAddr::Tcp { address } => address.set_port(port),
+            #[cfg(all(
+                not(all(target_arch = "wasm32", target_os = "unknown")),
+                feature = "proxy"
+            ))]
+            ServerAddr::Proxied { address, .. } => address.set_port(port),
+            #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+            ServerAddr::Ws { .. } => {
+                let _ = port;
+            }
+        }
+        addr
+    }
+
+    /// Create a new instance of this transport to communicate with `addr`.
+    pub fn new_transport(self, addr: &ServerAddr) -> DynTransport {
+        match self {
+            TransportKind::Full => Box::new(transport::Full::new()),
+            TransportKind::Intermediate => Box::new(transport::Intermediate::new()),
+            TransportKind::Abridged => Box::new(transport::Abridged::new()),
+            TransportKind::Obfuscated => {
+                Box::new(transport::Obfuscated::new(transport::Intermediate::new()))
+            }
+            TransportKind::Http => {
+                let host = match addr {
+                    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+                    ServerAddr::Tcp { address } => address.ip().to_string(),
+                    #[cfg(all(
+                        not(all(target_arch = "wasm32", target_os = "unknown")),
+                        feature = "proxy"
+                    ))]
+                    ServerAddr::Proxied { address, .. } => address.ip().to_string(),
+                    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+                    ServerAddr::Ws { address } => address.clone(),
+                };
+                Box::new(transport::Http::new(&host))
+            }
+        }
+    }
+}
+
+/// Whether the `error` that occured while connecting could be caused by the transport used,
+/// meaning that another one may work.
+///
+/// This is the case when the connection is reset or the server replies with garbage, which
+/// is what happens when some middlebox interferes with the traffic.
+///
+/// An unknown auth key is a problem with the key and not the transport, so it is not a
+/// transport failure either. Floods (see [`is_transport_flood`]) are not transport failures,
+/// since they are applied to the address connecting, no matter which transport it uses.
+pub fn is_transport_failure(error: &AuthorizationError) -> bool {
+    match error {
+        AuthorizationError::Invoke(InvocationError::Read(ReadError::Io(_))) => true,
+        AuthorizationError::Invoke(InvocationError::Read(ReadError::Transport(
+            transport::Error::TransportCode(code),
+        ))) => *code != AUTH_KEY_NOT_FOUND && *code != TRANSPORT_FLOOD,
+        AuthorizationError::Invoke(InvocationError::Read(ReadError::Transport(_))) => true,
+        _ => false,
+    }
+}
+
+/// Whether connecting failed because too many connections were made from the same address,
+/// in which case the server closes the connection with the `-429` transport error code.
+pub fn is_transport_flood(error: &AuthorizationError) -> bool {
+    matches!(
+        error,
+        AuthorizationError::Invoke(InvocationError::Read(ReadError::Transport(
+            transport::Error::TransportCode(TRANSPORT_FLOOD),
+        )))
+    )
+}
+
+/// Try to `connect` to `addr` with each of the transport `kinds`, in order, until one works.
+///
+/// The `connect` function receives the kind of transport to use, along with the address to use
+/// it on (see [`TransportKind::server_addr`]).
+///
+/// The next transport is only tried if connecting failed because of the transport (see
+/// [`is_transport_failure`]). If the server reports a flood instead (see [`is_transport_flood`]),
+/// the same transport is tried again a few times after waiting, with an increasing delay. Each
+/// transport gets its own retries.
+/// Other errors, and the error of the last transport, are returned immediately.
+///
+/// On success, the kind of the transport that worked is returned too, so that it can be
+/// remembered and tried first next time.
+///
+/// Panics if `kinds` is empty.
+pub async fn connect_with_fallback<R, F, Fut>(
+    kinds: &[TransportKind],
+    addr: &ServerAddr,
+    mut connect: F,
+) -> Result<(R, TransportKind), AuthorizationError>
+where
+    F: FnMut(TransportKind, ServerAddr) -> Fut,
+    Fut: Future<Output = Result<R, AuthorizationError>>,
+{
+    assert!(!kinds.is_empty(), "at least one transport to try");
+
+    let mut kinds = kinds.iter().copied().peekable();
+    let mut kind = kinds.next().unwrap();
+    let mut flood_retries = 0;
+    loop {
+        let kind_addr = kind.server_addr(addr);
+        match connect(kind, kind_addr.clone()).await {
+            Ok(result) => break Ok((result, kind)),
+            Err(e) if flood_retries < TRANSPORT_FLOOD_RETRIES && is_transport_flood(&e) => {
+                let delay = TRANSPORT_FLOOD_DELAY * 2u32.pow(flood_retries);
+                flood_retries += 1;
+                warn!(
+                    "flood connecting to {:?} using the {:?} transport, retrying in {:?}",
+                    kind_addr, kind, delay
+                );
+                sleep(delay).await;
+            }
+            Err(e) if kinds.peek().is_some() && is_transport_failure(&e) => {
+                warn!(
+                    "failed to connect to {:?} using the {:?} transport, trying the next one: {}",
+                    kind_addr, kind, e
+                );
+                kind = kinds.next().unwrap();
+                flood_retries = 0;
+            }
+            Err(e) => break Err(e),
+        }
+    }
+}
+
+#[cfg(all(test, not(all(target_arch = "wasm32", target_os = "unknown"))))]
+mod tests {
+    use super::*;
+    use std::io;
+
+    fn tcp_addr() -> ServerAddr {
+        ServerAddr::Tcp {
+            address: "149.154.167.51:5222".parse().unwrap(),
+        }
+    }
+
+    fn reset() -> AuthorizationError {
+        AuthorizationError::Invoke(InvocationError::Read(ReadError::Io(io::Error::new(
+            io::ErrorKind::ConnectionReset,
+            "reset",
+        ))))
+    }
+
+    fn flood() -> AuthorizationError {
+        AuthorizationError::Invoke(InvocationError::Read(ReadError::Transport(
+            transport::Error::TransportCode(TRANSPORT_FLOOD),
+        )))
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .start_paused(true)
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn id_roundtrip() {
+        for kind in [TransportKind::Full]
+            .into_iter()
+            .chain(TransportKind::FALLBACK_ORDER)
+        {
+            assert_eq!(TransportKind::from_id(kind.id()), Some(kind));
+        }
+        assert_eq!(TransportKind::from_id(-1), None);
+    }
+
+    #[test]
+    fn server_addr_port() {
+        let port = |kind: TransportKind| match kind.server_addr(&tcp_addr()) {
+            ServerAddr::Tcp { address } => address.port(),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!(),
+        };
+        assert_eq!(port(TransportKind::Intermediate), 5222);
+        assert_eq!(port(TransportKind::Abridged), 5222);
+        assert_eq!(port(TransportKind::Obfuscated), 443);
+        assert_eq!(port(TransportKind::Http), 80);
+    }
+
+    #[test]
+    fn fallback_on_transport_failure() {
+        let mut tried = Vec::new();
+        let result = block_on(connect_with_fallback(
+            &TransportKind::FALLBACK_ORDER,
+            &tcp_addr(),
+            |kind, _| {
+                tried.push(kind);
+                async move {
+                    if kind == TransportKind::Obfuscated {
+                        Ok(())
+                    } else {
+                        Err(reset())
+                    }
+                }
+            },
+        ));
+
+        assert!(matches!(result, Ok(((), TransportKind::Obfuscated))));
+        assert_eq!(
+            tried,
+            vec![
+                TransportKind::Intermediate,
+                TransportKind::Abridged,
+                TransportKind::Obfuscated
+            ]
+        );
+    }
+
+    #[test]
+    fn no_fallback_on_other_errors() {
+        let mut tried = 0;
+        let result = block_on(connect_with_fallback(
+            &TransportKind::FALLBACK_ORDER,
+            &tcp_addr(),
+            |_, _| {
+                tried += 1;
+                async { Err::<(), _>(AuthorizationError::Invoke(InvocationError::Dropped)) }
+            },
+        ));
+
+        assert!(result.is_err());
+        assert_eq!(tried, 1);
+    }
+
+    #[test]
+    fn flood_retries_same_transport() {
+        let mut tried = Vec::new();
+        let result = block_on(connect_with_fallback(
+            &TransportKind::FALLBACK_ORDER,
+            &tcp_addr(),
+            |kind, _| {
+                tried.push(kind);
+                let attempt = tried.len();
+                async move {
+                    if attempt < 3 {
+                        Err(flood())
+                    } else {
+                        Ok(())
+                    }
+                }
+            },
+        ));
+
+        assert!(matches!(result, Ok(((), TransportKind::Intermediate))));
+        assert_eq!(tried, vec![TransportKind::Intermediate; 3]);
+    }
+
+    #[test]
+    fn flood_gives_up_without_fallback() {
+        let mut tried = Vec::new();
+        let result = block_on(connect_with_fallback(
+            &TransportKind::FALLBACK_ORDER,
+            &tcp_addr(),
+            |kind, _| {
+                tried.push(kind);
+                async { Err::<(), _>(flood()) }
+            },
+        ));
+
+        assert!(matches!(result, Err(ref e) if is_transport_flood(e)));
+        assert_eq!(
+            tried,
+            vec![TransportKind::Intermediate; TRANSPORT_FLOOD_RETRIES as usize + 1]
+        );
+    }
+
+    #[test]
+    fn flood_retries_reset_on_fallback() {
+        let mut tried = Vec::new();
+        let floods = TRANSPORT_FLOOD_RETRIES as usize;
+        let result = block_on(connect_with_fallback(
+            &TransportKind::FALLBACK_ORDER,
+            &tcp_addr(),
+            |kind, _| {
+                tried.push(kind);
+                let attempt = tried.len();
+                async move {
+                    if attempt <= floods {
+                        Err(flood())
+                    } else if kind == TransportKind::Intermediate {
+                        Err(reset())
+                    } else if attempt <= 2 * floods + 1 {
+                        Err(flood())
+                    } else {
+                        Ok(())
+                    }
+                }
+            },
+        ));
+
+        assert!(matches!(result, Ok(((), TransportKind::Abridged))));
+        let mut expected = vec![TransportKind::Intermediate; floods + 1];
+        expected.extend(vec![TransportKind::Abridged; floods + 1]);
+        assert_eq!(tried, expected);
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use grammers_mtproto::mtp::MsgState;
+
+/// an **Observer** of the events happening inside a `Sender`, useful to collect metrics.
+///
+/// by implementing this trait and passing it to the `InitParams` inside the `Client` you can
+/// export statistics about the connection, for example to Prometheus.
+///
+/// every method does nothing by default, so only the events you care about need to be
+/// implemented. note that they are called from within the network loop, so they should return
+/// quickly (incrementing some atomic counters is fine, performing IO is not).
+#[allow(unused_variables)]
+pub trait SenderObserver: Send + Sync {
+    /// called after `bytes` have been written to the network.
+    fn on_bytes_sent(&self, bytes: usize) {}
+
+    /// called after `bytes` have been read from the network.
+    fn on_bytes_received(&self, bytes: usize) {}
+
+    /// called when the amount of requests which are waiting for a response changes to `count`.
+    fn on_requests_in_flight(&self, count: usize) {}
+
+    /// called when a request that was already sent has to be sent again, for example after a
+    /// bad message notification or a reconnection.
+    fn on_request_retry(&self) {}
+
+    /// called after reconnecting successfully, with the amount of `attempts` that failed before.
+    fn on_reconnect(&self, attempts: usize) {}
+
+    /// called when `messages` are packed inside a single container to be sent together.
+    fn on_container_sent(&self, messages: usize) {}
+
+    /// called when the server reports that the message `answer_msg_id` it sent was never
+    /// acknowledged, which can mean it never arrived. `msg_id` is the request it answered, if any.
+    ///
+    /// the message is acknowledged right after, but what it contained may have been lost.
+    fn on_unacked_answer(&self, msg_id: Option<i64>, answer_msg_id: i64) {}
+
+    /// called when the server voluntarily reports the `state` of the message `msg_id` sent to it,
+    /// for example to tell that it was never received.
+    fn on_msg_state(&self, msg_id: i64, state: MsgState) {}
+}
+
+/// the default implementation of the **SenderObserver**, which ignores every event.
+pub struct NoObserver;
+
+impl SenderObserver for NoObserver {}
+
+/// a **SenderObserver** which records every event through the [`metrics`] facade, so that they
+/// reach whichever recorder is installed (for example, a Prometheus exporter).
+///
+/// the following metrics are recorded:
+///
+/// * `grammers_bytes_sent_total` and `grammers_bytes_received_total` counters.
+/// * `grammers_requests_in_flight` gauge.
+/// * `grammers_request_retries_total`, `grammers_reconnects_total` and
+///   `grammers_unacked_answers_total` counters.
+/// * `grammers_reconnect_attempts` and `grammers_container_messages` histograms.
+/// * `grammers_msg_states_total` counter, labeled by `state` (`unknown`, `not_received` or
+///   `received`).
+#[cfg(feature = "metrics")]
+pub struct MetricsObserver;
+
+#[cfg(feature = "metrics")]
+impl SenderObserver for MetricsObserver {
+    fn on_bytes_sent(&self, bytes: usize) {
+        metrics::counter!("grammers_bytes_sent_total").increment(bytes as u64);
+    }
+
+    fn on_bytes_received(&self, bytes: usize) {
+        metrics::counter!("grammers_bytes_received_total").increment(bytes as u64);
+    }
+
+    fn on_requests_in_flight(&self, count: usize) {
+        metrics::gauge!("grammers_requests_in_flight").set(count as f64);
+    }
+
+    fn on_request_retry(&self) {
+        metrics::counter!("grammers_request_retries_total").increment(1);
+    }
+
+    fn on_reconnect(&self, attempts: usize) {
+        metrics::counter!("grammers_reconnects_total").increment(1);
+        metrics::histogram!("grammers_reconnect_attempts").record(attempts as f64);
+    }
+
+    fn on_container_sent(&self, messages: usize) {
+        metrics::histogram!("grammers_container_messages").record(messages as f64);
+    }
+
+    fn on_unacked_answer(&self, _msg_id: Option<i64>, _answer_msg_id: i64) {
+        metrics::counter!("grammers_unacked_answers_total").increment(1);
+    }
+
+    fn on_msg_state(&self, _msg_id: i64, state: MsgState) {
+        let state = if state.unknown() {
+            "unknown"
+        } else if state.not_received() {
+            "not_received"
+        } else {
+            "received"
+        };
+        metrics::counter!("grammers_msg_states_total", "state" => state).increment(1);
+    }
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use metrics::{
+        Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata,
+        Recorder, SharedString, Unit,
+    };
+    use std::sync::{Arc, Mutex};
+
+    type Log = Arc<Mutex<Vec<(String, f64)>>>;
+
+    /// Records the name of every metric along with the value it was updated with.
+    struct Handle(String, Log);
+
+    impl CounterFn for Handle {
+        fn increment(&self, value: u64) {
+            self.1.lock().unwrap().push((self.0.clone(), value as f64));
+        }
+
+        fn absolute(&self, value: u64) {
+            CounterFn::increment(self, value);
+        }
+    }
+
+    impl GaugeFn for Handle {
+        fn increment(&self, value: f64) {
+            self.1.lock().unwrap().push((self.0.clone(), value));
+        }
+
+        fn decrement(&self, value: f64) {
+            GaugeFn::increment(self, -value);
+        }
+
+        fn set(&self, value: f64) {
+            GaugeFn::increment(self, value);
+        }
+    }
+
+    impl HistogramFn for Handle {
+        fn record(&self, value: f64) {
+            self.1.lock().unwrap().push((self.0.clone(), value));
+        }
+    }
+
+    struct LogRecorder(Log);
+
+    impl LogRecorder {
+        fn handle(&self, key: &Key) -> Arc<Handle> {
+            let mut name = key.name().to_string();
+            for label in key.labels() {
+                name.push_str(&format!("{{{}={}}}", label.key(), label.value()));
+            }
+            Arc::new(Handle(name, self.0.clone()))
+        }
+    }
+
+    impl Recorder for LogRecorder {
+        fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+        fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+            Counter::from_arc(self.handle(key))
+        }
+
+        fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+            Gauge::from_arc(self.handle(key))
+        }
+
+        fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+            Histogram::from_arc(self.handle(key))
+        }
+    }
+
+    #[test]
+    fn events_are_recorded_as_metrics() {
+        let recorder = LogRecorder(Log::default());
+        metrics::with_local_recorder(&recorder, || {
+            let observer = MetricsObserver;
+            observer.on_bytes_sent(10);
+            observer.on_bytes_received(20);
+            observer.on_requests_in_flight(3);
+            observer.on_request_retry();
+            observer.on_reconnect(2);
+            observer.on_container_sent(4);
+            observer.on_unacked_answer(None, 7);
+            observer.on_msg_state(7, MsgState::new(2));
+        });
+
+        let log = recorder.0.lock().unwrap();
+        let log = log
+            .iter()
+            .map(|(n, v)| (n.as_str(), *v))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            log,
+            [
+                ("grammers_bytes_sent_total", 10.0),
+                ("grammers_bytes_received_total", 20.0),
+                ("grammers_requests_in_flight", 3.0),
+                ("grammers_request_retries_total", 1.0),
+                ("grammers_reconnects_total", 1.0),
+                ("grammers_reconnect_attempts", 2.0),
+                ("grammers_container_messages", 4.0),
+                ("grammers_unacked_answers_total", 1.0),
+                ("grammers_msg_states_total{state=not_received}", 1.0),
+            ]
+        );
+    }
+}
@@ -7,14 +7,16 @@
 // except according to those terms.
 use std::ops::ControlFlow;
 use std::time::Duration;
+use web_time::SystemTime;
 
 /// a simple **Reconnection** Handler.
 ///
 /// with implementing this trait and passing it to the `InitParams` inside the `Client` you can have your own
 /// custom implementations for handling connection failures.
 ///
-/// the default implementation is **NoReconnect** which does not handle anything! there is also a `FixedReconnect`
-/// which sets a fixed attempt count and a duration
+/// the **NoReconnect** implementation does not handle anything! there is also a `FixedReconnect`
+/// which sets a fixed attempt count and a duration, and an `ExponentialBackoff` which waits longer
+/// after every failed attempt
 ///
 /// note that this will return a `ControlFlow<(), Duration>` which tells the handler either `Break` the Connection Attempt *or*
 /// `Continue` After the Given `Duration`
@@ -25,7 +27,7 @@ pub trait ReconnectionPolicy: Send + Sync {
     fn should_retry(&self, attempts: usize) -> ControlFlow<(), Duration>;
 }
 
-/// the default implementation of the **ReconnectionPolicy**.
+/// the simplest implementation of the **ReconnectionPolicy**, which never reconnects.
 pub struct NoReconnect;
 
 /// simple *Fixed* sized implementation for the **ReconnectionPolicy** trait.
@@ -34,6 +36,47 @@ pub struct FixedReconnect {
     pub delay: Duration,
 }
 
+/// *Exponential backoff* implementation for the **ReconnectionPolicy** trait.
+///
+/// the delay starts at `initial_delay` and doubles after every failed attempt, up to `max_delay`.
+/// when `jitter` is enabled, a random amount of up to half the delay is subtracted, so that many
+/// clients disconnected at the same time don't all reconnect at once.
+///
+/// once more than `max_attempts` reconnections fail, the error is surfaced instead. if it's
+/// `None`, the policy never gives up.
+pub struct ExponentialBackoff {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub max_attempts: Option<usize>,
+    pub jitter: bool,
+}
+
+impl ReconnectionPolicy for ExponentialBackoff {
+    fn should_retry(&self, attempts: usize) -> ControlFlow<(), Duration> {
+        if self.max_attempts.is_some_and(|max| attempts > max) {
+            return ControlFlow::Break(());
+        }
+
+        let exponent = attempts.saturating_sub(1).min(31) as u32;
+        let delay = self
+            .initial_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+
+        if self.jitter {
+            // The jitter does not need to be secure, so the clock is a good enough source.
+            let nanos = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|d| d.subsec_nanos())
+                .unwrap_or(0);
+            let jitter = delay.mul_f64(f64::from(nanos % 1000) / 2000.0);
+            ControlFlow::Continue(delay - jitter)
+        } else {
+            ControlFlow::Continue(delay)
+        }
+    }
+}
+
 impl ReconnectionPolicy for FixedReconnect {
     fn should_retry(&self, attempts: usize) -> ControlFlow<(), Duration> {
         if attempts <= self.attempts {
@@ -49,3 +92,59 @@ impl ReconnectionPolicy for NoReconnect {
         ControlFlow::Break(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backoff(jitter: bool) -> ExponentialBackoff {
+        ExponentialBackoff {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+            max_attempts: Some(5),
+            jitter,
+        }
+    }
+
+    #[test]
+    fn exponential_backoff_doubles_up_to_max() {
+        let policy = backoff(false);
+        let delays = (1..=5)
+            .map(|attempt| match policy.should_retry(attempt) {
+                ControlFlow::Continue(delay) => delay.as_secs(),
+                ControlFlow::Break(()) => panic!("gave up too early"),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(delays, [1, 2, 4, 8, 10]);
+    }
+
+    #[test]
+    fn exponential_backoff_jitter_is_bounded() {
+        let policy = backoff(true);
+        for attempt in 1..=5 {
+            let ControlFlow::Continue(delay) = policy.should_retry(attempt) else {
+                panic!("gave up too early");
+            };
+            let ControlFlow::Continue(max) = backoff(false).should_retry(attempt) else {
+                unreachable!();
+            };
+            assert!(delay <= max);
+            assert!(delay >= max / 2);
+        }
+    }
+
+    #[test]
+    fn exponential_backoff_gives_up_after_max_attempts() {
+        assert_eq!(backoff(false).should_retry(6), ControlFlow::Break(()));
+
+        let forever = ExponentialBackoff {
+            max_attempts: None,
+            ..backoff(false)
+        };
+        assert_eq!(
+            forever.should_retry(1000),
+            ControlFlow::Continue(Duration::from_secs(10))
+        );
+    }
+}
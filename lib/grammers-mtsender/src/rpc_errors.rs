@@ -0,0 +1,242 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+//! Catalogue of the known RPC errors, so that they can be matched on without comparing strings.
+//!
+//! The list is not exhaustive, since Telegram may introduce new errors at any time. Errors that
+//! are not known are represented by [`RpcErrorKind::Other`].
+
+/// Generate the [`RpcErrorKind`] enumeration from the name of each error.
+///
+/// Errors with a value (such as `FLOOD_WAIT_X`) are listed by the name without the value, as
+/// stored in [`RpcError::name`](crate::RpcError::name).
+macro_rules! rpc_errors {
+    (
+        with_value {
+            $( $(#[$value_meta:meta])* $value_name:literal => $value_variant:ident, )*
+        }
+        without_value {
+            $( $(#[$meta:meta])* $name:literal => $variant:ident, )*
+        }
+    ) => {
+        /// The kind of an [`RpcError`](crate::RpcError), parsed from its name.
+        ///
+        /// Obtained through [`RpcError::kind`](crate::RpcError::kind). New variants may be
+        /// added at any time as more errors become known, which would make a previous
+        /// [`RpcErrorKind::Other`] become one of them.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use grammers_mtsender::{RpcError, RpcErrorKind};
+        ///
+        /// # let error = RpcError { code: 420, name: "FLOOD_WAIT".to_string(), value: Some(31), caused_by: None };
+        /// match error.kind() {
+        ///     RpcErrorKind::FloodWait(seconds) => assert_eq!(seconds, 31),
+        ///     RpcErrorKind::ChatWriteForbidden => panic!("cannot write in this chat"),
+        ///     _ => panic!("unexpected error {error}"),
+        /// }
+        /// ```
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        #[non_exhaustive]
+        pub enum RpcErrorKind {
+            $(
+                #[doc = concat!("`", $value_name, "_X`.")]
+                $(#[$value_meta])*
+                $value_variant(u32),
+            )*
+            $(
+                #[doc = concat!("`", $name, "`.")]
+                $(#[$meta])*
+                $variant,
+            )*
+            /// Any other error, with its name.
+            Other(String),
+        }
+
+        impl RpcErrorKind {
+            /// Determine the kind of the error with the given name and value.
+            pub fn parse(name: &str, value: Option<u32>) -> Self {
+                match (name, value) {
+                    $( ($value_name, Some(value)) => Self::$value_variant(value), )*
+                    $( ($name, None) => Self::$variant, )*
+                    _ => Self::Other(name.to_string()),
+                }
+            }
+
+            /// The name of the error, without its value. This is the same as
+            /// [`RpcError::name`](crate::RpcError::name).
+            pub fn name(&self) -> &str {
+                match self {
+                    $( Self::$value_variant(_) => $value_name, )*
+                    $( Self::$variant => $name, )*
+                    Self::Other(name) => name,
+                }
+            }
+
+            /// The value contained in the error, if any.
+            pub fn value(&self) -> Option<u32> {
+                match self {
+                    $( Self::$value_variant(value) => Some(*value), )*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+rpc_errors! {
+    with_value {
+        /// Too many requests were made, and this many seconds must pass before trying again.
+        "FLOOD_WAIT" => FloodWait,
+        /// Like [`RpcErrorKind::FloodWait`], but the wait could be avoided with Telegram Premium.
+        "FLOOD_PREMIUM_WAIT" => FloodPremiumWait,
+        /// Slow mode is enabled in the chat, and this many seconds must pass before sending again.
+        "SLOWMODE_WAIT" => SlowmodeWait,
+        /// Too many attempts were made with a test phone number.
+        "FLOOD_TEST_PHONE_WAIT" => FloodTestPhoneWait,
+        /// The takeout session must wait this many seconds before it can be used.
+        "TAKEOUT_INIT_DELAY" => TakeoutInitDelay,
+        /// The password was changed recently, and this many seconds must pass.
+        "PASSWORD_TOO_FRESH" => PasswordTooFresh,
+        /// The session was created recently, and this many seconds must pass.
+        "SESSION_TOO_FRESH" => SessionTooFresh,
+        /// The phone number is registered in the datacenter with this identifier.
+        "PHONE_MIGRATE" => PhoneMigrate,
+        /// The account is registered in the datacenter with this identifier.
+        "USER_MIGRATE" => UserMigrate,
+        /// The request must be made to the datacenter with this identifier.
+        "NETWORK_MIGRATE" => NetworkMigrate,
+        /// The file is stored in the datacenter with this identifier.
+        "FILE_MIGRATE" => FileMigrate,
+        /// The statistics are stored in the datacenter with this identifier.
+        "STATS_MIGRATE" => StatsMigrate,
+        /// The part with this index is missing from the uploaded file.
+        "FILE_PART_MISSING" => FilePartMissing,
+        /// An internal error occurred when communicating with the datacenter with this identifier.
+        "INTERDC_CALL_ERROR" => InterdcCallError,
+        /// Like [`RpcErrorKind::InterdcCallError`], but the request may be re-sent as-is.
+        "INTERDC_CALL_RICH_ERROR" => InterdcCallRichError,
+    }
+    without_value {
+        "AUTH_KEY_UNREGISTERED" => AuthKeyUnregistered,
+        "AUTH_KEY_DUPLICATED" => AuthKeyDuplicated,
+        "AUTH_RESTART" => AuthRestart,
+        "SESSION_PASSWORD_NEEDED" => SessionPasswordNeeded,
+        "SESSION_REVOKED" => SessionRevoked,
+        "PASSWORD_HASH_INVALID" => PasswordHashInvalid,
+        "PHONE_CODE_INVALID" => PhoneCodeInvalid,
+        "PHONE_CODE_EXPIRED" => PhoneCodeExpired,
+        "PHONE_CODE_EMPTY" => PhoneCodeEmpty,
+        "PHONE_NUMBER_INVALID" => PhoneNumberInvalid,
+        "PHONE_NUMBER_BANNED" => PhoneNumberBanned,
+        "PHONE_NUMBER_UNOCCUPIED" => PhoneNumberUnoccupied,
+        "PHONE_NUMBER_OCCUPIED" => PhoneNumberOccupied,
+        "ACCESS_TOKEN_INVALID" => AccessTokenInvalid,
+        "ACCESS_TOKEN_EXPIRED" => AccessTokenExpired,
+        "USER_DEACTIVATED" => UserDeactivated,
+        "USER_DEACTIVATED_BAN" => UserDeactivatedBan,
+        "INPUT_USER_DEACTIVATED" => InputUserDeactivated,
+        "PEER_ID_INVALID" => PeerIdInvalid,
+        "PEER_FLOOD" => PeerFlood,
+        "USER_ID_INVALID" => UserIdInvalid,
+        "USER_IS_BLOCKED" => UserIsBlocked,
+        "USER_IS_BOT" => UserIsBot,
+        "USER_BOT_INVALID" => UserBotInvalid,
+        "USER_PRIVACY_RESTRICTED" => UserPrivacyRestricted,
+        "USER_NOT_PARTICIPANT" => UserNotParticipant,
+        "USER_ALREADY_PARTICIPANT" => UserAlreadyParticipant,
+        "USER_BANNED_IN_CHANNEL" => UserBannedInChannel,
+        "USERNAME_INVALID" => UsernameInvalid,
+        "USERNAME_NOT_OCCUPIED" => UsernameNotOccupied,
+        "USERNAME_OCCUPIED" => UsernameOccupied,
+        "CHAT_ID_INVALID" => ChatIdInvalid,
+        "CHAT_DEACTIVATED" => ChatDeactivated,
+        "CHAT_ADMIN_REQUIRED" => ChatAdminRequired,
+        "CHAT_WRITE_FORBIDDEN" => ChatWriteForbidden,
+        "CHAT_RESTRICTED" => ChatRestricted,
+        "CHAT_FORWARDS_RESTRICTED" => ChatForwardsRestricted,
+        "CHAT_SEND_MEDIA_FORBIDDEN" => ChatSendMediaForbidden,
+        "CHAT_NOT_MODIFIED" => ChatNotModified,
+        "CHANNEL_INVALID" => ChannelInvalid,
+        "CHANNEL_PRIVATE" => ChannelPrivate,
+        "CHANNELS_TOO_MUCH" => ChannelsTooMuch,
+        "INVITE_HASH_EMPTY" => InviteHashEmpty,
+        "INVITE_HASH_EXPIRED" => InviteHashExpired,
+        "INVITE_HASH_INVALID" => InviteHashInvalid,
+        "INVITE_REQUEST_SENT" => InviteRequestSent,
+        "MESSAGE_ID_INVALID" => MessageIdInvalid,
+        "MESSAGE_IDS_EMPTY" => MessageIdsEmpty,
+        "MESSAGE_EMPTY" => MessageEmpty,
+        "MESSAGE_TOO_LONG" => MessageTooLong,
+        "MESSAGE_NOT_MODIFIED" => MessageNotModified,
+        "MESSAGE_AUTHOR_REQUIRED" => MessageAuthorRequired,
+        "MESSAGE_DELETE_FORBIDDEN" => MessageDeleteForbidden,
+        "MESSAGE_EDIT_TIME_EXPIRED" => MessageEditTimeExpired,
+        "ENTITIES_TOO_LONG" => EntitiesTooLong,
+        "ENTITY_BOUNDS_INVALID" => EntityBoundsInvalid,
+        "RANDOM_ID_DUPLICATE" => RandomIdDuplicate,
+        "SCHEDULE_DATE_TOO_LATE" => ScheduleDateTooLate,
+        "SCHEDULE_TOO_MUCH" => ScheduleTooMuch,
+        "REPLY_MARKUP_INVALID" => ReplyMarkupInvalid,
+        "BUTTON_DATA_INVALID" => ButtonDataInvalid,
+        "QUERY_ID_INVALID" => QueryIdInvalid,
+        "BOT_METHOD_INVALID" => BotMethodInvalid,
+        "MEDIA_EMPTY" => MediaEmpty,
+        "MEDIA_INVALID" => MediaInvalid,
+        "MEDIA_CAPTION_TOO_LONG" => MediaCaptionTooLong,
+        "PHOTO_INVALID_DIMENSIONS" => PhotoInvalidDimensions,
+        "FILE_REFERENCE_EXPIRED" => FileReferenceExpired,
+        "FILE_ID_INVALID" => FileIdInvalid,
+        "FILE_PARTS_INVALID" => FilePartsInvalid,
+        "TOPIC_CLOSED" => TopicClosed,
+        "TOPIC_DELETED" => TopicDeleted,
+        "RPC_CALL_FAIL" => RpcCallFail,
+        "TIMEOUT" => Timeout,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_known_errors() {
+        assert_eq!(
+            RpcErrorKind::parse("FLOOD_WAIT", Some(31)),
+            RpcErrorKind::FloodWait(31)
+        );
+        assert_eq!(
+            RpcErrorKind::parse("PEER_ID_INVALID", None),
+            RpcErrorKind::PeerIdInvalid
+        );
+        assert_eq!(RpcErrorKind::FloodWait(31).name(), "FLOOD_WAIT");
+        assert_eq!(RpcErrorKind::FloodWait(31).value(), Some(31));
+        assert_eq!(RpcErrorKind::PeerIdInvalid.value(), None);
+    }
+
+    #[test]
+    fn check_unknown_errors() {
+        assert_eq!(
+            RpcErrorKind::parse("SOMETHING_NEW", None),
+            RpcErrorKind::Other("SOMETHING_NEW".into())
+        );
+        // A known name with an unexpected value (or lack of it) is not the known error.
+        assert_eq!(
+            RpcErrorKind::parse("FLOOD_WAIT", None),
+            RpcErrorKind::Other("FLOOD_WAIT".into())
+        );
+        assert_eq!(
+            RpcErrorKind::parse("PEER_ID_INVALID", Some(1)),
+            RpcErrorKind::Other("PEER_ID_INVALID".into())
+        );
+        assert_eq!(
+            RpcErrorKind::Other("SOMETHING_NEW".into()).name(),
+            "SOMETHING_NEW"
+        );
+    }
+}
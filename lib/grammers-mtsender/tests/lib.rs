@@ -12,9 +12,10 @@ pub const TELEGRAM_TEST_DC_2: &str = "149.154.167.40:443";
 pub const TELEGRAM_DEFAULT_TEST_DC: &str = TELEGRAM_TEST_DC_2;
 
 use grammers_mtproto::transport;
-use grammers_mtsender::{connect, NoReconnect};
+use grammers_mtsender::{connect, connect_with_temp_auth, NoReconnect};
 use grammers_tl_types::{enums, functions, Deserializable, RemoteCall, LAYER};
 use std::str::FromStr;
+use std::time::Duration;
 
 use simple_logger::SimpleLogger;
 use tokio::runtime;
@@ -74,3 +75,64 @@ fn test_invoke_encrypted_method() {
         }
     });
 }
+
+#[test]
+fn test_invoke_encrypted_method_with_temp_auth_key() {
+    let rt = runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        let addr = grammers_mtsender::ServerAddr::Tcp {
+            address: std::net::SocketAddr::from_str(TELEGRAM_TEST_DC_2).unwrap(),
+        };
+        let (sender, _) = connect(transport::Full::new(), addr.clone(), &NoReconnect)
+            .await
+            .unwrap();
+        let auth_key = sender.auth_key();
+        drop(sender);
+
+        let expires_in = Duration::from_secs(10);
+        let (mut sender, enqueuer) = connect_with_temp_auth(
+            transport::Full::new(),
+            addr,
+            auth_key,
+            // Test datacenters are offset by 10000 when binding keys.
+            10002,
+            expires_in,
+            &NoReconnect,
+        )
+        .await
+        .unwrap();
+
+        let expiry = sender.temp_auth_key_expiry().unwrap();
+        assert!(expiry <= web_time::Instant::now() + expires_in);
+        assert_ne!(sender.auth_key(), auth_key);
+
+        let mut rx = enqueuer.enqueue(&functions::InvokeWithLayer {
+            layer: LAYER,
+            query: functions::InitConnection {
+                api_id: 1,
+                device_model: "Test".to_string(),
+                system_version: "0.1".to_string(),
+                app_version: "0.1".to_string(),
+                system_lang_code: "en".to_string(),
+                lang_pack: "".to_string(),
+                lang_code: "".to_string(),
+                proxy: None,
+                params: None,
+                query: functions::help::GetNearestDc {},
+            },
+        });
+        loop {
+            sender.step().await.unwrap();
+            if let Ok(response) = rx.try_recv() {
+                let body = response.unwrap();
+                let response =
+                    <functions::help::GetNearestDc as RemoteCall>::Return::from_bytes(&body);
+                assert!(matches!(response, Ok(enums::NearestDc::Dc(_))));
+                break;
+            }
+        }
+    });
+}
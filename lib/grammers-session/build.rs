@@ -20,14 +20,15 @@ fn main() -> std::io::Result<()> {
     )?);
 
     // Using boxed variants in the definitions so that deserialization fails if any constructor ID changes.
+    // Optional fields added later pin the original ID, since old sessions never set their flag.
     let definitions = parse_tl_file(
         r#"
-        dataCenter flags:# id:int ipv4:flags.0?int ipv6:flags.1?int128 port:int auth:flags.2?bytes = DataCenter;
+        dataCenter#756d16b6 flags:# id:int ipv4:flags.0?int ipv6:flags.1?int128 port:int auth:flags.2?bytes transport:flags.3?int = DataCenter;
         dataCenterWs flags:# id:int url:string auth:flags.0?bytes = DataCenter;
         user id:long dc:int bot:Bool = User;
         channelState channel_id:long pts:int = ChannelState;
         updateState pts:int qts:int date:int seq:int channels:Vector<ChannelState> = UpdateState;
-        session flags:# dcs:Vector<DataCenter> user:flags.0?User state:flags.1?UpdateState = Session;
+        session#a73eb8ce flags:# dcs:Vector<DataCenter> user:flags.0?User state:flags.1?UpdateState future_auth_tokens:flags.2?Vector<bytes> time_offset:flags.3?int = Session;
         "#,
     )
     .map(Result::unwrap)
@@ -23,10 +23,13 @@ pub use message_box::{Gap, MessageBox};
 use std::fmt;
 use std::fs::{File, OpenOptions};
 use std::io::{self, Read, Seek, Write};
-use std::net::{SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
 use std::path::Path;
 use std::sync::Mutex;
 
+// Official clients keep the same amount of tokens around.
+const MAX_FUTURE_AUTH_TOKENS: usize = 20;
+
 // Needed for auto-generated definitions.
 use grammers_tl_types::{deserialize, Deserializable, Identifiable, Serializable};
 
@@ -42,6 +45,8 @@ impl Session {
                 dcs: Vec::new(),
                 user: None,
                 state: None,
+                future_auth_tokens: None,
+                time_offset: None,
             }),
         }
     }
@@ -107,9 +112,36 @@ impl Session {
             .next()
     }
 
+    /// Returns the TCP address stored for the given datacenter, if any.
+    pub fn dc_tcp_addr(&self, dc_id: i32) -> Option<SocketAddr> {
+        self.session
+            .lock()
+            .unwrap()
+            .dcs
+            .iter()
+            .filter_map(|dc| match dc {
+                enums::DataCenter::Center(types::DataCenter {
+                    id,
+                    ipv4,
+                    ipv6,
+                    port,
+                    ..
+                }) if *id == dc_id => match (ipv6, ipv4) {
+                    (Some(ip), _) => Some(SocketAddr::from((Ipv6Addr::from(*ip), *port as u16))),
+                    (None, Some(ip)) => Some(SocketAddr::from((
+                        Ipv4Addr::from(ip.to_le_bytes()),
+                        *port as u16,
+                    ))),
+                    (None, None) => None,
+                },
+                _ => None,
+            })
+            .next()
+    }
+
     fn insert_dc(&self, dc: enums::DataCenter) {
         let mut session = self.session.lock().unwrap();
-        if let Some(pos) = session.dcs.iter().position(|dc| dc.id() == dc.id()) {
+        if let Some(pos) = session.dcs.iter().position(|stored| stored.id() == dc.id()) {
             session.dcs.remove(pos);
         }
         session.dcs.push(dc);
@@ -128,11 +160,51 @@ impl Session {
                 ipv6: ip_v6.map(|addr| addr.ip().octets()),
                 port: addr.port() as i32,
                 auth: Some(auth.into()),
+                transport: self.dc_transport(id),
             }
             .into(),
         );
     }
 
+    /// Returns the identifier of the transport known to work with the given datacenter, if any.
+    pub fn dc_transport(&self, dc_id: i32) -> Option<i32> {
+        self.session
+            .lock()
+            .unwrap()
+            .dcs
+            .iter()
+            .find_map(|dc| match dc {
+                enums::DataCenter::Center(types::DataCenter { id, transport, .. })
+                    if *id == dc_id =>
+                {
+                    *transport
+                }
+                _ => None,
+            })
+    }
+
+    /// Remember the identifier of the transport that worked to connect to the given datacenter.
+    ///
+    /// Nothing is stored unless the datacenter was inserted with [`Session::insert_dc_tcp`].
+    pub fn set_dc_transport(&self, dc_id: i32, transport: i32) {
+        for dc in self.session.lock().unwrap().dcs.iter_mut() {
+            if let enums::DataCenter::Center(dc) = dc {
+                if dc.id == dc_id {
+                    dc.transport = Some(transport);
+                }
+            }
+        }
+    }
+
+    /// Forget everything stored about the given datacenter, including its authorization key.
+    pub fn remove_dc(&self, dc_id: i32) {
+        self.session
+            .lock()
+            .unwrap()
+            .dcs
+            .retain(|dc| dc.id() != dc_id);
+    }
+
     pub fn insert_dc_ws(&self, id: i32, url: &str, auth: [u8; 256]) {
         self.insert_dc(
             types::DataCenterWs {
@@ -168,8 +240,43 @@ impl Session {
         self.session.lock().unwrap().state = Some(state.into())
     }
 
+    /// Returns the tokens received when previous logins completed, oldest first.
+    pub fn future_auth_tokens(&self) -> Vec<Vec<u8>> {
+        self.session
+            .lock()
+            .unwrap()
+            .future_auth_tokens
+            .clone()
+            .unwrap_or_default()
+    }
+
+    /// Remember a token received when a login completed or the user logged out.
+    ///
+    /// Only the most recent tokens are kept. Sending them the next time a login code is
+    /// requested lets Telegram know that the same device is logging in again.
+    pub fn add_future_auth_token(&self, token: Vec<u8>) {
+        let mut session = self.session.lock().unwrap();
+        let tokens = session.future_auth_tokens.get_or_insert_with(Vec::new);
+        tokens.retain(|t| *t != token);
+        tokens.push(token);
+        if tokens.len() > MAX_FUTURE_AUTH_TOKENS {
+            tokens.drain(..tokens.len() - MAX_FUTURE_AUTH_TOKENS);
+        }
+    }
+
+    /// Returns the last known difference between the server's clock and ours, in seconds.
+    pub fn time_offset(&self) -> i32 {
+        self.session.lock().unwrap().time_offset.unwrap_or(0)
+    }
+
+    /// Remember the difference between the server's clock and ours, so that it can be used
+    /// by the next connection before the server has had a chance to correct it.
+    pub fn set_time_offset(&self, offset: i32) {
+        self.session.lock().unwrap().time_offset = Some(offset);
+    }
+
     pub fn get_dcs(&self) -> Vec<enums::DataCenter> {
-        self.session.lock().unwrap().dcs.iter().cloned().collect()
+        self.session.lock().unwrap().dcs.to_vec()
     }
 
     #[must_use]
@@ -177,6 +284,35 @@ impl Session {
         enums::Session::Session(self.session.lock().unwrap().clone()).to_bytes()
     }
 
+    /// Export only what is needed to reuse the authorization of this session: the data centers
+    /// with an authorization key and the logged-in user, if any.
+    ///
+    /// Unlike [`Session::save`], the update state is left out, so the blob stays small and
+    /// loading it with [`Session::load`] will not try to catch up on missed updates. This is
+    /// meant for short-lived clients that never persist anything on their own, such as CLI tools
+    /// or CI jobs, which can store the blob wherever is convenient and hand it back on the next
+    /// run to skip the key exchange and sign-in.
+    #[must_use]
+    pub fn export_auth(&self) -> Vec<u8> {
+        let session = self.session.lock().unwrap();
+        enums::Session::Session(types::Session {
+            dcs: session
+                .dcs
+                .iter()
+                .filter(|dc| match dc {
+                    enums::DataCenter::Center(dc) => dc.auth.is_some(),
+                    enums::DataCenter::Ws(dc) => dc.auth.is_some(),
+                })
+                .cloned()
+                .collect(),
+            user: session.user.clone(),
+            state: None,
+            future_auth_tokens: session.future_auth_tokens.clone(),
+            time_offset: session.time_offset,
+        })
+        .to_bytes()
+    }
+
     /// Saves the session to a file.
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
         let mut file = OpenOptions::new().write(true).open(path.as_ref())?;
@@ -203,3 +339,88 @@ impl fmt::Display for Error {
 }
 
 impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn export_auth_skips_state() {
+        let session = Session::new();
+        session.insert_dc_tcp(2, &"127.0.0.1:443".parse().unwrap(), [1; 256]);
+        session.set_user(123, 2, true);
+        session.set_state(UpdateState {
+            pts: 1,
+            qts: 2,
+            date: 3,
+            seq: 4,
+            channels: Vec::new(),
+        });
+
+        let exported = Session::load(&session.export_auth()).unwrap();
+        assert_eq!(exported.dc_auth_key(2), Some([1; 256]));
+        assert_eq!(exported.get_user().map(|user| user.id), Some(123));
+        assert!(exported.get_state().is_none());
+    }
+
+    #[test]
+    fn future_auth_tokens_keep_most_recent() {
+        let session = Session::new();
+        assert!(session.future_auth_tokens().is_empty());
+
+        for i in 0..MAX_FUTURE_AUTH_TOKENS as u8 + 5 {
+            session.add_future_auth_token(vec![i]);
+        }
+        session.add_future_auth_token(vec![10]);
+
+        let tokens = Session::load(&session.save()).unwrap().future_auth_tokens();
+        assert_eq!(tokens.len(), MAX_FUTURE_AUTH_TOKENS);
+        assert_eq!(tokens.first(), Some(&vec![5]));
+        assert_eq!(tokens.last(), Some(&vec![10]));
+    }
+
+    #[test]
+    fn insert_dc_replaces_same_id_only() {
+        let session = Session::new();
+        session.insert_dc_tcp(1, &"127.0.0.1:443".parse().unwrap(), [1; 256]);
+        session.insert_dc_tcp(2, &"127.0.0.2:443".parse().unwrap(), [2; 256]);
+        session.insert_dc_tcp(2, &"[::1]:80".parse().unwrap(), [3; 256]);
+
+        assert_eq!(session.get_dcs().len(), 2);
+        assert_eq!(session.dc_auth_key(1), Some([1; 256]));
+        assert_eq!(session.dc_auth_key(2), Some([3; 256]));
+        assert_eq!(
+            session.dc_tcp_addr(1),
+            Some("127.0.0.1:443".parse().unwrap())
+        );
+        assert_eq!(session.dc_tcp_addr(2), Some("[::1]:80".parse().unwrap()));
+    }
+
+    #[test]
+    fn dc_transport_survives_reinsert_and_save() {
+        let session = Session::new();
+        session.set_dc_transport(2, 3);
+        assert_eq!(session.dc_transport(2), None);
+
+        session.insert_dc_tcp(2, &"127.0.0.1:443".parse().unwrap(), [1; 256]);
+        session.set_dc_transport(2, 3);
+        session.insert_dc_tcp(2, &"127.0.0.1:80".parse().unwrap(), [1; 256]);
+
+        let loaded = Session::load(&session.save()).unwrap();
+        assert_eq!(loaded.dc_transport(2), Some(3));
+        assert_eq!(loaded.dc_transport(1), None);
+    }
+
+    #[test]
+    fn time_offset_survives_save() {
+        let session = Session::new();
+        assert_eq!(session.time_offset(), 0);
+
+        session.set_time_offset(-42);
+        assert_eq!(Session::load(&session.save()).unwrap().time_offset(), -42);
+        assert_eq!(
+            Session::load(&session.export_auth()).unwrap().time_offset(),
+            -42
+        );
+    }
+}
@@ -107,7 +107,7 @@ impl MessageBox {
             state
                 .channels
                 .iter()
-                .map(|ChannelStateEnum::State(c)| (Entry::Channel(c.channel_id))),
+                .map(|ChannelStateEnum::State(c)| Entry::Channel(c.channel_id)),
         );
 
         Self {
@@ -12,7 +12,7 @@ use crate::grouper;
 use crate::metadata::Metadata;
 use crate::rustifier;
 use crate::{ignore_type, Config};
-use grammers_tl_parser::tl::{Definition, ParameterType, Type};
+use grammers_tl_parser::tl::{Category, Definition, ParameterType, Type};
 use std::collections::HashSet;
 use std::io::{self, Write};
 
@@ -100,7 +100,7 @@ fn write_common_field_impl<W: Write>(
     indent: &str,
     ty: &Type,
     metadata: &Metadata,
-    _config: &Config,
+    config: &Config,
 ) -> io::Result<()> {
     // Don't generate if only one type
     let definitions = metadata.defs_with_type(ty);
@@ -117,7 +117,7 @@ fn write_common_field_impl<W: Write>(
             .filter(|p| match p.ty {
                 ParameterType::Flags => false,
                 ParameterType::Normal { .. } => {
-                    !rustifier::parameters::qual_name(p).contains("Option<")
+                    !rustifier::parameters::qual_name(p, config).contains("Option<")
                 }
             })
             .collect();
@@ -143,7 +143,7 @@ fn write_common_field_impl<W: Write>(
         rustifier::types::type_name(ty)
     )?;
     for param in common_params {
-        let qual_name = rustifier::parameters::qual_name(param);
+        let qual_name = rustifier::parameters::qual_name(param, config);
         writeln!(
             file,
             "{}    pub fn {}(&self) -> {} {{\n{}        match self {{",
@@ -400,6 +400,7 @@ pub(crate) fn write_enums_mod<W: Write>(
     for key in sorted_keys.into_iter() {
         // Begin possibly inner mod
         let indent = if let Some(ns) = key {
+            crate::write_namespace_cfg(file, ns, Category::Types, metadata, config)?;
             writeln!(file, "    #[allow(clippy::large_enum_variant)]")?;
             writeln!(file, "    pub mod {ns} {{")?;
             "        "
@@ -407,6 +408,7 @@ pub(crate) fn write_enums_mod<W: Write>(
             "    "
         };
 
+        crate::write_alloc_imports(file, indent, config)?;
         for ty in grouped[key].iter().filter(|ty| !ignore_type(ty)) {
             write_definition(&mut file, indent, ty, metadata, config)?;
         }
@@ -22,22 +22,32 @@ use std::io::{self, Write};
 
 pub struct Config {
     pub gen_name_for_id: bool,
+    pub gen_json_schema: bool,
     pub deserializable_functions: bool,
     pub impl_debug: bool,
     pub impl_from_type: bool,
     pub impl_from_enum: bool,
     pub impl_serde: bool,
+    pub impl_builders: bool,
+    pub shared_bytes: bool,
+    pub namespace_features: bool,
+    pub no_std: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             gen_name_for_id: false,
+            gen_json_schema: false,
             deserializable_functions: false,
             impl_debug: true,
             impl_from_type: true,
             impl_from_enum: true,
             impl_serde: false,
+            impl_builders: false,
+            shared_bytes: false,
+            namespace_features: false,
+            no_std: false,
         }
     }
 }
@@ -95,6 +105,10 @@ pub fn name_for_id(id: u32) -> &'static str {{
         )?;
     }
 
+    if config.gen_json_schema {
+        write_json_schema(file, definitions)?;
+    }
+
     let metadata = metadata::Metadata::new(definitions);
     structs::write_category_mod(file, Category::Types, definitions, &metadata, config)?;
     structs::write_category_mod(file, Category::Functions, definitions, &metadata, config)?;
@@ -102,3 +116,70 @@ pub fn name_for_id(id: u32) -> &'static str {{
 
     Ok(())
 }
+
+/// Write the attribute to only compile the namespace of the given category when its feature is
+/// enabled, if namespaces should be feature-gated.
+///
+/// Functions are always gated. Types (and their enums) are only gated if no definition outside of
+/// the namespace uses them, so that every feature can be enabled on its own.
+fn write_namespace_cfg(
+    file: &mut impl Write,
+    ns: &str,
+    category: Category,
+    metadata: &metadata::Metadata,
+    config: &Config,
+) -> io::Result<()> {
+    if config.namespace_features
+        && (category == Category::Functions || !metadata.is_shared_namespace(ns))
+    {
+        writeln!(file, "    #[cfg(feature = \"tl-{ns}\")]")?;
+    }
+    Ok(())
+}
+
+/// Write the imports needed by every module when the prelude of `std` is not available.
+fn write_alloc_imports(file: &mut impl Write, indent: &str, config: &Config) -> io::Result<()> {
+    if config.no_std {
+        writeln!(file, "{indent}#[allow(unused_imports)]")?;
+        writeln!(
+            file,
+            "{indent}use alloc::{{boxed::Box, string::String, vec::Vec}};"
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the definitions as a table sorted by identifier, which is used by `crate::json`
+/// to make sense of serialized objects at runtime.
+fn write_json_schema(file: &mut impl Write, definitions: &[Definition]) -> io::Result<()> {
+    let mut definitions = definitions.iter().collect::<Vec<_>>();
+    definitions.sort_by_key(|def| def.id);
+    definitions.dedup_by_key(|def| def.id);
+
+    writeln!(
+        file,
+        "/// Definitions of every constructor and function, sorted by identifier."
+    )?;
+    writeln!(
+        file,
+        "pub(crate) static JSON_SCHEMA: &[crate::json::Definition] = &["
+    )?;
+    for def in definitions {
+        write!(
+            file,
+            r#"    crate::json::Definition {{ id: 0x{:x}, name: "{}", ty: "{}", params: &["#,
+            def.id,
+            def.full_name(),
+            def.ty
+        )?;
+        for (i, param) in def.params.iter().enumerate() {
+            if i != 0 {
+                write!(file, ", ")?;
+            }
+            write!(file, r#"("{}", "{}")"#, param.name, param.ty)?;
+        }
+        writeln!(file, "] }},")?;
+    }
+    writeln!(file, "];")?;
+    Ok(())
+}
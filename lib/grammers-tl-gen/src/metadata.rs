@@ -14,6 +14,7 @@ pub(crate) struct Metadata<'a> {
     recursing_defs: HashSet<u32>,
     defs_with_type: HashMap<(&'a Vec<String>, &'a String), Vec<&'a Definition>>,
     unused_flags: HashMap<(&'a Vec<String>, &'a String), Vec<&'a Parameter>>,
+    shared_namespaces: HashSet<&'a str>,
 }
 
 impl<'a> Metadata<'a> {
@@ -22,6 +23,7 @@ impl<'a> Metadata<'a> {
             recursing_defs: HashSet::new(),
             defs_with_type: HashMap::new(),
             unused_flags: HashMap::new(),
+            shared_namespaces: HashSet::new(),
         };
 
         definitions.iter().for_each(|d| {
@@ -63,13 +65,15 @@ impl<'a> Metadata<'a> {
             }
         });
 
+        metadata.shared_namespaces = find_shared_namespaces(definitions);
+
         metadata
     }
 
     pub fn is_unused_flag(&self, def: &Definition, flag: &Parameter) -> bool {
         self.unused_flags
             .get(&(&def.namespace, &def.name))
-            .map(|flags| flags.iter().any(|f| *f == flag))
+            .map(|flags| flags.contains(&flag))
             .unwrap_or(false)
     }
 
@@ -82,6 +86,40 @@ impl<'a> Metadata<'a> {
     pub fn defs_with_type(&self, ty: &'a Type) -> &Vec<&Definition> {
         &self.defs_with_type[&(&ty.namespace, &ty.name)]
     }
+
+    /// Returns `true` if definitions outside of the namespace use its types, which means that
+    /// they cannot be disabled without breaking the definitions using them.
+    pub fn is_shared_namespace(&self, ns: &str) -> bool {
+        self.shared_namespaces.contains(ns)
+    }
+}
+
+/// Find the namespaces whose types are used by definitions outside of them.
+///
+/// Because every type such a definition can reach is then part of a shared namespace as well,
+/// the remaining namespaces only depend on shared ones, and never on each other.
+fn find_shared_namespaces(definitions: &[Definition]) -> HashSet<&str> {
+    fn add_type_namespaces<'a>(ty: &'a Type, own: &str, output: &mut HashSet<&'a str>) {
+        if let Some(ns) = ty.namespace.first() {
+            if ns != own {
+                output.insert(ns);
+            }
+        }
+        if let Some(generic_arg) = &ty.generic_arg {
+            add_type_namespaces(generic_arg, own, output);
+        }
+    }
+
+    let mut shared = HashSet::new();
+    for def in definitions {
+        let own = def.namespace.first().map(|s| s.as_str()).unwrap_or("");
+        add_type_namespaces(&def.ty, own, &mut shared);
+        def.params.iter().for_each(|param| match &param.ty {
+            ParameterType::Flags => {}
+            ParameterType::Normal { ty, .. } => add_type_namespaces(ty, own, &mut shared),
+        });
+    }
+    shared
 }
 
 fn def_self_references(
@@ -17,6 +17,7 @@
 //! * `item_path` for use as a qualified item path (`Vec::<u8>`).
 //! * `attr_name` for use as an attribute name (`foo_bar: ()`).
 
+use crate::Config;
 use grammers_tl_parser::tl::{Definition, Parameter, ParameterType, Type};
 
 /// Get the rusty type name for a certain definition, excluding namespace.
@@ -125,11 +126,13 @@ pub mod definitions {
 pub mod types {
     use super::*;
 
-    fn builtin_type(ty: &Type, path: bool) -> Option<&'static str> {
+    fn builtin_type(ty: &Type, path: bool, config: &Config) -> Option<&'static str> {
         Some(match ty.name.as_ref() {
             "Bool" => "bool",
             "bytes" => {
-                if path {
+                if config.shared_bytes {
+                    "::bytes::Bytes"
+                } else if path {
                     "Vec::<u8>"
                 } else {
                     "Vec<u8>"
@@ -164,12 +167,12 @@ pub mod types {
     // name and item paths so this method is used for both:
     // 1. use `::<...>` instead of `<...>` to specify type arguments
     // 2. missing angle brackets in associated item path
-    fn get_path(ty: &Type, path: bool) -> String {
+    fn get_path(ty: &Type, path: bool, config: &Config) -> String {
         if ty.generic_ref {
             return ty.name.clone();
         }
 
-        let mut result = if let Some(name) = builtin_type(ty, path) {
+        let mut result = if let Some(name) = builtin_type(ty, path, config) {
             name.to_string()
         } else {
             let mut result = String::new();
@@ -191,7 +194,7 @@ pub mod types {
                 result.push_str("::");
             }
             result.push('<');
-            result.push_str(&qual_name(generic_ty));
+            result.push_str(&qual_name(generic_ty, config));
             result.push('>');
         }
 
@@ -202,19 +205,19 @@ pub mod types {
         rusty_type_name(&ty.name)
     }
 
-    pub fn qual_name(ty: &Type) -> String {
-        get_path(ty, false)
+    pub fn qual_name(ty: &Type, config: &Config) -> String {
+        get_path(ty, false, config)
     }
 
-    pub fn item_path(ty: &Type) -> String {
-        get_path(ty, true)
+    pub fn item_path(ty: &Type, config: &Config) -> String {
+        get_path(ty, true, config)
     }
 }
 
 pub mod parameters {
     use super::*;
 
-    pub fn qual_name(param: &Parameter) -> String {
+    pub fn qual_name(param: &Parameter, config: &Config) -> String {
         match &param.ty {
             ParameterType::Flags => "u32".into(),
             ParameterType::Normal { ty, flag } if flag.is_some() && ty.name == "true" => {
@@ -225,7 +228,7 @@ pub mod parameters {
                 if flag.is_some() {
                     result.push_str("Option<");
                 }
-                result.push_str(&types::qual_name(ty));
+                result.push_str(&types::qual_name(ty, config));
                 if flag.is_some() {
                     result.push('>');
                 }
@@ -326,56 +329,56 @@ mod tests {
     #[test]
     fn check_type_qual_name() {
         let ty = "InputPeer".parse().unwrap();
-        let name = types::qual_name(&ty);
+        let name = types::qual_name(&ty, &Config::default());
         assert_eq!(name, "crate::enums::InputPeer");
     }
 
     #[test]
     fn check_type_qual_namespaced_name() {
         let ty = "storage.FileType".parse().unwrap();
-        let name = types::qual_name(&ty);
+        let name = types::qual_name(&ty, &Config::default());
         assert_eq!(name, "crate::enums::storage::FileType");
     }
 
     #[test]
     fn check_type_qual_bare_name() {
         let ty = "ipPort".parse().unwrap();
-        let name = types::qual_name(&ty);
+        let name = types::qual_name(&ty, &Config::default());
         assert_eq!(name, "crate::types::IpPort");
     }
 
     #[test]
     fn check_type_qual_namespaced_bare_name() {
         let ty = "storage.fileUnknown".parse().unwrap();
-        let name = types::qual_name(&ty);
+        let name = types::qual_name(&ty, &Config::default());
         assert_eq!(name, "crate::types::storage::FileUnknown");
     }
 
     #[test]
     fn check_type_bytes_qual_name() {
         let ty = "bytes".parse().unwrap();
-        let name = types::qual_name(&ty);
+        let name = types::qual_name(&ty, &Config::default());
         assert_eq!(name, "Vec<u8>");
     }
 
     #[test]
     fn check_type_large_int_qual_name() {
         let ty = "int256".parse().unwrap();
-        let name = types::qual_name(&ty);
+        let name = types::qual_name(&ty, &Config::default());
         assert_eq!(name, "[u8; 32]");
     }
 
     #[test]
     fn check_type_raw_vec_qual_name() {
         let ty = "vector<long>".parse().unwrap();
-        let name = types::qual_name(&ty);
+        let name = types::qual_name(&ty, &Config::default());
         assert_eq!(name, "crate::RawVec<i64>");
     }
 
     #[test]
     fn check_type_vec_qual_name() {
         let ty = "Vector<Bool>".parse().unwrap();
-        let name = types::qual_name(&ty);
+        let name = types::qual_name(&ty, &Config::default());
         assert_eq!(name, "Vec<bool>");
     }
 
@@ -383,28 +386,39 @@ mod tests {
     fn check_type_generic_ref_qual_name() {
         let mut ty: Type = "X".parse().unwrap();
         ty.generic_ref = true;
-        let name = types::qual_name(&ty);
+        let name = types::qual_name(&ty, &Config::default());
         assert_eq!(name, "X");
     }
 
     #[test]
     fn check_type_item_path() {
         let ty = "Vector<FileHash>".parse().unwrap();
-        let name = types::item_path(&ty);
+        let name = types::item_path(&ty, &Config::default());
         assert_eq!(name, "Vec::<crate::enums::FileHash>");
     }
 
     #[test]
     fn check_type_bytes_item_path() {
         let ty = "bytes".parse().unwrap();
-        let name = types::item_path(&ty);
+        let name = types::item_path(&ty, &Config::default());
         assert_eq!(name, "Vec::<u8>");
     }
 
+    #[test]
+    fn check_type_shared_bytes_path() {
+        let ty = "bytes".parse().unwrap();
+        let config = Config {
+            shared_bytes: true,
+            ..Config::default()
+        };
+        assert_eq!(types::qual_name(&ty, &config), "::bytes::Bytes");
+        assert_eq!(types::item_path(&ty, &config), "::bytes::Bytes");
+    }
+
     #[test]
     fn check_type_large_int_item_path() {
         let ty = "int256".parse().unwrap();
-        let name = types::item_path(&ty);
+        let name = types::item_path(&ty, &Config::default());
         assert_eq!(name, "<[u8; 32]>");
     }
 
@@ -413,28 +427,28 @@ mod tests {
     #[test]
     fn check_param_qual_name() {
         let param = "pts:int".parse().unwrap();
-        let name = parameters::qual_name(&param);
+        let name = parameters::qual_name(&param, &Config::default());
         assert_eq!(name, "i32");
     }
 
     #[test]
     fn check_param_flag_def_qual_name() {
         let param = "flags:#".parse().unwrap();
-        let name = parameters::qual_name(&param);
+        let name = parameters::qual_name(&param, &Config::default());
         assert_eq!(name, "u32");
     }
 
     #[test]
     fn check_param_flags_qual_name() {
         let param = "timeout:flags.1?int".parse().unwrap();
-        let name = parameters::qual_name(&param);
+        let name = parameters::qual_name(&param, &Config::default());
         assert_eq!(name, "Option<i32>");
     }
 
     #[test]
     fn check_param_true_flags_qual_name() {
         let param = "big:flags.0?true".parse().unwrap();
-        let name = parameters::qual_name(&param);
+        let name = parameters::qual_name(&param, &Config::default());
         assert_eq!(name, "bool");
     }
 
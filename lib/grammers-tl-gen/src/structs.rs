@@ -12,7 +12,7 @@ use crate::grouper;
 use crate::metadata::Metadata;
 use crate::rustifier;
 use crate::{ignore_type, Config};
-use grammers_tl_parser::tl::{Category, Definition, ParameterType};
+use grammers_tl_parser::tl::{Category, Definition, Parameter, ParameterType};
 use std::io::{self, Write};
 
 /// Get the list of generic parameters:
@@ -86,7 +86,7 @@ fn write_struct<W: Write>(
                 // Flags are computed on-the-fly, not stored
             }
             ParameterType::Normal { ty, .. } => {
-                if config.impl_serde && ty.name.as_str() == "bytes" {
+                if config.impl_serde && !config.shared_bytes && ty.name.as_str() == "bytes" {
                     writeln!(file, "{}    #[serde(with = \"serde_bytes\")]", indent)?;
                 }
                 writeln!(
@@ -94,7 +94,7 @@ fn write_struct<W: Write>(
                     "{}    pub {}: {},",
                     indent,
                     rustifier::parameters::attr_name(param),
-                    rustifier::parameters::qual_name(param),
+                    rustifier::parameters::qual_name(param, config),
                 )?;
             }
         }
@@ -255,6 +255,7 @@ fn write_deserializable<W: Write>(
     indent: &str,
     def: &Definition,
     metadata: &Metadata,
+    config: &Config,
 ) -> io::Result<()> {
     writeln!(
         file,
@@ -310,7 +311,7 @@ fn write_deserializable<W: Write>(
                         write!(
                             file,
                             "{}::deserialize(buf)?",
-                            rustifier::types::item_path(ty)
+                            rustifier::types::item_path(ty, config)
                         )?;
                     }
                     if flag.is_some() {
@@ -359,6 +360,7 @@ fn write_rpc<W: Write>(
     indent: &str,
     def: &Definition,
     _metadata: &Metadata,
+    config: &Config,
 ) -> io::Result<()> {
     writeln!(
         file,
@@ -372,7 +374,7 @@ fn write_rpc<W: Write>(
         file,
         "{}    type Return = {}{};",
         indent,
-        rustifier::types::qual_name(&def.ty),
+        rustifier::types::qual_name(&def.ty, config),
         if def.ty.generic_ref { "::Return" } else { "" },
     )?;
     writeln!(file, "{indent}}}")?;
@@ -394,6 +396,7 @@ fn write_impl_from<W: Write>(
     indent: &str,
     def: &Definition,
     metadata: &Metadata,
+    config: &Config,
 ) -> io::Result<()> {
     let infallible = metadata.defs_with_type(&def.ty).len() == 1;
     let type_name = rustifier::definitions::type_name(def);
@@ -403,7 +406,7 @@ fn write_impl_from<W: Write>(
         "{}impl {}From<{}> for {} {{",
         indent,
         if infallible { "" } else { "Try" },
-        rustifier::types::qual_name(&def.ty),
+        rustifier::types::qual_name(&def.ty, config),
         type_name,
     )?;
     if !infallible {
@@ -414,7 +417,7 @@ fn write_impl_from<W: Write>(
         "{}    fn {try_}from(x: {cls}) -> {result}Self{error} {{",
         indent,
         try_ = if infallible { "" } else { "try_" },
-        cls = rustifier::types::qual_name(&def.ty),
+        cls = rustifier::types::qual_name(&def.ty, config),
         result = if infallible { "" } else { "Result<" },
         error = if infallible { "" } else { ", Self::Error>" },
     )?;
@@ -423,7 +426,7 @@ fn write_impl_from<W: Write>(
         file,
         "{}            {cls}::{name}{data} => {ok}{deref}{value}{body}{paren},",
         indent,
-        cls = rustifier::types::qual_name(&def.ty),
+        cls = rustifier::types::qual_name(&def.ty, config),
         name = rustifier::definitions::variant_name(def),
         data = if def.params.is_empty() { "" } else { "(x)" },
         ok = if infallible { "" } else { "Ok(" },
@@ -449,6 +452,126 @@ fn write_impl_from<W: Write>(
     Ok(())
 }
 
+/// Defines the `impl` with builder methods corresponding to the definition,
+/// if it has any optional fields:
+///
+/// ```ignore
+/// impl Name {
+///     pub fn new(field: Type) -> Self {
+///         Self { field, flag: false, optional: None }
+///     }
+///
+///     pub fn flag(mut self, flag: bool) -> Self {
+///         self.flag = flag;
+///         self
+///     }
+///
+///     pub fn optional(mut self, optional: Type) -> Self {
+///         self.optional = Some(optional);
+///         self
+///     }
+/// }
+/// ```
+///
+/// Definitions without required fields also get `impl Default`.
+fn write_builder<W: Write>(
+    file: &mut W,
+    indent: &str,
+    def: &Definition,
+    _metadata: &Metadata,
+    config: &Config,
+) -> io::Result<()> {
+    let is_optional =
+        |param: &Parameter| matches!(param.ty, ParameterType::Normal { flag: Some(_), .. });
+    if !def.params.iter().any(is_optional) {
+        return Ok(());
+    }
+
+    let generics = get_generic_param_list(def, "");
+    let type_name = rustifier::definitions::type_name(def);
+    let required = def
+        .params
+        .iter()
+        .filter(|p| !matches!(p.ty, ParameterType::Flags) && !is_optional(p))
+        .collect::<Vec<_>>();
+
+    writeln!(file, "{indent}#[allow(clippy::wrong_self_convention)]")?;
+    writeln!(file, "{indent}impl{generics} {type_name}{generics} {{")?;
+    writeln!(file, "{indent}    #[allow(clippy::too_many_arguments)]")?;
+    write!(file, "{indent}    pub fn new(")?;
+    for (i, param) in required.iter().enumerate() {
+        if i != 0 {
+            write!(file, ", ")?;
+        }
+        write!(
+            file,
+            "{}: {}",
+            rustifier::parameters::attr_name(param),
+            rustifier::parameters::qual_name(param, config)
+        )?;
+    }
+    writeln!(file, ") -> Self {{")?;
+    writeln!(file, "{indent}        Self {{")?;
+    for param in def.params.iter() {
+        let name = rustifier::parameters::attr_name(param);
+        match &param.ty {
+            ParameterType::Flags => {}
+            ParameterType::Normal { flag: None, .. } => {
+                writeln!(file, "{indent}            {name},")?;
+            }
+            ParameterType::Normal { ty, .. } if ty.name == "true" => {
+                writeln!(file, "{indent}            {name}: false,")?;
+            }
+            ParameterType::Normal { .. } => {
+                writeln!(file, "{indent}            {name}: None,")?;
+            }
+        }
+    }
+    writeln!(file, "{indent}        }}")?;
+    writeln!(file, "{indent}    }}")?;
+
+    for param in def.params.iter() {
+        let name = rustifier::parameters::attr_name(param);
+        // These would shadow `Self::new` and `Default::default`.
+        if name == "new" || name == "default" {
+            continue;
+        }
+        let (ty, value) = match &param.ty {
+            ParameterType::Normal {
+                ty, flag: Some(_), ..
+            } if ty.name == "true" => ("bool".to_string(), name.clone()),
+            ParameterType::Normal {
+                ty, flag: Some(_), ..
+            } => (
+                rustifier::types::qual_name(ty, config),
+                format!("Some({name})"),
+            ),
+            ParameterType::Flags | ParameterType::Normal { flag: None, .. } => continue,
+        };
+        writeln!(file)?;
+        writeln!(
+            file,
+            "{indent}    pub fn {name}(mut self, {name}: {ty}) -> Self {{"
+        )?;
+        writeln!(file, "{indent}        self.{name} = {value};")?;
+        writeln!(file, "{indent}        self")?;
+        writeln!(file, "{indent}    }}")?;
+    }
+    writeln!(file, "{indent}}}")?;
+
+    if required.is_empty() {
+        writeln!(
+            file,
+            "{indent}impl{generics} Default for {type_name}{generics} {{"
+        )?;
+        writeln!(file, "{indent}    fn default() -> Self {{")?;
+        writeln!(file, "{indent}        Self::new()")?;
+        writeln!(file, "{indent}    }}")?;
+        writeln!(file, "{indent}}}")?;
+    }
+    Ok(())
+}
+
 /// Writes an entire definition as Rust code (`struct` and `impl`).
 fn write_definition<W: Write>(
     file: &mut W,
@@ -461,13 +584,16 @@ fn write_definition<W: Write>(
     write_identifiable(file, indent, def, metadata)?;
     write_serializable(file, indent, def, metadata)?;
     if def.category == Category::Types || config.deserializable_functions {
-        write_deserializable(file, indent, def, metadata)?;
+        write_deserializable(file, indent, def, metadata, config)?;
     }
     if def.category == Category::Functions {
-        write_rpc(file, indent, def, metadata)?;
+        write_rpc(file, indent, def, metadata, config)?;
     }
     if def.category == Category::Types && config.impl_from_enum {
-        write_impl_from(file, indent, def, metadata)?;
+        write_impl_from(file, indent, def, metadata, config)?;
+    }
+    if config.impl_builders {
+        write_builder(file, indent, def, metadata, config)?;
     }
     Ok(())
 }
@@ -528,16 +654,18 @@ pub(crate) fn write_category_mod<W: Write>(
         let indent = if key.is_empty() {
             "    "
         } else {
+            crate::write_namespace_cfg(file, key, category, metadata, config)?;
             writeln!(file, "    #[allow(clippy::unreadable_literal)]")?;
             writeln!(file, "    pub mod {key} {{")?;
             "        "
         };
 
+        crate::write_alloc_imports(file, indent, config)?;
         if category == Category::Types && config.impl_from_enum {
             // If all of the conversions are infallible this will be unused.
             // Don't bother checking this beforehand, just allow warnings.
             writeln!(file, "{indent}#[allow(unused_imports)]")?;
-            writeln!(file, "{indent}use std::convert::TryFrom;")?;
+            writeln!(file, "{indent}use core::convert::TryFrom;")?;
         }
 
         for definition in grouped[key]
@@ -24,11 +24,16 @@ fn gen_rust_code(definitions: &[Definition]) -> io::Result<String> {
         LAYER,
         &Config {
             gen_name_for_id: false,
+            gen_json_schema: true,
             deserializable_functions: true,
             impl_debug: true,
             impl_from_enum: true,
             impl_from_type: true,
             impl_serde: true,
+            impl_builders: true,
+            shared_bytes: false,
+            namespace_features: true,
+            no_std: true,
         },
     )?;
     Ok(String::from_utf8(file).unwrap())
@@ -136,3 +141,109 @@ fn generic_bytes_with_serde_bytes() -> io::Result<()> {
     assert!(result.contains("pub stripped_thumb: Option<Vec<u8>>,"));
     Ok(())
 }
+
+#[test]
+fn json_schema_lists_sorted_definitions() -> io::Result<()> {
+    let definitions = get_definitions(
+        "
+        inputPeerPhotoFileLocation#37257e99 flags:# big:flags.0?true peer:InputPeer photo_id:long = InputFileLocation;
+        inputPeerEmpty#7f3b18ea = InputPeer;
+    ",
+    );
+    let result = gen_rust_code(&definitions)?;
+    eprintln!("{result}");
+    let empty = result
+        .find(r#"crate::json::Definition { id: 0x7f3b18ea, name: "inputPeerEmpty", ty: "InputPeer", params: &[] },"#)
+        .unwrap();
+    let location = result
+        .find(concat!(
+            r#"crate::json::Definition { id: 0x37257e99, name: "inputPeerPhotoFileLocation", "#,
+            r##"ty: "InputFileLocation", params: &[("flags", "#"), ("big", "flags.0?true"), "##,
+            r#"("peer", "InputPeer"), ("photo_id", "long")] },"#
+        ))
+        .unwrap();
+    assert!(location < empty);
+    Ok(())
+}
+
+#[test]
+fn namespaces_are_feature_gated_unless_shared() -> io::Result<()> {
+    let definitions = get_definitions(
+        "
+        updateNewStickerSet#688a30aa stickerset:messages.StickerSet = Update;
+        messages.stickerSet#6e153f16 set:StickerSet = messages.StickerSet;
+        storage.fileJpeg#7efe0e = storage.FileType;
+        upload.file#96a18d5 type:storage.FileType = upload.File;
+        ---functions---
+        messages.getStickerSet#c8a0ec74 hash:int = messages.StickerSet;
+        upload.getFile#be5335be offset:long = upload.File;
+    ",
+    );
+    let result = gen_rust_code(&definitions)?;
+    eprintln!("{result}");
+    let gated = |ns: &str, allow: &str| {
+        result.contains(&format!(
+            "#[cfg(feature = \"tl-{ns}\")]\n    #[allow({allow})]\n    pub mod {ns} {{"
+        ))
+    };
+    // Used by definitions outside of them, so only their functions are gated.
+    assert!(!gated("messages", "clippy::large_enum_variant"));
+    assert!(!gated("storage", "clippy::large_enum_variant"));
+    assert_eq!(
+        result.matches(r#"#[cfg(feature = "tl-messages")]"#).count(),
+        1
+    );
+    assert!(gated("messages", "clippy::unreadable_literal"));
+    assert!(!result.contains(r#"#[cfg(feature = "tl-storage")]"#));
+    // Only used by itself, so everything is gated.
+    assert!(gated("upload", "clippy::large_enum_variant"));
+    assert_eq!(
+        result.matches(r#"#[cfg(feature = "tl-upload")]"#).count(),
+        3
+    );
+    Ok(())
+}
+
+#[test]
+fn builders_fill_optional_fields() -> io::Result<()> {
+    let definitions = get_definitions(
+        "
+        ---functions---
+        messages.sendMessage#983f9745 flags:# silent:flags.5?true peer:InputPeer reply_to:flags.0?InputReplyTo message:string = Updates;
+        updates.getState#edd4882a = updates.State;
+        help.getAppConfig#61e3f854 flags:# hash:flags.0?int = help.AppConfig;
+    ",
+    );
+    let result = gen_rust_code(&definitions)?;
+    eprintln!("{result}");
+    assert!(result.contains("pub fn new(peer: crate::enums::InputPeer, message: String) -> Self {"));
+    assert!(result.contains("silent: false,"));
+    assert!(result.contains("reply_to: None,"));
+    assert!(result.contains("pub fn silent(mut self, silent: bool) -> Self {"));
+    assert!(result
+        .contains("pub fn reply_to(mut self, reply_to: crate::enums::InputReplyTo) -> Self {"));
+    assert!(result.contains("self.reply_to = Some(reply_to);"));
+    assert!(!result.contains("impl GetState {"));
+    assert!(result.contains("impl Default for GetAppConfig {"));
+    assert!(!result.contains("impl Default for SendMessage {"));
+    Ok(())
+}
+
+#[test]
+fn modules_import_alloc_types() -> io::Result<()> {
+    let definitions = get_definitions(
+        "
+        inputPeerEmpty#7f3b18ea = InputPeer;
+        auth.sentCode#5e002502 phone_code_hash:string = auth.SentCode;
+    ",
+    );
+    let result = gen_rust_code(&definitions)?;
+    let imports = result
+        .matches("use alloc::{boxed::Box, string::String, vec::Vec};")
+        .count();
+    // Root and `auth` modules of both types and enums.
+    assert_eq!(imports, 4);
+    assert!(result.contains("use core::convert::TryFrom;"));
+    assert!(!result.contains("std::"));
+    Ok(())
+}
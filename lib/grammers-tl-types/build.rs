@@ -49,19 +49,42 @@ fn find_layer(file: &str) -> io::Result<Option<i32>> {
     }))
 }
 
+/// Get the path to a schema file, which can be overridden through an environment variable.
+fn schema_path(var: &str, default: &str) -> String {
+    println!("cargo:rerun-if-env-changed={var}");
+    let path = env::var(var).unwrap_or_else(|_| default.to_string());
+    println!("cargo:rerun-if-changed={path}");
+    path
+}
+
 fn main() -> std::io::Result<()> {
-    let layer = match find_layer("tl/api.tl")? {
-        Some(x) => x,
-        None => panic!("no layer information found in api.tl"),
+    let api_path = schema_path("GRAMMERS_TL_API", "tl/api.tl");
+    let mtproto_path = schema_path("GRAMMERS_TL_MTPROTO", "tl/mtproto.tl");
+    let secret_path = schema_path("GRAMMERS_TL_SECRET", "tl/secret.tl");
+
+    println!("cargo:rerun-if-env-changed=GRAMMERS_TL_LAYER");
+    let layer = match env::var("GRAMMERS_TL_LAYER") {
+        Ok(layer) => layer
+            .parse()
+            .unwrap_or_else(|_| panic!("GRAMMERS_TL_LAYER is not a valid layer: {layer}")),
+        Err(_) => match find_layer(&api_path)? {
+            Some(x) => x,
+            None => panic!("no layer information found in {api_path}"),
+        },
     };
+    // Lets the build scripts of dependent crates read `DEP_GRAMMERS_TL_TYPES_LAYER`.
+    println!("cargo:layer={layer}");
 
     let definitions = {
         let mut definitions = Vec::new();
         if cfg!(feature = "tl-api") {
-            definitions.extend(load_tl("tl/api.tl")?);
+            definitions.extend(load_tl(&api_path)?);
         }
         if cfg!(feature = "tl-mtproto") {
-            definitions.extend(load_tl("tl/mtproto.tl")?);
+            definitions.extend(load_tl(&mtproto_path)?);
+        }
+        if cfg!(feature = "tl-secret") {
+            definitions.extend(load_tl(&secret_path)?);
         }
         definitions
     };
@@ -72,11 +95,16 @@ fn main() -> std::io::Result<()> {
 
     let config = Config {
         gen_name_for_id: true,
+        gen_json_schema: cfg!(feature = "impl-json"),
         deserializable_functions: cfg!(feature = "deserializable-functions"),
         impl_debug: cfg!(feature = "impl-debug"),
         impl_from_enum: cfg!(feature = "impl-from-enum"),
         impl_from_type: cfg!(feature = "impl-from-type"),
         impl_serde: cfg!(feature = "impl-serde"),
+        impl_builders: cfg!(feature = "impl-builders"),
+        shared_bytes: cfg!(feature = "bytes"),
+        namespace_features: true,
+        no_std: true,
     };
 
     generate_rust_code(&mut file, &definitions, layer, &config)?;
@@ -5,7 +5,10 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-use std::fmt;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Error {
@@ -32,6 +35,7 @@ pub enum Error {
     },
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {}
 
 impl fmt::Display for Error {
@@ -48,11 +52,30 @@ impl fmt::Display for Error {
 pub struct Cursor<'a> {
     buf: &'a [u8],
     pos: usize,
+    // The buffer `buf` belongs to, if any, so that byte-strings can share it
+    #[cfg(feature = "bytes")]
+    shared: Option<&'a bytes::Bytes>,
 }
 
 impl<'a> Cursor<'a> {
     pub fn from_slice(buf: &'a [u8]) -> Self {
-        Self { buf, pos: 0 }
+        Self {
+            buf,
+            pos: 0,
+            #[cfg(feature = "bytes")]
+            shared: None,
+        }
+    }
+
+    /// Like `from_slice`, but byte-strings deserialized as `Bytes` will point inside `buf`
+    /// instead of being copied.
+    #[cfg(feature = "bytes")]
+    pub fn from_shared(buf: &'a bytes::Bytes) -> Self {
+        Self {
+            buf,
+            pos: 0,
+            shared: Some(buf),
+        }
     }
 
     // TODO not a fan we need to expose this (and a way to create `Cursor`),
@@ -82,6 +105,20 @@ impl<'a> Cursor<'a> {
         }
     }
 
+    /// Reads the next `len` bytes, sharing the underlying buffer if possible.
+    #[cfg(feature = "bytes")]
+    pub fn read_shared(&mut self, len: usize) -> Result<bytes::Bytes> {
+        if self.pos + len > self.buf.len() {
+            return Err(Error::UnexpectedEof);
+        }
+        let result = match self.shared {
+            Some(shared) => shared.slice(self.pos..self.pos + len),
+            None => bytes::Bytes::copy_from_slice(&self.buf[self.pos..self.pos + len]),
+        };
+        self.pos += len;
+        Ok(result)
+    }
+
     pub fn read_to_end(&mut self, buf: &mut Vec<u8>) -> Result<usize> {
         buf.extend(&self.buf[self.pos..]);
         let old = self.pos;
@@ -95,7 +132,7 @@ impl<'a> Cursor<'a> {
 /// so instead we just use a `[u8]` as our buffer.
 // TODO this is only public for session
 pub type Buffer<'a, 'b> = &'a mut Cursor<'b>;
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 /// This trait allows for data serialized according to the
 /// [Binary Data Serialization] to be deserialized into concrete instances.
@@ -105,7 +142,7 @@ pub trait Deserializable {
     /// Deserializes an instance of the type from a given buffer.
     fn deserialize(buf: Buffer) -> Result<Self>
     where
-        Self: std::marker::Sized;
+        Self: core::marker::Sized;
 
     /// Convenience function to deserialize an instance from a given buffer.
     ///
@@ -118,10 +155,67 @@ pub trait Deserializable {
     /// ```
     fn from_bytes(buf: &[u8]) -> Result<Self>
     where
-        Self: std::marker::Sized,
+        Self: core::marker::Sized,
     {
         Self::deserialize(&mut Cursor::from_slice(buf))
     }
+
+    /// Like `from_bytes`, but byte-strings in the instance will share the buffer rather than
+    /// being copied.
+    #[cfg(feature = "bytes")]
+    fn from_shared(buf: &bytes::Bytes) -> Result<Self>
+    where
+        Self: core::marker::Sized,
+    {
+        Self::deserialize(&mut Cursor::from_shared(buf))
+    }
+
+    /// Like `from_bytes`, but takes ownership of the buffer. With the `bytes` feature, the
+    /// byte-strings in the instance will share it rather than being copied.
+    ///
+    /// ```
+    /// use grammers_tl_types::Deserializable;
+    ///
+    /// assert_eq!(bool::from_owned(vec![0x37, 0x97, 0x79, 0xbc]).unwrap(), false);
+    /// ```
+    fn from_owned(buf: Vec<u8>) -> Result<Self>
+    where
+        Self: core::marker::Sized,
+    {
+        #[cfg(feature = "bytes")]
+        {
+            Self::from_shared(&bytes::Bytes::from(buf))
+        }
+        #[cfg(not(feature = "bytes"))]
+        {
+            Self::from_bytes(&buf)
+        }
+    }
+}
+
+/// Reads the length prefix of a byte-string, returning its length and padding.
+fn read_bytes_header(buf: Buffer) -> Result<(usize, usize)> {
+    let first_byte = buf.read_byte()?;
+    Ok(if first_byte == 254 {
+        let mut buffer = [0u8; 3];
+        buf.read_exact(&mut buffer)?;
+        let len = (buffer[0] as usize) | ((buffer[1] as usize) << 8) | ((buffer[2] as usize) << 16);
+
+        (len, len % 4)
+    } else {
+        let len = first_byte as usize;
+        (len, (len + 1) % 4)
+    })
+}
+
+/// Skips the padding after a byte-string.
+fn skip_bytes_padding(buf: Buffer, padding: usize) -> Result<()> {
+    if padding > 0 {
+        for _ in 0..(4 - padding) {
+            buf.read_byte()?;
+        }
+    }
+    Ok(())
 }
 
 impl Deserializable for bool {
@@ -397,28 +491,42 @@ impl Deserializable for Vec<u8> {
     /// assert_eq!(Vec::<u8>::from_bytes(&[0x01, 0x7f, 0x00, 0x00]).unwrap(), vec![0x7f_u8]);
     /// ```
     fn deserialize(buf: Buffer) -> Result<Self> {
-        let first_byte = buf.read_byte()?;
-        let (len, padding) = if first_byte == 254 {
-            let mut buffer = [0u8; 3];
-            buf.read_exact(&mut buffer)?;
-            let len =
-                (buffer[0] as usize) | ((buffer[1] as usize) << 8) | ((buffer[2] as usize) << 16);
-
-            (len, len % 4)
-        } else {
-            let len = first_byte as usize;
-            (len, (len + 1) % 4)
-        };
+        let (len, padding) = read_bytes_header(buf)?;
 
         let mut result = vec![0u8; len];
         buf.read_exact(&mut result)?;
 
-        if padding > 0 {
-            for _ in 0..(4 - padding) {
-                buf.read_byte()?;
-            }
-        }
+        skip_bytes_padding(buf, padding)?;
+        Ok(result)
+    }
+}
 
+#[cfg(feature = "bytes")]
+impl Deserializable for bytes::Bytes {
+    /// Deserializes a byte-string according to the following definition:
+    ///
+    /// * `string ? = String;`.
+    ///
+    /// If the buffer is shared (see [`Deserializable::from_shared`]), the result points inside
+    /// it and no copy is made.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use bytes::Bytes;
+    /// use grammers_tl_types::Deserializable;
+    ///
+    /// let buf = Bytes::from_static(&[0x01, 0x7f, 0x00, 0x00]);
+    /// let result = Bytes::from_shared(&buf).unwrap();
+    /// assert_eq!(result, &[0x7f_u8][..]);
+    /// assert_eq!(result.as_ptr(), buf[1..].as_ptr());
+    ///
+    /// assert_eq!(Bytes::from_bytes(&[0x01, 0x7f, 0x00, 0x00]).unwrap(), &[0x7f_u8][..]);
+    /// ```
+    fn deserialize(buf: Buffer) -> Result<Self> {
+        let (len, padding) = read_bytes_header(buf)?;
+        let result = buf.read_shared(len)?;
+        skip_bytes_padding(buf, padding)?;
         Ok(result)
     }
 }
@@ -0,0 +1,285 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Conversion of serialized objects into JSON values, meant for debugging.
+//!
+//! Only available with the `impl-json` feature.
+//!
+//! Unlike the `impl-serde` feature, this works on any [`Serializable`] object without deriving
+//! anything on the generated types. The object is serialized and then read back using a table
+//! of the definitions, so the output contains the constructor names from the `.tl` schema
+//! (under the `"_"` key) and the flags are expanded into the fields that depend on them.
+//!
+//! # Examples
+//!
+//! ```
+//! use grammers_tl_types as tl;
+//!
+//! let peer = tl::enums::InputPeer::User(tl::types::InputPeerUser {
+//!     user_id: 1,
+//!     access_hash: 2,
+//! });
+//!
+//! let json = tl::json::to_json(&peer).unwrap();
+//! assert_eq!(
+//!     json.to_string(),
+//!     r#"{"_":"inputPeerUser","user_id":1,"access_hash":2}"#
+//! );
+//! ```
+//!
+//! [`Serializable`]: ../trait.Serializable.html
+use crate::deserialize::{Cursor, Error, Result};
+use crate::generated::JSON_SCHEMA;
+use crate::{Deserializable, Serializable};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Write as _};
+
+/// Identifier of the boxed `vector` type.
+const VECTOR_ID: u32 = 0x1cb5c415;
+
+/// A single definition from the `.tl` schema, as seen by the generated table.
+#[derive(Debug)]
+pub(crate) struct Definition {
+    pub(crate) id: u32,
+    pub(crate) name: &'static str,
+    pub(crate) ty: &'static str,
+    pub(crate) params: &'static [(&'static str, &'static str)],
+}
+
+/// A JSON value.
+///
+/// Its `Display` implementation produces compact JSON, and the alternate `{:#}` form
+/// produces indented JSON.
+///
+/// Byte-strings and the 128 and 256-bit integers are represented as hexadecimal strings.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// The fields of an object, in the same order as in its definition.
+    Object(Vec<(String, Value)>),
+}
+
+/// Converts a serializable object into a JSON value.
+///
+/// The object must serialize to a boxed type, that is, it must start with a known constructor.
+pub fn to_json<S: Serializable + ?Sized>(object: &S) -> Result<Value> {
+    from_bytes(&object.to_bytes())
+}
+
+/// Reads a serialized boxed object and converts it into a JSON value.
+///
+/// This is useful for raw payloads, such as updates, which have not been deserialized.
+pub fn from_bytes(buf: &[u8]) -> Result<Value> {
+    read_boxed(&mut Cursor::from_slice(buf))
+}
+
+fn definition_for_id(id: u32) -> Option<&'static Definition> {
+    JSON_SCHEMA
+        .binary_search_by_key(&id, |def| def.id)
+        .ok()
+        .map(|i| &JSON_SCHEMA[i])
+}
+
+fn read_boxed(buf: &mut Cursor) -> Result<Value> {
+    let id = u32::deserialize(buf)?;
+    match id {
+        0x997275b5 => Ok(Value::Bool(true)),
+        0xbc799737 => Ok(Value::Bool(false)),
+        _ => match definition_for_id(id) {
+            Some(def) => read_object(def, buf),
+            // Includes vectors, as the type of their elements is unknown.
+            None => Err(Error::UnexpectedConstructor { id }),
+        },
+    }
+}
+
+fn read_object(def: &Definition, buf: &mut Cursor) -> Result<Value> {
+    let mut fields = vec![("_".to_string(), Value::String(def.name.to_string()))];
+    let mut flags = Vec::new();
+
+    for &(name, ty) in def.params {
+        if ty == "#" {
+            flags.push((name, u32::deserialize(buf)?));
+            continue;
+        }
+
+        let ty = match ty.split_once('?') {
+            Some((flag, ty)) => {
+                let set = flag
+                    .split_once('.')
+                    .and_then(|(flag, index)| {
+                        let index = index.parse::<u32>().ok()?;
+                        let value = flags.iter().find(|(name, _)| *name == flag)?.1;
+                        Some(value & (1 << index) != 0)
+                    })
+                    .unwrap_or(false);
+
+                if ty == "true" {
+                    fields.push((name.to_string(), Value::Bool(set)));
+                    continue;
+                }
+                if !set {
+                    continue;
+                }
+                ty
+            }
+            None => ty,
+        };
+
+        fields.push((name.to_string(), read_value(ty, buf)?));
+    }
+
+    Ok(Value::Object(fields))
+}
+
+fn read_value(ty: &str, buf: &mut Cursor) -> Result<Value> {
+    Ok(match ty {
+        "int" => Value::Number(i32::deserialize(buf)?.into()),
+        "long" => Value::Number(i64::deserialize(buf)?),
+        "double" => Value::Float(f64::deserialize(buf)?),
+        "int128" => Value::String(hex(&<[u8; 16]>::deserialize(buf)?)),
+        "int256" => Value::String(hex(&<[u8; 32]>::deserialize(buf)?)),
+        "string" => Value::String(String::deserialize(buf)?),
+        "bytes" => Value::String(hex(&Vec::<u8>::deserialize(buf)?)),
+        "Bool" => Value::Bool(bool::deserialize(buf)?),
+        "true" => Value::Bool(true),
+        "Object" => read_boxed(buf)?,
+        _ => {
+            if let Some(inner) = ty.strip_prefix("Vector<").and_then(|t| t.strip_suffix('>')) {
+                let id = u32::deserialize(buf)?;
+                if id != VECTOR_ID {
+                    return Err(Error::UnexpectedConstructor { id });
+                }
+                read_vector(inner, buf)?
+            } else if let Some(inner) = ty.strip_prefix("vector<").and_then(|t| t.strip_suffix('>'))
+            {
+                read_vector(inner, buf)?
+            } else if ty.starts_with('!') {
+                read_boxed(buf)?
+            } else if let Some(ty) = ty.strip_prefix('%') {
+                // Bare reference to a type with a single constructor.
+                read_bare(|def| def.ty == ty, buf)?
+            } else if ty
+                .rsplit('.')
+                .next()
+                .is_some_and(|name| name.starts_with(|c: char| c.is_ascii_lowercase()))
+            {
+                // Bare reference to a constructor.
+                read_bare(|def| def.name == ty, buf)?
+            } else {
+                read_boxed(buf)?
+            }
+        }
+    })
+}
+
+fn read_vector(ty: &str, buf: &mut Cursor) -> Result<Value> {
+    let len = u32::deserialize(buf)?;
+    Ok(Value::Array(
+        (0..len)
+            .map(|_| read_value(ty, buf))
+            .collect::<Result<_>>()?,
+    ))
+}
+
+fn read_bare(matches: impl Fn(&Definition) -> bool, buf: &mut Cursor) -> Result<Value> {
+    match JSON_SCHEMA.iter().find(|def| matches(def)) {
+        Some(def) => read_object(def, buf),
+        // The schema lacks the definition (e.g. if `tl-mtproto` is disabled), so the data
+        // can't be interpreted. Without a constructor, there's no better identifier to report.
+        None => Err(Error::UnexpectedConstructor { id: 0 }),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() * 2);
+    bytes
+        .iter()
+        .for_each(|b| write!(result, "{b:02x}").unwrap());
+    result
+}
+
+impl Value {
+    fn write(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        let pretty = f.alternate();
+        let newline = |f: &mut fmt::Formatter<'_>, indent: usize| {
+            if pretty {
+                write!(f, "\n{:1$}", "", indent * 2)
+            } else {
+                Ok(())
+            }
+        };
+
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Number(value) => write!(f, "{value}"),
+            Self::Float(value) if value.is_finite() => write!(f, "{value:?}"),
+            // JSON has no representation for infinity or NaN.
+            Self::Float(_) => f.write_str("null"),
+            Self::String(value) => write_string(f, value),
+            Self::Array(values) if values.is_empty() => f.write_str("[]"),
+            Self::Array(values) => {
+                f.write_char('[')?;
+                for (i, value) in values.iter().enumerate() {
+                    if i != 0 {
+                        f.write_char(',')?;
+                    }
+                    newline(f, indent + 1)?;
+                    value.write(f, indent + 1)?;
+                }
+                newline(f, indent)?;
+                f.write_char(']')
+            }
+            Self::Object(fields) if fields.is_empty() => f.write_str("{}"),
+            Self::Object(fields) => {
+                f.write_char('{')?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i != 0 {
+                        f.write_char(',')?;
+                    }
+                    newline(f, indent + 1)?;
+                    write_string(f, name)?;
+                    f.write_str(if pretty { ": " } else { ":" })?;
+                    value.write(f, indent + 1)?;
+                }
+                newline(f, indent)?;
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c < ' ' => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
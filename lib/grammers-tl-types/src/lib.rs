@@ -61,13 +61,31 @@
 //!
 //! * `impl-from-type`: implements `From<Type> for Enum`.
 //!
+//! * `impl-builders`: implements a `new` constructor taking the required fields, and builder
+//!   methods for the optional ones, on definitions with optional fields. Definitions without
+//!   required fields also implement `Default`.
+//!
+//! * `impl-json`: generates a table of the definitions, used by the [`json`] module to convert
+//!   any serialized object into a JSON value for debugging.
+//!
 //! * `tl-api`: generates code for the `api.tl`.
 //!   This is what high-level libraries often need.
 //!
 //! * `tl-mtproto`: generates code for the `mtproto.tl`.
 //!   Only useful for low-level libraries.
 //!
-//! * `impl-serde`: generates code for serde support
+//! * `tl-secret`: generates code for the `secret.tl`, with the definitions sent inside the
+//!   encrypted payload of secret chats. Requires `tl-api`.
+//!
+//! * `tl-namespaces`: enables the feature of every namespace below. Enabled by default.
+//!
+//! * `tl-account`, `tl-messages`, `tl-payments`, and so on: generates code for the
+//!   definitions in that namespace. Every namespace can be enabled on its own. The types of
+//!   namespaces used by other definitions (such as `messages` or `storage`) are always
+//!   generated, so only their functions depend on the feature.
+//!
+//! * `impl-serde`: derives `Serialize` and `Deserialize` on all generated types and enums,
+//!   so that raw API objects can be persisted or logged (for example, as JSON).
 //!
 //! [`types`]: types/index.html
 //! [`functions`]: functions/index.html
@@ -75,17 +93,25 @@
 //! [`Serializable`]: trait.Serializable.html
 //! [`Deserializable`]: trait.Deserializable.html
 //! [`LAYER`]: constant.LAYER.html
+//! [`json`]: json/index.html
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(unsafe_code)]
 
+extern crate alloc;
+
 pub mod deserialize;
 mod generated;
+#[cfg(feature = "impl-json")]
+pub mod json;
 pub mod serialize;
 
 pub use deserialize::{Cursor, Deserializable};
 pub use generated::{enums, functions, name_for_id, types, LAYER};
 pub use serialize::Serializable;
 
+use alloc::vec::Vec;
+
 #[cfg(feature = "impl-serde")]
 use serde_derive::{Deserialize, Serialize};
 
@@ -111,6 +137,30 @@ impl From<Vec<u8>> for Blob {
     }
 }
 
+/// The type used for byte-string fields, which is `bytes::Bytes` when the `bytes` feature is
+/// enabled, and `Vec<u8>` otherwise.
+#[cfg(feature = "bytes")]
+pub type ByteString = bytes::Bytes;
+
+/// The type used for byte-string fields, which is `bytes::Bytes` when the `bytes` feature is
+/// enabled, and `Vec<u8>` otherwise.
+#[cfg(not(feature = "bytes"))]
+pub type ByteString = Vec<u8>;
+
+/// Turn an owned buffer into a [`ByteString`], so that fields can be filled the same way
+/// whether the `bytes` feature is enabled or not.
+#[inline]
+pub fn byte_string(data: Vec<u8>) -> ByteString {
+    #[cfg(feature = "bytes")]
+    {
+        bytes::Bytes::from(data)
+    }
+    #[cfg(not(feature = "bytes"))]
+    {
+        data
+    }
+}
+
 /// Anything implementing this trait is identifiable by both ends (client-server)
 /// when performing Remote Procedure Calls (RPC) and transmission of objects.
 pub trait Identifiable {
@@ -5,6 +5,8 @@
 // <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
+use alloc::string::String;
+use alloc::vec::Vec;
 
 /// This trait allows for concrete instances to be serialized into
 /// binary data as specified by the [Binary Data Serialization].
@@ -275,6 +277,24 @@ impl Serializable for Vec<u8> {
     }
 }
 
+#[cfg(feature = "bytes")]
+impl Serializable for bytes::Bytes {
+    /// Serializes a byte-string according to the following definition:
+    ///
+    /// * `string ? = String;`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use grammers_tl_types::Serializable;
+    ///
+    /// assert_eq!(bytes::Bytes::from_static(&[0x7f]).to_bytes(), &[0x01, 0x7f, 0x00, 0x00]);
+    /// ```
+    fn serialize(&self, buf: &mut impl Extend<u8>) {
+        (&self[..]).serialize(buf)
+    }
+}
+
 impl Serializable for &[u8] {
     /// Serializes a byte-string according to the following definition:
     ///
@@ -0,0 +1,40 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+#![cfg(feature = "impl-builders")]
+use grammers_tl_types as tl;
+
+#[test]
+fn builder_sets_optional_fields() {
+    let request = tl::functions::messages::SendMessage::new(
+        tl::enums::InputPeer::PeerSelf,
+        "Hello".to_string(),
+        1,
+    )
+    .silent(true)
+    .schedule_date(10);
+
+    assert_eq!(request.message, "Hello");
+    assert!(request.silent);
+    assert!(!request.background);
+    assert_eq!(request.schedule_date, Some(10));
+    assert_eq!(request.reply_to, None);
+}
+
+#[test]
+fn default_leaves_all_fields_unset() {
+    let photo = tl::types::InputChatUploadedPhoto::default();
+    assert_eq!(
+        photo,
+        tl::types::InputChatUploadedPhoto {
+            file: None,
+            video: None,
+            video_start_ts: None,
+            video_emoji_markup: None,
+        }
+    );
+}
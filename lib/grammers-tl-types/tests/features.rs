@@ -0,0 +1,46 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+use std::collections::BTreeSet;
+use std::fs;
+
+fn schema_namespaces() -> BTreeSet<String> {
+    ["tl/api.tl", "tl/mtproto.tl", "tl/secret.tl"]
+        .iter()
+        .flat_map(|path| {
+            fs::read_to_string(path)
+                .unwrap()
+                .lines()
+                .filter(|line| !line.starts_with("//"))
+                .filter_map(|line| {
+                    let name = line.split_once('#')?.0;
+                    let (ns, _) = name.split_once('.')?;
+                    Some(ns.to_string())
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn namespace_features() -> BTreeSet<String> {
+    let manifest = fs::read_to_string("Cargo.toml").unwrap();
+    let start = manifest.find("tl-namespaces = [").unwrap();
+    let end = start + manifest[start..].find(']').unwrap();
+    manifest[start..end]
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let feature = line.trim().trim_end_matches(',').trim_matches('"');
+            feature.strip_prefix("tl-").unwrap().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn every_namespace_has_a_feature() {
+    assert_eq!(schema_namespaces(), namespace_features());
+}
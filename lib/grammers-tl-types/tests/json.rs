@@ -0,0 +1,88 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+#![cfg(feature = "impl-json")]
+use grammers_tl_types as tl;
+use tl::json::{self, Value};
+
+#[test]
+fn flags_are_expanded() {
+    let media = tl::enums::InputMedia::UploadedPhoto(tl::types::InputMediaUploadedPhoto {
+        spoiler: true,
+        file: tl::types::InputFile {
+            id: 1,
+            parts: 2,
+            name: "a.jpg".to_string(),
+            md5_checksum: String::new(),
+        }
+        .into(),
+        stickers: None,
+        ttl_seconds: Some(10),
+    });
+
+    assert_eq!(
+        json::to_json(&media).unwrap().to_string(),
+        concat!(
+            r#"{"_":"inputMediaUploadedPhoto","spoiler":true,"#,
+            r#""file":{"_":"inputFile","id":1,"parts":2,"name":"a.jpg","md5_checksum":""},"#,
+            r#""ttl_seconds":10}"#
+        )
+    );
+}
+
+#[test]
+fn vectors_and_bytes_are_converted() {
+    let request = tl::functions::messages::GetMessages {
+        id: vec![tl::types::InputMessageId { id: 7 }.into()],
+    };
+    assert_eq!(
+        json::to_json(&request).unwrap().to_string(),
+        r#"{"_":"messages.getMessages","id":[{"_":"inputMessageID","id":7}]}"#
+    );
+
+    let password = tl::types::InputCheckPasswordSrp {
+        srp_id: -1,
+        a: [0x01, 0xab].into_iter().collect(),
+        m1: Default::default(),
+    };
+    // Bare types can't be read back, so use the boxed form.
+    let password = tl::enums::InputCheckPasswordSrp::Srp(password);
+    assert_eq!(
+        json::to_json(&password).unwrap().to_string(),
+        r#"{"_":"inputCheckPasswordSRP","srp_id":-1,"A":"01ab","M1":""}"#
+    );
+}
+
+#[test]
+fn unknown_constructors_fail() {
+    assert!(matches!(
+        json::from_bytes(&[1, 2, 3, 4]),
+        Err(tl::deserialize::Error::UnexpectedConstructor { id: 0x04030201 })
+    ));
+}
+
+#[test]
+fn values_are_pretty_printed() {
+    let value = Value::Object(vec![
+        ("_".to_string(), Value::String("a\"b\n".to_string())),
+        (
+            "list".to_string(),
+            Value::Array(vec![Value::Number(1), Value::Null]),
+        ),
+        ("empty".to_string(), Value::Array(Vec::new())),
+        ("x".to_string(), Value::Float(1.0)),
+    ]);
+
+    assert_eq!(
+        value.to_string(),
+        r#"{"_":"a\"b\n","list":[1,null],"empty":[],"x":1.0}"#
+    );
+    assert_eq!(
+        format!("{value:#}"),
+        "{\n  \"_\": \"a\\\"b\\n\",\n  \"list\": [\n    1,\n    null\n  ],\n  \"empty\": [],\n  \"x\": 1.0\n}"
+    );
+}
@@ -0,0 +1,34 @@
+// Copyright 2020 - developers of the `grammers` project.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+#![cfg(feature = "impl-serde")]
+use grammers_tl_types as tl;
+
+#[test]
+fn types_round_trip_through_json() {
+    let original = tl::enums::InputPeer::User(tl::types::InputPeerUser {
+        user_id: 123,
+        access_hash: -456,
+    });
+
+    let json = serde_json::to_string(&original).unwrap();
+    let restored: tl::enums::InputPeer = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, original);
+}
+
+#[test]
+fn bytes_round_trip_through_json() {
+    let original = tl::types::InputCheckPasswordSrp {
+        srp_id: 1,
+        a: [0, 1, 2, 255].into_iter().collect(),
+        m1: Default::default(),
+    };
+
+    let json = serde_json::to_string(&original).unwrap();
+    let restored: tl::types::InputCheckPasswordSrp = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, original);
+}